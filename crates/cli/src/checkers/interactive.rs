@@ -1,30 +1,93 @@
+use crate::events::{ErrorKind, Event, EventSink, PromptAction};
 use crate::{info_1, Interactor};
 use crate::{info_2, print_error};
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use colored::*;
-use skyspell_core::{Checker, CheckerState, Dictionary, SpellingError};
-use skyspell_core::{IgnoreStore, Operation};
-use skyspell_core::{Project, RelativePath};
-use std::collections::HashSet;
-use std::path::PathBuf;
+use skyspell_core::{detect_mixed_script, MixedScript};
+use skyspell_core::{directory_pattern, Project, RelativePath};
+use skyspell_core::{output, ProcessOutcome};
+use skyspell_core::{Checker, CheckerState, Dictionary, SpellingError, SuggestionWorker};
+use skyspell_core::{IgnoreStore, LangDictionaries, LangOpener, Operation, TokenClassifier};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
+const ACTIONS: [(&str, &str); 12] = [
+    ("g", "Add word to global ignore list"),
+    ("l", "Add word to the ignore list for the current language"),
+    ("e", "Add word to ignore list for this extension"),
+    ("p", "Add word to ignore list for the current project"),
+    ("f", "Add word to ignore list for the current file"),
+    ("i", "Add the whole identifier to the global ignore list"),
+    ("n", "Always skip this file name"),
+    ("s", "Always skip this file"),
+    ("d", "Always skip the directory"),
+    ("o", "Open the file in your editor"),
+    ("x", "Skip this error"),
+    ("q", "Quit"),
+];
+
+// How each action is described in the summary of the session
+const HANDLED: [(&str, &str); 11] = [
+    ("g", "added to the global ignore list"),
+    ("l", "added to the ignore list for the language"),
+    ("e", "added to the ignore list for the extension"),
+    ("p", "added to the ignore list for the project"),
+    ("f", "added to the ignore list for the file"),
+    ("i", "ignored with their identifier"),
+    ("n", "skipped with their file name"),
+    ("s", "skipped with their file"),
+    ("d", "skipped with their directory"),
+    ("o", "replaced in the editor"),
+    ("x", "skipped"),
+];
+
+// Never keep the user waiting longer than this for suggestions
+const SUGGESTIONS_TIMEOUT: Duration = Duration::from_millis(200);
 
 pub struct InteractiveChecker<I: Interactor, D: Dictionary> {
     project: Project,
     interactor: I,
     dictionary: D,
+    langs: LangDictionaries<D>,
     ignore_store: IgnoreStore,
     state: CheckerState,
     skipped: HashSet<String>,
+    errors: usize,
+    suggestions: Option<SuggestionWorker>,
+    check_confusables: bool,
+    extension_counts: HashMap<String, usize>,
+    total_files: Option<usize>,
+    // Files checked or skipped so far
+    files_visited: usize,
+    in_file: bool,
+    header_printed: bool,
+    // Number of errors handled with each action
+    handled: HashMap<&'static str, usize>,
 }
 
 impl<I: Interactor, D: Dictionary> Checker<D> for InteractiveChecker<I, D> {
     type SourceContext = ();
 
     fn success(&self) -> Result<()> {
+        if self.event_sink().is_none() {
+            print_session_summary(self.files_visited, &self.handled);
+        }
+        if let Some(sink) = self.event_sink() {
+            let mut skipped: Vec<_> = self.skipped.iter().cloned().collect();
+            skipped.sort();
+            sink.emit(Event::Summary {
+                errors: self.errors,
+                skipped,
+            });
+        }
+        self.check_event_sink()?;
         if !self.skipped.is_empty() {
             bail!("Some errors were skipped")
         } else {
-            info_2!("No errors found");
+            if self.event_sink().is_none() {
+                info_2!("No errors found");
+            }
             Ok(())
         }
     }
@@ -34,35 +97,110 @@ impl<I: Interactor, D: Dictionary> Checker<D> for InteractiveChecker<I, D> {
     }
 
     fn dictionary(&self) -> &D {
-        &self.dictionary
+        self.langs.current().unwrap_or(&self.dictionary)
+    }
+
+    fn select_lang(&mut self, lang: Option<&str>) -> bool {
+        self.langs.select(lang, self.dictionary.lang())
     }
 
     fn ignore_store(&mut self) -> &mut IgnoreStore {
         &mut self.ignore_store
     }
 
+    fn classifier(&self) -> TokenClassifier<'_, D> {
+        TokenClassifier::new(self.dictionary(), &self.ignore_store).with_skipped(&self.skipped)
+    }
+
     fn state(&mut self) -> Option<&mut CheckerState> {
         Some(&mut self.state)
     }
 
+    fn on_file_start(&mut self, _relative_path: &RelativePath) {
+        self.files_visited += 1;
+        self.in_file = true;
+        self.header_printed = false;
+    }
+
+    fn on_file_done(&mut self, _relative_path: &RelativePath, _outcome: &ProcessOutcome) {
+        // Files skipped before being read are not started
+        if !self.in_file {
+            self.files_visited += 1;
+        }
+        self.in_file = false;
+    }
+
     fn handle_error(
         &mut self,
         error: &SpellingError,
         _context: &Self::SourceContext,
     ) -> Result<()> {
-        let (line, column) = error.pos();
         let word = error.word();
-        if self.skipped.contains(word) {
-            return Ok(());
-        }
         let relative_path = error.relative_path();
-        self.on_error(&relative_path, (line, column), word)
+        // Errors in file names are found before the files are visited
+        if error.is_file_name() {
+            self.errors += 1;
+            let identifier = Some(error.identifier()).filter(|i| *i != word);
+            return self.on_error(&relative_path, error.pos(), 0, word, identifier);
+        }
+        if !self.header_printed && self.event_sink().is_none() {
+            self.print_file_header(&relative_path);
+        }
+        self.header_printed = true;
+        self.errors += 1;
+        let column = error.columns().char + 1;
+        let identifier = Some(error.identifier()).filter(|i| *i != word);
+        self.on_error(&relative_path, error.pos(), column, word, identifier)
     }
 
     fn apply_operation(&mut self, mut operation: Operation) -> Result<()> {
         operation.execute(&mut self.ignore_store)?;
-        self.state.set_last_operation(operation.clone())
+        self.state
+            .set_last_operation(operation.clone(), self.project.path())
+    }
+
+    fn undo(&mut self) -> Result<()> {
+        undo_last_operation(
+            &self.project,
+            &self.interactor,
+            &mut self.ignore_store,
+            &mut self.state,
+        )
+    }
+}
+
+/// Undo the last operation performed for the project, after asking for
+/// confirmation if it applies to all projects
+pub(crate) fn undo_last_operation(
+    project: &Project,
+    interactor: &impl Interactor,
+    ignore_store: &mut IgnoreStore,
+    state: &mut CheckerState,
+) -> Result<()> {
+    let project_path = project.path();
+    if let Some(entry) = state.last_operation(project_path) {
+        if entry.is_global() {
+            let operation = &entry.operation;
+            let (word, scope) = (operation.word(), operation.scope());
+            let prompt = if operation.is_removal() {
+                format!("Undo removing '{word}' from the {scope} ignore list?")
+            } else {
+                format!("Undo adding '{word}' to the {scope} ignore list?")
+            };
+            if !interactor.confirm(&prompt) {
+                info_2!("Nothing was undone");
+                return Ok(());
+            }
+        }
     }
+    let mut operation = match state.last_operation(project_path) {
+        None => bail!("Nothing to undo"),
+        Some(e) => e.operation.clone(),
+    };
+    operation.ensure_applied(ignore_store)?;
+    operation.undo(ignore_store)?;
+    state.pop_last_operation(project_path)?;
+    Ok(())
 }
 
 impl<I: Interactor, D: Dictionary> InteractiveChecker<I, D> {
@@ -71,134 +209,464 @@ impl<I: Interactor, D: Dictionary> InteractiveChecker<I, D> {
         interactor: I,
         dictionary: D,
         ignore_store: IgnoreStore,
-        state_toml: Option<PathBuf>,
+        state: CheckerState,
     ) -> Result<Self> {
-        info_1!(
-            "Checking project {} for spelling errors",
-            project.path().as_str().bold()
-        );
-        let state = CheckerState::load(state_toml)?;
+        if interactor.event_sink().is_none() {
+            info_1!(
+                "Checking project {} for spelling errors",
+                project.path().as_str().bold()
+            );
+        }
         Ok(Self {
             project,
             dictionary,
+            langs: LangDictionaries::default(),
             interactor,
             ignore_store,
             skipped: HashSet::new(),
+            errors: 0,
             state,
+            suggestions: None,
+            check_confusables: false,
+            extension_counts: HashMap::new(),
+            total_files: None,
+            files_visited: 0,
+            in_file: false,
+            header_printed: false,
+            handled: HashMap::new(),
         })
     }
 
-    fn on_error(&mut self, path: &RelativePath, pos: (usize, usize), error: &str) -> Result<()> {
-        let lang = self.dictionary().lang().to_owned();
-        let (lineno, column) = pos;
-        let prefix = format!("{path}:{lineno}:{column}");
-        println!("{} {}", prefix, error.bold().red());
-        let prompt = r#"What to do?
-g : Add word to global ignore list
-l : Add word to the ignore list for the current language
-e : Add word to ignore list for this extension
-p : Add word to ignore list for the current project
-f : Add word to ignore list for the current file
-x : Skip this error
-q : Quit
-> "#;
+    /// Number of files to check during the run, shown before the errors
+    /// of each file
+    pub fn set_total_files(&mut self, total_files: usize) {
+        self.total_files = Some(total_files);
+    }
 
-        loop {
-            let letter = self.interactor.input_letter(prompt, "glepfnsxq");
-            match letter.as_ref() {
-                "g" => {
-                    if self.on_global_ignore(error)? {
-                        break;
-                    }
-                }
-                "l" => {
-                    if self.on_lang(error, &lang)? {
-                        break;
-                    }
-                }
-                "e" => {
-                    if self.on_extension(path, error)? {
-                        break;
-                    }
-                }
-                "p" => {
-                    if self.on_project_ignore(error)? {
-                        break;
-                    }
+    // Printed before the first error of each file
+    fn print_file_header(&self, relative_path: &RelativePath) {
+        let index = match self.total_files {
+            Some(total) => format!("[{}/{total}]", self.files_visited),
+            None => format!("[{}]", self.files_visited),
+        };
+        let so_far = match self.errors {
+            1 => "1 error".to_string(),
+            n => format!("{n} errors"),
+        };
+        output::info(&format!(
+            "{} {relative_path} — {so_far} so far this session",
+            index.bold()
+        ));
+    }
+
+    /// Used to open the dictionaries for the languages files are tagged with
+    pub fn set_lang_opener(&mut self, opener: LangOpener<D>) {
+        self.langs.set_opener(opener);
+    }
+
+    /// Suggestions for each error are computed by the worker while
+    /// the user is prompted
+    pub fn set_suggestion_worker(&mut self, mut worker: SuggestionWorker) {
+        worker.set_forbidden_words(self.ignore_store.forbidden_words());
+        self.suggestions = Some(worker);
+    }
+
+    /// Tell the user when an unknown word mixes several scripts
+    pub fn set_check_confusables(&mut self, check_confusables: bool) {
+        self.check_confusables = check_confusables;
+    }
+
+    /// Number of files checked during the run for each extension, used
+    /// to discourage ignoring words for an extension used by a single file
+    pub fn set_extension_counts(&mut self, extension_counts: HashMap<String, usize>) {
+        self.extension_counts = extension_counts;
+    }
+
+    // The actions available for an error in `path`, with their description.
+    // The identifier is only given when the word was extracted from a
+    // longer token, like `Xgboost` in `XgboostClassifier`
+    fn actions(
+        &self,
+        path: &RelativePath,
+        identifier: Option<&str>,
+        in_file_name: bool,
+    ) -> Vec<(&'static str, String)> {
+        let extension = self.ignore_store.extension_of(path);
+        let mut res = vec![];
+        // Files at the root of the project have no directory to skip
+        let directory = path.parent().filter(|_| !path.is_outside_project());
+        for (key, description) in ACTIONS {
+            match (key, &extension) {
+                // Ignoring the word for the file, or skipping the file, would
+                // not fix the name - and there is nothing to open
+                ("f" | "n" | "s" | "d" | "o", _) if in_file_name => continue,
+                ("e", None) => continue,
+                ("i", _) => match identifier {
+                    None => continue,
+                    Some(i) => res.push((key, format!("{description}: {i}"))),
+                },
+                ("d", _) => match &directory {
+                    None => continue,
+                    Some(d) => res.push((key, format!("{description} {}/", d.normalize()))),
+                },
+                ("e", Some(e)) if self.extension_counts.get(e.as_ref()) == Some(&1) => res.push((
+                    key,
+                    format!("{description} (only this file uses .{e} — consider 'f' instead)"),
+                )),
+                _ => res.push((key, description.to_string())),
+            }
+        }
+        res
+    }
+
+    fn mixed_script(&self, word: &str) -> Option<MixedScript> {
+        if self.check_confusables {
+            detect_mixed_script(word)
+        } else {
+            None
+        }
+    }
+
+    /// Suggestions for `word`, empty if they take too long to compute
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        match &self.suggestions {
+            Some(worker) => worker.suggest_with_timeout(word, SUGGESTIONS_TIMEOUT),
+            None => vec![],
+        }
+    }
+
+    fn event_sink(&self) -> Option<&dyn EventSink> {
+        self.interactor.event_sink()
+    }
+
+    fn check_event_sink(&self) -> Result<()> {
+        match self.event_sink().and_then(|s| s.take_error()) {
+            Some(e) => Err(e).context("Could not write events"),
+            None => Ok(()),
+        }
+    }
+
+    // Apply the operation, then tell the user about it
+    fn apply_and_report(&mut self, operation: Operation, message: &str) -> Result<bool> {
+        let word = operation.word().to_string();
+        let scope = operation.scope();
+        self.apply_operation(operation)?;
+        match self.event_sink() {
+            Some(sink) => sink.emit(Event::Applied { word, scope }),
+            None => info_2!("{}", message),
+        }
+        Ok(true)
+    }
+
+    // The chosen action could not be applied - returns false so that
+    // the user is asked again
+    fn reject(&self, message: &str) -> bool {
+        match self.event_sink() {
+            Some(sink) => sink.emit(Event::Rejected {
+                message: message.to_string(),
+            }),
+            None => print_error!("{}", message),
+        }
+        false
+    }
+
+    // Returns the letters the user can choose from
+    // `column` is the one shown to the user: in chars, starting at 1
+    fn prompt(
+        &self,
+        path: &RelativePath,
+        lineno: usize,
+        column: usize,
+        error: &str,
+        identifier: Option<&str>,
+        actions: &[(&'static str, String)],
+    ) -> String {
+        let in_file_name = lineno == 0;
+        let mixed_script = self.mixed_script(error);
+        let sink = match self.event_sink() {
+            Some(sink) => sink,
+            None => {
+                let location = match in_file_name {
+                    true => format!("{path} (file name)"),
+                    false => format!("{path}:{lineno}:{column}"),
+                };
+                match identifier {
+                    Some(i) => println!("{location} {} (in {i})", error.bold().red()),
+                    None => println!("{location} {}", error.bold().red()),
                 }
-                "f" => {
-                    if self.on_file_ignore(error, path)? {
-                        break;
-                    }
+                if let Some(mixed_script) = mixed_script {
+                    print_mixed_script(&mixed_script);
                 }
+                return actions.iter().map(|(key, _)| *key).collect();
+            }
+        };
+        let full_path = self.project.path().as_ref().join(path);
+        let (kind, message, suggestion) = match mixed_script {
+            _ if in_file_name => (ErrorKind::Filename, None, None),
+            Some(m) => (ErrorKind::MixedScript, Some(m.message()), m.suggestion),
+            None => (ErrorKind::UnknownWord, None, None),
+        };
+        let context = match in_file_name {
+            true => path.file_name().unwrap_or_default().to_string(),
+            false => line_at(&full_path, lineno).unwrap_or_default(),
+        };
+        sink.emit(Event::Error {
+            path: path.to_string(),
+            line: lineno,
+            column,
+            word: error.to_string(),
+            identifier: identifier.map(|i| i.to_string()),
+            kind,
+            context,
+            message,
+            suggestion,
+        });
+        // The frontend owns the terminal, so we can't open an editor
+        let actions: Vec<_> = actions.iter().filter(|(key, _)| *key != "o").collect();
+        let choices = actions.iter().map(|(key, _)| *key).collect();
+        let actions = actions
+            .into_iter()
+            .map(|(key, description)| PromptAction {
+                key: key.to_string(),
+                description: description.clone(),
+            })
+            .collect();
+        sink.emit(Event::Prompt { actions });
+        choices
+    }
+
+    fn on_error(
+        &mut self,
+        path: &RelativePath,
+        pos: (usize, usize),
+        column: usize,
+        error: &str,
+        identifier: Option<&str>,
+    ) -> Result<()> {
+        let lang = self.dictionary().lang().to_owned();
+        if let Some(worker) = &self.suggestions {
+            worker.prefetch(error);
+        }
+        // See SpellingError::is_file_name()
+        let actions = self.actions(path, identifier, pos.0 == 0);
+        let choices = self.prompt(path, pos.0, column, error, identifier, &actions);
+        let mut prompt = "What to do?\n".to_string();
+        for (key, description) in &actions {
+            prompt.push_str(&format!("{key} : {description}\n"));
+        }
+        prompt.push_str("> ");
+
+        loop {
+            let letter = self.interactor.input_letter(&prompt, &choices);
+            let handled = match letter.as_ref() {
+                "g" => self.on_global_ignore(error)?,
+                "l" => self.on_lang(error, &lang)?,
+                "e" => self.on_extension(path, error)?,
+                "p" => self.on_project_ignore(error)?,
+                "f" => self.on_file_ignore(error, path)?,
+                "i" => match identifier {
+                    Some(identifier) => self.on_identifier_ignore(identifier)?,
+                    None => unreachable!("'i' is only offered with an identifier"),
+                },
+                "n" => self.on_skip_file_name(path)?,
+                "s" => self.on_skip_path(path)?,
+                "d" => self.on_skip_directory(path)?,
+                "o" => self.on_open_editor(path, pos, error)?,
                 "q" => {
+                    // The session also stops when events can no longer be written
+                    self.check_event_sink()?;
                     bail!("Interrupted by user")
                 }
                 "x" => {
                     self.skipped.insert(error.to_string());
-                    break;
+                    true
                 }
                 _ => {
                     unreachable!()
                 }
+            };
+            if handled {
+                let key = ACTIONS
+                    .iter()
+                    .map(|(key, _)| *key)
+                    .find(|key| *key == letter)
+                    .expect("choices should come from ACTIONS");
+                *self.handled.entry(key).or_default() += 1;
+                break;
             }
         }
         Ok(())
     }
 
+    // Returns true if the error is gone once the editor exits
+    fn on_open_editor(
+        &mut self,
+        relative_path: &RelativePath,
+        pos: (usize, usize),
+        error: &str,
+    ) -> Result<bool> {
+        let (lineno, column) = pos;
+        let full_path = self.project.path().as_ref().join(relative_path);
+        self.interactor
+            .open_editor(&full_path, lineno, column + 1)?;
+        let line = line_at(&full_path, lineno);
+        let still_there = line
+            .as_deref()
+            .and_then(|line| line.get(column..column + error.len()))
+            == Some(error);
+        if still_there {
+            return Ok(self.reject(&format!("'{error}' is still there")));
+        }
+        info_2!("'{}' was changed in {}", error, relative_path);
+        Ok(true)
+    }
+
+    // Skipping is not an operation, so it cannot be undone. The checker
+    // stops reading the file, and skips the matching files for the rest
+    // of the run
+    fn skip_and_report(&mut self, pattern: &str, message: &str) -> Result<bool> {
+        match self.event_sink() {
+            Some(sink) => sink.emit(Event::SkipAdded {
+                pattern: pattern.to_string(),
+            }),
+            None => info_2!("{}", message),
+        }
+        Ok(true)
+    }
+
+    fn on_skip_file_name(&mut self, relative_path: &RelativePath) -> Result<bool> {
+        let file_name = match relative_path.file_name() {
+            None => return Ok(self.reject(&format!("{relative_path} has no file name"))),
+            Some(f) => f.to_string(),
+        };
+        self.ignore_store.skip_file_name(&file_name)?;
+        self.skip_and_report(
+            &file_name,
+            &format!("Files named '{file_name}' will be skipped"),
+        )
+    }
+
+    fn on_skip_path(&mut self, relative_path: &RelativePath) -> Result<bool> {
+        if relative_path.is_outside_project() {
+            return Ok(self.reject(&format!(
+                "{relative_path} is outside the project, use 'n' to skip files with this name instead"
+            )));
+        }
+        self.ignore_store.skip_path(relative_path)?;
+        self.skip_and_report(
+            &format!("/{}", relative_path.normalize()),
+            &format!("{relative_path} will be skipped"),
+        )
+    }
+
+    fn on_skip_directory(&mut self, relative_path: &RelativePath) -> Result<bool> {
+        let directory = match relative_path.parent() {
+            Some(d) if !relative_path.is_outside_project() => d,
+            _ => {
+                return Ok(self.reject(&format!(
+                    "{relative_path} is not in a directory of the project"
+                )))
+            }
+        };
+        self.ignore_store.skip_directory(&directory)?;
+        self.skip_and_report(
+            &directory_pattern(&directory),
+            &format!("Files in {}/ will be skipped", directory.normalize()),
+        )
+    }
+
     fn on_global_ignore(&mut self, error: &str) -> Result<bool> {
         let operation = Operation::new_ignore(error);
-        self.apply_operation(operation)?;
-        info_2!("Added '{}' to the global ignore list", error);
-        Ok(true)
+        self.apply_and_report(
+            operation,
+            &format!("Added '{error}' to the global ignore list"),
+        )
     }
 
     fn on_extension(&mut self, relative_path: &RelativePath, error: &str) -> Result<bool> {
-        let extension = match relative_path.extension() {
+        let extension = match self.ignore_store.extension_of(relative_path) {
             None => {
-                print_error!("{} has no extension", relative_path);
-                return Ok(false);
+                return Ok(self.reject(&format!("{relative_path} has no extension")));
             }
-            Some(e) => e,
+            Some(e) => e.into_owned(),
         };
 
         let operation = Operation::new_ignore_for_extension(error, &extension);
-        self.apply_operation(operation)?;
-        info_2!(
-            "Added '{}' to the ignore list for extension '{}'",
-            error,
-            extension
-        );
-        Ok(true)
+        self.apply_and_report(
+            operation,
+            &format!("Added '{error}' to the ignore list for extension '{extension}'"),
+        )
     }
 
     fn on_lang(&mut self, error: &str, lang: &str) -> Result<bool> {
         let operation = Operation::new_ignore_for_lang(error, lang);
-        self.apply_operation(operation)?;
-        info_2!("Added '{}' to the ignore list for '{}'", error, lang);
-        Ok(true)
+        self.apply_and_report(
+            operation,
+            &format!("Added '{error}' to the ignore list for '{lang}'"),
+        )
     }
 
     fn on_project_ignore(&mut self, error: &str) -> Result<bool> {
         let operation = Operation::new_ignore_for_project(error);
-        self.apply_operation(operation)?;
-        info_2!(
-            "Added '{}' to the ignore list for the current project",
-            error
-        );
-        Ok(true)
+        self.apply_and_report(
+            operation,
+            &format!("Added '{error}' to the ignore list for the current project"),
+        )
+    }
+
+    fn on_identifier_ignore(&mut self, identifier: &str) -> Result<bool> {
+        let operation = Operation::new_ignore_identifier(identifier);
+        self.apply_and_report(
+            operation,
+            &format!("Added '{identifier}' to the global list of ignored identifiers"),
+        )
     }
 
     fn on_file_ignore(&mut self, error: &str, relative_path: &RelativePath) -> Result<bool> {
+        if relative_path.is_outside_project() {
+            return Ok(self.reject(&format!(
+                "{relative_path} is outside the project, use 'p' to ignore the word for the project instead"
+            )));
+        }
         let operation = Operation::new_ignore_for_path(error, relative_path);
-        self.apply_operation(operation)?;
-        info_2!(
-            "Added '{}' to the ignore list for path '{}'",
-            error,
-            relative_path
-        );
-        Ok(true)
+        self.apply_and_report(
+            operation,
+            &format!("Added '{error}' to the ignore list for path '{relative_path}'"),
+        )
+    }
+}
+
+/// Tell how many files were visited, and how many errors were handled with
+/// each action - keys are the letters of the actions
+pub(crate) fn print_session_summary(files_visited: usize, handled: &HashMap<&str, usize>) {
+    match files_visited {
+        1 => info_2!("Visited 1 file"),
+        n => info_2!("Visited {n} files"),
     }
+    for (key, description) in HANDLED {
+        match handled.get(key) {
+            None => (),
+            Some(1) => output::info(&format!("  1 error {description}")),
+            Some(n) => output::info(&format!("  {n} errors {description}")),
+        }
+    }
+}
+
+fn print_mixed_script(mixed_script: &MixedScript) {
+    let mut message = format!("{} {}", "Mixed script:".yellow(), mixed_script.message());
+    if let Some(suggestion) = &mixed_script.suggestion {
+        message.push_str(&format!(" - did you mean '{suggestion}'?"));
+    }
+    println!("{message}");
+}
+
+// Lines start at 1
+fn line_at(path: &Path, lineno: usize) -> Option<String> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    contents
+        .lines()
+        .nth(lineno.saturating_sub(1))
+        .map(|l| l.to_string())
 }
 
 #[cfg(test)]
@@ -1,7 +1,9 @@
 use super::InteractiveChecker;
 use crate::tests::FakeInteractor;
 use skyspell_core::tests::FakeDictionary;
-use skyspell_core::{Checker, IgnoreStore, Project, RelativePath};
+use skyspell_core::{output, ProcessOutcome, SkipReason, SuggestionWorker, Token, Verbosity};
+use skyspell_core::{Checker, CheckerState, IgnoreStore, Operation, Project, RelativePath};
+use std::collections::HashMap;
 use tempfile::TempDir;
 
 type TestChecker = InteractiveChecker<FakeInteractor, FakeDictionary>;
@@ -22,14 +24,9 @@ impl TestApp {
         let global_toml = temp_dir.path().join("global.toml");
         let local_toml = temp_dir.path().join("skyspell.toml");
         let ignore_store = IgnoreStore::load(global_toml, local_toml).unwrap();
-        let checker = TestChecker::new(
-            project,
-            interactor,
-            dictionary,
-            ignore_store,
-            Some(state_toml),
-        )
-        .unwrap();
+        let state = CheckerState::load(Some(state_toml)).unwrap();
+        let checker =
+            TestChecker::new(project, interactor, dictionary, ignore_store, state).unwrap();
         Self { checker }
     }
 
@@ -43,6 +40,32 @@ impl TestApp {
         self.checker.interactor.push_text(answer)
     }
 
+    fn push_bool(&mut self, answer: bool) {
+        self.checker.interactor.push_bool(answer)
+    }
+
+    fn push_edit(&mut self, contents: &str) {
+        self.checker.interactor.push_edit(contents)
+    }
+
+    fn handle_token_in(
+        &mut self,
+        token: &str,
+        relative_name: &str,
+        contents: &str,
+        pos: (usize, usize),
+    ) {
+        let project_path = self.checker.project().path();
+        let full_path = project_path.as_ref().join(relative_name);
+        std::fs::write(full_path, contents).unwrap();
+        let relative_path = self.to_relative_path(relative_name);
+        let line = contents.lines().nth(pos.0.saturating_sub(1)).unwrap_or("");
+        let token = Token::new(token, line, pos);
+        self.checker
+            .handle_token(&token, &relative_path, &())
+            .unwrap()
+    }
+
     fn to_relative_path(&self, path: &str) -> RelativePath {
         let project_path = self.checker.project.path();
         let path = project_path.as_ref().join(path);
@@ -54,8 +77,9 @@ impl TestApp {
         let full_path = project_path.as_ref().join(relative_name);
         std::fs::write(full_path, "").unwrap();
         let relative_path = self.to_relative_path(relative_name);
+        let token = Token::new(token, "", (3, 42));
         self.checker
-            .handle_token(token, &relative_path, (3, 42), &())
+            .handle_token(&token, &relative_path, &())
             .unwrap()
     }
 
@@ -127,6 +151,49 @@ fn test_adding_word_to_extension() {
     app.end();
 }
 
+#[test]
+fn test_no_extension_option_for_files_without_extension() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.push_text("x");
+
+    app.handle_token("foo", "Makefile");
+
+    let (prompt, choices) = app.checker.interactor.last_letter_prompt().unwrap();
+    assert!(!prompt.contains("e : "));
+    assert!(!choices.contains('e'));
+    assert!(choices.contains('f'));
+    app.end();
+}
+
+#[test]
+fn test_hint_when_only_one_file_uses_the_extension() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.checker.set_extension_counts(HashMap::from([
+        ("orig".to_string(), 1),
+        ("py".to_string(), 2),
+    ]));
+    app.push_text("x");
+    app.push_text("x");
+
+    app.handle_token("foo", "foo.py.orig");
+    let (prompt, _) = app.checker.interactor.last_letter_prompt().unwrap();
+    assert!(prompt.contains("(only this file uses .orig — consider 'f' instead)"));
+
+    app.handle_token("bar", "bar.py");
+    let (prompt, choices) = app.checker.interactor.last_letter_prompt().unwrap();
+    assert!(!prompt.contains("only this file"));
+    assert!(choices.contains('e'));
+    app.end();
+}
+
 #[test]
 fn test_adding_word_to_lang() {
     let temp_dir = tempfile::Builder::new()
@@ -178,6 +245,50 @@ fn test_ignore_word_to_project_file() {
     app.end()
 }
 
+#[test]
+fn test_ignore_the_whole_identifier() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.push_text("i");
+    let line = "XgboostClassifier";
+    std::fs::write(temp_dir.path().join("project/model.py"), line).unwrap();
+    let relative_path = app.to_relative_path("model.py");
+
+    for (word, column) in [("Xgboost", 0), ("Classifier", 7)] {
+        let token = Token::new(word, line, (1, column)).with_identifier(line);
+        app.checker
+            .handle_token(&token, &relative_path, &())
+            .unwrap();
+    }
+
+    let store = app.checker.ignore_store();
+    assert!(store.is_ignored_identifier("XgboostClassifier"));
+    assert!(!store.is_ignored("Xgboost"));
+    app.end();
+}
+
+#[test]
+fn test_no_identifier_option_for_whole_words() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    std::fs::write(temp_dir.path().join("project/foo.py"), "").unwrap();
+    let foo_py = app.to_relative_path("foo.py");
+
+    let actions = app.checker.actions(&foo_py, None, false);
+    let with_identifier = app.checker.actions(&foo_py, Some("fooBar"), false);
+
+    assert!(actions.iter().all(|(key, _)| *key != "i"));
+    assert!(with_identifier
+        .iter()
+        .any(|(key, description)| *key == "i" && description.ends_with(": fooBar")));
+}
+
 #[test]
 fn test_remember_skipped_words() {
     let temp_dir = tempfile::Builder::new()
@@ -193,3 +304,333 @@ fn test_remember_skipped_words() {
 
     app.end();
 }
+
+#[test]
+fn test_cannot_ignore_word_for_file_outside_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.push_text("f");
+    app.push_text("p");
+    let intro_md = RelativePath::from_path_unchecked("../handbook/intro.md".into());
+
+    app.checker
+        .handle_token(&Token::new("foo", "", (3, 42)), &intro_md, &())
+        .unwrap();
+
+    assert!(app.is_ignored_for_project("foo"));
+    app.end()
+}
+
+#[test]
+fn test_fixing_error_in_editor() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.push_text("o");
+    app.push_edit("hello\nthe fixed word\n");
+
+    app.handle_token_in("fxied", "foo.txt", "hello\nthe fxied word\n", (2, 4));
+
+    assert!(!app.is_ignored("fxied"));
+    app.end();
+}
+
+#[test]
+fn test_asking_again_when_error_is_still_there_after_editing() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.push_text("o");
+    app.push_edit("hello\nthe fxied word\n");
+    app.push_text("g");
+
+    app.handle_token_in("fxied", "foo.txt", "hello\nthe fxied word\n", (2, 4));
+
+    assert!(app.is_ignored("fxied"));
+    app.end();
+}
+
+#[test]
+fn test_undo_global_operation_asks_for_confirmation() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.push_text("g");
+    app.handle_token("foo", "foo.txt");
+
+    app.push_bool(false);
+    app.checker.undo().unwrap();
+    assert!(app.is_ignored("foo"));
+
+    app.push_bool(true);
+    app.checker.undo().unwrap();
+    assert!(!app.is_ignored("foo"));
+
+    app.end();
+}
+
+#[test]
+fn test_undo_prompt_depends_on_the_operation() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.push_text("g");
+    app.handle_token("foo", "foo.txt");
+    app.checker
+        .apply_operation(Operation::new_remove(vec![Operation::new_ignore("foo")]))
+        .unwrap();
+
+    app.push_bool(true);
+    app.checker.undo().unwrap();
+    assert_eq!(
+        app.checker.interactor.last_confirm_prompt().unwrap(),
+        "Undo removing 'foo' from the global ignore list?"
+    );
+    assert!(app.is_ignored("foo"));
+
+    app.push_bool(true);
+    app.checker.undo().unwrap();
+    assert_eq!(
+        app.checker.interactor.last_confirm_prompt().unwrap(),
+        "Undo adding 'foo' to the global ignore list?"
+    );
+    assert!(!app.is_ignored("foo"));
+
+    app.end();
+}
+
+#[test]
+fn test_undo_project_operation_does_not_ask_for_confirmation() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.push_text("p");
+    app.handle_token("foo", "foo.txt");
+
+    app.checker.undo().unwrap();
+
+    assert!(!app.is_ignored_for_project("foo"));
+    app.end();
+}
+
+#[test]
+fn test_suggestions_are_prefetched_while_prompting() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.checker.set_suggestion_worker(SuggestionWorker::new(|| {
+        let mut dictionary = FakeDictionary::new();
+        dictionary.add_suggestions("fooo", &["foo".to_string()]);
+        Ok(dictionary)
+    }));
+    app.push_text("x");
+
+    app.handle_token("fooo", "foo.py");
+
+    assert_eq!(app.checker.suggestions("fooo"), &["foo"]);
+    app.end();
+}
+
+#[test]
+fn test_skip_file_name_for_the_rest_of_the_run() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let project_path = temp_dir.path().join("project");
+    let mut paths = vec![];
+    for dir in ["a", "b"] {
+        std::fs::create_dir(project_path.join(dir)).unwrap();
+        let path = project_path.join(dir).join("yarn.lock");
+        std::fs::write(&path, "fooo barr\n").unwrap();
+        paths.push(path);
+    }
+    app.push_text("n");
+
+    let first = app.checker.process(&paths[0], &()).unwrap();
+    let second = app.checker.process(&paths[1], &()).unwrap();
+
+    // 'barr' in the first file and both errors in the second one were not reported
+    assert_eq!(first, ProcessOutcome::Skipped(SkipReason::SkipFile));
+    assert_eq!(second, ProcessOutcome::Skipped(SkipReason::SkipFile));
+    assert_eq!(app.checker.errors, 1);
+    let relative_path = RelativePath::from_path_unchecked("c/yarn.lock".into());
+    assert!(app.checker.ignore_store().is_skipped(&relative_path));
+    app.end();
+}
+
+#[test]
+fn test_skip_path() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let project_path = temp_dir.path().join("project");
+    let foo_md = project_path.join("foo.md");
+    std::fs::write(&foo_md, "fooo\n").unwrap();
+    app.push_text("s");
+
+    app.checker.process(&foo_md, &()).unwrap();
+
+    let foo_md = app.to_relative_path("foo.md");
+    let other_foo_md = RelativePath::from_path_unchecked("other/foo.md".into());
+    let store = app.checker.ignore_store();
+    assert!(store.is_skipped(&foo_md));
+    assert!(!store.is_skipped(&other_foo_md));
+    app.end();
+}
+
+#[test]
+fn test_skip_directory() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir_all(project_path.join("assets/fonts")).unwrap();
+    let license = project_path.join("assets/fonts/LICENSE");
+    let readme = project_path.join("assets/README.md");
+    std::fs::write(&license, "fooo barr\n").unwrap();
+    std::fs::write(&readme, "bazz\n").unwrap();
+    app.push_text("d");
+    app.push_text("x");
+
+    let first = app.checker.process(&license, &()).unwrap();
+    let second = app.checker.process(&readme, &()).unwrap();
+
+    // Only the fonts directory is skipped
+    assert_eq!(first, ProcessOutcome::Skipped(SkipReason::SkipFile));
+    assert_eq!(second, ProcessOutcome::Checked);
+    let store = app.checker.ignore_store();
+    assert!(store.has_skip_pattern("/assets/fonts/"));
+    let nested = RelativePath::from_path_unchecked("assets/fonts/woff/LICENSE".into());
+    assert!(store.is_skipped(&nested));
+    app.end();
+}
+
+#[test]
+fn test_no_directory_to_skip_at_the_root_of_the_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let at_root = RelativePath::from_path_unchecked("foo.md".into());
+    let in_docs = RelativePath::from_path_unchecked("docs/foo.md".into());
+
+    let at_root = app.checker.actions(&at_root, None, false);
+    let in_docs = app.checker.actions(&in_docs, None, false);
+
+    assert!(at_root.iter().all(|(key, _)| *key != "d"));
+    assert!(in_docs.contains(&("d", "Always skip the directory docs/".to_string())));
+}
+
+#[test]
+fn test_header_before_the_errors_of_each_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.add_known(&["hello"]);
+    let project_path = temp_dir.path().join("project");
+    let mut paths = vec![];
+    for (name, contents) in [
+        ("a.txt", "fooo barr\n"),
+        ("b.txt", "hello\n"),
+        ("c.txt", "bazz\n"),
+    ] {
+        let path = project_path.join(name);
+        std::fs::write(&path, contents).unwrap();
+        paths.push(path);
+    }
+    app.checker.set_total_files(paths.len());
+    for _ in 0..3 {
+        app.push_text("x");
+    }
+
+    let captured = output::capture(Verbosity::Normal, || {
+        for path in &paths {
+            app.checker.process(path, &()).unwrap();
+        }
+    });
+
+    let headers: Vec<_> = captured
+        .stdout
+        .lines()
+        .filter(|l| l.contains("so far this session"))
+        .collect();
+    assert_eq!(headers.len(), 2, "{captured:?}");
+    assert!(headers[0].contains("a.txt — 0 errors"), "{captured:?}");
+    assert!(headers[0].contains("[1/3]"), "{captured:?}");
+    assert!(headers[1].contains("c.txt — 2 errors"), "{captured:?}");
+    assert!(headers[1].contains("[3/3]"), "{captured:?}");
+    app.end();
+}
+
+#[test]
+fn test_session_summary() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let foo_md = temp_dir.path().join("project").join("foo.md");
+    std::fs::write(&foo_md, "fooo barr bazz\n").unwrap();
+    app.push_text("g");
+    app.push_text("p");
+    app.push_text("p");
+
+    let captured = output::capture(Verbosity::Normal, || {
+        app.checker.process(&foo_md, &()).unwrap();
+        app.checker.success().unwrap();
+    });
+
+    assert!(captured.stdout.contains("Visited 1 file"), "{captured:?}");
+    assert!(
+        captured
+            .stdout
+            .contains("1 error added to the global ignore list"),
+        "{captured:?}"
+    );
+    assert!(
+        captured
+            .stdout
+            .contains("2 errors added to the ignore list for the project"),
+        "{captured:?}"
+    );
+    app.end();
+}
+
+#[test]
+fn test_no_file_actions_for_errors_in_file_names() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let path = RelativePath::from_path_unchecked("docs/instalation.md".into());
+
+    let actions = app.checker.actions(&path, None, true);
+
+    let keys: Vec<_> = actions.iter().map(|(key, _)| *key).collect();
+    assert_eq!(keys, ["g", "l", "e", "p", "x", "q"]);
+}
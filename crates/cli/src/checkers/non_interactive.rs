@@ -1,10 +1,20 @@
-use crate::{info_1, info_2, OutputFormat};
-use anyhow::{bail, Result};
+use crate::events::ErrorKind;
+use crate::{info_1, info_2, print_error, CheckFailures, FileFailure, OutputFormat, SortBy};
+use anyhow::{bail, Context, Result};
 use colored::*;
 use serde::Serialize;
-use skyspell_core::{Checker, Dictionary, IgnoreStore, Operation, SpellingError};
-use skyspell_core::{Project, RelativePath};
+use skyspell_core::{detect_mixed_script, output, Checker, Dictionary, Encoding, ErrorSink};
+use skyspell_core::{normalize_path, Project, RelativePath, ReportEntry, SeenFindings};
+use skyspell_core::{IgnoreReason, IgnoreStore};
+use skyspell_core::{LangDictionaries, LangOpener, Operation, SpellingError, TokenClassifier};
+use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+/// How columns are counted in the JSON output
+const COLUMN_UNIT: &str = "char";
 
 #[derive(Debug, Serialize)]
 struct Range {
@@ -13,19 +23,325 @@ struct Range {
     end_column: usize,
 }
 
+impl Range {
+    // Errors in file names are not in the file: line and columns are 0
+    fn of(error: &SpellingError) -> Self {
+        if error.is_file_name() {
+            return Self {
+                line: 0,
+                start_column: 0,
+                end_column: 0,
+            };
+        }
+        let (line, _) = error.pos();
+        let start_column = error.columns().char + 1;
+        let end_column = start_column + error.word().chars().count() - 1;
+        Self {
+            line,
+            start_column,
+            end_column,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct Error {
     word: String,
     range: Range,
+    kind: ErrorKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suggestion: Option<String>,
+    // The source of the rule ignoring the word, see set_strict_scopes()
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suppressed_by: Option<String>,
+}
+
+/// A line whose errors were not reported because most of its words
+/// are unknown, see IgnoreStore::max_line_error_ratio()
+#[derive(Debug, Serialize)]
+struct SkippedLine {
+    path: String,
+    line: usize,
+}
+
+// A FileFailure, with the path shown like the ones of the errors
+#[derive(Debug, Serialize)]
+struct PathFailure<'a> {
+    path: Cow<'a, str>,
+    message: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct PathError<'a> {
+    path: Cow<'a, str>,
+    #[serde(flatten)]
+    error: &'a Error,
+}
+
+// Keeps the errors for the final report, and prints them as soon
+// as they are found when the report does not need to sort them
+#[derive(Default)]
+struct ErrorCollector {
+    print_errors: bool,
+    check_confusables: bool,
+    errors: BTreeMap<String, Vec<Error>>,
+    num_errors: usize,
+    // Words only ignored by rules outside the strict scopes, by path
+    suppressed: BTreeMap<String, Vec<Error>>,
+    skipped_lines: Vec<SkippedLine>,
+    // Files that are not in UTF-8, by path
+    encodings: BTreeMap<String, Encoding>,
+    // The project root, when paths are shown as absolute paths - they
+    // are always stored relative to the project
+    absolute_root: Option<PathBuf>,
+}
+
+impl ErrorSink for ErrorCollector {
+    fn on_error(&mut self, error: &SpellingError) -> Result<()> {
+        self.num_errors += 1;
+        let token = error.word();
+        let path = error.relative_path();
+        let range = Range::of(error);
+        let mixed_script = if self.check_confusables {
+            detect_mixed_script(token)
+        } else {
+            None
+        };
+        let error = match mixed_script {
+            _ if error.is_file_name() => Error {
+                word: token.to_string(),
+                range,
+                kind: ErrorKind::Filename,
+                message: None,
+                suggestion: None,
+                suppressed_by: None,
+            },
+            Some(mixed_script) => Error {
+                word: token.to_string(),
+                range,
+                kind: ErrorKind::MixedScript,
+                message: Some(mixed_script.message()),
+                suggestion: mixed_script.suggestion,
+                suppressed_by: None,
+            },
+            None => Error {
+                word: token.to_string(),
+                range,
+                kind: ErrorKind::UnknownWord,
+                message: None,
+                suggestion: None,
+                suppressed_by: None,
+            },
+        };
+        if self.print_errors {
+            self.print_error(&path.normalize(), &error);
+        }
+        let entry = self.errors.entry(path.normalize());
+        let errors_for_entry = entry.or_default();
+        errors_for_entry.push(error);
+        Ok(())
+    }
+}
+
+impl ErrorCollector {
+    // Relative to the project with forward slashes, or canonical and
+    // absolute with --absolute-paths
+    fn shown_path<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        let root = match &self.absolute_root {
+            Some(root) => root,
+            None => return Cow::Borrowed(path),
+        };
+        let full_path = root.join(path);
+        let full_path = std::fs::canonicalize(&full_path).unwrap_or(full_path);
+        Cow::Owned(normalize_path(&full_path.to_string_lossy()))
+    }
+
+    // Same keys as shown_path(), for the JSON output
+    fn with_shown_paths<'a, T>(
+        &self,
+        by_path: &'a BTreeMap<String, T>,
+    ) -> BTreeMap<Cow<'a, str>, &'a T> {
+        by_path
+            .iter()
+            .map(|(path, value)| (self.shown_path(path), value))
+            .collect()
+    }
+
+    fn print_error(&self, path: &str, error: &Error) {
+        print_error(&self.shown_path(path), error);
+    }
+
+    fn on_suppressed(&mut self, error: &SpellingError, reason: &IgnoreReason) {
+        let token = error.word();
+        let path = error.relative_path();
+        let kind = match error.is_file_name() {
+            true => ErrorKind::Filename,
+            false => ErrorKind::UnknownWord,
+        };
+        let error = Error {
+            word: token.to_string(),
+            range: Range::of(error),
+            kind,
+            message: Some(reason.to_string()),
+            suggestion: None,
+            suppressed_by: Some(reason.source().to_string()),
+        };
+        if self.print_errors {
+            self.print_error(&path.normalize(), &error);
+        }
+        self.suppressed
+            .entry(path.normalize())
+            .or_default()
+            .push(error);
+    }
+}
+
+fn print_error(path: &str, error: &Error) {
+    let Error {
+        range,
+        word,
+        kind,
+        message,
+        suggestion,
+        suppressed_by,
+    } = error;
+    let Range {
+        line,
+        start_column,
+        end_column,
+    } = range;
+    let prefix = format!("{path}:{line}:{start_column}:{end_column}");
+    let mut details = String::new();
+    if let Some(message) = message {
+        details.push_str(&format!(" - {message}"));
+    }
+    if let Some(suggestion) = suggestion {
+        details.push_str(&format!(" - did you mean '{suggestion}'?"));
+    }
+    let label = match suppressed_by {
+        Some(source) => format!("suppressed-by: {source}").yellow(),
+        None => "error".red(),
+    };
+    output::print(&format!(
+        "{}: {}: {}: {}{}",
+        prefix,
+        label,
+        kind.description().clear(),
+        word,
+        details
+    ));
+}
+
+/// An unknown word found too many times to be reported as an error,
+/// see set_min_occurrences_to_accept()
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(crate) struct FrequentWord {
+    word: String,
+    count: usize,
+}
+
+/// An entry of the ignore lists that did not cause any word to be
+/// ignored, see set_unused_ignores()
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(crate) struct UnusedIgnore {
+    pub(crate) word: String,
+    pub(crate) scope: String,
+    // Set when the entry was removed with --fix
+    pub(crate) removed: bool,
+}
+
+pub(crate) fn print_unused_ignores(unused_ignores: &[UnusedIgnore]) {
+    if unused_ignores.is_empty() {
+        info_2!("No unused ignore entries");
+        return;
+    }
+    info_2!("Unused ignore entries:");
+    for UnusedIgnore {
+        word,
+        scope,
+        removed,
+    } in unused_ignores
+    {
+        let removed = if *removed { " - removed" } else { "" };
+        output::info(&format!("  {word} ({scope}){removed}"));
+    }
+}
+
+/// An entry of the ignore lists and the number of words it caused to
+/// be ignored, see set_ignore_hits()
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(crate) struct IgnoreHit {
+    pub(crate) word: String,
+    pub(crate) scope: String,
+    pub(crate) hits: usize,
+}
+
+fn print_ignore_hits(ignore_hits: &[IgnoreHit]) {
+    if ignore_hits.is_empty() {
+        info_2!("No word was ignored");
+        return;
+    }
+    info_2!("Ignored words:");
+    let width = ignore_hits.iter().map(|h| h.word.len()).max().unwrap_or(0);
+    for IgnoreHit { word, scope, hits } in ignore_hits {
+        output::info(&format!("  {word:width$}  {scope:20}  {hits}"));
+    }
+}
+
+/// Errors found by a checker running in a worker thread
+pub(crate) struct WorkerErrors {
+    errors: BTreeMap<String, Vec<Error>>,
+    num_errors: usize,
+    suppressed: BTreeMap<String, Vec<Error>>,
+    skipped_lines: Vec<SkippedLine>,
+    encodings: BTreeMap<String, Encoding>,
 }
 
 pub struct NonInteractiveChecker<D: Dictionary> {
     project: Project,
     dictionary: D,
+    langs: LangDictionaries<D>,
     ignore_store: IgnoreStore,
     output_format: OutputFormat,
-    errors: BTreeMap<String, Vec<Error>>,
-    num_errors: usize,
+    sort_by: SortBy,
+    collector: ErrorCollector,
+    failures: Vec<FileFailure>,
+    min_occurrences_to_accept: Option<usize>,
+    frequent_words: Vec<FrequentWord>,
+    seen_findings: Option<SeenFindings>,
+    num_hidden: usize,
+    output_path: Option<PathBuf>,
+    unused_ignores: Option<Vec<UnusedIgnore>>,
+    strict_unused: bool,
+    ignore_hits: Option<Vec<IgnoreHit>>,
+    strict_fail: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonOutput<'a, T: Serialize> {
+    // The paths below are relative to this directory, unless
+    // --absolute-paths is used
+    project: String,
+    errors: T,
+    failures: Vec<PathFailure<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequent_words: Option<&'a [FrequentWord]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hidden: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    skipped_lines: Vec<SkippedLine>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    encodings: BTreeMap<Cow<'a, str>, &'a Encoding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unused_ignores: Option<&'a [UnusedIgnore]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ignore_hits: Option<&'a [IgnoreHit]>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    suppressed: BTreeMap<Cow<'a, str>, &'a Vec<Error>>,
+    // Columns start at 1 and count Unicode scalar values
+    column_unit: &'static str,
 }
 
 impl<D: Dictionary> NonInteractiveChecker<D> {
@@ -44,34 +360,387 @@ impl<D: Dictionary> NonInteractiveChecker<D> {
         Ok(Self {
             project,
             dictionary,
+            langs: LangDictionaries::default(),
             ignore_store,
             output_format,
-            errors: BTreeMap::new(),
-            num_errors: 0,
+            sort_by: SortBy::default(),
+            collector: ErrorCollector {
+                print_errors: output_format == OutputFormat::Text,
+                ..Default::default()
+            },
+            failures: vec![],
+            min_occurrences_to_accept: None,
+            frequent_words: vec![],
+            seen_findings: None,
+            num_hidden: 0,
+            output_path: None,
+            unused_ignores: None,
+            strict_unused: false,
+            ignore_hits: None,
+            strict_fail: false,
         })
     }
 
-    fn print_error(&self, path: &RelativePath, error: &Error) {
-        let Error { range, word } = error;
-        let Range {
-            line,
-            start_column,
-            end_column,
-        } = range;
-        let prefix = format!("{path}:{line}:{start_column}:{end_column}");
-        println!(
-            "{}: {}: {}: {}",
-            prefix,
-            "error".red(),
-            "unknown word".clear(),
-            word
-        );
+    /// Used to open the dictionaries for the languages files are tagged with
+    pub fn set_lang_opener(&mut self, opener: LangOpener<D>) {
+        self.langs.set_opener(opener);
+    }
+
+    pub(crate) fn lang_opener(&self) -> Option<LangOpener<D>> {
+        self.langs.opener().cloned()
+    }
+
+    pub fn set_sort_by(&mut self, sort_by: SortBy) {
+        self.sort_by = sort_by;
+        self.update_print_errors();
+    }
+
+    /// Only report the words found less than `min_occurrences` times in
+    /// all the checked files - the other ones are listed separately by
+    /// split_frequent_words()
+    pub fn set_min_occurrences_to_accept(&mut self, min_occurrences: usize) {
+        self.min_occurrences_to_accept = Some(min_occurrences);
+        self.update_print_errors();
+    }
+
+    /// Don't report the words already found in the same file by a previous
+    /// run - they are removed by hide_seen_findings()
+    pub fn set_seen_findings(&mut self, seen_findings: SeenFindings) {
+        self.seen_findings = Some(seen_findings);
+        self.update_print_errors();
+    }
+
+    /// Write the JSON document to this file instead of stdout, and print
+    /// the usual summary on stdout
+    pub fn set_output_path(&mut self, output_path: PathBuf) {
+        self.output_path = Some(output_path);
+    }
+
+    /// Report the entries of the ignore lists that were not used by the
+    /// check. With `strict`, the ones that were not removed are an error
+    pub(crate) fn set_unused_ignores(&mut self, unused_ignores: Vec<UnusedIgnore>, strict: bool) {
+        self.unused_ignores = Some(unused_ignores);
+        self.strict_unused = strict;
+    }
+
+    /// Report how many words each entry of the ignore lists caused to
+    /// be ignored
+    pub(crate) fn set_ignore_hits(&mut self, ignore_hits: Vec<IgnoreHit>) {
+        self.ignore_hits = Some(ignore_hits);
+    }
+
+    /// Fail when some words are only ignored by rules outside the strict
+    /// scopes, see IgnoreStore::set_strict_scopes()
+    pub(crate) fn set_strict_fail(&mut self, strict_fail: bool) {
+        self.strict_fail = strict_fail;
+    }
+
+    /// Show canonical absolute paths instead of paths relative to the
+    /// project, in the text and in the JSON output
+    pub(crate) fn set_absolute_paths(&mut self, absolute_paths: bool) {
+        self.collector.absolute_root = match absolute_paths {
+            true => Some(self.project.path().as_ref().to_path_buf()),
+            false => None,
+        };
+    }
+
+    // Errors can only be printed as soon as they are found when
+    // nothing has to be known about the other files
+    fn update_print_errors(&mut self) {
+        self.collector.print_errors = self.output_format == OutputFormat::Text
+            && self.sort_by == SortBy::Path
+            && self.min_occurrences_to_accept.is_none()
+            && self.seen_findings.is_none();
+    }
+
+    /// Once every file is checked, remove the errors already found by a
+    /// previous run, and return all the findings of this one
+    pub(crate) fn hide_seen_findings(&mut self) -> SeenFindings {
+        let findings = SeenFindings::from_entries(&self.report_entries());
+        let seen = match &self.seen_findings {
+            None => return findings,
+            Some(seen) => seen,
+        };
+        let collector = &mut self.collector;
+        for (path, errors) in collector.errors.iter_mut() {
+            errors.retain(|e| !seen.contains(path, &e.word));
+        }
+        collector.errors.retain(|_, errors| !errors.is_empty());
+        let num_errors = collector.errors.values().map(|e| e.len()).sum();
+        self.num_hidden = collector.num_errors - num_errors;
+        collector.num_errors = num_errors;
+        findings
+    }
+
+    /// Once every file is checked, remove the errors for words found at
+    /// least `min_occurrences_to_accept` times
+    pub(crate) fn split_frequent_words(&mut self) {
+        let min_occurrences = match self.min_occurrences_to_accept {
+            None => return,
+            Some(n) => n,
+        };
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for error in self.collector.errors.values().flatten() {
+            *counts.entry(&error.word).or_default() += 1;
+        }
+        let mut frequent_words: Vec<_> = counts
+            .into_iter()
+            .filter(|(_, count)| *count >= min_occurrences)
+            .map(|(word, count)| FrequentWord {
+                word: word.to_string(),
+                count,
+            })
+            .collect();
+        // Most frequent first
+        frequent_words.sort_by_key(|f| std::cmp::Reverse(f.count));
+        let is_frequent = |word: &str| frequent_words.iter().any(|f| f.word == word);
+        let collector = &mut self.collector;
+        for errors in collector.errors.values_mut() {
+            errors.retain(|e| !is_frequent(&e.word));
+        }
+        collector.errors.retain(|_, errors| !errors.is_empty());
+        collector.num_errors = collector.errors.values().map(|e| e.len()).sum();
+        self.frequent_words = frequent_words;
+    }
+
+    /// A checker for a worker thread: it does not print anything, and its
+    /// errors are merged into the main checker with merge_worker_errors()
+    pub(crate) fn new_worker(
+        project: Project,
+        dictionary: D,
+        ignore_store: IgnoreStore,
+        check_confusables: bool,
+    ) -> Self {
+        Self {
+            project,
+            dictionary,
+            langs: LangDictionaries::default(),
+            ignore_store,
+            output_format: OutputFormat::default(),
+            sort_by: SortBy::default(),
+            collector: ErrorCollector {
+                check_confusables,
+                ..Default::default()
+            },
+            failures: vec![],
+            min_occurrences_to_accept: None,
+            frequent_words: vec![],
+            seen_findings: None,
+            num_hidden: 0,
+            output_path: None,
+            unused_ignores: None,
+            strict_unused: false,
+            ignore_hits: None,
+            strict_fail: false,
+        }
+    }
+
+    pub(crate) fn into_worker_errors(self) -> WorkerErrors {
+        let ErrorCollector {
+            errors,
+            num_errors,
+            suppressed,
+            skipped_lines,
+            encodings,
+            ..
+        } = self.collector;
+        WorkerErrors {
+            errors,
+            num_errors,
+            suppressed,
+            skipped_lines,
+            encodings,
+        }
+    }
+
+    /// Add the errors found by the workers, then print them all
+    /// if they would have been printed as soon as found
+    pub(crate) fn merge_worker_errors(&mut self, worker_errors: Vec<WorkerErrors>) {
+        let collector = &mut self.collector;
+        for worker in worker_errors {
+            let WorkerErrors {
+                errors,
+                num_errors,
+                suppressed,
+                skipped_lines,
+                encodings,
+            } = worker;
+            collector.num_errors += num_errors;
+            collector.skipped_lines.extend(skipped_lines);
+            collector.encodings.extend(encodings);
+            for (path, errors) in errors {
+                collector.errors.entry(path).or_default().extend(errors);
+            }
+            for (path, suppressed) in suppressed {
+                collector
+                    .suppressed
+                    .entry(path)
+                    .or_default()
+                    .extend(suppressed);
+            }
+        }
+        collector
+            .skipped_lines
+            .sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        if collector.print_errors {
+            for (path, errors) in &collector.errors {
+                for error in errors {
+                    collector.print_error(path, error);
+                }
+            }
+            for (path, suppressed) in &collector.suppressed {
+                for error in suppressed {
+                    collector.print_error(path, error);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn check_confusables(&self) -> bool {
+        self.collector.check_confusables
+    }
+
+    /// Report unknown words mixing several scripts as mixed-script errors
+    pub fn set_check_confusables(&mut self, check_confusables: bool) {
+        self.collector.check_confusables = check_confusables;
+    }
+
+    pub(crate) fn set_failures(&mut self, failures: Vec<FileFailure>) {
+        self.failures = failures;
+    }
+
+    pub(crate) fn has_failures(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    /// The errors found so far, sorted by path and position
+    pub fn report_entries(&self) -> Vec<ReportEntry> {
+        self.collector
+            .errors
+            .iter()
+            .flat_map(|(path, errors)| {
+                errors.iter().map(move |e| ReportEntry {
+                    word: e.word.clone(),
+                    path: path.clone(),
+                    line: e.range.line,
+                    column: e.range.start_column,
+                })
+            })
+            .collect()
+    }
+
+    // Spelling errors take precedence: they tell the user what to fix
+    fn failures_result(&self) -> Result<()> {
+        if self.failures.is_empty() {
+            Ok(())
+        } else {
+            Err(CheckFailures(self.failures.len()).into())
+        }
+    }
+
+    // Only with --strict-unused, once there are no spelling errors
+    // and every file could be checked
+    fn unused_ignores_result(&self) -> Result<()> {
+        let unused_ignores = match &self.unused_ignores {
+            Some(u) if self.strict_unused => u,
+            _ => return Ok(()),
+        };
+        match unused_ignores.iter().filter(|u| !u.removed).count() {
+            0 => Ok(()),
+            1 => bail!("Found 1 unused ignore entry"),
+            n => bail!("Found {n} unused ignore entries"),
+        }
+    }
+
+    fn num_suppressed(&self) -> usize {
+        self.collector.suppressed.values().map(|s| s.len()).sum()
+    }
+
+    // Only with --strict-fail, like unused_ignores_result()
+    fn suppressed_result(&self) -> Result<()> {
+        if !self.strict_fail {
+            return Ok(());
+        }
+        match self.num_suppressed() {
+            0 => Ok(()),
+            1 => bail!("Found 1 word only ignored outside the strict scopes"),
+            n => bail!("Found {n} words only ignored outside the strict scopes"),
+        }
+    }
+
+    // Note: errors are already sorted by path and position in the map,
+    // and tokens are always processed in order
+    fn sorted_errors(&self) -> Vec<(&str, &Error)> {
+        let mut res: Vec<_> = self
+            .collector
+            .errors
+            .iter()
+            .flat_map(|(path, errors)| errors.iter().map(move |e| (path.as_str(), e)))
+            .collect();
+        if self.sort_by == SortBy::Word {
+            res.sort_by(|(_, a), (_, b)| a.word.cmp(&b.word));
+        }
+        res
     }
 
     fn success_text(&self) -> Result<()> {
-        match self.num_errors {
+        if !self.collector.print_errors {
+            for (path, error) in self.sorted_errors() {
+                self.collector.print_error(path, error);
+            }
+            for (path, suppressed) in &self.collector.suppressed {
+                for error in suppressed {
+                    self.collector.print_error(path, error);
+                }
+            }
+        }
+        match self.num_suppressed() {
+            0 => (),
+            1 => info_2!("1 word is only ignored outside the strict scopes"),
+            n => info_2!("{n} words are only ignored outside the strict scopes"),
+        }
+        if !self.frequent_words.is_empty() {
+            info_2!("Frequent unknown words (consider ignoring):");
+            for FrequentWord { word, count } in &self.frequent_words {
+                output::info(&format!("  {word} ({count})"));
+            }
+        }
+        match self.collector.skipped_lines.len() {
+            0 => (),
+            1 => info_2!("1 line skipped due to error density"),
+            n => info_2!("{n} lines skipped due to error density"),
+        }
+        if let Some(unused_ignores) = &self.unused_ignores {
+            print_unused_ignores(unused_ignores);
+        }
+        if let Some(ignore_hits) = &self.ignore_hits {
+            print_ignore_hits(ignore_hits);
+        }
+        match self.num_hidden {
+            0 => (),
+            1 => info_2!("1 error already found by a previous run was not shown"),
+            n => info_2!("{n} errors already found by a previous run were not shown"),
+        }
+        if !self.failures.is_empty() {
+            print_error!("Some files could not be checked:");
+            for FileFailure { path, message } in &self.failures {
+                let path = self.collector.shown_path(path);
+                output::error(&format!("  {path}: {message}"));
+            }
+        }
+        self.summary()
+    }
+
+    fn summary(&self) -> Result<()> {
+        match self.collector.num_errors {
+            0 if !self.failures.is_empty() => self.failures_result(),
             0 => {
-                info_2!("Success! No spelling errors found");
+                self.unused_ignores_result()?;
+                self.suppressed_result()?;
+                match self.num_hidden {
+                    0 => info_2!("Success! No spelling errors found"),
+                    _ => info_2!("Success! No new spelling errors found"),
+                }
                 Ok(())
             }
             1 => bail!("Found just one tiny spelling error"),
@@ -80,21 +749,115 @@ impl<D: Dictionary> NonInteractiveChecker<D> {
     }
 
     fn success_json(&self) -> Result<()> {
-        let json = serde_json::to_string(&self.errors).expect("errors should be serializable");
-        println!("{json}");
-        if self.errors.is_empty() {
-            Ok(())
+        let collector = &self.collector;
+        let project = normalize_path(&self.project.path().as_str());
+        let failures = || {
+            self.failures
+                .iter()
+                .map(|FileFailure { path, message }| PathFailure {
+                    path: collector.shown_path(path),
+                    message,
+                })
+                .collect()
+        };
+        let frequent_words = self
+            .min_occurrences_to_accept
+            .map(|_| self.frequent_words.as_slice());
+        let hidden = self.seen_findings.as_ref().map(|_| self.num_hidden);
+        let skipped_lines = || {
+            collector
+                .skipped_lines
+                .iter()
+                .map(|SkippedLine { path, line }| SkippedLine {
+                    path: collector.shown_path(path).into_owned(),
+                    line: *line,
+                })
+                .collect()
+        };
+        let encodings = || collector.with_shown_paths(&collector.encodings);
+        let unused_ignores = self.unused_ignores.as_deref();
+        let ignore_hits = self.ignore_hits.as_deref();
+        let suppressed = || collector.with_shown_paths(&collector.suppressed);
+        let json = match self.sort_by {
+            SortBy::Path => serde_json::to_string(&JsonOutput {
+                project,
+                errors: collector.with_shown_paths(&collector.errors),
+                failures: failures(),
+                frequent_words,
+                hidden,
+                skipped_lines: skipped_lines(),
+                encodings: encodings(),
+                unused_ignores,
+                ignore_hits,
+                suppressed: suppressed(),
+                column_unit: COLUMN_UNIT,
+            }),
+            SortBy::Word => {
+                let by_word: Vec<_> = self
+                    .sorted_errors()
+                    .into_iter()
+                    .map(|(path, error)| PathError {
+                        path: collector.shown_path(path),
+                        error,
+                    })
+                    .collect();
+                serde_json::to_string(&JsonOutput {
+                    project,
+                    errors: by_word,
+                    failures: failures(),
+                    frequent_words,
+                    hidden,
+                    skipped_lines: skipped_lines(),
+                    encodings: encodings(),
+                    unused_ignores,
+                    ignore_hits,
+                    suppressed: suppressed(),
+                    column_unit: COLUMN_UNIT,
+                })
+            }
+        }
+        .expect("errors should be serializable");
+        if let Some(output_path) = &self.output_path {
+            write_output(output_path, &json)?;
+            info_2!("Results written to {}", output_path.display());
+            return self.summary();
+        }
+        output::print(&json);
+        if self.collector.errors.is_empty() {
+            self.failures_result()?;
+            self.unused_ignores_result()?;
+            self.suppressed_result()
         } else {
             bail!("Found some errors");
         }
     }
 }
 
+// Write to a temporary file first, so that a CI step never reads a
+// half-written document
+fn write_output(path: &Path, contents: &str) -> Result<()> {
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let write = || -> std::io::Result<()> {
+        let mut temp_file = NamedTempFile::new_in(parent)?;
+        writeln!(temp_file, "{contents}")?;
+        temp_file.persist(path)?;
+        Ok(())
+    };
+    write().with_context(|| format!("Could not write results to {}", path.display()))
+}
+
 impl<D: Dictionary> Checker<D> for NonInteractiveChecker<D> {
     type SourceContext = ();
 
     fn dictionary(&self) -> &D {
-        &self.dictionary
+        self.langs.current().unwrap_or(&self.dictionary)
+    }
+
+    fn select_lang(&mut self, lang: Option<&str>) -> bool {
+        self.langs.select(lang, self.dictionary.lang())
     }
 
     fn handle_error(
@@ -102,30 +865,32 @@ impl<D: Dictionary> Checker<D> for NonInteractiveChecker<D> {
         error: &SpellingError,
         _context: &Self::SourceContext,
     ) -> Result<()> {
-        self.num_errors += 1;
-        let (line, column) = error.pos();
-        let start_column = column + 1;
-        let token = error.word();
-        let path = error.relative_path();
-        let end_column = start_column + token.chars().count() - 1;
-        let range = Range {
-            line,
-            start_column,
-            end_column,
-        };
-        let error = Error {
-            word: token.to_string(),
-            range,
-        };
-        if self.output_format == OutputFormat::Text {
-            self.print_error(&path, &error);
-        }
-        let entry = self.errors.entry(path.normalize());
-        let errors_for_entry = entry.or_default();
-        errors_for_entry.push(error);
+        self.collector.on_error(error)
+    }
+
+    fn handle_suppressed(
+        &mut self,
+        error: &SpellingError,
+        reason: &IgnoreReason,
+        _context: &Self::SourceContext,
+    ) -> Result<()> {
+        self.collector.on_suppressed(error, reason);
         Ok(())
     }
 
+    fn on_encoding(&mut self, relative_path: &RelativePath, encoding: Encoding) {
+        self.collector
+            .encodings
+            .insert(relative_path.normalize(), encoding);
+    }
+
+    fn on_line_skipped(&mut self, relative_path: &RelativePath, line: usize) {
+        self.collector.skipped_lines.push(SkippedLine {
+            path: relative_path.normalize(),
+            line,
+        });
+    }
+
     fn success(&self) -> Result<()> {
         match self.output_format {
             OutputFormat::Text => self.success_text(),
@@ -141,6 +906,10 @@ impl<D: Dictionary> Checker<D> for NonInteractiveChecker<D> {
         &mut self.ignore_store
     }
 
+    fn classifier(&self) -> TokenClassifier<'_, D> {
+        TokenClassifier::new(self.dictionary(), &self.ignore_store)
+    }
+
     fn apply_operation(&mut self, mut operation: Operation) -> Result<()> {
         operation.execute(&mut self.ignore_store)
     }
@@ -1,7 +1,14 @@
+use clap::Parser;
+use skyspell_core::output::{self, Captured};
+use skyspell_core::tests::NON_ASCII_LINE;
 use skyspell_core::{tests::FakeDictionary, Checker, IgnoreStore, Project, RelativePath};
+use skyspell_core::{ProcessOutcome, ReportEntry, SeenFindings, SkipReason, Verbosity};
+use std::sync::Arc;
 use tempfile::TempDir;
 
-use crate::{NonInteractiveChecker, OutputFormat};
+use super::FrequentWord;
+use crate::events::ErrorKind;
+use crate::{check_with, CheckOpts, NonInteractiveChecker, OutputFormat, SortBy};
 
 type TestChecker = NonInteractiveChecker<FakeDictionary>;
 struct TestApp {
@@ -10,6 +17,10 @@ struct TestApp {
 
 impl TestApp {
     fn new(temp_dir: &TempDir) -> Self {
+        Self::with_output_format(temp_dir, OutputFormat::Text)
+    }
+
+    fn with_output_format(temp_dir: &TempDir, output_format: OutputFormat) -> Self {
         let dictionary = FakeDictionary::new();
 
         let project_path = temp_dir.path().join("project");
@@ -18,8 +29,7 @@ impl TestApp {
         let global_toml = temp_dir.path().join("global.toml");
         let local_toml = temp_dir.path().join("skyspell.toml");
         let ignore_store = IgnoreStore::load(global_toml, local_toml).unwrap();
-        let checker =
-            TestChecker::new(project, dictionary, ignore_store, OutputFormat::Text).unwrap();
+        let checker = TestChecker::new(project, dictionary, ignore_store, output_format).unwrap();
         Self { checker }
     }
 
@@ -53,5 +63,460 @@ last line";
         .skip_token("SKIP_THIS", &foo_py)
         .unwrap();
     app.checker.process(&foo_py_path, &()).unwrap();
-    assert!(app.checker.errors.is_empty());
+    assert!(app.checker.collector.errors.is_empty());
+}
+
+// Create the files in the given order, then check the whole project like
+// `skyspell check` does, in whatever order the files are found
+fn check_files(app: &mut TestApp, temp_dir: &TempDir, files: &[(&str, &str)]) -> String {
+    for (name, contents) in files {
+        let path = temp_dir.path().join("project").join(name);
+        std::fs::write(&path, contents).unwrap();
+    }
+    let opts = CheckOpts::try_parse_from(["check", "--non-interactive"]).unwrap();
+    check_with(&mut app.checker, &opts, OutputFormat::Json, false).unwrap();
+    serde_json::to_string(&app.checker.collector.errors).unwrap()
+}
+
+#[test]
+fn test_errors_do_not_depend_on_creation_order() {
+    let temp_dir_1 = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let temp_dir_2 = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app_1 = TestApp::new(&temp_dir_1);
+    let mut app_2 = TestApp::new(&temp_dir_2);
+    let files: Vec<_> = (0..20)
+        .map(|i| (format!("file{i}.md"), "one two".to_string()))
+        .collect();
+    let files: Vec<_> = files
+        .iter()
+        .map(|(name, contents)| (name.as_str(), contents.as_str()))
+        .collect();
+    let reversed: Vec<_> = files.iter().rev().copied().collect();
+
+    let first = check_files(&mut app_1, &temp_dir_1, &files);
+    let second = check_files(&mut app_2, &temp_dir_2, &reversed);
+
+    assert_eq!(first, second);
+    assert!(first.find("file0.md").unwrap() < first.find("file1.md").unwrap());
+}
+
+#[test]
+fn test_words_of_ignored_identifiers_are_not_checked() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.checker
+        .ignore_store()
+        .ignore_identifier("XgboostClassifier")
+        .unwrap();
+
+    let errors = check_files(
+        &mut app,
+        &temp_dir,
+        &[("model.py", "model = XgboostClassifier()\nXgboostModel\n")],
+    );
+
+    let checked = app.checker.dictionary.checked_words();
+    assert!(!checked.contains(&"Classifier".to_string()), "{checked:?}");
+    assert!(errors.contains("\"Xgboost\""), "{errors}");
+    assert!(!errors.contains("Classifier"), "{errors}");
+}
+
+#[test]
+fn test_sort_errors_by_word() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.checker.set_sort_by(SortBy::Word);
+
+    check_files(
+        &mut app,
+        &temp_dir,
+        &[("a.md", "zeta beta"), ("b.md", "alpha")],
+    );
+
+    let actual: Vec<_> = app
+        .checker
+        .sorted_errors()
+        .into_iter()
+        .map(|(path, error)| (path, error.word.as_str()))
+        .collect();
+    assert_eq!(
+        actual,
+        &[("b.md", "alpha"), ("a.md", "beta"), ("a.md", "zeta")]
+    );
+}
+
+#[test]
+fn test_check_confusables() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.checker.set_check_confusables(true);
+
+    let errors = check_files(&mut app, &temp_dir, &[("a.md", "fooo c\u{43e}de")]);
+
+    let errors: serde_json::Value = serde_json::from_str(&errors).unwrap();
+    assert_eq!(
+        errors,
+        serde_json::json!({
+            "a.md": [
+                {
+                    "word": "fooo",
+                    "range": {"line": 1, "start_column": 1, "end_column": 4},
+                    "kind": "unknown-word",
+                },
+                {
+                    "word": "c\u{43e}de",
+                    "range": {"line": 1, "start_column": 6, "end_column": 9},
+                    "kind": "mixed-script",
+                    "message": "'\u{43e}' (U+043E) looks like 'o'",
+                    "suggestion": "code",
+                },
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_confusables_are_not_checked_by_default() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+
+    check_files(&mut app, &temp_dir, &[("a.md", "c\u{43e}de")]);
+
+    let errors = &app.checker.collector.errors["a.md"];
+    assert_eq!(errors[0].kind, ErrorKind::UnknownWord);
+}
+
+#[test]
+fn test_frequent_words_are_not_reported() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.checker.set_min_occurrences_to_accept(3);
+    assert!(!app.checker.collector.print_errors);
+
+    check_files(
+        &mut app,
+        &temp_dir,
+        &[
+            ("a.md", "acme fooo acme"),
+            ("b.md", "acme zorg zorg zorg"),
+            ("c.md", "acme"),
+        ],
+    );
+    app.checker.split_frequent_words();
+
+    let actual: Vec<_> = app
+        .checker
+        .report_entries()
+        .into_iter()
+        .map(|e| (e.path, e.word))
+        .collect();
+    assert_eq!(actual, [("a.md".to_string(), "fooo".to_string())]);
+    assert_eq!(app.checker.collector.num_errors, 1);
+    assert_eq!(
+        app.checker.frequent_words,
+        [
+            FrequentWord {
+                word: "acme".to_string(),
+                count: 4
+            },
+            FrequentWord {
+                word: "zorg".to_string(),
+                count: 3
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_all_words_are_reported_without_threshold() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+
+    check_files(&mut app, &temp_dir, &[("a.md", "acme acme acme")]);
+    app.checker.split_frequent_words();
+
+    assert_eq!(app.checker.report_entries().len(), 3);
+    assert!(app.checker.frequent_words.is_empty());
+}
+
+#[test]
+fn test_seen_findings_are_not_reported() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let seen = SeenFindings::from_entries(&[ReportEntry {
+        word: "fooo".to_string(),
+        path: "a.md".to_string(),
+        line: 1,
+        column: 1,
+    }]);
+    app.checker.set_seen_findings(seen);
+    assert!(!app.checker.collector.print_errors);
+
+    check_files(
+        &mut app,
+        &temp_dir,
+        &[("a.md", "fooo barr fooo"), ("b.md", "fooo")],
+    );
+    let findings = app.checker.hide_seen_findings();
+
+    let actual: Vec<_> = app
+        .checker
+        .report_entries()
+        .into_iter()
+        .map(|e| (e.path, e.word))
+        .collect();
+    assert_eq!(
+        actual,
+        [
+            ("a.md".to_string(), "barr".to_string()),
+            ("b.md".to_string(), "fooo".to_string())
+        ]
+    );
+    assert_eq!(app.checker.collector.num_errors, 2);
+    assert_eq!(app.checker.num_hidden, 2);
+    assert!(findings.contains("a.md", "fooo"));
+    assert!(findings.contains("a.md", "barr"));
+    assert!(findings.contains("b.md", "fooo"));
+}
+
+#[test]
+fn test_columns_are_in_chars() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    for word in ["e", "te", "日本語"] {
+        app.checker.dictionary.add_known(word);
+    }
+
+    let errors = check_files(&mut app, &temp_dir, &[("a.md", NON_ASCII_LINE)]);
+
+    let errors: serde_json::Value = serde_json::from_str(&errors).unwrap();
+    assert_eq!(
+        errors,
+        serde_json::json!({
+            "a.md": [
+                {
+                    "word": "fooo",
+                    "range": {"line": 1, "start_column": 13, "end_column": 16},
+                    "kind": "unknown-word",
+                },
+            ]
+        })
+    );
+}
+
+fn check_with_verbosity(verbosity: Verbosity) -> Captured {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    output::capture(verbosity, || {
+        let mut app = TestApp::new(&temp_dir);
+        let foo_md = temp_dir.path().join("project/foo.md");
+        std::fs::write(&foo_md, "fooo\n").unwrap();
+        app.checker.process(&foo_md, &()).unwrap();
+        app.checker.success().unwrap_err();
+    })
+}
+
+#[test]
+fn test_quiet_check_only_prints_errors() {
+    let captured = check_with_verbosity(Verbosity::Quiet);
+
+    assert!(captured.stdout.contains("foo.md:1:1:4"), "{captured:?}");
+    assert!(
+        !captured.stdout.contains("Checking project"),
+        "{captured:?}"
+    );
+    assert_eq!(captured.stdout.lines().count(), 1);
+    assert_eq!(captured.stderr, "");
+}
+
+#[test]
+fn test_check_prints_progress_by_default() {
+    let captured = check_with_verbosity(Verbosity::Normal);
+
+    assert!(captured.stdout.contains("Checking project"), "{captured:?}");
+    assert!(captured.stdout.contains("foo.md:1:1:4"), "{captured:?}");
+    assert_eq!(captured.stderr, "");
+}
+
+#[test]
+fn test_quiet_json_check_only_prints_the_document() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let captured = output::capture(Verbosity::Quiet, || {
+        let mut app = TestApp::new(&temp_dir);
+        app.checker.output_format = OutputFormat::Json;
+        app.checker.success().unwrap();
+    });
+
+    assert!(captured.stdout.starts_with("{\"project\":"), "{captured:?}");
+    assert_eq!(captured.stdout.lines().count(), 1);
+}
+
+#[test]
+fn test_json_output_to_file_only_prints_the_summary() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let results = temp_dir.path().join("results.json");
+    let mut res = None;
+    let captured = output::capture(Verbosity::Normal, || {
+        let mut app = TestApp::with_output_format(&temp_dir, OutputFormat::Json);
+        app.checker.set_output_path(results.clone());
+        let foo_md = temp_dir.path().join("project/foo.md");
+        std::fs::write(&foo_md, "fooo\n").unwrap();
+        app.checker.process(&foo_md, &()).unwrap();
+        res = Some(app.checker.success());
+    });
+
+    let err = res.unwrap().unwrap_err();
+    assert_eq!(err.to_string(), "Found just one tiny spelling error");
+    let expected = format!("Results written to {}", results.display());
+    assert_eq!(captured.stdout.lines().count(), 1, "{captured:?}");
+    assert!(captured.stdout.contains(&expected), "{captured:?}");
+    assert_eq!(captured.stderr, "");
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&results).unwrap()).unwrap();
+    assert_eq!(json["errors"]["foo.md"][0]["word"], "fooo");
+}
+
+#[test]
+fn test_files_tagged_with_another_lang() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.checker.set_lang_opener(Arc::new(|_lang: &str| {
+        let mut dictionary = FakeDictionary::new();
+        for word in ["lang", "fr", "bonjour"] {
+            dictionary.add_known(word);
+        }
+        Ok(dictionary)
+    }));
+
+    check_files(
+        &mut app,
+        &temp_dir,
+        &[
+            ("fr.md", "---\nlang: fr\n---\nbonjour\n"),
+            ("en.md", "bonjour\n"),
+        ],
+    );
+
+    let errors = &app.checker.collector.errors;
+    assert!(!errors.contains_key("fr.md"));
+    assert_eq!(errors["en.md"][0].word, "bonjour");
+}
+
+#[test]
+fn test_check_file_names() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.checker.dictionary.add_known("install");
+    app.checker.dictionary.add_known("Guide");
+    let docs = temp_dir.path().join("project/documantation");
+    std::fs::create_dir(&docs).unwrap();
+    let mut paths = vec![];
+    for name in ["installGuide.md", "instalation.md", "instalation.rs"] {
+        let path = docs.join(name);
+        std::fs::write(&path, "").unwrap();
+        paths.push(path);
+    }
+    app.checker
+        .ignore_store()
+        .ignore_for_extension("instalation", "rs")
+        .unwrap();
+
+    app.checker.process_file_names(&paths, &()).unwrap();
+
+    let errors = &app.checker.collector.errors;
+    // The directory is reported once, and the extensions are not checked
+    assert_eq!(
+        errors.keys().collect::<Vec<_>>(),
+        ["documantation", "documantation/instalation.md"]
+    );
+    assert_eq!(errors["documantation"].len(), 1);
+    let json = serde_json::to_string(&errors["documantation/instalation.md"]).unwrap();
+    assert_eq!(
+        json,
+        r#"[{"word":"instalation","range":{"line":0,"start_column":0,"end_column":0},"kind":"filename"}]"#
+    );
+}
+
+#[test]
+fn test_names_of_skipped_files_are_not_checked() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let path = temp_dir.path().join("project/instalation.md");
+    std::fs::write(&path, "").unwrap();
+    app.checker
+        .ignore_store()
+        .add_skip_pattern("instalation.md")
+        .unwrap();
+
+    app.checker.process_file_names(&[path], &()).unwrap();
+
+    assert!(app.checker.collector.errors.is_empty());
+}
+
+#[test]
+fn test_files_that_are_too_large_are_skipped() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.checker.project.set_max_file_size(10);
+    let big = temp_dir.path().join("project/big.txt");
+    std::fs::write(&big, "Some errors in a big file").unwrap();
+    let small = temp_dir.path().join("project/small.txt");
+    std::fs::write(&small, "fooo").unwrap();
+
+    let big_outcome = app.checker.process(&big, &()).unwrap();
+    let small_outcome = app.checker.process(&small, &()).unwrap();
+
+    assert_eq!(big_outcome, ProcessOutcome::Skipped(SkipReason::TooLarge));
+    assert_eq!(small_outcome, ProcessOutcome::Checked);
+    assert!(!app.checker.collector.errors.contains_key("big.txt"));
 }
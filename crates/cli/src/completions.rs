@@ -0,0 +1,40 @@
+use std::io::Write;
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+use skyspell_core::IgnoreStore;
+
+use crate::Opts;
+
+/// Values printed by `skyspell __complete`, used by the completion scripts
+#[derive(Debug, PartialEq, Eq, clap::ValueEnum, Clone, Copy)]
+pub enum CompletionKind {
+    RemoveWordGlobal,
+    RemoveWordProject,
+    Extensions,
+}
+
+pub fn write_completions(shell: Shell, out: &mut impl Write) {
+    let mut command = Opts::command();
+    clap_complete::generate(shell, &mut command, "skyspell", out);
+    let dynamic = match shell {
+        Shell::Bash => include_str!("completions/bash.sh"),
+        Shell::Zsh => include_str!("completions/zsh.zsh"),
+        Shell::Fish => include_str!("completions/fish.fish"),
+        _ => "",
+    };
+    // Errors are ignored, like in clap_complete::generate()
+    let _ = out.write_all(dynamic.as_bytes());
+}
+
+pub fn completion_candidates(ignore_store: &IgnoreStore, kind: CompletionKind) -> Vec<String> {
+    let candidates: Vec<&str> = match kind {
+        CompletionKind::RemoveWordGlobal => ignore_store.global_words().collect(),
+        CompletionKind::RemoveWordProject => ignore_store.project_words().collect(),
+        CompletionKind::Extensions => ignore_store.extensions().collect(),
+    };
+    candidates.into_iter().map(|x| x.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests;
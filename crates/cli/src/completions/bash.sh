@@ -0,0 +1,27 @@
+
+# Complete ignored words and extensions with the contents of the ignore store
+_skyspell_dynamic() {
+    local cur prev kind word
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    kind=""
+    for word in "${COMP_WORDS[@]:1:COMP_CWORD-1}"; do
+        case "$word" in
+            remove) kind="remove-word-global" ;;
+            --project) [ -n "$kind" ] && kind="remove-word-project" ;;
+        esac
+    done
+    if [ -n "$kind" ]; then
+        if [ "$prev" = "--extension" ]; then
+            COMPREPLY=($(compgen -W "$(skyspell __complete extensions 2>/dev/null)" -- "$cur"))
+            return 0
+        fi
+        if [[ "$cur" != -* ]]; then
+            COMPREPLY=($(compgen -W "$(skyspell __complete "$kind" 2>/dev/null)" -- "$cur"))
+            return 0
+        fi
+    fi
+    _skyspell "$@"
+}
+
+complete -F _skyspell_dynamic -o bashdefault -o default skyspell
@@ -0,0 +1,5 @@
+
+# Complete ignored words and extensions with the contents of the ignore store
+complete -c skyspell -n "__fish_seen_subcommand_from remove; and not __fish_contains_opt project" -f -a "(skyspell __complete remove-word-global 2>/dev/null)"
+complete -c skyspell -n "__fish_seen_subcommand_from remove; and __fish_contains_opt project" -f -a "(skyspell __complete remove-word-project 2>/dev/null)"
+complete -c skyspell -n "__fish_seen_subcommand_from remove" -l extension -f -r -a "(skyspell __complete extensions 2>/dev/null)"
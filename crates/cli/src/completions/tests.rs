@@ -0,0 +1,59 @@
+use skyspell_core::tests::create_store;
+use tempfile::TempDir;
+
+use super::*;
+
+fn get_script(shell: Shell) -> String {
+    let mut out = vec![];
+    write_completions(shell, &mut out);
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_static_completions() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+        let script = get_script(shell);
+        assert!(script.contains("history"), "{shell}: {script}");
+    }
+}
+
+#[test]
+fn test_dynamic_completions() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+        let script = get_script(shell);
+        assert!(script.contains("remove-word-global"), "{shell}");
+        assert!(script.contains("remove-word-project"), "{shell}");
+        assert!(script.contains("skyspell __complete extensions"), "{shell}");
+    }
+}
+
+#[test]
+fn test_completion_candidates() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = create_store(
+        &temp_dir,
+        r#"
+        global = ["foo", "bar"]
+
+        [extensions]
+        py = ["defaultdict"]
+        rs = []
+        "#,
+        r#"
+        project = ["baz"]
+        "#,
+    );
+
+    assert_eq!(
+        completion_candidates(&store, CompletionKind::RemoveWordGlobal),
+        &["bar", "foo"]
+    );
+    assert_eq!(
+        completion_candidates(&store, CompletionKind::RemoveWordProject),
+        &["baz"]
+    );
+    assert_eq!(
+        completion_candidates(&store, CompletionKind::Extensions),
+        &["py"]
+    );
+}
@@ -0,0 +1,154 @@
+//! Guidance for unknown subcommands and flags, on top of the suggestions
+//! made by clap - in particular for people used to other spell checkers
+use clap::builder::StyledStr;
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{Command, CommandFactory, Parser};
+use skyspell_core::edit_distance;
+
+use crate::Opts;
+
+/// Typos further away than this are not worth a suggestion
+const MAX_DISTANCE: usize = 2;
+
+// Verbs of cspell, typos or codespell, how to do the same with skyspell,
+// and what to show when no arguments are given. The first three are also
+// hidden subcommands, see `Action::foreign_verb()`
+const FOREIGN_VERBS: [(&str, &str, &str); 8] = [
+    ("ignore", "skyspell add {args}", "WORD"),
+    ("allow", "skyspell add {args} --project", "WORD"),
+    ("skipfile", "skyspell skip {args}", "PATTERN"),
+    ("exclude", "skyspell skip {args}", "PATTERN"),
+    ("unignore", "skyspell remove {args}", "WORD"),
+    ("lint", "skyspell check {args}", ""),
+    ("words", "skyspell list", ""),
+    ("trace", "skyspell explain {args}", "PATH"),
+];
+
+// Flags of the same tools, and their skyspell equivalent
+const FOREIGN_FLAGS: [(&str, &str); 6] = [
+    (
+        "--global",
+        "words are added to the global ignore list when no scope is given",
+    ),
+    (
+        "--file",
+        "use --relative-path to ignore a word for a single file",
+    ),
+    (
+        "--path",
+        "use --relative-path to ignore a word for a single file",
+    ),
+    ("--ext", "use --extension"),
+    ("--language", "use --lang"),
+    ("--exclude", "use `skyspell skip` to add skip patterns"),
+];
+
+/// Like Opts::try_parse_from(), with skyspell-specific tips in the errors
+pub(crate) fn parse_opts<I, T>(args: I) -> Result<Opts, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<String>,
+{
+    let args: Vec<String> = args.into_iter().map(|a| a.into()).collect();
+    Opts::try_parse_from(&args).map_err(|e| add_guidance(e, &Opts::command(), &args))
+}
+
+/// How to do what `verb` does in another spell checker
+pub(crate) fn translate_foreign_verb(verb: &str, args: &[String]) -> Option<String> {
+    let (_, translation, placeholder) = FOREIGN_VERBS.iter().find(|(v, _, _)| *v == verb)?;
+    let args = match args {
+        [] => placeholder.to_string(),
+        args => args.join(" "),
+    };
+    let translation = translation.replace("{args}", &args);
+    let translation = translation.trim_end();
+    Some(format!(
+        "skyspell has no `{verb}` command - to do the same, {translation}"
+    ))
+}
+
+fn add_guidance(mut error: clap::Error, cmd: &Command, args: &[String]) -> clap::Error {
+    let tips = match error.kind() {
+        ErrorKind::InvalidSubcommand => match error.get(ContextKind::InvalidSubcommand) {
+            Some(ContextValue::String(invalid)) => subcommand_tips(&error, cmd, invalid, args),
+            _ => vec![],
+        },
+        ErrorKind::UnknownArgument => match error.get(ContextKind::InvalidArg) {
+            Some(ContextValue::String(invalid)) => flag_tips(&error, cmd, invalid, args),
+            _ => vec![],
+        },
+        _ => vec![],
+    };
+    if tips.is_empty() {
+        return error;
+    }
+    let mut suggested = match error.get(ContextKind::Suggested) {
+        Some(ContextValue::StyledStrs(s)) => s.clone(),
+        _ => vec![],
+    };
+    suggested.extend(tips.into_iter().map(StyledStr::from));
+    error.insert(ContextKind::Suggested, ContextValue::StyledStrs(suggested));
+    error
+}
+
+fn subcommand_tips(
+    error: &clap::Error,
+    cmd: &Command,
+    invalid: &str,
+    args: &[String],
+) -> Vec<String> {
+    // What follows the verb on the command line
+    let rest: Vec<_> = args
+        .iter()
+        .skip_while(|a| *a != invalid)
+        .skip(1)
+        .cloned()
+        .collect();
+    if let Some(translation) = translate_foreign_verb(invalid, &rest) {
+        return vec![translation];
+    }
+    if error.get(ContextKind::SuggestedSubcommand).is_some() {
+        return vec![];
+    }
+    let names = cmd
+        .get_subcommands()
+        .filter(|s| !s.is_hide_set())
+        .map(|s| s.get_name().to_string());
+    match closest(invalid, names) {
+        Some(name) => vec![format!("did you mean `skyspell {name}`?")],
+        None => vec!["run `skyspell --help` to see the available commands".to_string()],
+    }
+}
+
+fn flag_tips(error: &clap::Error, cmd: &Command, invalid: &str, args: &[String]) -> Vec<String> {
+    let flag = invalid.split('=').next().unwrap_or(invalid);
+    if let Some((_, tip)) = FOREIGN_FLAGS.iter().find(|(f, _)| *f == flag) {
+        return vec![tip.to_string()];
+    }
+    if error.get(ContextKind::SuggestedArg).is_some() {
+        return vec![];
+    }
+    // The flags of the subcommand, and the global ones
+    let subcommand = args.iter().find_map(|a| cmd.find_subcommand(a));
+    let flags = cmd
+        .get_arguments()
+        .chain(subcommand.into_iter().flat_map(|s| s.get_arguments()))
+        .filter(|a| !a.is_hide_set())
+        .filter_map(|a| a.get_long())
+        .map(|l| format!("--{l}"));
+    match closest(flag, flags) {
+        Some(flag) => vec![format!("did you mean `{flag}`?")],
+        None => vec![],
+    }
+}
+
+fn closest(invalid: &str, candidates: impl Iterator<Item = String>) -> Option<String> {
+    candidates
+        .map(|c| (edit_distance(invalid, &c), c))
+        .filter(|(d, _)| *d <= MAX_DISTANCE)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,91 @@
+use super::*;
+
+fn parse_error(args: &[&str]) -> String {
+    let args: Vec<_> = std::iter::once("skyspell")
+        .chain(args.iter().copied())
+        .collect();
+    match parse_opts(args.iter().copied()) {
+        Ok(_) => panic!("{args:?} should not parse"),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[test]
+fn test_valid_command_line() {
+    assert!(parse_opts(["skyspell", "add", "foo", "--project"]).is_ok());
+}
+
+#[test]
+fn test_verb_of_another_spell_checker() {
+    let message = parse_error(&["unignore", "foo"]);
+
+    assert!(
+        message
+            .contains("skyspell has no `unignore` command - to do the same, skyspell remove foo"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_verb_of_another_spell_checker_without_arguments() {
+    let message = parse_error(&["exclude"]);
+
+    assert!(
+        message
+            .contains("skyspell has no `exclude` command - to do the same, skyspell skip PATTERN"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_unknown_subcommand_far_from_any_other() {
+    let message = parse_error(&["frobnicate"]);
+
+    assert!(
+        message.contains("run `skyspell --help` to see the available commands"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_flag_of_another_spell_checker() {
+    let message = parse_error(&["add", "foo", "--ext"]);
+
+    assert!(message.contains("use --extension"), "{message}");
+}
+
+#[test]
+fn test_flag_of_another_spell_checker_with_a_value() {
+    let message = parse_error(&["add", "foo", "--file=foo.py"]);
+
+    assert!(
+        message.contains("use --relative-path to ignore a word for a single file"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_misspelled_flag_of_the_subcommand() {
+    let message = parse_error(&["add", "foo", "--projct"]);
+
+    assert!(message.contains("--project"), "{message}");
+}
+
+#[test]
+fn test_closest_candidate() {
+    let candidates = ["--lang", "--list", "--project"].map(String::from);
+
+    assert_eq!(
+        closest("--lagn", candidates.into_iter()),
+        Some("--lang".to_string())
+    );
+}
+
+#[test]
+fn test_translate_foreign_verb() {
+    assert_eq!(
+        translate_foreign_verb("allow", &["foo".to_string()]).unwrap(),
+        "skyspell has no `allow` command - to do the same, skyspell add foo --project"
+    );
+    assert!(translate_foreign_verb("add", &[]).is_none());
+}
@@ -0,0 +1,77 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct EditorCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl EditorCommand {
+    pub fn run(&self) -> Result<()> {
+        let status = Command::new(&self.program)
+            .args(&self.args)
+            .status()
+            .with_context(|| format!("Could not start editor '{}'", self.program))?;
+        if !status.success() {
+            bail!("Editor '{}' exited with {status}", self.program);
+        }
+        Ok(())
+    }
+}
+
+/// Return the editor to use, from $VISUAL or $EDITOR, defaulting to vi
+pub fn get_editor() -> String {
+    for key in ["VISUAL", "EDITOR"] {
+        if let Ok(value) = std::env::var(key) {
+            if !value.trim().is_empty() {
+                return value;
+            }
+        }
+    }
+    "vi".to_string()
+}
+
+/// Build the command to open `path` at the given line and column (both starting at 1).
+/// `editor` may contain arguments, like `code --wait`
+pub fn editor_command(editor: &str, path: &Path, line: usize, column: usize) -> EditorCommand {
+    let mut words = editor.split_whitespace().map(|x| x.to_string());
+    let program = words.next().unwrap_or_else(|| "vi".to_string());
+    let mut args: Vec<String> = words.collect();
+    let binary_name = Path::new(&program)
+        .file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let path = path.to_string_lossy().to_string();
+    match binary_name.as_ref() {
+        "vim" | "nvim" | "gvim" => {
+            args.push(format!("+call cursor({line}, {column})"));
+            args.push(path);
+        }
+        "emacs" | "emacsclient" | "kak" => {
+            args.push(format!("+{line}:{column}"));
+            args.push(path);
+        }
+        "nano" => {
+            args.push(format!("+{line},{column}"));
+            args.push(path);
+        }
+        "code" | "codium" => {
+            args.push("--goto".to_string());
+            args.push(format!("{path}:{line}:{column}"));
+        }
+        "hx" | "helix" => {
+            args.push(format!("{path}:{line}:{column}"));
+        }
+        _ => {
+            args.push(format!("+{line}"));
+            args.push(path);
+        }
+    }
+    EditorCommand { program, args }
+}
+
+#[cfg(test)]
+mod tests;
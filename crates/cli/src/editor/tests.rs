@@ -0,0 +1,40 @@
+use super::*;
+
+fn get_args(editor: &str) -> (String, Vec<String>) {
+    let EditorCommand { program, args } = editor_command(editor, Path::new("foo.md"), 3, 7);
+    (program, args)
+}
+
+#[test]
+fn test_vim() {
+    let (program, args) = get_args("/usr/bin/nvim");
+    assert_eq!(program, "/usr/bin/nvim");
+    assert_eq!(args, &["+call cursor(3, 7)", "foo.md"]);
+}
+
+#[test]
+fn test_emacs() {
+    let (_, args) = get_args("emacs");
+    assert_eq!(args, &["+3:7", "foo.md"]);
+}
+
+#[test]
+fn test_vs_code_with_arguments() {
+    let (program, args) = get_args("code --wait");
+    assert_eq!(program, "code");
+    assert_eq!(args, &["--wait", "--goto", "foo.md:3:7"]);
+}
+
+#[test]
+fn test_unknown_editor_uses_line_only() {
+    let (program, args) = get_args("vi");
+    assert_eq!(program, "vi");
+    assert_eq!(args, &["+3", "foo.md"]);
+}
+
+#[test]
+fn test_empty_editor() {
+    let (program, args) = get_args("  ");
+    assert_eq!(program, "vi");
+    assert_eq!(args, &["+3", "foo.md"]);
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+/// Something that happened during an interactive session, written
+/// as one line of JSON by frontends that are not a terminal
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Error {
+        path: String,
+        line: usize,
+        column: usize,
+        word: String,
+        /// The whole token the word was extracted from, like
+        /// `XgboostClassifier` for `Xgboost` - only when they differ
+        #[serde(skip_serializing_if = "Option::is_none")]
+        identifier: Option<String>,
+        kind: ErrorKind,
+        /// The whole line containing the error, or the file name for
+        /// the errors in file names, which are on line 0
+        context: String,
+        /// Why the word is suspicious, for mixed-script errors
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        suggestion: Option<String>,
+    },
+    Prompt {
+        actions: Vec<PromptAction>,
+    },
+    Question {
+        kind: QuestionKind,
+        message: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        choices: Vec<String>,
+    },
+    Applied {
+        word: String,
+        scope: String,
+    },
+    /// A pattern was added to the list of skipped files
+    SkipAdded {
+        pattern: String,
+    },
+    Rejected {
+        message: String,
+    },
+    InvalidCommand {
+        message: String,
+    },
+    /// The action exists, but cannot be used with this frontend
+    UnsupportedAction {
+        action: String,
+        message: String,
+    },
+    Summary {
+        errors: usize,
+        skipped: Vec<String>,
+    },
+}
+
+/// Mixed-script errors are only reported with `check --check-confusables`,
+/// and the errors in file names with `check --check-filenames`
+#[derive(Debug, Serialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+    UnknownWord,
+    MixedScript,
+    Filename,
+}
+
+impl ErrorKind {
+    pub fn description(self) -> &'static str {
+        match self {
+            ErrorKind::UnknownWord => "unknown word",
+            ErrorKind::MixedScript => "mixed script",
+            ErrorKind::Filename => "unknown word in file name",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct PromptAction {
+    pub key: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionKind {
+    Text,
+    Select,
+    Confirm,
+}
+
+/// What the frontend can send back, one JSON object per line
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    Choose { action: String },
+    Reply { text: String },
+    Select { index: Option<usize> },
+    Confirm { answer: bool },
+    Quit,
+}
+
+pub trait EventSink {
+    fn emit(&self, event: Event);
+
+    /// The first error met while emitting events, if any - the session
+    /// should then stop, since the frontend no longer gets them
+    fn take_error(&self) -> Option<std::io::Error> {
+        None
+    }
+}
@@ -0,0 +1,113 @@
+//! Show why a file is checked or skipped, and why a word would be
+//! reported or accepted in it
+use anyhow::Result;
+use skyspell_core::tokens::is_acronym;
+use skyspell_core::{Dictionary, FileExplanation, IgnoreReason, IgnoreStore, RelativePath};
+
+pub(crate) fn format_file(explanation: &FileExplanation) -> Vec<String> {
+    let FileExplanation {
+        relative_path,
+        walk_exclusion,
+        skip_rule,
+        extension,
+        checked_extension,
+        size,
+        max_file_size,
+    } = explanation;
+    let mut res = vec![relative_path.normalize()];
+    match skip_rule {
+        Some(rule) => res.push(format!("  skip rules: {rule}")),
+        None => res.push("  skip rules: no match".to_string()),
+    }
+    match walk_exclusion {
+        Some(exclusion) => res.push(format!("  project walk: excluded, {exclusion}")),
+        None => res.push("  project walk: included".to_string()),
+    }
+    match checked_extension {
+        true => res.push(format!("  extension: '{extension}' is checked")),
+        false => res.push(format!("  extension: '{extension}' is not checked")),
+    }
+    match max_file_size {
+        0 => res.push(format!("  size: {size} bytes, no limit")),
+        max => res.push(format!("  size: {size} bytes, limit: {max} bytes")),
+    }
+    let verdict = if let Some(rule) = skip_rule {
+        format!("skipped: {rule}")
+    } else if !checked_extension {
+        "skipped: extension not checked".to_string()
+    } else if explanation.is_too_large() {
+        "skipped: too large".to_string()
+    } else if walk_exclusion.is_some() {
+        "checked when given on the command line, but not when checking the whole project"
+            .to_string()
+    } else {
+        "checked".to_string()
+    };
+    res.push(format!("=> {verdict}"));
+    res
+}
+
+/// The checks made by `TokenClassifier::classify()`, in the same order
+#[derive(Debug)]
+pub(crate) struct WordExplanation {
+    word: String,
+    lang: String,
+    acronym: bool,
+    in_dictionary: bool,
+    ignore_reason: Option<IgnoreReason>,
+}
+
+impl WordExplanation {
+    pub(crate) fn new(
+        word: &str,
+        relative_path: &RelativePath,
+        ignore_store: &IgnoreStore,
+        dictionary: &impl Dictionary,
+    ) -> Result<Self> {
+        let lang = dictionary.lang().to_string();
+        let acronym = ignore_store
+            .acronyms_min_length()
+            .is_some_and(|min_length| is_acronym(word, min_length));
+        Ok(Self {
+            word: word.to_string(),
+            acronym,
+            in_dictionary: dictionary.check(word)?,
+            ignore_reason: ignore_store.ignore_reason(word, relative_path, &lang),
+            lang,
+        })
+    }
+
+    pub(crate) fn is_reported(&self) -> bool {
+        !self.acronym && !self.in_dictionary && self.ignore_reason.is_none()
+    }
+
+    pub(crate) fn format(&self) -> Vec<String> {
+        let Self {
+            word,
+            lang,
+            acronym,
+            in_dictionary,
+            ignore_reason,
+        } = self;
+        let mut res = vec![format!("'{word}', lang: {lang}")];
+        if *acronym {
+            res.push("  acronyms: accepted as an acronym".to_string());
+        }
+        match in_dictionary {
+            true => res.push("  dictionary: known".to_string()),
+            false => res.push("  dictionary: unknown".to_string()),
+        }
+        match ignore_reason {
+            Some(reason) => res.push(format!("  ignore rules: {reason}")),
+            None => res.push("  ignore rules: no match".to_string()),
+        }
+        match self.is_reported() {
+            true => res.push("=> reported as an error".to_string()),
+            false => res.push("=> accepted".to_string()),
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,219 @@
+use std::path::PathBuf;
+
+use super::*;
+
+use skyspell_core::tests::FakeDictionary;
+use skyspell_core::{PersonalDictionary, SkipRule, WalkExclusion};
+use tempfile::TempDir;
+
+fn explanation() -> FileExplanation {
+    FileExplanation {
+        relative_path: RelativePath::from_path_unchecked("docs/intro.md".into()),
+        walk_exclusion: None,
+        skip_rule: None,
+        extension: "md".to_string(),
+        checked_extension: true,
+        size: 120,
+        max_file_size: 1000,
+    }
+}
+
+#[test]
+fn test_format_checked_file() {
+    assert_eq!(
+        format_file(&explanation()),
+        [
+            "docs/intro.md",
+            "  skip rules: no match",
+            "  project walk: included",
+            "  extension: 'md' is checked",
+            "  size: 120 bytes, limit: 1000 bytes",
+            "=> checked",
+        ]
+    );
+}
+
+#[test]
+fn test_format_skipped_file() {
+    let explanation = FileExplanation {
+        skip_rule: Some(SkipRule::Pattern("docs/".to_string())),
+        ..explanation()
+    };
+
+    let lines = format_file(&explanation);
+
+    assert_eq!(lines[1], "  skip rules: matches the skip pattern 'docs/'");
+    assert_eq!(
+        lines.last().unwrap(),
+        "=> skipped: matches the skip pattern 'docs/'"
+    );
+}
+
+#[test]
+fn test_format_file_with_unchecked_extension() {
+    let explanation = FileExplanation {
+        checked_extension: false,
+        ..explanation()
+    };
+
+    let lines = format_file(&explanation);
+
+    assert_eq!(lines[3], "  extension: 'md' is not checked");
+    assert_eq!(lines.last().unwrap(), "=> skipped: extension not checked");
+}
+
+#[test]
+fn test_format_too_large_file() {
+    let explanation = FileExplanation {
+        size: 2000,
+        ..explanation()
+    };
+
+    assert_eq!(
+        format_file(&explanation).last().unwrap(),
+        "=> skipped: too large"
+    );
+}
+
+#[test]
+fn test_format_file_without_size_limit() {
+    let explanation = FileExplanation {
+        size: 2000,
+        max_file_size: 0,
+        ..explanation()
+    };
+
+    let lines = format_file(&explanation);
+
+    assert_eq!(lines[4], "  size: 2000 bytes, no limit");
+    assert_eq!(lines.last().unwrap(), "=> checked");
+}
+
+#[test]
+fn test_format_gitignored_file() {
+    let explanation = FileExplanation {
+        walk_exclusion: Some(WalkExclusion::Ignored {
+            ignore_file: PathBuf::from(".gitignore"),
+            pattern: "docs/".to_string(),
+        }),
+        ..explanation()
+    };
+
+    let lines = format_file(&explanation);
+
+    assert_eq!(
+        lines[2],
+        "  project walk: excluded, ignored by 'docs/' in .gitignore"
+    );
+    assert_eq!(
+        lines.last().unwrap(),
+        "=> checked when given on the command line, but not when checking the whole project"
+    );
+}
+
+fn load_store(temp_dir: &TempDir, local: &str) -> IgnoreStore {
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    std::fs::write(&local_toml, local).unwrap();
+    IgnoreStore::load(global_toml, local_toml).unwrap()
+}
+
+fn explain_word(ignore_store: &IgnoreStore, word: &str) -> WordExplanation {
+    let mut dictionary = FakeDictionary::new();
+    dictionary.add_known("hello");
+    let relative_path = RelativePath::from_path_unchecked("intro.md".into());
+    WordExplanation::new(word, &relative_path, ignore_store, &dictionary).unwrap()
+}
+
+#[test]
+fn test_explain_unknown_word() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ignore_store = load_store(&temp_dir, "");
+
+    let explanation = explain_word(&ignore_store, "helo");
+
+    assert!(explanation.is_reported());
+    assert_eq!(
+        explanation.format(),
+        [
+            "'helo', lang: en_US",
+            "  dictionary: unknown",
+            "  ignore rules: no match",
+            "=> reported as an error",
+        ]
+    );
+}
+
+#[test]
+fn test_explain_known_word() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ignore_store = load_store(&temp_dir, "");
+
+    let explanation = explain_word(&ignore_store, "hello");
+
+    assert!(!explanation.is_reported());
+    assert_eq!(explanation.format()[1], "  dictionary: known");
+}
+
+#[test]
+fn test_explain_ignored_word() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ignore_store = load_store(&temp_dir, r#"project = ["skyspell"]"#);
+
+    let explanation = explain_word(&ignore_store, "skyspell");
+
+    assert!(!explanation.is_reported());
+    assert_eq!(
+        explanation.format()[2],
+        "  ignore rules: in the ignore list for the project"
+    );
+}
+
+#[test]
+fn test_explain_acronym() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ignore_store = load_store(&temp_dir, "accept_acronyms = true");
+
+    let explanation = explain_word(&ignore_store, "HTTPS");
+
+    assert!(!explanation.is_reported());
+    assert_eq!(
+        explanation.format()[1],
+        "  acronyms: accepted as an acronym"
+    );
+}
+
+#[test]
+fn test_explain_word_from_personal_dictionary() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let pws = temp_dir.path().join(".aspell.en.pws");
+    std::fs::write(&pws, "personal_ws-1.1 en 1 utf-8\nskyspell\n").unwrap();
+    let mut ignore_store = load_store(&temp_dir, "");
+    ignore_store.set_personal_dictionary(PersonalDictionary::load(&pws).unwrap());
+
+    let explanation = explain_word(&ignore_store, "skyspell");
+
+    assert!(!explanation.is_reported());
+    assert_eq!(
+        explanation.format()[2],
+        format!(
+            "  ignore rules: in the personal dictionary {}",
+            pws.display()
+        )
+    );
+}
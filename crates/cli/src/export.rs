@@ -0,0 +1,188 @@
+//! One-way export of the ignore lists, for projects where other
+//! spell checkers are used too
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use skyspell_core::IgnoreStore;
+
+#[derive(Debug, PartialEq, Eq, clap::ValueEnum, Clone, Copy)]
+pub enum ExportFormat {
+    Cspell,
+    Typos,
+}
+
+/// The exported config, and what could not be represented in it
+#[derive(Debug)]
+pub(crate) struct Export {
+    pub(crate) contents: String,
+    pub(crate) warnings: Vec<String>,
+}
+
+pub(crate) fn export(ignore_store: &IgnoreStore, format: ExportFormat) -> Export {
+    let contents = match format {
+        ExportFormat::Cspell => to_cspell(ignore_store),
+        ExportFormat::Typos => to_typos(ignore_store),
+    };
+    Export {
+        contents,
+        warnings: lossy_warnings(ignore_store),
+    }
+}
+
+// Global and project words are ignored everywhere in the project
+fn words(ignore_store: &IgnoreStore) -> BTreeSet<&str> {
+    ignore_store
+        .global_words()
+        .chain(ignore_store.project_words())
+        .chain(ignore_store.extended_project_words())
+        .collect()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CspellConfig<'a> {
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    words: BTreeSet<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ignore_paths: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    overrides: Vec<CspellOverride<'a>>,
+}
+
+#[derive(Serialize)]
+struct CspellOverride<'a> {
+    filename: String,
+    words: &'a BTreeSet<String>,
+}
+
+fn to_cspell(ignore_store: &IgnoreStore) -> String {
+    let overrides = ignore_store
+        .extension_words()
+        .iter()
+        .filter(|(_, words)| !words.is_empty())
+        .map(|(extension, words)| CspellOverride {
+            filename: format!("**/*.{extension}"),
+            words,
+        })
+        .collect();
+    let config = CspellConfig {
+        words: words(ignore_store),
+        ignore_paths: ignore_store.skip_patterns().collect(),
+        overrides,
+    };
+    let mut res = serde_json::to_string_pretty(&config).expect("config should be serializable");
+    res.push('\n');
+    res
+}
+
+// In typos, mapping a word to itself marks it as correct
+type TyposWords<'a> = BTreeMap<&'a str, &'a str>;
+
+fn typos_words<'a>(words: impl Iterator<Item = &'a str>) -> TyposWords<'a> {
+    words.map(|w| (w, w)).collect()
+}
+
+#[derive(Serialize)]
+struct TyposConfig<'a> {
+    #[serde(skip_serializing_if = "TyposDefault::is_empty")]
+    default: TyposDefault<'a>,
+    #[serde(skip_serializing_if = "TyposFiles::is_empty")]
+    files: TyposFiles<'a>,
+    #[serde(rename = "type", skip_serializing_if = "BTreeMap::is_empty")]
+    types: BTreeMap<&'a str, TyposType<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TyposDefault<'a> {
+    extend_words: TyposWords<'a>,
+}
+
+impl TyposDefault<'_> {
+    fn is_empty(&self) -> bool {
+        self.extend_words.is_empty()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TyposFiles<'a> {
+    extend_exclude: Vec<&'a str>,
+}
+
+impl TyposFiles<'_> {
+    fn is_empty(&self) -> bool {
+        self.extend_exclude.is_empty()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TyposType<'a> {
+    extend_glob: Vec<String>,
+    extend_words: TyposWords<'a>,
+}
+
+fn to_typos(ignore_store: &IgnoreStore) -> String {
+    let types = ignore_store
+        .extension_words()
+        .iter()
+        .filter(|(_, words)| !words.is_empty())
+        .map(|(extension, words)| {
+            let typos_type = TyposType {
+                extend_glob: vec![format!("*.{extension}")],
+                extend_words: typos_words(words.iter().map(|w| w.as_str())),
+            };
+            (extension.as_str(), typos_type)
+        })
+        .collect();
+    let config = TyposConfig {
+        default: TyposDefault {
+            extend_words: typos_words(words(ignore_store).into_iter()),
+        },
+        files: TyposFiles {
+            extend_exclude: ignore_store.skip_patterns().collect(),
+        },
+        types,
+    };
+    toml::to_string(&config).expect("config should be serializable")
+}
+
+// Neither cspell nor typos can ignore words for a given lang or a given
+// file, and their regular expressions don't work like ours
+fn lossy_warnings(ignore_store: &IgnoreStore) -> Vec<String> {
+    let mut res = vec![];
+    let mut warn = |what: String, items: &BTreeSet<String>| {
+        if items.is_empty() {
+            return;
+        }
+        let items: Vec<_> = items.iter().map(|i| i.as_str()).collect();
+        res.push(format!("{what} were not exported: {}", items.join(", ")));
+    };
+    for (lang, words) in ignore_store.lang_words() {
+        warn(format!("Words ignored for lang {lang}"), words);
+    }
+    for (lang, words) in ignore_store.project_lang_words() {
+        warn(
+            format!("Words ignored for the project and lang {lang}"),
+            words,
+        );
+    }
+    for (path, words) in ignore_store.path_words() {
+        warn(format!("Words ignored for path {path}"), words);
+    }
+    for (path, tokens) in ignore_store.path_skipped_tokens() {
+        warn(format!("Tokens skipped in path {path}"), tokens);
+    }
+    let global_regexes = ignore_store.global_regexes().map(String::from).collect();
+    warn("Global regexes".to_string(), &global_regexes);
+    for (extension, patterns) in ignore_store.extension_regexes() {
+        warn(format!("Regexes for extension {extension}"), patterns);
+    }
+    let project_regexes = ignore_store.project_regexes().map(String::from).collect();
+    warn("Project regexes".to_string(), &project_regexes);
+    res
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,98 @@
+use super::*;
+
+use skyspell_core::IgnoreStore;
+use tempfile::TempDir;
+
+const GLOBAL_TOML: &str = r#"
+global = ["skyspell", "toml"]
+
+[extensions]
+py = ["kwargs", "numpy"]
+rs = ["impl"]
+
+[lang]
+fr = ["bonjour"]
+"#;
+
+const LOCAL_TOML: &str = r#"
+patterns = ["*.lock", "vendor/"]
+project = ["kakoune", "skyspell"]
+
+[paths]
+"docs/notes.md" = ["fooo"]
+
+[regex]
+project = ['^tmpVar\w+$']
+"#;
+
+fn load_store(temp_dir: &TempDir) -> IgnoreStore {
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    std::fs::write(&global_toml, GLOBAL_TOML).unwrap();
+    std::fs::write(&local_toml, LOCAL_TOML).unwrap();
+    IgnoreStore::load(global_toml, local_toml).unwrap()
+}
+
+#[test]
+fn test_export_to_cspell() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ignore_store = load_store(&temp_dir);
+
+    let export = export(&ignore_store, ExportFormat::Cspell);
+
+    assert_eq!(export.contents, include_str!("golden/cspell.json"));
+}
+
+#[test]
+fn test_export_to_typos() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ignore_store = load_store(&temp_dir);
+
+    let export = export(&ignore_store, ExportFormat::Typos);
+
+    assert_eq!(export.contents, include_str!("golden/_typos.toml"));
+}
+
+#[test]
+fn test_export_warns_about_lossy_conversions() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ignore_store = load_store(&temp_dir);
+
+    let export = export(&ignore_store, ExportFormat::Typos);
+
+    assert_eq!(
+        export.warnings,
+        [
+            "Words ignored for lang fr were not exported: bonjour",
+            "Words ignored for path docs/notes.md were not exported: fooo",
+            r"Project regexes were not exported: ^tmpVar\w+$",
+        ]
+    );
+}
+
+#[test]
+fn test_export_empty_store() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    let ignore_store = IgnoreStore::load(global_toml, local_toml).unwrap();
+
+    let cspell = export(&ignore_store, ExportFormat::Cspell);
+    let typos = export(&ignore_store, ExportFormat::Typos);
+
+    assert_eq!(cspell.contents, "{}\n");
+    assert_eq!(typos.contents, "");
+    assert!(cspell.warnings.is_empty());
+}
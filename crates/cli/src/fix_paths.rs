@@ -0,0 +1,141 @@
+//! Move the words ignored for files that no longer exist to their new path
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use skyspell_core::{IgnoreStore, Operation, Project, RelativePath};
+
+use crate::Interactor;
+
+/// The words ignored for `from` should be ignored for `to` instead
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Retarget {
+    pub(crate) from: String,
+    pub(crate) to: String,
+}
+
+/// Paths with ignored words that do not exist in the project
+pub(crate) fn stale_paths(project: &Project, ignore_store: &IgnoreStore) -> Vec<String> {
+    let project_path = project.path().as_ref();
+    ignore_store
+        .path_words()
+        .iter()
+        .filter(|(path, words)| !words.is_empty() && !project_path.join(path).exists())
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+/// Ask where the words of each stale path should go, among the files
+/// with the same name - `files` are relative to the project
+pub(crate) fn choose_targets(
+    interactor: &impl Interactor,
+    ignore_store: &IgnoreStore,
+    stale: &[String],
+    files: &[String],
+) -> Vec<Retarget> {
+    let mut res = vec![];
+    for path in stale {
+        let words = describe_words(ignore_store, path);
+        let file_name = Path::new(path).file_name();
+        let candidates: Vec<_> = files
+            .iter()
+            .filter(|f| Path::new(f).file_name() == file_name)
+            .map(|f| f.as_str())
+            .collect();
+        if candidates.is_empty() {
+            interactor.info(&format!(
+                "{path} does not exist and no file has the same name - words: {words}"
+            ));
+            continue;
+        }
+        let mut choices = candidates.clone();
+        choices.push("Leave it");
+        let prompt = format!("{path} does not exist, move its words ({words}) to");
+        match interactor.select(&prompt, &choices) {
+            Some(i) if i < candidates.len() => res.push(Retarget {
+                from: path.clone(),
+                to: candidates[i].to_string(),
+            }),
+            _ => (),
+        }
+    }
+    res
+}
+
+/// Follow the renames recorded by git, oldest first, to find the current
+/// path of each stale path
+pub(crate) fn git_targets(
+    project: &Project,
+    stale: &[String],
+    renames: &[(PathBuf, PathBuf)],
+) -> Vec<Retarget> {
+    let project_path = project.path().as_ref();
+    let relative = |p: &Path| {
+        p.strip_prefix(project_path)
+            .ok()
+            .map(|p| RelativePath::from_path_unchecked(p.to_path_buf()).normalize())
+    };
+    let mut new_paths: HashMap<String, String> = HashMap::new();
+    for (old, new) in renames {
+        if let (Some(old), Some(new)) = (relative(old), relative(new)) {
+            new_paths.insert(old, new);
+        }
+    }
+    let mut res = vec![];
+    for path in stale {
+        let mut current = path;
+        // Files can be renamed back and forth
+        let mut seen = HashSet::new();
+        while let Some(next) = new_paths.get(current) {
+            if !seen.insert(next) {
+                break;
+            }
+            current = next;
+        }
+        if current != path && project_path.join(current).exists() {
+            res.push(Retarget {
+                from: path.clone(),
+                to: current.clone(),
+            });
+        }
+    }
+    res
+}
+
+/// Remove the words from the old paths and add them to the new ones,
+/// unless they are already ignored there
+pub(crate) fn retarget_operations(
+    ignore_store: &IgnoreStore,
+    retargets: &[Retarget],
+) -> Vec<Operation> {
+    let mut res = vec![];
+    for Retarget { from, to } in retargets {
+        let from = RelativePath::from_path_unchecked(PathBuf::from(from));
+        let to = RelativePath::from_path_unchecked(PathBuf::from(to));
+        for word in words_for_path(ignore_store, &from.normalize()) {
+            res.push(Operation::new_remove(vec![Operation::new_ignore_for_path(
+                &word, &from,
+            )]));
+            let add = Operation::new_ignore_for_path(&word, &to);
+            if !add.is_applied(ignore_store) {
+                res.push(add);
+            }
+        }
+    }
+    res
+}
+
+fn words_for_path(ignore_store: &IgnoreStore, path: &str) -> BTreeSet<String> {
+    ignore_store
+        .path_words()
+        .get(path)
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub(crate) fn describe_words(ignore_store: &IgnoreStore, path: &str) -> String {
+    let words: Vec<_> = words_for_path(ignore_store, path).into_iter().collect();
+    words.join(", ")
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,143 @@
+use super::*;
+
+use crate::tests::FakeInteractor;
+use skyspell_core::SKYSPELL_LOCAL_IGNORE;
+use tempfile::TempDir;
+
+fn new_project(temp_dir: &TempDir) -> (Project, IgnoreStore) {
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir_all(project_path.join("docs")).unwrap();
+    let project = Project::new(&project_path).unwrap();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = project_path.join(SKYSPELL_LOCAL_IGNORE);
+    let ignore_store = IgnoreStore::load(global_toml, local_toml).unwrap();
+    (project, ignore_store)
+}
+
+fn ignore_for(ignore_store: &mut IgnoreStore, word: &str, path: &str) {
+    let path = RelativePath::from_path_unchecked(PathBuf::from(path));
+    ignore_store.ignore_for_path(word, &path).unwrap();
+}
+
+fn touch(project: &Project, path: &str) {
+    std::fs::write(project.path().as_ref().join(path), "").unwrap();
+}
+
+#[test]
+fn test_stale_paths() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let (project, mut ignore_store) = new_project(&temp_dir);
+    touch(&project, "docs/b.md");
+    ignore_for(&mut ignore_store, "foo", "docs/a.md");
+    ignore_for(&mut ignore_store, "bar", "docs/b.md");
+
+    let actual = stale_paths(&project, &ignore_store);
+
+    assert_eq!(actual, ["docs/a.md"]);
+}
+
+#[test]
+fn test_choose_targets_among_files_with_the_same_name() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let (_project, mut ignore_store) = new_project(&temp_dir);
+    ignore_for(&mut ignore_store, "foo", "docs/a.md");
+    ignore_for(&mut ignore_store, "bar", "docs/c.md");
+    let stale = ["docs/a.md".to_string(), "docs/c.md".to_string()];
+    let files = [
+        "a.md".to_string(),
+        "guide/a.md".to_string(),
+        "b.md".to_string(),
+    ];
+    let interactor = FakeInteractor::new();
+    interactor.push_int(1);
+
+    let actual = choose_targets(&interactor, &ignore_store, &stale, &files);
+
+    assert_eq!(
+        actual,
+        [Retarget {
+            from: "docs/a.md".to_string(),
+            to: "guide/a.md".to_string(),
+        }]
+    );
+    assert!(interactor.is_empty());
+}
+
+#[test]
+fn test_choose_to_leave_a_stale_path() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let (_project, mut ignore_store) = new_project(&temp_dir);
+    ignore_for(&mut ignore_store, "foo", "docs/a.md");
+    let stale = ["docs/a.md".to_string()];
+    let files = ["a.md".to_string()];
+    let interactor = FakeInteractor::new();
+    interactor.push_int(1);
+
+    let actual = choose_targets(&interactor, &ignore_store, &stale, &files);
+
+    assert!(actual.is_empty());
+}
+
+#[test]
+fn test_git_targets_follow_successive_renames() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let (project, _) = new_project(&temp_dir);
+    touch(&project, "docs/c.md");
+    let root = project.path().as_ref();
+    let renames = [
+        (root.join("docs/a.md"), root.join("docs/b.md")),
+        (root.join("other.md"), root.join("other2.md")),
+        (root.join("docs/b.md"), root.join("docs/c.md")),
+    ];
+    let stale = ["docs/a.md".to_string(), "other.md".to_string()];
+
+    let actual = git_targets(&project, &stale, &renames);
+
+    assert_eq!(
+        actual,
+        [Retarget {
+            from: "docs/a.md".to_string(),
+            to: "docs/c.md".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_retarget_and_undo() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let (_project, mut ignore_store) = new_project(&temp_dir);
+    ignore_for(&mut ignore_store, "foo", "docs/a.md");
+    ignore_for(&mut ignore_store, "bar", "docs/a.md");
+    ignore_for(&mut ignore_store, "bar", "docs/b.md");
+    let retargets = [Retarget {
+        from: "docs/a.md".to_string(),
+        to: "docs/b.md".to_string(),
+    }];
+
+    let mut operation = Operation::new_batch(retarget_operations(&ignore_store, &retargets));
+    operation.execute(&mut ignore_store).unwrap();
+
+    let words = |store: &IgnoreStore, path: &str| describe_words(store, path);
+    assert!(!ignore_store.path_words().contains_key("docs/a.md"));
+    assert_eq!(words(&ignore_store, "docs/b.md"), "bar, foo");
+
+    operation.undo(&mut ignore_store).unwrap();
+
+    assert_eq!(words(&ignore_store, "docs/a.md"), "bar, foo");
+    assert_eq!(words(&ignore_store, "docs/b.md"), "bar");
+}
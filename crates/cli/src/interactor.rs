@@ -1,10 +1,19 @@
+use std::path::Path;
+
+use anyhow::Result;
 use dialoguer::{Confirm, Input, Select};
 
+use crate::editor::{editor_command, get_editor};
+use crate::events::EventSink;
+
 pub trait Interactor {
     fn input(&self, prompt: &str) -> String;
     fn input_letter(&self, prompt: &str, choices: &str) -> String;
     fn select(&self, prompt: &str, choices: &[&str]) -> Option<usize>;
     fn confirm(&self, prompt: &str) -> bool;
+    /// Open `path` in an editor at the given line and column (both starting at 1),
+    /// and wait for the editor to exit
+    fn open_editor(&self, path: &Path, line: usize, column: usize) -> Result<()>;
 
     fn info(&self, message: &str) {
         println!("{}", message);
@@ -13,6 +22,12 @@ pub trait Interactor {
     fn error(&self, message: &str) {
         eprintln!("{}", message);
     }
+
+    /// Where to send structured events, for frontends that are not
+    /// a terminal - when set, nothing else is printed on stdout
+    fn event_sink(&self) -> Option<&dyn EventSink> {
+        None
+    }
 }
 
 pub struct ConsoleInteractor;
@@ -59,6 +74,11 @@ impl Interactor for ConsoleInteractor {
         Confirm::new().with_prompt(prompt).interact().unwrap()
     }
 
+    fn open_editor(&self, path: &Path, line: usize, column: usize) -> Result<()> {
+        let editor = get_editor();
+        editor_command(&editor, path, line, column).run()
+    }
+
     fn error(&self, message: &str) {
         eprintln!("{}", message);
     }
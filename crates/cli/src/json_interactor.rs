@@ -0,0 +1,165 @@
+use std::cell::RefCell;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::events::{Command, Event, EventSink, QuestionKind};
+use crate::Interactor;
+
+// The frontend owns the terminal, so the 'o' action is never offered
+const OPEN_EDITOR_UNSUPPORTED: &str = "Opening an editor is not supported with the JSON frontend";
+
+/// An Interactor for frontends driving the interactive session over a pipe:
+/// events are written as newline-delimited JSON, and commands are read
+/// from single JSON lines
+pub struct JsonInteractor<R: BufRead, W: Write> {
+    reader: RefCell<R>,
+    writer: RefCell<W>,
+    // Set when the frontend can no longer read our output
+    write_error: RefCell<Option<io::Error>>,
+}
+
+impl<R: BufRead, W: Write> JsonInteractor<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader: RefCell::new(reader),
+            writer: RefCell::new(writer),
+            write_error: RefCell::new(None),
+        }
+    }
+
+    // End of input is treated as a request to quit, so that the
+    // session stops if the frontend goes away
+    fn read_command(&self) -> Command {
+        loop {
+            if self.write_error.borrow().is_some() {
+                return Command::Quit;
+            }
+            let mut line = String::new();
+            match self.reader.borrow_mut().read_line(&mut line) {
+                Ok(0) => return Command::Quit,
+                Ok(_) => (),
+                Err(e) => {
+                    self.invalid_command(&format!("Could not read command: {e}"));
+                    return Command::Quit;
+                }
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(command) => return command,
+                Err(e) => self.invalid_command(&format!("Could not parse command: {e}")),
+            }
+        }
+    }
+
+    fn invalid_command(&self, message: &str) {
+        self.emit(Event::InvalidCommand {
+            message: message.to_string(),
+        })
+    }
+
+    fn question(&self, kind: QuestionKind, message: &str, choices: &[&str]) {
+        self.emit(Event::Question {
+            kind,
+            message: message.to_string(),
+            choices: choices.iter().map(|x| x.to_string()).collect(),
+        })
+    }
+}
+
+impl<R: BufRead, W: Write> EventSink for JsonInteractor<R, W> {
+    fn emit(&self, event: Event) {
+        if self.write_error.borrow().is_some() {
+            return;
+        }
+        let json = serde_json::to_string(&event).expect("events should be serializable");
+        let mut writer = self.writer.borrow_mut();
+        if let Err(e) = writeln!(writer, "{json}").and_then(|()| writer.flush()) {
+            *self.write_error.borrow_mut() = Some(e);
+        }
+    }
+
+    fn take_error(&self) -> Option<io::Error> {
+        self.write_error.borrow_mut().take()
+    }
+}
+
+impl<R: BufRead, W: Write> Interactor for JsonInteractor<R, W> {
+    fn input(&self, prompt: &str) -> String {
+        self.question(QuestionKind::Text, prompt, &[]);
+        loop {
+            match self.read_command() {
+                Command::Reply { text } => return text,
+                Command::Quit => return String::new(),
+                c => self.invalid_command(&format!("Expected a reply, got {c:?}")),
+            }
+        }
+    }
+
+    // The prompt itself is sent by the checker as a Prompt event, with
+    // the description of each action
+    fn input_letter(&self, _prompt: &str, choices: &str) -> String {
+        loop {
+            match self.read_command() {
+                Command::Choose { action } if action.len() == 1 && choices.contains(&action) => {
+                    return action
+                }
+                Command::Choose { action } if action == "o" => {
+                    self.emit(Event::UnsupportedAction {
+                        action,
+                        message: OPEN_EDITOR_UNSUPPORTED.to_string(),
+                    })
+                }
+                Command::Quit => return "q".to_string(),
+                c => self.invalid_command(&format!("Expected one of '{choices}', got {c:?}")),
+            }
+        }
+    }
+
+    fn select(&self, prompt: &str, choices: &[&str]) -> Option<usize> {
+        self.question(QuestionKind::Select, prompt, choices);
+        loop {
+            match self.read_command() {
+                Command::Select { index: Some(i) } if i >= choices.len() => {
+                    self.invalid_command(&format!("Index {i} is out of range"))
+                }
+                Command::Select { index } => return index,
+                Command::Quit => return None,
+                c => self.invalid_command(&format!("Expected a selection, got {c:?}")),
+            }
+        }
+    }
+
+    fn confirm(&self, prompt: &str) -> bool {
+        self.question(QuestionKind::Confirm, prompt, &[]);
+        loop {
+            match self.read_command() {
+                Command::Confirm { answer } => return answer,
+                Command::Quit => return false,
+                c => self.invalid_command(&format!("Expected a confirmation, got {c:?}")),
+            }
+        }
+    }
+
+    fn open_editor(&self, _path: &Path, _line: usize, _column: usize) -> Result<()> {
+        bail!(OPEN_EDITOR_UNSUPPORTED)
+    }
+
+    fn info(&self, _message: &str) {}
+
+    fn error(&self, message: &str) {
+        self.emit(Event::Rejected {
+            message: message.to_string(),
+        })
+    }
+
+    fn event_sink(&self) -> Option<&dyn EventSink> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,268 @@
+use std::cell::RefCell;
+use std::io::{Cursor, Write};
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+use skyspell_core::tests::{FakeDictionary, NON_ASCII_LINE};
+use skyspell_core::{Checker, CheckerState, IgnoreStore, Project};
+use tempfile::TempDir;
+
+use super::*;
+use crate::InteractiveChecker;
+
+// Lets the test read what the interactor wrote after the checker took ownership of it
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SharedBuffer {
+    fn events(&self) -> Vec<Value> {
+        let bytes = self.0.borrow();
+        let text = std::str::from_utf8(&bytes).unwrap();
+        text.lines()
+            .map(|l| serde_json::from_str(l).expect("each line should be valid JSON"))
+            .collect()
+    }
+}
+
+type TestInteractor = JsonInteractor<Cursor<Vec<u8>>, SharedBuffer>;
+
+fn new_interactor(commands: &[&str]) -> (TestInteractor, SharedBuffer) {
+    let input = commands.join("\n");
+    let output = SharedBuffer::default();
+    let interactor = JsonInteractor::new(Cursor::new(input.into_bytes()), output.clone());
+    (interactor, output)
+}
+
+fn new_checker<W: Write>(
+    temp_dir: &TempDir,
+    interactor: JsonInteractor<Cursor<Vec<u8>>, W>,
+    known: &[&str],
+) -> InteractiveChecker<JsonInteractor<Cursor<Vec<u8>>, W>, FakeDictionary> {
+    let mut dictionary = FakeDictionary::new();
+    for word in known {
+        dictionary.add_known(word);
+    }
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir(&project_path).unwrap();
+    let project = Project::new(&project_path).unwrap();
+    let state = CheckerState::load(Some(temp_dir.path().join("state.toml"))).unwrap();
+    let ignore_store = IgnoreStore::load(
+        temp_dir.path().join("global.toml"),
+        temp_dir.path().join("skyspell.toml"),
+    )
+    .unwrap();
+    InteractiveChecker::new(project, interactor, dictionary, ignore_store, state).unwrap()
+}
+
+fn prompt_keys(event: &Value) -> Vec<&str> {
+    event["actions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["key"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_full_session() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let (interactor, output) = new_interactor(&[
+        "not json",
+        r#"{"command": "choose", "action": "o"}"#,
+        r#"{"command": "choose", "action": "p"}"#,
+        r#"{"command": "choose", "action": "x"}"#,
+    ]);
+    let mut checker = new_checker(&temp_dir, interactor, &["Hello", "This", "is"]);
+    let readme = temp_dir.path().join("project/README.md");
+    std::fs::write(&readme, "Hello fooo\nThis is bar\n").unwrap();
+
+    checker.process(&readme, &()).unwrap();
+    let res = checker.success();
+
+    assert!(res.is_err(), "'bar' was skipped");
+    let events = output.events();
+    let kinds: Vec<_> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        kinds,
+        &[
+            "error",
+            "prompt",
+            "invalid_command",
+            "unsupported_action",
+            "applied",
+            "error",
+            "prompt",
+            "summary"
+        ]
+    );
+    assert_eq!(
+        events[0],
+        json!({
+            "event": "error",
+            "path": "README.md",
+            "line": 1,
+            "column": 7,
+            "word": "fooo",
+            "kind": "unknown-word",
+            "context": "Hello fooo",
+        })
+    );
+    assert_eq!(
+        prompt_keys(&events[1]),
+        &["g", "l", "e", "p", "f", "n", "s", "x", "q"]
+    );
+    assert_eq!(
+        events[3],
+        json!({
+            "event": "unsupported_action",
+            "action": "o",
+            "message": "Opening an editor is not supported with the JSON frontend",
+        })
+    );
+    assert_eq!(
+        events[4],
+        json!({"event": "applied", "word": "fooo", "scope": "project"})
+    );
+    assert_eq!(events[5]["word"], "bar");
+    assert_eq!(
+        events[7],
+        json!({"event": "summary", "errors": 2, "skipped": ["bar"]})
+    );
+}
+
+#[test]
+fn test_quit_on_end_of_input() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let (interactor, output) = new_interactor(&[]);
+    let mut checker = new_checker(&temp_dir, interactor, &[]);
+    let readme = temp_dir.path().join("project/README.md");
+    std::fs::write(&readme, "fooo\n").unwrap();
+
+    let res = checker.process(&readme, &());
+
+    assert!(res.is_err());
+    let kinds: Vec<_> = output
+        .events()
+        .iter()
+        .map(|e| e["event"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(kinds, &["error", "prompt"]);
+}
+
+#[test]
+fn test_mixed_script_error() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let (interactor, output) = new_interactor(&[r#"{"command": "choose", "action": "x"}"#]);
+    let mut checker = new_checker(&temp_dir, interactor, &[]);
+    checker.set_check_confusables(true);
+    let readme = temp_dir.path().join("project/README.md");
+    std::fs::write(&readme, "c\u{43e}de\n").unwrap();
+
+    checker.process(&readme, &()).unwrap();
+
+    let events = output.events();
+    assert_eq!(
+        events[0],
+        json!({
+            "event": "error",
+            "path": "README.md",
+            "line": 1,
+            "column": 1,
+            "word": "c\u{43e}de",
+            "kind": "mixed-script",
+            "context": "c\u{43e}de",
+            "message": "'\u{43e}' (U+043E) looks like 'o'",
+            "suggestion": "code",
+        })
+    );
+}
+
+#[test]
+fn test_confirm() {
+    let (interactor, output) = new_interactor(&[
+        r#"{"command": "reply", "text": "yes"}"#,
+        r#"{"command": "confirm", "answer": true}"#,
+    ]);
+
+    assert!(interactor.confirm("Undo?"));
+
+    let events = output.events();
+    assert_eq!(
+        events[0],
+        json!({"event": "question", "kind": "confirm", "message": "Undo?"})
+    );
+    assert_eq!(events[1]["event"], "invalid_command");
+}
+
+#[test]
+fn test_error_column_is_in_chars() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let (interactor, output) = new_interactor(&[r#"{"command": "choose", "action": "x"}"#]);
+    let mut checker = new_checker(&temp_dir, interactor, &["e", "te", "日本語"]);
+    let readme = temp_dir.path().join("project/README.md");
+    std::fs::write(&readme, NON_ASCII_LINE).unwrap();
+
+    checker.process(&readme, &()).unwrap();
+
+    let events = output.events();
+    assert_eq!(events[0]["word"], "fooo");
+    assert_eq!(events[0]["column"], 13);
+}
+
+// Like stdout once the frontend has exited
+struct ClosedPipe;
+
+impl Write for ClosedPipe {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_closed_output_stops_the_session() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let input = r#"{"command": "choose", "action": "x"}"#;
+    let interactor = JsonInteractor::new(Cursor::new(input.as_bytes().to_vec()), ClosedPipe);
+    let mut checker = new_checker(&temp_dir, interactor, &[]);
+    let readme = temp_dir.path().join("project/README.md");
+    std::fs::write(&readme, "fooo\n").unwrap();
+
+    let err = checker.process(&readme, &()).unwrap_err();
+
+    assert!(
+        format!("{err:#}").contains("Could not write events"),
+        "{err:#}"
+    );
+}
@@ -1,46 +1,105 @@
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{BufRead, Read};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use clap::Parser;
+use clap_complete::Shell;
 use colored::*;
 
+use serde::Serialize;
+use skyspell_core::directory_pattern;
+use skyspell_core::suggest_replacements;
+use skyspell_core::AcceptAcronyms;
 use skyspell_core::Checker;
+use skyspell_core::CheckerState;
+use skyspell_core::Classification;
 use skyspell_core::Dictionary;
+use skyspell_core::DictionaryCache;
+use skyspell_core::ExtractMode;
+use skyspell_core::FileExplanation;
+use skyspell_core::HookOptions;
+use skyspell_core::IgnoreStats;
 use skyspell_core::IgnoreStore;
+use skyspell_core::IgnoredWord;
+use skyspell_core::LangOpener;
+use skyspell_core::LangRules;
+use skyspell_core::LimitUsage;
+use skyspell_core::LocalIgnore;
+use skyspell_core::Operation;
+use skyspell_core::PersistentCachedDictionary;
+use skyspell_core::PersonalDictionary;
 use skyspell_core::ProcessOutcome;
 use skyspell_core::Project;
+use skyspell_core::ProjectPath;
+use skyspell_core::ReportEntry;
+use skyspell_core::SkipReason;
+use skyspell_core::SkyspellError;
+use skyspell_core::SuggestionWorker;
 use skyspell_core::SystemDictionary;
+use skyspell_core::TokenClassifier;
+use skyspell_core::TokenProcessor;
+use skyspell_core::Verbosity;
+use skyspell_core::IGNORE_SOURCES;
+use skyspell_core::SKYSPELL_LOCAL_IGNORE;
+use skyspell_core::{builtin_list, builtin_list_names, find_project_root, global_path};
+use skyspell_core::{load_ops_file, open_dictionary, output, parse_file_size, RelativePath};
+
+use crate::checkers::non_interactive::{print_unused_ignores, IgnoreHit, UnusedIgnore};
+use crate::completions::{completion_candidates, write_completions, CompletionKind};
+use crate::did_you_mean::{parse_opts, translate_foreign_verb};
+use crate::explain::{format_file, WordExplanation};
+use crate::export::{export, ExportFormat};
+use crate::fix_paths::{choose_targets, describe_words, git_targets, retarget_operations};
+use crate::fix_paths::{stale_paths, Retarget};
+use crate::parallel::{check_in_parallel, default_jobs};
 
 mod checkers;
+mod completions;
+mod did_you_mean;
+mod editor;
+pub mod events;
+mod explain;
+mod export;
+mod fix_paths;
 pub mod interactor;
+mod json_interactor;
+mod parallel;
+#[cfg(feature = "tui")]
+mod review;
+mod vcs;
+use checkers::interactive::undo_last_operation;
 pub use checkers::{InteractiveChecker, NonInteractiveChecker};
 pub use interactor::{ConsoleInteractor, Interactor};
+pub use json_interactor::JsonInteractor;
 
 #[macro_export]
 macro_rules! info_1 {
     ($($arg:tt)*) => ({
-        println!("{} {}", "::".bold().blue(), format!($($arg)*));
+        skyspell_core::output::info(&format!("{} {}", "::".bold().blue(), format!($($arg)*)));
     })
 }
 
 #[macro_export]
 macro_rules! info_2 {
     ($($arg:tt)*) => ({
-        println!("{} {}", "=>".bold().blue(), format!($($arg)*));
+        skyspell_core::output::info(&format!("{} {}", "=>".bold().blue(), format!($($arg)*)));
     })
 }
 
 #[macro_export]
 macro_rules! info_3 {
     ($($arg:tt)*) => ({
-        println!("{} {}", "*".bold().blue(), format!($($arg)*));
+        skyspell_core::output::info(&format!("{} {}", "*".bold().blue(), format!($($arg)*)));
     })
 }
 
 #[macro_export]
 macro_rules! print_error {
     ($($arg:tt)*) => ({
-    eprintln!("{} {}", "Error:".red(), format!($($arg)*));
+        skyspell_core::output::error(&format!("{} {}", "Error:".red(), format!($($arg)*)));
     })
 }
 
@@ -51,6 +110,20 @@ pub enum OutputFormat {
     Json,
 }
 
+#[derive(Debug, PartialEq, Eq, clap::ValueEnum, Clone, Copy, Default)]
+pub enum Frontend {
+    #[default]
+    Console,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq, clap::ValueEnum, Clone, Copy, Default)]
+pub enum SortBy {
+    #[default]
+    Path,
+    Word,
+}
+
 impl OutputFormat {
     fn is_text(&self) -> bool {
         matches!(self, OutputFormat::Text)
@@ -60,15 +133,73 @@ impl OutputFormat {
 #[derive(Parser)]
 #[clap(version)]
 pub struct Opts {
-    #[clap(long, help = "Language to use")]
-    pub lang: String,
+    #[clap(
+        long,
+        help = "Language to use - required to check files and get suggestions"
+    )]
+    pub lang: Option<String>,
 
     #[clap(long, help = "Project path")]
     project_path: Option<PathBuf>,
 
+    #[clap(
+        long,
+        help = "Dictionary provider to use, like 'aspell' or 'hunspell' - use an unknown name to list them"
+    )]
+    provider: Option<String>,
+
     #[clap(long, value_enum, short = 'o', help = "Output format")]
     output_format: Option<OutputFormat>,
 
+    #[clap(
+        long,
+        short = 'q',
+        conflicts_with = "verbose",
+        help = "Only print errors, and the results of the command"
+    )]
+    quiet: bool,
+
+    #[clap(
+        long,
+        short = 'v',
+        action = clap::ArgAction::Count,
+        help = "Explain why files are skipped and print the output of the post_operation hook - use -vv to also list the checked files"
+    )]
+    verbose: u8,
+
+    #[clap(
+        long,
+        help = "Fail when the post_operation hook fails, instead of printing a warning"
+    )]
+    strict_hooks: bool,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "Also accept the words of this aspell, hunspell or Enchant personal dictionary - see `use_personal_dictionary` in the README"
+    )]
+    use_personal_dict: Option<PathBuf>,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "Where to store the undo history, the reports and the caches - defaults to $SKYSPELL_STATE_DIR, or to the skyspell directory in the user's data directory"
+    )]
+    state_dir: Option<PathBuf>,
+
+    #[clap(
+        long,
+        conflicts_with = "state_dir",
+        help = "Keep the undo history, the reports and the caches in memory, and never write them to disk"
+    )]
+    no_state: bool,
+
+    #[clap(
+        long,
+        help = "If an ignore file cannot be parsed, for instance after a crash, replace it with the backup of its previous version"
+    )]
+    recover_ignore_file: bool,
+
     #[clap(subcommand)]
     action: Action,
 }
@@ -85,12 +216,155 @@ enum Action {
     Add(AddOpts),
     #[clap(about = "Remove word from one of the ignore lists")]
     Remove(RemoveOpts),
+    #[clap(about = "Skip the files matching a pattern, or a whole directory")]
+    Skip(SkipOpts),
+    #[clap(about = "Remove a pattern added with skip")]
+    Unskip(SkipOpts),
+    #[clap(about = "Ignore all the words matching a regular expression")]
+    AddRegex(RegexOpts),
+    #[clap(about = "Remove a regular expression added with add-regex")]
+    RemoveRegex(RegexOpts),
+    #[clap(about = "Never check the words of an identifier, like 'XgboostClassifier'")]
+    AddIdentifier(IdentifierOpts),
     #[clap(about = "Check files for spelling errors")]
     Check(CheckOpts),
+    #[cfg(feature = "tui")]
+    #[clap(about = "Check files, then handle the errors word by word in a terminal UI")]
+    Review(ReviewOpts),
     #[clap(about = "Suggest replacements for the given error")]
     Suggest(SuggestOpts),
     #[clap(about = "Undo last operation")]
-    Undo,
+    Undo(UndoOpts),
+    #[clap(about = "Show recorded operations, newest first")]
+    History(HistoryOpts),
+    #[clap(
+        about = "Apply the operations listed in a file, skipping the ones already applied",
+        long_about = APPLY_OPS_HELP
+    )]
+    ApplyOps(ApplyOpsOpts),
+    #[clap(about = "Append every following operation in this project to a file")]
+    Record(RecordOpts),
+    #[clap(about = "Show the errors saved by `check --non-interactive --save-report`")]
+    ShowReport,
+    #[clap(about = "Add words from the last saved report, by index")]
+    AddFromReport(AddFromReportOpts),
+    #[clap(about = "Move the words ignored for files that no longer exist to their new path")]
+    FixPaths(FixPathsOpts),
+    #[clap(about = "Add the ignored words of another project to this one")]
+    Import(ImportOpts),
+    #[clap(about = "Check the whole project and list the ignore entries that were not used")]
+    Prune(PruneOpts),
+    #[clap(about = "Show ignored words and the files they come from, or the builtin word lists")]
+    List(ListOpts),
+    #[clap(about = "List the ignore lists that are close to the limits set in the local config")]
+    LintIgnores,
+    #[clap(
+        about = "Print the ignore lists in the format of another spell checker",
+        long_about = EXPORT_HELP
+    )]
+    Export(ExportOpts),
+    #[clap(about = "Show why a file is checked or skipped, and why a word is reported or not")]
+    Explain(ExplainOpts),
+    #[clap(about = "Show the words extracted from a file or a string, and where they come from")]
+    Tokens(TokensOpts),
+    #[clap(about = "Print completion script for the given shell")]
+    Completions(CompletionsOpts),
+    #[clap(name = "__complete", hide = true)]
+    Complete(CompleteOpts),
+    // Verbs of other spell checkers, which only print the skyspell equivalent
+    #[clap(hide = true)]
+    Ignore(ForeignVerbOpts),
+    #[clap(hide = true)]
+    Allow(ForeignVerbOpts),
+    #[clap(name = "skipfile", hide = true)]
+    SkipFile(ForeignVerbOpts),
+}
+
+#[derive(Parser)]
+struct ForeignVerbOpts {
+    #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+#[derive(Parser)]
+struct CompletionsOpts {
+    #[clap(value_enum)]
+    shell: Shell,
+}
+
+#[derive(Parser)]
+struct ListOpts {
+    #[clap(
+        long,
+        num_args = 0..=1,
+        value_name = "NAME",
+        help = "Show the builtin word lists, or the words of the given one"
+    )]
+    builtin: Option<Option<String>>,
+
+    #[clap(
+        long,
+        conflicts_with = "builtin",
+        help = "Show the skip patterns of the project instead"
+    )]
+    skipped: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["builtin", "skipped"],
+        help = "Only show the words ignored for the current project, grouped by category"
+    )]
+    project: bool,
+}
+
+const EXPORT_HELP: &str = "\
+Print the ignore lists in the format of another spell checker, on stdout
+
+With --format cspell, a cspell.json fragment is printed:
+  * global and project words go to `words`
+  * skipped patterns go to `ignorePaths`
+  * words ignored for an extension go to `overrides`, with a `**/*.<ext>` filename
+
+With --format typos, a _typos.toml file is printed:
+  * global and project words go to `default.extend-words`
+  * skipped patterns go to `files.extend-exclude`
+  * words ignored for an extension go to `type.<ext>.extend-words`, with a `*.<ext>` glob
+
+Words ignored for a lang or a path, skipped tokens and regexes cannot be
+represented: they are listed in warnings on stderr";
+
+#[derive(Parser)]
+struct ExportOpts {
+    #[clap(long, value_enum, help = "The spell checker to export to")]
+    format: ExportFormat,
+}
+
+#[derive(Parser)]
+struct ExplainOpts {
+    #[clap(help = "The file to explain")]
+    path: PathBuf,
+
+    #[clap(
+        long,
+        help = "Also explain whether this word would be reported in the file - requires --lang"
+    )]
+    word: Option<String>,
+
+    #[clap(
+        long,
+        value_parser = parse_file_size,
+        help = "Same as `check --max-file-size`"
+    )]
+    max_file_size: Option<u64>,
+
+    #[clap(long, value_delimiter = ',', help = "Same as `check --extensions`")]
+    extensions: Vec<String>,
+}
+
+#[derive(Parser)]
+struct CompleteOpts {
+    #[clap(value_enum)]
+    kind: CompletionKind,
 }
 
 #[derive(Parser)]
@@ -106,6 +380,20 @@ struct AddOpts {
 
     #[clap(long, help = "Add word to the ignore list for the given path")]
     relative_path: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Add word to the ignore list for the given language - use with --project to only ignore it for the current project"
+    )]
+    lang: Option<String>,
+
+    #[clap(
+        long,
+        requires = "project",
+        conflicts_with_all = ["extension", "relative_path", "lang"],
+        help = "List the word under this category in the ignore list for the current project"
+    )]
+    category: Option<String>,
 }
 
 #[derive(Parser)]
@@ -116,177 +404,2652 @@ struct CheckOpts {
     )]
     non_interactive: bool,
 
-    #[clap(help = "List of paths to check")]
-    paths: Vec<PathBuf>,
-}
+    #[clap(
+        long,
+        conflicts_with = "non_interactive",
+        help = "Ask what to do for each unknown word even if stdin or stdout is not a terminal"
+    )]
+    force_interactive: bool,
 
-#[derive(Parser)]
-struct SuggestOpts {
-    word: String,
-}
+    #[clap(
+        long,
+        help = "Also check this directory outside the project, using the project's ignore rules"
+    )]
+    also: Vec<PathBuf>,
 
-#[derive(Parser)]
-struct RemoveOpts {
-    #[clap(help = "The word to remove")]
-    word: String,
+    #[clap(
+        long,
+        value_parser = parse_file_size,
+        help = "Skip files larger than this size, like '5MB'. Use 0 to check files of any size"
+    )]
+    max_file_size: Option<u64>,
 
     #[clap(
         long,
-        help = "Remove word from the ignore list for the current project"
+        value_delimiter = ',',
+        help = "Only check files with these extensions, like 'md,txt'. Use 'none' for files without an extension"
     )]
-    project: bool,
+    extensions: Vec<String>,
 
     #[clap(
         long,
-        help = "Remove word from the ignore list for the given extension"
+        value_name = "EXTENSION",
+        conflicts_with_all = ["modified", "all"],
+        help = "Check the paths given on the command line or with --paths-from as if they had this extension, like 'py' for a script without one"
     )]
-    extension: Option<String>,
+    assume_extension: Option<String>,
 
-    #[clap(long, help = "Remove word from the ignore list for the given path")]
-    relative_path: Option<PathBuf>,
-}
+    #[clap(
+        long,
+        value_enum,
+        help = "How to sort errors in non-interactive mode (default: path)"
+    )]
+    sort_by: Option<SortBy>,
 
-fn add(project: Project, mut ignore_store: IgnoreStore, opts: &AddOpts) -> Result<()> {
-    let word = &opts.word;
-    match (&opts.relative_path, &opts.extension, &opts.project) {
-        (None, None, false) => ignore_store.ignore(word),
-        (None, Some(e), _) => ignore_store.ignore_for_extension(word, e),
-        (Some(relative_path), None, _) => {
-            let relative_path = project.get_relative_path(relative_path)?;
-            ignore_store.ignore_for_path(word, &relative_path)
-        }
-        (None, None, true) => ignore_store.ignore_for_project(word),
-        (Some(_), Some(_), _) => bail!("Cannot use both --relative-path and --extension"),
-    }
-}
+    #[clap(
+        long,
+        hide = true,
+        help = "Print how many lookups were performed in each ignore list"
+    )]
+    stats: bool,
 
-fn remove(project: Project, mut ignore_store: IgnoreStore, opts: &RemoveOpts) -> Result<()> {
-    let word = &opts.word;
-    match (&opts.relative_path, &opts.extension, &opts.project) {
-        (None, None, false) => ignore_store.remove_ignored(word),
-        (None, Some(e), _) => ignore_store.remove_ignored_for_extension(word, e),
-        (Some(relative_path), None, _) => {
-            let relative_path = project.get_relative_path(relative_path)?;
-            ignore_store.remove_ignored_for_path(word, &relative_path)
-        }
-        (None, None, true) => ignore_store.remove_ignored_for_project(word),
-        (Some(_), Some(_), _) => bail!("Cannot use both --relative-path and --extension"),
-    }
-}
+    #[clap(
+        long,
+        help = "If the project ignore file cannot be read, warn and check the project without it"
+    )]
+    ignore_broken_config: bool,
 
-fn check(
-    project: Project,
-    ignore_store: IgnoreStore,
-    dictionary: impl Dictionary,
-    opts: &CheckOpts,
-    output_format: OutputFormat,
-) -> Result<()> {
-    let interactive = !opts.non_interactive;
+    #[clap(
+        long,
+        value_enum,
+        help = "How to interact with the user in interactive mode - 'json' reads commands from stdin and writes events to stdout"
+    )]
+    frontend: Option<Frontend>,
 
-    match interactive {
-        false => {
-            let mut checker =
-                NonInteractiveChecker::new(project, dictionary, ignore_store, output_format)?;
-            check_with(&mut checker, &opts.paths, output_format)
-        }
-        true => {
-            let interactor = ConsoleInteractor;
-            let mut checker =
-                InteractiveChecker::new(project, interactor, dictionary, ignore_store, None)?;
-            check_with(&mut checker, &opts.paths, output_format)
-        }
-    }
-}
+    #[clap(
+        long,
+        help = "In non-interactive mode, stop at the first file that cannot be checked instead of reporting it at the end"
+    )]
+    fail_fast: bool,
 
-fn check_with<C, D>(checker: &mut C, paths: &[PathBuf], output_format: OutputFormat) -> Result<()>
-where
-    C: Checker<D, SourceContext = ()>,
-    D: Dictionary,
-{
-    let project = checker.project();
-    let mut paths = paths.to_vec();
-    if paths.is_empty() {
-        let walker = project.walk()?;
-        for dir_entry in walker {
-            let dir_entry = dir_entry?;
-            let file_type = dir_entry.file_type().expect("walker yielded stdin");
-            if !file_type.is_file() {
-                continue;
-            }
-            let path = dir_entry.path();
-            paths.push(path.to_path_buf());
-        }
-    }
+    #[clap(
+        long,
+        requires = "non_interactive",
+        help = "Save the errors, so that they can be added later with `skyspell add-from-report`"
+    )]
+    save_report: bool,
 
-    let mut checked = 0;
-    let mut skipped = 0;
-    for path in paths {
-        let outcome = checker.process(&path, &())?;
-        match outcome {
-            ProcessOutcome::Skipped => skipped += 1,
-            ProcessOutcome::Checked => checked += 1,
-        }
-    }
+    #[clap(
+        long,
+        overrides_with = "persistent_cache",
+        help = "In non-interactive mode, don't remember what the dictionary said about each word from one run to the next"
+    )]
+    no_persistent_cache: bool,
 
-    if output_format.is_text() {
-        info_3!("Checked {checked} files - {skipped} skipped");
-    }
+    #[clap(
+        long,
+        overrides_with = "no_persistent_cache",
+        help = "Remember what the dictionary said about each word from one run to the next (default)"
+    )]
+    persistent_cache: bool,
 
-    checker.success()
-}
+    #[clap(
+        long,
+        requires = "non_interactive",
+        value_name = "PATH",
+        help = "With --output-format json, write the results to this file and only print a summary"
+    )]
+    output: Option<PathBuf>,
 
-fn undo(project: Project, dictionary: impl Dictionary, ignore_store: IgnoreStore) -> Result<()> {
-    let interactor = ConsoleInteractor;
-    let mut checker = InteractiveChecker::new(project, interactor, dictionary, ignore_store, None)?;
-    checker.undo()
-}
+    #[clap(
+        long,
+        num_args = 0..=1,
+        default_missing_value = "2",
+        value_name = "MIN_LENGTH",
+        help = "Accept all-caps words like 'HTTP' without looking them up, if they are at least MIN_LENGTH letters long (default: 2)"
+    )]
+    accept_acronyms: Option<usize>,
 
-fn suggest(dictionary: impl Dictionary, opts: &SuggestOpts) -> Result<()> {
-    let word = &opts.word;
-    if dictionary.check(word)? {
-        return Ok(());
-    }
+    #[clap(
+        long,
+        help = "Accept capitalized words like 'Alice' without looking them up, unless they start a sentence"
+    )]
+    ignore_capitalized: bool,
 
-    let suggestions = dictionary.suggest(word)?;
+    #[clap(
+        long,
+        conflicts_with = "paths",
+        help = "Only check files that are modified, staged or untracked according to git"
+    )]
+    modified: bool,
 
-    for suggestion in suggestions.iter() {
-        println!("{}", suggestion);
-    }
+    #[clap(
+        long,
+        conflicts_with_all = ["paths", "modified", "paths_from"],
+        help = "Check the whole project, even if check_paths is set in the local config"
+    )]
+    all: bool,
 
-    Ok(())
-}
+    #[clap(
+        long,
+        value_delimiter = ',',
+        help = "Also ignore the words of these builtin lists, like 'programming,unix' - see `skyspell list`"
+    )]
+    builtin_lists: Vec<String>,
 
-fn run<D: Dictionary>(
-    project: Project,
-    opts: &Opts,
-    dictionary: D,
-    ignore_store: IgnoreStore,
-) -> Result<()> {
-    let output_format = opts.output_format.unwrap_or_default();
-    match &opts.action {
-        Action::Add(opts) => add(project, ignore_store, opts),
-        Action::Remove(opts) => remove(project, ignore_store, opts),
-        Action::Check(opts) => check(project, ignore_store, dictionary, opts, output_format),
-        Action::Suggest(opts) => suggest(dictionary, opts),
-        Action::Undo => undo(project, dictionary, ignore_store),
-    }
-}
+    #[clap(
+        long,
+        help = "Report unknown words mixing several scripts, like 'code' spelled with a Cyrillic 'o', as mixed-script errors"
+    )]
+    check_confusables: bool,
 
-pub fn main() -> Result<()> {
-    SystemDictionary::init();
+    #[clap(
+        long,
+        help = "Also check the names of the files and of the directories containing them"
+    )]
+    check_filenames: bool,
 
-    let opts: Opts = Opts::parse();
-    let lang = &opts.lang;
-    let project_path = match opts.project_path.clone() {
-        Some(p) => p,
-        None => std::env::current_dir().context("Could not get current working directory")?,
-    };
+    #[clap(
+        long,
+        requires = "non_interactive",
+        help = "Number of files to check in parallel in non-interactive mode (default: number of CPUs)"
+    )]
+    jobs: Option<NonZeroUsize>,
+
+    #[clap(
+        long,
+        requires = "non_interactive",
+        value_name = "N",
+        help = "Don't report unknown words found at least N times in the checked files, list them separately instead"
+    )]
+    min_occurrences_to_accept: Option<NonZeroUsize>,
+
+    #[clap(
+        long,
+        help = "Check the given paths even if they are outside the project, using only the global ignore lists"
+    )]
+    allow_outside_project: bool,
+
+    #[clap(
+        long,
+        help = "Fail on files that are not valid UTF-8, instead of ignoring the invalid bytes"
+    )]
+    strict_utf8: bool,
+
+    #[clap(
+        long,
+        help = "Decode and check the files in a legacy encoding like windows-1252, instead of skipping them"
+    )]
+    transcode: bool,
 
-    let dictionary = SystemDictionary::new(lang)?;
-    let project = Project::new(&project_path)?;
-    let ignore_store = project.ignore_store()?;
+    #[clap(
+        long,
+        help = "Don't check the entries of gettext catalogs flagged as fuzzy"
+    )]
+    skip_fuzzy: bool,
 
-    run(project, &opts, dictionary, ignore_store)
+    #[clap(long, help = "Don't check the obsolete entries of gettext catalogs")]
+    skip_obsolete: bool,
+
+    #[clap(
+        long,
+        value_parser = parse_error_ratio,
+        help = "Don't report the errors of lines where the ratio of unknown words is above this (default: 0.8, 1 to check all lines)"
+    )]
+    max_line_error_ratio: Option<f64>,
+
+    #[clap(
+        long,
+        requires = "non_interactive",
+        help = "Only report the words that were not found in the same file by the previous run with --only-new"
+    )]
+    only_new: bool,
+
+    #[clap(
+        long,
+        requires = "only_new",
+        help = "Forget the words found by the previous run with --only-new, and report them all"
+    )]
+    reset_seen: bool,
+
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with = "modified",
+        help = "Also check the paths listed in FILE, one per line - use '-' to read them from stdin"
+    )]
+    paths_from: Option<PathBuf>,
+
+    #[clap(
+        short = '0',
+        long,
+        requires = "paths_from",
+        help = "Paths in the --paths-from list are separated by NUL characters instead of newlines"
+    )]
+    null: bool,
+
+    #[clap(
+        long,
+        requires = "non_interactive",
+        conflicts_with_all = ["paths", "modified", "paths_from"],
+        help = "Also list the entries of the ignore lists that did not cause any word to be ignored"
+    )]
+    check_unused_ignores: bool,
+
+    #[clap(
+        long,
+        requires = "check_unused_ignores",
+        help = "Remove the unused entries of the project ignore file"
+    )]
+    fix: bool,
+
+    #[clap(
+        long,
+        requires = "check_unused_ignores",
+        help = "Fail when some ignore entries are unused"
+    )]
+    strict_unused: bool,
+
+    #[clap(
+        long,
+        requires = "non_interactive",
+        help = "Also list the entries of the ignore lists that caused words to be ignored, with the number of words for each"
+    )]
+    report_ignored: bool,
+
+    #[clap(
+        long,
+        requires = "non_interactive",
+        value_delimiter = ',',
+        value_parser = parse_ignore_source,
+        value_name = "SCOPES",
+        help = "Only trust the ignore rules of these scopes, like 'project,path', and list the words that only other rules ignore, like the global list"
+    )]
+    strict_scopes: Vec<String>,
+
+    #[clap(
+        long,
+        requires = "strict_scopes",
+        help = "Fail when some words are only ignored by rules outside --strict-scopes"
+    )]
+    strict_fail: bool,
+
+    #[clap(
+        long,
+        requires = "non_interactive",
+        help = "Report errors with canonical absolute paths instead of paths relative to the project"
+    )]
+    absolute_paths: bool,
+
+    #[clap(help = "List of paths to check")]
+    paths: Vec<PathBuf>,
+}
+
+#[cfg(feature = "tui")]
+#[derive(Parser)]
+struct ReviewOpts {
+    #[clap(
+        long,
+        conflicts_with = "paths",
+        help = "Check the whole project, even if check_paths is set in the local config"
+    )]
+    all: bool,
+
+    #[clap(help = "List of paths to check")]
+    paths: Vec<PathBuf>,
+}
+
+#[derive(Parser)]
+struct PruneOpts {
+    #[clap(long, help = "Remove the unused entries of the project ignore file")]
+    fix: bool,
+}
+
+#[derive(Parser)]
+struct UndoOpts {
+    #[clap(
+        long,
+        help = "Discard the last operation from the history without changing the ignore lists - useful when they were edited by hand"
+    )]
+    force: bool,
+}
+
+const APPLY_OPS_HELP: &str = "\
+Apply the operations listed in a file, skipping the ones already applied
+
+The file contains operations in the same format as the history, for instance:
+
+  [[operations]]
+  Ignore = { word = \"foo\" }
+
+  [[operations]]
+  IgnoreForExtension = { word = \"kwargs\", extension = \"py\" }
+
+  [[operations]]
+  Skip = { pattern = \"Cargo.lock\" }
+
+  [[operations]]
+  Remove = { removed = [{ IgnoreForProject = { word = \"baz\" } }] }
+
+The operations that were applied are undone together by `skyspell undo`.
+Use `skyspell record --to` to write such a file";
+
+#[derive(Parser)]
+struct ApplyOpsOpts {
+    #[clap(help = "The file containing the operations")]
+    path: PathBuf,
+}
+
+#[derive(Parser)]
+struct RecordOpts {
+    #[clap(
+        long,
+        required_unless_present = "stop",
+        help = "The file to append operations to"
+    )]
+    to: Option<PathBuf>,
+
+    #[clap(long, conflicts_with = "to", help = "Stop recording")]
+    stop: bool,
+}
+
+#[derive(Parser)]
+struct HistoryOpts {
+    #[clap(long, help = "Only show operations for this word")]
+    word: Option<String>,
+
+    #[clap(long, help = "Maximum number of operations to show")]
+    limit: Option<usize>,
+}
+
+#[derive(Parser)]
+struct SuggestOpts {
+    #[clap(help = "The words to get suggestions for")]
+    words: Vec<String>,
+
+    #[clap(long, help = "Also read words from stdin, one per line")]
+    stdin: bool,
+
+    #[clap(
+        long,
+        help = "With --output-format json, print one JSON object per line instead of an array"
+    )]
+    stream: bool,
+
+    #[clap(
+        long,
+        help = "Words ignored for this file are known - implies --project"
+    )]
+    path: Option<PathBuf>,
+
+    #[clap(long, help = "Words ignored for the project are known")]
+    project: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, clap::ValueEnum, Clone, Copy)]
+enum TokensMode {
+    Default,
+    Latex,
+    Python,
+    Markdown,
+}
+
+impl TokensMode {
+    fn extract_mode(&self) -> ExtractMode {
+        match self {
+            // Markdown only changes how the front matter is skipped
+            TokensMode::Default | TokensMode::Markdown => ExtractMode::Default,
+            TokensMode::Latex => ExtractMode::Latex,
+            TokensMode::Python => ExtractMode::Python,
+        }
+    }
+}
+
+#[derive(Parser)]
+struct TokensOpts {
+    #[clap(
+        required_unless_present = "text",
+        conflicts_with = "text",
+        help = "The file to read"
+    )]
+    path: Option<PathBuf>,
+
+    #[clap(long, help = "Read the tokens from this string instead of a file")]
+    text: Option<String>,
+
+    #[clap(
+        long,
+        value_enum,
+        help = "How words are extracted - from the file extension by default"
+    )]
+    mode: Option<TokensMode>,
+}
+
+#[derive(Parser)]
+struct AddFromReportOpts {
+    #[clap(
+        required = true,
+        help = "Indexes of the errors, as shown by show-report"
+    )]
+    indexes: Vec<usize>,
+
+    #[clap(long, help = "Add words to the ignore list for the current project")]
+    project: bool,
+
+    #[clap(
+        long,
+        help = "Add each word to the ignore list for the extension of the file it was found in"
+    )]
+    extension: bool,
+
+    #[clap(
+        long,
+        help = "Add each word to the ignore list for the file it was found in"
+    )]
+    file: bool,
+
+    #[clap(
+        long,
+        help = "Add words to the ignore list for the given language - use with --project to only ignore them for the current project"
+    )]
+    lang: Option<String>,
+}
+
+#[derive(Parser)]
+struct FixPathsOpts {
+    #[clap(
+        long,
+        help = "Follow the renames recorded by git instead of asking for each missing file"
+    )]
+    from_git: bool,
+}
+
+/// The entries `skyspell import` takes from the other project. The global
+/// and extension lists are not in the project, so there is nothing to import
+#[derive(Debug, PartialEq, Eq, clap::ValueEnum, Clone, Copy)]
+enum ImportScope {
+    Project,
+    Lang,
+    Path,
+    Identifier,
+    Skip,
+}
+
+impl ImportScope {
+    fn of(operation: &Operation) -> Option<Self> {
+        use Operation::*;
+        match operation {
+            IgnoreForProject(_) => Some(Self::Project),
+            IgnoreForProjectAndLang(_) => Some(Self::Lang),
+            IgnoreForPath(_) => Some(Self::Path),
+            IgnoreIdentifierForProject(_) => Some(Self::Identifier),
+            Skip(_) => Some(Self::Skip),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Parser)]
+struct ImportOpts {
+    #[clap(long, help = "The root of the project to import from")]
+    from: PathBuf,
+
+    #[clap(
+        long,
+        value_delimiter = ',',
+        default_value = "project,lang,path,identifier",
+        help = "What to import - 'skip' imports the skip patterns"
+    )]
+    scopes: Vec<ImportScope>,
+}
+
+#[derive(Parser)]
+struct RegexOpts {
+    #[clap(help = "The regular expression, like '^tmpVar\\w+$'")]
+    pattern: String,
+
+    #[clap(long, help = "Only for the current project")]
+    project: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "project",
+        help = "Only for files with the given extension"
+    )]
+    extension: Option<String>,
+}
+
+#[derive(Parser)]
+struct IdentifierOpts {
+    #[clap(help = "The whole identifier, as it appears in the files")]
+    identifier: String,
+
+    #[clap(long, help = "Only for the current project")]
+    project: bool,
+}
+
+#[derive(Parser)]
+struct RemoveOpts {
+    #[clap(help = "The word to remove")]
+    word: String,
+
+    #[clap(
+        long,
+        help = "Remove word from the ignore list for the current project"
+    )]
+    project: bool,
+
+    #[clap(
+        long,
+        help = "Remove word from the ignore list for the given extension"
+    )]
+    extension: Option<String>,
+
+    #[clap(long, help = "Remove word from the ignore list for the given path")]
+    relative_path: Option<PathBuf>,
+
+    #[clap(long, help = "Remove word from the ignore list for the given language")]
+    lang: Option<String>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["project", "extension", "relative_path", "lang"],
+        help = "Remove word from all the ignore lists containing it"
+    )]
+    everywhere: bool,
+}
+
+#[derive(Parser)]
+struct SkipOpts {
+    #[clap(
+        required_unless_present_any = ["directory", "relative_path"],
+        conflicts_with_all = ["directory", "relative_path"],
+        help = "A pattern using the .gitignore syntax, like '*.lock'"
+    )]
+    pattern: Option<String>,
+
+    #[clap(
+        long,
+        conflicts_with = "relative_path",
+        help = "Every file in this directory of the project, and in its subdirectories"
+    )]
+    directory: Option<PathBuf>,
+
+    #[clap(long, help = "This file of the project only")]
+    relative_path: Option<PathBuf>,
+}
+
+fn get_operation(
+    project: &Project,
+    word: &str,
+    for_project: bool,
+    extension: &Option<String>,
+    relative_path: &Option<PathBuf>,
+    lang: &Option<String>,
+) -> Result<Operation> {
+    let operation = match (relative_path, extension, lang, for_project) {
+        (None, None, None, false) => Operation::new_ignore(word),
+        (None, Some(e), None, _) => Operation::new_ignore_for_extension(word, e),
+        (Some(relative_path), None, None, _) => {
+            let relative_path = project.get_relative_path(relative_path)?;
+            Operation::new_ignore_for_path(word, &relative_path)
+        }
+        (None, None, None, true) => Operation::new_ignore_for_project(word),
+        (None, None, Some(l), false) => Operation::new_ignore_for_lang(word, l),
+        (None, None, Some(l), true) => Operation::new_ignore_for_project_and_lang(word, l),
+        _ => {
+            bail!("Conflicting options: use at most one of --relative-path, --extension and --lang")
+        }
+    };
+    Ok(operation)
+}
+
+fn add(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &AddOpts,
+) -> Result<()> {
+    let mut operation = match &opts.category {
+        Some(category) => Operation::new_ignore_for_project_in_category(&opts.word, category),
+        None => get_operation(
+            &project,
+            &opts.word,
+            opts.project,
+            &opts.extension,
+            &opts.relative_path,
+            &opts.lang,
+        )?,
+    };
+    operation.execute(&mut ignore_store)?;
+    state.set_last_operation(operation, project.path())
+}
+
+fn add_regex(mut ignore_store: IgnoreStore, opts: &RegexOpts) -> Result<()> {
+    let RegexOpts {
+        pattern,
+        project,
+        extension,
+    } = opts;
+    if *project {
+        ignore_store.ignore_regex_for_project(pattern)
+    } else {
+        ignore_store.ignore_regex(pattern, extension.as_deref())
+    }
+}
+
+fn add_identifier(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &IdentifierOpts,
+) -> Result<()> {
+    let IdentifierOpts {
+        identifier,
+        project: for_project,
+    } = opts;
+    let mut operation = if *for_project {
+        Operation::new_ignore_identifier_for_project(identifier)
+    } else {
+        Operation::new_ignore_identifier(identifier)
+    };
+    operation.execute(&mut ignore_store)?;
+    state.set_last_operation(operation, project.path())
+}
+
+fn remove_regex(mut ignore_store: IgnoreStore, opts: &RegexOpts) -> Result<()> {
+    let RegexOpts {
+        pattern,
+        project,
+        extension,
+    } = opts;
+    if *project {
+        ignore_store.remove_regex_for_project(pattern)
+    } else {
+        ignore_store.remove_regex(pattern, extension.as_deref())
+    }
+}
+
+fn report_operation(entry: &ReportEntry, opts: &AddFromReportOpts) -> Result<Operation> {
+    let word = &entry.word;
+    let operation = match (opts.extension, opts.file, &opts.lang, opts.project) {
+        (false, false, None, false) => Operation::new_ignore(word),
+        (true, false, None, _) => match entry.extension() {
+            Some(e) => Operation::new_ignore_for_extension(word, e),
+            None => bail!("{} has no extension", entry.path),
+        },
+        (false, true, None, _) => {
+            let relative_path = RelativePath::from_path_unchecked(PathBuf::from(&entry.path));
+            Operation::new_ignore_for_path(word, &relative_path)
+        }
+        (false, false, None, true) => Operation::new_ignore_for_project(word),
+        (false, false, Some(l), false) => Operation::new_ignore_for_lang(word, l),
+        (false, false, Some(l), true) => Operation::new_ignore_for_project_and_lang(word, l),
+        _ => bail!("Conflicting options: use at most one of --extension, --file and --lang"),
+    };
+    Ok(operation)
+}
+
+fn add_from_report(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &AddFromReportOpts,
+) -> Result<()> {
+    let report = state.report(project.path())?;
+    let mut operations: Vec<Operation> = vec![];
+    for index in &opts.indexes {
+        let operation = report_operation(report.entry(*index)?, opts)?;
+        if !operation.is_applied(&ignore_store) && !operations.contains(&operation) {
+            operations.push(operation);
+        }
+    }
+    if operations.is_empty() {
+        info_2!("All the words were already ignored");
+        return Ok(());
+    }
+    let messages: Vec<_> = operations
+        .iter()
+        .map(|o| format!("Added '{}' to the {} ignore list", o.word(), o.scope()))
+        .collect();
+    // Recorded as a single operation, so that `skyspell undo` removes
+    // all the words at once
+    let mut operation = Operation::new_batch(operations);
+    operation.execute(&mut ignore_store)?;
+    for message in messages {
+        info_2!("{message}");
+    }
+    state.set_last_operation(operation, project.path())
+}
+
+fn fix_paths(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &FixPathsOpts,
+) -> Result<()> {
+    let stale = stale_paths(&project, &ignore_store);
+    if stale.is_empty() {
+        info_2!("All the files with ignored words exist");
+        return Ok(());
+    }
+    let retargets = if opts.from_git {
+        let renames = vcs::renamed_files(project.path().as_ref())?;
+        git_targets(&project, &stale, &renames)
+    } else {
+        let project_path = project.path();
+        let files: Vec<_> = collect_paths(&project)?
+            .iter()
+            .filter_map(|p| RelativePath::new(project_path, p).ok())
+            .map(|p| p.normalize())
+            .collect();
+        choose_targets(&ConsoleInteractor, &ignore_store, &stale, &files)
+    };
+    for path in &stale {
+        if opts.from_git && !retargets.iter().any(|r| &r.from == path) {
+            output::warning(&format!(
+                "{} {path} does not exist and was not renamed - words: {}",
+                "Warning:".yellow(),
+                describe_words(&ignore_store, path)
+            ));
+        }
+    }
+    if retargets.is_empty() {
+        return Ok(());
+    }
+    // Recorded as a single operation, so that `skyspell undo` moves
+    // all the words back at once
+    let mut operation = Operation::new_batch(retarget_operations(&ignore_store, &retargets));
+    operation.execute(&mut ignore_store)?;
+    for Retarget { from, to } in &retargets {
+        info_2!("Moved the words ignored for {from} to {to}");
+    }
+    state.set_last_operation(operation, project.path())
+}
+
+#[derive(Debug, Serialize)]
+struct ReportItem<'a> {
+    index: usize,
+    #[serde(flatten)]
+    entry: &'a ReportEntry,
+}
+
+fn show_report(project: Project, state: CheckerState, output_format: OutputFormat) -> Result<()> {
+    let report = state.report(project.path())?;
+    let items: Vec<_> = report
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| ReportItem {
+            index: i + 1,
+            entry,
+        })
+        .collect();
+    match output_format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string(&items).expect("report should be serializable");
+            println!("{json}");
+        }
+        OutputFormat::Text => {
+            for ReportItem { index, entry } in items {
+                let ReportEntry {
+                    word,
+                    path,
+                    line,
+                    column,
+                } = entry;
+                println!("{index:>4} {path}:{line}:{column} {word}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn remove(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &RemoveOpts,
+) -> Result<()> {
+    let word = &opts.word;
+    let removed = if opts.everywhere {
+        let operations = ignore_store.operations_for(word);
+        if operations.is_empty() {
+            bail!("'{word}' was not in any ignore list");
+        }
+        operations
+    } else {
+        let operation = get_operation(
+            &project,
+            word,
+            opts.project,
+            &opts.extension,
+            &opts.relative_path,
+            &opts.lang,
+        )?;
+        if !operation.is_applied(&ignore_store) {
+            bail!("'{word}' was not in the {} ignore list", operation.scope());
+        }
+        vec![operation]
+    };
+    let scopes: Vec<_> = removed.iter().map(|o| o.scope()).collect();
+    // Recorded as a single operation, so that `skyspell undo` adds
+    // the word back everywhere
+    let mut operation = Operation::new_remove(removed);
+    operation.execute(&mut ignore_store)?;
+    for scope in scopes {
+        println!("Removed '{word}' from the {scope} ignore list");
+    }
+    state.set_last_operation(operation, project.path())
+}
+
+fn skip_pattern(project: &Project, opts: &SkipOpts) -> Result<String> {
+    if let Some(pattern) = &opts.pattern {
+        return Ok(pattern.clone());
+    }
+    let (path, is_dir) = match (&opts.directory, &opts.relative_path) {
+        (Some(directory), _) => (directory, true),
+        (None, Some(relative_path)) => (relative_path, false),
+        (None, None) => bail!("Nothing to skip"),
+    };
+    if is_dir && !path.is_dir() {
+        bail!("{} is not a directory", path.display());
+    }
+    let relative_path = project.get_relative_path(path)?;
+    if relative_path.is_outside_project() {
+        bail!("{} is outside the project", path.display());
+    }
+    if is_dir {
+        Ok(directory_pattern(&relative_path))
+    } else {
+        Ok(format!("/{}", relative_path.normalize()))
+    }
+}
+
+fn import(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &ImportOpts,
+) -> Result<()> {
+    let local_path = opts.from.join(SKYSPELL_LOCAL_IGNORE);
+    if !local_path.exists() {
+        bail!("{} not found", local_path.display());
+    }
+    let entries = LocalIgnore::load(&local_path)?.entries();
+    let project_path = project.path().as_ref();
+    let mut operations: Vec<Operation> = vec![];
+    let mut num_duplicates = 0;
+    // Words ignored for files that do not exist in this project, by path
+    let mut dropped: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for operation in entries {
+        if !ImportScope::of(&operation).is_some_and(|s| opts.scopes.contains(&s)) {
+            continue;
+        }
+        if let Some(path) = operation.relative_path() {
+            if !project_path.join(path.as_ref()).exists() {
+                dropped
+                    .entry(path.normalize())
+                    .or_default()
+                    .push(operation.word().to_string());
+                continue;
+            }
+        }
+        if operation.is_applied(&ignore_store) || operations.contains(&operation) {
+            num_duplicates += 1;
+            continue;
+        }
+        operations.push(operation);
+    }
+    for (path, words) in &dropped {
+        output::warning(&format!(
+            "{} {path} does not exist in this project - words not imported: {}",
+            "Warning:".yellow(),
+            words.join(", ")
+        ));
+    }
+    match num_duplicates {
+        0 => (),
+        1 => info_2!("Skipped 1 entry already in this project"),
+        n => info_2!("Skipped {n} entries already in this project"),
+    }
+    if operations.is_empty() {
+        info_2!("Nothing to import from {}", local_path.display());
+        return Ok(());
+    }
+    let num_imported = operations.len();
+    // Recorded as a single operation, so that `skyspell undo` removes
+    // everything that was imported at once
+    let mut operation = Operation::new_batch(operations);
+    operation.execute(&mut ignore_store)?;
+    match num_imported {
+        1 => info_2!("Imported 1 entry from {}", local_path.display()),
+        n => info_2!("Imported {n} entries from {}", local_path.display()),
+    }
+    state.set_last_operation(operation, project.path())
+}
+
+fn skip(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &SkipOpts,
+) -> Result<()> {
+    let pattern = skip_pattern(&project, opts)?;
+    let mut operation = Operation::new_skip(&pattern);
+    if operation.is_applied(&ignore_store) {
+        bail!("'{pattern}' is already a skip pattern");
+    }
+    operation.execute(&mut ignore_store)?;
+    output::print(&format!("Files matching '{pattern}' will be skipped"));
+    state.set_last_operation(operation, project.path())
+}
+
+fn unskip(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &SkipOpts,
+) -> Result<()> {
+    let pattern = skip_pattern(&project, opts)?;
+    let skipped = Operation::new_skip(&pattern);
+    if !skipped.is_applied(&ignore_store) {
+        bail!("'{pattern}' is not a skip pattern");
+    }
+    let mut operation = Operation::new_remove(vec![skipped]);
+    operation.execute(&mut ignore_store)?;
+    output::print(&format!("Files matching '{pattern}' will be checked again"));
+    state.set_last_operation(operation, project.path())
+}
+
+/// Where `check` gets its dictionaries from, see run()
+struct Dictionaries<'a, D, F> {
+    open: &'a F,
+    lang_opener: Option<LangOpener<D>>,
+    suggestions: Option<SuggestionWorker>,
+}
+
+fn check<D, F>(
+    mut project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    dictionaries: Dictionaries<'_, D, F>,
+    opts: &CheckOpts,
+    output_format: OutputFormat,
+) -> Result<()>
+where
+    D: Dictionary + 'static,
+    F: Fn() -> Result<D> + Sync,
+{
+    let Dictionaries {
+        open: open_dictionary,
+        lang_opener,
+        suggestions,
+    } = dictionaries;
+    let frontend = opts.frontend.unwrap_or_default();
+    let mut interactive = !opts.non_interactive;
+    // The console frontend would wait forever for answers that never come
+    if interactive && frontend == Frontend::Console && !opts.force_interactive {
+        if let Some(stream) = output::non_terminal_stream() {
+            output::warning(&format!(
+                "{} {stream} is not a terminal; running in non-interactive mode, pass --non-interactive to silence this",
+                "Warning:".yellow()
+            ));
+            interactive = false;
+        }
+    }
+    for extra_root in &opts.also {
+        project.add_extra_root(extra_root)?;
+    }
+    if let Some(max_file_size) = opts.max_file_size {
+        project.set_max_file_size(max_file_size);
+    }
+    if !opts.extensions.is_empty() {
+        project.set_extensions(&opts.extensions);
+    }
+    if let Some(min_length) = opts.accept_acronyms {
+        ignore_store.set_accept_acronyms(AcceptAcronyms::MinLength(min_length));
+    }
+    if opts.ignore_capitalized {
+        ignore_store.set_ignore_capitalized(true);
+    }
+    for name in &opts.builtin_lists {
+        ignore_store.enable_builtin_list(name)?;
+    }
+    if opts.strict_utf8 {
+        ignore_store.set_strict_utf8(true);
+    }
+    if opts.transcode {
+        ignore_store.set_transcode(true);
+    }
+    if opts.skip_fuzzy || opts.skip_obsolete {
+        let mut po_options = ignore_store.po_options();
+        po_options.skip_fuzzy |= opts.skip_fuzzy;
+        po_options.skip_obsolete |= opts.skip_obsolete;
+        ignore_store.set_po_options(po_options);
+    }
+    if let Some(ratio) = opts.max_line_error_ratio {
+        ignore_store.set_max_line_error_ratio(ratio);
+    }
+    if opts.check_unused_ignores || opts.report_ignored {
+        ignore_store.set_record_uses(true);
+    }
+    if !opts.strict_scopes.is_empty() {
+        ignore_store.set_strict_scopes(opts.strict_scopes.clone());
+    }
+
+    if !interactive && frontend != Frontend::Console {
+        bail!("--frontend can only be used in interactive mode");
+    }
+
+    if let Some(output) = &opts.output {
+        check_output_path(output, output_format)?;
+    }
+
+    let dictionary = open_dictionary()?;
+    match interactive {
+        false => {
+            // The cache lives beside the state, so --no-state also disables it
+            let cache = match (opts.no_persistent_cache, state.data_dir()) {
+                (false, Some(data_dir)) => {
+                    DictionaryCache::load(data_dir, dictionary.provider(), dictionary.lang())
+                }
+                _ => DictionaryCache::in_memory(dictionary.provider(), dictionary.lang()),
+            };
+            let cache = Arc::new(cache);
+            let dictionary = PersistentCachedDictionary::new(dictionary, cache.clone());
+            let open_dictionary = || {
+                Ok(PersistentCachedDictionary::new(
+                    open_dictionary()?,
+                    cache.clone(),
+                ))
+            };
+            let mut checker =
+                NonInteractiveChecker::new(project, dictionary, ignore_store, output_format)?;
+            if let Some(open) = lang_opener {
+                // Only the dictionary for the main language is worth caching
+                // between runs
+                checker.set_lang_opener(Arc::new(move |lang: &str| {
+                    let dictionary = open(lang)?;
+                    let cache =
+                        DictionaryCache::in_memory(dictionary.provider(), dictionary.lang());
+                    Ok(PersistentCachedDictionary::new(dictionary, Arc::new(cache)))
+                }));
+            }
+            checker.set_sort_by(opts.sort_by.unwrap_or_default());
+            if let Some(output) = &opts.output {
+                checker.set_output_path(output.clone());
+            }
+            checker.set_check_confusables(opts.check_confusables);
+            checker.set_strict_fail(opts.strict_fail);
+            checker.set_absolute_paths(opts.absolute_paths);
+            if let Some(n) = opts.min_occurrences_to_accept {
+                checker.set_min_occurrences_to_accept(n.get());
+            }
+            if opts.only_new {
+                let seen = match opts.reset_seen {
+                    true => None,
+                    false => state.seen_findings(checker.project().path()),
+                };
+                checker.set_seen_findings(seen.cloned().unwrap_or_default());
+            }
+            let keep_going = !opts.fail_fast;
+            let jobs = opts.jobs.map_or_else(default_jobs, |n| n.get());
+            let failures = if jobs > 1 {
+                check_in_parallel(&mut checker, &open_dictionary, opts, output_format, jobs)?
+            } else {
+                check_with(&mut checker, opts, output_format, keep_going)?
+            };
+            // Not being able to save the cache only makes the next run slower
+            if let Err(e) = cache.save() {
+                output::warning(&format!("{} {e:#}", "Warning:".yellow()));
+            }
+            checker.set_failures(failures);
+            let findings = checker.hide_seen_findings();
+            if opts.only_new {
+                state.save_seen_findings(checker.project().path(), findings)?;
+            }
+            checker.split_frequent_words();
+            if opts.check_unused_ignores {
+                // Words of the files that could not be checked may need the
+                // entries that look unused
+                let fix = opts.fix && !checker.has_failures();
+                if opts.fix && !fix {
+                    output::warning(&format!(
+                        "{} some files could not be checked, not removing unused ignore entries",
+                        "Warning:".yellow()
+                    ));
+                }
+                let project_path = checker.project().path().clone();
+                let unused =
+                    unused_ignores(checker.ignore_store(), &mut state, &project_path, fix)?;
+                checker.set_unused_ignores(unused, opts.strict_unused);
+            }
+            if opts.report_ignored {
+                let hits = ignore_hits(checker.ignore_store());
+                checker.set_ignore_hits(hits);
+            }
+            if opts.save_report {
+                let entries = checker.report_entries();
+                let count = entries.len();
+                state.save_report(checker.project().path(), entries)?;
+                if output_format.is_text() && count > 0 {
+                    info_3!("Saved {count} errors - use `skyspell show-report` to see them");
+                }
+            }
+            checker.success()
+        }
+        true => match frontend {
+            Frontend::Console => {
+                let interactor = ConsoleInteractor;
+                let mut checker =
+                    InteractiveChecker::new(project, interactor, dictionary, ignore_store, state)?;
+                if let Some(worker) = suggestions {
+                    checker.set_suggestion_worker(worker);
+                }
+                if let Some(opener) = lang_opener {
+                    checker.set_lang_opener(opener);
+                }
+                checker.set_check_confusables(opts.check_confusables);
+                check_interactively(&mut checker, opts, output_format)?;
+                checker.success()
+            }
+            Frontend::Json => {
+                let interactor = JsonInteractor::new(std::io::stdin().lock(), std::io::stdout());
+                let mut checker =
+                    InteractiveChecker::new(project, interactor, dictionary, ignore_store, state)?;
+                if let Some(worker) = suggestions {
+                    checker.set_suggestion_worker(worker);
+                }
+                if let Some(opener) = lang_opener {
+                    checker.set_lang_opener(opener);
+                }
+                checker.set_check_confusables(opts.check_confusables);
+                // Only events should be written on stdout
+                check_interactively(&mut checker, opts, OutputFormat::Json)?;
+                checker.success()
+            }
+        },
+    }
+}
+
+/// The entries of the ignore lists that did not cause any word to be
+/// ignored since the store was loaded. With `fix`, the ones of the local
+/// ignore file are removed in a single operation, so that `skyspell undo`
+/// adds them back - the global ones may be used by other projects
+fn unused_ignores(
+    ignore_store: &mut IgnoreStore,
+    state: &mut CheckerState,
+    project_path: &ProjectPath,
+    fix: bool,
+) -> Result<Vec<UnusedIgnore>> {
+    let unused = ignore_store.unused_entries();
+    let removed: Vec<_> = match fix {
+        true => unused.iter().filter(|o| !o.is_global()).cloned().collect(),
+        false => vec![],
+    };
+    if !removed.is_empty() {
+        let mut operation = Operation::new_remove(removed.clone());
+        operation.execute(ignore_store)?;
+        state.set_last_operation(operation, project_path)?;
+    }
+    Ok(unused
+        .into_iter()
+        .map(|o| UnusedIgnore {
+            word: o.word().to_string(),
+            scope: o.scope(),
+            removed: removed.contains(&o),
+        })
+        .collect())
+}
+
+// The entries that caused words to be ignored, the most used first
+fn ignore_hits(ignore_store: &IgnoreStore) -> Vec<IgnoreHit> {
+    let used = ignore_store.used_entries();
+    let mut hits: Vec<_> = used
+        .hits()
+        .map(|(scope, word, hits)| IgnoreHit {
+            word: word.to_string(),
+            scope: scope.to_string(),
+            hits,
+        })
+        .collect();
+    hits.sort_by_key(|h| std::cmp::Reverse(h.hits));
+    hits
+}
+
+// Like `check --non-interactive --check-unused-ignores`, without
+// reporting the spelling errors
+fn prune<D: Dictionary>(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    dictionary: D,
+    mut state: CheckerState,
+    opts: &PruneOpts,
+    output_format: OutputFormat,
+) -> Result<()> {
+    let project_path = project.path().clone();
+    let paths = collect_default_paths(&project, false)?;
+    ignore_store.set_record_uses(true);
+    let mut checker = NonInteractiveChecker::new_worker(project, dictionary, ignore_store, false);
+    // Every file must be checked for the list to be right
+    for path in paths {
+        process_file(&mut checker, &path, false)?;
+    }
+    let unused = unused_ignores(checker.ignore_store(), &mut state, &project_path, opts.fix)?;
+    match output_format {
+        OutputFormat::Text => print_unused_ignores(&unused),
+        OutputFormat::Json => {
+            let json = serde_json::to_string(&unused).expect("entries should be serializable");
+            println!("{json}");
+        }
+    }
+    Ok(())
+}
+
+// Fail before checking anything rather than after
+fn check_output_path(output: &Path, output_format: OutputFormat) -> Result<()> {
+    if output_format.is_text() {
+        bail!("--output can only be used with --output-format json");
+    }
+    let parent = match output.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    if !parent.is_dir() {
+        bail!(
+            "Cannot write results to {}: {} is not a directory",
+            output.display(),
+            parent.display()
+        );
+    }
+    Ok(())
+}
+
+/// A file that could not be checked
+#[derive(Debug, Serialize)]
+pub(crate) struct FileFailure {
+    path: String,
+    message: String,
+}
+
+/// Returned when some files could not be checked and no spelling error
+/// was found, so that main() can tell it apart from spelling errors
+#[derive(Debug)]
+pub struct CheckFailures(pub usize);
+
+impl std::fmt::Display for CheckFailures {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            1 => write!(f, "Could not check 1 file"),
+            n => write!(f, "Could not check {n} files"),
+        }
+    }
+}
+
+impl std::error::Error for CheckFailures {}
+
+pub const TOOL_FAILURE_EXIT_CODE: i32 = 2;
+
+/// Used when an ignore file or the project cannot be read
+pub const CONFIG_ERROR_EXIT_CODE: i32 = 3;
+
+/// Used when the dictionary cannot be opened
+pub const DICTIONARY_ERROR_EXIT_CODE: i32 = 4;
+
+/// The exit code for an error returned by main(), or None for the
+/// default one - also used for spelling errors
+pub fn exit_code(error: &anyhow::Error) -> Option<i32> {
+    if error.is::<CheckFailures>() {
+        return Some(TOOL_FAILURE_EXIT_CODE);
+    }
+    match error.downcast_ref()? {
+        SkyspellError::IgnoreStoreIo { .. }
+        | SkyspellError::IgnoreStoreParse { .. }
+        | SkyspellError::ProjectNotFound { .. } => Some(CONFIG_ERROR_EXIT_CODE),
+        SkyspellError::DictionaryUnavailable { .. } => Some(DICTIONARY_ERROR_EXIT_CODE),
+        SkyspellError::NotIgnored { .. }
+        | SkyspellError::LimitReached { .. }
+        | SkyspellError::OperationConflict(_) => None,
+    }
+}
+
+/// The files to check, see paths_to_check()
+pub(crate) struct PathsToCheck {
+    paths: Vec<PathBuf>,
+    /// True when the paths were given on the command line or with --paths-from
+    explicit: bool,
+    /// Paths listed with --paths-from that do not exist
+    missing: Vec<PathBuf>,
+}
+
+/// The files to check: the ones given on the command line or listed in
+/// the --paths-from file, the modified ones, or the default ones
+fn paths_to_check(project: &Project, opts: &CheckOpts) -> Result<PathsToCheck> {
+    let explicit = !opts.paths.is_empty() || opts.paths_from.is_some();
+    let mut missing = vec![];
+    let paths = if opts.modified {
+        let project_path = project.path().as_ref();
+        vcs::modified_files(project_path)?
+            .into_iter()
+            .filter(|p| p.starts_with(project_path))
+            .collect()
+    } else if explicit {
+        let mut paths = opts.paths.to_vec();
+        if let Some(list) = &opts.paths_from {
+            for path in read_paths_from(list, opts.null)? {
+                match path.exists() {
+                    true => paths.push(path),
+                    false => missing.push(path),
+                }
+            }
+        }
+        if !opts.allow_outside_project {
+            for path in &paths {
+                ensure_in_project(project, path)?;
+            }
+        }
+        dedup_paths(paths)
+    } else {
+        collect_default_paths(project, opts.all)?
+    };
+    Ok(PathsToCheck {
+        paths,
+        explicit,
+        missing,
+    })
+}
+
+// Read the paths listed in `list`, or in stdin if it is '-'. Empty
+// entries are ignored
+fn read_paths_from(list: &Path, null_separated: bool) -> Result<Vec<PathBuf>> {
+    let contents = if list == Path::new("-") {
+        let mut contents = vec![];
+        std::io::stdin()
+            .read_to_end(&mut contents)
+            .context("Could not read the list of paths from stdin")?;
+        contents
+    } else {
+        std::fs::read(list)
+            .with_context(|| format!("Could not read the list of paths from {}", list.display()))?
+    };
+    parse_paths_list(&contents, null_separated)
+        .with_context(|| format!("While reading the list of paths from {}", list.display()))
+}
+
+fn parse_error_ratio(text: &str) -> Result<f64> {
+    let ratio: f64 = text
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("Could not parse '{text}' as a ratio"))?;
+    if !(0.0..=1.0).contains(&ratio) {
+        bail!("Ratio should be between 0 and 1, got '{text}'");
+    }
+    Ok(ratio)
+}
+
+fn parse_ignore_source(text: &str) -> Result<String> {
+    if !IGNORE_SOURCES.contains(&text) {
+        bail!(
+            "Unknown scope '{text}', expected one of: {}",
+            IGNORE_SOURCES.join(", ")
+        );
+    }
+    Ok(text.to_string())
+}
+
+fn parse_paths_list(contents: &[u8], null_separated: bool) -> Result<Vec<PathBuf>> {
+    let separator = if null_separated { b'\0' } else { b'\n' };
+    let mut res = vec![];
+    for entry in contents.split(|b| *b == separator) {
+        let entry = match null_separated {
+            true => entry,
+            // Response files written on Windows
+            false => entry.strip_suffix(b"\r").unwrap_or(entry),
+        };
+        if entry.is_empty() {
+            continue;
+        }
+        let entry = std::str::from_utf8(entry)
+            .with_context(|| format!("Path is not valid UTF-8: {}", entry.escape_ascii()))?;
+        res.push(PathBuf::from(entry));
+    }
+    Ok(res)
+}
+
+// Keep the first occurrence of each file, however it is spelled
+fn dedup_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|p| seen.insert(std::fs::canonicalize(p).unwrap_or_else(|_| p.clone())))
+        .collect()
+}
+
+// Missing files are reported like the other files that cannot be checked
+fn ensure_in_project(project: &Project, path: &Path) -> Result<()> {
+    if let Ok(false) = project.contains(path) {
+        bail!(
+            "{} is outside the project ({}) - use --allow-outside-project to check it anyway",
+            path.display(),
+            project.path()
+        );
+    }
+    Ok(())
+}
+
+/// What happened to a file during the check
+pub(crate) enum FileOutcome {
+    Processed(ProcessOutcome),
+    Failed(FileFailure),
+}
+
+// When `keep_going` is set, errors are returned as a failure for
+// this file. Panics are not caught: the checker could not be trusted
+// after one
+fn process_file<C, D>(checker: &mut C, path: &Path, keep_going: bool) -> Result<FileOutcome>
+where
+    C: Checker<D, SourceContext = ()>,
+    D: Dictionary,
+{
+    match checker.process(path, &()) {
+        Ok(outcome) => Ok(FileOutcome::Processed(outcome)),
+        Err(e) if !keep_going => Err(e),
+        Err(e) => Ok(FileOutcome::Failed(FileFailure {
+            path: checker
+                .to_relative_path(path)
+                .map(|p| p.normalize())
+                .unwrap_or_else(|_| path.display().to_string()),
+            message: format!("{e:#}"),
+        })),
+    }
+}
+
+/// Counts the checked files, and tells the user about the ones
+/// that were not
+struct Tally {
+    explicit_paths: bool,
+    missing: Vec<PathBuf>,
+    output_format: OutputFormat,
+    checked: usize,
+    skipped: usize,
+    failures: Vec<FileFailure>,
+}
+
+impl Tally {
+    fn new(paths_to_check: &PathsToCheck, output_format: OutputFormat) -> Self {
+        Self {
+            explicit_paths: paths_to_check.explicit,
+            missing: paths_to_check.missing.clone(),
+            output_format,
+            checked: 0,
+            skipped: 0,
+            failures: vec![],
+        }
+    }
+
+    fn record(&mut self, project: &Project, path: &Path, outcome: FileOutcome) -> Result<()> {
+        match outcome {
+            FileOutcome::Failed(failure) => {
+                if self.output_format.is_text() {
+                    print_error!("Could not check {}: {}", failure.path, failure.message);
+                }
+                self.failures.push(failure);
+            }
+            FileOutcome::Processed(ProcessOutcome::Skipped(reason)) => {
+                self.skipped += 1;
+                let relative_path = project.get_relative_path(path)?;
+                if self.explicit_paths && reason == SkipReason::TooLarge {
+                    print_too_large(project, &relative_path);
+                } else if reason == SkipReason::TooLarge {
+                    output::verbose(&format!(
+                        "Skipped: {relative_path} (larger than {} bytes)",
+                        project.max_file_size()
+                    ));
+                } else if self.explicit_paths && reason == SkipReason::Encoding {
+                    output::warning(&format!(
+                        "{} {relative_path} is not in UTF-8 and was skipped - use --transcode to check it",
+                        "Warning:".yellow()
+                    ));
+                } else {
+                    output::verbose(&format!(
+                        "Skipped: {relative_path} ({})",
+                        skip_reason_description(reason)
+                    ));
+                }
+            }
+            FileOutcome::Processed(ProcessOutcome::Checked) => {
+                self.checked += 1;
+                output::debug(&format!("Checked: {}", path.display()));
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Vec<FileFailure> {
+        let Tally {
+            checked,
+            skipped,
+            failures,
+            missing,
+            ..
+        } = self;
+        // Printed on stderr, so that the JSON output is not affected
+        for path in &missing {
+            output::warning(&format!(
+                "{} {} does not exist",
+                "Warning:".yellow(),
+                path.display()
+            ));
+        }
+        match missing.len() {
+            0 => (),
+            1 => output::warning(&format!(
+                "{} 1 listed path does not exist",
+                "Warning:".yellow()
+            )),
+            n => output::warning(&format!(
+                "{} {n} listed paths do not exist",
+                "Warning:".yellow()
+            )),
+        }
+        if self.output_format.is_text() {
+            match failures.len() {
+                0 => info_3!("Checked {checked} files - {skipped} skipped"),
+                n => info_3!("Checked {checked} files - {skipped} skipped - {n} failed"),
+            }
+        }
+        failures
+    }
+}
+
+// Note: in interactive mode, quitting is reported as an error, so
+// `keep_going` must be false
+fn check_with<C, D>(
+    checker: &mut C,
+    opts: &CheckOpts,
+    output_format: OutputFormat,
+    keep_going: bool,
+) -> Result<Vec<FileFailure>>
+where
+    C: Checker<D, SourceContext = ()>,
+    D: Dictionary,
+{
+    let paths_to_check = paths_to_check(checker.project(), opts)?;
+    assume_extension(checker, &paths_to_check, opts)?;
+    check_paths(checker, paths_to_check, opts, output_format, keep_going)
+}
+
+// Like check_with(), but tell the interactive checker how many files
+// use each extension first
+fn check_interactively<I, D>(
+    checker: &mut InteractiveChecker<I, D>,
+    opts: &CheckOpts,
+    output_format: OutputFormat,
+) -> Result<Vec<FileFailure>>
+where
+    I: Interactor,
+    D: Dictionary,
+{
+    let paths_to_check = paths_to_check(checker.project(), opts)?;
+    assume_extension(checker, &paths_to_check, opts)?;
+    checker.set_extension_counts(extension_counts(&paths_to_check.paths));
+    checker.set_total_files(paths_to_check.paths.len());
+    check_paths(checker, paths_to_check, opts, output_format, false)
+}
+
+/// With --assume-extension, check the explicit paths as if they had the
+/// given extension - the files found by walking the project keep theirs
+pub(crate) fn assume_extension<C, D>(
+    checker: &mut C,
+    paths_to_check: &PathsToCheck,
+    opts: &CheckOpts,
+) -> Result<()>
+where
+    C: Checker<D>,
+    D: Dictionary,
+{
+    let extension = match &opts.assume_extension {
+        Some(e) => e,
+        None => return Ok(()),
+    };
+    if !paths_to_check.explicit {
+        bail!("--assume-extension only applies to paths given on the command line or with --paths-from");
+    }
+    for path in &paths_to_check.paths {
+        let relative_path = checker.to_relative_path(path)?;
+        checker
+            .ignore_store()
+            .set_assumed_extension(&relative_path, extension);
+    }
+    Ok(())
+}
+
+fn extension_counts(paths: &[PathBuf]) -> HashMap<String, usize> {
+    let mut res = HashMap::new();
+    for extension in paths.iter().filter_map(|p| p.extension()) {
+        *res.entry(extension.to_string_lossy().to_string())
+            .or_insert(0) += 1;
+    }
+    res
+}
+
+fn check_paths<C, D>(
+    checker: &mut C,
+    paths_to_check: PathsToCheck,
+    opts: &CheckOpts,
+    output_format: OutputFormat,
+    keep_going: bool,
+) -> Result<Vec<FileFailure>>
+where
+    C: Checker<D, SourceContext = ()>,
+    D: Dictionary,
+{
+    if opts.check_filenames {
+        checker.process_file_names(&paths_to_check.paths, &())?;
+    }
+    let mut tally = Tally::new(&paths_to_check, output_format);
+    for path in paths_to_check.paths {
+        let outcome = process_file(checker, &path, keep_going)?;
+        tally.record(checker.project(), &path, outcome)?;
+    }
+    let checked = tally.checked;
+    let failures = tally.finish();
+
+    if opts.stats {
+        print_stats(checked, checker.ignore_store().stats());
+    }
+
+    Ok(failures)
+}
+
+/// Return every file in the project, sorted by their path relative
+/// to the project, so that the output does not depend on the file system
+fn collect_paths(project: &Project) -> Result<Vec<PathBuf>> {
+    collect_paths_from(project, &[project.path().as_ref().to_path_buf()])
+}
+
+// The `check_paths` of the local config if any, unless `all` is set. The
+// ones that do not exist are skipped with a warning
+fn collect_default_paths(project: &Project, all: bool) -> Result<Vec<PathBuf>> {
+    let check_paths = project.check_paths();
+    if all || check_paths.is_empty() {
+        return collect_paths(project);
+    }
+    let mut roots = vec![];
+    for path in check_paths {
+        if path.exists() {
+            roots.push(path.clone());
+        } else {
+            output::warning(&format!(
+                "{} {} is listed in check_paths but does not exist",
+                "Warning:".yellow(),
+                path.strip_prefix(project.path()).unwrap_or(path).display()
+            ));
+        }
+    }
+    collect_paths_from(project, &roots)
+}
+
+// The files in `roots` and in the extra roots of the project
+fn collect_paths_from(project: &Project, roots: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    let walker = match project.walk_from(roots) {
+        Some(walker) => walker,
+        None => return Ok(paths),
+    };
+    for dir_entry in walker {
+        let dir_entry = dir_entry?;
+        let file_type = dir_entry.file_type().expect("walker yielded stdin");
+        if !file_type.is_file() {
+            continue;
+        }
+        let path = dir_entry.path();
+        paths.push(path.to_path_buf());
+    }
+    let project_path = project.path().as_ref();
+    paths.sort_by(|a, b| {
+        let a = a.strip_prefix(project_path).unwrap_or(a);
+        let b = b.strip_prefix(project_path).unwrap_or(b);
+        a.cmp(b)
+    });
+    Ok(paths)
+}
+
+// Printed on stderr so that it does not interfere with JSON output
+fn print_stats(checked: usize, stats: IgnoreStats) {
+    let IgnoreStats {
+        words,
+        not_ignored,
+        global,
+        project_and_lang,
+        lang,
+        extension,
+        project,
+        path,
+        extended,
+        regex,
+    } = stats;
+    output::note(&format!(
+        "Checked {checked} files: {words} lookups of unknown words, {not_ignored} of them not ignored"
+    ));
+    output::note("Ignore store lookups:");
+    output::note(&format!("  global: {global}"));
+    output::note(&format!("  project and lang: {project_and_lang}"));
+    output::note(&format!("  lang: {lang}"));
+    output::note(&format!("  extension: {extension}"));
+    output::note(&format!("  project: {project}"));
+    output::note(&format!("  path: {path}"));
+    output::note(&format!("  extended: {extended}"));
+    output::note(&format!("  regex: {regex}"));
+}
+
+fn print_too_large(project: &Project, relative_path: &RelativePath) {
+    let max_file_size = project.max_file_size();
+    output::warning(&format!(
+        "{} {relative_path} is larger than {max_file_size} bytes and was skipped - use --max-file-size to change the limit",
+        "Warning:".yellow()
+    ));
+}
+
+fn skip_reason_description(reason: SkipReason) -> &'static str {
+    match reason {
+        SkipReason::SkipFile => "matches a skip pattern",
+        SkipReason::TooLarge => "too large",
+        SkipReason::Extension => "extension not checked",
+        SkipReason::Encoding => "not in UTF-8 - use --transcode to check it",
+    }
+}
+
+fn undo(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &UndoOpts,
+) -> Result<()> {
+    if !state.is_persistent() {
+        bail!("Nothing to undo: there is no persistent state with --no-state, so the operations of previous runs are not known");
+    }
+    if opts.force {
+        let operation = match state.discard_last_operation(project.path())? {
+            None => bail!("Nothing to undo"),
+            Some(o) => o,
+        };
+        println!(
+            "Discarded operation on '{}' for the {} ignore list - ignore lists were not changed",
+            operation.word(),
+            operation.scope()
+        );
+        return Ok(());
+    }
+    let interactor = ConsoleInteractor;
+    match undo_last_operation(&project, &interactor, &mut ignore_store, &mut state) {
+        Err(e) if matches!(e.downcast_ref(), Some(SkyspellError::OperationConflict(_))) => {
+            bail!("{e}\nUse `skyspell undo --force` to discard it from the history")
+        }
+        res => res,
+    }
+}
+
+fn apply_ops(
+    project: Project,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    opts: &ApplyOpsOpts,
+) -> Result<()> {
+    let operations = load_ops_file(&opts.path)?;
+    let mut applied: Vec<Operation> = vec![];
+    for mut operation in operations {
+        let word = operation.word().to_string();
+        let scope = operation.scope();
+        if operation.is_applied(&ignore_store) {
+            match operation.is_removal() {
+                true => println!("Already done: '{word}' is not in the {scope} ignore list"),
+                false => println!("Already done: '{word}' is in the {scope} ignore list"),
+            }
+            continue;
+        }
+        if let Err(e) = operation.execute(&mut ignore_store) {
+            // Leave the ignore lists as they were
+            for operation in applied.iter_mut().rev() {
+                operation.undo(&mut ignore_store)?;
+            }
+            return Err(e.context("Could not apply operations, the ignore lists were not changed"));
+        }
+        match operation.is_removal() {
+            true => info_2!("Removed '{word}' from the {scope} ignore list"),
+            false => info_2!("Added '{word}' to the {scope} ignore list"),
+        }
+        applied.push(operation);
+    }
+    if applied.is_empty() {
+        return Ok(());
+    }
+    // Recorded as a single operation, so that `skyspell undo` reverts
+    // the whole file at once
+    let operation = Operation::new_batch(applied);
+    state.set_last_operation_from(operation, project.path(), &opts.path)
+}
+
+fn record(project: Project, mut state: CheckerState, opts: &RecordOpts) -> Result<()> {
+    if !state.is_persistent() {
+        bail!("Can't record operations with --no-state: there is no persistent state to remember the ops file");
+    }
+    let path = match &opts.to {
+        Some(p) => p,
+        None => {
+            match state.stop_recording(project.path())? {
+                Some(path) => println!("Stopped recording to {}", path.display()),
+                None => println!("Not recording"),
+            }
+            return Ok(());
+        }
+    };
+    // Store an absolute path, so that recording works from any directory
+    if !path.exists() {
+        std::fs::write(path, "").with_context(|| format!("Could not create {}", path.display()))?;
+    }
+    let path = std::fs::canonicalize(path)
+        .with_context(|| format!("Could not canonicalize {}", path.display()))?;
+    state.start_recording(project.path(), &path)?;
+    info_2!("Recording operations to {}", path.display());
+    Ok(())
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum HistoryKind {
+    Add,
+    Remove,
+    Skip,
+}
+
+impl HistoryKind {
+    fn of(operation: &Operation) -> Self {
+        if operation.is_removal() {
+            Self::Remove
+        } else if operation.is_skip() {
+            Self::Skip
+        } else {
+            Self::Add
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct HistoryItem {
+    word: String,
+    scope: String,
+    kind: HistoryKind,
+    date: Option<String>,
+    undone: bool,
+}
+
+fn history(state: CheckerState, opts: &HistoryOpts, output_format: OutputFormat) -> Result<()> {
+    let entries = state.history().filter(|e| match &opts.word {
+        Some(w) => e.operation.word() == w,
+        None => true,
+    });
+    let limit = opts.limit.unwrap_or(usize::MAX);
+    let items: Vec<_> = entries
+        .take(limit)
+        .map(|e| HistoryItem {
+            word: e.operation.word().to_string(),
+            scope: e.operation.scope(),
+            kind: HistoryKind::of(&e.operation),
+            date: e.date(),
+            undone: e.undone,
+        })
+        .collect();
+
+    match output_format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string(&items).expect("history should be serializable");
+            output::print(&json);
+        }
+        OutputFormat::Text => {
+            for item in items {
+                let HistoryItem {
+                    word,
+                    scope,
+                    kind,
+                    date,
+                    undone,
+                } = item;
+                let date = date.unwrap_or_else(|| "unknown date".to_string());
+                let undone = if undone { " (undone)" } else { "" };
+                let description = match kind {
+                    HistoryKind::Add => format!("added '{word}' to the {scope} ignore list"),
+                    HistoryKind::Remove => {
+                        format!("removed '{word}' from the {scope} ignore list")
+                    }
+                    HistoryKind::Skip => format!("skipped files matching '{word}'"),
+                };
+                output::print(&format!("{date} {description}{undone}"));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct WordSuggestions {
+    word: String,
+    known: bool,
+    // Set when the word is known because of the ignore store
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    suggestions: Vec<String>,
+}
+
+/// The file or the project the words come from, for `suggest --path`
+/// and `suggest --project`
+struct SuggestContext<'a> {
+    ignore_store: &'a IgnoreStore,
+    relative_path: RelativePath,
+}
+
+fn word_suggestions(
+    dictionary: &impl Dictionary,
+    word: &str,
+    context: Option<&SuggestContext>,
+) -> Result<WordSuggestions> {
+    let (known, reason) = match context {
+        None => (dictionary.check(word)?, None),
+        Some(context) => {
+            let classifier = TokenClassifier::new(dictionary, context.ignore_store);
+            match classifier.classify(word, &context.relative_path)? {
+                Classification::Known => (true, None),
+                Classification::Ignored(reason) | Classification::Suppressed(reason) => {
+                    (true, Some(reason.key()))
+                }
+                Classification::Skipped | Classification::Error => (false, None),
+            }
+        }
+    };
+    let suggestions = if known {
+        vec![]
+    } else {
+        let forbidden = context
+            .map(|c| c.ignore_store.forbidden_words())
+            .unwrap_or_default();
+        suggest_replacements(dictionary, word, &forbidden)?
+    };
+    Ok(WordSuggestions {
+        word: word.to_string(),
+        known,
+        reason,
+        suggestions,
+    })
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct TokenInfo {
+    word: String,
+    line: usize,
+    // In chars, starting at 1
+    column: usize,
+    // In bytes, from the start of the line
+    start: usize,
+    end: usize,
+    identifier: String,
+}
+
+fn token_infos(
+    reader: impl BufRead,
+    file_name: &str,
+    mode: Option<TokensMode>,
+) -> Result<Vec<TokenInfo>> {
+    let mut processor = TokenProcessor::new(reader, file_name);
+    if let Some(mode) = mode {
+        processor.set_extract_mode(mode.extract_mode());
+    }
+    let mut res = vec![];
+    for token in processor {
+        let token = token?;
+        let (line, start) = token.pos;
+        res.push(TokenInfo {
+            line,
+            column: token.columns.char + 1,
+            start,
+            end: start + token.text.len(),
+            word: token.text,
+            identifier: token.identifier,
+        });
+    }
+    Ok(res)
+}
+
+fn tokens(opts: &TokensOpts, output_format: OutputFormat) -> Result<()> {
+    let infos = match (&opts.text, &opts.path) {
+        (Some(text), _) => token_infos(text.as_bytes(), "", opts.mode)?,
+        (None, Some(path)) => {
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("Could not open {}", path.display()))?;
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default();
+            token_infos(std::io::BufReader::new(file), &file_name, opts.mode)?
+        }
+        // Enforced by clap
+        (None, None) => bail!("No file given - pass one or use --text"),
+    };
+
+    match output_format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string(&infos).expect("tokens should be serializable");
+            output::print(&json);
+        }
+        OutputFormat::Text => {
+            for TokenInfo {
+                word,
+                line,
+                column,
+                start,
+                end,
+                identifier,
+            } in infos
+            {
+                output::print(&format!(
+                    "{line}:{column}\t{start}..{end}\t{word}\t{identifier}"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// One word per line, ignoring blank lines
+fn read_words(reader: impl BufRead) -> Result<Vec<String>> {
+    let mut res = vec![];
+    for line in reader.lines() {
+        let line = line.context("Could not read words")?;
+        let word = line.trim();
+        if !word.is_empty() {
+            res.push(word.to_string());
+        }
+    }
+    Ok(res)
+}
+
+fn suggest(
+    project: &Project,
+    ignore_store: &IgnoreStore,
+    dictionary: impl Dictionary,
+    opts: &SuggestOpts,
+    output_format: OutputFormat,
+) -> Result<()> {
+    let relative_path = match &opts.path {
+        Some(path) => Some(project.get_relative_path(path)?),
+        None if opts.project => Some(RelativePath::from_path_unchecked(PathBuf::new())),
+        None => None,
+    };
+    let context = relative_path.map(|relative_path| SuggestContext {
+        ignore_store,
+        relative_path,
+    });
+
+    let mut words = opts.words.clone();
+    if opts.stdin {
+        words.extend(read_words(std::io::stdin().lock())?);
+    }
+    if words.is_empty() {
+        bail!("No words given - pass them as arguments or use --stdin");
+    }
+
+    // With just one word, print one suggestion per line, and
+    // nothing if the word is known
+    let single_word = words.len() == 1;
+    let mut results = vec![];
+    for word in words {
+        let result = word_suggestions(&dictionary, &word, context.as_ref())?;
+        match output_format {
+            OutputFormat::Json if opts.stream => {
+                let json =
+                    serde_json::to_string(&result).expect("suggestions should be serializable");
+                output::print(&json);
+            }
+            OutputFormat::Json => results.push(result),
+            OutputFormat::Text if single_word => {
+                for suggestion in result.suggestions.iter() {
+                    output::print(suggestion);
+                }
+            }
+            OutputFormat::Text => {
+                let WordSuggestions {
+                    word,
+                    known,
+                    reason,
+                    suggestions,
+                } = result;
+                if let Some(reason) = reason {
+                    output::print(&format!("{word}: {reason}"));
+                } else if known {
+                    output::print(&format!("{word}: known"));
+                } else {
+                    output::print(&format!("{word}: {}", suggestions.join(", ")));
+                }
+            }
+        }
+    }
+
+    if output_format == OutputFormat::Json && !opts.stream {
+        let json = serde_json::to_string(&results).expect("suggestions should be serializable");
+        output::print(&json);
+    }
+
+    Ok(())
+}
+
+// The dictionary is only opened for the actions that need it: this
+// can be slow, and fail if no provider is installed
+fn run<D: Dictionary + 'static>(
+    project: Project,
+    opts: &Opts,
+    open_dictionary: impl Fn() -> Result<D> + Sync,
+    lang_opener: Option<LangOpener<D>>,
+    mut ignore_store: IgnoreStore,
+    mut state: CheckerState,
+    suggestions: Option<SuggestionWorker>,
+) -> Result<()> {
+    load_personal_dictionary(&mut ignore_store, opts)?;
+    let output_format = opts.output_format.unwrap_or_default();
+    let hook_options = HookOptions {
+        verbose: output::verbosity() >= Verbosity::Verbose,
+        strict: opts.strict_hooks,
+    };
+    state.set_post_operation_hook(ignore_store.post_operation_hook(), hook_options);
+    match &opts.action {
+        Action::Add(opts) => add(project, ignore_store, state, opts),
+        Action::Remove(opts) => remove(project, ignore_store, state, opts),
+        Action::Skip(opts) => skip(project, ignore_store, state, opts),
+        Action::Unskip(opts) => unskip(project, ignore_store, state, opts),
+        Action::AddRegex(opts) => add_regex(ignore_store, opts),
+        Action::RemoveRegex(opts) => remove_regex(ignore_store, opts),
+        Action::AddIdentifier(opts) => add_identifier(project, ignore_store, state, opts),
+        Action::Check(opts) => {
+            let dictionaries = Dictionaries {
+                open: &open_dictionary,
+                lang_opener,
+                suggestions,
+            };
+            check(
+                project,
+                ignore_store,
+                state,
+                dictionaries,
+                opts,
+                output_format,
+            )
+        }
+        #[cfg(feature = "tui")]
+        Action::Review(opts) => review::review(project, ignore_store, open_dictionary, state, opts),
+        Action::Suggest(opts) => suggest(
+            &project,
+            &ignore_store,
+            open_dictionary()?,
+            opts,
+            output_format,
+        ),
+        Action::Undo(opts) => undo(project, ignore_store, state, opts),
+        Action::History(opts) => history(state, opts, output_format),
+        Action::ApplyOps(opts) => apply_ops(project, ignore_store, state, opts),
+        Action::Record(opts) => record(project, state, opts),
+        Action::ShowReport => show_report(project, state, output_format),
+        Action::AddFromReport(opts) => add_from_report(project, ignore_store, state, opts),
+        Action::FixPaths(opts) => fix_paths(project, ignore_store, state, opts),
+        Action::Import(opts) => import(project, ignore_store, state, opts),
+        Action::Prune(opts) => prune(
+            project,
+            ignore_store,
+            open_dictionary()?,
+            state,
+            opts,
+            output_format,
+        ),
+        Action::List(opts) => list(&ignore_store, opts, output_format),
+        Action::LintIgnores => lint_ignores(&ignore_store, output_format),
+        Action::Ignore(opts) => foreign_verb("ignore", &opts.args),
+        Action::Allow(opts) => foreign_verb("allow", &opts.args),
+        Action::SkipFile(opts) => foreign_verb("skipfile", &opts.args),
+        Action::Export(opts) => {
+            export_ignore_lists(&ignore_store, opts);
+            Ok(())
+        }
+        Action::Explain(opts) => explain(project, &ignore_store, open_dictionary, opts),
+        Action::Tokens(opts) => tokens(opts, output_format),
+        Action::Completions(opts) => {
+            write_completions(opts.shell, &mut std::io::stdout());
+            Ok(())
+        }
+        Action::Complete(opts) => {
+            complete(&ignore_store, opts.kind);
+            Ok(())
+        }
+    }
+}
+
+// --state-dir and --no-state take precedence over `default_dir`, and over
+// the SKYSPELL_STATE_DIR environment variable
+fn open_state(opts: &Opts, default_dir: Option<&Path>) -> Result<CheckerState> {
+    if opts.no_state {
+        return Ok(CheckerState::in_memory());
+    }
+    let state_dir = match (&opts.state_dir, default_dir) {
+        (Some(dir), _) => dir.as_path(),
+        (None, Some(dir)) => dir,
+        (None, None) => return CheckerState::load(None),
+    };
+    std::fs::create_dir_all(state_dir)
+        .with_context(|| format!("Could not create state dir {}", state_dir.display()))?;
+    CheckerState::load(Some(state_dir.join("state.toml")))
+}
+
+// Hidden subcommands for the verbs of other spell checkers: always fail,
+// with the skyspell command to use instead
+fn foreign_verb(verb: &str, args: &[String]) -> Result<()> {
+    match translate_foreign_verb(verb, args) {
+        Some(translation) => bail!("{translation}"),
+        None => bail!("Unknown command: {verb}"),
+    }
+}
+
+// Read again on each run, so that words added with other tools are taken into account
+fn load_personal_dictionary(ignore_store: &mut IgnoreStore, opts: &Opts) -> Result<()> {
+    let path = match (&opts.use_personal_dict, &opts.lang) {
+        (Some(path), _) => path.clone(),
+        (None, Some(lang)) if ignore_store.use_personal_dictionary() => {
+            match PersonalDictionary::find(opts.provider.as_deref(), lang) {
+                Some(path) => path,
+                None => {
+                    output::verbose(&format!("No personal dictionary found for '{lang}'"));
+                    return Ok(());
+                }
+            }
+        }
+        _ => return Ok(()),
+    };
+    let personal_dictionary = PersonalDictionary::load(&path)?;
+    output::verbose(&format!(
+        "Using {} words from {}",
+        personal_dictionary.len(),
+        path.display()
+    ));
+    ignore_store.set_personal_dictionary(personal_dictionary);
+    Ok(())
+}
+
+fn list(ignore_store: &IgnoreStore, opts: &ListOpts, output_format: OutputFormat) -> Result<()> {
+    if opts.skipped {
+        list_skip_patterns(ignore_store, output_format);
+        return Ok(());
+    }
+    if opts.project {
+        list_project_words(ignore_store, output_format);
+        return Ok(());
+    }
+    match &opts.builtin {
+        Some(Some(name)) => {
+            for word in builtin_list(name)? {
+                println!("{word}");
+            }
+        }
+        Some(None) => {
+            for name in builtin_list_names() {
+                println!("{name}");
+            }
+        }
+        None => list_ignored_words(ignore_store, output_format),
+    }
+    Ok(())
+}
+
+fn list_ignored_words(ignore_store: &IgnoreStore, output_format: OutputFormat) {
+    let ignored_words = ignore_store.ignored_words();
+    match output_format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string(&ignored_words)
+                .expect("ignored words should be serializable");
+            println!("{json}");
+        }
+        OutputFormat::Text => {
+            for IgnoredWord {
+                word,
+                scope,
+                source,
+                regex,
+                category,
+            } in ignored_words
+            {
+                if regex {
+                    println!("/{word}/ - {scope} regex - {source}");
+                } else if let Some(category) = category {
+                    println!("{word} - {scope} ({category}) - {source}");
+                } else {
+                    println!("{word} - {scope} - {source}");
+                }
+            }
+        }
+    }
+}
+
+// The words without a category first, then each category with its
+// words indented below
+fn list_project_words(ignore_store: &IgnoreStore, output_format: OutputFormat) {
+    let categories = ignore_store.project_categories();
+    let uncategorized: BTreeSet<_> = ignore_store
+        .project_words()
+        .filter(|w| !categories.values().any(|c| c.contains(*w)))
+        .collect();
+    match output_format {
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "words": uncategorized,
+                "categories": categories,
+            });
+            output::print(&json.to_string());
+        }
+        OutputFormat::Text => {
+            for word in uncategorized {
+                output::print(word);
+            }
+            for (category, words) in categories {
+                output::print(&format!("{category}:"));
+                for word in words {
+                    output::print(&format!("  {word}"));
+                }
+            }
+        }
+    }
+}
+
+fn list_skip_patterns(ignore_store: &IgnoreStore, output_format: OutputFormat) {
+    let patterns: Vec<_> = ignore_store.skip_patterns().collect();
+    match output_format {
+        OutputFormat::Json => {
+            let json =
+                serde_json::to_string(&patterns).expect("skip patterns should be serializable");
+            output::print(&json);
+        }
+        OutputFormat::Text => {
+            for pattern in patterns {
+                output::print(pattern);
+            }
+        }
+    }
+}
+
+/// Ignore lists with at least this percentage of their limit are reported
+const LIMIT_WARNING_PERCENT: usize = 90;
+
+fn lint_ignores(ignore_store: &IgnoreStore, output_format: OutputFormat) -> Result<()> {
+    let near_limit: Vec<_> = ignore_store
+        .limit_usage()
+        .into_iter()
+        .filter(|u| u.words * 100 >= u.limit * LIMIT_WARNING_PERCENT)
+        .collect();
+    match output_format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string(&near_limit).expect("usage should be serializable");
+            println!("{json}");
+        }
+        OutputFormat::Text => {
+            if near_limit.is_empty() {
+                info_2!("No ignore list is close to its limit");
+            }
+            for LimitUsage {
+                scope,
+                words,
+                limit,
+            } in &near_limit
+            {
+                println!("{scope}: {words} words, limit: {limit}");
+            }
+        }
+    }
+    // Can happen when the limits are lowered
+    match near_limit.iter().filter(|u| u.words > u.limit).count() {
+        0 => Ok(()),
+        1 => bail!("One ignore list is over its limit"),
+        n => bail!("{n} ignore lists are over their limit"),
+    }
+}
+
+// Warnings are printed on stderr, so that the output can be redirected
+// to a file
+fn export_ignore_lists(ignore_store: &IgnoreStore, opts: &ExportOpts) {
+    let export = export(ignore_store, opts.format);
+    for warning in export.warnings {
+        output::warning(&format!("{} {warning}", "Warning:".yellow()));
+    }
+    print!("{}", export.contents);
+}
+
+fn explain<D: Dictionary>(
+    mut project: Project,
+    ignore_store: &IgnoreStore,
+    open_dictionary: impl Fn() -> Result<D>,
+    opts: &ExplainOpts,
+) -> Result<()> {
+    if let Some(max_file_size) = opts.max_file_size {
+        project.set_max_file_size(max_file_size);
+    }
+    if !opts.extensions.is_empty() {
+        project.set_extensions(&opts.extensions);
+    }
+    let explanation = FileExplanation::new(&project, ignore_store, &opts.path)?;
+    for line in format_file(&explanation) {
+        println!("{line}");
+    }
+    // Only open the dictionary when needed, so that --lang is optional
+    if let Some(word) = &opts.word {
+        let dictionary = open_dictionary()?;
+        let relative_path = &explanation.relative_path;
+        let word_explanation =
+            WordExplanation::new(word, relative_path, ignore_store, &dictionary)?;
+        println!();
+        for line in word_explanation.format() {
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
+
+fn complete(ignore_store: &IgnoreStore, kind: CompletionKind) {
+    for candidate in completion_candidates(ignore_store, kind) {
+        println!("{candidate}");
+    }
+}
+
+// Used when --project-path is not given: paths on the command line are
+// still relative to `cwd`
+// From --project-path, or detected from the current directory
+fn project_path(opts: &Opts) -> Result<PathBuf> {
+    if let Some(p) = &opts.project_path {
+        return Ok(p.clone());
+    }
+    let cwd = std::env::current_dir().context("Could not get current working directory")?;
+    // Completion scripts must not print anything
+    let silent = matches!(opts.action, Action::Complete(_) | Action::Completions(_));
+    Ok(detect_project_path(&cwd, silent))
+}
+
+fn detect_project_path(cwd: &Path, silent: bool) -> PathBuf {
+    if let Some(root) = find_project_root(cwd) {
+        return root;
+    }
+    if !silent {
+        output::note(&format!(
+            "No {SKYSPELL_LOCAL_IGNORE} file or VCS root found, using {} as the project",
+            cwd.display()
+        ));
+    }
+    cwd.to_path_buf()
+}
+
+// Before anything reads the ignore files, see IgnoreStore::recover()
+fn recover_ignore_files(project_path: &Path, global_path: &Path) -> Result<()> {
+    let local_path = project_path.join(SKYSPELL_LOCAL_IGNORE);
+    for path in IgnoreStore::recover(global_path, &local_path)? {
+        output::warning(&format!(
+            "{} restored {} from its backup",
+            "Warning:".yellow().bold(),
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+fn open_project(
+    project_path: &Path,
+    global_path: &Path,
+    ignore_broken_config: bool,
+) -> Result<(Project, IgnoreStore)> {
+    let res = Project::new(project_path).and_then(|project| {
+        let ignore_store = IgnoreStore::load(global_path.to_path_buf(), project.ignore_path())?;
+        Ok((project, ignore_store))
+    });
+    match res {
+        Err(e) if ignore_broken_config => {
+            output::warning(&format!("{} {e:#}", "Warning:".yellow().bold()));
+            output::warning(&format!(
+                "{} checking the project without its ignore file",
+                "Warning:".yellow().bold()
+            ));
+            let project = Project::new_without_local_ignore(project_path)?;
+            let ignore_store =
+                IgnoreStore::load_without_local(global_path.to_path_buf(), project.ignore_path())?;
+            Ok((project, ignore_store))
+        }
+        res => res,
+    }
+}
+
+pub fn main() -> Result<()> {
+    SystemDictionary::init();
+
+    let opts = parse_opts(std::env::args_os().map(|a| a.to_string_lossy().to_string()))
+        .unwrap_or_else(|e| e.exit());
+    output::set_verbosity(Verbosity::from_flags(opts.quiet, opts.verbose));
+    let project_path = project_path(&opts)?;
+    if opts.recover_ignore_file {
+        recover_ignore_files(&project_path, &global_path()?)?;
+    }
+
+    match &opts.action {
+        Action::Completions(completions_opts) => {
+            write_completions(completions_opts.shell, &mut std::io::stdout());
+            return Ok(());
+        }
+        Action::Tokens(tokens_opts) => {
+            // Does not need a project nor a dictionary
+            return tokens(tokens_opts, opts.output_format.unwrap_or_default());
+        }
+        Action::List(list_opts) => {
+            // Does not need a dictionary
+            let (_, ignore_store) = open_project(&project_path, &global_path()?, false)?;
+            return list(
+                &ignore_store,
+                list_opts,
+                opts.output_format.unwrap_or_default(),
+            );
+        }
+        Action::LintIgnores => {
+            // Does not need a dictionary
+            let (_, ignore_store) = open_project(&project_path, &global_path()?, false)?;
+            return lint_ignores(&ignore_store, opts.output_format.unwrap_or_default());
+        }
+        // Only print the skyspell equivalent
+        Action::Ignore(verb_opts) => return foreign_verb("ignore", &verb_opts.args),
+        Action::Allow(verb_opts) => return foreign_verb("allow", &verb_opts.args),
+        Action::SkipFile(verb_opts) => return foreign_verb("skipfile", &verb_opts.args),
+        Action::Complete(complete_opts) => {
+            // Called by completion scripts: don't load the dictionary, and
+            // never fail so that we don't break the shell
+            let opened = global_path().and_then(|g| open_project(&project_path, &g, false));
+            if let Ok((_, ignore_store)) = opened {
+                complete(&ignore_store, complete_opts.kind);
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let ignore_broken_config = matches!(&opts.action, Action::Check(o) if o.ignore_broken_config);
+    let (project, ignore_store) =
+        open_project(&project_path, &global_path()?, ignore_broken_config)?;
+    let state = open_state(&opts, None)?;
+    let lang_options = ignore_store.lang_options();
+    let lang = opts.lang.clone();
+    let provider = opts.provider.clone();
+    let open_lang_dictionary = move || {
+        let lang = match &lang {
+            Some(l) => l,
+            None => bail!("The --lang option is required"),
+        };
+        let dictionary = open_dictionary(provider.as_deref(), lang)?;
+        Ok(LangRules::new(dictionary, &lang_options))
+    };
+    // Only the interactive checker needs suggestions
+    let suggestions = match (&opts.action, &opts.lang) {
+        (Action::Check(o), Some(lang)) if !o.non_interactive => {
+            let provider = opts.provider.clone();
+            let lang = lang.to_string();
+            Some(SuggestionWorker::new(move || {
+                open_dictionary(provider.as_deref(), &lang)
+            }))
+        }
+        _ => None,
+    };
+
+    // Files tagged with another language are checked with its dictionary
+    let provider = opts.provider.clone();
+    let lang_options = ignore_store.lang_options();
+    let lang_opener: LangOpener<_> = Arc::new(move |lang: &str| {
+        let dictionary = open_dictionary(provider.as_deref(), lang)?;
+        Ok(LangRules::new(dictionary, &lang_options))
+    });
+
+    run(
+        project,
+        &opts,
+        open_lang_dictionary,
+        Some(lang_opener),
+        ignore_store,
+        state,
+        suggestions,
+    )
+}
+
+/// Run skyspell with the given arguments, as if from the command line,
+/// but with the dictionary returned by `open_dictionary`, and with the
+/// global ignore file and the state file in `data_dir`. Used by programs
+/// embedding skyspell, and by the end-to-end tests
+pub fn run_embedded<D: Dictionary + 'static>(
+    args: &[&str],
+    open_dictionary: impl Fn() -> Result<D> + Sync,
+    data_dir: &Path,
+) -> Result<()> {
+    let opts = parse_opts(std::iter::once("skyspell").chain(args.iter().copied()))?;
+    output::set_verbosity(Verbosity::from_flags(opts.quiet, opts.verbose));
+    let project_path = project_path(&opts)?;
+    let ignore_broken_config = matches!(&opts.action, Action::Check(o) if o.ignore_broken_config);
+    let global_path = data_dir.join("global.toml");
+    if opts.recover_ignore_file {
+        recover_ignore_files(&project_path, &global_path)?;
+    }
+    let (project, ignore_store) = open_project(&project_path, &global_path, ignore_broken_config)?;
+    let state = open_state(&opts, Some(data_dir))?;
+    run(
+        project,
+        &opts,
+        open_dictionary,
+        None,
+        ignore_store,
+        state,
+        None,
+    )
 }
 
 #[cfg(test)]
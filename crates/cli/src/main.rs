@@ -1,5 +1,12 @@
 use anyhow::Result;
 
 fn main() -> Result<()> {
-    skyspell::main()
+    let res = skyspell::main();
+    if let Err(e) = &res {
+        if let Some(code) = skyspell::exit_code(e) {
+            eprintln!("Error: {e:?}");
+            std::process::exit(code);
+        }
+    }
+    res
 }
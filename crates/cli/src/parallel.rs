@@ -0,0 +1,183 @@
+//! Checking files in several threads, for `check --non-interactive`
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use skyspell_core::UsedEntries;
+use skyspell_core::{Checker, Dictionary, IgnoreStats, IgnoreStore, LangOpener, Project};
+
+use crate::checkers::non_interactive::WorkerErrors;
+use crate::{assume_extension, paths_to_check, print_stats, process_file};
+use crate::{CheckOpts, FileFailure, FileOutcome};
+use crate::{NonInteractiveChecker, OutputFormat, Tally};
+
+/// Number of threads used when --jobs is not given
+pub(crate) fn default_jobs() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+type Paths = Mutex<Receiver<(usize, PathBuf)>>;
+
+/// What a worker found
+struct WorkerResults {
+    // With the index of each path, so that the outcomes can be
+    // reported in the same order as when checking sequentially
+    outcomes: Vec<(usize, PathBuf, FileOutcome)>,
+    errors: WorkerErrors,
+    stats: IgnoreStats,
+    used: UsedEntries,
+}
+
+/// Like check_with(), but the files are checked by `jobs` threads. Errors
+/// are printed once every file is checked, sorted by path
+pub(crate) fn check_in_parallel<D, F>(
+    checker: &mut NonInteractiveChecker<D>,
+    open_dictionary: &F,
+    opts: &CheckOpts,
+    output_format: OutputFormat,
+    jobs: usize,
+) -> Result<Vec<FileFailure>>
+where
+    D: Dictionary,
+    F: Fn() -> Result<D> + Sync,
+{
+    let paths_to_check = paths_to_check(checker.project(), opts)?;
+    assume_extension(checker, &paths_to_check, opts)?;
+    // Each directory is only checked once, so this is not worth sharing
+    // between the workers
+    if opts.check_filenames {
+        checker.process_file_names(&paths_to_check.paths, &())?;
+    }
+    let mut tally = Tally::new(&paths_to_check, output_format);
+    let paths = paths_to_check.paths;
+    let jobs = jobs.clamp(1, paths.len().max(1));
+    let (sender, receiver) = mpsc::channel();
+    for (index, path) in paths.into_iter().enumerate() {
+        sender
+            .send((index, path))
+            .expect("receiver should still exist");
+    }
+    drop(sender);
+    let paths = Mutex::new(receiver);
+    let stop = AtomicBool::new(false);
+    let keep_going = !opts.fail_fast;
+    let check_confusables = checker.check_confusables();
+    let lang_opener = checker.lang_opener();
+
+    let results: Vec<Result<WorkerResults>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..jobs)
+            .map(|_| {
+                let project = checker.project().clone();
+                let ignore_store = checker.ignore_store().clone();
+                let lang_opener = lang_opener.clone();
+                let (paths, stop) = (&paths, &stop);
+                scope.spawn(move || {
+                    let worker = Worker {
+                        project,
+                        ignore_store,
+                        check_confusables,
+                        lang_opener,
+                    };
+                    worker.run(open_dictionary, paths, stop, keep_going)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+
+    let mut outcomes = vec![];
+    let mut worker_errors = vec![];
+    let mut stats = IgnoreStats::default();
+    let mut used = UsedEntries::default();
+    for result in results {
+        let result = result?;
+        outcomes.extend(result.outcomes);
+        worker_errors.push(result.errors);
+        stats += result.stats;
+        used += result.used;
+    }
+    // Needed by --check-unused-ignores and --report-ignored
+    checker.ignore_store().add_used_entries(used);
+    checker.merge_worker_errors(worker_errors);
+
+    outcomes.sort_by_key(|(index, _, _)| *index);
+    for (_, path, outcome) in outcomes {
+        tally.record(checker.project(), &path, outcome)?;
+    }
+    let checked = tally.checked;
+    let failures = tally.finish();
+
+    if opts.stats {
+        print_stats(checked, stats);
+    }
+
+    Ok(failures)
+}
+
+// What each worker needs to create its own checker
+struct Worker<D> {
+    project: Project,
+    ignore_store: IgnoreStore,
+    check_confusables: bool,
+    lang_opener: Option<LangOpener<D>>,
+}
+
+impl<D: Dictionary> Worker<D> {
+    fn run<F>(
+        self,
+        open_dictionary: &F,
+        paths: &Paths,
+        stop: &AtomicBool,
+        keep_going: bool,
+    ) -> Result<WorkerResults>
+    where
+        F: Fn() -> Result<D> + Sync,
+    {
+        // The dictionary is created in the worker thread, so that
+        // providers don't need to be Send
+        let dictionary = open_dictionary()?;
+        let mut checker = NonInteractiveChecker::new_worker(
+            self.project,
+            dictionary,
+            self.ignore_store,
+            self.check_confusables,
+        );
+        if let Some(opener) = self.lang_opener {
+            checker.set_lang_opener(opener);
+        }
+        let mut outcomes = vec![];
+        while !stop.load(Ordering::Relaxed) {
+            let next = paths.lock().expect("paths lock was poisoned").recv();
+            let (index, path) = match next {
+                Ok(n) => n,
+                Err(_) => break,
+            };
+            match process_file(&mut checker, &path, keep_going) {
+                Ok(outcome) => outcomes.push((index, path, outcome)),
+                Err(e) => {
+                    // Only happens with --fail-fast
+                    stop.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
+            }
+        }
+        let stats = checker.ignore_store().stats();
+        let used = checker.ignore_store().used_entries();
+        Ok(WorkerResults {
+            outcomes,
+            errors: checker.into_worker_errors(),
+            stats,
+            used,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
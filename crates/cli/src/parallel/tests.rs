@@ -0,0 +1,104 @@
+use super::*;
+
+use clap::Parser;
+use skyspell_core::tests::FakeDictionary;
+use skyspell_core::SKYSPELL_LOCAL_IGNORE;
+use std::path::Path;
+use tempfile::TempDir;
+
+use crate::check_with;
+
+fn create_tree(temp_dir: &TempDir) -> PathBuf {
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir(&project_path).unwrap();
+    std::fs::write(
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+        "patterns = [\"skipped/\"]\nstrict_utf8 = true\n",
+    )
+    .unwrap();
+    for i in 0..300 {
+        let dir = project_path.join(format!("dir{}", i % 7));
+        std::fs::create_dir_all(&dir).unwrap();
+        let contents = format!("hello wrld{} foo\nfoo brd\n", i % 5);
+        std::fs::write(dir.join(format!("file{i}.md")), contents).unwrap();
+    }
+    std::fs::create_dir(project_path.join("skipped")).unwrap();
+    std::fs::write(project_path.join("skipped/notes.md"), "wrld").unwrap();
+    // Not valid UTF-8, with strict_utf8: reported as a failure
+    std::fs::write(project_path.join("broken.md"), [0xfd, 0xfe, 0xff]).unwrap();
+    project_path
+}
+
+fn open_dictionary() -> Result<FakeDictionary> {
+    let mut dictionary = FakeDictionary::new();
+    dictionary.add_known("hello");
+    dictionary.add_known("foo");
+    Ok(dictionary)
+}
+
+fn new_checker(temp_dir: &TempDir, project_path: &Path) -> NonInteractiveChecker<FakeDictionary> {
+    let project = Project::new(project_path).unwrap();
+    let ignore_store = IgnoreStore::load(
+        temp_dir.path().join("global.toml"),
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+    )
+    .unwrap();
+    let dictionary = open_dictionary().unwrap();
+    NonInteractiveChecker::new(project, dictionary, ignore_store, OutputFormat::Json).unwrap()
+}
+
+#[test]
+fn test_parallel_check_finds_the_same_errors_as_sequential_check() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let project_path = create_tree(&temp_dir);
+    let opts = CheckOpts::try_parse_from(["check", "--non-interactive"]).unwrap();
+
+    let mut sequential = new_checker(&temp_dir, &project_path);
+    let sequential_failures = check_with(&mut sequential, &opts, OutputFormat::Json, true).unwrap();
+    let mut parallel = new_checker(&temp_dir, &project_path);
+    let parallel_failures = check_in_parallel(
+        &mut parallel,
+        &open_dictionary,
+        &opts,
+        OutputFormat::Json,
+        4,
+    )
+    .unwrap();
+
+    let entries = sequential.report_entries();
+    assert_eq!(entries.len(), 600);
+    assert!(entries.iter().all(|e| !e.path.starts_with("skipped")));
+    assert_eq!(parallel.report_entries(), entries);
+    let failed_paths = |failures: &[FileFailure]| -> Vec<String> {
+        failures.iter().map(|f| f.path.clone()).collect()
+    };
+    assert_eq!(failed_paths(&sequential_failures), ["broken.md"]);
+    assert_eq!(failed_paths(&parallel_failures), ["broken.md"]);
+}
+
+#[test]
+fn test_more_jobs_than_files() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir(&project_path).unwrap();
+    std::fs::write(project_path.join("one.md"), "wrld").unwrap();
+    let opts = CheckOpts::try_parse_from(["check", "--non-interactive"]).unwrap();
+    let mut checker = new_checker(&temp_dir, &project_path);
+
+    check_in_parallel(
+        &mut checker,
+        &open_dictionary,
+        &opts,
+        OutputFormat::Json,
+        16,
+    )
+    .unwrap();
+
+    assert_eq!(checker.report_entries().len(), 1);
+}
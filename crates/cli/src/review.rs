@@ -0,0 +1,576 @@
+//! `skyspell review`: check the project first, then triage the unknown
+//! words in a terminal UI, one word at a time rather than one error at
+//! a time
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use skyspell_core::{output, CheckerState, Dictionary, ErrorSink, IgnoreStore, Operation};
+use skyspell_core::{Project, RelativePath, SinkChecker, SpellingError};
+
+use crate::checkers::interactive::print_session_summary;
+use crate::{collect_default_paths, dedup_paths, ensure_in_project, process_file};
+use crate::{info_2, ReviewOpts};
+
+const KEYS: &str = "g global  l lang  e extension  p project  f file  n file name  s skip file  x skip  u undo  / filter  q quit";
+
+/// Where an unknown word was found
+#[derive(Debug, Clone)]
+pub(crate) struct Occurrence {
+    pub(crate) path: RelativePath,
+    /// Starting at 1
+    pub(crate) line: usize,
+    /// In chars, starting at 1
+    pub(crate) column: usize,
+    /// Byte offset of the word in `context`
+    pub(crate) start: usize,
+    pub(crate) context: String,
+}
+
+struct Entry {
+    word: String,
+    occurrences: Vec<Occurrence>,
+    // The letter of the action that handled the word, if any
+    handled: Option<&'static str>,
+}
+
+// What `u` reverts: the entries handled by the action, and whether the
+// action added an operation to the history
+struct Done {
+    entries: Vec<usize>,
+    operation: bool,
+}
+
+/// The state of a review session, independent of the terminal
+pub(crate) struct Review {
+    project: Project,
+    ignore_store: IgnoreStore,
+    state: CheckerState,
+    lang: String,
+    files_visited: usize,
+    entries: Vec<Entry>,
+    filter: String,
+    // Index in visible()
+    selected: usize,
+    done: Vec<Done>,
+}
+
+impl Review {
+    pub(crate) fn new(
+        project: Project,
+        ignore_store: IgnoreStore,
+        state: CheckerState,
+        lang: &str,
+        files_visited: usize,
+        errors: Vec<(String, Occurrence)>,
+    ) -> Self {
+        let mut entries: Vec<Entry> = vec![];
+        let mut indexes = HashMap::new();
+        for (word, occurrence) in errors {
+            let index = *indexes.entry(word.clone()).or_insert_with(|| {
+                entries.push(Entry {
+                    word,
+                    occurrences: vec![],
+                    handled: None,
+                });
+                entries.len() - 1
+            });
+            entries[index].occurrences.push(occurrence);
+        }
+        // The most frequent words first
+        entries.sort_by(|a, b| {
+            b.occurrences
+                .len()
+                .cmp(&a.occurrences.len())
+                .then_with(|| a.word.cmp(&b.word))
+        });
+        Self {
+            project,
+            ignore_store,
+            state,
+            lang: lang.to_string(),
+            files_visited,
+            entries,
+            filter: String::new(),
+            selected: 0,
+            done: vec![],
+        }
+    }
+
+    /// The words not handled yet and matching the filter, with the
+    /// number of times they were found
+    pub(crate) fn words(&self) -> Vec<(&str, usize)> {
+        self.visible()
+            .into_iter()
+            .map(|i| {
+                let entry = &self.entries[i];
+                (entry.word.as_str(), entry.occurrences.len())
+            })
+            .collect()
+    }
+
+    pub(crate) fn selected(&self) -> Option<usize> {
+        match self.visible().len() {
+            0 => None,
+            n => Some(self.selected.min(n - 1)),
+        }
+    }
+
+    pub(crate) fn selected_word(&self) -> Option<&str> {
+        self.selected_entry().map(|i| self.entries[i].word.as_str())
+    }
+
+    pub(crate) fn occurrences(&self) -> &[Occurrence] {
+        match self.selected_entry() {
+            Some(i) => &self.entries[i].occurrences,
+            None => &[],
+        }
+    }
+
+    pub(crate) fn select_next(&mut self) {
+        if let Some(selected) = self.selected() {
+            self.selected = (selected + 1).min(self.visible().len() - 1);
+        }
+    }
+
+    pub(crate) fn select_previous(&mut self) {
+        if let Some(selected) = self.selected() {
+            self.selected = selected.saturating_sub(1);
+        }
+    }
+
+    pub(crate) fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    /// Only show the words containing `filter`, ignoring case
+    pub(crate) fn set_filter(&mut self, filter: &str) {
+        self.filter = filter.to_string();
+        self.selected = 0;
+    }
+
+    fn visible(&self) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.handled.is_none() && e.word.to_lowercase().contains(&filter))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn selected_entry(&self) -> Option<usize> {
+        self.selected().map(|i| self.visible()[i])
+    }
+
+    /// Apply the action of the interactive checker with this letter to
+    /// every occurrence of the selected word, and return what to tell
+    /// the user
+    pub(crate) fn apply(&mut self, key: &str) -> Result<String> {
+        let index = match self.selected_entry() {
+            None => return Ok("Nothing to do".to_string()),
+            Some(i) => i,
+        };
+        let key = match HANDLED_KEYS.iter().find(|k| **k == key) {
+            None => bail!("Unknown action: {key}"),
+            Some(k) => *k,
+        };
+        let word = self.entries[index].word.clone();
+        let (operation, message) = match self.operation_for(key, index) {
+            Err(rejected) => return Ok(rejected),
+            Ok(o) => o,
+        };
+        let handled = match key {
+            "n" | "s" => self.covered_by_skip(key, index),
+            _ => vec![index],
+        };
+        let has_operation = operation.is_some();
+        if let Some(mut operation) = operation {
+            operation.execute(&mut self.ignore_store)?;
+            self.state
+                .set_last_operation(operation, self.project.path())?;
+        }
+        for &i in &handled {
+            self.entries[i].handled = Some(key);
+        }
+        self.done.push(Done {
+            entries: handled,
+            operation: has_operation,
+        });
+        Ok(message.unwrap_or_else(|| format!("Skipped '{word}'")))
+    }
+
+    /// Revert the last action of the session
+    pub(crate) fn undo(&mut self) -> Result<String> {
+        let done = match self.done.last() {
+            None => return Ok("Nothing to undo".to_string()),
+            Some(d) => d,
+        };
+        if done.operation {
+            // Unlike in interactive mode, global operations are undone
+            // without confirmation: they were applied during the session
+            let project_path = self.project.path();
+            let mut operation = match self.state.last_operation(project_path) {
+                None => bail!("Nothing to undo"),
+                Some(e) => e.operation.clone(),
+            };
+            operation.ensure_applied(&self.ignore_store)?;
+            operation.undo(&mut self.ignore_store)?;
+            self.state.pop_last_operation(project_path)?;
+        }
+        let done = self.done.pop().expect("done should not be empty");
+        for &i in &done.entries {
+            self.entries[i].handled = None;
+        }
+        let first = done.entries[0];
+        self.selected = self.visible().iter().position(|i| *i == first).unwrap_or(0);
+        Ok(format!(
+            "Undid the action on '{}'",
+            self.entries[first].word
+        ))
+    }
+
+    /// Print the summary of the session, and fail like the interactive
+    /// checker when some words were not handled
+    pub(crate) fn finish(&self) -> Result<()> {
+        let mut handled = HashMap::new();
+        for entry in &self.entries {
+            if let Some(key) = entry.handled {
+                *handled.entry(key).or_insert(0) += entry.occurrences.len();
+            }
+        }
+        print_session_summary(self.files_visited, &handled);
+        let skipped = self
+            .entries
+            .iter()
+            .any(|e| matches!(e.handled, None | Some("x")));
+        if skipped {
+            bail!("Some errors were skipped")
+        }
+        info_2!("No errors found");
+        Ok(())
+    }
+
+    // The operation to apply, if any, and the message telling what it did.
+    // Returns Err with a message when the action is not possible
+    fn operation_for(
+        &self,
+        key: &str,
+        index: usize,
+    ) -> Result<(Option<Operation>, Option<String>), String> {
+        let entry = &self.entries[index];
+        let word = entry.word.as_str();
+        let mut paths: Vec<&RelativePath> = vec![];
+        for occurrence in &entry.occurrences {
+            if !paths.contains(&&occurrence.path) {
+                paths.push(&occurrence.path);
+            }
+        }
+        let mut operations = vec![];
+        let message = match key {
+            "g" => {
+                operations.push(Operation::new_ignore(word));
+                format!("Added '{word}' to the global ignore list")
+            }
+            "l" => {
+                operations.push(Operation::new_ignore_for_lang(word, &self.lang));
+                format!("Added '{word}' to the ignore list for '{}'", self.lang)
+            }
+            "e" => {
+                let mut extensions = BTreeSet::new();
+                for path in paths {
+                    match path.extension() {
+                        None => return Err(format!("{path} has no extension")),
+                        Some(e) => extensions.insert(e.to_string()),
+                    };
+                }
+                for extension in &extensions {
+                    operations.push(Operation::new_ignore_for_extension(word, extension));
+                }
+                let extensions: Vec<_> = extensions.into_iter().collect();
+                format!(
+                    "Added '{word}' to the ignore list for extension '{}'",
+                    extensions.join("', '")
+                )
+            }
+            "p" => {
+                operations.push(Operation::new_ignore_for_project(word));
+                format!("Added '{word}' to the ignore list for the current project")
+            }
+            "f" => {
+                for path in &paths {
+                    if path.is_outside_project() {
+                        return Err(format!("{path} is outside the project, use 'p' to ignore the word for the project instead"));
+                    }
+                    operations.push(Operation::new_ignore_for_path(word, path));
+                }
+                match paths.len() {
+                    1 => format!("Added '{word}' to the ignore list for its file"),
+                    n => format!("Added '{word}' to the ignore list for {n} files"),
+                }
+            }
+            "n" => {
+                let mut file_names = BTreeSet::new();
+                for path in paths {
+                    match path.file_name() {
+                        None => return Err(format!("{path} has no file name")),
+                        Some(f) => file_names.insert(f.to_string()),
+                    };
+                }
+                for file_name in &file_names {
+                    operations.push(Operation::new_skip(file_name));
+                }
+                let file_names: Vec<_> = file_names.into_iter().collect();
+                format!("Files named '{}' will be skipped", file_names.join("', '"))
+            }
+            "s" => {
+                for path in &paths {
+                    if path.is_outside_project() {
+                        return Err(format!("{path} is outside the project, use 'n' to skip files with this name instead"));
+                    }
+                    operations.push(Operation::new_skip(&format!("/{}", path.normalize())));
+                }
+                match paths.len() {
+                    1 => "Its file will be skipped".to_string(),
+                    n => format!("{n} files will be skipped"),
+                }
+            }
+            _ => return Ok((None, None)),
+        };
+        // Operations already applied by hand would fail
+        operations.retain(|o| !o.is_applied(&self.ignore_store));
+        let operation = match operations.len() {
+            0 => None,
+            1 => operations.pop(),
+            _ => Some(Operation::new_batch(operations)),
+        };
+        Ok((operation, Some(message)))
+    }
+
+    // Skipping files also handles the other words only found in them
+    fn covered_by_skip(&self, key: &str, index: usize) -> Vec<usize> {
+        let skipped: BTreeSet<_> = self.entries[index]
+            .occurrences
+            .iter()
+            .filter_map(|o| skip_key(key, o))
+            .collect();
+        let mut res = vec![index];
+        for (i, entry) in self.entries.iter().enumerate() {
+            let covered = entry
+                .occurrences
+                .iter()
+                .all(|o| skip_key(key, o).is_some_and(|k| skipped.contains(&k)));
+            if i != index && entry.handled.is_none() && covered {
+                res.push(i);
+            }
+        }
+        res
+    }
+}
+
+// What the skip pattern added by `key` matches: the file name for 'n',
+// the path for 's'
+fn skip_key(key: &str, occurrence: &Occurrence) -> Option<String> {
+    match key {
+        "n" => occurrence.path.file_name().map(|f| f.to_string()),
+        _ => Some(occurrence.path.normalize()),
+    }
+}
+
+// The letters of the interactive checker that make sense for a word
+const HANDLED_KEYS: [&str; 8] = ["g", "l", "e", "p", "f", "n", "s", "x"];
+
+// Collects the errors found during the check
+#[derive(Default)]
+struct Collector {
+    files_visited: usize,
+    errors: Vec<(String, Occurrence)>,
+    project_path: PathBuf,
+}
+
+impl ErrorSink for Collector {
+    fn on_file_start(&mut self, _relative_path: &RelativePath) {
+        self.files_visited += 1;
+    }
+
+    fn on_error(&mut self, error: &SpellingError) -> Result<()> {
+        let path = error.relative_path();
+        let (line, start) = error.pos();
+        let full_path = self.project_path.join(&path);
+        let context = std::fs::read_to_string(full_path)
+            .ok()
+            .and_then(|c| c.lines().nth(line.saturating_sub(1)).map(|l| l.to_string()))
+            .unwrap_or_default();
+        self.errors.push((
+            error.word().to_string(),
+            Occurrence {
+                path,
+                line,
+                column: error.columns().char + 1,
+                start,
+                context,
+            },
+        ));
+        Ok(())
+    }
+}
+
+/// Check the files, then open the terminal UI to handle the errors found
+pub(crate) fn review<D: Dictionary>(
+    project: Project,
+    ignore_store: IgnoreStore,
+    open_dictionary: impl Fn() -> Result<D>,
+    state: CheckerState,
+    opts: &ReviewOpts,
+) -> Result<()> {
+    // Fail before checking anything rather than after
+    if let Some(stream) = output::non_terminal_stream() {
+        bail!("{stream} is not a terminal, use `skyspell check --non-interactive` instead");
+    }
+    let paths = match opts.paths.is_empty() {
+        true => collect_default_paths(&project, opts.all)?,
+        false => {
+            for path in &opts.paths {
+                ensure_in_project(&project, path)?;
+            }
+            dedup_paths(opts.paths.clone())
+        }
+    };
+    let dictionary = open_dictionary()?;
+    let lang = dictionary.lang().to_string();
+    let collector = Collector {
+        project_path: project.path().as_ref().to_path_buf(),
+        ..Default::default()
+    };
+    let mut checker = SinkChecker::new(project, dictionary, ignore_store, collector);
+    for path in paths {
+        process_file(&mut checker, &path, false)?;
+    }
+    let (project, ignore_store, collector) = checker.into_parts();
+    let mut review = Review::new(
+        project,
+        ignore_store,
+        state,
+        &lang,
+        collector.files_visited,
+        collector.errors,
+    );
+    if review.selected().is_some() {
+        let mut terminal = ratatui::init();
+        let res = run_ui(&mut terminal, &mut review);
+        ratatui::restore();
+        res?;
+    }
+    review.finish()
+}
+
+fn run_ui(terminal: &mut DefaultTerminal, review: &mut Review) -> Result<()> {
+    let mut message = String::new();
+    let mut filtering = false;
+    loop {
+        terminal.draw(|frame| draw(frame, review, &message, filtering))?;
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Ok(());
+        }
+        if filtering {
+            let mut filter = review.filter().to_string();
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => filtering = false,
+                KeyCode::Backspace => {
+                    filter.pop();
+                }
+                KeyCode::Char(c) => filter.push(c),
+                _ => (),
+            }
+            review.set_filter(&filter);
+            continue;
+        }
+        message = match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => {
+                review.select_next();
+                String::new()
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                review.select_previous();
+                String::new()
+            }
+            KeyCode::Char('/') => {
+                filtering = true;
+                String::new()
+            }
+            KeyCode::Char('u') => review.undo().unwrap_or_else(|e| format!("Error: {e:#}")),
+            KeyCode::Char(c) if HANDLED_KEYS.contains(&c.to_string().as_str()) => review
+                .apply(&c.to_string())
+                .unwrap_or_else(|e| format!("Error: {e:#}")),
+            _ => continue,
+        };
+    }
+}
+
+fn draw(frame: &mut Frame, review: &Review, message: &str, filtering: bool) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(2)]).areas(frame.area());
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(main);
+
+    let items: Vec<_> = review
+        .words()
+        .into_iter()
+        .map(|(word, count)| ListItem::new(format!("{word} ({count})")))
+        .collect();
+    let title = match review.filter() {
+        "" => "Words".to_string(),
+        filter => format!("Words matching '{filter}'"),
+    };
+    let words = List::new(items)
+        .block(Block::bordered().title(title))
+        .highlight_style(Style::new().reversed());
+    let mut list_state = ListState::default().with_selected(review.selected());
+    frame.render_stateful_widget(words, left, &mut list_state);
+
+    let word = review.selected_word().unwrap_or_default();
+    let mut lines = vec![];
+    for occurrence in review.occurrences() {
+        let Occurrence {
+            path,
+            line,
+            column,
+            start,
+            context,
+        } = occurrence;
+        lines.push(Line::from(format!("{path}:{line}:{column}")).bold());
+        let end = start + word.len();
+        let line = match (context.get(..*start), context.get(*start..end)) {
+            (Some(before), Some(found)) => Line::from(vec![
+                Span::raw(format!("  {before}")),
+                Span::raw(found).red().bold(),
+                Span::raw(context.get(end..).unwrap_or_default()),
+            ]),
+            _ => Line::from(format!("  {context}")),
+        };
+        lines.push(line);
+    }
+    let occurrences = Paragraph::new(lines).block(Block::bordered().title("Occurrences"));
+    frame.render_widget(occurrences, right);
+
+    let help = match filtering {
+        true => format!("/{}", review.filter()),
+        false => KEYS.to_string(),
+    };
+    let status_lines = vec![Line::from(message), Line::from(help).dim()];
+    frame.render_widget(Paragraph::new(status_lines), status);
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,113 @@
+use super::{Occurrence, Review};
+use skyspell_core::{CheckerState, IgnoreStore, Project, RelativePath};
+use tempfile::TempDir;
+
+fn new_review(temp_dir: &TempDir, errors: &[(&str, &str)]) -> Review {
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir(&project_path).unwrap();
+    let project = Project::new(&project_path).unwrap();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = project_path.join("skyspell-ignore.toml");
+    let ignore_store = IgnoreStore::load(global_toml, local_toml).unwrap();
+    let state = CheckerState::load(Some(temp_dir.path().join("state.toml"))).unwrap();
+    let errors = errors
+        .iter()
+        .map(|(word, path)| {
+            let occurrence = Occurrence {
+                path: RelativePath::from_path_unchecked((*path).into()),
+                line: 1,
+                column: 1,
+                start: 0,
+                context: word.to_string(),
+            };
+            (word.to_string(), occurrence)
+        })
+        .collect();
+    Review::new(project, ignore_store, state, "en_US", 2, errors)
+}
+
+#[test]
+fn test_most_frequent_words_first() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let review = new_review(
+        &temp_dir,
+        &[("foo", "a.py"), ("bar", "a.py"), ("bar", "b.rs")],
+    );
+
+    assert_eq!(review.words(), vec![("bar", 2), ("foo", 1)]);
+    assert_eq!(review.selected_word(), Some("bar"));
+    assert_eq!(review.occurrences().len(), 2);
+}
+
+#[test]
+fn test_filter() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut review = new_review(&temp_dir, &[("foo", "a.py"), ("bar", "a.py")]);
+
+    review.set_filter("O");
+
+    assert_eq!(review.words(), vec![("foo", 1)]);
+}
+
+#[test]
+fn test_ignore_for_every_file_then_undo() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut review = new_review(&temp_dir, &[("foo", "a.py"), ("foo", "b.py")]);
+
+    review.apply("f").unwrap();
+
+    assert!(review.words().is_empty());
+    let a_py = RelativePath::from_path_unchecked("a.py".into());
+    assert!(review.ignore_store.is_ignored_for_path("foo", &a_py));
+
+    review.undo().unwrap();
+
+    assert_eq!(review.words(), vec![("foo", 2)]);
+    assert!(!review.ignore_store.is_ignored_for_path("foo", &a_py));
+}
+
+#[test]
+fn test_skipping_a_file_handles_the_words_only_found_there() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut review = new_review(
+        &temp_dir,
+        &[
+            ("foo", "a.py"),
+            ("foo", "a.py"),
+            ("bar", "a.py"),
+            ("baz", "b.py"),
+        ],
+    );
+
+    review.apply("s").unwrap();
+
+    assert_eq!(review.words(), vec![("baz", 1)]);
+    assert!(review.ignore_store.has_skip_pattern("/a.py"));
+}
+
+#[test]
+fn test_fails_when_words_are_left() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut review = new_review(&temp_dir, &[("foo", "a.py"), ("bar", "a.py")]);
+    review.apply("p").unwrap();
+    assert!(review.finish().is_err());
+
+    review.apply("p").unwrap();
+
+    review.finish().unwrap();
+}
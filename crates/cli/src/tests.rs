@@ -1,7 +1,7 @@
 use super::*;
 
 use skyspell_core::tests::FakeDictionary;
-use skyspell_core::{RelativePath, SKYSPELL_LOCAL_IGNORE};
+use skyspell_core::{normalize_path, ProjectPath, RelativePath, SKYSPELL_LOCAL_IGNORE};
 
 use tempfile::TempDir;
 
@@ -12,6 +12,7 @@ struct TestApp {
     dictionary: FakeDictionary,
     ignore_store: IgnoreStore,
     project: Project,
+    state: CheckerState,
 }
 
 impl TestApp {
@@ -23,13 +24,20 @@ impl TestApp {
         let global_path = temp_dir.path().join("global.toml");
         let ignore_store = IgnoreStore::load(global_path, local_path).unwrap();
         let project = Project::new(&project_path).unwrap();
+        let state = Self::load_state(temp_dir);
         Self {
             dictionary,
             ignore_store,
             project,
+            state,
         }
     }
 
+    fn load_state(temp_dir: &TempDir) -> CheckerState {
+        let state_toml = temp_dir.path().join("state.toml");
+        CheckerState::load(Some(state_toml)).unwrap()
+    }
+
     fn load_store(temp_dir: &TempDir) -> IgnoreStore {
         let global_path = temp_dir.path().join("global.toml");
         let local_path = temp_dir.path().join("project").join(SKYSPELL_LOCAL_IGNORE);
@@ -43,7 +51,23 @@ impl TestApp {
         (full_path, relative_path)
     }
 
-    fn run(self, args: &[&str]) -> Result<()> {
+    fn run(mut self, args: &[&str]) -> Result<()> {
+        let dictionary = std::mem::take(&mut self.dictionary);
+        self.run_with(args, move || Ok(dictionary.clone()))
+    }
+
+    // Like run(), as if no dictionary provider was installed
+    fn run_without_provider(self, args: &[&str]) -> Result<()> {
+        self.run_with(args, || -> Result<FakeDictionary> {
+            anyhow::bail!("No dictionary provider found")
+        })
+    }
+
+    fn run_with(
+        self,
+        args: &[&str],
+        open_dictionary: impl Fn() -> Result<FakeDictionary> + Sync,
+    ) -> Result<()> {
         let project_path_as_str = self.project.as_str();
         let mut with_arg0 = vec!["skyspell"];
         with_arg0.push("--project-path");
@@ -55,7 +79,15 @@ impl TestApp {
 
         with_arg0.extend(args);
         let opts = Opts::try_parse_from(with_arg0)?;
-        super::run(self.project, &opts, self.dictionary, self.ignore_store)
+        super::run(
+            self.project,
+            &opts,
+            open_dictionary,
+            None,
+            self.ignore_store,
+            self.state,
+            None,
+        )
     }
 }
 
@@ -87,6 +119,41 @@ fn test_add_for_project() {
     assert!(store.is_ignored_for_project("foo"));
 }
 
+#[test]
+fn test_add_for_project_in_category() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    app.run(&["add", "dexamethasone", "--project", "--category", "medical"])
+        .unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_project("dexamethasone"));
+    assert!(store.project_categories()["medical"].contains("dexamethasone"));
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored_for_project("dexamethasone"));
+    assert!(store.project_categories().is_empty());
+}
+
+#[test]
+fn test_category_requires_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    app.run(&["add", "dexamethasone", "--category", "medical"])
+        .unwrap_err();
+}
+
 #[test]
 fn test_add_for_extension() {
     let temp_dir = tempfile::Builder::new()
@@ -192,6 +259,155 @@ fn test_remove_for_extension() {
     assert!(!store.is_ignored_for_extension("foo", "py"));
 }
 
+#[test]
+fn test_remove_when_not_ignored() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.ignore_store.ignore("foo").unwrap();
+
+    let err = app.run(&["remove", "foo", "--project"]).unwrap_err();
+
+    assert_eq!(err.to_string(), "'foo' was not in the project ignore list");
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored("foo"));
+}
+
+#[test]
+fn test_remove_everywhere() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.ignore_store.ignore("foo").unwrap();
+    app.ignore_store.ignore_for_extension("foo", "py").unwrap();
+    app.ignore_store.ignore_for_project("foo").unwrap();
+    app.ignore_store.ignore_for_project("bar").unwrap();
+
+    app.run(&["remove", "foo", "--everywhere"]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored("foo"));
+    assert!(!store.is_ignored_for_extension("foo", "py"));
+    assert!(!store.is_ignored_for_project("foo"));
+    assert!(store.is_ignored_for_project("bar"));
+}
+
+#[test]
+fn test_remove_everywhere_when_not_ignored() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app.run(&["remove", "foo", "--everywhere"]).unwrap_err();
+
+    assert_eq!(err.to_string(), "'foo' was not in any ignore list");
+}
+
+#[test]
+fn test_undo_remove_everywhere() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.ignore_store.ignore("foo").unwrap();
+    app.ignore_store.ignore_for_project("foo").unwrap();
+    app.run(&["remove", "foo", "--everywhere"]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored("foo"));
+    assert!(store.is_ignored_for_project("foo"));
+}
+
+#[test]
+fn test_add_and_undo_do_not_need_a_dictionary() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run_without_provider(&["add", "foo", "--project"])
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run_without_provider(&["remove", "foo", "--project"])
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run_without_provider(&["undo"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run_without_provider(&["history"]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_project("foo"));
+    let app = TestApp::new(&temp_dir);
+    let err = app
+        .run_without_provider(&["check", "--non-interactive"])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "No dictionary provider found");
+}
+
+#[test]
+fn test_undo_after_ignore_file_was_edited_by_hand() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "foo"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "bar", "--project"]).unwrap();
+    let local_toml = temp_dir.path().join("project").join(SKYSPELL_LOCAL_IGNORE);
+    std::fs::write(&local_toml, "").unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    let err = app.run(&["undo"]).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Cannot undo the last operation: 'bar' is no longer in the project ignore list\n\
+         Use `skyspell undo --force` to discard it from the history"
+    );
+    // The operation is still there, so trying again gives the same error
+    let app = TestApp::new(&temp_dir);
+    let err = app.run(&["undo"]).unwrap_err();
+    assert!(err.to_string().contains("'bar' is no longer"));
+    let state = TestApp::load_state(&temp_dir);
+    assert!(!state.history().next().unwrap().undone);
+}
+
+#[test]
+fn test_undo_force() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "foo", "--project"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "bar", "--project"]).unwrap();
+    let mut store = TestApp::load_store(&temp_dir);
+    store.remove_ignored_for_project("bar").unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo", "--force"]).unwrap();
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_project("foo"));
+
+    // The next undo goes to the previous operation
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored_for_project("foo"));
+}
+
 #[test]
 fn test_check_errors_in_two_files() {
     let temp_dir = tempfile::Builder::new()
@@ -213,56 +429,2277 @@ fn test_check_errors_in_two_files() {
 }
 
 #[test]
-fn test_check_happy() {
+fn test_check_accept_acronyms() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let check = |args: &[&str]| {
+        let mut app = TestApp::new(&temp_dir);
+        let (foo_full, _) = app.ensure_file("foo.md");
+        std::fs::write(foo_full, "This is MYPROJ_FOOBAR").unwrap();
+        for word in &["This", "is"] {
+            app.dictionary.add_known(word);
+        }
+        app.run(args)
+    };
+
+    check(&["check", "--non-interactive", "--accept-acronyms"]).unwrap();
+
+    let err = check(&["check", "--non-interactive"]).unwrap_err();
+    assert!(err.to_string().contains("Found 2 spelling errors"));
+}
+
+#[test]
+fn test_check_ignore_capitalized() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let check = |contents: &str| {
+        let mut app = TestApp::new(&temp_dir);
+        let (foo_full, _) = app.ensure_file("foo.md");
+        std::fs::write(foo_full, contents).unwrap();
+        for word in &["Then", "met"] {
+            app.dictionary.add_known(word);
+        }
+        app.run(&["check", "--non-interactive", "--ignore-capitalized"])
+    };
+
+    check("Then Frodo met Smeagol").unwrap();
+
+    // Sentence-initial words are still looked up
+    let err = check("Smeagol met Frodo").unwrap_err();
+    assert!(err.to_string().contains("one tiny spelling error"));
+}
+
+#[test]
+fn test_check_builtin_lists() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let check = |lists: &[&str]| {
+        let mut app = TestApp::new(&temp_dir);
+        let (foo_md, _) = app.ensure_file("foo.md");
+        std::fs::write(foo_md, "Restart with systemd").unwrap();
+        for word in &["Restart", "with"] {
+            app.dictionary.add_known(word);
+        }
+        let mut args = vec!["check", "--non-interactive"];
+        args.extend(lists);
+        app.run(&args)
+    };
+
+    check(&["--builtin-lists", "programming,unix"]).unwrap();
+
+    // 'systemd' is only in the unix list
+    let err = check(&["--builtin-lists", "programming"]).unwrap_err();
+    assert!(err.to_string().contains("one tiny spelling error"));
+    let err = check(&[]).unwrap_err();
+    assert!(err.to_string().contains("one tiny spelling error"));
+}
+
+#[test]
+fn test_check_unknown_builtin_list() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app
+        .run(&["check", "--non-interactive", "--builtin-lists", "nope"])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("Unknown builtin list 'nope'"));
+}
+
+#[test]
+fn test_check_output_in_missing_directory() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let output = temp_dir.path().join("nope/results.json");
+
+    let err = app
+        .run(&[
+            "--output-format",
+            "json",
+            "check",
+            "--non-interactive",
+            "--output",
+            &output.to_string_lossy(),
+        ])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("is not a directory"), "{err}");
+}
+
+#[test]
+fn test_check_output_requires_json() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let output = temp_dir.path().join("results.json");
+
+    let err = app
+        .run(&[
+            "check",
+            "--non-interactive",
+            "--output",
+            &output.to_string_lossy(),
+        ])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("--output-format json"), "{err}");
+}
+
+#[test]
+fn test_check_saves_the_dictionary_cache() {
     let temp_dir = tempfile::Builder::new()
         .prefix("test-skyspell")
         .tempdir()
         .unwrap();
     let mut app = TestApp::new(&temp_dir);
-    let (foo_full, _) = app.ensure_file("foo.md");
-    let (bar_full, _) = app.ensure_file("bar.md");
-    std::fs::write(foo_full, "This is fine").unwrap();
-    std::fs::write(bar_full, "This is also fine").unwrap();
-    for word in &["This", "is", "also", "fine"] {
-        app.dictionary.add_known(word);
-    }
+    let (foo_md, _) = app.ensure_file("foo.md");
+    std::fs::write(foo_md, "Hello").unwrap();
+    app.dictionary.add_known("Hello");
 
     app.run(&["check", "--non-interactive"]).unwrap();
+
+    let cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+    assert_eq!(cache.len(), 1);
 }
 
 #[test]
-fn test_suggest() {
+fn test_check_without_persistent_cache() {
     let temp_dir = tempfile::Builder::new()
         .prefix("test-skyspell")
         .tempdir()
         .unwrap();
     let mut app = TestApp::new(&temp_dir);
-    app.dictionary
-        .add_suggestions("hel", &["hello".to_string(), "hell".to_string()]);
+    let (foo_md, _) = app.ensure_file("foo.md");
+    std::fs::write(foo_md, "Hello").unwrap();
+    app.dictionary.add_known("Hello");
 
-    app.run(&["suggest", "hel"]).unwrap();
+    app.run(&["check", "--non-interactive", "--no-persistent-cache"])
+        .unwrap();
+
+    let cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+    assert!(cache.is_empty());
 }
 
 #[test]
-fn test_reading_ignore_patterns_from_store() {
+fn test_list_builtin() {
     let temp_dir = tempfile::Builder::new()
         .prefix("test-skyspell")
         .tempdir()
         .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["list", "--builtin"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["list", "--builtin", "unix"]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    let err = app.run(&["list", "--builtin", "nope"]).unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("Available lists: cloud, programming, unix"));
+}
+
+fn write_limits(temp_dir: &TempDir, local: &str) {
     let project_path = temp_dir.path().join("project");
-    std::fs::create_dir(&project_path).unwrap();
-    let ignore_path = project_path.join(SKYSPELL_LOCAL_IGNORE);
-    std::fs::write(
-        ignore_path,
-        r#"
-        patterns = ["*.lock"]
-        "#,
-    )
-    .unwrap();
+    std::fs::create_dir_all(&project_path).unwrap();
+    std::fs::write(project_path.join(SKYSPELL_LOCAL_IGNORE), local).unwrap();
+}
 
+#[test]
+fn test_add_past_the_limit() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    write_limits(&temp_dir, "project = [\"foo\"]\n[limits]\nproject = 1\n");
     let app = TestApp::new(&temp_dir);
-    let (foo_full, _) = app.ensure_file("foo.lock");
-    std::fs::write(foo_full, "error").unwrap();
 
-    app.run(&["check", "--non-interactive"]).unwrap();
+    let err = app.run(&["add", "bar", "--project"]).unwrap_err();
+
+    assert!(
+        err.to_string().contains("The project ignore list is full"),
+        "{err}"
+    );
+    let ignore_store = TestApp::load_store(&temp_dir);
+    assert!(!ignore_store.is_ignored_for_project("bar"));
+}
+
+#[test]
+fn test_lint_ignores() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    write_limits(&temp_dir, "project = [\"foo\"]\n[limits]\nproject = 1\n");
+    let app = TestApp::new(&temp_dir);
+    app.run(&["lint-ignores"]).unwrap();
+
+    // The limit was lowered after the words were added
+    write_limits(&temp_dir, "project = [\"foo\"]\n[limits]\nproject = 0\n");
+    let app = TestApp::new(&temp_dir);
+    let err = app.run(&["lint-ignores"]).unwrap_err();
+
+    assert_eq!(err.to_string(), "One ignore list is over its limit");
+}
+
+#[test]
+fn test_check_with_extended_ignore_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    std::fs::write(temp_dir.path().join("org.toml"), r#"project = ["orgword"]"#).unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    std::fs::write(
+        temp_dir.path().join("project").join(SKYSPELL_LOCAL_IGNORE),
+        r#"extends = ["../org.toml"]"#,
+    )
+    .unwrap();
+    app.ignore_store = TestApp::load_store(&temp_dir);
+    let (foo_md, _) = app.ensure_file("foo.md");
+    std::fs::write(foo_md, "This is orgword").unwrap();
+    for word in &["This", "is"] {
+        app.dictionary.add_known(word);
+    }
+
+    app.run(&["check", "--non-interactive"]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["list"]).unwrap();
+
+    // Without the extended file, the word is an error again
+    std::fs::remove_file(temp_dir.path().join("project").join(SKYSPELL_LOCAL_IGNORE)).unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    for word in &["This", "is"] {
+        app.dictionary.add_known(word);
+    }
+    let err = app.run(&["check", "--non-interactive"]).unwrap_err();
+    assert!(err.to_string().contains("one tiny spelling error"));
+}
+
+#[test]
+fn test_add_regex() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add-regex", "^fixture_[a-f0-9]+$", "--extension", "rs"])
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let (foo_rs, _) = app.ensure_file("foo.rs");
+    std::fs::write(foo_rs, "let fixture_ab12cd").unwrap();
+    app.dictionary.add_known("let");
+
+    app.run(&["check", "--non-interactive"]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["remove-regex", "^fixture_[a-f0-9]+$", "--extension", "rs"])
+        .unwrap();
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.ignored_words().is_empty());
+}
+
+#[test]
+fn test_add_identifier() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add-identifier", "XgboostClassifier"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add-identifier", "tmpVarXq", "--project"])
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (model_py, _) = app.ensure_file("model.py");
+    std::fs::write(model_py, "XgboostClassifier tmpVarXq").unwrap();
+
+    app.run(&["check", "--non-interactive"]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_identifier("XgboostClassifier"));
+    assert!(store.is_ignored_identifier_for_project("tmpVarXq"));
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored_identifier_for_project("tmpVarXq"));
+}
+
+#[test]
+fn test_add_invalid_regex() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app.run(&["add-regex", "tmp(", "--project"]).unwrap_err();
+
+    assert!(err.to_string().contains("Invalid regex 'tmp('"));
+}
+
+#[test]
+fn test_check_accept_acronyms_min_length() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.md");
+    std::fs::write(foo_full, "This is MYPROJ_FOO").unwrap();
+    for word in &["This", "is"] {
+        app.dictionary.add_known(word);
+    }
+
+    let err = app
+        .run(&["check", "--non-interactive", "--accept-acronyms", "4"])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("one tiny spelling error"));
+}
+
+fn save_report(temp_dir: &TempDir) {
+    let mut app = TestApp::new(temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.md");
+    std::fs::write(foo_full, "This is fooo and barr").unwrap();
+    for word in &["This", "is", "and"] {
+        app.dictionary.add_known(word);
+    }
+    app.run(&["check", "--non-interactive", "--save-report"])
+        .unwrap_err();
+}
+
+#[test]
+fn test_add_from_report() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    save_report(&temp_dir);
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add-from-report", "2", "1", "--project"])
+        .unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_project("fooo"));
+    assert!(store.is_ignored_for_project("barr"));
+    let state = TestApp::load_state(&temp_dir);
+    let entry = state.history().next().unwrap();
+    assert_eq!(entry.operation.word(), "barr, fooo");
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored_for_project("fooo"));
+    assert!(!store.is_ignored_for_project("barr"));
+}
+
+#[test]
+fn test_add_from_report_for_extension() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    save_report(&temp_dir);
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add-from-report", "1", "--extension"]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_extension("fooo", "md"));
+}
+
+#[test]
+fn test_add_from_report_index_out_of_range() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    save_report(&temp_dir);
+
+    let app = TestApp::new(&temp_dir);
+    let err = app.run(&["add-from-report", "1", "3"]).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Index 3 is out of range: the report contains 2 errors"
+    );
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored("fooo"));
+}
+
+#[test]
+fn test_add_from_stale_report() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    save_report(&temp_dir);
+    let foo_md = temp_dir.path().join("project/foo.md");
+    std::fs::write(foo_md, "This is fooo").unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    let err = app.run(&["add-from-report", "1"]).unwrap_err();
+
+    assert!(err.to_string().contains("out of date"));
+}
+
+#[test]
+fn test_show_report_without_report() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app.run(&["show-report"]).unwrap_err();
+
+    assert!(err.to_string().contains("No report found"));
+}
+
+#[test]
+fn test_save_report_requires_non_interactive() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    assert!(app.run(&["check", "--save-report"]).is_err());
+}
+
+fn run_git(path: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(["-c", "commit.gpgsign=false"])
+        .args(args)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_check_modified() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let project_path = app.project.path().as_ref().to_path_buf();
+    let (committed, _) = app.ensure_file("committed.md");
+    std::fs::write(committed, "This is fooo").unwrap();
+    run_git(&project_path, &["init", "--quiet"]);
+    run_git(&project_path, &["add", "committed.md"]);
+    run_git(&project_path, &["commit", "--quiet", "-m", "initial"]);
+    let check = |contents: &str| {
+        let mut app = TestApp::new(&temp_dir);
+        let (new_file, _) = app.ensure_file("new file.md");
+        std::fs::write(new_file, contents).unwrap();
+        for word in &["This", "is", "fine"] {
+            app.dictionary.add_known(word);
+        }
+        app.run(&["check", "--non-interactive", "--modified"])
+    };
+
+    // 'fooo' in the committed file is not reported
+    check("This is fine").unwrap();
+
+    let err = check("This is fiine").unwrap_err();
+    assert!(err.to_string().contains("one tiny spelling error"));
+}
+
+#[test]
+fn test_check_modified_outside_git_repository() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app
+        .run(&["check", "--non-interactive", "--modified"])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("not in a git repository"));
+}
+
+#[test]
+fn test_check_happy() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.md");
+    let (bar_full, _) = app.ensure_file("bar.md");
+    std::fs::write(foo_full, "This is fine").unwrap();
+    std::fs::write(bar_full, "This is also fine").unwrap();
+    for word in &["This", "is", "also", "fine"] {
+        app.dictionary.add_known(word);
+    }
+
+    app.run(&["check", "--non-interactive"]).unwrap();
+}
+
+#[test]
+fn test_check_only_some_extensions() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let check = |extensions: &[&str]| {
+        let mut app = TestApp::new(&temp_dir);
+        let (readme_full, _) = app.ensure_file("README.md");
+        let (makefile_full, _) = app.ensure_file("Makefile");
+        let (main_full, _) = app.ensure_file("main.rs");
+        std::fs::write(readme_full, "This is fine").unwrap();
+        std::fs::write(makefile_full, "This is fiine").unwrap();
+        std::fs::write(main_full, "fn main() {}").unwrap();
+        for word in &["This", "is", "fine"] {
+            app.dictionary.add_known(word);
+        }
+        let mut args = vec!["check", "--non-interactive"];
+        args.extend(extensions);
+        app.run(&args)
+    };
+
+    // Only README.md is checked
+    check(&["--extensions", "md", "--extensions", "txt"]).unwrap();
+
+    // 'fiine' in the Makefile
+    let err = check(&["--extensions", "md,none", "--extensions", "txt"]).unwrap_err();
+    assert!(err.to_string().contains("one tiny spelling error"));
+
+    // 'fn' and 'main' in main.rs
+    let err = check(&["--extensions", "rs"]).unwrap_err();
+    assert!(err.to_string().contains("Found 2 spelling errors"));
+}
+
+fn write_unreadable_file(app: &TestApp, name: &str) {
+    let (full_path, _) = app.ensure_file(name);
+    // Not valid UTF-8, which is an error with --strict-utf8
+    std::fs::write(full_path, b"This is \xff\xfe").unwrap();
+}
+
+#[test]
+fn test_check_keeps_going_after_a_failure() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    write_unreadable_file(&app, "bad.md");
+    let (good_full, _) = app.ensure_file("good.md");
+    std::fs::write(good_full, "This is fine").unwrap();
+    for word in &["This", "is", "fine"] {
+        app.dictionary.add_known(word);
+    }
+
+    let err = app
+        .run(&["check", "--non-interactive", "--strict-utf8"])
+        .unwrap_err();
+
+    let failures = err.downcast_ref::<CheckFailures>().unwrap();
+    assert_eq!(failures.0, 1);
+}
+
+#[test]
+fn test_check_spelling_errors_take_precedence_over_failures() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    write_unreadable_file(&app, "bad.md");
+    let (foo_full, _) = app.ensure_file("foo.md");
+    std::fs::write(foo_full, "This is fiine").unwrap();
+    for word in &["This", "is"] {
+        app.dictionary.add_known(word);
+    }
+
+    let err = app
+        .run(&["check", "--non-interactive", "--strict-utf8"])
+        .unwrap_err();
+
+    assert!(!err.is::<CheckFailures>());
+    assert!(err.to_string().contains("spelling error"));
+}
+
+#[test]
+fn test_check_fail_fast() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    write_unreadable_file(&app, "bad.md");
+
+    let err = app
+        .run(&["check", "--non-interactive", "--strict-utf8", "--fail-fast"])
+        .unwrap_err();
+
+    assert!(!err.is::<CheckFailures>());
+}
+
+#[test]
+fn test_check_ignores_invalid_utf8_by_default() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let check = |args: &[&str]| {
+        let mut app = TestApp::new(&temp_dir);
+        let (full_path, _) = app.ensure_file("mixed.md");
+        // Mostly UTF-8, so not skipped as being in an other encoding
+        std::fs::write(
+            full_path,
+            b"This is caf\xc3\xa9\nThis \xff is caf\xc3\xa9\n",
+        )
+        .unwrap();
+        for word in &["This", "is", "café"] {
+            app.dictionary.add_known(word);
+        }
+        let mut result = None;
+        let captured = output::capture(Verbosity::Normal, || {
+            result = Some(app.run(args));
+        });
+        (result.unwrap(), captured)
+    };
+
+    let (result, captured) = check(&["check", "--non-interactive", "--jobs", "1"]);
+    result.unwrap();
+    assert!(captured.stdout.contains("Checked 1 files"), "{captured:?}");
+    assert!(
+        captured
+            .stderr
+            .contains("mixed.md: one line is not valid UTF-8, invalid bytes were ignored"),
+        "{captured:?}"
+    );
+
+    let (result, _) = check(&["check", "--non-interactive", "--strict-utf8"]);
+    let err = result.unwrap_err();
+    assert_eq!(err.downcast_ref::<CheckFailures>().unwrap().0, 1);
+}
+
+#[test]
+fn test_check_only_new() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.md");
+    std::fs::write(&foo_full, "fooo").unwrap();
+    let only_new = ["check", "--non-interactive", "--jobs", "1", "--only-new"];
+
+    let err = app.run(&only_new).unwrap_err();
+    assert!(err.to_string().contains("spelling error"));
+
+    // Already seen
+    let app = TestApp::new(&temp_dir);
+    app.run(&only_new).unwrap();
+
+    std::fs::write(&foo_full, "fooo barr").unwrap();
+    let app = TestApp::new(&temp_dir);
+    let err = app.run(&only_new).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Found just one tiny spelling error"));
+
+    let app = TestApp::new(&temp_dir);
+    let mut reset = only_new.to_vec();
+    reset.push("--reset-seen");
+    let err = app.run(&reset).unwrap_err();
+    assert!(err.to_string().contains("Found 2 spelling errors"));
+
+    let state = TestApp::load_state(&temp_dir);
+    let project_path = ProjectPath::new(&temp_dir.path().join("project")).unwrap();
+    let seen = state.seen_findings(&project_path).unwrap();
+    assert!(seen.contains("foo.md", "barr"));
+}
+
+#[test]
+fn test_exit_codes() {
+    let parse_error = SkyspellError::IgnoreStoreParse {
+        path: PathBuf::from("skyspell-ignore.toml"),
+        line: 1,
+        column: 1,
+        message: "invalid".to_string(),
+    };
+    let parse_error = anyhow::Error::from(parse_error).context("While loading the project");
+    assert_eq!(exit_code(&parse_error), Some(CONFIG_ERROR_EXIT_CODE));
+
+    let dictionary_error = anyhow::Error::from(SkyspellError::DictionaryUnavailable {
+        lang: "en_US".to_string(),
+        provider: "enchant".to_string(),
+        message: "No dictionary".to_string(),
+    });
+    assert_eq!(
+        exit_code(&dictionary_error),
+        Some(DICTIONARY_ERROR_EXIT_CODE)
+    );
+
+    let failures = anyhow::Error::from(CheckFailures(1));
+    assert_eq!(exit_code(&failures), Some(TOOL_FAILURE_EXIT_CODE));
+
+    assert_eq!(exit_code(&anyhow::anyhow!("Found 2 spelling errors")), None);
+}
+
+#[test]
+fn test_suggest() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.dictionary
+        .add_suggestions("hel", &["hello".to_string(), "hell".to_string()]);
+
+    app.run(&["suggest", "hel"]).unwrap();
+}
+
+#[test]
+fn test_suggest_several_words_as_json() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.dictionary.add_known("hello");
+    app.dictionary
+        .add_suggestions("hel", &["hello".to_string()]);
+
+    let captured = output::capture(Verbosity::Normal, || {
+        app.run(&["--output-format", "json", "suggest", "hel", "hello"])
+            .unwrap();
+    });
+
+    let json: serde_json::Value = serde_json::from_str(&captured.stdout).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([
+            {"word": "hel", "known": false, "suggestions": ["hello"]},
+            {"word": "hello", "known": true, "suggestions": []},
+        ])
+    );
+}
+
+#[test]
+fn test_suggest_requires_words() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app.run(&["suggest"]).unwrap_err();
+
+    assert!(err.to_string().contains("No words given"));
+}
+
+#[test]
+fn test_word_suggestions() {
+    let mut dictionary = FakeDictionary::new();
+    dictionary.add_known("hello");
+    dictionary.add_suggestions("hel", &["hello".to_string(), "hell".to_string()]);
+
+    let known = word_suggestions(&dictionary, "hello", None).unwrap();
+    let unknown = word_suggestions(&dictionary, "hel", None).unwrap();
+
+    assert_eq!(
+        known,
+        WordSuggestions {
+            word: "hello".to_string(),
+            known: true,
+            reason: None,
+            suggestions: vec![],
+        }
+    );
+    assert!(!unknown.known);
+    assert_eq!(unknown.suggestions, &["hello", "hell"]);
+}
+
+#[test]
+fn test_word_suggestions_for_a_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.ignore_store
+        .ignore_for_extension("recieve", "py")
+        .unwrap();
+    app.dictionary
+        .add_suggestions("recieve", &["receive".to_string()]);
+    let (_, foo_py) = app.ensure_file("foo.py");
+    let (_, foo_md) = app.ensure_file("foo.md");
+    let in_python = SuggestContext {
+        ignore_store: &app.ignore_store,
+        relative_path: foo_py,
+    };
+    let in_markdown = SuggestContext {
+        ignore_store: &app.ignore_store,
+        relative_path: foo_md,
+    };
+
+    let ignored = word_suggestions(&app.dictionary, "recieve", Some(&in_python)).unwrap();
+    let unknown = word_suggestions(&app.dictionary, "recieve", Some(&in_markdown)).unwrap();
+
+    assert_eq!(
+        ignored,
+        WordSuggestions {
+            word: "recieve".to_string(),
+            known: true,
+            reason: Some("ignored-for-extension(py)".to_string()),
+            suggestions: vec![],
+        }
+    );
+    assert!(!unknown.known);
+    assert_eq!(unknown.reason, None);
+    assert_eq!(unknown.suggestions, &["receive"]);
+}
+
+#[test]
+fn test_suggest_for_a_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.ignore_store.ignore_for_project("recieve").unwrap();
+    let (foo_py, _) = app.ensure_file("foo.py");
+    let foo_py = foo_py.to_string_lossy().to_string();
+
+    let captured = output::capture(Verbosity::Normal, || {
+        app.run(&["suggest", "--path", &foo_py, "recieve", "wrold"])
+            .unwrap();
+    });
+
+    assert_eq!(captured.stdout, "recieve: ignored-for-project\nwrold: \n");
+
+    // Without --path, only the dictionary is asked
+    let captured = output::capture(Verbosity::Normal, || {
+        TestApp::new(&temp_dir)
+            .run(&["suggest", "recieve", "wrold"])
+            .unwrap();
+    });
+
+    assert_eq!(captured.stdout, "recieve: \nwrold: \n");
+}
+
+#[test]
+fn test_read_words_skips_blank_lines() {
+    let input = "hel\n\n  wrold \n";
+
+    let words = read_words(input.as_bytes()).unwrap();
+
+    assert_eq!(words, &["hel", "wrold"]);
+}
+
+#[test]
+fn test_token_infos() {
+    let input = "# Title\nlet fooBar = 1;\n";
+
+    let infos = token_infos(input.as_bytes(), "notes.txt", None).unwrap();
+
+    let foo = &infos[2];
+    assert_eq!(
+        foo,
+        &TokenInfo {
+            word: "foo".to_string(),
+            line: 2,
+            column: 5,
+            start: 4,
+            end: 7,
+            identifier: "fooBar".to_string(),
+        }
+    );
+    let bar = &infos[3];
+    assert_eq!((bar.start, bar.end), (7, 10));
+    assert_eq!(bar.identifier, "fooBar");
+}
+
+#[test]
+fn test_token_infos_with_mode() {
+    let input = r"\newpage";
+
+    let default = token_infos(input.as_bytes(), "", None).unwrap();
+    let latex = token_infos(input.as_bytes(), "", Some(TokensMode::Latex)).unwrap();
+
+    assert_eq!(default[0].word, "ewpage");
+    assert_eq!(latex[0].word, "newpage");
+}
+
+#[test]
+fn test_tokens_does_not_need_a_dictionary() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let captured = output::capture(Verbosity::Normal, || {
+        app.run_without_provider(&["tokens", "--text", "some string"])
+            .unwrap();
+    });
+
+    let words: Vec<_> = captured
+        .stdout
+        .lines()
+        .map(|l| l.split('\t').nth(2).unwrap())
+        .collect();
+    assert_eq!(words, &["some", "string"]);
+}
+
+#[test]
+fn test_reading_ignore_patterns_from_store() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir(&project_path).unwrap();
+    let ignore_path = project_path.join(SKYSPELL_LOCAL_IGNORE);
+    std::fs::write(
+        ignore_path,
+        r#"
+        patterns = ["*.lock"]
+        "#,
+    )
+    .unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.lock");
+    std::fs::write(foo_full, "error").unwrap();
+
+    app.run(&["check", "--non-interactive"]).unwrap();
+}
+
+fn write_handbook(temp_dir: &TempDir) -> PathBuf {
+    let handbook_path = temp_dir.path().join("handbook");
+    std::fs::create_dir(&handbook_path).unwrap();
+    std::fs::write(handbook_path.join("intro.md"), "This is an eror").unwrap();
+    handbook_path
+}
+
+#[test]
+fn test_check_also_extra_root() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let handbook_path = write_handbook(&temp_dir);
+    for word in &["This", "is", "an"] {
+        app.dictionary.add_known(word);
+    }
+
+    let err = app
+        .run(&[
+            "check",
+            "--non-interactive",
+            "--also",
+            &handbook_path.to_string_lossy(),
+        ])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("spelling error"))
+}
+
+#[test]
+fn test_check_extra_paths_use_project_ignore_rules() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir(&project_path).unwrap();
+    std::fs::write(
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+        r#"
+        extra_paths = ["../handbook"]
+        project = ["eror"]
+        "#,
+    )
+    .unwrap();
+    write_handbook(&temp_dir);
+    let mut app = TestApp::new(&temp_dir);
+    for word in &["This", "is", "an"] {
+        app.dictionary.add_known(word);
+    }
+    assert_eq!(app.project.extra_roots().len(), 1);
+
+    app.run(&["check", "--non-interactive"]).unwrap();
+}
+
+#[test]
+fn test_add_records_operation_in_history() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "foo"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "bar", "--project"]).unwrap();
+
+    let state = TestApp::load_state(&temp_dir);
+    let history: Vec<_> = state
+        .history()
+        .map(|e| (e.operation.word(), e.operation.scope()))
+        .collect();
+    assert_eq!(
+        history,
+        &[
+            ("bar", "project".to_string()),
+            ("foo", "global".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_history() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    for args in [
+        &["add", "foo"][..],
+        &["add", "bar", "--project"],
+        &["skip", "*.lock"],
+        &["remove", "foo"],
+    ] {
+        TestApp::new(&temp_dir).run(args).unwrap();
+    }
+
+    let app = TestApp::new(&temp_dir);
+    let captured = output::capture(Verbosity::Normal, || {
+        app.run(&["--output-format", "json", "history"]).unwrap();
+    });
+
+    let json: serde_json::Value = serde_json::from_str(&captured.stdout).unwrap();
+    let items: Vec<_> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| {
+            (
+                i["word"].as_str().unwrap(),
+                i["scope"].as_str().unwrap(),
+                i["kind"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        items,
+        &[
+            ("foo", "global", "remove"),
+            ("*.lock", "skipped files", "skip"),
+            ("bar", "project", "add"),
+            ("foo", "global", "add"),
+        ]
+    );
+
+    let app = TestApp::new(&temp_dir);
+    let captured = output::capture(Verbosity::Normal, || {
+        app.run(&["history", "--word", "foo", "--limit", "1"])
+            .unwrap();
+    });
+
+    let lines: Vec<_> = captured.stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "{lines:?}");
+    assert!(
+        lines[0].ends_with("removed 'foo' from the global ignore list"),
+        "{lines:?}"
+    );
+
+    let app = TestApp::new(&temp_dir);
+    let captured = output::capture(Verbosity::Normal, || {
+        app.run(&["history", "--word", "*.lock"]).unwrap();
+    });
+
+    assert!(
+        captured.stdout.contains("skipped files matching '*.lock'"),
+        "{captured:?}"
+    );
+}
+
+#[test]
+fn test_add_for_project_and_lang() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    app.run(&["add", "colour", "--project", "--lang", "en_GB"])
+        .unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_project_and_lang("colour", "en_GB"));
+    assert!(!store.is_ignored_for_lang("colour", "en_GB"));
+}
+
+#[test]
+fn test_remove_for_project_and_lang() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.ignore_store
+        .ignore_for_project_and_lang("colour", "en_GB")
+        .unwrap();
+
+    app.run(&["remove", "colour", "--project", "--lang", "en_GB"])
+        .unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored_for_project_and_lang("colour", "en_GB"));
+}
+
+#[test]
+fn test_add_with_conflicting_options() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app
+        .run(&["add", "colour", "--extension", "md", "--lang", "en_GB"])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("Conflicting options"));
+}
+
+#[test]
+fn test_skip_files_that_are_too_large() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.txt");
+    std::fs::write(&foo_full, "Some errors in a big file").unwrap();
+
+    // Explicit paths get a warning
+    let captured = output::capture(Verbosity::Normal, || {
+        app.run(&[
+            "check",
+            "--non-interactive",
+            "--max-file-size",
+            "10",
+            &foo_full.to_string_lossy(),
+        ])
+        .unwrap();
+    });
+    assert!(
+        captured
+            .stderr
+            .contains("foo.txt is larger than 10 bytes and was skipped"),
+        "{captured:?}"
+    );
+
+    // The others are only reported with -v
+    let check = ["check", "--non-interactive", "--max-file-size", "10"];
+    let captured = output::capture(Verbosity::Normal, || {
+        TestApp::new(&temp_dir).run(&check).unwrap();
+    });
+    assert!(!captured.stderr.contains("foo.txt"), "{captured:?}");
+    let captured = output::capture(Verbosity::Verbose, || {
+        TestApp::new(&temp_dir).run(&check).unwrap();
+    });
+    assert!(
+        captured
+            .stderr
+            .contains("Skipped: foo.txt (larger than 10 bytes)"),
+        "{captured:?}"
+    );
+}
+
+#[test]
+fn test_max_file_size_from_config() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir(&project_path).unwrap();
+    std::fs::write(
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+        r#"
+        max_file_size = "1kB"
+        "#,
+    )
+    .unwrap();
+
+    let app = TestApp::new(&temp_dir);
+
+    assert_eq!(app.project.max_file_size(), 1024);
+}
+
+#[test]
+fn test_max_file_size_zero_disables_the_limit() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.txt");
+    std::fs::write(&foo_full, "Some errors").unwrap();
+
+    let err = app
+        .run(&["check", "--non-interactive", "--max-file-size", "0"])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("spelling errors"));
+}
+
+fn relative_paths_in_walk_order(temp_dir: &TempDir, names: &[&str]) -> Vec<String> {
+    let app = TestApp::new(temp_dir);
+    for name in names {
+        let full_path = app.project.path().as_ref().join(name);
+        std::fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+        std::fs::write(&full_path, "").unwrap();
+    }
+    let paths = collect_paths(&app.project).unwrap();
+    paths
+        .iter()
+        .map(|p| app.project.get_relative_path(p).unwrap().normalize())
+        .collect()
+}
+
+#[test]
+fn test_walked_paths_are_sorted() {
+    let temp_dir_1 = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let temp_dir_2 = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+
+    let first = relative_paths_in_walk_order(&temp_dir_1, &["c.md", "a/z.md", "b.md", "a.md"]);
+    let second = relative_paths_in_walk_order(&temp_dir_2, &["a.md", "b.md", "a/z.md", "c.md"]);
+
+    assert_eq!(first, &["a/z.md", "a.md", "b.md", "c.md"]);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_check_with_stats() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.md");
+    std::fs::write(foo_full, "This is fine").unwrap();
+    let (bar_full, _) = app.ensure_file("bar.md");
+    std::fs::write(bar_full, "This is foo and barr").unwrap();
+    for word in &["This", "is", "fine", "and"] {
+        app.dictionary.add_known(word);
+    }
+    app.ignore_store.ignore("foo").unwrap();
+
+    let mut result = None;
+    let captured = output::capture(Verbosity::Normal, || {
+        result = Some(app.run(&["check", "--non-interactive", "--stats", "--jobs", "1"]));
+    });
+
+    let err = result.unwrap().unwrap_err();
+    assert_eq!(err.to_string(), "Found just one tiny spelling error");
+    // Unknown words are looked up once to compute the ratio of errors
+    // in their line, and once more when reported
+    assert!(
+        captured
+            .stderr
+            .contains("Checked 2 files: 3 lookups of unknown words, 2 of them not ignored"),
+        "{captured:?}"
+    );
+    assert!(captured.stderr.contains("  global: 3"), "{captured:?}");
+}
+
+#[test]
+fn test_explain_does_not_need_a_dictionary() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (full_path, _) = app.ensure_file("foo.md");
+    let full_path = full_path.to_string_lossy().to_string();
+
+    app.run_without_provider(&["explain", &full_path]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    let err = app
+        .run_without_provider(&["explain", &full_path, "--word", "foo"])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "No dictionary provider found");
+}
+
+#[test]
+fn test_explain_missing_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let missing = temp_dir.path().join("project/missing.md");
+
+    let res = app.run(&["explain", &missing.to_string_lossy(), "--word", "foo"]);
+
+    assert!(res.is_err());
+}
+
+const OPS_TOML: &str = r#"
+[[operations]]
+Ignore = { word = "foo" }
+
+[[operations]]
+IgnoreForExtension = { word = "kwargs", extension = "py" }
+
+[[operations]]
+Skip = { pattern = "Cargo.lock" }
+
+[[operations]]
+Remove = { removed = [{ IgnoreForProject = { word = "baz" } }] }
+"#;
+
+#[test]
+fn test_apply_ops() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ops_toml = temp_dir.path().join("ops.toml");
+    std::fs::write(&ops_toml, OPS_TOML).unwrap();
+    let ops_toml = ops_toml.to_string_lossy().to_string();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "baz", "--project"]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["apply-ops", &ops_toml]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored("foo"));
+    assert!(store.is_ignored_for_extension("kwargs", "py"));
+    assert!(store.has_skip_pattern("Cargo.lock"));
+    assert!(!store.is_ignored_for_project("baz"));
+
+    // Applying the file again does nothing
+    let app = TestApp::new(&temp_dir);
+    app.run(&["apply-ops", &ops_toml]).unwrap();
+    let state = TestApp::load_state(&temp_dir);
+    assert_eq!(state.history().count(), 2);
+
+    // The whole file is undone at once
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored("foo"));
+    assert!(!store.is_ignored_for_extension("kwargs", "py"));
+    assert!(!store.has_skip_pattern("Cargo.lock"));
+    assert!(store.is_ignored_for_project("baz"));
+}
+
+#[test]
+fn test_apply_ops_is_all_or_nothing() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ops_toml = temp_dir.path().join("ops.toml");
+    // The project words are removed by the first operation, so removing
+    // both of them in the second one fails
+    let contents = r#"
+[[operations]]
+Ignore = { word = "foo" }
+
+[[operations]]
+Remove = { removed = [{ IgnoreForProject = { word = "bar" } }] }
+
+[[operations]]
+Remove = { removed = [{ IgnoreForProject = { word = "bar" } }, { IgnoreForProject = { word = "baz" } }] }
+"#;
+    std::fs::write(&ops_toml, contents).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "bar", "--project"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "baz", "--project"]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    let err = app
+        .run(&["apply-ops", &ops_toml.to_string_lossy()])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("were not changed"), "{err}");
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored("foo"));
+    assert!(store.is_ignored_for_project("bar"));
+    assert!(store.is_ignored_for_project("baz"));
+}
+
+#[test]
+fn test_record_operations() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ops_toml = temp_dir.path().join("ops.toml");
+    let ops_toml_str = ops_toml.to_string_lossy().to_string();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["record", "--to", &ops_toml_str]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "foo"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "bar", "--extension", "py"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["record", "--stop"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "baz"]).unwrap();
+
+    assert_eq!(
+        load_ops_file(&ops_toml).unwrap(),
+        [
+            Operation::new_ignore("foo"),
+            Operation::new_ignore_for_extension("bar", "py")
+        ]
+    );
+}
+
+#[test]
+fn test_applying_the_recorded_file_does_not_append_to_it() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let ops_toml = temp_dir.path().join("ops.toml");
+    std::fs::write(&ops_toml, OPS_TOML).unwrap();
+    let ops_toml_str = ops_toml.to_string_lossy().to_string();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["record", "--to", &ops_toml_str]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["apply-ops", &ops_toml_str]).unwrap();
+
+    assert_eq!(std::fs::read_to_string(&ops_toml).unwrap(), OPS_TOML);
+}
+
+fn write_outside_project(temp_dir: &TempDir) -> PathBuf {
+    let other = temp_dir.path().join("other");
+    std::fs::create_dir_all(&other).unwrap();
+    let notes = other.join("notes.md");
+    std::fs::write(&notes, "global projword barr").unwrap();
+    notes
+}
+
+#[test]
+fn test_check_rejects_files_outside_the_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let notes = write_outside_project(&temp_dir);
+    let app = TestApp::new(&temp_dir);
+
+    let err = app
+        .run(&["check", "--non-interactive", &notes.to_string_lossy()])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("is outside the project"), "{err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_check_rejects_symlinks_escaping_the_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let notes = write_outside_project(&temp_dir);
+    let app = TestApp::new(&temp_dir);
+    let link = app.project.path().as_ref().join("link.md");
+    std::os::unix::fs::symlink(&notes, &link).unwrap();
+
+    let err = app
+        .run(&["check", "--non-interactive", &link.to_string_lossy()])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("is outside the project"), "{err}");
+}
+
+#[test]
+fn test_check_outside_the_project_with_global_lists_only() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let notes = write_outside_project(&temp_dir);
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "global"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "projword", "--project"]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    let notes = notes.to_string_lossy();
+    app.run(&[
+        "check",
+        "--non-interactive",
+        "--save-report",
+        "--allow-outside-project",
+        &notes,
+    ])
+    .unwrap_err();
+
+    let state = TestApp::load_state(&temp_dir);
+    let app = TestApp::new(&temp_dir);
+    let report = state.last_report(app.project.path()).unwrap();
+    let words: Vec<_> = report.entries.iter().map(|e| e.word.as_str()).collect();
+    assert_eq!(words, ["projword", "barr"]);
+    let expected = std::fs::canonicalize(notes.as_ref()).unwrap();
+    assert_eq!(
+        report.entries[0].path,
+        normalize_path(&expected.to_string_lossy())
+    );
+}
+
+// Check with --save-report and return the words found, by path
+fn check_and_report(temp_dir: &TempDir, args: &[&str]) -> Vec<(String, String)> {
+    let app = TestApp::new(temp_dir);
+    let mut check = vec!["check", "--non-interactive", "--save-report"];
+    check.extend(args);
+    let _ = app.run(&check);
+    let state = TestApp::load_state(temp_dir);
+    let app = TestApp::new(temp_dir);
+    match state.last_report(app.project.path()) {
+        None => vec![],
+        Some(report) => report
+            .entries
+            .iter()
+            .map(|e| (e.path.clone(), e.word.clone()))
+            .collect(),
+    }
+}
+
+#[test]
+fn test_check_paths_from_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.md");
+    let (bar_full, _) = app.ensure_file("bar.md");
+    let (other_full, _) = app.ensure_file("other.md");
+    std::fs::write(&foo_full, "fooo").unwrap();
+    std::fs::write(&bar_full, "barr").unwrap();
+    std::fs::write(other_full, "othr").unwrap();
+    let list = temp_dir.path().join("paths.txt");
+    let missing = app.project.path().as_ref().join("missing.md");
+    let contents = format!(
+        "{}\r\n{}\n\n{}\n",
+        foo_full.display(),
+        foo_full.display(),
+        missing.display()
+    );
+    std::fs::write(&list, contents).unwrap();
+
+    let bar_full = bar_full.to_string_lossy();
+    let actual = check_and_report(
+        &temp_dir,
+        &["--paths-from", &list.to_string_lossy(), &bar_full],
+    );
+
+    assert_eq!(
+        actual,
+        [
+            ("bar.md".to_string(), "barr".to_string()),
+            ("foo.md".to_string(), "fooo".to_string()),
+        ]
+    );
+}
+
+// File names cannot contain newlines on Windows
+#[cfg(unix)]
+#[test]
+fn test_check_null_separated_paths_from_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (odd_full, _) = app.ensure_file("odd\nname.md");
+    std::fs::write(&odd_full, "oddd").unwrap();
+    let list = temp_dir.path().join("paths.txt");
+    let mut contents = odd_full.to_string_lossy().into_owned().into_bytes();
+    contents.push(b'\0');
+    std::fs::write(&list, contents).unwrap();
+
+    let actual = check_and_report(&temp_dir, &["--paths-from", &list.to_string_lossy(), "-0"]);
+
+    assert_eq!(actual, [("odd\nname.md".to_string(), "oddd".to_string())]);
+}
+
+#[test]
+fn test_empty_paths_from_file_checks_nothing() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.md");
+    std::fs::write(&foo_full, "fooo").unwrap();
+    let list = temp_dir.path().join("paths.txt");
+    std::fs::write(&list, "").unwrap();
+
+    let check = || {
+        let app = TestApp::new(&temp_dir);
+        app.run(&[
+            "check",
+            "--non-interactive",
+            "--paths-from",
+            &list.to_string_lossy(),
+        ])
+    };
+
+    check().unwrap();
+
+    std::fs::write(&list, foo_full.to_string_lossy().as_bytes()).unwrap();
+    let err = check().unwrap_err();
+    assert!(err.to_string().contains("one tiny spelling error"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_post_operation_hook() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_path).unwrap();
+    std::fs::write(
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+        "[hooks]\npost_operation = \"echo $SKYSPELL_WORD >> hook.log\"\n",
+    )
+    .unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "foo", "--project"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+
+    let log = std::fs::read_to_string(project_path.join("hook.log")).unwrap();
+    assert_eq!(log, "foo\nfoo\n");
+}
+
+fn check_dense_lines(temp_dir: &TempDir, args: &[&str]) -> Vec<String> {
+    let mut app = TestApp::new(temp_dir);
+    let (foo_full, _) = app.ensure_file("foo.md");
+    let contents = "This is a typo: fooo\ndata: aGVsbG8g d29ybGQg dGhpcyBp cyBiYXNl NjQgZGF0\n";
+    std::fs::write(foo_full, contents).unwrap();
+    for word in &["This", "is", "a", "typo", "data"] {
+        app.dictionary.add_known(word);
+    }
+    let mut check = vec!["check", "--non-interactive", "--save-report"];
+    check.extend(args);
+    let _ = app.run(&check);
+    let state = TestApp::load_state(temp_dir);
+    let app = TestApp::new(temp_dir);
+    let report = state.last_report(app.project.path()).unwrap();
+    report.entries.iter().map(|e| e.word.clone()).collect()
+}
+
+#[test]
+fn test_check_skips_lines_with_too_many_errors() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+
+    let actual = check_dense_lines(&temp_dir, &[]);
+
+    assert_eq!(actual, ["fooo"]);
+}
+
+#[test]
+fn test_check_max_line_error_ratio_of_one_checks_all_lines() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+
+    let actual = check_dense_lines(&temp_dir, &["--max-line-error-ratio", "1"]);
+
+    assert_eq!(actual[0], "fooo");
+    assert!(actual.contains(&"Ghpcy".to_string()), "{actual:?}");
+}
+
+#[test]
+fn test_check_max_line_error_ratio_must_be_between_zero_and_one() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app
+        .run(&[
+            "check",
+            "--non-interactive",
+            "--max-line-error-ratio",
+            "1.5",
+        ])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("between 0 and 1"), "{err}");
+}
+
+fn check_string_values(temp_dir: &TempDir, file_name: &str, contents: &str) -> Vec<String> {
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_path).unwrap();
+    std::fs::write(
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+        "string_values_only = true\n",
+    )
+    .unwrap();
+    let mut app = TestApp::new(temp_dir);
+    let (full, _) = app.ensure_file(file_name);
+    std::fs::write(full, contents).unwrap();
+    app.dictionary.add_known("Hello");
+    let _ = app.run(&["check", "--non-interactive", "--save-report"]);
+    let state = TestApp::load_state(temp_dir);
+    let app = TestApp::new(temp_dir);
+    let report = state.last_report(app.project.path()).unwrap();
+    report.entries.iter().map(|e| e.word.clone()).collect()
+}
+
+#[test]
+fn test_check_string_values_only() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+
+    let actual = check_string_values(&temp_dir, "en.json", "{\"greetng\": \"Hello wrold\"}\n");
+
+    assert_eq!(actual, ["wrold"]);
+}
+
+#[test]
+fn test_check_whole_file_when_string_values_cannot_be_parsed() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+
+    let actual = check_string_values(&temp_dir, "en.json", "{\"greetng\": \"Hello wrold\"\n");
+
+    assert_eq!(actual, ["greetng", "wrold"]);
+}
+
+#[test]
+fn test_check_only_translations_in_gettext_catalogs() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (full, _) = app.ensure_file("fr.po");
+    let contents =
+        "#, fuzzy\nmsgid \"Helo\"\nmsgstr \"Bonjor\"\n\nmsgid \"Wrold\"\nmsgstr \"Mnde\"\n";
+    std::fs::write(full, contents).unwrap();
+    let _ = app.run(&[
+        "check",
+        "--non-interactive",
+        "--save-report",
+        "--skip-fuzzy",
+    ]);
+
+    let state = TestApp::load_state(&temp_dir);
+    let app = TestApp::new(&temp_dir);
+    let report = state.last_report(app.project.path()).unwrap();
+    let words: Vec<_> = report.entries.iter().map(|e| e.word.as_str()).collect();
+    assert_eq!(words, ["Mnde"]);
+}
+
+#[test]
+fn test_check_files_in_other_encodings() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut app = TestApp::new(&temp_dir);
+    app.dictionary.add_known("café");
+    let (utf16_full, _) = app.ensure_file("utf16.txt");
+    let utf16: Vec<u8> = "\u{feff}café fooo"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    std::fs::write(utf16_full, utf16).unwrap();
+    let (legacy_full, _) = app.ensure_file("legacy.txt");
+    std::fs::write(legacy_full, b"caf\xe9 barr").unwrap();
+
+    let captured = output::capture(Verbosity::Normal, || {
+        let _ = app.run(&["--output-format", "json", "check", "--non-interactive"]);
+    });
+
+    let json: serde_json::Value = serde_json::from_str(&captured.stdout).unwrap();
+    assert_eq!(json["errors"]["utf16.txt"][0]["word"], "fooo");
+    assert_eq!(json["errors"]["utf16.txt"][0]["range"]["start_column"], 6);
+    assert!(json["errors"].get("legacy.txt").is_none(), "{json}");
+    assert_eq!(json["encodings"]["utf16.txt"], "UTF-16LE");
+    assert_eq!(json["encodings"]["legacy.txt"], "windows-1252");
+
+    let mut app = TestApp::new(&temp_dir);
+    app.dictionary.add_known("café");
+    let captured = output::capture(Verbosity::Normal, || {
+        let _ = app.run(&[
+            "--output-format",
+            "json",
+            "check",
+            "--non-interactive",
+            "--transcode",
+        ]);
+    });
+
+    let json: serde_json::Value = serde_json::from_str(&captured.stdout).unwrap();
+    assert_eq!(json["errors"]["legacy.txt"][0]["word"], "barr");
+}
+
+#[test]
+fn test_verbose_check_explains_skipped_files() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_path).unwrap();
+    std::fs::write(
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+        "patterns = [\"skipped.md\"]\n",
+    )
+    .unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.ensure_file("skipped.md");
+    app.ensure_file("checked.md");
+
+    let captured = output::capture(Verbosity::Debug, || {
+        app.run(&["check", "--non-interactive"]).unwrap();
+    });
+
+    assert!(
+        captured
+            .stderr
+            .contains("Skipped: skipped.md (matches a skip pattern)"),
+        "{captured:?}"
+    );
+    assert!(captured.stderr.contains("Checked: "), "{captured:?}");
+}
+
+#[test]
+fn test_verb_of_another_spell_checker_is_translated() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app.run(&["ignore", "foo"]).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "skyspell has no `ignore` command - to do the same, skyspell add foo"
+    );
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored("foo"));
+}
+
+#[test]
+fn test_quiet_and_verbose_conflict() {
+    let res = Opts::try_parse_from(["skyspell", "--quiet", "-v", "check"]);
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_check_from_nested_directory_uses_the_project_root() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let project_path = temp_dir.path().join("project");
+    let nested = project_path.join("docs/guide");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::create_dir(project_path.join(".git")).unwrap();
+    let foo_md = nested.join("foo.md");
+    std::fs::write(&foo_md, "fooo\n").unwrap();
+
+    let detected = detect_project_path(&nested, true);
+    assert_eq!(detected, project_path);
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "fooo", "--project"]).unwrap();
+    let app = TestApp::new(&temp_dir);
+    app.run(&["check", "--non-interactive", &foo_md.to_string_lossy()])
+        .unwrap();
+}
+
+#[test]
+fn test_no_project_root_found() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let cwd = temp_dir.path().join("somewhere");
+    std::fs::create_dir(&cwd).unwrap();
+    if find_project_root(&cwd).is_some() {
+        // The temporary directory is inside a project
+        return;
+    }
+
+    let captured = output::capture(Verbosity::Normal, || {
+        assert_eq!(detect_project_path(&cwd, false), cwd);
+    });
+
+    assert!(captured.stderr.contains("as the project"), "{captured:?}");
+}
+
+#[test]
+fn test_fix_paths_from_git() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let project_path = app.project.path().as_ref().to_path_buf();
+    let (old_full, old) = app.ensure_file("old.md");
+    run_git(&project_path, &["init", "--quiet"]);
+    run_git(&project_path, &["add", "old.md"]);
+    run_git(&project_path, &["commit", "--quiet", "-m", "initial"]);
+    app.run(&[
+        "add",
+        "fooo",
+        "--relative-path",
+        &old_full.to_string_lossy(),
+    ])
+    .unwrap();
+    run_git(&project_path, &["mv", "old.md", "new.md"]);
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["fix-paths", "--from-git"]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    let new = RelativePath::from_path_unchecked(PathBuf::from("new.md"));
+    assert!(store.is_ignored_for_path("fooo", &new));
+    assert!(!store.path_words().contains_key("old.md"));
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_path("fooo", &old));
+    assert!(!store.is_ignored_for_path("fooo", &new));
+}
+
+// "impl" is only ignored for .rs files, and "unused" nowhere in the files
+fn app_with_unused_ignores(temp_dir: &TempDir) -> TestApp {
+    let mut app = TestApp::new(temp_dir);
+    let (main_rs, _) = app.ensure_file("main.rs");
+    std::fs::write(main_rs, "impl").unwrap();
+    app.ignore_store.ignore_for_extension("impl", "rs").unwrap();
+    app.ignore_store.ignore_for_project("unused").unwrap();
+    app.ignore_store.ignore("global_unused").unwrap();
+    app
+}
+
+#[test]
+fn test_check_unused_ignores_is_not_an_error_by_default() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = app_with_unused_ignores(&temp_dir);
+
+    app.run(&["check", "--non-interactive", "--check-unused-ignores"])
+        .unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_project("unused"));
+}
+
+#[test]
+fn test_check_strict_unused() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = app_with_unused_ignores(&temp_dir);
+
+    let err = app
+        .run(&[
+            "check",
+            "--non-interactive",
+            "--check-unused-ignores",
+            "--strict-unused",
+        ])
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "Found 2 unused ignore entries");
+}
+
+#[test]
+fn test_check_unused_ignores_fix() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = app_with_unused_ignores(&temp_dir);
+
+    let err = app
+        .run(&[
+            "check",
+            "--non-interactive",
+            "--check-unused-ignores",
+            "--fix",
+            "--strict-unused",
+        ])
+        .unwrap_err();
+
+    // Only the entry of the global file is left
+    assert_eq!(err.to_string(), "Found 1 unused ignore entry");
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored_for_project("unused"));
+    assert!(store.is_ignored("global_unused"));
+    assert!(store.is_ignored_for_extension("impl", "rs"));
+}
+
+#[test]
+fn test_prune_fix_can_be_undone() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = app_with_unused_ignores(&temp_dir);
+    app.run(&["prune", "--fix"]).unwrap();
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored_for_project("unused"));
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_project("unused"));
+}
+
+#[test]
+fn test_check_unused_ignores_requires_the_whole_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+    let (main_rs, _) = app.ensure_file("main.rs");
+
+    let res = app.run(&[
+        "check",
+        "--non-interactive",
+        "--check-unused-ignores",
+        &main_rs.to_string_lossy(),
+    ]);
+
+    assert!(res.is_err());
+}
+
+fn app_with_global_ignores(temp_dir: &TempDir) -> TestApp {
+    let mut app = TestApp::new(temp_dir);
+    let (main_rs, _) = app.ensure_file("main.rs");
+    std::fs::write(main_rs, "impl fooo barr").unwrap();
+    app.ignore_store.ignore("fooo").unwrap();
+    app.ignore_store.ignore_for_extension("impl", "rs").unwrap();
+    app.ignore_store.ignore("barr").unwrap();
+    app.ignore_store.ignore_for_project("barr").unwrap();
+    app
+}
+
+#[test]
+fn test_check_strict_scopes() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = app_with_global_ignores(&temp_dir);
+
+    let captured = output::capture(Verbosity::Normal, || {
+        app.run(&[
+            "--output-format",
+            "json",
+            "check",
+            "--non-interactive",
+            "--strict-scopes",
+            "project,path",
+        ])
+        .unwrap();
+    });
+
+    let json: serde_json::Value = serde_json::from_str(&captured.stdout).unwrap();
+    assert_eq!(json["errors"], serde_json::json!({}));
+    let suppressed = &json["suppressed"]["main.rs"];
+    assert_eq!(suppressed[0]["word"], "impl");
+    assert_eq!(suppressed[0]["suppressed_by"], "extension");
+    assert_eq!(suppressed[1]["word"], "fooo");
+    assert_eq!(suppressed[1]["suppressed_by"], "global");
+    assert!(suppressed.get(2).is_none(), "{json}");
+}
+
+#[test]
+fn test_check_strict_fail() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = app_with_global_ignores(&temp_dir);
+
+    let err = app
+        .run(&[
+            "check",
+            "--non-interactive",
+            "--strict-scopes",
+            "project",
+            "--strict-fail",
+        ])
+        .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Found 2 words only ignored outside the strict scopes"
+    );
+}
+
+#[test]
+fn test_check_strict_scopes_must_be_known() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let res = app.run(&["check", "--non-interactive", "--strict-scopes", "projet"]);
+
+    assert!(res.is_err());
+}
+
+// A project next to the one used by TestApp, with its own ignore file
+fn other_project(temp_dir: &TempDir, local_toml: &str) -> String {
+    let other_path = temp_dir.path().join("other");
+    std::fs::create_dir_all(&other_path).unwrap();
+    std::fs::write(other_path.join(SKYSPELL_LOCAL_IGNORE), local_toml).unwrap();
+    other_path.to_string_lossy().to_string()
+}
+
+#[test]
+fn test_import_merges_into_a_non_empty_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let other = other_project(
+        &temp_dir,
+        r#"
+patterns = ["Cargo.lock"]
+project = ["kwargs", "fooo"]
+identifiers = ["XgboostClassifier"]
+"#,
+    );
+    let app = TestApp::new(&temp_dir);
+    app.run(&["add", "fooo", "--project"]).unwrap();
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["import", "--from", &other]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_project("kwargs"));
+    assert!(store.is_ignored_for_project("fooo"));
+    assert!(store.is_ignored_identifier_for_project("XgboostClassifier"));
+    // Skip patterns are only imported when asked for
+    assert!(!store.has_skip_pattern("Cargo.lock"));
+
+    // The whole import is undone at once, and the words that were
+    // already there stay
+    let app = TestApp::new(&temp_dir);
+    app.run(&["undo"]).unwrap();
+    let store = TestApp::load_store(&temp_dir);
+    assert!(!store.is_ignored_for_project("kwargs"));
+    assert!(!store.is_ignored_identifier_for_project("XgboostClassifier"));
+    assert!(store.is_ignored_for_project("fooo"));
+
+    let app = TestApp::new(&temp_dir);
+    app.run(&["import", "--from", &other, "--scopes", "skip"])
+        .unwrap();
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.has_skip_pattern("Cargo.lock"));
+    assert!(!store.is_ignored_for_project("kwargs"));
+}
+
+#[test]
+fn test_import_drops_paths_that_do_not_exist_here() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let other = other_project(
+        &temp_dir,
+        r#"
+[paths]
+"main.rs" = ["impl"]
+"old.md" = ["fooo"]
+"#,
+    );
+    let app = TestApp::new(&temp_dir);
+    let (_, main_rs) = app.ensure_file("main.rs");
+
+    app.run(&["import", "--from", &other]).unwrap();
+
+    let store = TestApp::load_store(&temp_dir);
+    assert!(store.is_ignored_for_path("impl", &main_rs));
+    assert!(!store.path_words().contains_key("old.md"));
+}
+
+#[test]
+fn test_import_without_an_ignore_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let app = TestApp::new(&temp_dir);
+
+    let err = app
+        .run(&["import", "--from", &temp_dir.path().to_string_lossy()])
+        .unwrap_err();
+
+    assert!(err.to_string().contains("not found"), "{err}");
 }
@@ -1,5 +1,8 @@
 use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::path::Path;
+
+use anyhow::Result;
 
 use crate::Interactor;
 
@@ -8,11 +11,15 @@ enum Answer {
     Text(String),
     Int(Option<usize>),
     Bool(bool),
+    Edit(String),
 }
 
 #[derive(Debug, Default)]
 pub struct FakeInteractor {
     answers: RefCell<VecDeque<Answer>>,
+    // The prompt and the choices of each call to input_letter()
+    letter_prompts: RefCell<Vec<(String, String)>>,
+    confirm_prompts: RefCell<Vec<String>>,
 }
 
 impl FakeInteractor {
@@ -34,6 +41,23 @@ impl FakeInteractor {
         self.answers.borrow_mut().push_front(Answer::Bool(b))
     }
 
+    /// Simulate the user replacing the contents of the file in their editor
+    pub fn push_edit(&self, contents: &str) {
+        self.answers
+            .borrow_mut()
+            .push_front(Answer::Edit(contents.to_string()))
+    }
+
+    /// The prompt and the choices of the last call to input_letter()
+    pub fn last_letter_prompt(&self) -> Option<(String, String)> {
+        self.letter_prompts.borrow().last().cloned()
+    }
+
+    /// The prompt of the last call to confirm()
+    pub fn last_confirm_prompt(&self) -> Option<String> {
+        self.confirm_prompts.borrow().last().cloned()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.answers.borrow().is_empty()
     }
@@ -58,6 +82,9 @@ impl Interactor for FakeInteractor {
 
     fn input_letter(&self, prompt: &str, choices: &str) -> String {
         println!("{}", prompt);
+        self.letter_prompts
+            .borrow_mut()
+            .push((prompt.to_string(), choices.to_string()));
         let answer = self
             .answers
             .borrow_mut()
@@ -96,6 +123,7 @@ impl Interactor for FakeInteractor {
 
     fn confirm(&self, prompt: &str) -> bool {
         println!("{} >", prompt);
+        self.confirm_prompts.borrow_mut().push(prompt.to_string());
         let answer = self
             .answers
             .borrow_mut()
@@ -109,6 +137,22 @@ impl Interactor for FakeInteractor {
             a => panic!("Should have got a boolean answer, got {:?}", a),
         }
     }
+
+    fn open_editor(&self, path: &Path, line: usize, column: usize) -> Result<()> {
+        println!("Opening {}:{}:{}", path.display(), line, column);
+        let answer = self
+            .answers
+            .borrow_mut()
+            .pop_back()
+            .expect("should have got a recorded answer");
+        match answer {
+            Answer::Edit(contents) => {
+                std::fs::write(path, contents)?;
+                Ok(())
+            }
+            a => panic!("Should have got an edit answer, got {:?}", a),
+        }
+    }
 }
 
 #[test]
@@ -0,0 +1,121 @@
+//! Asking git which files to check
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+fn git(repo_path: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output()
+        .context("Could not run git - is it installed?")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            bail!("{} is not in a git repository", repo_path.display());
+        }
+        bail!("git {} failed: {}", args.join(" "), stderr.trim());
+    }
+    Ok(output.stdout)
+}
+
+fn repo_root(repo_path: &Path) -> Result<PathBuf> {
+    let stdout = git(repo_path, &["rev-parse", "--show-toplevel"])?;
+    let root = String::from_utf8(stdout).context("git returned a non UTF-8 path")?;
+    Ok(PathBuf::from(root.trim_end()))
+}
+
+/// Staged, unstaged and untracked files, not counting the ignored
+/// and deleted ones
+pub(crate) fn modified_files(repo_path: &Path) -> Result<Vec<PathBuf>> {
+    let root = repo_root(repo_path).map_err(|e| {
+        anyhow!("{e} - use `skyspell check` without --modified to check the whole project")
+    })?;
+    let stdout = git(
+        repo_path,
+        &["status", "--porcelain=v1", "-z", "--untracked-files=all"],
+    )?;
+    let paths = parse_porcelain(&stdout)?;
+    Ok(paths.iter().map(|p| root.join(p)).collect())
+}
+
+/// Parse the output of `git status --porcelain=v1 -z` - paths are
+/// relative to the root of the repository
+fn parse_porcelain(output: &[u8]) -> Result<Vec<String>> {
+    let mut res = vec![];
+    let mut records = output.split(|b| *b == 0).filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        let record = std::str::from_utf8(record).context("git status returned a non UTF-8 path")?;
+        if record.len() < 4 {
+            bail!("Unexpected git status record: '{record}'");
+        }
+        let (status, path) = record.split_at(3);
+        let mut status = status.chars();
+        let index = status.next().unwrap_or(' ');
+        let worktree = status.next().unwrap_or(' ');
+        // With -z, renames and copies are followed by the original path
+        if index == 'R' || index == 'C' {
+            records
+                .next()
+                .ok_or_else(|| anyhow!("Missing original path for '{path}'"))?;
+        }
+        if index == 'D' || worktree == 'D' || index == '!' {
+            continue;
+        }
+        res.push(path.to_string());
+    }
+    Ok(res)
+}
+
+/// Files renamed in the history of the repository, then in the index,
+/// oldest first - paths are absolute
+pub(crate) fn renamed_files(repo_path: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let root = repo_root(repo_path)?;
+    let find_renames = ["--name-status", "--find-renames", "-z"];
+    let mut args = vec!["log", "--reverse", "--format="];
+    args.extend(find_renames);
+    let mut stdout = git(repo_path, &args)?;
+    stdout.push(0);
+    let mut args = vec!["diff", "--cached"];
+    args.extend(find_renames);
+    stdout.extend(git(repo_path, &args)?);
+    let renames = parse_name_status(&stdout)?;
+    Ok(renames
+        .into_iter()
+        .map(|(old, new)| (root.join(old), root.join(new)))
+        .collect())
+}
+
+/// Parse the renames in the output of `git log --name-status -z` or
+/// `git diff --name-status -z` - paths are relative to the root of the
+/// repository
+fn parse_name_status(output: &[u8]) -> Result<Vec<(String, String)>> {
+    let mut res = vec![];
+    let mut records = output.split(|b| *b == 0).filter(|r| !r.is_empty());
+    while let Some(status) = records.next() {
+        let status = std::str::from_utf8(status).context("git returned a non UTF-8 status")?;
+        // Renames and copies are followed by the original path, then the
+        // new one. Scores like in `R087` are ignored
+        let num_paths = match status.chars().next() {
+            Some('R') | Some('C') => 2,
+            _ => 1,
+        };
+        let mut paths = vec![];
+        for _ in 0..num_paths {
+            let path = records
+                .next()
+                .ok_or_else(|| anyhow!("Missing path after '{status}'"))?;
+            let path = std::str::from_utf8(path).context("git returned a non UTF-8 path")?;
+            paths.push(path.to_string());
+        }
+        if let (true, [old, new]) = (status.starts_with('R'), paths.as_slice()) {
+            res.push((old.clone(), new.clone()));
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests;
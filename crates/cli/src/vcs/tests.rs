@@ -0,0 +1,71 @@
+use super::*;
+
+#[test]
+fn test_parse_modified_and_untracked() {
+    let output = b" M src/lib.rs\0M  README.md\0?? new file.md\0";
+
+    let paths = parse_porcelain(output).unwrap();
+
+    assert_eq!(paths, &["src/lib.rs", "README.md", "new file.md"]);
+}
+
+#[test]
+fn test_parse_renames() {
+    let output = b"R  docs/new name.md\0docs/old name.md\0 M other.md\0";
+
+    let paths = parse_porcelain(output).unwrap();
+
+    assert_eq!(paths, &["docs/new name.md", "other.md"]);
+}
+
+#[test]
+fn test_deleted_files_are_not_returned() {
+    let output = b"D  removed.md\0 D also_removed.md\0AM added.md\0";
+
+    let paths = parse_porcelain(output).unwrap();
+
+    assert_eq!(paths, &["added.md"]);
+}
+
+#[test]
+fn test_parse_empty_output() {
+    assert!(parse_porcelain(b"").unwrap().is_empty());
+}
+
+#[test]
+fn test_parse_truncated_rename() {
+    let output = b"R  new.md\0";
+
+    assert!(parse_porcelain(output).is_err());
+}
+
+#[test]
+fn test_not_a_git_repository() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+
+    let err = modified_files(temp_dir.path()).unwrap_err();
+
+    assert!(err.to_string().contains("is not in a git repository"));
+}
+
+#[test]
+fn test_parse_renames_in_name_status() {
+    let output = b"\0A\0docs/a.md\0R087\0docs/a.md\0docs/b.md\0M\0README.md\0C100\0x.md\0y.md\0";
+
+    let renames = parse_name_status(output).unwrap();
+
+    assert_eq!(
+        renames,
+        [("docs/a.md".to_string(), "docs/b.md".to_string())]
+    );
+}
+
+#[test]
+fn test_parse_truncated_name_status() {
+    let output = b"R100\0old.md\0";
+
+    assert!(parse_name_status(output).is_err());
+}
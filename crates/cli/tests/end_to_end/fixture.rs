@@ -0,0 +1,177 @@
+//! A temporary project, checked by the same code as the `skyspell` binary
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use skyspell_core::output::{self, Captured};
+use skyspell_core::tests::FakeDictionary;
+use skyspell_core::{IgnoreStore, Verbosity, SKYSPELL_LOCAL_IGNORE};
+use tempfile::TempDir;
+
+/// Set this environment variable to rewrite the golden files instead of
+/// comparing them with the output
+const UPDATE_GOLDEN: &str = "SKYSPELL_UPDATE_GOLDEN";
+
+pub struct Fixture {
+    temp_dir: TempDir,
+    dictionary: FakeDictionary,
+}
+
+/// What a run of skyspell did
+pub struct Outcome {
+    pub result: Result<()>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Outcome {
+    /// The exit code of the binary
+    pub fn exit_code(&self) -> i32 {
+        match &self.result {
+            Ok(()) => 0,
+            Err(e) => skyspell::exit_code(e).unwrap_or(1),
+        }
+    }
+
+    pub fn error(&self) -> String {
+        match &self.result {
+            Ok(()) => panic!("should have failed, stdout: {}", self.stdout),
+            Err(e) => format!("{e:#}"),
+        }
+    }
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let temp_dir = tempfile::Builder::new()
+            .prefix("test-skyspell")
+            .tempdir()
+            .expect("should create a temp dir");
+        std::fs::create_dir(temp_dir.path().join("project")).expect("should create the project");
+        std::fs::create_dir(temp_dir.path().join("data")).expect("should create the data dir");
+        Self {
+            temp_dir,
+            dictionary: FakeDictionary::new(),
+        }
+    }
+
+    /// Write a file of the project, creating its parent directories
+    pub fn with_file(self, relative_path: &str, contents: &str) -> Self {
+        self.write(relative_path, contents);
+        self
+    }
+
+    pub fn with_known(mut self, words: &[&str]) -> Self {
+        for word in words {
+            self.dictionary.add_known(word);
+        }
+        self
+    }
+
+    /// Turn the project into a git repository, with every file committed
+    pub fn with_git(self) -> Self {
+        self.git(&["init", "--quiet"]);
+        self.git(&["add", "."]);
+        self.git(&["commit", "--quiet", "--message", "Initial commit"]);
+        self
+    }
+
+    pub fn project_path(&self) -> PathBuf {
+        self.temp_dir.path().join("project")
+    }
+
+    /// Where the global ignore file and the state are stored
+    pub fn data_dir(&self) -> PathBuf {
+        self.temp_dir.path().join("data")
+    }
+
+    pub fn path(&self, relative_path: &str) -> PathBuf {
+        self.project_path().join(relative_path)
+    }
+
+    pub fn write(&self, relative_path: &str, contents: &str) {
+        let path = self.path(relative_path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("should create parent dir");
+        }
+        std::fs::write(path, contents).expect("should write file");
+    }
+
+    pub fn read(&self, relative_path: &str) -> String {
+        std::fs::read_to_string(self.path(relative_path)).expect("should read file")
+    }
+
+    /// Replace the path of the project in `output` by `<project>`, so that
+    /// it can be compared with a golden file
+    pub fn redact(&self, output: &str) -> String {
+        let project_path = self.project_path();
+        let canonical = std::fs::canonicalize(&project_path).expect("should canonicalize");
+        [canonical, project_path]
+            .iter()
+            .fold(output.to_string(), |output, path| {
+                output.replace(&*path.to_string_lossy(), "<project>")
+            })
+    }
+
+    /// The ignore store, as saved by the previous runs
+    pub fn ignore_store(&self) -> IgnoreStore {
+        IgnoreStore::load(
+            self.data_dir().join("global.toml"),
+            self.path(SKYSPELL_LOCAL_IGNORE),
+        )
+        .expect("should load the ignore store")
+    }
+
+    /// Run skyspell in the project with the given arguments
+    pub fn run(&self, args: &[&str]) -> Outcome {
+        let project_path = self.project_path();
+        let project_path = project_path.to_string_lossy();
+        let mut all_args = vec!["--project-path", &project_path, "--lang", "en_US"];
+        all_args.extend(args);
+        let dictionary = self.dictionary.clone();
+        let data_dir = self.data_dir();
+        let mut result = None;
+        let Captured { stdout, stderr, .. } = output::capture(Verbosity::Normal, || {
+            result = Some(skyspell::run_embedded(
+                &all_args,
+                || Ok(dictionary.clone()),
+                &data_dir,
+            ));
+        });
+        Outcome {
+            result: result.expect("run_embedded() should have been called"),
+            stdout,
+            stderr,
+        }
+    }
+
+    fn git(&self, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(self.project_path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(["-c", "commit.gpgsign=false"])
+            .args(args)
+            .status()
+            .expect("should run git");
+        assert!(status.success(), "git {args:?} failed");
+    }
+}
+
+/// Compare `actual` with the contents of `golden/<name>`
+pub fn assert_golden(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/end_to_end/golden")
+        .join(name);
+    if std::env::var_os(UPDATE_GOLDEN).is_some() {
+        std::fs::write(&path, actual).expect("should write golden file");
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {e}", path.display()));
+    assert_eq!(
+        actual,
+        expected,
+        "{} does not match the output - if the change is deliberate, run the tests again with {UPDATE_GOLDEN}=1",
+        path.display()
+    );
+}
@@ -0,0 +1,514 @@
+//! Run skyspell over real projects, and check its exit code, its output
+//! and the ignore files it leaves behind
+mod fixture;
+
+use std::path::{Path, PathBuf};
+
+use fixture::{assert_golden, Fixture};
+
+const IGNORE_TOML: &str = r#"patterns = ["vendor/"]
+project = ["skyspell"]
+"#;
+
+// Nested directories, spaces and non-ASCII characters in file names, and
+// a committed ignore file
+fn sample_project() -> Fixture {
+    Fixture::new()
+        .with_known(&["Hello", "world", "the", "is", "here"])
+        .with_file("skyspell-ignore.toml", IGNORE_TOML)
+        .with_file("README.md", "Hello skyspell world\n")
+        .with_file("docs/my notes.md", "Hello\nthe fooo is here\n")
+        .with_file("docs/été/café.txt", "Hello barr\n")
+        .with_file("vendor/lib.md", "qwzx\n")
+        .with_file(".gitignore", "build/\n")
+        .with_file("build/out.txt", "zzyzx\n")
+        .with_git()
+}
+
+#[test]
+fn test_check_reports_errors_in_every_directory() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&["check", "--non-interactive"]);
+
+    assert_eq!(outcome.exit_code(), 1);
+    assert_eq!(outcome.error(), "Found 2 spelling errors");
+    assert_eq!(outcome.stderr, "");
+    assert!(
+        outcome
+            .stdout
+            .contains("docs/my notes.md:2:5:8: error: unknown word: fooo"),
+        "{}",
+        outcome.stdout
+    );
+    assert!(
+        outcome
+            .stdout
+            .contains("docs/été/café.txt:1:7:10: error: unknown word: barr"),
+        "{}",
+        outcome.stdout
+    );
+}
+
+#[test]
+fn test_check_honors_gitignore_and_skip_patterns() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&["check", "--non-interactive"]);
+
+    assert!(!outcome.stdout.contains("zzyzx"), "{}", outcome.stdout);
+    assert!(!outcome.stdout.contains("qwzx"), "{}", outcome.stdout);
+}
+
+#[test]
+fn test_check_paths_with_dot_dot() {
+    let fixture = sample_project();
+    let path = fixture.path("docs/été/../my notes.md");
+
+    let outcome = fixture.run(&["check", "--non-interactive", &path.to_string_lossy()]);
+
+    assert_eq!(outcome.error(), "Found just one tiny spelling error");
+    assert!(
+        outcome.stdout.contains("docs/my notes.md:2:5:8"),
+        "{}",
+        outcome.stdout
+    );
+}
+
+// `path`, relative to the current directory
+fn relative_to_current_dir(path: &Path) -> PathBuf {
+    let current_dir = std::env::current_dir().unwrap();
+    let mut res: PathBuf = current_dir.components().skip(1).map(|_| "..").collect();
+    res.push(path.strip_prefix("/").unwrap());
+    res
+}
+
+#[test]
+fn test_paths_are_relative_to_the_project_however_files_are_given() {
+    let fixture = sample_project();
+    let notes = fixture.path("docs/my notes.md");
+    let relative = relative_to_current_dir(&notes);
+    assert!(relative.is_relative());
+
+    let walked = fixture.run(&["check", "--non-interactive"]);
+    let absolute = fixture.run(&["check", "--non-interactive", &notes.to_string_lossy()]);
+    let relative = fixture.run(&["check", "--non-interactive", &relative.to_string_lossy()]);
+
+    let expected = "docs/my notes.md:2:5:8: error: unknown word: fooo";
+    assert!(walked.stdout.contains(expected), "{}", walked.stdout);
+    assert!(absolute.stdout.contains(expected), "{}", absolute.stdout);
+    assert_eq!(absolute.stdout, relative.stdout);
+}
+
+#[test]
+fn test_absolute_paths() {
+    let fixture = sample_project();
+    let notes = std::fs::canonicalize(fixture.path("docs/my notes.md")).unwrap();
+
+    let outcome = fixture.run(&["check", "--non-interactive", "--absolute-paths"]);
+
+    let expected = format!("{}:2:5:8: error: unknown word: fooo", notes.display());
+    assert!(outcome.stdout.contains(&expected), "{}", outcome.stdout);
+}
+
+#[test]
+fn test_json_output_has_the_project_root() {
+    let fixture = sample_project();
+    let notes = std::fs::canonicalize(fixture.path("docs/my notes.md")).unwrap();
+    let run = |extra_args: &[&str]| {
+        let mut args = vec!["--output-format", "json", "check", "--non-interactive"];
+        args.extend(extra_args);
+        let outcome = fixture.run(&args);
+        serde_json::from_str::<serde_json::Value>(&outcome.stdout).unwrap()
+    };
+
+    let relative = run(&[]);
+    let absolute = run(&["--absolute-paths"]);
+
+    assert_eq!(relative["project"], absolute["project"]);
+    let project = PathBuf::from(relative["project"].as_str().unwrap());
+    assert_eq!(project.join("docs/my notes.md"), notes);
+    assert!(relative["errors"]["docs/my notes.md"].is_array());
+    assert!(absolute["errors"][&*notes.to_string_lossy()].is_array());
+}
+
+#[test]
+fn test_add_then_undo() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&["add", "fooo", "--project"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    assert!(fixture.ignore_store().is_ignored_for_project("fooo"));
+    assert!(fixture.read("skyspell-ignore.toml").contains("\"fooo\""));
+
+    let cafe_txt = fixture.path("docs/été/café.txt");
+    let cafe_txt = cafe_txt.to_string_lossy();
+    let outcome = fixture.run(&["add", "barr", "--relative-path", &cafe_txt]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    let outcome = fixture.run(&["check", "--non-interactive"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+
+    let outcome = fixture.run(&["undo"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    let outcome = fixture.run(&["check", "--non-interactive"]);
+    assert_eq!(outcome.error(), "Found just one tiny spelling error");
+    assert!(outcome.stdout.contains("barr"), "{}", outcome.stdout);
+}
+
+#[test]
+fn test_skip_then_unskip_a_directory() {
+    let fixture = sample_project();
+    let ete = fixture.path("docs/été");
+    let ete = ete.to_string_lossy();
+
+    let outcome = fixture.run(&["skip", "--directory", &ete]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    assert!(fixture.read("skyspell-ignore.toml").contains("/docs/été/"));
+    let outcome = fixture.run(&["check", "--non-interactive"]);
+    assert_eq!(outcome.error(), "Found just one tiny spelling error");
+    assert!(!outcome.stdout.contains("barr"), "{}", outcome.stdout);
+
+    let outcome = fixture.run(&["list", "--skipped"]);
+    assert_eq!(outcome.stdout, "/docs/été/\nvendor/\n");
+
+    let outcome = fixture.run(&["unskip", "--directory", &ete]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    let outcome = fixture.run(&["check", "--non-interactive"]);
+    assert_eq!(outcome.error(), "Found 2 spelling errors");
+
+    let outcome = fixture.run(&["unskip", "--directory", &ete]);
+    assert_eq!(outcome.error(), "'/docs/été/' is not a skip pattern");
+}
+
+#[test]
+fn test_undo_skip() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&["skip", "*.txt"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    assert!(fixture.ignore_store().has_skip_pattern("*.txt"));
+
+    let outcome = fixture.run(&["undo"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    assert!(!fixture.ignore_store().has_skip_pattern("*.txt"));
+}
+
+#[test]
+fn test_broken_ignore_file() {
+    let fixture = sample_project().with_file("skyspell-ignore.toml", "project = [");
+
+    let outcome = fixture.run(&["check", "--non-interactive"]);
+
+    assert_eq!(outcome.exit_code(), 3);
+}
+
+#[test]
+fn test_recover_truncated_ignore_file() {
+    let fixture = sample_project();
+    let outcome = fixture.run(&["add", "--project", "foo"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    let contents = fixture.read("skyspell-ignore.toml");
+    // As if the write stopped in the middle of the new word
+    let end = contents.find("\"foo\"").unwrap() + 2;
+    fixture.write("skyspell-ignore.toml", &contents[..end]);
+
+    let outcome = fixture.run(&["check", "--non-interactive"]);
+    assert_eq!(outcome.exit_code(), 3);
+    assert!(
+        outcome.error().contains("--recover-ignore-file"),
+        "{}",
+        outcome.error()
+    );
+
+    let outcome = fixture.run(&["--recover-ignore-file", "list"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    assert!(
+        outcome.stderr.contains("from its backup"),
+        "{}",
+        outcome.stderr
+    );
+    assert!(!fixture.ignore_store().is_ignored_for_project("foo"));
+}
+
+#[test]
+fn test_check_ignoring_broken_ignore_file() {
+    let fixture = sample_project().with_file("skyspell-ignore.toml", "project = [");
+
+    let outcome = fixture.run(&["check", "--non-interactive", "--ignore-broken-config"]);
+
+    assert!(
+        outcome
+            .stderr
+            .contains("checking the project without its ignore file"),
+        "{}",
+        outcome.stderr
+    );
+    // Without the ignore file, vendor/ is checked and 'skyspell' is unknown
+    assert_eq!(outcome.error(), "Found 4 spelling errors");
+}
+
+#[test]
+fn test_json_output() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&["--output-format", "json", "check", "--non-interactive"]);
+
+    assert_eq!(outcome.exit_code(), 1);
+    assert_golden("check.json", &fixture.redact(&outcome.stdout));
+}
+
+#[test]
+fn test_json_output_sorted_by_word() {
+    let fixture = sample_project();
+    let output = fixture.data_dir().join("results.json");
+
+    let outcome = fixture.run(&[
+        "--output-format",
+        "json",
+        "check",
+        "--non-interactive",
+        "--sort-by",
+        "word",
+        "--output",
+        &output.to_string_lossy(),
+    ]);
+
+    assert_eq!(outcome.exit_code(), 1);
+    let contents = std::fs::read_to_string(output).unwrap();
+    assert_golden("check-by-word.json", &fixture.redact(&contents));
+}
+
+#[test]
+fn test_check_without_a_terminal_falls_back_to_non_interactive() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&["check"]);
+
+    assert_eq!(outcome.exit_code(), 1);
+    assert_eq!(outcome.error(), "Found 2 spelling errors");
+    assert!(
+        outcome
+            .stderr
+            .contains("is not a terminal; running in non-interactive mode"),
+        "{}",
+        outcome.stderr
+    );
+    assert!(outcome.stdout.contains("fooo"), "{}", outcome.stdout);
+}
+
+#[test]
+fn test_force_interactive_conflicts_with_non_interactive() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&["check", "--non-interactive", "--force-interactive"]);
+
+    assert!(
+        outcome.error().contains("cannot be used with"),
+        "{}",
+        outcome.error()
+    );
+}
+
+fn project_with_check_paths() -> Fixture {
+    Fixture::new()
+        .with_known(&["Hello"])
+        .with_file(
+            "skyspell-ignore.toml",
+            "check_paths = [\"docs\", \"README.md\", \"missing\"]\n",
+        )
+        .with_file("README.md", "Hello fooo\n")
+        .with_file("docs/guide.md", "Hello barr\n")
+        .with_file("vendor/lib.md", "Hello qwzx\n")
+}
+
+#[test]
+fn test_check_only_the_configured_paths() {
+    let fixture = project_with_check_paths();
+
+    let outcome = fixture.run(&["check", "--non-interactive"]);
+
+    assert_eq!(outcome.error(), "Found 2 spelling errors");
+    assert!(!outcome.stdout.contains("qwzx"), "{}", outcome.stdout);
+    assert!(
+        outcome
+            .stderr
+            .contains("missing is listed in check_paths but does not exist"),
+        "{}",
+        outcome.stderr
+    );
+}
+
+#[test]
+fn test_check_all_ignores_the_configured_paths() {
+    let fixture = project_with_check_paths();
+
+    let outcome = fixture.run(&["check", "--non-interactive", "--all"]);
+
+    assert_eq!(outcome.error(), "Found 3 spelling errors");
+}
+
+#[test]
+fn test_paths_on_the_command_line_override_the_configured_paths() {
+    let fixture = project_with_check_paths();
+    let path = fixture.path("vendor/lib.md");
+
+    let outcome = fixture.run(&["check", "--non-interactive", &path.to_string_lossy()]);
+
+    assert_eq!(outcome.error(), "Found just one tiny spelling error");
+}
+
+#[test]
+fn test_report_ignored() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&[
+        "--output-format",
+        "json",
+        "check",
+        "--non-interactive",
+        "--report-ignored",
+    ]);
+
+    assert_eq!(outcome.exit_code(), 1);
+    let json: serde_json::Value = serde_json::from_str(&outcome.stdout).unwrap();
+    assert_eq!(
+        json["ignore_hits"],
+        serde_json::json!([{"word": "skyspell", "scope": "project", "hits": 1}])
+    );
+}
+
+#[test]
+fn test_report_ignored_as_text() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&["check", "--non-interactive", "--report-ignored"]);
+
+    assert!(
+        outcome.stdout.contains("Ignored words:"),
+        "{}",
+        outcome.stdout
+    );
+    assert!(
+        outcome.stdout.contains("skyspell  project"),
+        "{}",
+        outcome.stdout
+    );
+}
+
+#[test]
+fn test_use_personal_dictionary() {
+    let fixture = sample_project();
+    let home = tempfile::tempdir().unwrap();
+    let pws = home.path().join(".aspell.en.pws");
+    let contents = "personal_ws-1.1 en 2 utf-8\nfooo\nbarr\n";
+    std::fs::write(&pws, contents).unwrap();
+
+    let outcome = fixture.run(&[
+        "--use-personal-dict",
+        &pws.to_string_lossy(),
+        "check",
+        "--non-interactive",
+    ]);
+
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    assert_eq!(std::fs::read_to_string(&pws).unwrap(), contents);
+}
+
+#[test]
+fn test_add_then_undo_with_a_state_dir() {
+    let fixture = sample_project();
+    let state_dir = tempfile::tempdir().unwrap();
+    let state_dir = state_dir.path().join("state");
+    let state_dir_arg = state_dir.to_string_lossy();
+
+    let outcome = fixture.run(&["--state-dir", &state_dir_arg, "add", "fooo", "--project"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    assert!(state_dir.join("state.toml").exists());
+    assert!(!fixture.data_dir().join("state.toml").exists());
+
+    let outcome = fixture.run(&["--state-dir", &state_dir_arg, "undo"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    assert!(!fixture.ignore_store().is_ignored_for_project("fooo"));
+}
+
+#[test]
+fn test_add_then_undo_without_state() {
+    let fixture = sample_project();
+
+    let outcome = fixture.run(&["--no-state", "add", "fooo", "--project"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    assert!(fixture.ignore_store().is_ignored_for_project("fooo"));
+    let outcome = fixture.run(&["--no-state", "check", "--non-interactive"]);
+    assert_eq!(outcome.error(), "Found just one tiny spelling error");
+
+    let outcome = fixture.run(&["--no-state", "undo"]);
+    assert!(
+        outcome.error().contains("no persistent state"),
+        "{}",
+        outcome.error()
+    );
+    assert!(fixture.ignore_store().is_ignored_for_project("fooo"));
+    // Neither the state nor the dictionary cache were written
+    let written: Vec<_> = std::fs::read_dir(fixture.data_dir())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .filter(|name| name != "global.toml")
+        .collect();
+    assert!(written.is_empty(), "{written:?}");
+}
+
+#[test]
+fn test_check_filenames() {
+    let fixture = Fixture::new()
+        .with_known(&["Hello", "docs", "guide", "skyspell", "ignore"])
+        .with_file("docz/instalation.md", "Hello\n")
+        .with_file("docz/guide.md", "Hello\n");
+
+    let outcome = fixture.run(&["check", "--non-interactive", "--check-filenames"]);
+
+    assert_eq!(outcome.exit_code(), 1);
+    assert_eq!(outcome.error(), "Found 2 spelling errors");
+    // The directory is only reported once, even if it contains two files
+    assert!(
+        outcome.stdout.contains(
+            "docz:0:0:0: error: unknown word in file name: docz\n\
+            docz/instalation.md:0:0:0: error: unknown word in file name: instalation\n"
+        ),
+        "{}",
+        outcome.stdout
+    );
+}
+
+#[test]
+fn test_check_with_assumed_extension() {
+    let fixture = Fixture::new()
+        .with_known(&["print", "world"])
+        .with_file("build", "print(rb'world')\nfrobnicate()\n");
+    let outcome = fixture.run(&["add", "frobnicate", "--extension", "py"]);
+    assert_eq!(outcome.exit_code(), 0, "{:?}", outcome.result);
+    let build = fixture.path("build");
+    let build = build.to_string_lossy();
+
+    let outcome = fixture.run(&["check", "--non-interactive", &build]);
+    assert_eq!(outcome.exit_code(), 1);
+    assert_eq!(outcome.error(), "Found 2 spelling errors");
+    assert!(outcome.stdout.contains("rb'world"), "{}", outcome.stdout);
+
+    let outcome = fixture.run(&[
+        "check",
+        "--non-interactive",
+        "--assume-extension",
+        "py",
+        &build,
+    ]);
+    assert_eq!(outcome.exit_code(), 0, "{}", outcome.stdout);
+
+    // Files found by walking the project keep their extension
+    let outcome = fixture.run(&["check", "--non-interactive", "--assume-extension", "py"]);
+    assert_eq!(outcome.exit_code(), 1);
+    assert!(
+        outcome.error().contains("--assume-extension only applies"),
+        "{:?}",
+        outcome.result
+    );
+}
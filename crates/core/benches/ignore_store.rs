@@ -0,0 +1,65 @@
+//! Lookups in the ignore store, with lists of various sizes.
+//!
+//! Run with `cargo bench -p skyspell_core`
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use skyspell_core::{IgnoreStore, RelativePath};
+use std::hint::black_box;
+use tempfile::TempDir;
+
+const SIZES: [usize; 3] = [100, 10_000, 100_000];
+
+fn words(size: usize) -> String {
+    let words: Vec<_> = (0..size).map(|i| format!("\"word{i}\"")).collect();
+    words.join(", ")
+}
+
+// Write the toml files directly - calling ignore() for each word
+// would rewrite the whole file every time
+fn create_store(temp_dir: &TempDir, size: usize) -> IgnoreStore {
+    let words = words(size);
+    let global_toml = temp_dir.path().join("global.toml");
+    let global =
+        format!("global = [{words}]\n\n[extensions]\nrs = [{words}]\n\n[lang]\nen = [{words}]\n");
+    std::fs::write(&global_toml, global).unwrap();
+    let local_toml = temp_dir.path().join("skyspell-ignore.toml");
+    let local = format!("project = [{words}]\n\n[paths]\n\"foo.rs\" = [{words}]\n");
+    std::fs::write(&local_toml, local).unwrap();
+    IgnoreStore::load(global_toml, local_toml).unwrap()
+}
+
+fn bench_lookups(c: &mut Criterion) {
+    let foo_rs = RelativePath::from_path_unchecked("foo.rs".into());
+    for size in SIZES {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = create_store(&temp_dir, size);
+        let present = format!("word{}", size / 2);
+
+        let mut group = c.benchmark_group("is_ignored");
+        group.bench_with_input(BenchmarkId::new("present", size), &present, |b, word| {
+            b.iter(|| store.is_ignored(black_box(word)))
+        });
+        group.bench_with_input(BenchmarkId::new("missing", size), "missing", |b, word| {
+            b.iter(|| store.is_ignored(black_box(word)))
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group("is_ignored_for_extension");
+        group.bench_with_input(BenchmarkId::new("present", size), &present, |b, word| {
+            b.iter(|| store.is_ignored_for_extension(black_box(word), "rs"))
+        });
+        group.bench_with_input(BenchmarkId::new("missing", size), "missing", |b, word| {
+            b.iter(|| store.is_ignored_for_extension(black_box(word), "rs"))
+        });
+        group.finish();
+
+        // For missing words, should_ignore() has to go through every list
+        let mut group = c.benchmark_group("should_ignore");
+        group.bench_with_input(BenchmarkId::new("missing", size), "missing", |b, word| {
+            b.iter(|| store.should_ignore(black_box(word), &foo_rs, "en_US"))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_lookups);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use std::path::Path;
 
 use anyhow::{bail, Result};
-use skyspell_core::{Checker, IgnoreStore, SpellingError};
+use skyspell_core::{Checker, IgnoreStore, SpellingError, TokenClassifier};
 use skyspell_core::{Project, SystemDictionary};
 
 struct ExampleChecker {
@@ -44,6 +44,10 @@ impl Checker<SystemDictionary> for ExampleChecker {
         &mut self.ignore_store
     }
 
+    fn classifier(&self) -> TokenClassifier<'_, SystemDictionary> {
+        TokenClassifier::new(&self.dictionary, &self.ignore_store)
+    }
+
     fn success(&self) -> Result<()> {
         if self.error_count != 0 {
             bail!("Found some errors");
@@ -0,0 +1,59 @@
+//! Replace files in a way that survives crashes: other processes - and
+//! the next run after a crash or a Ctrl-C - see either the old contents
+//! or the new ones, never a truncated file
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use tempfile::NamedTempFile;
+
+/// Where write_with_backup() keeps the previous version of `path`, like
+/// `skyspell-ignore.toml.bak`
+pub(crate) fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Write `contents` to a temporary file in the same directory, flush it
+/// to disk, then rename it over `path`. The permissions of `path` are kept
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let mut temp_file = NamedTempFile::new_in(parent)?;
+    temp_file.write_all(contents)?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        temp_file
+            .as_file()
+            .set_permissions(metadata.permissions())?;
+    }
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(path)?;
+    sync_dir(parent);
+    Ok(())
+}
+
+/// Like write_atomically(), but first replace the backup of `path` with
+/// its current contents, if any - see backup_path()
+pub(crate) fn write_with_backup(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    match std::fs::read(path) {
+        Ok(previous) => write_atomically(&backup_path(path), &previous)?,
+        Err(e) if e.kind() == ErrorKind::NotFound => (),
+        Err(e) => return Err(e),
+    }
+    write_atomically(path, contents)
+}
+
+// So that the rename itself is on disk. Directories cannot be opened
+// like this on Windows, and failing here would not undo the write anyway
+fn sync_dir(dir: &Path) {
+    if cfg!(unix) {
+        if let Ok(dir) = std::fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,68 @@
+use super::*;
+
+use crate::tests::get_test_dir;
+
+#[test]
+fn test_backup_path() {
+    assert_eq!(
+        backup_path(Path::new("project/skyspell-ignore.toml")),
+        Path::new("project/skyspell-ignore.toml.bak")
+    );
+}
+
+#[test]
+fn test_write_atomically() {
+    let temp_dir = get_test_dir();
+    let path = temp_dir.path().join("foo.toml");
+
+    write_atomically(&path, b"one").unwrap();
+    write_atomically(&path, b"two").unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "two");
+    assert!(!backup_path(&path).exists());
+    // No temporary file is left behind
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn test_only_the_previous_version_is_backed_up() {
+    let temp_dir = get_test_dir();
+    let path = temp_dir.path().join("foo.toml");
+
+    write_with_backup(&path, b"one").unwrap();
+    assert!(!backup_path(&path).exists());
+
+    write_with_backup(&path, b"two").unwrap();
+    write_with_backup(&path, b"three").unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "three");
+    assert_eq!(std::fs::read_to_string(backup_path(&path)).unwrap(), "two");
+}
+
+#[test]
+fn test_concurrent_writers_never_leave_a_partial_file() {
+    let temp_dir = get_test_dir();
+    let path = temp_dir.path().join("foo.toml");
+    let contents: Vec<Vec<u8>> = (b'a'..=b'd').map(|c| vec![c; 64 * 1024]).collect();
+    write_with_backup(&path, &contents[0]).unwrap();
+
+    std::thread::scope(|scope| {
+        for written in &contents {
+            let path = &path;
+            scope.spawn(move || {
+                for _ in 0..20 {
+                    write_with_backup(path, written).unwrap();
+                }
+            });
+        }
+        for _ in 0..200 {
+            let read = std::fs::read(&path).unwrap();
+            assert!(contents.contains(&read), "read {} bytes", read.len());
+        }
+    });
+
+    for path in [path.clone(), backup_path(&path)] {
+        let read = std::fs::read(&path).unwrap();
+        assert!(contents.contains(&read), "{}", path.display());
+    }
+}
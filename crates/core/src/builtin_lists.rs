@@ -0,0 +1,55 @@
+//! Lists of technical words shipped with skyspell, that projects can
+//! enable instead of ignoring the same words one by one
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+
+const BUILTIN_LISTS: [(&str, &str); 3] = [
+    ("cloud", include_str!("../data/wordlists/cloud.txt")),
+    (
+        "programming",
+        include_str!("../data/wordlists/programming.txt"),
+    ),
+    ("unix", include_str!("../data/wordlists/unix.txt")),
+];
+
+pub fn builtin_list_names() -> Vec<&'static str> {
+    BUILTIN_LISTS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Words of the builtin list with the given name, in lower case
+pub fn builtin_list(name: &str) -> Result<Vec<&'static str>> {
+    match BUILTIN_LISTS.iter().find(|(n, _)| *n == name) {
+        Some((_, contents)) => Ok(contents
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect()),
+        None => bail!(
+            "Unknown builtin list '{name}'\nAvailable lists: {}",
+            builtin_list_names().join(", ")
+        ),
+    }
+}
+
+/// Words from the enabled builtin lists
+#[derive(Debug, Default, Clone)]
+pub struct BuiltinWords {
+    words: HashSet<&'static str>,
+}
+
+impl BuiltinWords {
+    pub fn enable(&mut self, name: &str) -> Result<()> {
+        self.words.extend(builtin_list(name)?);
+        Ok(())
+    }
+
+    /// Lists only contain lower case words, but they may
+    /// start a sentence
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word) || self.words.contains(word.to_lowercase().as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests;
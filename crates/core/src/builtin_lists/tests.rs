@@ -0,0 +1,37 @@
+use super::*;
+
+#[test]
+fn test_builtin_lists_are_sorted_and_lower_case() {
+    for name in builtin_list_names() {
+        let words = builtin_list(name).unwrap();
+        assert!(!words.is_empty(), "{name} is empty");
+        let mut sorted = words.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(words, sorted, "{name} should be sorted without duplicates");
+        for word in words {
+            assert_eq!(word, word.to_lowercase(), "in {name}");
+        }
+    }
+}
+
+#[test]
+fn test_unknown_builtin_list() {
+    let err = builtin_list("no-such-list").unwrap_err();
+
+    let message = err.to_string();
+    assert!(message.contains("Unknown builtin list 'no-such-list'"));
+    assert!(message.contains("programming"));
+}
+
+#[test]
+fn test_enabled_lists() {
+    let mut words = BuiltinWords::default();
+    assert!(!words.contains("enum"));
+
+    words.enable("programming").unwrap();
+
+    assert!(words.contains("enum"));
+    assert!(words.contains("Enum"));
+    assert!(!words.contains("systemd"));
+}
@@ -1,27 +1,64 @@
-use crate::{Dictionary, IgnoreStore, Operation, TokenProcessor};
-use crate::{Project, RelativePath};
+use crate::atomic_write::write_atomically;
+use crate::encoding::{decode_to_utf8, detect_encoding, Encoding};
+use crate::file_lang::{is_same_lang, lang_tag};
+use crate::hooks::{HookEvent, HookOptions, PostOperationHook};
+use crate::ops_file::append_to_ops_file;
+use crate::output;
+use crate::po::{is_po_file, msgstr_ranges};
+use crate::report::{Report, ReportEntry, SeenFindings};
+use crate::tokens::{Columns, Token};
+use crate::{string_values, Dictionary, IgnoreStore, Operation, StructuredFormat, TokenProcessor};
+use crate::{Classification, IgnoreReason, Project, ProjectPath, RelativePath, TokenClassifier};
 use anyhow::{anyhow, bail, Context, Result};
 use directories_next::BaseDirs;
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::BufReader;
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 
 pub struct SpellingError {
     word: String,
     source_path: PathBuf,
     pos: (usize, usize),
+    columns: Columns,
+    identifier: String,
 }
 
 impl SpellingError {
+    /// `pos` is the line, starting at 1, and the byte offset in the line -
+    /// use with_columns() if the line may not be ASCII
     pub fn new(word: String, pos: (usize, usize), source_path: PathBuf) -> Self {
         Self {
+            identifier: word.clone(),
             word,
             pos,
+            columns: Columns::ascii(pos.1),
             source_path,
         }
     }
 
+    pub fn with_columns(mut self, columns: Columns) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// The whole token the word was extracted from, like
+    /// `XgboostClassifier` for `Xgboost` - the word itself by default
+    pub fn with_identifier(mut self, identifier: &str) -> Self {
+        self.identifier = identifier.to_string();
+        self
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Where the word starts, in bytes, chars and UTF-16 code units
+    pub fn columns(&self) -> Columns {
+        self.columns
+    }
+
     pub fn word(&self) -> &str {
         &self.word
     }
@@ -33,11 +70,29 @@ impl SpellingError {
     pub fn pos(&self) -> (usize, usize) {
         self.pos
     }
+
+    /// True for the errors found by Checker::process_file_name(), which
+    /// are on line 0 - the lines of the files start at 1
+    pub fn is_file_name(&self) -> bool {
+        self.pos.0 == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    // Matched one of the patterns in the skip file
+    SkipFile,
+    // Larger than the project's maximum file size
+    TooLarge,
+    // Extension not in the list of extensions to check
+    Extension,
+    // Not in UTF-8, nor in an encoding given by a byte order mark
+    Encoding,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ProcessOutcome {
-    Skipped,
+    Skipped(SkipReason),
     Checked,
 }
 
@@ -49,8 +104,7 @@ pub trait Checker<D: Dictionary> {
     fn project(&self) -> &Project;
 
     fn to_relative_path(&self, path: &Path) -> Result<RelativePath> {
-        let project_path = self.project().path();
-        RelativePath::new(project_path, path)
+        self.project().get_relative_path(path)
     }
 
     // Were all the errors handled properly?
@@ -58,6 +112,10 @@ pub trait Checker<D: Dictionary> {
 
     fn ignore_store(&mut self) -> &mut IgnoreStore;
 
+    /// Used by process() to sort the tokens - usually built from the
+    /// dictionary and the ignore store of the checker
+    fn classifier(&self) -> TokenClassifier<'_, D>;
+
     fn state(&mut self) -> Option<&mut CheckerState> {
         None
     }
@@ -67,102 +125,477 @@ pub trait Checker<D: Dictionary> {
         source_path: &Path,
         context: &Self::SourceContext,
     ) -> Result<ProcessOutcome> {
-        let skip_file = self.project().skip_file();
         let relative_path = self.to_relative_path(source_path)?;
-        if skip_file.is_skipped(&relative_path) {
-            return Ok(ProcessOutcome::Skipped);
-        }
-        let file = File::open(source_path)?;
-        let reader = BufReader::new(&file);
-        let file_name = source_path
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy();
-        let mut token_processor = TokenProcessor::new(reader, &file_name);
-        let skipped_tokens = self.ignore_store().skipped_tokens(&relative_path);
+        let assumed_extension = self
+            .ignore_store()
+            .assumed_extension(&relative_path)
+            .map(|e| e.to_string());
+        let extension = assumed_extension
+            .clone()
+            .or_else(|| relative_path.extension().map(|e| e.into_owned()));
+        let mut skip_reason = None;
+        if self.should_skip(&relative_path) {
+            skip_reason = Some(SkipReason::SkipFile);
+        } else if !self.project().is_checked_extension(extension.as_deref()) {
+            skip_reason = Some(SkipReason::Extension);
+        } else {
+            let max_file_size = self.project().max_file_size();
+            if max_file_size != 0 {
+                let metadata = std::fs::metadata(source_path).with_context(|| {
+                    format!("Could not read metadata of {}", source_path.display())
+                })?;
+                if metadata.len() > max_file_size {
+                    skip_reason = Some(SkipReason::TooLarge);
+                }
+            }
+        }
+        let mut bytes = vec![];
+        if skip_reason.is_none() {
+            bytes = std::fs::read(source_path)?;
+            let detected = detect_encoding(&bytes);
+            let transcode = detected.bom || self.ignore_store().transcode();
+            if detected.encoding != Encoding::Utf8 {
+                self.on_encoding(&relative_path, detected.encoding);
+                if transcode {
+                    output::verbose(&format!(
+                        "{relative_path}: decoded from {}, positions are in the decoded text",
+                        detected.encoding.name()
+                    ));
+                } else if self.ignore_store().strict_utf8() {
+                    bail!(
+                        "Error when reading: '{relative_path}': not valid UTF-8, looks like {}",
+                        detected.encoding.name()
+                    );
+                } else {
+                    skip_reason = Some(SkipReason::Encoding);
+                }
+            }
+            if transcode {
+                bytes = decode_to_utf8(&bytes, detected.encoding).into_owned();
+            }
+        }
+        if let Some(reason) = skip_reason {
+            let outcome = ProcessOutcome::Skipped(reason);
+            self.on_file_done(&relative_path, &outcome);
+            return Ok(outcome);
+        }
+        self.on_file_start(&relative_path);
+        let lang = lang_tag(source_path);
+        if !self.select_lang(lang.as_deref()) {
+            output::verbose(&format!(
+                "Warning: {relative_path}: tagged as '{}', but there is no dictionary for it, checking with '{}'",
+                lang.unwrap_or_default(),
+                self.dictionary().lang()
+            ));
+        }
+        // With an assumed extension, the file is read as if it was named
+        // like this - for instance to extract words as for Python code
+        let file_name = Path::new(source_path.file_name().unwrap_or_default());
+        let file_name = match &assumed_extension {
+            Some(e) => file_name.with_extension(e),
+            None => file_name.to_path_buf(),
+        };
+        let file_name = file_name.to_string_lossy();
+        let mut token_processor = TokenProcessor::new(bytes.as_slice(), &file_name);
+        let ignore_store = self.ignore_store();
+        let skipped_tokens = ignore_store.skipped_tokens(&relative_path);
         token_processor.skip_tokens(&skipped_tokens);
-        for token in token_processor {
-            let token = token?;
-            self.handle_token(&token.text, &relative_path, token.pos, context)?;
+        token_processor.skip_tokens(&ignore_store.ignored_identifiers());
+        token_processor.set_skip_front_matter(ignore_store.skip_front_matter());
+        token_processor.skip_regions(ignore_store.skipped_regions());
+        token_processor.set_strict_utf8(ignore_store.strict_utf8());
+        // Only the translations of gettext catalogs are in the language
+        // of the dictionary
+        if is_po_file(&file_name) {
+            let text = String::from_utf8_lossy(&bytes);
+            token_processor.set_string_values(msgstr_ranges(&text, ignore_store.po_options()));
         }
+        if ignore_store.string_values_only() {
+            if let Some(format) = StructuredFormat::from_file_name(&file_name) {
+                let values = std::str::from_utf8(&bytes)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|text| string_values(format, text));
+                match values {
+                    Ok(values) => token_processor.set_string_values(values),
+                    Err(e) => output::warning(&format!(
+                        "Warning: {relative_path}: could not parse {} ({e}), checking the whole file",
+                        format.name()
+                    )),
+                }
+            }
+        }
+        let max_ratio = ignore_store.max_line_error_ratio();
+        let mut line_tokens: Vec<LineToken> = vec![];
+        loop {
+            let token = token_processor.next().transpose()?;
+            let line_done = match (&token, line_tokens.first()) {
+                (_, None) => false,
+                (Some(token), Some(first)) => token.pos.0 != first.token.pos.0,
+                (None, Some(_)) => true,
+            };
+            if line_done {
+                let tokens = std::mem::take(&mut line_tokens);
+                handle_line(self, tokens, &relative_path, max_ratio, context)?;
+                // Handling the errors may have added a skip pattern matching
+                // this file - for instance in the interactive checker
+                if self.ignore_store().is_skipped(&relative_path) {
+                    let outcome = ProcessOutcome::Skipped(SkipReason::SkipFile);
+                    self.on_file_done(&relative_path, &outcome);
+                    return Ok(outcome);
+                }
+            }
+            let token = match token {
+                Some(token) => token,
+                None => break,
+            };
+            let unknown = self.is_unknown(&token, &relative_path)?;
+            line_tokens.push(LineToken { token, unknown });
+        }
+        if let Some(line) = token_processor.unterminated_region() {
+            output::warning(&format!(
+                "Warning: {relative_path}:{line}: skipped region is never closed, ignoring the rest of the file"
+            ));
+        }
+        match token_processor.invalid_utf8_lines() {
+            0 => (),
+            1 => output::warning(&format!(
+                "Warning: {relative_path}: one line is not valid UTF-8, invalid bytes were ignored"
+            )),
+            n => output::warning(&format!(
+                "Warning: {relative_path}: {n} lines are not valid UTF-8, invalid bytes were ignored"
+            )),
+        }
+        self.on_file_done(&relative_path, &ProcessOutcome::Checked);
         Ok(ProcessOutcome::Checked)
     }
 
-    fn handle_error(&mut self, error: &SpellingError, context: &Self::SourceContext) -> Result<()>;
+    /// Check the names of the files in `paths`, and of the directories
+    /// containing them, once each. The names of the skipped files and
+    /// of the files outside the project are not checked
+    fn process_file_names(
+        &mut self,
+        paths: &[PathBuf],
+        context: &Self::SourceContext,
+    ) -> Result<()> {
+        let mut seen = HashSet::new();
+        for path in paths {
+            let relative_path = self.to_relative_path(path)?;
+            if relative_path.is_outside_project() || self.should_skip(&relative_path) {
+                continue;
+            }
+            let normalized = relative_path.normalize();
+            let components: Vec<_> = normalized.split('/').collect();
+            for end in 1..=components.len() {
+                let prefix = components[..end].join("/");
+                if !seen.insert(prefix.clone()) {
+                    continue;
+                }
+                let is_file = end == components.len();
+                let name_path = RelativePath::from_path_unchecked(PathBuf::from(prefix));
+                self.process_file_name(&name_path, is_file, context)?;
+            }
+        }
+        Ok(())
+    }
 
-    fn handle_token(
+    /// Check the words of the last component of `relative_path`, without
+    /// its extension for files. The errors are on line 0, column 0, see
+    /// SpellingError::is_file_name()
+    fn process_file_name(
         &mut self,
-        token: &str,
         relative_path: &RelativePath,
-        pos: (usize, usize),
+        is_file: bool,
         context: &Self::SourceContext,
     ) -> Result<()> {
-        let dictionary = self.dictionary();
-        let lang = dictionary.lang().to_owned();
-        let in_dict = dictionary.check(token)?;
-        if in_dict {
-            return Ok(());
+        let path = relative_path.as_ref();
+        let name = match is_file {
+            true => path.file_stem(),
+            false => path.file_name(),
+        };
+        let name = name.unwrap_or_default().to_string_lossy().into_owned();
+        // No file name, so that the words are extracted as in plain text
+        let mut token_processor = TokenProcessor::new(name.as_bytes(), "");
+        token_processor.skip_tokens(&self.ignore_store().ignored_identifiers());
+        while let Some(mut token) = token_processor.next().transpose()? {
+            token.pos = (0, 0);
+            token.columns = Columns::ascii(0);
+            self.handle_token(&token, relative_path, context)?;
         }
-        let should_ignore = self
-            .ignore_store()
-            .should_ignore(token, relative_path, &lang);
-        if should_ignore {
-            return Ok(());
+        Ok(())
+    }
+
+    /// Checked by process() before each file, so that skip patterns
+    /// added during the run are taken into account
+    fn should_skip(&mut self, relative_path: &RelativePath) -> bool {
+        self.project().skip_file().is_skipped(relative_path)
+            || self.ignore_store().is_skipped(relative_path)
+    }
+
+    /// Called by process() before reading a file that is not skipped
+    fn on_file_start(&mut self, _relative_path: &RelativePath) {}
+
+    /// Called by process() for the files that are not in UTF-8, before
+    /// they are decoded or skipped
+    fn on_encoding(&mut self, _relative_path: &RelativePath, _encoding: Encoding) {}
+
+    /// Called by process() with the language the file is tagged with, see
+    /// lang_tag(). Checkers that can open other dictionaries use the one
+    /// for this language until the next file. Returns false when the file
+    /// is checked with the default dictionary instead
+    fn select_lang(&mut self, lang: Option<&str>) -> bool {
+        match lang {
+            None => true,
+            Some(lang) => is_same_lang(lang, self.dictionary().lang()),
         }
-        let path = relative_path.as_ref();
-        let error = SpellingError::new(token.to_owned(), pos, path.to_path_buf());
-        self.handle_error(&error, context)?;
+    }
+
+    /// Called by process() once a file is checked or skipped - but not
+    /// if reading it failed
+    fn on_file_done(&mut self, _relative_path: &RelativePath, _outcome: &ProcessOutcome) {}
+
+    fn handle_error(&mut self, error: &SpellingError, context: &Self::SourceContext) -> Result<()>;
+
+    /// Called by process() instead of handle_error() for the words only
+    /// ignored by rules outside the strict scopes, see
+    /// IgnoreStore::set_strict_scopes()
+    fn handle_suppressed(
+        &mut self,
+        _error: &SpellingError,
+        _reason: &IgnoreReason,
+        _context: &Self::SourceContext,
+    ) -> Result<()> {
         Ok(())
     }
 
+    /// Called by process() instead of reporting the errors of a line
+    /// with too many unknown words, see `max_line_error_ratio`
+    fn on_line_skipped(&mut self, _relative_path: &RelativePath, _line: usize) {}
+
+    /// True if the token is neither in the dictionary nor ignored
+    fn is_unknown(&self, token: &Token, relative_path: &RelativePath) -> Result<bool> {
+        let classification = self.classifier().classify_token(token, relative_path)?;
+        Ok(classification.is_unknown())
+    }
+
+    fn handle_token(
+        &mut self,
+        token: &Token,
+        relative_path: &RelativePath,
+        context: &Self::SourceContext,
+    ) -> Result<()> {
+        let classification = self.classifier().classify_token(token, relative_path)?;
+        let path = relative_path.as_ref();
+        let error = || {
+            SpellingError::new(token.text.clone(), token.pos, path.to_path_buf())
+                .with_columns(token.columns)
+                .with_identifier(&token.identifier)
+        };
+        match classification {
+            Classification::Error => self.handle_error(&error(), context),
+            Classification::Suppressed(reason) => {
+                self.handle_suppressed(&error(), &reason, context)
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn apply_operation(&mut self, mut operation: Operation) -> Result<()> {
+        let project_path = self.project().path().clone();
         let store = self.ignore_store();
         operation.execute(store)?;
         if let Some(state) = self.state() {
-            state.set_last_operation(operation.clone())?;
+            state.set_last_operation(operation.clone(), &project_path)?;
         }
         Ok(())
     }
 
     fn undo(&mut self) -> Result<()> {
+        let project_path = self.project().path().clone();
         let state = match self.state() {
             None => bail!("Cannot undo"),
             Some(s) => s,
         };
-        let last_operation = state.pop_last_operation()?;
-        let mut last_operation = match last_operation {
+        let mut last_operation = match state.last_operation(&project_path) {
             None => bail!("Nothing to undo"),
-            Some(o) => o,
+            Some(e) => e.operation.clone(),
         };
         let store = self.ignore_store();
-        last_operation.undo(store)
+        last_operation.ensure_applied(store)?;
+        last_operation.undo(store)?;
+        // Only mark the operation as undone once the store is updated,
+        // so that a failed undo can be retried
+        if let Some(state) = self.state() {
+            state.pop_last_operation(&project_path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Lines with fewer tokens are always checked, see `max_line_error_ratio`
+pub const MIN_TOKENS_FOR_ERROR_RATIO: usize = 5;
+
+struct LineToken {
+    token: Token,
+    unknown: bool,
+}
+
+// Report the unknown tokens of a line, unless there are so many of them
+// that the line is probably not text - like base64 or a hash dump
+fn handle_line<C, D>(
+    checker: &mut C,
+    tokens: Vec<LineToken>,
+    relative_path: &RelativePath,
+    max_ratio: f64,
+    context: &C::SourceContext,
+) -> Result<()>
+where
+    C: Checker<D> + ?Sized,
+    D: Dictionary,
+{
+    let unknown = tokens.iter().filter(|t| t.unknown).count();
+    if is_dense(unknown, tokens.len(), max_ratio) {
+        let line = tokens.first().map(|t| t.token.pos.0).unwrap_or_default();
+        checker.on_line_skipped(relative_path, line);
+        return Ok(());
     }
+    for token in tokens.iter().filter(|t| t.unknown) {
+        // Checked again, because handling the previous errors may have
+        // changed the ignore lists
+        checker.handle_token(&token.token, relative_path, context)?;
+        if checker.ignore_store().is_skipped(relative_path) {
+            break;
+        }
+    }
+    Ok(())
 }
 
+fn is_dense(unknown: usize, total: usize, max_ratio: f64) -> bool {
+    total >= MIN_TOKENS_FOR_ERROR_RATIO && unknown as f64 / total as f64 > max_ratio
+}
+
+/// Maximum number of operations kept in the state file
+const MAX_HISTORY_SIZE: usize = 1000;
+
+/// Set this environment variable to store the state somewhere else than
+/// in the user's data directory
+pub const SKYSPELL_STATE_DIR: &str = "SKYSPELL_STATE_DIR";
+
 pub struct CheckerState {
-    storage_path: PathBuf,
+    // None for a state that only lives in memory
+    storage_path: Option<PathBuf>,
     inner: StateInner,
+    post_operation_hook: Option<PostOperationHook>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    #[serde(with = "crate::operations::versioned")]
+    pub operation: Operation,
+    // Unix timestamp, in seconds. None for operations recorded by
+    // older versions of skyspell
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+    #[serde(default)]
+    pub undone: bool,
+    // Canonical path of the project the operation was performed for.
+    // None for global operations, which can be undone from any project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn is_global(&self) -> bool {
+        self.project.is_none()
+    }
+
+    fn applies_to(&self, project_path: &ProjectPath) -> bool {
+        match &self.project {
+            None => true,
+            Some(p) => p == &project_path.as_str(),
+        }
+    }
+
+    pub fn date(&self) -> Option<String> {
+        let timestamp = self.timestamp?;
+        let date_time = OffsetDateTime::from_unix_timestamp(timestamp).ok()?;
+        date_time.format(&Rfc3339).ok()
+    }
+}
+
+// A row of the history in the state file. Rows that cannot be read -
+// written by a newer version of skyspell, or edited by hand - are kept
+// as they are, so that a single bad row does not make the whole history
+// unusable
+#[derive(Debug, Clone)]
+enum HistoryRow {
+    Entry(HistoryEntry),
+    Unreadable { value: toml::Value, error: String },
+}
+
+impl HistoryRow {
+    fn entry(&self) -> Option<&HistoryEntry> {
+        match self {
+            HistoryRow::Entry(e) => Some(e),
+            HistoryRow::Unreadable { .. } => None,
+        }
+    }
+}
+
+impl Serialize for HistoryRow {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            HistoryRow::Entry(e) => e.serialize(serializer),
+            HistoryRow::Unreadable { value, .. } => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HistoryRow {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = toml::Value::deserialize(deserializer)?;
+        let row = match HistoryEntry::deserialize(value.clone()) {
+            Ok(e) => HistoryRow::Entry(e),
+            Err(e) => HistoryRow::Unreadable {
+                value,
+                error: e.message().trim().to_string(),
+            },
+        };
+        Ok(row)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Default)]
 struct StateInner {
+    // Only kept so that we can read state files written by older versions
+    #[serde(default, skip_serializing)]
     last_operation: Option<Operation>,
+    #[serde(default)]
+    history: Vec<HistoryRow>,
+    // Last report for each project, by canonical project path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    reports: BTreeMap<String, Report>,
+    // Ops file where operations are appended, by canonical project path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    recordings: BTreeMap<String, PathBuf>,
+    // Findings of the last `check --only-new`, by canonical project path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    seen: BTreeMap<String, SeenFindings>,
 }
 
 impl CheckerState {
+    /// Load the state from `state_toml`, or from the state directory -
+    /// see state_dir()
     pub fn load(state_toml: Option<PathBuf>) -> Result<Self> {
         let state_toml = match state_toml {
             None => {
-                let base_dirs =
-                    BaseDirs::new().ok_or_else(|| anyhow!("Could not get home directory"))?;
-                let data_dir = base_dirs.data_dir().join("skyspell");
+                let data_dir = state_dir(std::env::var_os(SKYSPELL_STATE_DIR))?;
                 std::fs::create_dir_all(&data_dir)
                     .with_context(|| format!("Could not create data dir {}", data_dir.display()))?;
                 data_dir.join("state.toml")
             }
             Some(p) => p,
         };
-        let inner: StateInner = if state_toml.exists() {
+        let mut inner: StateInner = if state_toml.exists() {
             let contents = std::fs::read_to_string(&state_toml)
                 .with_context(|| format!("Could not read from {}", state_toml.display()))?;
             toml::from_str(&contents)
@@ -170,29 +603,270 @@ impl CheckerState {
         } else {
             Default::default()
         };
+        if let Some(operation) = inner.last_operation.take() {
+            inner.history.push(HistoryRow::Entry(HistoryEntry {
+                operation,
+                timestamp: None,
+                undone: false,
+                project: None,
+            }));
+        }
 
         Ok(CheckerState {
-            storage_path: state_toml,
+            storage_path: Some(state_toml),
             inner,
+            post_operation_hook: None,
         })
     }
 
-    pub fn set_last_operation(&mut self, operation: Operation) -> Result<()> {
-        self.inner.last_operation = Some(operation);
+    /// A state that is never read from nor written to disk: the history,
+    /// the reports and the caches only last as long as the process
+    pub fn in_memory() -> Self {
+        CheckerState {
+            storage_path: None,
+            inner: StateInner::default(),
+            post_operation_hook: None,
+        }
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.storage_path.is_some()
+    }
+
+    /// Run `command` each time an operation is recorded or undone
+    pub fn set_post_operation_hook(&mut self, command: Option<&str>, options: HookOptions) {
+        self.post_operation_hook = command.map(|c| PostOperationHook::new(c, options));
+    }
+
+    fn run_post_operation_hook(
+        &self,
+        operation: &Operation,
+        event: HookEvent,
+        project_path: &ProjectPath,
+    ) -> Result<()> {
+        match &self.post_operation_hook {
+            Some(hook) => hook.run(operation, event, project_path),
+            None => Ok(()),
+        }
+    }
+
+    pub fn set_last_operation(
+        &mut self,
+        operation: Operation,
+        project_path: &ProjectPath,
+    ) -> Result<()> {
+        if let Some(ops_file) = self.recording(project_path) {
+            append_to_ops_file(ops_file, &operation)?;
+        }
+        self.push_history(operation.clone(), project_path)?;
+        self.run_post_operation_hook(&operation, HookEvent::Execute, project_path)
+    }
+
+    /// Like set_last_operation(), for operations read from an ops file:
+    /// they are not appended to the same file again
+    pub fn set_last_operation_from(
+        &mut self,
+        operation: Operation,
+        project_path: &ProjectPath,
+        ops_file: &Path,
+    ) -> Result<()> {
+        if let Some(recording) = self.recording(project_path) {
+            if !is_same_file(recording, ops_file) {
+                append_to_ops_file(recording, &operation)?;
+            }
+        }
+        self.push_history(operation.clone(), project_path)?;
+        self.run_post_operation_hook(&operation, HookEvent::Execute, project_path)
+    }
+
+    fn push_history(&mut self, operation: Operation, project_path: &ProjectPath) -> Result<()> {
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let project = if operation.is_global() {
+            None
+        } else {
+            Some(project_path.as_str().to_string())
+        };
+        self.inner.history.push(HistoryRow::Entry(HistoryEntry {
+            operation,
+            timestamp: Some(timestamp),
+            undone: false,
+            project,
+        }));
+        let size = self.inner.history.len();
+        if size > MAX_HISTORY_SIZE {
+            self.inner.history.drain(..size - MAX_HISTORY_SIZE);
+        }
         self.save()
     }
 
-    pub fn pop_last_operation(&mut self) -> Result<Option<Operation>> {
-        let result = self.inner.last_operation.take();
+    /// Last operation that was not undone yet, either global or performed
+    /// for the given project
+    pub fn last_operation(&self, project_path: &ProjectPath) -> Option<&HistoryEntry> {
+        self.history()
+            .find(|e| !e.undone && e.applies_to(project_path))
+    }
+
+    /// Mark the last operation that was not undone yet as undone, and return it.
+    /// Only global operations and operations performed for the given project are considered
+    pub fn pop_last_operation(&mut self, project_path: &ProjectPath) -> Result<Option<Operation>> {
+        let res = self.discard_last_operation(project_path)?;
+        if let Some(operation) = &res {
+            self.run_post_operation_hook(operation, HookEvent::Undo, project_path)?;
+        }
+        Ok(res)
+    }
+
+    /// Like pop_last_operation(), when the operation was not undone in the
+    /// ignore store - for instance because the ignore files were edited by hand.
+    /// Unreadable rows of the history are skipped with a warning
+    pub fn discard_last_operation(
+        &mut self,
+        project_path: &ProjectPath,
+    ) -> Result<Option<Operation>> {
+        let mut result = None;
+        for row in self.inner.history.iter_mut().rev() {
+            match row {
+                HistoryRow::Unreadable { error, .. } => {
+                    output::warning(&format!("Skipping unreadable history entry: {error}"));
+                }
+                HistoryRow::Entry(entry) => {
+                    if !entry.undone && entry.applies_to(project_path) {
+                        entry.undone = true;
+                        result = Some(entry.operation.clone());
+                        break;
+                    }
+                }
+            }
+        }
         self.save()?;
         Ok(result)
     }
 
+    /// Save the errors found in the project, replacing the previous report.
+    /// A run without errors just removes it
+    pub fn save_report(
+        &mut self,
+        project_path: &ProjectPath,
+        entries: Vec<ReportEntry>,
+    ) -> Result<()> {
+        let key = project_path.as_str().to_string();
+        if entries.is_empty() {
+            self.inner.reports.remove(&key);
+        } else {
+            let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+            let report = Report::new(project_path, entries, timestamp);
+            self.inner.reports.insert(key, report);
+        }
+        self.save()
+    }
+
+    /// The last report saved for the project - fails if the files
+    /// containing errors changed since then
+    pub fn report(&self, project_path: &ProjectPath) -> Result<&Report> {
+        let report = match self.last_report(project_path) {
+            Some(r) => r,
+            None => bail!(
+                "No report found for this project - run `skyspell check --non-interactive --save-report` first"
+            ),
+        };
+        if report.is_stale(project_path) {
+            bail!("The report is out of date because some files changed - run `skyspell check --non-interactive --save-report` again");
+        }
+        Ok(report)
+    }
+
+    /// The last report saved for the project, even if the files changed since
+    pub fn last_report(&self, project_path: &ProjectPath) -> Option<&Report> {
+        self.inner.reports.get(project_path.as_str().as_ref())
+    }
+
+    /// Findings saved by the last `check --only-new` in the project
+    pub fn seen_findings(&self, project_path: &ProjectPath) -> Option<&SeenFindings> {
+        self.inner.seen.get(project_path.as_str().as_ref())
+    }
+
+    /// Replace the saved findings of the project. Saving no findings
+    /// just removes them
+    pub fn save_seen_findings(
+        &mut self,
+        project_path: &ProjectPath,
+        findings: SeenFindings,
+    ) -> Result<()> {
+        let key = project_path.as_str().to_string();
+        if findings.is_empty() {
+            self.inner.seen.remove(&key);
+        } else {
+            self.inner.seen.insert(key, findings);
+        }
+        self.save()
+    }
+
+    /// Append every operation performed in the project to the given ops file
+    pub fn start_recording(&mut self, project_path: &ProjectPath, ops_file: &Path) -> Result<()> {
+        let key = project_path.as_str().to_string();
+        self.inner.recordings.insert(key, ops_file.to_path_buf());
+        self.save()
+    }
+
+    /// Return the ops file operations were appended to, if any
+    pub fn stop_recording(&mut self, project_path: &ProjectPath) -> Result<Option<PathBuf>> {
+        let res = self.inner.recordings.remove(project_path.as_str().as_ref());
+        self.save()?;
+        Ok(res)
+    }
+
+    pub fn recording(&self, project_path: &ProjectPath) -> Option<&Path> {
+        self.inner
+            .recordings
+            .get(project_path.as_str().as_ref())
+            .map(|p| p.as_path())
+    }
+
+    /// The directory containing the state file, if any
+    pub fn data_dir(&self) -> Option<&Path> {
+        let storage_path = self.storage_path.as_ref()?;
+        Some(storage_path.parent().unwrap_or(Path::new(".")))
+    }
+
+    /// Recorded operations, newest first
+    pub fn history(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.inner.history.iter().rev().filter_map(|r| r.entry())
+    }
+
+    // Like the ignore files, so that an interrupted save never leaves
+    // a truncated history behind
     fn save(&self) -> Result<()> {
+        let storage_path = match &self.storage_path {
+            Some(p) => p,
+            None => return Ok(()),
+        };
         let contents = toml_edit::ser::to_string_pretty(&self.inner)
             .with_context(|| "Could not serialize state")?;
-        std::fs::write(&self.storage_path, contents)
+        write_atomically(storage_path, contents.as_bytes())
             .with_context(|| "Could not write to storage path")?;
         Ok(())
     }
 }
+
+/// Where the state is stored: the value of SKYSPELL_STATE_DIR if set,
+/// the skyspell directory in the user's data directory otherwise
+pub fn state_dir(env_value: Option<std::ffi::OsString>) -> Result<PathBuf> {
+    match env_value {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => {
+            let base_dirs =
+                BaseDirs::new().ok_or_else(|| anyhow!("Could not get home directory"))?;
+            Ok(base_dirs.data_dir().join("skyspell"))
+        }
+    }
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,296 @@
+use crate::tests::{get_test_dir, new_project_path};
+
+use super::*;
+
+#[test]
+fn test_history_is_kept_after_undo() {
+    let temp_dir = get_test_dir();
+    let state_toml = temp_dir.path().join("state.toml");
+    let project = new_project_path(&temp_dir, "project");
+    let mut state = CheckerState::load(Some(state_toml.clone())).unwrap();
+    state
+        .set_last_operation(Operation::new_ignore("foo"), &project)
+        .unwrap();
+    state
+        .set_last_operation(Operation::new_ignore_for_project("bar"), &project)
+        .unwrap();
+
+    let popped = state.pop_last_operation(&project).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore_for_project("bar")));
+
+    let state = CheckerState::load(Some(state_toml)).unwrap();
+    let history: Vec<_> = state.history().collect();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].operation.word(), "bar");
+    assert!(history[0].undone);
+    assert!(history[0].timestamp.is_some());
+    assert_eq!(history[1].operation.word(), "foo");
+    assert!(!history[1].undone);
+}
+
+#[test]
+fn test_undo_skips_undone_operations() {
+    let temp_dir = get_test_dir();
+    let state_toml = temp_dir.path().join("state.toml");
+    let project = new_project_path(&temp_dir, "project");
+    let mut state = CheckerState::load(Some(state_toml)).unwrap();
+    state
+        .set_last_operation(Operation::new_ignore("foo"), &project)
+        .unwrap();
+    state
+        .set_last_operation(Operation::new_ignore("bar"), &project)
+        .unwrap();
+
+    state.pop_last_operation(&project).unwrap();
+    let popped = state.pop_last_operation(&project).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore("foo")));
+
+    assert!(state.pop_last_operation(&project).unwrap().is_none());
+}
+
+#[test]
+fn test_read_state_written_by_older_versions() {
+    let temp_dir = get_test_dir();
+    let state_toml = temp_dir.path().join("state.toml");
+    std::fs::write(
+        &state_toml,
+        r#"
+        [last_operation.Ignore]
+        word = "foo"
+        "#,
+    )
+    .unwrap();
+
+    let project = new_project_path(&temp_dir, "project");
+    let mut state = CheckerState::load(Some(state_toml)).unwrap();
+
+    let entry = state.history().next().unwrap();
+    assert_eq!(entry.timestamp, None);
+    assert_eq!(entry.date(), None);
+    let popped = state.pop_last_operation(&project).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore("foo")));
+}
+
+#[test]
+fn test_undo_only_reverts_operations_for_the_current_project() {
+    let temp_dir = get_test_dir();
+    let state_toml = temp_dir.path().join("state.toml");
+    let project_a = new_project_path(&temp_dir, "a");
+    let project_b = new_project_path(&temp_dir, "b");
+    let mut state = CheckerState::load(Some(state_toml.clone())).unwrap();
+    state
+        .set_last_operation(Operation::new_ignore_for_project("foo"), &project_a)
+        .unwrap();
+    state
+        .set_last_operation(Operation::new_ignore_for_project("bar"), &project_b)
+        .unwrap();
+    state
+        .set_last_operation(Operation::new_ignore_for_project("baz"), &project_a)
+        .unwrap();
+
+    let mut state = CheckerState::load(Some(state_toml)).unwrap();
+    let popped = state.pop_last_operation(&project_b).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore_for_project("bar")));
+    assert!(state.pop_last_operation(&project_b).unwrap().is_none());
+
+    let popped = state.pop_last_operation(&project_a).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore_for_project("baz")));
+    let popped = state.pop_last_operation(&project_a).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore_for_project("foo")));
+}
+
+#[test]
+fn test_global_operations_can_be_undone_from_any_project() {
+    let temp_dir = get_test_dir();
+    let state_toml = temp_dir.path().join("state.toml");
+    let project_a = new_project_path(&temp_dir, "a");
+    let project_b = new_project_path(&temp_dir, "b");
+    let mut state = CheckerState::load(Some(state_toml)).unwrap();
+    state
+        .set_last_operation(Operation::new_ignore("foo"), &project_a)
+        .unwrap();
+    state
+        .set_last_operation(Operation::new_ignore_for_project("bar"), &project_a)
+        .unwrap();
+
+    let entry = state.last_operation(&project_b).unwrap();
+    assert!(entry.is_global());
+    assert_eq!(entry.operation, Operation::new_ignore("foo"));
+
+    let entry = state.last_operation(&project_a).unwrap();
+    assert!(!entry.is_global());
+
+    let popped = state.pop_last_operation(&project_b).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore("foo")));
+    let popped = state.pop_last_operation(&project_a).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore_for_project("bar")));
+}
+
+#[test]
+fn test_recording_operations() {
+    let temp_dir = get_test_dir();
+    let state_toml = temp_dir.path().join("state.toml");
+    let ops_toml = temp_dir.path().join("ops.toml");
+    let project_a = new_project_path(&temp_dir, "a");
+    let project_b = new_project_path(&temp_dir, "b");
+    let mut state = CheckerState::load(Some(state_toml.clone())).unwrap();
+    state.start_recording(&project_a, &ops_toml).unwrap();
+
+    let mut state = CheckerState::load(Some(state_toml)).unwrap();
+    assert_eq!(state.recording(&project_a), Some(ops_toml.as_path()));
+    state
+        .set_last_operation(Operation::new_ignore("foo"), &project_a)
+        .unwrap();
+    state
+        .set_last_operation(Operation::new_ignore("bar"), &project_b)
+        .unwrap();
+    // Already in the ops file
+    state
+        .set_last_operation_from(Operation::new_ignore("baz"), &project_a, &ops_toml)
+        .unwrap();
+    let stopped = state.stop_recording(&project_a).unwrap();
+    state
+        .set_last_operation(Operation::new_ignore("qux"), &project_a)
+        .unwrap();
+
+    assert_eq!(stopped, Some(ops_toml.clone()));
+    assert_eq!(
+        crate::load_ops_file(&ops_toml).unwrap(),
+        [Operation::new_ignore("foo")]
+    );
+    assert_eq!(state.history().count(), 4);
+}
+
+#[test]
+fn test_state_dir_from_the_environment() {
+    let temp_dir = get_test_dir();
+    let state_dir = temp_dir.path().join("state");
+    let project = new_project_path(&temp_dir, "project");
+    // No other test loads the state from the default location
+    std::env::set_var(SKYSPELL_STATE_DIR, &state_dir);
+
+    let mut state = CheckerState::load(None).unwrap();
+    state
+        .set_last_operation(Operation::new_ignore("foo"), &project)
+        .unwrap();
+    std::env::remove_var(SKYSPELL_STATE_DIR);
+
+    assert_eq!(state.data_dir(), Some(state_dir.as_path()));
+    let mut state = CheckerState::load(Some(state_dir.join("state.toml"))).unwrap();
+    let popped = state.pop_last_operation(&project).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore("foo")));
+}
+
+#[test]
+fn test_empty_state_dir_is_ignored() {
+    let state_dir = state_dir(Some("".into())).unwrap();
+
+    assert!(state_dir.ends_with("skyspell"));
+}
+
+#[test]
+fn test_in_memory_state() {
+    let temp_dir = get_test_dir();
+    let project = new_project_path(&temp_dir, "project");
+    let mut state = CheckerState::in_memory();
+
+    state
+        .set_last_operation(Operation::new_ignore("foo"), &project)
+        .unwrap();
+
+    assert!(!state.is_persistent());
+    assert_eq!(state.data_dir(), None);
+    let popped = state.pop_last_operation(&project).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore("foo")));
+    let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+    assert_eq!(entries.len(), 1, "only the project should exist");
+}
+
+#[test]
+fn test_unversioned_history_is_upgraded_on_save() {
+    let temp_dir = get_test_dir();
+    let state_toml = temp_dir.path().join("state.toml");
+    std::fs::write(
+        &state_toml,
+        r#"
+        [[history]]
+        timestamp = 1700000000
+        undone = false
+        [history.operation.Ignore]
+        word = "foo"
+        "#,
+    )
+    .unwrap();
+    let project = new_project_path(&temp_dir, "project");
+    let mut state = CheckerState::load(Some(state_toml.clone())).unwrap();
+    assert_eq!(
+        state.last_operation(&project).unwrap().operation,
+        Operation::new_ignore("foo")
+    );
+
+    state
+        .set_last_operation(Operation::new_ignore("bar"), &project)
+        .unwrap();
+
+    let contents = std::fs::read_to_string(&state_toml).unwrap();
+    assert_eq!(contents.matches("v = 1").count(), 2, "{contents}");
+    let state = CheckerState::load(Some(state_toml)).unwrap();
+    let words: Vec<_> = state.history().map(|e| e.operation.word()).collect();
+    assert_eq!(words, &["bar", "foo"]);
+}
+
+#[test]
+fn test_undo_skips_unreadable_history_entries() {
+    let temp_dir = get_test_dir();
+    let state_toml = temp_dir.path().join("state.toml");
+    std::fs::write(
+        &state_toml,
+        r#"
+        [[history]]
+        undone = false
+        [history.operation]
+        v = 1
+        [history.operation.op.Ignore]
+        word = "foo"
+
+        [[history]]
+        undone = false
+        [history.operation]
+        v = 1
+        [history.operation.op.NoSuchOperation]
+        word = "bar"
+
+        [[history]]
+        undone = false
+        [history.operation]
+        v = 1
+        [history.operation.op.Ignore]
+        word = "baz"
+        "#,
+    )
+    .unwrap();
+    let project = new_project_path(&temp_dir, "project");
+    let mut state = CheckerState::load(Some(state_toml.clone())).unwrap();
+    assert_eq!(state.history().count(), 2);
+
+    let popped = state.pop_last_operation(&project).unwrap();
+    assert_eq!(popped, Some(Operation::new_ignore("baz")));
+
+    let mut popped = None;
+    let captured = output::capture(output::Verbosity::Normal, || {
+        popped = state.pop_last_operation(&project).unwrap();
+    });
+    assert_eq!(popped, Some(Operation::new_ignore("foo")));
+    assert!(
+        captured
+            .stderr
+            .contains("Skipping unreadable history entry"),
+        "{captured:?}"
+    );
+
+    // The unreadable row is kept as is
+    let contents = std::fs::read_to_string(&state_toml).unwrap();
+    assert!(contents.contains("NoSuchOperation"), "{contents}");
+    let state = CheckerState::load(Some(state_toml)).unwrap();
+    assert!(state.history().all(|e| e.undone));
+}
@@ -0,0 +1,128 @@
+//! Decide whether a token is a spelling error, independently of what
+//! the frontends do with the errors
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::tokens::{is_acronym, is_capitalized};
+use crate::{Dictionary, IgnoreReason, IgnoreStore, RelativePath, Token};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Classification {
+    /// In the dictionary, accepted as an acronym or a proper noun, or
+    /// part of an ignored identifier
+    Known,
+    Ignored(IgnoreReason),
+    /// Only ignored by rules outside the strict scopes, see
+    /// IgnoreStore::set_strict_scopes() - the first of them
+    Suppressed(IgnoreReason),
+    /// Already reported during this session, and skipped by the user
+    Skipped,
+    Error,
+}
+
+impl Classification {
+    /// True if the token should be reported
+    pub fn is_error(&self) -> bool {
+        matches!(self, Classification::Error)
+    }
+
+    /// True if the token is neither known nor ignored - skipped and
+    /// suppressed tokens count, for instance when looking for lines with
+    /// too many errors
+    pub fn is_unknown(&self) -> bool {
+        matches!(
+            self,
+            Classification::Skipped | Classification::Suppressed(_) | Classification::Error
+        )
+    }
+}
+
+/// The checks made by `Checker::process()` on each token, in order:
+/// ignored identifiers, proper nouns, acronyms, dictionary, ignore store,
+/// then the words skipped during the session
+pub struct TokenClassifier<'a, D: Dictionary> {
+    dictionary: &'a D,
+    ignore_store: &'a IgnoreStore,
+    skipped: Option<&'a HashSet<String>>,
+}
+
+impl<'a, D: Dictionary> TokenClassifier<'a, D> {
+    pub fn new(dictionary: &'a D, ignore_store: &'a IgnoreStore) -> Self {
+        Self {
+            dictionary,
+            ignore_store,
+            skipped: None,
+        }
+    }
+
+    pub fn with_skipped(mut self, skipped: &'a HashSet<String>) -> Self {
+        self.skipped = Some(skipped);
+        self
+    }
+
+    /// Like classify(), but also accept the words of ignored identifiers,
+    /// and capitalized words that do not start a sentence when
+    /// `ignore_capitalized` is set - they are probably names
+    pub fn classify_token(
+        &self,
+        token: &Token,
+        relative_path: &RelativePath,
+    ) -> Result<Classification> {
+        // Usually skipped before being extracted, unless the identifier
+        // was ignored while checking the file
+        if self
+            .ignore_store
+            .should_ignore_identifier(&token.identifier)
+        {
+            return Ok(Classification::Known);
+        }
+        if self.ignore_store.ignore_capitalized()
+            && !token.sentence_start
+            && is_capitalized(&token.text)
+        {
+            return Ok(Classification::Known);
+        }
+        self.classify(&token.text, relative_path)
+    }
+
+    pub fn classify(&self, word: &str, relative_path: &RelativePath) -> Result<Classification> {
+        if let Some(min_length) = self.ignore_store.acronyms_min_length() {
+            if is_acronym(word, min_length) {
+                return Ok(Classification::Known);
+            }
+        }
+        if self.dictionary.check(word)? {
+            return Ok(Classification::Known);
+        }
+        let lang = self.dictionary.lang();
+        match self.ignore_store.strict_scopes() {
+            None => {
+                if let Some(reason) = self.ignore_store.ignore_reason(word, relative_path, lang) {
+                    return Ok(Classification::Ignored(reason));
+                }
+            }
+            Some(scopes) => {
+                let reasons = self
+                    .ignore_store
+                    .all_ignore_reasons(word, relative_path, lang);
+                let trusted = reasons
+                    .iter()
+                    .find(|r| scopes.iter().any(|s| s == r.source()));
+                if let Some(reason) = trusted {
+                    return Ok(Classification::Ignored(reason.clone()));
+                }
+                if let Some(reason) = reasons.into_iter().next() {
+                    return Ok(Classification::Suppressed(reason));
+                }
+            }
+        }
+        if self.skipped.is_some_and(|s| s.contains(word)) {
+            return Ok(Classification::Skipped);
+        }
+        Ok(Classification::Error)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,158 @@
+use crate::tests::{create_store, get_empty_store, get_test_dir, relative_path, FakeDictionary};
+
+use super::*;
+
+#[test]
+fn test_known_words() {
+    let temp_dir = get_test_dir();
+    let mut dictionary = FakeDictionary::new();
+    dictionary.add_known("hello");
+    let store = get_empty_store(&temp_dir);
+    let classifier = TokenClassifier::new(&dictionary, &store);
+
+    let actual = classifier
+        .classify("hello", &relative_path("foo.txt"))
+        .unwrap();
+
+    assert_eq!(actual, Classification::Known);
+}
+
+#[test]
+fn test_acronyms_are_known_when_accepted() {
+    let temp_dir = get_test_dir();
+    let dictionary = FakeDictionary::new();
+    let store = create_store(&temp_dir, "", "accept_acronyms = true\n");
+    let classifier = TokenClassifier::new(&dictionary, &store);
+
+    let actual = classifier
+        .classify("HTTP", &relative_path("foo.txt"))
+        .unwrap();
+
+    assert_eq!(actual, Classification::Known);
+}
+
+#[test]
+fn test_capitalized_words_are_known_unless_they_start_a_sentence() {
+    let temp_dir = get_test_dir();
+    let dictionary = FakeDictionary::new();
+    let store = create_store(&temp_dir, "", "ignore_capitalized = true\n");
+    let classifier = TokenClassifier::new(&dictionary, &store);
+    let path = relative_path("book.md");
+    let line = "Smeagol sees Smeagol";
+
+    let first = Token::new("Smeagol", line, (1, 0));
+    let second = Token::new("Smeagol", line, (1, 13));
+
+    assert_eq!(
+        classifier.classify_token(&first, &path).unwrap(),
+        Classification::Error
+    );
+    assert_eq!(
+        classifier.classify_token(&second, &path).unwrap(),
+        Classification::Known
+    );
+}
+
+#[test]
+fn test_words_of_ignored_identifiers_never_reach_the_dictionary() {
+    let temp_dir = get_test_dir();
+    let dictionary = FakeDictionary::new();
+    let store = create_store(&temp_dir, "identifiers = [\"XgboostClassifier\"]\n", "");
+    let classifier = TokenClassifier::new(&dictionary, &store);
+    let path = relative_path("model.py");
+    let line = "XgboostClassifier Xgboost";
+
+    let in_identifier = Token::new("Classifier", line, (1, 7)).with_identifier("XgboostClassifier");
+    let alone = Token::new("Xgboost", line, (1, 18));
+
+    assert_eq!(
+        classifier.classify_token(&in_identifier, &path).unwrap(),
+        Classification::Known
+    );
+    assert_eq!(
+        classifier.classify_token(&alone, &path).unwrap(),
+        Classification::Error
+    );
+    assert_eq!(dictionary.checked_words(), ["Xgboost"]);
+}
+
+#[test]
+fn test_capitalized_words_are_checked_by_default() {
+    let temp_dir = get_test_dir();
+    let dictionary = FakeDictionary::new();
+    let store = get_empty_store(&temp_dir);
+    let classifier = TokenClassifier::new(&dictionary, &store);
+    let line = "Alice sees Smeagol";
+
+    let token = Token::new("Smeagol", line, (1, 11));
+
+    assert_eq!(
+        classifier
+            .classify_token(&token, &relative_path("book.md"))
+            .unwrap(),
+        Classification::Error
+    );
+}
+
+#[test]
+fn test_ignored_words_come_with_their_reason() {
+    let temp_dir = get_test_dir();
+    let dictionary = FakeDictionary::new();
+    let mut store = get_empty_store(&temp_dir);
+    store.ignore_for_extension("fn", "rs").unwrap();
+    let classifier = TokenClassifier::new(&dictionary, &store);
+
+    let actual = classifier.classify("fn", &relative_path("foo.rs")).unwrap();
+
+    assert_eq!(
+        actual,
+        Classification::Ignored(IgnoreReason::Extension("rs".to_string()))
+    );
+    let actual = classifier.classify("fn", &relative_path("foo.py")).unwrap();
+    assert_eq!(actual, Classification::Error);
+}
+
+#[test]
+fn test_session_skips() {
+    let temp_dir = get_test_dir();
+    let dictionary = FakeDictionary::new();
+    let store = get_empty_store(&temp_dir);
+    let skipped = HashSet::from(["foo".to_string()]);
+    let classifier = TokenClassifier::new(&dictionary, &store).with_skipped(&skipped);
+
+    let foo = classifier
+        .classify("foo", &relative_path("foo.txt"))
+        .unwrap();
+    let bar = classifier
+        .classify("bar", &relative_path("foo.txt"))
+        .unwrap();
+
+    assert_eq!(foo, Classification::Skipped);
+    assert!(foo.is_unknown());
+    assert!(!foo.is_error());
+    assert_eq!(bar, Classification::Error);
+}
+
+#[test]
+fn test_words_only_ignored_outside_the_strict_scopes_are_suppressed() {
+    let temp_dir = get_test_dir();
+    let dictionary = FakeDictionary::new();
+    let mut store = get_empty_store(&temp_dir);
+    store.ignore("foo").unwrap();
+    store.ignore("bar").unwrap();
+    store.ignore_for_project("bar").unwrap();
+    store.set_strict_scopes(vec!["project".to_string(), "path".to_string()]);
+    let classifier = TokenClassifier::new(&dictionary, &store);
+
+    let foo = classifier
+        .classify("foo", &relative_path("foo.txt"))
+        .unwrap();
+    let bar = classifier
+        .classify("bar", &relative_path("foo.txt"))
+        .unwrap();
+
+    assert_eq!(foo, Classification::Suppressed(IgnoreReason::Global));
+    assert!(foo.is_unknown());
+    assert!(!foo.is_error());
+    assert_eq!(bar, Classification::Ignored(IgnoreReason::Project));
+}
@@ -0,0 +1,173 @@
+//! Detection of words mixing several scripts, like 'cоde' spelled with
+//! a Cyrillic 'о'. They look fine, but the dictionary does not know them
+//!
+//! Only Latin, Greek and Cyrillic letters that look like ASCII letters are
+//! in the table - this is not the full Unicode confusables set
+
+// Non-ASCII letters, and the ASCII letter they look like
+const CONFUSABLES: [(char, char); 49] = [
+    // Cyrillic lower case
+    ('а', 'a'),
+    ('с', 'c'),
+    ('ԁ', 'd'),
+    ('е', 'e'),
+    ('һ', 'h'),
+    ('і', 'i'),
+    ('ј', 'j'),
+    ('о', 'o'),
+    ('р', 'p'),
+    ('ԛ', 'q'),
+    ('ѕ', 's'),
+    ('у', 'y'),
+    ('ԝ', 'w'),
+    ('х', 'x'),
+    // Cyrillic upper case
+    ('А', 'A'),
+    ('В', 'B'),
+    ('С', 'C'),
+    ('Е', 'E'),
+    ('Н', 'H'),
+    ('І', 'I'),
+    ('Ј', 'J'),
+    ('К', 'K'),
+    ('М', 'M'),
+    ('О', 'O'),
+    ('Р', 'P'),
+    ('Ѕ', 'S'),
+    ('Т', 'T'),
+    ('Х', 'X'),
+    ('Ү', 'Y'),
+    // Greek lower case
+    ('ο', 'o'),
+    ('ν', 'v'),
+    ('ι', 'i'),
+    ('κ', 'k'),
+    ('ρ', 'p'),
+    // Greek upper case
+    ('Α', 'A'),
+    ('Β', 'B'),
+    ('Ε', 'E'),
+    ('Ζ', 'Z'),
+    ('Η', 'H'),
+    ('Ι', 'I'),
+    ('Κ', 'K'),
+    ('Μ', 'M'),
+    ('Ν', 'N'),
+    ('Ο', 'O'),
+    ('Ρ', 'P'),
+    ('Τ', 'T'),
+    ('Υ', 'Y'),
+    ('Χ', 'X'),
+    // Latin
+    ('ı', 'i'),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Other,
+}
+
+fn script(c: char) -> Script {
+    match c as u32 {
+        0x0041..=0x005A | 0x0061..=0x007A | 0x00C0..=0x024F | 0x1E00..=0x1EFF => Script::Latin,
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x0400..=0x052F => Script::Cyrillic,
+        _ => Script::Other,
+    }
+}
+
+fn lookalike(c: char) -> Option<char> {
+    CONFUSABLES
+        .iter()
+        .find(|(confusable, _)| *confusable == c)
+        .map(|(_, ascii)| *ascii)
+}
+
+/// A suspicious character found in a word
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MixedScript {
+    pub character: char,
+    /// The ASCII letter `character` looks like, if any
+    pub lookalike: Option<char>,
+    /// The word with every lookalike replaced by its ASCII letter
+    pub suggestion: Option<String>,
+}
+
+impl MixedScript {
+    /// Like `U+043E`
+    pub fn codepoint(&self) -> String {
+        format!("U+{:04X}", self.character as u32)
+    }
+
+    pub fn message(&self) -> String {
+        let character = self.character;
+        let codepoint = self.codepoint();
+        match self.lookalike {
+            Some(ascii) => format!("'{character}' ({codepoint}) looks like '{ascii}'"),
+            None => format!(
+                "'{character}' ({codepoint}) is not from the same script as the other letters"
+            ),
+        }
+    }
+}
+
+// The script most letters are from, preferring Latin in case of a tie
+fn main_script(letters: &[char]) -> Script {
+    let mut res = Script::Latin;
+    let mut max = 0;
+    for candidate in [
+        Script::Latin,
+        Script::Greek,
+        Script::Cyrillic,
+        Script::Other,
+    ] {
+        let count = letters.iter().filter(|c| script(**c) == candidate).count();
+        if count > max {
+            res = candidate;
+            max = count;
+        }
+    }
+    res
+}
+
+/// Return the first suspicious character of `word`, if it mixes several
+/// scripts, or if all its letters look like ASCII letters without being ones
+pub fn detect_mixed_script(word: &str) -> Option<MixedScript> {
+    let letters: Vec<_> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    let main_script = main_script(&letters);
+    let all_lookalikes = !letters.is_empty() && letters.iter().all(|c| lookalike(*c).is_some());
+    let character = if all_lookalikes {
+        letters[0]
+    } else {
+        letters
+            .iter()
+            .copied()
+            .find(|c| script(*c) != main_script)?
+    };
+
+    // Only suggest to replace letters by their ASCII lookalike when
+    // the word is mostly written in Latin
+    let suggestion = if main_script == Script::Latin || all_lookalikes {
+        let replaced: String = word
+            .chars()
+            .map(|c| match lookalike(c) {
+                Some(ascii) if all_lookalikes || script(c) != main_script => ascii,
+                _ => c,
+            })
+            .collect();
+        (replaced != word).then_some(replaced)
+    } else {
+        None
+    };
+    Some(MixedScript {
+        character,
+        lookalike: lookalike(character),
+        suggestion,
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,55 @@
+use super::*;
+
+#[test]
+fn test_detect_mixed_script() {
+    // word, suspicious character, lookalike, suggestion
+    let cases: &[(&str, char, Option<char>, Option<&str>)] = &[
+        ("cоde", 'о', Some('o'), Some("code")),
+        ("Сlass", 'С', Some('C'), Some("Class")),
+        ("cоdе", 'о', Some('o'), Some("code")),
+        ("ρarse", 'ρ', Some('p'), Some("parse")),
+        ("сор", 'с', Some('c'), Some("cop")),
+        ("fooжbar", 'ж', None, None),
+        ("данные_x", 'x', None, None),
+    ];
+    for (word, character, lookalike, suggestion) in cases {
+        let actual = detect_mixed_script(word);
+        let expected = MixedScript {
+            character: *character,
+            lookalike: *lookalike,
+            suggestion: suggestion.map(|s| s.to_string()),
+        };
+        assert_eq!(actual, Some(expected), "{word}");
+    }
+}
+
+#[test]
+fn test_words_from_a_single_script_are_not_suspicious() {
+    for word in [
+        "code",
+        "café",
+        "привет",
+        "λόγος",
+        "kırmızı",
+        "日本語",
+        "",
+        "42",
+    ] {
+        assert_eq!(detect_mixed_script(word), None, "{word}");
+    }
+}
+
+#[test]
+fn test_message() {
+    let cases = [
+        ("cоde", "'о' (U+043E) looks like 'o'"),
+        (
+            "fooжbar",
+            "'ж' (U+0436) is not from the same script as the other letters",
+        ),
+    ];
+    for (word, expected) in cases {
+        let mixed_script = detect_mixed_script(word).unwrap();
+        assert_eq!(mixed_script.message(), expected, "{word}");
+    }
+}
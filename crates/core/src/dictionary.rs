@@ -8,3 +8,104 @@ pub trait Dictionary {
     fn lang(&self) -> &str;
     fn provider(&self) -> &str;
 }
+
+impl<D: Dictionary + ?Sized> Dictionary for Box<D> {
+    fn check(&self, word: &str) -> Result<bool> {
+        (**self).check(word)
+    }
+
+    fn suggest(&self, error: &str) -> Result<Vec<String>> {
+        (**self).suggest(error)
+    }
+
+    fn lang(&self) -> &str {
+        (**self).lang()
+    }
+
+    fn provider(&self) -> &str {
+        (**self).provider()
+    }
+}
+
+/// Fix common mistakes in language tags, like `en-us` for `en_US`.
+/// Enchant uses underscores, while Windows uses hyphens
+pub fn normalize_lang(lang: &str) -> String {
+    let separator = if cfg!(windows) { "-" } else { "_" };
+    let mut parts = lang.trim().split(['_', '-']);
+    let code = parts.next().unwrap_or_default().to_lowercase();
+    let mut res = vec![code];
+    for part in parts {
+        if part.len() == 2 {
+            res.push(part.to_uppercase());
+        } else {
+            res.push(part.to_string());
+        }
+    }
+    res.join(separator)
+}
+
+/// Number of insertions, deletions, substitutions and transpositions
+/// of adjacent characters needed to turn `a` into `b`, ignoring case
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<_> = a.to_lowercase().chars().collect();
+    let b: Vec<_> = b.to_lowercase().chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Return the languages from `available` that are close to `lang`,
+/// closest first: the ones sharing the same language code - for instance
+/// `en_GB` and `en_US` for `en_AU` - and the ones with a typo - like `en_US`
+/// for `en_SU`
+pub fn close_languages(lang: &str, available: &[String]) -> Vec<String> {
+    let code = |l: &str| {
+        l.split(['_', '-'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    };
+    let lang_code = code(lang);
+    let max_distance = if lang.len() <= 3 { 1 } else { 2 };
+    let mut res: Vec<_> = available
+        .iter()
+        .map(|l| (edit_distance(lang, l), l))
+        .filter(|(distance, l)| code(l) == lang_code || *distance <= max_distance)
+        .collect();
+    res.sort();
+    res.dedup();
+    res.into_iter().map(|(_, l)| l.clone()).collect()
+}
+
+/// Error message for a language for which no dictionary could be opened
+pub(crate) fn unknown_lang_message(lang: &str, available: &[String]) -> String {
+    if available.is_empty() {
+        return String::new();
+    }
+    let matches = close_languages(lang, available);
+    if matches.is_empty() {
+        format!("Available languages: {}", available.join(", "))
+    } else {
+        format!("Did you mean: {}?", matches.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests;
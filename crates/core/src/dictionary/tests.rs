@@ -0,0 +1,62 @@
+use super::*;
+
+fn available() -> Vec<String> {
+    ["de_DE", "en_US", "en_GB", "en", "fr"]
+        .iter()
+        .map(|x| x.to_string())
+        .collect()
+}
+
+#[test]
+fn test_close_languages_same_code() {
+    let actual = close_languages("en_AU", &available());
+    assert_eq!(actual, &["en_GB", "en_US", "en"]);
+}
+
+#[test]
+fn test_close_languages_with_hyphen() {
+    let actual = close_languages("fr-CA", &available());
+    assert_eq!(actual, &["fr"]);
+}
+
+#[test]
+fn test_close_languages_no_match() {
+    let actual = close_languages("it_IT", &available());
+    assert!(actual.is_empty());
+}
+
+#[test]
+fn test_close_languages_with_typo() {
+    let actual = close_languages("en_SU", &available());
+    assert_eq!(actual, &["en_US", "en_GB", "en"]);
+}
+
+#[test]
+fn test_close_languages_with_typo_in_code() {
+    let actual = close_languages("ed_DE", &available());
+    assert_eq!(actual, &["de_DE"]);
+}
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("en_US", "en_US"), 0);
+    assert_eq!(edit_distance("en_US", "en_us"), 0);
+    assert_eq!(edit_distance("en_US", "en_SU"), 1);
+    assert_eq!(edit_distance("en_US", "en"), 3);
+    assert_eq!(edit_distance("", "fr"), 2);
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_normalize_lang() {
+    assert_eq!(normalize_lang("en-US"), "en_US");
+    assert_eq!(normalize_lang("en_us"), "en_US");
+    assert_eq!(normalize_lang(" FR "), "fr");
+    assert_eq!(normalize_lang("de_DE_frami"), "de_DE_frami");
+}
+
+#[test]
+#[cfg(windows)]
+fn test_normalize_lang() {
+    assert_eq!(normalize_lang("en_us"), "en-US");
+}
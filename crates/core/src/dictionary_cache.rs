@@ -0,0 +1,228 @@
+//! Remember what the dictionary said about each word from one run to
+//! the next, so that checking a project again does not look up the same
+//! words over and over
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::atomic_write::write_atomically;
+use crate::{output, Dictionary};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of words in a cache file - the least recently used
+/// ones are dropped first
+pub const DICTIONARY_CACHE_SIZE: usize = 200_000;
+
+/// Age after which a cache file is discarded, so that the words added by
+/// an update of the system dictionaries are eventually taken into account
+pub const DICTIONARY_CACHE_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// A cache file is only valid for the provider and the language
+// it was written for, and until it gets too old
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    provider: String,
+    lang: String,
+    /// Seconds since the epoch - missing in the files written by older
+    /// versions, which are then discarded
+    #[serde(default)]
+    created: u64,
+    /// Least recently used first
+    words: Vec<(String, bool)>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    // The value is the verdict of the dictionary, and when the word was
+    // last used
+    words: HashMap<String, (bool, u64)>,
+    tick: u64,
+    changed: bool,
+}
+
+impl Entries {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// The verdicts of the dictionary for one provider and one language,
+/// shared by all the threads checking files
+#[derive(Debug)]
+pub struct DictionaryCache {
+    // None when the cache is not persisted
+    path: Option<PathBuf>,
+    provider: String,
+    lang: String,
+    capacity: usize,
+    max_age: Duration,
+    // Kept when the cache is saved again, so that it expires even if it
+    // is used every day
+    created: u64,
+    entries: Mutex<Entries>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl DictionaryCache {
+    /// A cache that only lasts for the current run
+    pub fn in_memory(provider: &str, lang: &str) -> Self {
+        Self {
+            path: None,
+            provider: provider.to_string(),
+            lang: lang.to_string(),
+            capacity: DICTIONARY_CACHE_SIZE,
+            max_age: DICTIONARY_CACHE_MAX_AGE,
+            created: now(),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Load the cache for `provider` and `lang` from `dir`. A cache file
+    /// that cannot be read is discarded with a warning
+    pub fn load(dir: &Path, provider: &str, lang: &str) -> Self {
+        let path = dir.join(format!("dictionary-cache-{provider}-{lang}.json"));
+        let mut res = Self::in_memory(provider, lang);
+        let words = match res.read(&path) {
+            Ok(Some(cache_file)) => {
+                res.created = cache_file.created;
+                cache_file.words
+            }
+            Ok(None) => vec![],
+            Err(e) => {
+                output::warning(&format!("Warning: discarding dictionary cache: {e:#}"));
+                vec![]
+            }
+        };
+        let entries = res.entries.get_mut().expect("cache lock was poisoned");
+        for (word, known) in words {
+            let tick = entries.next_tick();
+            entries.words.insert(word, (known, tick));
+        }
+        res.path = Some(path);
+        res
+    }
+
+    // None if the cache file does not exist or is no longer valid
+    fn read(&self, path: &Path) -> Result<Option<CacheFile>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        let cache_file: CacheFile = serde_json::from_str(&contents)
+            .with_context(|| format!("Could not parse {}", path.display()))?;
+        if cache_file.provider != self.provider || cache_file.lang != self.lang {
+            return Ok(None);
+        }
+        let age = Duration::from_secs(self.created.saturating_sub(cache_file.created));
+        if age > self.max_age {
+            return Ok(None);
+        }
+        Ok(Some(cache_file))
+    }
+
+    fn get(&self, word: &str) -> Option<bool> {
+        let mut entries = self.entries.lock().expect("cache lock was poisoned");
+        let tick = entries.next_tick();
+        let (known, last_used) = entries.words.get_mut(word)?;
+        *last_used = tick;
+        Some(*known)
+    }
+
+    fn insert(&self, word: &str, known: bool) {
+        let mut entries = self.entries.lock().expect("cache lock was poisoned");
+        let tick = entries.next_tick();
+        entries.words.insert(word.to_string(), (known, tick));
+        entries.changed = true;
+    }
+
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().expect("cache lock was poisoned");
+        entries.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write the cache file, keeping the most recently used words. Does
+    /// nothing for in-memory caches, or when no word was added
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let entries = self.entries.lock().expect("cache lock was poisoned");
+        if !entries.changed {
+            return Ok(());
+        }
+        let mut words: Vec<_> = entries.words.iter().collect();
+        words.sort_by_key(|(_, (_, last_used))| *last_used);
+        let skip = words.len().saturating_sub(self.capacity);
+        let cache_file = CacheFile {
+            provider: self.provider.clone(),
+            lang: self.lang.clone(),
+            created: self.created,
+            words: words
+                .into_iter()
+                .skip(skip)
+                .map(|(word, (known, _))| (word.clone(), *known))
+                .collect(),
+        };
+        let contents = serde_json::to_string(&cache_file).expect("cache should be serializable");
+        // So that another run never reads a half-written cache
+        write_atomically(path, contents.as_bytes())
+            .with_context(|| format!("Could not write {}", path.display()))
+    }
+}
+
+/// A dictionary that only asks `inner` about the words missing from the cache
+pub struct PersistentCachedDictionary<D: Dictionary> {
+    inner: D,
+    cache: Arc<DictionaryCache>,
+}
+
+impl<D: Dictionary> PersistentCachedDictionary<D> {
+    pub fn new(inner: D, cache: Arc<DictionaryCache>) -> Self {
+        Self { inner, cache }
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+}
+
+impl<D: Dictionary> Dictionary for PersistentCachedDictionary<D> {
+    fn check(&self, word: &str) -> Result<bool> {
+        if let Some(known) = self.cache.get(word) {
+            return Ok(known);
+        }
+        let known = self.inner.check(word)?;
+        self.cache.insert(word, known);
+        Ok(known)
+    }
+
+    fn suggest(&self, error: &str) -> Result<Vec<String>> {
+        self.inner.suggest(error)
+    }
+
+    fn lang(&self) -> &str {
+        self.inner.lang()
+    }
+
+    fn provider(&self) -> &str {
+        self.inner.provider()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,171 @@
+use std::cell::Cell;
+
+use crate::tests::{get_test_dir, FakeDictionary};
+
+use super::*;
+
+// Counts the lookups made by the provider
+struct CountingDictionary {
+    inner: FakeDictionary,
+    calls: Cell<usize>,
+}
+
+impl CountingDictionary {
+    fn new(known: &[&str]) -> Self {
+        let mut inner = FakeDictionary::new();
+        for word in known {
+            inner.add_known(word);
+        }
+        Self {
+            inner,
+            calls: Cell::new(0),
+        }
+    }
+}
+
+impl Dictionary for CountingDictionary {
+    fn check(&self, word: &str) -> Result<bool> {
+        self.calls.set(self.calls.get() + 1);
+        self.inner.check(word)
+    }
+
+    fn suggest(&self, error: &str) -> Result<Vec<String>> {
+        self.inner.suggest(error)
+    }
+
+    fn lang(&self) -> &str {
+        self.inner.lang()
+    }
+
+    fn provider(&self) -> &str {
+        self.inner.provider()
+    }
+}
+
+fn cached(dir: &Path, known: &[&str]) -> PersistentCachedDictionary<CountingDictionary> {
+    let cache = DictionaryCache::load(dir, "fake", "en_US");
+    PersistentCachedDictionary::new(CountingDictionary::new(known), Arc::new(cache))
+}
+
+#[test]
+fn test_second_run_does_not_call_the_provider() {
+    let temp_dir = get_test_dir();
+    let first = cached(temp_dir.path(), &["hello"]);
+    assert!(first.check("hello").unwrap());
+    assert!(!first.check("fooo").unwrap());
+    assert!(first.check("hello").unwrap());
+    assert_eq!(first.inner().calls.get(), 2);
+    first.cache.save().unwrap();
+
+    let second = cached(temp_dir.path(), &["hello"]);
+    assert!(second.check("hello").unwrap());
+    assert!(!second.check("fooo").unwrap());
+
+    assert_eq!(second.inner().calls.get(), 0);
+}
+
+#[test]
+fn test_cache_is_per_lang() {
+    let temp_dir = get_test_dir();
+    let first = cached(temp_dir.path(), &["hello"]);
+    first.check("hello").unwrap();
+    first.cache.save().unwrap();
+
+    let cache = DictionaryCache::load(temp_dir.path(), "fake", "fr_FR");
+
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_corrupted_cache_is_discarded() {
+    let temp_dir = get_test_dir();
+    let path = temp_dir.path().join("dictionary-cache-fake-en_US.json");
+    std::fs::write(&path, "{not json").unwrap();
+
+    let dictionary = cached(temp_dir.path(), &["hello"]);
+    assert!(dictionary.check("hello").unwrap());
+    dictionary.cache.save().unwrap();
+
+    let cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+    assert_eq!(cache.get("hello"), Some(true));
+}
+
+#[test]
+fn test_least_recently_used_words_are_dropped() {
+    let temp_dir = get_test_dir();
+    let mut cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+    cache.capacity = 2;
+    let dictionary = PersistentCachedDictionary::new(CountingDictionary::new(&[]), Arc::new(cache));
+    dictionary.check("one").unwrap();
+    dictionary.check("two").unwrap();
+    dictionary.check("three").unwrap();
+    dictionary.check("one").unwrap();
+    dictionary.cache.save().unwrap();
+
+    let cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get("one"), Some(false));
+    assert_eq!(cache.get("two"), None);
+}
+
+#[test]
+fn test_in_memory_cache_is_not_saved() {
+    let temp_dir = get_test_dir();
+    let cache = DictionaryCache::in_memory("fake", "en_US");
+    let dictionary = PersistentCachedDictionary::new(CountingDictionary::new(&[]), Arc::new(cache));
+    dictionary.check("fooo").unwrap();
+    dictionary.check("fooo").unwrap();
+    dictionary.cache.save().unwrap();
+
+    assert_eq!(dictionary.inner().calls.get(), 1);
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_old_cache_is_discarded() {
+    let temp_dir = get_test_dir();
+    let mut cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+    cache.created -= DICTIONARY_CACHE_MAX_AGE.as_secs() + 1;
+    let dictionary = PersistentCachedDictionary::new(CountingDictionary::new(&[]), Arc::new(cache));
+    dictionary.check("fooo").unwrap();
+    dictionary.cache.save().unwrap();
+
+    let cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_saving_again_keeps_the_creation_time() {
+    let temp_dir = get_test_dir();
+    let mut cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+    cache.created -= 60;
+    let created = cache.created;
+    let dictionary = PersistentCachedDictionary::new(CountingDictionary::new(&[]), Arc::new(cache));
+    dictionary.check("fooo").unwrap();
+    dictionary.cache.save().unwrap();
+
+    let second = cached(temp_dir.path(), &[]);
+    second.check("barr").unwrap();
+    second.cache.save().unwrap();
+
+    let cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+    assert_eq!(cache.created, created);
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_cache_without_creation_time_is_discarded() {
+    let temp_dir = get_test_dir();
+    let path = temp_dir.path().join("dictionary-cache-fake-en_US.json");
+    std::fs::write(
+        &path,
+        r#"{"provider": "fake", "lang": "en_US", "words": [["hello", true]]}"#,
+    )
+    .unwrap();
+
+    let cache = DictionaryCache::load(temp_dir.path(), "fake", "en_US");
+
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,132 @@
+//! Recognize the files that are not encoded in UTF-8, and decode them
+//! so that they can be checked like the others
+use std::borrow::Cow;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Encoding {
+    #[serde(rename = "UTF-8")]
+    Utf8,
+    #[serde(rename = "UTF-16LE")]
+    Utf16Le,
+    #[serde(rename = "UTF-16BE")]
+    Utf16Be,
+    #[serde(rename = "windows-1252")]
+    Windows1252,
+}
+
+impl Encoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Windows1252 => "windows-1252",
+        }
+    }
+}
+
+/// The encoding of a file, and whether it was given by a byte order mark -
+/// in this case there is no doubt about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectedEncoding {
+    pub encoding: Encoding,
+    pub bom: bool,
+}
+
+const BOMS: [(&[u8], Encoding); 3] = [
+    (b"\xef\xbb\xbf", Encoding::Utf8),
+    (b"\xff\xfe", Encoding::Utf16Le),
+    (b"\xfe\xff", Encoding::Utf16Be),
+];
+
+// Code points of the bytes 0x80 to 0x9f in windows-1252. The five unused
+// bytes are mapped to the C1 controls, like browsers do
+const WINDOWS_1252_HIGH: [u16; 32] = [
+    0x20ac, 0x0081, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021, 0x02c6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0x008d, 0x017d, 0x008f, 0x0090, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0x02dc, 0x2122, 0x0161, 0x203a, 0x0153, 0x009d, 0x017e, 0x0178,
+];
+
+/// Guess the encoding of `bytes`:
+///  - a byte order mark always wins
+///  - text with a NUL byte every other byte is UTF-16
+///  - text containing valid multi-byte UTF-8 sequences is UTF-8, even with
+///    a few invalid bytes
+///  - other text with bytes above 0x7f is in a legacy 8-bit encoding, most
+///    likely windows-1252 - which is also a superset of Latin-1 for text
+pub fn detect_encoding(bytes: &[u8]) -> DetectedEncoding {
+    if let Some((_, encoding)) = BOMS.iter().find(|(bom, _)| bytes.starts_with(bom)) {
+        return DetectedEncoding {
+            encoding: *encoding,
+            bom: true,
+        };
+    }
+    let encoding = if let Some(encoding) = utf16_without_bom(bytes) {
+        encoding
+    } else if std::str::from_utf8(bytes).is_ok() || has_utf8_sequences(bytes) {
+        Encoding::Utf8
+    } else {
+        Encoding::Windows1252
+    };
+    DetectedEncoding {
+        encoding,
+        bom: false,
+    }
+}
+
+/// The contents of `bytes` in UTF-8, without the byte order mark. Invalid
+/// UTF-8 is left as is, and invalid UTF-16 is replaced by U+FFFD
+pub fn decode_to_utf8(bytes: &[u8], encoding: Encoding) -> Cow<'_, [u8]> {
+    let bom = BOMS
+        .iter()
+        .find(|(bom, e)| *e == encoding && bytes.starts_with(bom))
+        .map_or(0, |(bom, _)| bom.len());
+    let bytes = &bytes[bom..];
+    let text: String = match encoding {
+        Encoding::Utf8 => return Cow::Borrowed(bytes),
+        Encoding::Utf16Le => decode_utf16(bytes, u16::from_le_bytes),
+        Encoding::Utf16Be => decode_utf16(bytes, u16::from_be_bytes),
+        Encoding::Windows1252 => bytes
+            .iter()
+            .map(|b| match b {
+                0x80..=0x9f => char::from_u32(WINDOWS_1252_HIGH[(b - 0x80) as usize] as u32)
+                    .unwrap_or('\u{fffd}'),
+                _ => *b as char,
+            })
+            .collect(),
+    };
+    Cow::Owned(text.into_bytes())
+}
+
+fn decode_utf16(bytes: &[u8], to_u16: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks_exact(2).map(|c| to_u16([c[0], c[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+// Mostly ASCII text encoded in UTF-16 has a NUL byte in every other byte
+fn utf16_without_bom(bytes: &[u8]) -> Option<Encoding> {
+    if bytes.len() < 4 {
+        return None;
+    }
+    let pairs = bytes.len() / 2;
+    let even_nuls = bytes.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_nuls = bytes.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+    if odd_nuls * 2 > pairs && even_nuls * 10 < pairs {
+        Some(Encoding::Utf16Le)
+    } else if even_nuls * 2 > pairs && odd_nuls * 10 < pairs {
+        Some(Encoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+fn has_utf8_sequences(bytes: &[u8]) -> bool {
+    bytes.utf8_chunks().any(|chunk| !chunk.valid().is_ascii())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,96 @@
+use super::*;
+
+fn decoded(bytes: &[u8]) -> (DetectedEncoding, String) {
+    let detected = detect_encoding(bytes);
+    let text = decode_to_utf8(bytes, detected.encoding);
+    (detected, String::from_utf8(text.into_owned()).unwrap())
+}
+
+#[test]
+fn test_utf16le_with_bom() {
+    let (detected, text) = decoded(include_bytes!("fixtures/utf16le_bom.txt"));
+
+    assert_eq!(
+        detected,
+        DetectedEncoding {
+            encoding: Encoding::Utf16Le,
+            bom: true
+        }
+    );
+    assert_eq!(text, "Café au lait\nNaïve fooo\n");
+}
+
+#[test]
+fn test_utf16be_with_bom() {
+    let bytes = b"\xfe\xff\x00h\x00i";
+
+    let (detected, text) = decoded(bytes);
+
+    assert_eq!(detected.encoding, Encoding::Utf16Be);
+    assert_eq!(text, "hi");
+}
+
+#[test]
+fn test_utf16_without_bom() {
+    let bytes: Vec<u8> = "hello world"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+
+    let (detected, text) = decoded(&bytes);
+
+    assert_eq!(
+        detected,
+        DetectedEncoding {
+            encoding: Encoding::Utf16Le,
+            bom: false
+        }
+    );
+    assert_eq!(text, "hello world");
+}
+
+#[test]
+fn test_windows1252() {
+    let (detected, text) = decoded(include_bytes!("fixtures/windows1252.txt"));
+
+    assert_eq!(
+        detected,
+        DetectedEncoding {
+            encoding: Encoding::Windows1252,
+            bom: false
+        }
+    );
+    assert_eq!(text, "Café au lait\n“Naïve” fooo – €5\n");
+}
+
+#[test]
+fn test_utf8_bom_is_removed() {
+    let (detected, text) = decoded("\u{feff}café".as_bytes());
+
+    assert_eq!(
+        detected,
+        DetectedEncoding {
+            encoding: Encoding::Utf8,
+            bom: true
+        }
+    );
+    assert_eq!(text, "café");
+}
+
+#[test]
+fn test_utf8_with_a_few_invalid_bytes() {
+    let bytes = b"caf\xc3\xa9 \xff";
+
+    let detected = detect_encoding(bytes);
+
+    assert_eq!(detected.encoding, Encoding::Utf8);
+    assert_eq!(decode_to_utf8(bytes, detected.encoding).as_ref(), bytes);
+}
+
+#[test]
+fn test_ascii() {
+    let detected = detect_encoding(b"hello");
+
+    assert_eq!(detected.encoding, Encoding::Utf8);
+    assert!(!detected.bom);
+}
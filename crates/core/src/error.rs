@@ -0,0 +1,101 @@
+//! Errors that programs embedding skyspell_core may want to tell apart.
+//!
+//! Functions still return `anyhow::Result`, so that context can be added
+//! along the way - use `error.downcast_ref::<SkyspellError>()` to get
+//! the variant
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SkyspellError {
+    /// An ignore file could not be read or written
+    #[error("{}: {error}", path.display())]
+    IgnoreStoreIo {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+
+    /// An ignore file is not valid TOML, or does not match the expected
+    /// format. Line and column start at 1
+    #[error("{}:{line}:{column}: {message}", path.display())]
+    IgnoreStoreParse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    /// The dictionary for `lang` could not be opened with `provider`
+    #[error("{message}")]
+    DictionaryUnavailable {
+        lang: String,
+        provider: String,
+        message: String,
+    },
+
+    /// The project path does not exist or cannot be accessed
+    #[error("Could not canonicalize project path: {}: {error}", path.display())]
+    ProjectNotFound {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+
+    /// Trying to remove a word from an ignore list that does not contain
+    /// it - `scope` is the same as `Operation::scope()`
+    #[error("'{word}' is not in the {scope} ignore list")]
+    NotIgnored { word: String, scope: String },
+
+    /// Adding a word would go past the limit set for this ignore list -
+    /// `scope` is the same as `Operation::scope()`
+    #[error("The {scope} ignore list is full: the local config limits it to {limit} words\nIgnore the word for a narrower scope, like an extension or a single file, add a regex with add-regex, or enable one of the builtin lists - see `skyspell list --builtin`")]
+    LimitReached { scope: String, limit: usize },
+
+    /// The ignore store no longer matches what an operation did
+    #[error("Cannot undo the last operation: {0}")]
+    OperationConflict(String),
+}
+
+impl SkyspellError {
+    pub(crate) fn not_ignored(word: &str, scope: impl Into<String>) -> Self {
+        Self::NotIgnored {
+            word: word.to_string(),
+            scope: scope.into(),
+        }
+    }
+
+    pub(crate) fn io(path: &Path, error: std::io::Error) -> Self {
+        Self::IgnoreStoreIo {
+            path: path.to_path_buf(),
+            error,
+        }
+    }
+
+    pub(crate) fn parse(path: &Path, contents: &str, error: toml::de::Error) -> Self {
+        let offset = error.span().map_or(0, |s| s.start);
+        let (line, column) = line_and_column(contents, offset);
+        Self::IgnoreStoreParse {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: error.message().to_string(),
+        }
+    }
+}
+
+// Both start at 1, and the column counts chars, not bytes
+fn line_and_column(contents: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(contents.len());
+    if offset == contents.len() && contents.ends_with('\n') {
+        offset -= 1;
+    }
+    while !contents.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &contents[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,28 @@
+use super::*;
+
+#[test]
+fn test_line_and_column() {
+    let contents = "foo\nbär = baz\n";
+
+    assert_eq!(line_and_column(contents, 0), (1, 1));
+    assert_eq!(line_and_column(contents, 4), (2, 1));
+    // 'ä' is two bytes long
+    assert_eq!(line_and_column(contents, 9), (2, 5));
+    // End of file: on the last line, not after it
+    assert_eq!(line_and_column(contents, 100), (2, 10));
+}
+
+#[test]
+fn test_parse_error() {
+    let contents = "project = [\"foo\"]\npaths = \n";
+    let toml_error = toml::from_str::<toml::Table>(contents).unwrap_err();
+    let path = Path::new("skyspell-ignore.toml");
+
+    let error = SkyspellError::parse(path, contents, toml_error);
+
+    let SkyspellError::IgnoreStoreParse { line, column, .. } = &error else {
+        panic!("unexpected error: {error:?}");
+    };
+    assert_eq!((*line, *column), (2, 9));
+    assert!(error.to_string().starts_with("skyspell-ignore.toml:2:9: "));
+}
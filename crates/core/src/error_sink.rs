@@ -0,0 +1,107 @@
+//! For programs that want the results of a check as soon as they are
+//! known, like editor integrations:
+//!
+//! ```ignore
+//! let mut checker = SinkChecker::new(project, dictionary, ignore_store, |error: &SpellingError| {
+//!     println!("{}", error.word());
+//!     Ok(())
+//! });
+//! checker.process(&path, &())?;
+//! ```
+use anyhow::Result;
+
+use crate::{Checker, Dictionary, IgnoreStore, Operation, ProcessOutcome, Project, RelativePath};
+use crate::{SpellingError, TokenClassifier};
+
+/// Receives the spelling errors one by one, while the files are processed.
+/// Processing waits for each call to return
+pub trait ErrorSink {
+    fn on_file_start(&mut self, _relative_path: &RelativePath) {}
+
+    fn on_error(&mut self, error: &SpellingError) -> Result<()>;
+
+    fn on_file_done(&mut self, _relative_path: &RelativePath, _outcome: &ProcessOutcome) {}
+}
+
+impl<F: FnMut(&SpellingError) -> Result<()>> ErrorSink for F {
+    fn on_error(&mut self, error: &SpellingError) -> Result<()> {
+        self(error)
+    }
+}
+
+/// A Checker that sends everything it finds to an ErrorSink
+pub struct SinkChecker<D: Dictionary, S: ErrorSink> {
+    project: Project,
+    dictionary: D,
+    ignore_store: IgnoreStore,
+    sink: S,
+}
+
+impl<D: Dictionary, S: ErrorSink> SinkChecker<D, S> {
+    pub fn new(project: Project, dictionary: D, ignore_store: IgnoreStore, sink: S) -> Self {
+        Self {
+            project,
+            dictionary,
+            ignore_store,
+            sink,
+        }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    /// The ignore store is given back too, so that operations can be
+    /// applied once the check is done
+    pub fn into_parts(self) -> (Project, IgnoreStore, S) {
+        (self.project, self.ignore_store, self.sink)
+    }
+}
+
+impl<D: Dictionary, S: ErrorSink> Checker<D> for SinkChecker<D, S> {
+    type SourceContext = ();
+
+    fn dictionary(&self) -> &D {
+        &self.dictionary
+    }
+
+    fn project(&self) -> &Project {
+        &self.project
+    }
+
+    // The sink decides what errors mean
+    fn success(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn ignore_store(&mut self) -> &mut IgnoreStore {
+        &mut self.ignore_store
+    }
+
+    fn classifier(&self) -> TokenClassifier<'_, D> {
+        TokenClassifier::new(&self.dictionary, &self.ignore_store)
+    }
+
+    fn handle_error(&mut self, error: &SpellingError, _context: &()) -> Result<()> {
+        self.sink.on_error(error)
+    }
+
+    fn on_file_start(&mut self, relative_path: &RelativePath) {
+        self.sink.on_file_start(relative_path)
+    }
+
+    fn on_file_done(&mut self, relative_path: &RelativePath, outcome: &ProcessOutcome) {
+        self.sink.on_file_done(relative_path, outcome)
+    }
+
+    fn apply_operation(&mut self, mut operation: Operation) -> Result<()> {
+        operation.execute(&mut self.ignore_store)
+    }
+}
+
+#[cfg(test)]
+mod tests;
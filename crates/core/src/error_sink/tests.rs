@@ -0,0 +1,112 @@
+use crate::tests::{get_empty_store, get_test_dir, FakeDictionary};
+use crate::SkipReason;
+
+use super::*;
+
+#[derive(Default)]
+struct RecordingSink {
+    events: Vec<String>,
+}
+
+impl ErrorSink for RecordingSink {
+    fn on_file_start(&mut self, relative_path: &RelativePath) {
+        self.events.push(format!("start {relative_path}"));
+    }
+
+    fn on_error(&mut self, error: &SpellingError) -> Result<()> {
+        let (line, column) = error.pos();
+        self.events
+            .push(format!("error {} {line}:{column}", error.word()));
+        Ok(())
+    }
+
+    fn on_file_done(&mut self, relative_path: &RelativePath, outcome: &ProcessOutcome) {
+        self.events
+            .push(format!("done {relative_path} {outcome:?}"));
+    }
+}
+
+fn new_checker<S: ErrorSink>(
+    temp_dir: &tempfile::TempDir,
+    sink: S,
+) -> SinkChecker<FakeDictionary, S> {
+    let mut dictionary = FakeDictionary::new();
+    dictionary.add_known("hello");
+    let project = Project::new(temp_dir.path()).unwrap();
+    let ignore_store = get_empty_store(temp_dir);
+    SinkChecker::new(project, dictionary, ignore_store, sink)
+}
+
+#[test]
+fn test_errors_and_file_boundaries_are_sent_in_order() {
+    let temp_dir = get_test_dir();
+    let mut checker = new_checker(&temp_dir, RecordingSink::default());
+    let foo = temp_dir.path().join("foo.txt");
+    std::fs::write(&foo, "hello fooo\nbarr\n").unwrap();
+
+    let outcome = checker.process(&foo, &()).unwrap();
+
+    assert_eq!(outcome, ProcessOutcome::Checked);
+    assert_eq!(
+        checker.into_sink().events,
+        &[
+            "start foo.txt",
+            "error fooo 1:6",
+            "error barr 2:0",
+            "done foo.txt Checked"
+        ]
+    );
+}
+
+#[test]
+fn test_skipped_files_are_signaled() {
+    let temp_dir = get_test_dir();
+    let mut checker = new_checker(&temp_dir, RecordingSink::default());
+    checker.project.set_extensions(&["md".to_string()]);
+    let foo = temp_dir.path().join("foo.txt");
+    std::fs::write(&foo, "fooo\n").unwrap();
+
+    let outcome = checker.process(&foo, &()).unwrap();
+
+    assert_eq!(outcome, ProcessOutcome::Skipped(SkipReason::Extension));
+    assert_eq!(checker.sink().events, &["done foo.txt Skipped(Extension)"]);
+}
+
+#[test]
+fn test_closure_as_sink() {
+    let temp_dir = get_test_dir();
+    let mut words = vec![];
+    let foo = temp_dir.path().join("foo.txt");
+    std::fs::write(&foo, "hello fooo barr\n").unwrap();
+    {
+        let sink = |error: &SpellingError| {
+            words.push(error.word().to_string());
+            Ok(())
+        };
+        let mut checker = new_checker(&temp_dir, sink);
+        checker.process(&foo, &()).unwrap();
+    }
+
+    assert_eq!(words, &["fooo", "barr"]);
+}
+
+#[test]
+fn test_accept_acronyms() {
+    let temp_dir = get_test_dir();
+    let mut words = vec![];
+    let foo = temp_dir.path().join("foo.txt");
+    std::fs::write(&foo, "MYPROJ_FOOBAR hello Fooo FOOs AB\n").unwrap();
+    {
+        let sink = |error: &SpellingError| {
+            words.push(error.word().to_string());
+            Ok(())
+        };
+        let mut checker = new_checker(&temp_dir, sink);
+        checker
+            .ignore_store()
+            .set_accept_acronyms(crate::AcceptAcronyms::MinLength(3));
+        checker.process(&foo, &()).unwrap();
+    }
+
+    assert_eq!(words, &["Fooo", "AB"]);
+}
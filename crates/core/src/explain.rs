@@ -0,0 +1,69 @@
+//! Why a file is checked or skipped, replaying the decisions made
+//! by `Checker::process()` and by the project walk
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::{walk_exclusion, IgnoreStore, Project, RelativePath, SkipReason, SkipRule};
+use crate::{WalkExclusion, NO_EXTENSION};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileExplanation {
+    pub relative_path: RelativePath,
+    /// Set when walking the project does not list the file
+    pub walk_exclusion: Option<WalkExclusion>,
+    pub skip_rule: Option<SkipRule>,
+    /// The extension used to filter files, or NO_EXTENSION
+    pub extension: String,
+    /// False when the project only checks some extensions, and this is not one of them
+    pub checked_extension: bool,
+    pub size: u64,
+    /// 0 means no limit
+    pub max_file_size: u64,
+}
+
+impl FileExplanation {
+    pub fn new(project: &Project, ignore_store: &IgnoreStore, source_path: &Path) -> Result<Self> {
+        let relative_path = project.get_relative_path(source_path)?;
+        let full_path = project.path().as_ref().join(&relative_path);
+        let skip_rule = project
+            .skip_file()
+            .skip_rule(&relative_path)
+            .or_else(|| ignore_store.skip_rule(&relative_path));
+        let extension = relative_path
+            .extension()
+            .map_or_else(|| NO_EXTENSION.to_string(), |e| e.to_string());
+        let metadata = std::fs::metadata(source_path)
+            .with_context(|| format!("Could not read metadata of {}", source_path.display()))?;
+        Ok(Self {
+            walk_exclusion: walk_exclusion(project.path().as_ref(), &full_path),
+            skip_rule,
+            extension,
+            checked_extension: project.has_checked_extension(&relative_path),
+            size: metadata.len(),
+            max_file_size: project.max_file_size(),
+            relative_path,
+        })
+    }
+
+    pub fn is_too_large(&self) -> bool {
+        self.max_file_size != 0 && self.size > self.max_file_size
+    }
+
+    /// The outcome of `Checker::process()` when the file is given on the
+    /// command line - None if the file is checked
+    pub fn skip_reason(&self) -> Option<SkipReason> {
+        if self.skip_rule.is_some() {
+            Some(SkipReason::SkipFile)
+        } else if !self.checked_extension {
+            Some(SkipReason::Extension)
+        } else if self.is_too_large() {
+            Some(SkipReason::TooLarge)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
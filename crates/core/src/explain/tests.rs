@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+use super::*;
+use crate::tests::get_test_dir;
+use crate::SKYSPELL_LOCAL_IGNORE;
+
+fn setup(temp_dir: &TempDir, local: &str) -> (Project, IgnoreStore) {
+    let project_path = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_path).unwrap();
+    let local_toml = project_path.join(SKYSPELL_LOCAL_IGNORE);
+    std::fs::write(&local_toml, local).unwrap();
+    let project = Project::new(&project_path).unwrap();
+    let ignore_store = IgnoreStore::load(temp_dir.path().join("global.toml"), local_toml).unwrap();
+    (project, ignore_store)
+}
+
+fn write_file(project: &Project, name: &str, contents: &str) -> PathBuf {
+    let path = project.path().as_ref().join(name);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_checked_file() {
+    let temp_dir = get_test_dir();
+    let (project, ignore_store) = setup(&temp_dir, "");
+    let path = write_file(&project, "README.md", "hello");
+
+    let explanation = FileExplanation::new(&project, &ignore_store, &path).unwrap();
+
+    assert_eq!(explanation.relative_path.normalize(), "README.md");
+    assert_eq!(explanation.walk_exclusion, None);
+    assert_eq!(explanation.skip_rule, None);
+    assert_eq!(explanation.extension, "md");
+    assert_eq!(explanation.size, 5);
+    assert_eq!(explanation.skip_reason(), None);
+}
+
+#[test]
+fn test_skipped_by_pattern() {
+    let temp_dir = get_test_dir();
+    let (project, ignore_store) = setup(&temp_dir, r#"patterns = ["*.lock"]"#);
+    let path = write_file(&project, "front/yarn.lock", "");
+
+    let explanation = FileExplanation::new(&project, &ignore_store, &path).unwrap();
+
+    assert_eq!(
+        explanation.skip_rule,
+        Some(SkipRule::Pattern("*.lock".to_string()))
+    );
+    assert_eq!(explanation.skip_reason(), Some(SkipReason::SkipFile));
+}
+
+#[test]
+fn test_skip_pattern_added_after_opening_the_project() {
+    let temp_dir = get_test_dir();
+    let (project, mut ignore_store) = setup(&temp_dir, "");
+    let path = write_file(&project, "yarn.lock", "");
+
+    ignore_store.skip_file_name("yarn.lock").unwrap();
+    let explanation = FileExplanation::new(&project, &ignore_store, &path).unwrap();
+
+    assert_eq!(explanation.skip_reason(), Some(SkipReason::SkipFile));
+}
+
+#[test]
+fn test_local_ignore_file_is_skipped() {
+    let temp_dir = get_test_dir();
+    let (project, ignore_store) = setup(&temp_dir, "");
+    let path = project.ignore_path();
+
+    let explanation = FileExplanation::new(&project, &ignore_store, &path).unwrap();
+
+    assert_eq!(explanation.skip_rule, Some(SkipRule::LocalIgnoreFile));
+}
+
+#[test]
+fn test_extension_not_checked() {
+    let temp_dir = get_test_dir();
+    let (mut project, ignore_store) = setup(&temp_dir, "");
+    project.set_extensions(&["md".to_string()]);
+    let path = write_file(&project, "Makefile", "");
+
+    let explanation = FileExplanation::new(&project, &ignore_store, &path).unwrap();
+
+    assert_eq!(explanation.extension, NO_EXTENSION);
+    assert!(!explanation.checked_extension);
+    assert_eq!(explanation.skip_reason(), Some(SkipReason::Extension));
+}
+
+#[test]
+fn test_too_large() {
+    let temp_dir = get_test_dir();
+    let (project, ignore_store) = setup(&temp_dir, r#"max_file_size = "4""#);
+    let path = write_file(&project, "big.txt", "hello");
+
+    let explanation = FileExplanation::new(&project, &ignore_store, &path).unwrap();
+
+    assert_eq!(explanation.max_file_size, 4);
+    assert_eq!(explanation.skip_reason(), Some(SkipReason::TooLarge));
+}
+
+#[test]
+fn test_gitignored_file_is_still_checked_when_given_explicitly() {
+    let temp_dir = get_test_dir();
+    let (project, ignore_store) = setup(&temp_dir, "");
+    std::fs::create_dir(project.path().as_ref().join(".git")).unwrap();
+    write_file(&project, ".gitignore", "*.log\n");
+    let path = write_file(&project, "debug.log", "");
+
+    let explanation = FileExplanation::new(&project, &ignore_store, &path).unwrap();
+
+    assert!(matches!(
+        explanation.walk_exclusion,
+        Some(WalkExclusion::Ignored { .. })
+    ));
+    assert_eq!(explanation.skip_reason(), None);
+}
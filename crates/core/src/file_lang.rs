@@ -0,0 +1,183 @@
+//! Documentation files can be written in another language than the rest
+//! of the project. They tell so in their front matter, and are checked
+//! with the dictionary for this language
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::po::po_language;
+use crate::{normalize_lang, output, Dictionary};
+
+/// Only the first lines of a file are searched for a language tag
+pub const LANG_TAG_LINES: usize = 40;
+
+const TAGGED_EXTENSIONS: [&str; 5] = ["md", "markdown", "rst", "po", "pot"];
+
+/// The language a Markdown or reStructuredText file is tagged with, if any,
+/// see find_lang_tag() - or the language of a gettext catalog
+pub fn lang_tag(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    if !TAGGED_EXTENSIONS.contains(&extension.as_str()) {
+        return None;
+    }
+    let file = File::open(path).ok()?;
+    let lines: Vec<_> = BufReader::new(file)
+        .lines()
+        .take(LANG_TAG_LINES)
+        .map_while(|l| l.ok())
+        .collect();
+    let lines: Vec<_> = lines.iter().map(|l| l.as_str()).collect();
+    match extension.as_str() {
+        "po" | "pot" => po_language(&lines),
+        _ => find_lang_tag(&lines),
+    }
+}
+
+/// Look for, in this order:
+///  - a `skyspell:lang fr` directive, usually in a comment like
+///    `<!-- skyspell:lang fr -->`
+///  - a `lang` or `language` key in the YAML front matter
+///  - a `:lang:` or `:language:` field, as used by Sphinx
+pub fn find_lang_tag(lines: &[&str]) -> Option<String> {
+    lines
+        .iter()
+        .find_map(|l| directive_lang(l))
+        .or_else(|| front_matter_lang(lines))
+        .or_else(|| field_lang(lines))
+}
+
+fn directive_lang(line: &str) -> Option<String> {
+    let (_, rest) = line.split_once("skyspell:lang")?;
+    let value = rest.split_whitespace().next()?;
+    valid_lang(value.trim_end_matches("-->"))
+}
+
+fn front_matter_lang(lines: &[&str]) -> Option<String> {
+    let (first, rest) = lines.split_first()?;
+    if first.trim_end() != "---" {
+        return None;
+    }
+    for line in rest {
+        if matches!(line.trim_end(), "---" | "...") {
+            break;
+        }
+        // Nested keys belong to something else
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) if !key.starts_with(char::is_whitespace) => (key, value),
+            _ => continue,
+        };
+        if matches!(key.trim(), "lang" | "language") {
+            return valid_lang(value.trim().trim_matches(['"', '\'']));
+        }
+    }
+    None
+}
+
+fn field_lang(lines: &[&str]) -> Option<String> {
+    lines.iter().find_map(|line| {
+        let value = line
+            .strip_prefix(":language:")
+            .or_else(|| line.strip_prefix(":lang:"))?;
+        valid_lang(value.trim())
+    })
+}
+
+// Anything else is probably not a language tag
+pub(crate) fn valid_lang(value: &str) -> Option<String> {
+    let valid = !value.is_empty()
+        && value.len() <= 16
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    valid.then(|| value.to_string())
+}
+
+/// True if a file tagged with `tag` can be checked with the dictionary
+/// for `lang` - for instance `en` and `en_US`
+pub(crate) fn is_same_lang(tag: &str, lang: &str) -> bool {
+    let (tag, lang) = (normalize_lang(tag), normalize_lang(lang));
+    if tag == lang {
+        return true;
+    }
+    let has_region = tag.contains(['_', '-']);
+    !has_region && lang.split(['_', '-']).next() == Some(tag.as_str())
+}
+
+/// Opens the dictionary for a language, see LangDictionaries
+pub type LangOpener<D> = Arc<dyn Fn(&str) -> Result<D> + Send + Sync>;
+
+/// The dictionaries for the languages files are tagged with, opened when
+/// first needed. Checkers use the one selected for the current file, if
+/// any, instead of their own
+pub struct LangDictionaries<D> {
+    opener: Option<LangOpener<D>>,
+    // None when the dictionary could not be opened
+    opened: HashMap<String, Option<D>>,
+    current: Option<String>,
+}
+
+impl<D: Dictionary> Default for LangDictionaries<D> {
+    fn default() -> Self {
+        Self {
+            opener: None,
+            opened: HashMap::new(),
+            current: None,
+        }
+    }
+}
+
+impl<D: Dictionary> LangDictionaries<D> {
+    /// Without an opener, tagged files are checked with the default dictionary
+    pub fn set_opener(&mut self, opener: LangOpener<D>) {
+        self.opener = Some(opener);
+    }
+
+    pub fn opener(&self) -> Option<&LangOpener<D>> {
+        self.opener.as_ref()
+    }
+
+    /// The dictionary selected for the current file, or None to use the
+    /// default one
+    pub fn current(&self) -> Option<&D> {
+        let lang = self.current.as_ref()?;
+        self.opened.get(lang)?.as_ref()
+    }
+
+    /// Select the dictionary for the language the next file is tagged with.
+    /// `default_lang` is the language of the default dictionary. Returns
+    /// false when there is no dictionary for `lang`
+    pub fn select(&mut self, lang: Option<&str>, default_lang: &str) -> bool {
+        self.current = None;
+        let lang = match lang {
+            None => return true,
+            Some(l) if is_same_lang(l, default_lang) => return true,
+            Some(l) => normalize_lang(l),
+        };
+        let opener = match &self.opener {
+            None => return false,
+            Some(o) => o,
+        };
+        let dictionary = self
+            .opened
+            .entry(lang.clone())
+            .or_insert_with(|| match opener(&lang) {
+                Ok(d) => Some(d),
+                Err(e) => {
+                    output::verbose(&format!("Could not open dictionary for '{lang}': {e:#}"));
+                    None
+                }
+            });
+        if dictionary.is_none() {
+            return false;
+        }
+        self.current = Some(lang);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
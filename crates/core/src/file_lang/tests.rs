@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+
+use super::*;
+use crate::tests::{get_test_dir, FakeDictionary};
+
+#[test]
+fn test_yaml_front_matter() {
+    let lines = ["---", "title: Bonjour", "lang: fr", "---", "", "Bonjour"];
+
+    assert_eq!(find_lang_tag(&lines), Some("fr".to_string()));
+}
+
+#[test]
+fn test_yaml_language_key_with_quotes() {
+    let lines = ["---", "language: \"de_DE\"", "---"];
+
+    assert_eq!(find_lang_tag(&lines), Some("de_DE".to_string()));
+}
+
+#[test]
+fn test_keys_after_the_front_matter_are_ignored() {
+    let lines = ["---", "title: Hello", "---", "lang: fr"];
+
+    assert_eq!(find_lang_tag(&lines), None);
+}
+
+#[test]
+fn test_nested_keys_are_ignored() {
+    let lines = ["---", "translations:", "  lang: fr", "---"];
+
+    assert_eq!(find_lang_tag(&lines), None);
+}
+
+#[test]
+fn test_sphinx_field() {
+    let lines = ["Bienvenue", "=========", "", ":language: fr", "", "Bonjour"];
+
+    assert_eq!(find_lang_tag(&lines), Some("fr".to_string()));
+}
+
+#[test]
+fn test_comment_directive() {
+    let lines = ["# Bienvenue", "", "<!-- skyspell:lang fr -->", "Bonjour"];
+
+    assert_eq!(find_lang_tag(&lines), Some("fr".to_string()));
+}
+
+#[test]
+fn test_comment_directive_wins_over_front_matter() {
+    let lines = ["---", "lang: en", "---", "<!-- skyspell:lang fr-->"];
+
+    assert_eq!(find_lang_tag(&lines), Some("fr".to_string()));
+}
+
+#[test]
+fn test_only_documentation_files_are_tagged() {
+    let temp_dir = get_test_dir();
+    let contents = "---\nlang: fr\n---\nBonjour\n";
+    let readme = temp_dir.path().join("README.md");
+    std::fs::write(&readme, contents).unwrap();
+    let config = temp_dir.path().join("config.yml");
+    std::fs::write(&config, contents).unwrap();
+
+    assert_eq!(lang_tag(&readme), Some("fr".to_string()));
+    assert_eq!(lang_tag(&config), None);
+}
+
+#[test]
+fn test_tag_too_far_from_the_start() {
+    let temp_dir = get_test_dir();
+    let mut contents = "Hello\n".repeat(LANG_TAG_LINES);
+    contents.push_str("<!-- skyspell:lang fr -->\n");
+    let readme = temp_dir.path().join("README.md");
+    std::fs::write(&readme, contents).unwrap();
+
+    assert_eq!(lang_tag(&readme), None);
+}
+
+#[test]
+fn test_is_same_lang() {
+    assert!(is_same_lang("en", "en_US"));
+    assert!(is_same_lang("en-us", "en_US"));
+    assert!(!is_same_lang("en_GB", "en_US"));
+    assert!(!is_same_lang("fr", "en_US"));
+}
+
+#[test]
+fn test_select_dictionaries() {
+    let mut dictionaries = LangDictionaries::default();
+    dictionaries.set_opener(Arc::new(|lang: &str| {
+        if lang != "fr" {
+            bail!("no dictionary for {lang}");
+        }
+        let mut dictionary = FakeDictionary::new();
+        dictionary.add_known("bonjour");
+        Ok(dictionary)
+    }));
+
+    assert!(dictionaries.select(Some("fr"), "en_US"));
+    let french = dictionaries.current().unwrap();
+    assert!(french.check("bonjour").unwrap());
+
+    assert!(dictionaries.select(Some("en"), "en_US"));
+    assert!(dictionaries.current().is_none());
+
+    assert!(!dictionaries.select(Some("de"), "en_US"));
+    assert!(dictionaries.current().is_none());
+}
+
+#[test]
+fn test_no_opener() {
+    let mut dictionaries: LangDictionaries<FakeDictionary> = LangDictionaries::default();
+
+    assert!(!dictionaries.select(Some("fr"), "en_US"));
+    assert!(dictionaries.select(None, "en_US"));
+}
+
+#[test]
+fn test_gettext_catalog_language() {
+    let temp_dir = get_test_dir();
+    let fr_po = temp_dir.path().join("fr.po");
+    std::fs::write(
+        &fr_po,
+        "msgid \"\"\nmsgstr \"\"\n\"Language: fr\\n\"\n\nmsgid \"Hello\"\nmsgstr \"Bonjour\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(lang_tag(&fr_po), Some("fr".to_string()));
+}
@@ -0,0 +1,77 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use directories_next::BaseDirs;
+
+const RETRY_DELAY: Duration = Duration::from_millis(50);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An exclusive lock on a file, shared between processes and released
+/// when dropped.
+///
+/// The lock is taken on a separate file in the user's runtime directory (or
+/// data directory, when there is none), so that the locked file can be
+/// replaced while the lock is held
+pub(crate) struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    pub(crate) fn acquire(path: &Path) -> Result<Self> {
+        let lock_path = lock_path(path)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("while opening {}", lock_path.display()))?;
+        let start = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(Self { _file: file }),
+                Err(TryLockError::WouldBlock) => (),
+                Err(TryLockError::Error(e)) => {
+                    return Err(e).with_context(|| format!("while locking {}", path.display()))
+                }
+            }
+            if start.elapsed() > TIMEOUT {
+                bail!(
+                    "Timed out waiting for another process to finish updating {}",
+                    path.display(),
+                );
+            }
+            std::thread::sleep(RETRY_DELAY);
+        }
+    }
+}
+
+fn lock_path(path: &Path) -> Result<PathBuf> {
+    let absolute = std::path::absolute(path)
+        .with_context(|| format!("while getting absolute path of {}", path.display()))?;
+    let base_dirs = BaseDirs::new().ok_or_else(|| anyhow!("Could not get home directory"))?;
+    let locks_dir = base_dirs
+        .runtime_dir()
+        .unwrap_or_else(|| base_dirs.data_dir())
+        .join("skyspell")
+        .join("locks");
+    std::fs::create_dir_all(&locks_dir)
+        .with_context(|| format!("Could not create locks dir {}", locks_dir.display()))?;
+    let name = format!(
+        "{:016x}.lock",
+        fnv1a(absolute.as_os_str().as_encoded_bytes())
+    );
+    Ok(locks_dir.join(name))
+}
+
+// Unlike DefaultHasher, the result does not change between Rust releases,
+// so that processes built with different compilers take the same lock
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,122 @@
+//! Commands run after skyspell changes the ignore lists, set in the
+//! `[hooks]` section of the local ignore file
+use std::process::Command;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{output, Operation, ProjectPath};
+
+/// Set while a hook runs, so that calling skyspell from a hook
+/// does not run the hooks again
+pub const SKYSPELL_IN_HOOK: &str = "SKYSPELL_IN_HOOK";
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Hooks {
+    /// Run after an operation is performed or undone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_operation: Option<String>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.post_operation.is_none()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HookOptions {
+    /// Print the output of the hook
+    pub verbose: bool,
+    /// Fail when the hook fails, instead of printing a warning
+    pub strict: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Execute,
+    Undo,
+}
+
+impl HookEvent {
+    fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::Execute => "execute",
+            HookEvent::Undo => "undo",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PostOperationHook {
+    command: String,
+    options: HookOptions,
+}
+
+impl PostOperationHook {
+    pub(crate) fn new(command: &str, options: HookOptions) -> Self {
+        Self {
+            command: command.to_string(),
+            options,
+        }
+    }
+
+    /// Run the command from the project root, with the operation described
+    /// in environment variables. Does nothing when called from another hook
+    pub(crate) fn run(
+        &self,
+        operation: &Operation,
+        event: HookEvent,
+        project_path: &ProjectPath,
+    ) -> Result<()> {
+        if std::env::var_os(SKYSPELL_IN_HOOK).is_some() {
+            return Ok(());
+        }
+        let command = &self.command;
+        // The output is captured, so that it does not get mixed with
+        // the output of skyspell - which may be read by an editor
+        let output = shell(command)
+            .current_dir(project_path.as_ref())
+            .env(SKYSPELL_IN_HOOK, "1")
+            .env("SKYSPELL_OP_KIND", operation.kind())
+            .env("SKYSPELL_OP_EVENT", event.as_str())
+            .env("SKYSPELL_WORD", operation.word())
+            .env("SKYSPELL_SCOPE", operation.scope())
+            .output();
+        let error = match output {
+            Ok(output) => {
+                if self.options.verbose {
+                    eprint!("{}", String::from_utf8_lossy(&output.stdout));
+                    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+                }
+                if output.status.success() {
+                    return Ok(());
+                }
+                format!("post_operation hook '{command}' failed: {}", output.status)
+            }
+            Err(e) => format!("Could not run post_operation hook '{command}': {e}"),
+        };
+        if self.options.strict {
+            bail!(error);
+        }
+        output::warning(&format!("Warning: {error}"));
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut res = Command::new("sh");
+    res.arg("-c").arg(command);
+    res
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut res = Command::new("cmd");
+    res.arg("/C").arg(command);
+    res
+}
+
+#[cfg(test)]
+mod tests;
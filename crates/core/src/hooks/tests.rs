@@ -0,0 +1,104 @@
+use crate::tests::{get_test_dir, new_project_path};
+use crate::CheckerState;
+
+use super::*;
+
+// The commands below need a POSIX shell
+#[cfg(unix)]
+fn state_with_hook(temp_dir: &tempfile::TempDir, command: &str, strict: bool) -> CheckerState {
+    let mut state = CheckerState::load(Some(temp_dir.path().join("state.toml"))).unwrap();
+    let options = HookOptions {
+        verbose: false,
+        strict,
+    };
+    state.set_post_operation_hook(Some(command), options);
+    state
+}
+
+#[cfg(unix)]
+#[test]
+fn test_post_operation_hook_describes_the_operation() {
+    let temp_dir = get_test_dir();
+    let project_path = new_project_path(&temp_dir, "project");
+    let command = "echo $SKYSPELL_OP_EVENT $SKYSPELL_OP_KIND $SKYSPELL_WORD \
+                   $SKYSPELL_SCOPE $SKYSPELL_IN_HOOK >> hook.log";
+    let mut state = state_with_hook(&temp_dir, command, false);
+
+    state
+        .set_last_operation(Operation::new_ignore_for_project("foo"), &project_path)
+        .unwrap();
+    state.pop_last_operation(&project_path).unwrap();
+
+    let log = std::fs::read_to_string(project_path.as_ref().join("hook.log")).unwrap();
+    assert_eq!(
+        log,
+        "execute IgnoreForProject foo project 1\nundo IgnoreForProject foo project 1\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_discarding_an_operation_does_not_run_the_hook() {
+    let temp_dir = get_test_dir();
+    let project_path = new_project_path(&temp_dir, "project");
+    let mut state = state_with_hook(&temp_dir, "touch ran", false);
+    state
+        .set_last_operation(Operation::new_ignore_for_project("foo"), &project_path)
+        .unwrap();
+    let ran = project_path.as_ref().join("ran");
+    std::fs::remove_file(&ran).unwrap();
+
+    state.discard_last_operation(&project_path).unwrap();
+
+    assert!(!ran.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_failing_hook_is_a_warning() {
+    let temp_dir = get_test_dir();
+    let project_path = new_project_path(&temp_dir, "project");
+    let mut state = state_with_hook(&temp_dir, "exit 3", false);
+
+    let mut result = None;
+    let captured = output::capture(output::Verbosity::Normal, || {
+        result = Some(state.set_last_operation(Operation::new_ignore("foo"), &project_path));
+    });
+
+    result.unwrap().unwrap();
+    assert_eq!(
+        captured.stderr,
+        "Warning: post_operation hook 'exit 3' failed: exit status: 3\n"
+    );
+    assert!(captured.stdout.is_empty());
+    let entry = state.last_operation(&project_path).unwrap();
+    assert_eq!(entry.operation, Operation::new_ignore("foo"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_failing_hook_with_strict_hooks() {
+    let temp_dir = get_test_dir();
+    let project_path = new_project_path(&temp_dir, "project");
+    let mut state = state_with_hook(&temp_dir, "exit 3", true);
+
+    let err = state
+        .set_last_operation(Operation::new_ignore("foo"), &project_path)
+        .unwrap_err();
+
+    assert!(err.to_string().contains("hook 'exit 3' failed"));
+    // The operation is still recorded, so that it can be undone
+    assert!(state.last_operation(&project_path).is_some());
+}
+
+#[test]
+fn test_parse_hooks() {
+    let hooks: Hooks =
+        toml::from_str(r#"post_operation = "git add skyspell-ignore.toml""#).unwrap();
+
+    assert_eq!(
+        hooks.post_operation.as_deref(),
+        Some("git add skyspell-ignore.toml")
+    );
+    assert!(Hooks::default().is_empty());
+}
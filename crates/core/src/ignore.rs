@@ -2,14 +2,24 @@ use anyhow::{anyhow, bail, Context, Result};
 use directories_next::BaseDirs;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet, HashSet},
     path::{Path, PathBuf},
 };
 use toml;
 
-use crate::RelativePath;
+use crate::atomic_write::{backup_path, write_atomically, write_with_backup};
+use crate::builtin_lists::BuiltinWords;
+use crate::file_lock::FileLock;
+use crate::ignore_regex::{self, CompiledRegexes, GlobalRegexes, LocalRegexes};
+use crate::personal_dictionary::PersonalDictionary;
+use crate::{
+    directory_pattern, inflection_candidates, normalize_path, Hooks, LangOptions, Operation,
+    PoOptions, RelativePath, SkipFile, SkipRule, SkippedRegion, SkyspellError,
+};
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct GlobalIgnore {
     #[serde(default)]
     global: BTreeSet<String>,
@@ -19,56 +29,667 @@ pub struct GlobalIgnore {
 
     #[serde(default)]
     lang: BTreeMap<String, BTreeSet<String>>,
+
+    #[serde(default, skip_serializing_if = "GlobalRegexes::is_empty")]
+    regex: GlobalRegexes,
+
+    // Whole tokens, like `XgboostClassifier`, whose words are never checked
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    identifiers: BTreeSet<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct LocalIgnore {
     #[serde(default)]
     pub patterns: BTreeSet<String>,
 
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub extra_paths: BTreeSet<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub check_paths: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<String>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub match_inflections: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_front_matter: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strict_utf8: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub transcode: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub string_values_only: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_line_error_ratio: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_regions: Vec<RegionMarkers>,
+
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub compound_langs: BTreeSet<String>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fold_diacritics: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_acronyms: Option<AcceptAcronyms>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_capitalized: bool,
+
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub builtin_lists: BTreeSet<String>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub use_personal_dictionary: bool,
+
+    #[serde(default, skip_serializing_if = "IgnoreLimits::is_empty")]
+    pub limits: IgnoreLimits,
+
+    #[serde(default, skip_serializing_if = "PoOptions::is_default")]
+    pub po: PoOptions,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extends: Vec<Extends>,
+
+    #[serde(default, skip_serializing_if = "Hooks::is_empty")]
+    pub hooks: Hooks,
+
     #[serde(default)]
     project: BTreeSet<String>,
 
+    // Project words, grouped by a category chosen when adding them -
+    // like `medical = ["dexamethasone"]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    project_categories: BTreeMap<String, BTreeSet<String>>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    lang: BTreeMap<String, BTreeSet<String>>,
+
     #[serde(default)]
     paths: BTreeMap<String, BTreeSet<String>>,
 
     #[serde(default)]
     skipped: BTreeMap<String, BTreeSet<String>>,
+
+    #[serde(default, skip_serializing_if = "LocalRegexes::is_empty")]
+    regex: LocalRegexes,
+
+    // Like GlobalIgnore::identifiers, for this project only
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    identifiers: BTreeSet<String>,
+}
+
+/// Value of `accept_acronyms` in the local config: either `true`, `false`
+/// or the minimum length of the acronyms to accept
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AcceptAcronyms {
+    Enabled(bool),
+    MinLength(usize),
+}
+
+/// Used when `max_line_error_ratio` is not set
+pub const DEFAULT_MAX_LINE_ERROR_RATIO: f64 = 0.8;
+
+/// Used for `accept_acronyms = true`
+pub const DEFAULT_ACRONYM_MIN_LENGTH: usize = 2;
+
+impl AcceptAcronyms {
+    pub fn min_length(self) -> Option<usize> {
+        match self {
+            AcceptAcronyms::Enabled(true) => Some(DEFAULT_ACRONYM_MIN_LENGTH),
+            AcceptAcronyms::Enabled(false) => None,
+            AcceptAcronyms::MinLength(n) => Some(n),
+        }
+    }
+}
+
+/// Value of the `limits` table in the local config: the maximum number of
+/// words in each ignore list. The `extension`, `lang` and `path` limits
+/// apply to each list separately, and `lang` to the lists for a lang and
+/// for the project and a lang
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IgnoreLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extension: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<usize>,
+}
+
+impl IgnoreLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Size of an ignore list with a limit, see `IgnoreStore::limit_usage()`
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct LimitUsage {
+    /// Same as `Operation::scope()`
+    pub scope: String,
+    pub words: usize,
+    pub limit: usize,
+}
+
+// Fails if adding `word` to `words` would go past `limit`. Words already
+// in the list can always be added again
+fn check_limit(
+    limit: Option<usize>,
+    scope: impl Into<String>,
+    words: Option<&BTreeSet<String>>,
+    word: &str,
+) -> Result<()> {
+    let limit = match limit {
+        Some(l) => l,
+        None => return Ok(()),
+    };
+    let size = words.map_or(0, |w| w.len());
+    if size < limit || words.is_some_and(|w| w.contains(word)) {
+        return Ok(());
+    }
+    Err(SkyspellError::LimitReached {
+        scope: scope.into(),
+        limit,
+    }
+    .into())
+}
+
+/// Entry of the `extends` list in the local config: another local ignore
+/// file, whose project words are ignored too. Relative paths are resolved
+/// from the directory of the file containing the entry
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Extends {
+    Path(String),
+    Table {
+        path: String,
+        // Don't fail if the file does not exist
+        #[serde(default)]
+        optional: bool,
+    },
+}
+
+impl Extends {
+    pub fn path(&self) -> &str {
+        match self {
+            Extends::Path(p) => p,
+            Extends::Table { path, .. } => path,
+        }
+    }
+
+    pub fn optional(&self) -> bool {
+        match self {
+            Extends::Path(_) => false,
+            Extends::Table { optional, .. } => *optional,
+        }
+    }
+}
+
+// A local ignore file listed in `extends`, directly or not.
+// Never written to
+#[derive(Debug, Clone)]
+struct ExtendedIgnore {
+    path: PathBuf,
+    ignore: LocalIgnore,
+}
+
+/// An ignored word, and the file it comes from
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct IgnoredWord {
+    pub word: String,
+    pub scope: String,
+    pub source: String,
+    // Set when `word` is a regular expression
+    pub regex: bool,
+    // Only for project words added with a category
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
+/// Regular expressions matching the first and last lines of
+/// a region that should not be checked
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RegionMarkers {
+    pub begin: String,
+    pub end: String,
 }
 
 impl LocalIgnore {
     pub fn load(path: &Path) -> Result<Self> {
         if path.exists() {
-            load(path)
+            load_local(path)
         } else {
             Ok(Default::default())
         }
     }
+
+    /// The project entries of the file, as the operations adding them
+    /// to a project, followed by the skip patterns. Used to import them
+    /// into another project
+    pub fn entries(&self) -> Vec<Operation> {
+        let mut res = vec![];
+        for word in &self.project {
+            res.push(Operation::new_ignore_for_project(word));
+        }
+        for (category, words) in &self.project_categories {
+            for word in words {
+                res.push(Operation::new_ignore_for_project_in_category(
+                    word, category,
+                ));
+            }
+        }
+        for (lang, words) in &self.lang {
+            for word in words {
+                res.push(Operation::new_ignore_for_project_and_lang(word, lang));
+            }
+        }
+        for (path, words) in &self.paths {
+            let relative_path = RelativePath::from_path_unchecked(PathBuf::from(path));
+            for word in words {
+                res.push(Operation::new_ignore_for_path(word, &relative_path));
+            }
+        }
+        for identifier in &self.identifiers {
+            res.push(Operation::new_ignore_identifier_for_project(identifier));
+        }
+        for pattern in &self.patterns {
+            res.push(Operation::new_skip(pattern));
+        }
+        res
+    }
+
+    // The project words with a category and the ones without are looked
+    // up as a single list
+    fn project_words(&self) -> impl Iterator<Item = &String> {
+        self.project
+            .iter()
+            .chain(self.project_categories.values().flatten())
+    }
+
+    fn has_project_word(&self, word: &str) -> bool {
+        self.project.contains(word) || self.project_categories.values().any(|w| w.contains(word))
+    }
+
+    fn all_project_words(&self) -> BTreeSet<String> {
+        self.project_words().cloned().collect()
+    }
+
+    // Remove `word` wherever it is, and the categories left empty
+    fn remove_project_word(&mut self, word: &str) -> bool {
+        let mut present = self.project.remove(word);
+        for words in self.project_categories.values_mut() {
+            present |= words.remove(word);
+        }
+        self.project_categories.retain(|_, words| !words.is_empty());
+        present
+    }
 }
 
-#[derive(Debug)]
+/// The rule that causes a word to be ignored, see `IgnoreStore::ignore_reason()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IgnoreReason {
+    Global,
+    ProjectAndLang(String),
+    Lang(String),
+    Extension(String),
+    Project,
+    /// The path, as stored in the local ignore file
+    Path(String),
+    /// The path of the extended file
+    Extended(PathBuf),
+    /// The word is an inflection of `candidate`, which is ignored
+    Inflection {
+        candidate: String,
+        reason: Box<IgnoreReason>,
+    },
+    /// The pattern that matched
+    Regex(String),
+    Builtin,
+    /// The path of the personal dictionary of the provider
+    PersonalDictionary(PathBuf),
+}
+
+impl std::fmt::Display for IgnoreReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IgnoreReason::Global => write!(f, "in the global ignore list"),
+            IgnoreReason::ProjectAndLang(lang) => {
+                write!(f, "in the ignore list for the project and lang '{lang}'")
+            }
+            IgnoreReason::Lang(lang) => write!(f, "in the ignore list for lang '{lang}'"),
+            IgnoreReason::Extension(e) => write!(f, "in the ignore list for extension '{e}'"),
+            IgnoreReason::Project => write!(f, "in the ignore list for the project"),
+            IgnoreReason::Path(path) => write!(f, "in the ignore list for path '{path}'"),
+            IgnoreReason::Extended(path) => {
+                write!(f, "in the extended ignore file {}", path.display())
+            }
+            IgnoreReason::Inflection { candidate, reason } => {
+                write!(f, "an inflection of '{candidate}', which is {reason}")
+            }
+            IgnoreReason::Regex(pattern) => write!(f, "matching the regex '{pattern}'"),
+            IgnoreReason::Builtin => write!(f, "in one of the enabled builtin lists"),
+            IgnoreReason::PersonalDictionary(path) => {
+                write!(f, "in the personal dictionary {}", path.display())
+            }
+        }
+    }
+}
+
+/// The values returned by `IgnoreReason::source()`
+pub const IGNORE_SOURCES: &[&str] = &[
+    "global",
+    "project-and-lang",
+    "lang",
+    "extension",
+    "project",
+    "path",
+    "extended",
+    "regex",
+    "builtin",
+    "personal-dictionary",
+];
+
+impl IgnoreReason {
+    /// The kind of rule, without its details, like `extension` - for
+    /// inflections, the one of the rule ignoring the candidate
+    pub fn source(&self) -> &'static str {
+        match self {
+            IgnoreReason::Global => "global",
+            IgnoreReason::ProjectAndLang(_) => "project-and-lang",
+            IgnoreReason::Lang(_) => "lang",
+            IgnoreReason::Extension(_) => "extension",
+            IgnoreReason::Project => "project",
+            IgnoreReason::Path(_) => "path",
+            IgnoreReason::Extended(_) => "extended",
+            IgnoreReason::Inflection { reason, .. } => reason.source(),
+            IgnoreReason::Regex(_) => "regex",
+            IgnoreReason::Builtin => "builtin",
+            IgnoreReason::PersonalDictionary(_) => "personal-dictionary",
+        }
+    }
+
+    /// A short identifier for programs, like `ignored-for-extension(py)`
+    pub fn key(&self) -> String {
+        match self {
+            IgnoreReason::Global => "ignored-globally".to_string(),
+            IgnoreReason::ProjectAndLang(lang) => format!("ignored-for-project-and-lang({lang})"),
+            IgnoreReason::Lang(lang) => format!("ignored-for-lang({lang})"),
+            IgnoreReason::Extension(e) => format!("ignored-for-extension({e})"),
+            IgnoreReason::Project => "ignored-for-project".to_string(),
+            IgnoreReason::Path(path) => format!("ignored-for-path({path})"),
+            IgnoreReason::Extended(path) => format!("ignored-by-extended-file({})", path.display()),
+            IgnoreReason::Inflection { candidate, .. } => {
+                format!("ignored-as-inflection-of({candidate})")
+            }
+            IgnoreReason::Regex(pattern) => format!("ignored-by-regex({pattern})"),
+            IgnoreReason::Builtin => "ignored-by-builtin-list".to_string(),
+            IgnoreReason::PersonalDictionary(path) => {
+                format!("ignored-by-personal-dictionary({})", path.display())
+            }
+        }
+    }
+}
+
+/// Number of lookups performed in each ignore list by `should_ignore()`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct IgnoreStats {
+    pub words: u64,
+    /// Words that none of the lists ignored
+    pub not_ignored: u64,
+    pub global: u64,
+    pub project_and_lang: u64,
+    pub lang: u64,
+    pub extension: u64,
+    pub project: u64,
+    pub path: u64,
+    pub extended: u64,
+    pub regex: u64,
+}
+
+// Used to add up the stats of several copies of the store
+impl std::ops::AddAssign for IgnoreStats {
+    fn add_assign(&mut self, other: Self) {
+        self.words += other.words;
+        self.not_ignored += other.not_ignored;
+        self.global += other.global;
+        self.project_and_lang += other.project_and_lang;
+        self.lang += other.lang;
+        self.extension += other.extension;
+        self.project += other.project;
+        self.path += other.path;
+        self.extended += other.extended;
+        self.regex += other.regex;
+    }
+}
+
+/// The entries of the ignore lists that caused at least one word to be
+/// ignored, as (scope, word) pairs - the scopes are the ones returned by
+/// `Operation::scope()`, or "regex" for the patterns - with the number of
+/// words they caused to be ignored. See `IgnoreStore::unused_entries()`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UsedEntries(BTreeMap<(String, String), usize>);
+
+impl UsedEntries {
+    pub fn contains(&self, operation: &Operation) -> bool {
+        self.0
+            .contains_key(&(operation.scope(), operation.word().to_string()))
+    }
+
+    /// The scope, the word and the number of hits of each entry
+    pub fn hits(&self) -> impl Iterator<Item = (&str, &str, usize)> {
+        self.0
+            .iter()
+            .map(|((scope, word), hits)| (scope.as_str(), word.as_str(), *hits))
+    }
+}
+
+// Used to merge the entries used by several copies of the store
+impl std::ops::AddAssign for UsedEntries {
+    fn add_assign(&mut self, other: Self) {
+        for (entry, hits) in other.0 {
+            *self.0.entry(entry).or_default() += hits;
+        }
+    }
+}
+
+/// Cloning the store is cheap enough to give a copy to each thread
+/// checking files in parallel. Each copy has its own statistics
+#[derive(Debug, Clone)]
 pub struct IgnoreStore {
     global: GlobalIgnore,
     local: LocalIgnore,
+    extended: Vec<ExtendedIgnore>,
+    regexes: CompiledRegexes,
+    // Built from the patterns of the local file, and rebuilt each time
+    // they change
+    skip_file: SkipFile,
+    skipped_regions: Vec<SkippedRegion>,
+    acronyms_min_length: Option<usize>,
+    ignore_capitalized: bool,
+    strict_utf8: bool,
+    transcode: bool,
+    po_options: PoOptions,
+    max_line_error_ratio: f64,
+    builtin_words: BuiltinWords,
+    personal_dictionary: Option<PersonalDictionary>,
+    // Sources of IgnoreReason::source(), see set_strict_scopes()
+    strict_scopes: Option<Vec<String>>,
+    // Normalized relative path -> extension, see set_assumed_extension()
+    assumed_extensions: BTreeMap<String, String>,
+    stats: Cell<IgnoreStats>,
+    // Only filled when record_uses is set: this allocates for each ignored word
+    record_uses: bool,
+    used: RefCell<UsedEntries>,
     global_toml: PathBuf,
     local_toml: PathBuf,
+    // Set when the local ignore file could not be read, so that we
+    // don't overwrite it
+    local_is_broken: bool,
 }
 
 fn load<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
     if !path.exists() {
         return Ok(Default::default());
     }
-    let contents = std::fs::read_to_string(path)
-        .with_context(|| format!("While reading {}:", path.display()))?;
-    toml::from_str(&contents).with_context(|| format!("While parsing {}:", path.display()))
+    let contents = std::fs::read_to_string(path).map_err(|e| SkyspellError::io(path, e))?;
+    if let Some(line) = find_conflict_marker(&contents) {
+        return Err(SkyspellError::IgnoreStoreParse {
+            path: path.to_path_buf(),
+            line,
+            column: 1,
+            message: "found a merge conflict marker - please resolve the conflict first"
+                .to_string(),
+        }
+        .into());
+    }
+    toml::from_str(&contents).map_err(|e| {
+        let mut error = SkyspellError::parse(path, &contents, e);
+        let backup = backup_path(path);
+        if let SkyspellError::IgnoreStoreParse { message, .. } = &mut error {
+            if backup.exists() && load::<T>(&backup).is_ok() {
+                message.push_str(&format!(
+                    "\nThe previous version is in {} - run skyspell with --recover-ignore-file to restore it",
+                    backup.display()
+                ));
+            }
+        }
+        error.into()
+    })
+}
+
+// Replace the file at `path` by its backup, if the file cannot be parsed
+// and the backup can. Returns true if the backup was restored
+fn recover<T: DeserializeOwned + Default>(path: &Path) -> Result<bool> {
+    let _lock = FileLock::acquire(path)?;
+    let is_broken = match load::<T>(path) {
+        Ok(_) => false,
+        Err(e) => matches!(
+            e.downcast_ref(),
+            Some(SkyspellError::IgnoreStoreParse { .. })
+        ),
+    };
+    let backup = backup_path(path);
+    if !is_broken || !backup.exists() || load::<T>(&backup).is_err() {
+        return Ok(false);
+    }
+    let contents = std::fs::read(&backup).map_err(|e| SkyspellError::io(&backup, e))?;
+    write_atomically(path, &contents).map_err(|e| SkyspellError::io(path, e))?;
+    Ok(true)
+}
+
+// The local ignore file is at the root of the project
+fn project_root(local_toml: &Path) -> &Path {
+    local_toml.parent().unwrap_or_else(|| Path::new("."))
+}
+
+// Return the line number of the first merge conflict marker, if any
+fn find_conflict_marker(contents: &str) -> Option<usize> {
+    contents
+        .lines()
+        .position(|line| {
+            line.starts_with("<<<<<<<") || line.starts_with(">>>>>>>") || line == "======="
+        })
+        .map(|index| index + 1)
 }
 
+// Load the files listed in `extends`, then the files they extend, and so on.
+// `chain` contains the canonical paths of the files being loaded, so that
+// cycles can be detected
+fn skipped_regions(local_toml: &Path, local: &LocalIgnore) -> Result<Vec<SkippedRegion>> {
+    local
+        .skip_regions
+        .iter()
+        .map(|r| SkippedRegion::new(&r.begin, &r.end))
+        .collect::<Result<_>>()
+        .with_context(|| format!("While parsing {}:", local_toml.display()))
+}
+
+// The files extended by the local file, and by the files it extends
+fn extended(local_toml: &Path, local: &LocalIgnore) -> Result<Vec<ExtendedIgnore>> {
+    let mut res = vec![];
+    let mut chain = vec![local_toml
+        .canonicalize()
+        .unwrap_or_else(|_| local_toml.to_path_buf())];
+    load_extended(local_toml, local, &mut chain, &mut res)?;
+    Ok(res)
+}
+
+fn load_extended(
+    path: &Path,
+    local: &LocalIgnore,
+    chain: &mut Vec<PathBuf>,
+    res: &mut Vec<ExtendedIgnore>,
+) -> Result<()> {
+    let directory = path.parent().unwrap_or_else(|| Path::new("."));
+    for extends in &local.extends {
+        let target = directory.join(extends.path());
+        if !target.exists() {
+            if extends.optional() {
+                continue;
+            }
+            bail!(
+                "{}: cannot extend {}: file not found - use {{ path = \"{}\", optional = true }} if it may be missing",
+                path.display(),
+                target.display(),
+                extends.path(),
+            );
+        }
+        let canonical = target
+            .canonicalize()
+            .with_context(|| format!("Could not canonicalize {}", target.display()))?;
+        if chain.contains(&canonical) {
+            let cycle: Vec<_> = chain
+                .iter()
+                .chain([&canonical])
+                .map(|p| p.display().to_string())
+                .collect();
+            bail!("Cycle in extends: {}", cycle.join(" -> "));
+        }
+        let ignore = load_local(&target)?;
+        // Closer files come first
+        let index = res.len();
+        chain.push(canonical);
+        load_extended(&target, &ignore, chain, res)?;
+        chain.pop();
+        res.insert(
+            index,
+            ExtendedIgnore {
+                path: target,
+                ignore,
+            },
+        );
+    }
+    Ok(())
+}
+
+fn load_local(path: &Path) -> Result<LocalIgnore> {
+    let mut local: LocalIgnore = load(path)?;
+    local.paths = normalize_keys(std::mem::take(&mut local.paths));
+    local.skipped = normalize_keys(std::mem::take(&mut local.skipped));
+    Ok(local)
+}
+
+// Other processes never read a half-written file, and the previous
+// version is kept for recover()
 fn save<T: Serialize>(name: &'static str, value: T, path: &Path) -> Result<()> {
     let contents = toml::ser::to_string_pretty(&value)
         .with_context(|| format!("while serializing {name} values"))?;
-    std::fs::write(path, contents)
-        .with_context(|| format!("while writing to {}", path.display()))?;
-    Ok(())
+    write_with_backup(path, contents.as_bytes()).map_err(|e| SkyspellError::io(path, e).into())
 }
 
 pub fn global_path() -> Result<PathBuf> {
@@ -84,6 +705,53 @@ pub fn global_path() -> Result<PathBuf> {
     Ok(data_dir.join("global.toml"))
 }
 
+type WordsForPath = BTreeMap<String, BTreeSet<String>>;
+
+fn regex_scope(extension: Option<&str>) -> String {
+    match extension {
+        Some(e) => format!("extension {e}"),
+        None => "global".to_string(),
+    }
+}
+
+// Paths are case-insensitive on Windows: reuse the existing key
+// matching `path`, if any
+fn path_key(map: &WordsForPath, path: &str) -> String {
+    if cfg!(windows) && !map.contains_key(path) {
+        let lower = path.to_lowercase();
+        if let Some(key) = map.keys().find(|k| k.to_lowercase() == lower) {
+            return key.clone();
+        }
+    }
+    path.to_string()
+}
+
+// Files written by older versions, or on Windows, may contain backslashes
+fn normalize_keys(map: WordsForPath) -> WordsForPath {
+    let mut res = WordsForPath::new();
+    for (path, words) in map {
+        let key = path_key(&res, &normalize_path(&path));
+        res.entry(key).or_default().extend(words);
+    }
+    res
+}
+
+// The entries of `words` for ignored_words()
+fn ignored_words_in<'a>(
+    words: &'a BTreeSet<String>,
+    scope: &'a str,
+    source: &'a Path,
+    regex: bool,
+) -> impl Iterator<Item = IgnoredWord> + 'a {
+    words.iter().map(move |word| IgnoredWord {
+        word: word.to_string(),
+        scope: scope.to_string(),
+        source: source.display().to_string(),
+        regex,
+        category: None,
+    })
+}
+
 /// Since the Win32 API and Enchant do not use the same language tags,
 /// we remove only keep the stuff before `-` or `_` before storing them
 /// in the global configuration file
@@ -95,13 +763,104 @@ fn short_lang(lang: &str) -> &str {
 
 impl IgnoreStore {
     pub fn load(global_toml: PathBuf, local_toml: PathBuf) -> Result<Self> {
-        let global = load(&global_toml)?;
-        let local = load(&local_toml)?;
+        let global: GlobalIgnore = load(&global_toml)?;
+        let local = load_local(&local_toml)?;
+        let skipped_regions = skipped_regions(&local_toml, &local)?;
+        let acronyms_min_length = local.accept_acronyms.and_then(|a| a.min_length());
+        let ignore_capitalized = local.ignore_capitalized;
+        let strict_utf8 = local.strict_utf8;
+        let transcode = local.transcode;
+        let po_options = local.po;
+        let max_line_error_ratio = local
+            .max_line_error_ratio
+            .unwrap_or(DEFAULT_MAX_LINE_ERROR_RATIO);
+        let mut builtin_words = BuiltinWords::default();
+        for name in &local.builtin_lists {
+            builtin_words
+                .enable(name)
+                .with_context(|| format!("While parsing {}:", local_toml.display()))?;
+        }
+        let extended = extended(&local_toml, &local)?;
+        let regexes = CompiledRegexes::new(&global.regex, &local.regex).with_context(|| {
+            format!(
+                "While loading regexes from {} and {}:",
+                global_toml.display(),
+                local_toml.display()
+            )
+        })?;
+        let skip_file = SkipFile::new(project_root(&local_toml), &local)?;
         Ok(Self {
             global,
             local,
+            extended,
+            regexes,
+            skip_file,
+            skipped_regions,
+            acronyms_min_length,
+            ignore_capitalized,
+            strict_utf8,
+            transcode,
+            po_options,
+            max_line_error_ratio,
+            builtin_words,
+            personal_dictionary: None,
+            strict_scopes: None,
+            assumed_extensions: BTreeMap::new(),
+            stats: Default::default(),
+            record_uses: false,
+            used: Default::default(),
             global_toml,
             local_toml,
+            local_is_broken: false,
+        })
+    }
+
+    /// Restore the backups of the ignore files that cannot be parsed, if
+    /// the backups can. Each save keeps the previous version of the file
+    /// next to it, like `skyspell-ignore.toml.bak`. Returns the paths of
+    /// the restored files
+    pub fn recover(global_toml: &Path, local_toml: &Path) -> Result<Vec<PathBuf>> {
+        let mut res = vec![];
+        if recover::<GlobalIgnore>(global_toml)? {
+            res.push(global_toml.to_path_buf());
+        }
+        if recover::<LocalIgnore>(local_toml)? {
+            res.push(local_toml.to_path_buf());
+        }
+        Ok(res)
+    }
+
+    /// Load the global ignore file only, and use an empty local ignore list.
+    /// Used when the local ignore file is broken - trying to change it
+    /// is an error.
+    pub fn load_without_local(global_toml: PathBuf, local_toml: PathBuf) -> Result<Self> {
+        let global: GlobalIgnore = load(&global_toml)?;
+        let regexes = CompiledRegexes::new(&global.regex, &Default::default())
+            .with_context(|| format!("While loading regexes from {}:", global_toml.display()))?;
+        let skip_file = SkipFile::new(project_root(&local_toml), &Default::default())?;
+        Ok(Self {
+            global,
+            local: Default::default(),
+            extended: vec![],
+            regexes,
+            skip_file,
+            skipped_regions: vec![],
+            acronyms_min_length: None,
+            ignore_capitalized: false,
+            strict_utf8: false,
+            transcode: false,
+            po_options: Default::default(),
+            max_line_error_ratio: DEFAULT_MAX_LINE_ERROR_RATIO,
+            builtin_words: Default::default(),
+            personal_dictionary: None,
+            strict_scopes: None,
+            assumed_extensions: BTreeMap::new(),
+            stats: Default::default(),
+            record_uses: false,
+            used: Default::default(),
+            global_toml,
+            local_toml,
+            local_is_broken: true,
         })
     }
 
@@ -110,40 +869,787 @@ impl IgnoreStore {
     //
     // A word is ignored if:
     //   * it's in the global ignore list
+    //   * it's in the ignore list for the project and the current language
+    //   * it's in the global ignore list for the current language
     //   * the relative path has an extension and it's in the ignore list
     //     for this extension
     //   * it's in the ignore list for the project
     //   * it's in the ignore list for the relative path
+    //   * it's in the project ignore list of one of the files listed
+    //     in `extends`, or of the files they extend
+    //
+    // If `match_inflections` is set in the local config, the same rules
+    // are then applied to the candidates returned by inflection_candidates(),
+    // so that 'frobnicates' is ignored when 'frobnicate' is.
+    //
+    // Then, the word is ignored if it matches one of the regexes for the
+    // project, or for the extension of the relative path, or the global ones
+    //
+    // Finally, the word is ignored if it's in one of the enabled builtin lists,
+    // or in the personal dictionary of the provider, if one was loaded
     //
     // Otherwise, it's *not* ignored and the Checker will call handle_error()
     //
+    // For foreign files (see RelativePath::is_foreign()), only the global
+    // list, the lists for the language and the extension, the global
+    // regexes and the personal dictionary are used.
+    //
     pub fn should_ignore(&self, word: &str, relative_path: &RelativePath, lang: &str) -> bool {
+        self.ignore_reason(word, relative_path, lang).is_some()
+    }
+
+    /// Like should_ignore(), but return the rule that causes the word
+    /// to be ignored, if any
+    pub fn ignore_reason(
+        &self,
+        word: &str,
+        relative_path: &RelativePath,
+        lang: &str,
+    ) -> Option<IgnoreReason> {
+        self.ignore_reasons(word, relative_path, lang, false)
+            .into_iter()
+            .next()
+    }
+
+    /// Like ignore_reason(), but return every rule that causes the word
+    /// to be ignored, in the same order, instead of stopping at the first
+    /// one. Only the first one is recorded, see set_record_uses()
+    pub fn all_ignore_reasons(
+        &self,
+        word: &str,
+        relative_path: &RelativePath,
+        lang: &str,
+    ) -> Vec<IgnoreReason> {
+        self.ignore_reasons(word, relative_path, lang, true)
+    }
+
+    fn ignore_reasons(
+        &self,
+        word: &str,
+        relative_path: &RelativePath,
+        lang: &str,
+        all: bool,
+    ) -> Vec<IgnoreReason> {
+        self.count(|s| s.words += 1);
+        let mut res = self.ignored_by_rules(word, relative_path, lang, all);
+
+        if self.local.match_inflections && (all || res.is_empty()) {
+            for candidate in inflection_candidates(word) {
+                for reason in self.ignored_by_rules(&candidate, relative_path, lang, all) {
+                    res.push(IgnoreReason::Inflection {
+                        candidate: candidate.clone(),
+                        reason: Box::new(reason),
+                    });
+                }
+                if !all && !res.is_empty() {
+                    break;
+                }
+            }
+        }
+
+        if !self.regexes.is_empty() && (all || res.is_empty()) {
+            self.count(|s| s.regex += 1);
+            let extension = self.extension_of(relative_path);
+            let pattern = if relative_path.is_foreign() {
+                self.regexes.find_global_match(word, extension.as_deref())
+            } else {
+                self.regexes.find_match(word, extension.as_deref())
+            };
+            if let Some(pattern) = pattern {
+                res.push(IgnoreReason::Regex(pattern.to_string()));
+            }
+        }
+
+        if (all || res.is_empty())
+            && !relative_path.is_foreign()
+            && self.builtin_words.contains(word)
+        {
+            res.push(IgnoreReason::Builtin);
+        }
+
+        // Like the global list, this belongs to the user and not to the project
+        if let Some(personal_dictionary) = &self.personal_dictionary {
+            if (all || res.is_empty()) && personal_dictionary.contains(word) {
+                let path = personal_dictionary.path().to_path_buf();
+                res.push(IgnoreReason::PersonalDictionary(path));
+            }
+        }
+
+        match res.first() {
+            Some(IgnoreReason::Inflection { candidate, reason }) => {
+                self.record_use(candidate, reason)
+            }
+            Some(reason @ IgnoreReason::Regex(pattern)) => self.record_use(pattern, reason),
+            Some(reason) => self.record_use(word, reason),
+            None => self.count(|s| s.not_ignored += 1),
+        }
+        res
+    }
+
+    // The rules of the ignore lists, see should_ignore() - with `all`,
+    // keep looking once one of them ignores the word
+    fn ignored_by_rules(
+        &self,
+        word: &str,
+        relative_path: &RelativePath,
+        lang: &str,
+        all: bool,
+    ) -> Vec<IgnoreReason> {
+        let mut res = vec![];
+
+        self.count(|s| s.global += 1);
         if self.is_ignored(word) {
-            return true;
+            res.push(IgnoreReason::Global);
+            if !all {
+                return res;
+            }
         }
 
+        // The ignore lists of the project don't apply to foreign files
+        let for_project = !relative_path.is_foreign();
+
+        self.count(|s| s.project_and_lang += 1);
+        if for_project && self.is_ignored_for_project_and_lang(word, lang) {
+            res.push(IgnoreReason::ProjectAndLang(short_lang(lang).to_string()));
+            if !all {
+                return res;
+            }
+        }
+
+        self.count(|s| s.lang += 1);
         if self.is_ignored_for_lang(word, lang) {
-            return true;
+            res.push(IgnoreReason::Lang(short_lang(lang).to_string()));
+            if !all {
+                return res;
+            }
         }
 
-        if let Some(e) = relative_path.extension() {
+        if let Some(e) = self.extension_of(relative_path) {
+            self.count(|s| s.extension += 1);
             if self.is_ignored_for_extension(word, &e) {
-                return true;
+                res.push(IgnoreReason::Extension(e.to_string()));
+                if !all {
+                    return res;
+                }
             }
         }
 
+        if !for_project {
+            return res;
+        }
+
+        self.count(|s| s.project += 1);
         if self.is_ignored_for_project(word) {
-            return true;
+            res.push(IgnoreReason::Project);
+            if !all {
+                return res;
+            }
         }
 
+        self.count(|s| s.path += 1);
         if self.is_ignored_for_path(word, relative_path) {
-            return true;
+            res.push(IgnoreReason::Path(relative_path.normalize()));
+            if !all {
+                return res;
+            }
+        }
+
+        if !self.extended.is_empty() {
+            self.count(|s| s.extended += 1);
+            if let Some(path) = self.extended_ignoring(word, lang) {
+                res.push(IgnoreReason::Extended(path.to_path_buf()));
+            }
+        }
+
+        res
+    }
+
+    // Only the project words of the extended files are used - paths in
+    // there are relative to another project
+    fn extended_ignoring(&self, word: &str, lang: &str) -> Option<&Path> {
+        let lang = short_lang(lang);
+        self.extended
+            .iter()
+            .find(|e| {
+                e.ignore.has_project_word(word)
+                    || e.ignore.lang.get(lang).is_some_and(|s| s.contains(word))
+            })
+            .map(|e| e.path.as_path())
+    }
+
+    fn count(&self, update: impl FnOnce(&mut IgnoreStats)) {
+        let mut stats = self.stats.get();
+        update(&mut stats);
+        self.stats.set(stats);
+    }
+
+    pub fn stats(&self) -> IgnoreStats {
+        self.stats.get()
+    }
+
+    /// Keep track of the entries that cause words to be ignored, see
+    /// used_entries() and unused_entries()
+    pub fn set_record_uses(&mut self, record_uses: bool) {
+        self.record_uses = record_uses;
+    }
+
+    // Only the entries of the ignore lists and the regexes are recorded
+    fn record_use(&self, word: &str, reason: &IgnoreReason) {
+        if !self.record_uses {
+            return;
+        }
+        let scope = match reason {
+            IgnoreReason::Global => "global".to_string(),
+            IgnoreReason::ProjectAndLang(lang) => format!("project and lang {lang}"),
+            IgnoreReason::Lang(lang) => format!("lang {lang}"),
+            IgnoreReason::Extension(extension) => format!("extension {extension}"),
+            IgnoreReason::Project => "project".to_string(),
+            IgnoreReason::Path(path) => format!("path {}", path_key(&self.local.paths, path)),
+            IgnoreReason::Regex(_) => "regex".to_string(),
+            _ => return,
+        };
+        *self
+            .used
+            .borrow_mut()
+            .0
+            .entry((scope, word.to_string()))
+            .or_default() += 1;
+    }
+
+    /// The entries that caused at least one word to be ignored since
+    /// record_uses was set
+    pub fn used_entries(&self) -> UsedEntries {
+        self.used.borrow().clone()
+    }
+
+    /// Add the entries used by another copy of the store, for instance
+    /// one used by a worker thread
+    pub fn add_used_entries(&self, used: UsedEntries) {
+        *self.used.borrow_mut() += used;
+    }
+
+    /// One operation for each entry of the global and local ignore lists
+    /// that did not cause any word to be ignored since record_uses was set.
+    /// Undoing them removes the entries
+    pub fn unused_entries(&self) -> Vec<Operation> {
+        let used = self.used.borrow();
+        self.all_entries()
+            .into_iter()
+            .filter(|o| !used.contains(o))
+            .collect()
+    }
+
+    // Like operations_for(), for every word
+    fn all_entries(&self) -> Vec<Operation> {
+        let mut res = vec![];
+        for word in &self.global.global {
+            res.push(Operation::new_ignore(word));
+        }
+        for (extension, words) in &self.global.extensions {
+            for word in words {
+                res.push(Operation::new_ignore_for_extension(word, extension));
+            }
+        }
+        for (lang, words) in &self.global.lang {
+            for word in words {
+                res.push(Operation::new_ignore_for_lang(word, lang));
+            }
+        }
+        for word in self.local.project_words() {
+            res.push(Operation::new_ignore_for_project(word));
+        }
+        for (lang, words) in &self.local.lang {
+            for word in words {
+                res.push(Operation::new_ignore_for_project_and_lang(word, lang));
+            }
+        }
+        for (path, words) in &self.local.paths {
+            let relative_path = RelativePath::from_path_unchecked(PathBuf::from(path));
+            for word in words {
+                res.push(Operation::new_ignore_for_path(word, &relative_path));
+            }
+        }
+        res
+    }
+
+    /// Ignore the words of the builtin list with the given name, in
+    /// addition to the ones enabled in the local config
+    pub fn enable_builtin_list(&mut self, name: &str) -> Result<()> {
+        self.builtin_words.enable(name)
+    }
+
+    /// True if `use_personal_dictionary` is set in the local config -
+    /// see PersonalDictionary::find()
+    pub fn use_personal_dictionary(&self) -> bool {
+        self.local.use_personal_dictionary
+    }
+
+    /// Also ignore the words of this personal dictionary. It is never
+    /// written to, and the operations don't change it
+    pub fn set_personal_dictionary(&mut self, personal_dictionary: PersonalDictionary) {
+        self.personal_dictionary = Some(personal_dictionary);
+    }
+
+    /// Words that must never be suggested as replacements - the
+    /// forbidden words of the personal dictionary, if any
+    pub fn forbidden_words(&self) -> HashSet<String> {
+        self.personal_dictionary
+            .as_ref()
+            .map(|d| d.forbidden_words().clone())
+            .unwrap_or_default()
+    }
+
+    /// The size of each ignore list with a limit set in the local config
+    pub fn limit_usage(&self) -> Vec<LimitUsage> {
+        let limits = self.local.limits;
+        let mut res = vec![];
+        let mut add = |limit: Option<usize>, scope: String, words: &BTreeSet<String>| {
+            if let Some(limit) = limit {
+                res.push(LimitUsage {
+                    scope,
+                    words: words.len(),
+                    limit,
+                });
+            }
+        };
+        add(limits.global, "global".to_string(), &self.global.global);
+        for (extension, words) in &self.global.extensions {
+            add(limits.extension, format!("extension {extension}"), words);
+        }
+        for (lang, words) in &self.global.lang {
+            add(limits.lang, format!("lang {lang}"), words);
+        }
+        let project_words = self.local.all_project_words();
+        add(limits.project, "project".to_string(), &project_words);
+        for (lang, words) in &self.local.lang {
+            add(limits.lang, format!("project and lang {lang}"), words);
+        }
+        for (path, words) in &self.local.paths {
+            add(limits.path, format!("path {path}"), words);
+        }
+        res
+    }
+
+    /// If set, all-caps words at least this long are not looked up
+    pub fn acronyms_min_length(&self) -> Option<usize> {
+        self.acronyms_min_length
+    }
+
+    /// Override the `accept_acronyms` setting of the local config
+    pub fn set_accept_acronyms(&mut self, accept_acronyms: AcceptAcronyms) {
+        self.acronyms_min_length = accept_acronyms.min_length();
+    }
+
+    /// Errors are not reported for lines where the ratio of unknown words is
+    /// greater than this - 1 means all lines are checked
+    pub fn max_line_error_ratio(&self) -> f64 {
+        self.max_line_error_ratio
+    }
+
+    /// Override the `max_line_error_ratio` setting of the local config
+    pub fn set_max_line_error_ratio(&mut self, max_line_error_ratio: f64) {
+        self.max_line_error_ratio = max_line_error_ratio;
+    }
+
+    /// If set, capitalized words that do not start a sentence are not
+    /// looked up, see TokenClassifier::classify_token()
+    pub fn ignore_capitalized(&self) -> bool {
+        self.ignore_capitalized
+    }
+
+    /// Override the `ignore_capitalized` setting of the local config
+    pub fn set_ignore_capitalized(&mut self, ignore_capitalized: bool) {
+        self.ignore_capitalized = ignore_capitalized;
+    }
+
+    /// Check the file as if it had this extension ('py' or '.py'): for
+    /// the ignore lists and regexes of the extension, and to choose how
+    /// words are extracted - see Checker::process()
+    pub fn set_assumed_extension(&mut self, relative_path: &RelativePath, extension: &str) {
+        let extension = extension.trim().trim_start_matches('.').to_string();
+        self.assumed_extensions
+            .insert(relative_path.normalize(), extension);
+    }
+
+    /// The extension set with set_assumed_extension(), if any
+    pub fn assumed_extension(&self, relative_path: &RelativePath) -> Option<&str> {
+        self.assumed_extensions
+            .get(&relative_path.normalize())
+            .map(|e| e.as_str())
+    }
+
+    /// The extension the file is checked with: the assumed one if set,
+    /// its own otherwise
+    pub fn extension_of<'a>(&'a self, relative_path: &'a RelativePath) -> Option<Cow<'a, str>> {
+        match self.assumed_extension(relative_path) {
+            Some(e) => Some(Cow::Borrowed(e)),
+            None => relative_path.extension(),
+        }
+    }
+
+    /// If set, files containing invalid UTF-8 cannot be checked
+    pub fn strict_utf8(&self) -> bool {
+        self.strict_utf8
+    }
+
+    /// Override the `strict_utf8` setting of the local config
+    pub fn set_strict_utf8(&mut self, strict_utf8: bool) {
+        self.strict_utf8 = strict_utf8;
+    }
+
+    /// If set, files in a legacy encoding are decoded and checked,
+    /// instead of being skipped - see detect_encoding()
+    pub fn transcode(&self) -> bool {
+        self.transcode
+    }
+
+    /// Override the `transcode` setting of the local config
+    pub fn set_transcode(&mut self, transcode: bool) {
+        self.transcode = transcode;
+    }
+
+    /// The sources of the rules that are trusted when set, see
+    /// set_strict_scopes()
+    pub fn strict_scopes(&self) -> Option<&[String]> {
+        self.strict_scopes.as_deref()
+    }
+
+    /// Only trust the rules with these sources, as returned by
+    /// IgnoreReason::source(): words ignored by the other ones only
+    /// are classified as `Suppressed` instead of `Ignored`
+    pub fn set_strict_scopes(&mut self, scopes: Vec<String>) {
+        self.strict_scopes = Some(scopes);
+    }
+
+    /// Command to run after an operation is performed or undone
+    pub fn post_operation_hook(&self) -> Option<&str> {
+        self.local.hooks.post_operation.as_deref()
+    }
+
+    pub fn skip_front_matter(&self) -> bool {
+        self.local.skip_front_matter
+    }
+
+    /// Which entries of gettext catalogs are checked
+    pub fn po_options(&self) -> PoOptions {
+        self.po_options
+    }
+
+    /// Override the `po` table of the local config
+    pub fn set_po_options(&mut self, po_options: PoOptions) {
+        self.po_options = po_options;
+    }
+
+    /// If set, only the string values of JSON, YAML and TOML files are checked
+    pub fn string_values_only(&self) -> bool {
+        self.local.string_values_only
+    }
+
+    pub fn skipped_regions(&self) -> &[SkippedRegion] {
+        &self.skipped_regions
+    }
+
+    pub fn lang_options(&self) -> LangOptions {
+        LangOptions {
+            compound_langs: self.local.compound_langs.clone(),
+            fold_diacritics: self.local.fold_diacritics,
         }
+    }
+
+    pub fn global_words(&self) -> impl Iterator<Item = &str> {
+        self.global.global.iter().map(|x| x.as_str())
+    }
+
+    pub fn project_words(&self) -> impl Iterator<Item = &str> {
+        self.local.project_words().map(|x| x.as_str())
+    }
+
+    /// Project words added with a category, by category
+    pub fn project_categories(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.local.project_categories
+    }
 
-        false
+    /// Project words coming from the files listed in `extends`
+    pub fn extended_project_words(&self) -> impl Iterator<Item = &str> {
+        self.extended
+            .iter()
+            .flat_map(|e| e.ignore.project_words().map(|x| x.as_str()))
+    }
+
+    /// Words ignored for each extension
+    pub fn extension_words(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.global.extensions
+    }
+
+    /// Words ignored for each lang, in every project
+    pub fn lang_words(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.global.lang
+    }
+
+    /// Words ignored for each lang, in the current project only
+    pub fn project_lang_words(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.local.lang
+    }
+
+    /// Words ignored for each path, relative to the project
+    pub fn path_words(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.local.paths
+    }
+
+    /// Tokens skipped for each path, relative to the project
+    pub fn path_skipped_tokens(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.local.skipped
+    }
+
+    /// Patterns of the files that are never checked
+    pub fn skip_patterns(&self) -> impl Iterator<Item = &str> {
+        self.local.patterns.iter().map(|x| x.as_str())
+    }
+
+    pub fn global_regexes(&self) -> impl Iterator<Item = &str> {
+        self.global.regex.global.iter().map(|x| x.as_str())
+    }
+
+    pub fn extension_regexes(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.global.regex.extensions
+    }
+
+    pub fn project_regexes(&self) -> impl Iterator<Item = &str> {
+        self.local.regex.project.iter().map(|x| x.as_str())
+    }
+
+    /// One operation for each ignore list containing `word` - undoing them
+    /// removes the word everywhere
+    pub fn operations_for(&self, word: &str) -> Vec<Operation> {
+        let mut res = vec![];
+        if self.global.global.contains(word) {
+            res.push(Operation::new_ignore(word));
+        }
+        for (extension, words) in &self.global.extensions {
+            if words.contains(word) {
+                res.push(Operation::new_ignore_for_extension(word, extension));
+            }
+        }
+        for (lang, words) in &self.global.lang {
+            if words.contains(word) {
+                res.push(Operation::new_ignore_for_lang(word, lang));
+            }
+        }
+        if self.local.has_project_word(word) {
+            res.push(Operation::new_ignore_for_project(word));
+        }
+        for (lang, words) in &self.local.lang {
+            if words.contains(word) {
+                res.push(Operation::new_ignore_for_project_and_lang(word, lang));
+            }
+        }
+        for (path, words) in &self.local.paths {
+            if words.contains(word) {
+                let relative_path = RelativePath::from_path_unchecked(PathBuf::from(path));
+                res.push(Operation::new_ignore_for_path(word, &relative_path));
+            }
+        }
+        res
+    }
+
+    /// Every ignored word, from the global file, the local file and
+    /// the files it extends
+    pub fn ignored_words(&self) -> Vec<IgnoredWord> {
+        let global = &self.global;
+        let global_toml = self.global_toml.as_path();
+        let mut res: Vec<_> =
+            ignored_words_in(&global.global, "global", global_toml, false).collect();
+        for (extension, words) in &global.extensions {
+            let scope = format!("extension {extension}");
+            res.extend(ignored_words_in(words, &scope, global_toml, false));
+        }
+        for (lang, words) in &global.lang {
+            let scope = format!("lang {lang}");
+            res.extend(ignored_words_in(words, &scope, global_toml, false));
+        }
+        let locals = [(&self.local, &self.local_toml)]
+            .into_iter()
+            .chain(self.extended.iter().map(|e| (&e.ignore, &e.path)));
+        for (local, source) in locals {
+            res.extend(ignored_words_in(&local.project, "project", source, false));
+            for (category, words) in &local.project_categories {
+                res.extend(ignored_words_in(words, "project", source, false).map(|w| {
+                    IgnoredWord {
+                        category: Some(category.to_string()),
+                        ..w
+                    }
+                }));
+            }
+            for (lang, words) in &local.lang {
+                let scope = format!("project and lang {lang}");
+                res.extend(ignored_words_in(words, &scope, source, false));
+            }
+        }
+        for (path, words) in &self.local.paths {
+            let scope = format!("path {path}");
+            res.extend(ignored_words_in(words, &scope, &self.local_toml, false));
+        }
+        res.extend(ignored_words_in(
+            &global.regex.global,
+            "global",
+            global_toml,
+            true,
+        ));
+        for (extension, patterns) in &global.regex.extensions {
+            let scope = format!("extension {extension}");
+            res.extend(ignored_words_in(patterns, &scope, global_toml, true));
+        }
+        let local_regexes = &self.local.regex.project;
+        res.extend(ignored_words_in(
+            local_regexes,
+            "project",
+            &self.local_toml,
+            true,
+        ));
+        res.extend(ignored_words_in(
+            &global.identifiers,
+            "identifiers",
+            global_toml,
+            false,
+        ));
+        res.extend(ignored_words_in(
+            &self.local.identifiers,
+            "project identifiers",
+            &self.local_toml,
+            false,
+        ));
+        res
+    }
+
+    /// Ignore words matching `pattern` everywhere, or for the given
+    /// extension only
+    pub fn ignore_regex(&mut self, pattern: &str, extension: Option<&str>) -> Result<()> {
+        ignore_regex::compile(pattern)?;
+        let _lock = self.lock_global()?;
+        let regex = &mut self.global.regex;
+        let patterns = match extension {
+            Some(e) => regex.extensions.entry(e.to_string()).or_default(),
+            None => &mut regex.global,
+        };
+        patterns.insert(pattern.to_string());
+        self.save_global()?;
+        self.compile_regexes()
+    }
+
+    pub fn remove_regex(&mut self, pattern: &str, extension: Option<&str>) -> Result<()> {
+        let _lock = self.lock_global()?;
+        let regex = &mut self.global.regex;
+        let present = match extension {
+            Some(e) => {
+                let present = regex
+                    .extensions
+                    .get_mut(e)
+                    .is_some_and(|p| p.remove(pattern));
+                if regex.extensions.get(e).is_some_and(|p| p.is_empty()) {
+                    regex.extensions.remove(e);
+                }
+                present
+            }
+            None => regex.global.remove(pattern),
+        };
+        if !present {
+            bail!(
+                "'{pattern}' was not in the {} regexes",
+                regex_scope(extension)
+            );
+        }
+        self.save_global()?;
+        self.compile_regexes()
+    }
+
+    /// Ignore words matching `pattern` in the current project
+    pub fn ignore_regex_for_project(&mut self, pattern: &str) -> Result<()> {
+        ignore_regex::compile(pattern)?;
+        let _lock = self.lock_local()?;
+        self.local.regex.project.insert(pattern.to_string());
+        self.save_local()?;
+        self.compile_regexes()
+    }
+
+    pub fn remove_regex_for_project(&mut self, pattern: &str) -> Result<()> {
+        let _lock = self.lock_local()?;
+        if !self.local.regex.project.remove(pattern) {
+            bail!("'{pattern}' was not in the project regexes");
+        }
+        self.save_local()?;
+        self.compile_regexes()
+    }
+
+    // Both files were just read again, so other processes may have
+    // changed the patterns too
+    fn compile_regexes(&mut self) -> Result<()> {
+        self.regexes = CompiledRegexes::new(&self.global.regex, &self.local.regex)?;
+        Ok(())
+    }
+
+    /// Is the file skipped by one of the patterns of the local file?
+    /// Unlike `Project::skip_file()`, this takes into account the
+    /// patterns added since the project was opened
+    pub fn is_skipped(&self, relative_path: &RelativePath) -> bool {
+        self.skip_file.is_skipped(relative_path)
+    }
+
+    /// Like is_skipped(), but return the rule that causes the file to be skipped
+    pub fn skip_rule(&self, relative_path: &RelativePath) -> Option<SkipRule> {
+        self.skip_file.skip_rule(relative_path)
+    }
+
+    /// Skip every file with this name, wherever it is in the project
+    pub fn skip_file_name(&mut self, file_name: &str) -> Result<()> {
+        self.add_skip_pattern(file_name)
+    }
+
+    /// Skip this file only
+    pub fn skip_path(&mut self, relative_path: &RelativePath) -> Result<()> {
+        self.add_skip_pattern(&format!("/{}", relative_path.normalize()))
+    }
+
+    /// Skip every file in this directory, and in its subdirectories
+    pub fn skip_directory(&mut self, relative_dir: &RelativePath) -> Result<()> {
+        self.add_skip_pattern(&directory_pattern(relative_dir))
+    }
+
+    /// Skip the files matching the pattern, using the .gitignore syntax
+    pub fn add_skip_pattern(&mut self, pattern: &str) -> Result<()> {
+        let _lock = self.lock_local()?;
+        self.local.patterns.insert(pattern.to_string());
+        self.save_local()?;
+        self.skip_file = SkipFile::new(project_root(&self.local_toml), &self.local)?;
+        Ok(())
+    }
+
+    pub fn remove_skip_pattern(&mut self, pattern: &str) -> Result<()> {
+        let _lock = self.lock_local()?;
+        if !self.local.patterns.remove(pattern) {
+            return Err(SkyspellError::not_ignored(pattern, "skipped files").into());
+        }
+        self.save_local()?;
+        self.skip_file = SkipFile::new(project_root(&self.local_toml), &self.local)?;
+        Ok(())
+    }
+
+    pub fn has_skip_pattern(&self, pattern: &str) -> bool {
+        self.local.patterns.contains(pattern)
+    }
+
+    /// Extensions with at least one ignored word
+    pub fn extensions(&self) -> impl Iterator<Item = &str> {
+        self.global
+            .extensions
+            .iter()
+            .filter(|(_, words)| !words.is_empty())
+            .map(|(extension, _)| extension.as_str())
     }
 
     pub fn ignore(&mut self, word: &str) -> Result<()> {
+        let _lock = self.lock_global()?;
+        let limit = self.local.limits.global;
+        check_limit(limit, "global", Some(&self.global.global), word)?;
         self.global.global.insert(word.to_owned());
         self.save_global()
     }
@@ -153,14 +1659,22 @@ impl IgnoreStore {
     }
 
     pub fn remove_ignored(&mut self, word: &str) -> Result<()> {
+        let _lock = self.lock_global()?;
         let present = self.global.global.remove(word);
         if !present {
-            bail!("word {word} was not ignored");
+            return Err(SkyspellError::not_ignored(word, "global").into());
         }
         self.save_global()
     }
 
     pub fn ignore_for_extension(&mut self, word: &str, extension: &str) -> Result<()> {
+        let _lock = self.lock_global()?;
+        check_limit(
+            self.local.limits.extension,
+            format!("extension {extension}"),
+            self.global.extensions.get(extension),
+            word,
+        )?;
         let for_extension = self.global.extensions.get_mut(extension);
         match for_extension {
             Some(s) => {
@@ -184,17 +1698,28 @@ impl IgnoreStore {
     }
 
     pub fn remove_ignored_for_extension(&mut self, word: &str, extension: &str) -> Result<()> {
+        let _lock = self.lock_global()?;
         match self.global.extensions.get_mut(extension) {
             Some(set) => {
                 set.remove(word);
             }
-            None => bail!("{word} is not ignored for {extension}"),
+            None => {
+                let scope = format!("extension {extension}");
+                return Err(SkyspellError::not_ignored(word, scope).into());
+            }
         }
         self.save_global()
     }
 
     pub fn ignore_for_lang(&mut self, word: &str, lang: &str) -> Result<()> {
+        let _lock = self.lock_global()?;
         let lang = short_lang(lang);
+        check_limit(
+            self.local.limits.lang,
+            format!("lang {lang}"),
+            self.global.lang.get(lang),
+            word,
+        )?;
         let for_lang = self.global.lang.get_mut(lang);
         match for_lang {
             Some(s) => {
@@ -219,79 +1744,158 @@ impl IgnoreStore {
     }
 
     pub fn remove_ignored_for_lang(&mut self, word: &str, lang: &str) -> Result<()> {
+        let _lock = self.lock_global()?;
         let lang = short_lang(lang);
         match self.global.lang.get_mut(lang) {
             Some(set) => {
                 set.remove(word);
             }
-            None => bail!("{word} is not ignored for {lang}"),
+            None => return Err(SkyspellError::not_ignored(word, format!("lang {lang}")).into()),
         }
         self.save_global()
     }
 
     pub fn ignore_for_project(&mut self, word: &str) -> Result<()> {
-        self.local.project.insert(word.to_owned());
+        let _lock = self.lock_local()?;
+        let limit = self.local.limits.project;
+        check_limit(
+            limit,
+            "project",
+            Some(&self.local.all_project_words()),
+            word,
+        )?;
+        if !self.local.has_project_word(word) {
+            self.local.project.insert(word.to_owned());
+        }
+        self.save_local()
+    }
+
+    /// Like ignore_for_project(), but list the word under the given
+    /// category, moving it there if it already was in another one
+    pub fn ignore_for_project_in_category(&mut self, word: &str, category: &str) -> Result<()> {
+        let _lock = self.lock_local()?;
+        let limit = self.local.limits.project;
+        check_limit(
+            limit,
+            "project",
+            Some(&self.local.all_project_words()),
+            word,
+        )?;
+        self.local.remove_project_word(word);
+        self.local
+            .project_categories
+            .entry(category.to_string())
+            .or_default()
+            .insert(word.to_owned());
         self.save_local()
     }
 
     pub fn is_ignored_for_project(&self, word: &str) -> bool {
-        self.local.project.contains(word)
+        self.local.has_project_word(word)
     }
 
     pub fn remove_ignored_for_project(&mut self, word: &str) -> Result<()> {
-        let present = self.local.project.remove(word);
+        let _lock = self.lock_local()?;
+        let present = self.local.remove_project_word(word);
         if !present {
-            bail!("word {word} was not ignored");
+            return Err(SkyspellError::not_ignored(word, "project").into());
         }
         self.save_local()
     }
 
-    pub fn ignore_for_path(&mut self, word: &str, relative_path: &RelativePath) -> Result<()> {
-        let path: &str = &relative_path.normalize();
-        let for_path = self.local.paths.get_mut(path);
-        match for_path {
+    pub fn ignore_for_project_and_lang(&mut self, word: &str, lang: &str) -> Result<()> {
+        let _lock = self.lock_local()?;
+        let lang = short_lang(lang);
+        check_limit(
+            self.local.limits.lang,
+            format!("project and lang {lang}"),
+            self.local.lang.get(lang),
+            word,
+        )?;
+        let for_lang = self.local.lang.get_mut(lang);
+        match for_lang {
             Some(s) => {
                 s.insert(word.to_owned());
             }
             None => {
                 let mut set = BTreeSet::new();
                 set.insert(word.to_owned());
-                self.local.paths.insert(path.to_owned(), set);
+                self.local.lang.insert(lang.to_owned(), set);
             }
         };
         self.save_local()
     }
 
+    pub fn is_ignored_for_project_and_lang(&self, word: &str, lang: &str) -> bool {
+        let lang = short_lang(lang);
+        let for_lang = self.local.lang.get(lang);
+        match for_lang {
+            Some(s) => s.contains(word),
+            None => false,
+        }
+    }
+
+    pub fn remove_ignored_for_project_and_lang(&mut self, word: &str, lang: &str) -> Result<()> {
+        let _lock = self.lock_local()?;
+        let lang = short_lang(lang);
+        let present = match self.local.lang.get_mut(lang) {
+            Some(set) => set.remove(word),
+            None => false,
+        };
+        if !present {
+            let scope = format!("project and lang {lang}");
+            return Err(SkyspellError::not_ignored(word, scope).into());
+        }
+        if self.local.lang.get(lang).is_some_and(|s| s.is_empty()) {
+            self.local.lang.remove(lang);
+        }
+        self.save_local()
+    }
+
+    pub fn ignore_for_path(&mut self, word: &str, relative_path: &RelativePath) -> Result<()> {
+        let _lock = self.lock_local()?;
+        if relative_path.is_outside_project() {
+            bail!(
+                "Cannot add '{word}' to the ignore list for {relative_path}: path is outside the project - ignore it for the project instead"
+            );
+        }
+        let key = path_key(&self.local.paths, &relative_path.normalize());
+        check_limit(
+            self.local.limits.path,
+            format!("path {key}"),
+            self.local.paths.get(&key),
+            word,
+        )?;
+        self.local
+            .paths
+            .entry(key)
+            .or_default()
+            .insert(word.to_owned());
+        self.save_local()
+    }
+
     pub fn is_ignored_for_path(&self, word: &str, relative_path: &RelativePath) -> bool {
-        let path: &str = &relative_path.normalize();
-        let for_path = self.local.paths.get(path);
-        match for_path {
+        let key = path_key(&self.local.paths, &relative_path.normalize());
+        match self.local.paths.get(&key) {
             Some(s) => s.contains(word),
             None => false,
         }
     }
 
     pub fn skip_token(&mut self, token: &str, relative_path: &RelativePath) -> Result<()> {
-        let path: &str = &relative_path.normalize();
-        let for_path = self.local.skipped.get_mut(path);
-        match for_path {
-            Some(s) => {
-                s.insert(token.to_owned());
-            }
-            None => {
-                let mut set = BTreeSet::new();
-                set.insert(token.to_owned());
-                self.local.skipped.insert(path.to_owned(), set);
-            }
-        };
-        dbg!(&self.local.skipped);
+        let _lock = self.lock_local()?;
+        let key = path_key(&self.local.skipped, &relative_path.normalize());
+        self.local
+            .skipped
+            .entry(key)
+            .or_default()
+            .insert(token.to_owned());
         self.save_local()
     }
 
     pub fn skipped_tokens(&self, relative_path: &RelativePath) -> Vec<String> {
-        let path: &str = &relative_path.normalize();
-        let for_path = self.local.skipped.get(path);
-        match for_path {
+        let key = path_key(&self.local.skipped, &relative_path.normalize());
+        match self.local.skipped.get(&key) {
             Some(s) => s.iter().map(|s| s.to_owned()).collect(),
             None => vec![],
         }
@@ -302,21 +1906,124 @@ impl IgnoreStore {
         word: &str,
         relative_path: &crate::RelativePath,
     ) -> Result<()> {
-        let path: &str = &relative_path.normalize();
-        match self.local.paths.get_mut(path) {
+        let _lock = self.lock_local()?;
+        let path = relative_path.normalize();
+        let key = path_key(&self.local.paths, &path);
+        match self.local.paths.get_mut(&key) {
             Some(set) => {
                 set.remove(word);
+                if set.is_empty() {
+                    self.local.paths.remove(&key);
+                }
             }
-            None => bail!("{word} is not ignored path {path}"),
+            None => return Err(SkyspellError::not_ignored(word, format!("path {path}")).into()),
+        }
+        self.save_local()
+    }
+
+    /// Never check the words of `identifier` - the whole token they are
+    /// extracted from, like `XgboostClassifier` - in any project
+    pub fn ignore_identifier(&mut self, identifier: &str) -> Result<()> {
+        let _lock = self.lock_global()?;
+        self.global.identifiers.insert(identifier.to_owned());
+        self.save_global()
+    }
+
+    pub fn is_ignored_identifier(&self, identifier: &str) -> bool {
+        self.global.identifiers.contains(identifier)
+    }
+
+    pub fn remove_ignored_identifier(&mut self, identifier: &str) -> Result<()> {
+        let _lock = self.lock_global()?;
+        if !self.global.identifiers.remove(identifier) {
+            return Err(SkyspellError::not_ignored(identifier, "identifiers").into());
+        }
+        self.save_global()
+    }
+
+    /// Like ignore_identifier(), for the current project only
+    pub fn ignore_identifier_for_project(&mut self, identifier: &str) -> Result<()> {
+        let _lock = self.lock_local()?;
+        self.local.identifiers.insert(identifier.to_owned());
+        self.save_local()
+    }
+
+    pub fn is_ignored_identifier_for_project(&self, identifier: &str) -> bool {
+        self.local.identifiers.contains(identifier)
+    }
+
+    pub fn remove_ignored_identifier_for_project(&mut self, identifier: &str) -> Result<()> {
+        let _lock = self.lock_local()?;
+        if !self.local.identifiers.remove(identifier) {
+            return Err(SkyspellError::not_ignored(identifier, "project identifiers").into());
         }
         self.save_local()
     }
 
+    /// True if the words of `identifier` should not be checked, because
+    /// it is ignored globally, for the project or by one of the files
+    /// listed in `extends`
+    pub fn should_ignore_identifier(&self, identifier: &str) -> bool {
+        self.is_ignored_identifier(identifier)
+            || self.is_ignored_identifier_for_project(identifier)
+            || self
+                .extended
+                .iter()
+                .any(|e| e.ignore.identifiers.contains(identifier))
+    }
+
+    /// Every identifier for which should_ignore_identifier() is true
+    pub fn ignored_identifiers(&self) -> Vec<String> {
+        let extended = self.extended.iter().map(|e| &e.ignore.identifiers);
+        let all: BTreeSet<_> = [&self.global.identifiers, &self.local.identifiers]
+            .into_iter()
+            .chain(extended)
+            .flatten()
+            .cloned()
+            .collect();
+        all.into_iter().collect()
+    }
+
+    // Lock the global file and read it again, so that changes made by
+    // other processes since it was loaded are not lost. Changes must be
+    // saved before the lock is dropped
+    fn lock_global(&mut self) -> Result<FileLock> {
+        let lock = FileLock::acquire(&self.global_toml)?;
+        self.global = load(&self.global_toml)?;
+        self.compile_regexes()?;
+        Ok(lock)
+    }
+
+    // Like lock_global(), for the local file. What is built from its
+    // contents is built again, except for the settings that may have
+    // been overridden from the command line
+    fn lock_local(&mut self) -> Result<FileLock> {
+        self.ensure_local_is_writable()?;
+        let lock = FileLock::acquire(&self.local_toml)?;
+        self.local = load_local(&self.local_toml)?;
+        self.extended = extended(&self.local_toml, &self.local)?;
+        self.skipped_regions = skipped_regions(&self.local_toml, &self.local)?;
+        self.skip_file = SkipFile::new(project_root(&self.local_toml), &self.local)?;
+        self.compile_regexes()?;
+        Ok(lock)
+    }
+
+    fn ensure_local_is_writable(&self) -> Result<()> {
+        if self.local_is_broken {
+            bail!(
+                "Cannot update {} until it is fixed",
+                self.local_toml.display()
+            );
+        }
+        Ok(())
+    }
+
     fn save_global(&self) -> Result<()> {
         save("global", &self.global, &self.global_toml)
     }
 
     fn save_local(&self) -> Result<()> {
+        self.ensure_local_is_writable()?;
         save("local", &self.local, &self.local_toml)
     }
 }
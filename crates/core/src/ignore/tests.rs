@@ -141,6 +141,18 @@ fn test_remove_ignored_for_path_happy() {
     assert!(!store.is_ignored_for_path("foo", &foo_py));
 }
 
+#[test]
+fn test_remove_last_word_ignored_for_path() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let foo_py = RelativePath::from_path_unchecked(PathBuf::from("foo.py"));
+    store.ignore_for_path("foo", &foo_py).unwrap();
+
+    store.remove_ignored_for_path("foo", &foo_py).unwrap();
+
+    assert!(store.path_words().is_empty());
+}
+
 #[test]
 fn test_remove_ignored_for_path_when_not_ignored() {
     let temp_dir = get_test_dir();
@@ -174,6 +186,24 @@ fn relative_path(path: &str) -> RelativePath {
     RelativePath::from_path_unchecked(path.into())
 }
 
+#[test]
+fn test_should_ignore_for_assumed_extension() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let build = relative_path("build");
+    let notes_txt = relative_path("notes.txt");
+    store.ignore_for_extension("foo", "py").unwrap();
+    assert!(!store.should_ignore("foo", &build, "en_US"));
+
+    store.set_assumed_extension(&build, ".py");
+    store.set_assumed_extension(&notes_txt, "tex");
+
+    assert!(store.should_ignore("foo", &build, "en_US"));
+    assert_eq!(store.extension_of(&notes_txt).as_deref(), Some("tex"));
+    let foo_py = relative_path("foo.py");
+    assert_eq!(store.extension_of(&foo_py).as_deref(), Some("py"));
+}
+
 #[test]
 fn test_should_ignore_global() {
     let temp_dir = get_test_dir();
@@ -239,3 +269,1358 @@ fn test_skip_tokens() {
 
     assert_eq!(store.skipped_tokens(&foo_py), &["SOMETHING"]);
 }
+
+#[test]
+fn test_cannot_ignore_for_path_outside_project() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let intro_md = relative_path("../handbook/intro.md");
+
+    let err = store.ignore_for_path("foo", &intro_md).unwrap_err();
+
+    assert!(err.to_string().contains("outside the project"));
+    assert!(!store.is_ignored_for_path("foo", &intro_md));
+}
+
+#[test]
+fn test_ignored_for_project_and_lang() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let foo_md = relative_path("foo.md");
+
+    store
+        .ignore_for_project_and_lang("colour", "en_GB")
+        .unwrap();
+
+    assert!(store.is_ignored_for_project_and_lang("colour", "en_GB"));
+    assert!(!store.is_ignored_for_project_and_lang("colour", "fr_FR"));
+    assert!(!store.is_ignored_for_lang("colour", "en_GB"));
+    assert!(store.should_ignore("colour", &foo_md, "en_GB"));
+    assert!(!store.should_ignore("colour", &foo_md, "fr_FR"));
+}
+
+#[test]
+fn test_ignore_for_project_and_lang_writes_in_local_toml() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+
+    store
+        .ignore_for_project_and_lang("colour", "en_GB")
+        .unwrap();
+
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    let actual: LocalIgnore = load(&local_toml).unwrap();
+    assert!(actual.lang["en"].contains("colour"));
+}
+
+#[test]
+fn test_remove_ignored_for_project_and_lang_when_not_ignored() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+
+    assert!(store
+        .remove_ignored_for_project_and_lang("colour", "en_GB")
+        .is_err());
+}
+
+#[test]
+fn test_inflections_are_not_matched_by_default() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let foo_py = relative_path("foo.py");
+
+    store.ignore("frobnicate").unwrap();
+
+    assert!(!store.should_ignore("frobnicates", &foo_py, "en_US"));
+}
+
+#[test]
+fn test_should_ignore_inflections_when_enabled() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(&temp_dir, "", "match_inflections = true");
+    let foo_py = relative_path("foo.py");
+
+    store.ignore("frobnicate").unwrap();
+    store.ignore_for_project("stopped").unwrap();
+
+    assert!(store.should_ignore("frobnicates", &foo_py, "en_US"));
+    assert!(store.should_ignore("frobnicated", &foo_py, "en_US"));
+    assert!(store.should_ignore("frobnicating", &foo_py, "en_US"));
+    assert!(store.should_ignore("stop", &foo_py, "en_US"));
+    assert!(store.should_ignore("stopping", &foo_py, "en_US"));
+    assert!(!store.should_ignore("frob", &foo_py, "en_US"));
+}
+
+#[test]
+fn test_match_inflections_is_kept_when_saving() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(&temp_dir, "", "match_inflections = true");
+
+    store.ignore_for_project("foo").unwrap();
+
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    let actual: LocalIgnore = load(&local_toml).unwrap();
+    assert!(actual.match_inflections);
+}
+
+#[test]
+fn test_load_skipped_regions() {
+    let temp_dir = get_test_dir();
+    let store = create_store(
+        &temp_dir,
+        "",
+        r#"
+        skip_front_matter = true
+
+        [[skip_regions]]
+        begin = "BEGIN GENERATED"
+        end = "END GENERATED"
+        "#,
+    );
+
+    assert!(store.skip_front_matter());
+    assert_eq!(store.skipped_regions().len(), 1);
+}
+
+#[test]
+fn test_skipped_regions_are_kept_when_saving() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(
+        &temp_dir,
+        "",
+        r#"
+        [[skip_regions]]
+        begin = "BEGIN GENERATED"
+        end = "END GENERATED"
+        "#,
+    );
+
+    store.ignore_for_project("foo").unwrap();
+
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    let actual: LocalIgnore = load(&local_toml).unwrap();
+    assert_eq!(actual.skip_regions.len(), 1);
+    assert_eq!(actual.skip_regions[0].begin, "BEGIN GENERATED");
+}
+
+#[test]
+fn test_invalid_skipped_region() {
+    let temp_dir = get_test_dir();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    std::fs::write(
+        &local_toml,
+        r#"
+        [[skip_regions]]
+        begin = "("
+        end = "END"
+        "#,
+    )
+    .unwrap();
+
+    let err = IgnoreStore::load(global_toml, local_toml).unwrap_err();
+
+    assert!(format!("{err:?}").contains("Invalid region begin marker"));
+}
+
+#[test]
+fn test_count_queries_per_category() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let foo_py = relative_path("foo.py");
+    let foo = relative_path("foo");
+    store.ignore("one").unwrap();
+
+    store.should_ignore("one", &foo_py, "en_US");
+    store.should_ignore("two", &foo_py, "en_US");
+    store.should_ignore("two", &foo, "en_US");
+
+    let stats = store.stats();
+    assert_eq!(stats.words, 3);
+    assert_eq!(stats.not_ignored, 2);
+    assert_eq!(stats.global, 3);
+    assert_eq!(stats.project_and_lang, 2);
+    assert_eq!(stats.extension, 1);
+    assert_eq!(stats.path, 2);
+}
+
+#[test]
+fn test_entry_used_only_via_the_extension_list() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(
+        &temp_dir,
+        r#"
+        [extensions]
+        rs = ["impl"]
+        "#,
+        r#"
+        project = ["impl"]
+        "#,
+    );
+    store.set_record_uses(true);
+
+    store.should_ignore("impl", &relative_path("foo.rs"), "en_US");
+
+    // The extension list is looked up first, so the project entry
+    // was never needed
+    assert_eq!(
+        store.unused_entries(),
+        vec![Operation::new_ignore_for_project("impl")]
+    );
+}
+
+#[test]
+fn test_unused_entries() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(
+        &temp_dir,
+        r#"
+        global = ["one", "two"]
+        "#,
+        r#"
+        [paths]
+        "foo.md" = ["three"]
+        "#,
+    );
+    store.set_record_uses(true);
+
+    store.should_ignore("one", &relative_path("bar.md"), "en_US");
+    store.should_ignore("three", &relative_path("foo.md"), "en_US");
+
+    assert_eq!(store.unused_entries(), vec![Operation::new_ignore("two")]);
+}
+
+#[test]
+fn test_merge_used_entries() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(
+        &temp_dir,
+        r#"
+        global = ["one"]
+        "#,
+        "",
+    );
+    store.set_record_uses(true);
+    let copy = store.clone();
+
+    copy.should_ignore("one", &relative_path("foo.md"), "en_US");
+    assert_eq!(store.unused_entries(), vec![Operation::new_ignore("one")]);
+    store.add_used_entries(copy.used_entries());
+
+    assert!(store.unused_entries().is_empty());
+}
+
+#[test]
+fn test_uses_are_not_recorded_by_default() {
+    let temp_dir = get_test_dir();
+    let store = create_store(
+        &temp_dir,
+        r#"
+        global = ["one"]
+        "#,
+        "",
+    );
+
+    store.should_ignore("one", &relative_path("foo.md"), "en_US");
+
+    assert_eq!(store.used_entries(), UsedEntries::default());
+}
+
+#[test]
+fn test_count_hits() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(
+        &temp_dir,
+        r#"
+        global = ["one"]
+        "#,
+        r#"
+        project = ["two"]
+        "#,
+    );
+    store.set_record_uses(true);
+    let copy = store.clone();
+
+    store.should_ignore("one", &relative_path("foo.md"), "en_US");
+    store.should_ignore("one", &relative_path("bar.md"), "en_US");
+    copy.should_ignore("one", &relative_path("foo.md"), "en_US");
+    copy.should_ignore("two", &relative_path("foo.md"), "en_US");
+    store.add_used_entries(copy.used_entries());
+
+    let used = store.used_entries();
+    let hits: Vec<_> = used.hits().collect();
+    assert_eq!(hits, vec![("global", "one", 3), ("project", "two", 1)]);
+}
+
+#[test]
+fn test_merge_conflict_in_local_toml() {
+    let temp_dir = get_test_dir();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    std::fs::write(
+        &local_toml,
+        r#"project = [
+<<<<<<< HEAD
+    "foo",
+=======
+    "bar",
+>>>>>>> feature
+]
+"#,
+    )
+    .unwrap();
+
+    let err = IgnoreStore::load(global_toml, local_toml).unwrap_err();
+
+    let message = err.to_string();
+    assert!(message.contains("skyspell.toml:2"), "{message}");
+    assert!(message.contains("merge conflict"), "{message}");
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(SkyspellError::IgnoreStoreParse {
+            line: 2,
+            column: 1,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_removing_a_word_that_is_not_ignored() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+
+    let err = store.remove_ignored_for_project("foo").unwrap_err();
+
+    assert_eq!(err.to_string(), "'foo' is not in the project ignore list");
+    match err.downcast_ref() {
+        Some(SkyspellError::NotIgnored { word, scope }) => {
+            assert_eq!(word, "foo");
+            assert_eq!(scope, "project");
+        }
+        _ => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn test_parse_error_contains_location() {
+    let temp_dir = get_test_dir();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    std::fs::write(&local_toml, "project = [\"foo\"]\npaths = [\n").unwrap();
+
+    let err = IgnoreStore::load(global_toml, local_toml.clone()).unwrap_err();
+
+    let message = format!("{err:#}");
+    assert!(message.contains("skyspell.toml:2:10:"), "{message}");
+    match err.downcast_ref() {
+        Some(SkyspellError::IgnoreStoreParse { path, line, .. }) => {
+            assert_eq!(path, &local_toml);
+            assert_eq!(*line, 2);
+        }
+        _ => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn test_saving_keeps_the_previous_version() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let local_toml = temp_dir.path().join("skyspell.toml");
+
+    store.ignore_for_project("foo").unwrap();
+    store.ignore_for_project("bar").unwrap();
+
+    let backup = std::fs::read_to_string(temp_dir.path().join("skyspell.toml.bak")).unwrap();
+    assert!(backup.contains("foo"), "{backup}");
+    assert!(!backup.contains("bar"), "{backup}");
+    let contents = std::fs::read_to_string(local_toml).unwrap();
+    assert!(contents.contains("bar"), "{contents}");
+}
+
+#[test]
+fn test_recover_truncated_file_from_backup() {
+    let temp_dir = get_test_dir();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    std::fs::write(&local_toml, "project = [\"foo\", \"ba").unwrap();
+    std::fs::write(
+        temp_dir.path().join("skyspell.toml.bak"),
+        "project = [\"foo\"]\n",
+    )
+    .unwrap();
+
+    let err = IgnoreStore::load(global_toml.clone(), local_toml.clone()).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("skyspell.toml.bak"), "{message}");
+    assert!(message.contains("--recover-ignore-file"), "{message}");
+
+    let recovered = IgnoreStore::recover(&global_toml, &local_toml).unwrap();
+
+    assert_eq!(recovered, vec![local_toml.clone()]);
+    let store = IgnoreStore::load(global_toml, local_toml).unwrap();
+    assert!(store.is_ignored_for_project("foo"));
+}
+
+#[test]
+fn test_broken_backups_are_not_restored() {
+    let temp_dir = get_test_dir();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    std::fs::write(&local_toml, "project = [").unwrap();
+    std::fs::write(temp_dir.path().join("skyspell.toml.bak"), "project = ").unwrap();
+
+    let err = IgnoreStore::load(global_toml.clone(), local_toml.clone()).unwrap_err();
+    assert!(!err.to_string().contains("--recover-ignore-file"), "{err}");
+
+    let recovered = IgnoreStore::recover(&global_toml, &local_toml).unwrap();
+
+    assert!(recovered.is_empty());
+    assert_eq!(std::fs::read_to_string(&local_toml).unwrap(), "project = [");
+}
+
+#[test]
+fn test_load_without_local() {
+    let temp_dir = get_test_dir();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    std::fs::write(&global_toml, "global = [\"foo\"]").unwrap();
+    std::fs::write(&local_toml, "<<<<<<< HEAD").unwrap();
+
+    let mut store = IgnoreStore::load_without_local(global_toml, local_toml.clone()).unwrap();
+
+    assert!(store.is_ignored("foo"));
+    store.ignore("bar").unwrap();
+    store.ignore_for_project("bar").unwrap_err();
+    let contents = std::fs::read_to_string(&local_toml).unwrap();
+    assert_eq!(contents, "<<<<<<< HEAD");
+}
+
+#[test]
+fn test_paths_with_backslashes_are_normalized_when_loading() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(
+        &temp_dir,
+        "",
+        r#"
+[paths]
+"src\\foo.py" = ["foo"]
+"src/foo.py" = ["bar"]
+
+[skipped]
+"src\\foo.py" = ["SKIP"]
+"#,
+    );
+    let foo_py = RelativePath::from_path_unchecked(PathBuf::from("src/foo.py"));
+
+    assert!(store.is_ignored_for_path("foo", &foo_py));
+    assert!(store.is_ignored_for_path("bar", &foo_py));
+    assert_eq!(store.skipped_tokens(&foo_py), &["SKIP"]);
+
+    store.ignore_for_path("baz", &foo_py).unwrap();
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    let contents = std::fs::read_to_string(local_toml).unwrap();
+    assert!(!contents.contains('\\'));
+}
+
+#[cfg(windows)]
+#[test]
+fn test_paths_are_case_insensitive_on_windows() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let lower = RelativePath::from_path_unchecked(PathBuf::from("src\\foo.py"));
+    let upper = RelativePath::from_path_unchecked(PathBuf::from("SRC\\Foo.py"));
+
+    store.ignore_for_path("foo", &lower).unwrap();
+    store.ignore_for_path("bar", &upper).unwrap();
+
+    assert!(store.is_ignored_for_path("foo", &upper));
+    assert!(store.is_ignored_for_path("bar", &lower));
+    assert_eq!(store.local.paths.len(), 1);
+}
+
+#[test]
+fn test_changes_from_other_stores_are_kept() {
+    let temp_dir = get_test_dir();
+    let mut first = get_empty_store(&temp_dir);
+    let mut second = create_store(&temp_dir, "", "");
+
+    first.ignore("one").unwrap();
+    second.ignore("two").unwrap();
+    first.ignore_for_project("three").unwrap();
+    second.ignore_for_project("four").unwrap();
+
+    let store = IgnoreStore::load(
+        temp_dir.path().join("global.toml"),
+        temp_dir.path().join("skyspell.toml"),
+    )
+    .unwrap();
+    assert!(store.is_ignored("one"));
+    assert!(store.is_ignored("two"));
+    assert!(store.is_ignored_for_project("three"));
+    assert!(store.is_ignored_for_project("four"));
+}
+
+#[test]
+fn test_concurrent_updates() {
+    let temp_dir = get_test_dir();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            let global_toml = global_toml.clone();
+            let local_toml = local_toml.clone();
+            std::thread::spawn(move || {
+                let mut store = IgnoreStore::load(global_toml, local_toml).unwrap();
+                for j in 0..10 {
+                    store.ignore(&format!("global-{i}-{j}")).unwrap();
+                    store.ignore_for_project(&format!("local-{i}-{j}")).unwrap();
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let store = IgnoreStore::load(global_toml, local_toml).unwrap();
+    assert_eq!(store.global_words().count(), 40);
+    assert_eq!(store.project_words().count(), 40);
+}
+
+#[test]
+fn test_changes_made_by_other_processes_are_used_after_an_update() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    let mut other = IgnoreStore::load(global_toml, local_toml).unwrap();
+    other.ignore_regex(r"^tmpVar\w+$", None).unwrap();
+    other.ignore_regex_for_project("^fixture_").unwrap();
+    other.add_skip_pattern("*.lock").unwrap();
+    let foo_md = relative_path("foo.md");
+
+    store.ignore("foo").unwrap();
+    assert!(store.should_ignore("tmpVarOne", &foo_md, "en"));
+    assert!(!store.should_ignore("fixture_one", &foo_md, "en"));
+
+    store.ignore_for_project("bar").unwrap();
+    assert!(store.should_ignore("fixture_one", &foo_md, "en"));
+    assert!(store.is_skipped(&relative_path("Cargo.lock")));
+}
+
+#[test]
+fn test_operations_for_word() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    store.ignore("foo").unwrap();
+    store.ignore_for_extension("foo", "py").unwrap();
+    store.ignore_for_lang("foo", "fr_FR").unwrap();
+    store.ignore_for_project_and_lang("foo", "fr").unwrap();
+    store
+        .ignore_for_path("foo", &RelativePath::from_path_unchecked("src/a.py".into()))
+        .unwrap();
+    store.ignore_for_project("bar").unwrap();
+
+    let scopes: Vec<_> = store
+        .operations_for("foo")
+        .iter()
+        .map(|o| o.scope())
+        .collect();
+
+    assert_eq!(
+        scopes,
+        &[
+            "global",
+            "extension py",
+            "lang fr",
+            "project and lang fr",
+            "path src/a.py"
+        ]
+    );
+}
+
+#[test]
+fn test_accept_acronyms_setting() {
+    let temp_dir = get_test_dir();
+    for (value, expected) in [
+        ("true", Some(DEFAULT_ACRONYM_MIN_LENGTH)),
+        ("false", None),
+        ("4", Some(4)),
+    ] {
+        let store = create_store(&temp_dir, "", &format!("accept_acronyms = {value}"));
+        assert_eq!(store.acronyms_min_length(), expected, "for {value}");
+    }
+
+    let store = get_empty_store(&temp_dir);
+    assert_eq!(store.acronyms_min_length(), None);
+}
+
+#[test]
+fn test_builtin_lists() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(&temp_dir, "", r#"builtin_lists = ["unix"]"#);
+    let foo_rs = RelativePath::from_path_unchecked("foo.rs".into());
+    assert!(store.should_ignore("systemd", &foo_rs, "en_US"));
+    assert!(!store.should_ignore("kubectl_nope", &foo_rs, "en_US"));
+    assert!(!store.should_ignore("enum", &foo_rs, "en_US"));
+
+    store.enable_builtin_list("programming").unwrap();
+    assert!(store.should_ignore("enum", &foo_rs, "en_US"));
+
+    let store = get_empty_store(&temp_dir);
+    assert!(!store.should_ignore("systemd", &foo_rs, "en_US"));
+}
+
+#[test]
+fn test_personal_dictionary() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(&temp_dir, "", "use_personal_dictionary = true");
+    assert!(store.use_personal_dictionary());
+    let pws = temp_dir.path().join(".aspell.en.pws");
+    std::fs::write(&pws, "personal_ws-1.1 en 1 utf-8\nfrobnicate\n").unwrap();
+    let foo_rs = RelativePath::from_path_unchecked("foo.rs".into());
+    let foreign = RelativePath::from_path_unchecked(temp_dir.path().join("notes.md"));
+    assert!(!store.should_ignore("frobnicate", &foo_rs, "en_US"));
+
+    store.set_personal_dictionary(PersonalDictionary::load(&pws).unwrap());
+
+    assert_eq!(
+        store.ignore_reason("frobnicate", &foo_rs, "en_US"),
+        Some(IgnoreReason::PersonalDictionary(pws.clone()))
+    );
+    assert!(store.should_ignore("Frobnicate", &foreign, "en_US"));
+
+    // Ignoring the word for the project takes precedence, and does not touch the file
+    store.ignore_for_project("frobnicate").unwrap();
+    assert_eq!(
+        store.ignore_reason("frobnicate", &foo_rs, "en_US"),
+        Some(IgnoreReason::Project)
+    );
+    let contents = std::fs::read_to_string(&pws).unwrap();
+    assert_eq!(contents, "personal_ws-1.1 en 1 utf-8\nfrobnicate\n");
+}
+
+const LIMITS: &str = "[limits]
+global = 2
+extension = 1
+project = 1
+";
+
+#[test]
+fn test_limit_for_global_list() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(&temp_dir, "", LIMITS);
+    store.ignore("foo").unwrap();
+
+    // At the limit
+    store.ignore("bar").unwrap();
+    store.ignore("bar").unwrap();
+
+    // Over the limit
+    let err = store.ignore("baz").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<SkyspellError>(),
+        Some(SkyspellError::LimitReached { limit: 2, .. })
+    ));
+    assert!(err.to_string().contains("builtin lists"), "{err}");
+    assert!(!store.is_ignored("baz"));
+    let global = std::fs::read_to_string(temp_dir.path().join("global.toml")).unwrap();
+    assert!(!global.contains("baz"));
+}
+
+#[test]
+fn test_limits_apply_to_each_extension() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(&temp_dir, "", LIMITS);
+    store.ignore_for_extension("foo", "py").unwrap();
+    store.ignore_for_extension("foo", "rs").unwrap();
+
+    let err = store.ignore_for_extension("bar", "py").unwrap_err();
+
+    assert!(err.to_string().contains("extension py"), "{err}");
+}
+
+#[test]
+fn test_limit_for_project_list() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(&temp_dir, "", LIMITS);
+    store.ignore_for_project("foo").unwrap();
+
+    let err = store.ignore_for_project("bar").unwrap_err();
+
+    assert!(
+        err.to_string().contains("The project ignore list is full"),
+        "{err}"
+    );
+    let local = std::fs::read_to_string(temp_dir.path().join("skyspell.toml")).unwrap();
+    assert!(!local.contains("bar"));
+    // No limit for paths
+    let foo_py = RelativePath::from_path_unchecked("foo.py".into());
+    store.ignore_for_path("bar", &foo_py).unwrap();
+}
+
+#[test]
+fn test_limit_usage() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(&temp_dir, "", LIMITS);
+    store.ignore("foo").unwrap();
+    store.ignore_for_extension("foo", "py").unwrap();
+    store.ignore_for_lang("foo", "en").unwrap();
+
+    let usage = store.limit_usage();
+
+    let sizes: Vec<_> = usage
+        .iter()
+        .map(|u| (u.scope.as_str(), u.words, u.limit))
+        .collect();
+    assert_eq!(
+        sizes,
+        [("global", 1, 2), ("extension py", 1, 1), ("project", 0, 1)]
+    );
+}
+
+#[test]
+fn test_no_limits_by_default() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    for i in 0..100 {
+        store.ignore(&format!("word{i}")).unwrap();
+    }
+
+    assert!(store.limit_usage().is_empty());
+}
+
+#[test]
+fn test_unknown_builtin_list_in_config() {
+    let temp_dir = get_test_dir();
+    std::fs::write(temp_dir.path().join("global.toml"), "").unwrap();
+    std::fs::write(
+        temp_dir.path().join("skyspell.toml"),
+        r#"builtin_lists = ["nope"]"#,
+    )
+    .unwrap();
+
+    let res = IgnoreStore::load(
+        temp_dir.path().join("global.toml"),
+        temp_dir.path().join("skyspell.toml"),
+    );
+
+    assert!(res.is_err());
+}
+
+fn load_with_local(temp_dir: &TempDir, local: &str) -> Result<IgnoreStore> {
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("project").join("skyspell.toml");
+    std::fs::create_dir_all(temp_dir.path().join("project")).unwrap();
+    std::fs::write(&local_toml, local).unwrap();
+    IgnoreStore::load(global_toml, local_toml)
+}
+
+#[test]
+fn test_extends_chain() {
+    let temp_dir = get_test_dir();
+    std::fs::write(
+        temp_dir.path().join("team.toml"),
+        r#"
+        extends = ["org.toml"]
+        project = ["teamword"]
+        "#,
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join("org.toml"),
+        r#"
+        project = ["orgword"]
+
+        [lang]
+        fr = ["orgmot"]
+        "#,
+    )
+    .unwrap();
+
+    let mut store = load_with_local(&temp_dir, r#"extends = ["../team.toml"]"#).unwrap();
+
+    let foo_txt = RelativePath::from_path_unchecked("foo.txt".into());
+    assert!(store.should_ignore("teamword", &foo_txt, "en_US"));
+    assert!(store.should_ignore("orgword", &foo_txt, "en_US"));
+    assert!(store.should_ignore("orgmot", &foo_txt, "fr_FR"));
+    assert!(!store.should_ignore("orgmot", &foo_txt, "en_US"));
+    assert!(!store.is_ignored_for_project("orgword"));
+
+    // Extended files are never written to
+    store.ignore_for_project("projectword").unwrap();
+    let team_toml = std::fs::read_to_string(temp_dir.path().join("team.toml")).unwrap();
+    assert!(!team_toml.contains("projectword"));
+    let store = IgnoreStore::load(
+        temp_dir.path().join("global.toml"),
+        temp_dir.path().join("project").join("skyspell.toml"),
+    )
+    .unwrap();
+    assert!(store.should_ignore("orgword", &foo_txt, "en_US"));
+}
+
+#[test]
+fn test_extends_cycle() {
+    let temp_dir = get_test_dir();
+    std::fs::write(
+        temp_dir.path().join("team.toml"),
+        r#"extends = ["org.toml"]"#,
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join("org.toml"),
+        r#"extends = ["team.toml"]"#,
+    )
+    .unwrap();
+
+    let err = load_with_local(&temp_dir, r#"extends = ["../team.toml"]"#).unwrap_err();
+
+    let message = err.to_string();
+    assert!(message.starts_with("Cycle in extends: "), "{message}");
+    assert!(message.ends_with("team.toml"), "{message}");
+}
+
+#[test]
+fn test_extends_missing_file() {
+    let temp_dir = get_test_dir();
+
+    let err = load_with_local(&temp_dir, r#"extends = ["../org.toml"]"#).unwrap_err();
+
+    assert!(err.to_string().contains("file not found"));
+}
+
+#[test]
+fn test_extends_optional_missing_file() {
+    let temp_dir = get_test_dir();
+
+    let store = load_with_local(
+        &temp_dir,
+        r#"extends = [{ path = "../org.toml", optional = true }]"#,
+    )
+    .unwrap();
+
+    assert!(store.ignored_words().is_empty());
+}
+
+#[test]
+fn test_ignored_words_are_attributed_to_their_source() {
+    let temp_dir = get_test_dir();
+    std::fs::write(temp_dir.path().join("global.toml"), r#"global = ["one"]"#).unwrap();
+    std::fs::write(temp_dir.path().join("org.toml"), r#"project = ["orgword"]"#).unwrap();
+    let store = load_with_local(
+        &temp_dir,
+        r#"
+        extends = ["../org.toml"]
+        project = ["two"]
+        "#,
+    )
+    .unwrap();
+
+    let actual: Vec<_> = store
+        .ignored_words()
+        .into_iter()
+        .map(|w| {
+            let source = PathBuf::from(w.source);
+            let file_name = source.file_name().unwrap().to_string_lossy().to_string();
+            (w.word, w.scope, file_name)
+        })
+        .collect();
+
+    assert_eq!(
+        actual,
+        [
+            ("one".into(), "global".into(), "global.toml".into()),
+            ("two".into(), "project".into(), "skyspell.toml".into()),
+            ("orgword".into(), "project".into(), "org.toml".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_extends_is_kept_when_saving() {
+    let temp_dir = get_test_dir();
+    std::fs::write(temp_dir.path().join("org.toml"), "").unwrap();
+    let mut store = load_with_local(
+        &temp_dir,
+        r#"extends = ["../org.toml", { path = "../team.toml", optional = true }]"#,
+    )
+    .unwrap();
+
+    store.ignore_for_project("foo").unwrap();
+
+    let local_toml = temp_dir.path().join("project").join("skyspell.toml");
+    let local: LocalIgnore = load(&local_toml).unwrap();
+    assert_eq!(
+        local.extends,
+        [
+            Extends::Path("../org.toml".to_string()),
+            Extends::Table {
+                path: "../team.toml".to_string(),
+                optional: true
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_project_words_with_and_without_category() {
+    let temp_dir = get_test_dir();
+    let store = load_with_local(
+        &temp_dir,
+        r#"
+        project = ["foo"]
+
+        [project_categories]
+        medical = ["dexamethasone"]
+        names = ["alice", "bob"]
+        "#,
+    )
+    .unwrap();
+    let readme = RelativePath::from_path_unchecked("README.md".into());
+
+    for word in ["foo", "dexamethasone", "alice"] {
+        assert!(store.is_ignored_for_project(word));
+        assert!(store.should_ignore(word, &readme, "en_US"));
+    }
+    assert_eq!(
+        store.project_words().collect::<Vec<_>>(),
+        ["foo", "dexamethasone", "alice", "bob"]
+    );
+    let categories: Vec<_> = store
+        .ignored_words()
+        .into_iter()
+        .map(|w| (w.word, w.category))
+        .collect();
+    assert_eq!(
+        categories,
+        [
+            ("foo".to_string(), None),
+            ("dexamethasone".to_string(), Some("medical".to_string())),
+            ("alice".to_string(), Some("names".to_string())),
+            ("bob".to_string(), Some("names".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn test_ignore_for_project_in_category() {
+    let temp_dir = get_test_dir();
+    let mut store = load_with_local(&temp_dir, r#"project = ["foo", "bar"]"#).unwrap();
+
+    store
+        .ignore_for_project_in_category("foo", "names")
+        .unwrap();
+    store
+        .ignore_for_project_in_category("dexamethasone", "medical")
+        .unwrap();
+    // Already in a category, so it stays there
+    store.ignore_for_project("dexamethasone").unwrap();
+
+    let local_toml = temp_dir.path().join("project").join("skyspell.toml");
+    let local: LocalIgnore = load(&local_toml).unwrap();
+    assert_eq!(local.project, BTreeSet::from(["bar".to_string()]));
+    assert_eq!(
+        local.project_categories,
+        BTreeMap::from([
+            (
+                "medical".to_string(),
+                BTreeSet::from(["dexamethasone".to_string()])
+            ),
+            ("names".to_string(), BTreeSet::from(["foo".to_string()])),
+        ])
+    );
+}
+
+#[test]
+fn test_remove_project_word_from_its_category() {
+    let temp_dir = get_test_dir();
+    let mut store = load_with_local(
+        &temp_dir,
+        r#"
+        project = ["foo"]
+
+        [project_categories]
+        medical = ["dexamethasone"]
+        "#,
+    )
+    .unwrap();
+
+    store.remove_ignored_for_project("dexamethasone").unwrap();
+    store.remove_ignored_for_project("foo").unwrap();
+
+    assert!(!store.is_ignored_for_project("dexamethasone"));
+    // Empty categories are removed
+    assert!(store.project_categories().is_empty());
+    let err = store
+        .remove_ignored_for_project("dexamethasone")
+        .unwrap_err();
+    assert!(err.to_string().contains("dexamethasone"));
+}
+
+#[test]
+fn test_ignore_regex() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let foo_rs = RelativePath::from_path_unchecked("foo.rs".into());
+    let foo_py = RelativePath::from_path_unchecked("foo.py".into());
+
+    store.ignore_regex(r"^tmpVar\w+$", None).unwrap();
+    store
+        .ignore_regex("^fixture_[a-f0-9]+$", Some("rs"))
+        .unwrap();
+    store.ignore_regex_for_project("^gen[0-9]+$").unwrap();
+
+    assert!(store.should_ignore("tmpVarXq3", &foo_py, "en_US"));
+    assert!(store.should_ignore("fixture_ab12cd", &foo_rs, "en_US"));
+    assert!(!store.should_ignore("fixture_ab12cd", &foo_py, "en_US"));
+    assert!(store.should_ignore("gen42", &foo_py, "en_US"));
+
+    // Patterns are saved and compiled again when loading
+    let store = IgnoreStore::load(
+        temp_dir.path().join("global.toml"),
+        temp_dir.path().join("skyspell.toml"),
+    )
+    .unwrap();
+    assert!(store.should_ignore("fixture_ab12cd", &foo_rs, "en_US"));
+    let regexes: Vec<_> = store
+        .ignored_words()
+        .into_iter()
+        .filter(|w| w.regex)
+        .map(|w| (w.word, w.scope))
+        .collect();
+    assert_eq!(
+        regexes,
+        [
+            (r"^tmpVar\w+$".to_string(), "global".to_string()),
+            (
+                "^fixture_[a-f0-9]+$".to_string(),
+                "extension rs".to_string()
+            ),
+            ("^gen[0-9]+$".to_string(), "project".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_regexes_are_not_saved() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+
+    let err = store.ignore_regex("tmp(", None).unwrap_err();
+
+    assert!(err.to_string().contains("Invalid regex"));
+    let global_toml = std::fs::read_to_string(temp_dir.path().join("global.toml")).unwrap();
+    assert!(!global_toml.contains("tmp("));
+}
+
+#[test]
+fn test_remove_regex() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let foo_rs = RelativePath::from_path_unchecked("foo.rs".into());
+    store.ignore_regex("^fixture_", Some("rs")).unwrap();
+    store.ignore_regex_for_project("^gen").unwrap();
+
+    store.remove_regex("^fixture_", Some("rs")).unwrap();
+    store.remove_regex_for_project("^gen").unwrap();
+
+    assert!(!store.should_ignore("fixture_ab12", &foo_rs, "en_US"));
+    assert!(!store.should_ignore("gen42", &foo_rs, "en_US"));
+    let err = store.remove_regex("^fixture_", Some("rs")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "'^fixture_' was not in the extension rs regexes"
+    );
+}
+
+#[test]
+fn test_skip_file_name_writes_in_local_toml() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+
+    store.skip_file_name("yarn.lock").unwrap();
+
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    let actual: LocalIgnore = load(&local_toml).unwrap();
+    assert!(actual.patterns.contains("yarn.lock"));
+    assert!(store.is_skipped(&relative_path("front/yarn.lock")));
+    assert!(!store.is_skipped(&relative_path("front/package.json")));
+}
+
+#[test]
+fn test_skip_path_only_skips_this_file() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+
+    store.skip_path(&relative_path("docs/notes.md")).unwrap();
+
+    assert!(store.is_skipped(&relative_path("docs/notes.md")));
+    assert!(!store.is_skipped(&relative_path("notes.md")));
+    assert!(!store.is_skipped(&relative_path("other/docs/notes.md")));
+}
+
+#[test]
+fn test_skip_directory_skips_everything_below() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+
+    store.skip_directory(&relative_path("assets")).unwrap();
+    store
+        .skip_directory(&relative_path("db/migrations/"))
+        .unwrap();
+
+    assert!(store.has_skip_pattern("/assets/"));
+    assert!(store.has_skip_pattern("/db/migrations/"));
+    assert!(store.is_skipped(&relative_path("assets/logo.svg")));
+    assert!(store.is_skipped(&relative_path("assets/fonts/LICENSE.txt")));
+    assert!(store.is_skipped(&relative_path("db/migrations/2024/001_init.sql")));
+    assert!(!store.is_skipped(&relative_path("db/schema.sql")));
+    assert!(!store.is_skipped(&relative_path("src/assets/readme.md")));
+    assert!(!store.is_skipped(&relative_path("assets.md")));
+
+    store.remove_skip_pattern("/assets/").unwrap();
+
+    assert!(!store.is_skipped(&relative_path("assets/logo.svg")));
+    assert!(store.is_skipped(&relative_path("db/migrations/2024/001_init.sql")));
+}
+
+#[test]
+fn test_ignore_reason() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(
+        &temp_dir,
+        r#"
+        global = ["global"]
+
+        [extensions]
+        py = ["pyword"]
+
+        [lang]
+        fr = ["bonjour"]
+        "#,
+        r#"
+        project = ["projword", "frobnicate"]
+        match_inflections = true
+
+        [lang]
+        en = ["colour"]
+
+        [paths]
+        "foo.py" = ["pathword"]
+        "#,
+    );
+    store.ignore_regex_for_project("^gen[0-9]+$").unwrap();
+    store.enable_builtin_list("unix").unwrap();
+    let foo_py = relative_path("foo.py");
+
+    let reason = |word, lang| store.ignore_reason(word, &foo_py, lang);
+
+    assert_eq!(reason("global", "en_US"), Some(IgnoreReason::Global));
+    assert_eq!(
+        reason("colour", "en_US"),
+        Some(IgnoreReason::ProjectAndLang("en".to_string()))
+    );
+    assert_eq!(
+        reason("bonjour", "fr_FR"),
+        Some(IgnoreReason::Lang("fr".to_string()))
+    );
+    assert_eq!(
+        reason("pyword", "en_US"),
+        Some(IgnoreReason::Extension("py".to_string()))
+    );
+    assert_eq!(reason("projword", "en_US"), Some(IgnoreReason::Project));
+    assert_eq!(
+        reason("pathword", "en_US"),
+        Some(IgnoreReason::Path("foo.py".to_string()))
+    );
+    assert_eq!(
+        reason("frobnicates", "en_US"),
+        Some(IgnoreReason::Inflection {
+            candidate: "frobnicate".to_string(),
+            reason: Box::new(IgnoreReason::Project)
+        })
+    );
+    assert_eq!(
+        reason("gen42", "en_US"),
+        Some(IgnoreReason::Regex("^gen[0-9]+$".to_string()))
+    );
+    assert_eq!(reason("chmod", "en_US"), Some(IgnoreReason::Builtin));
+    assert_eq!(reason("bonjour", "en_US"), None);
+}
+
+#[test]
+fn test_all_ignore_reasons() {
+    let temp_dir = get_test_dir();
+    let store = create_store(
+        &temp_dir,
+        r#"
+        global = ["foo"]
+
+        [extensions]
+        py = ["foo"]
+        "#,
+        r#"
+        project = ["foo", "bar"]
+        "#,
+    );
+    let foo_py = relative_path("foo.py");
+
+    assert_eq!(
+        store.all_ignore_reasons("foo", &foo_py, "en_US"),
+        &[
+            IgnoreReason::Global,
+            IgnoreReason::Extension("py".to_string()),
+            IgnoreReason::Project,
+        ]
+    );
+    assert_eq!(
+        store.all_ignore_reasons("bar", &foo_py, "en_US"),
+        &[IgnoreReason::Project]
+    );
+    assert!(store.all_ignore_reasons("baz", &foo_py, "en_US").is_empty());
+}
+
+#[test]
+fn test_ignore_reason_message() {
+    let reason = IgnoreReason::Inflection {
+        candidate: "frobnicate".to_string(),
+        reason: Box::new(IgnoreReason::Lang("en".to_string())),
+    };
+
+    assert_eq!(
+        reason.to_string(),
+        "an inflection of 'frobnicate', which is in the ignore list for lang 'en'"
+    );
+}
+
+#[test]
+fn test_only_global_lists_apply_to_foreign_files() {
+    let temp_dir = get_test_dir();
+    let mut store = create_store(
+        &temp_dir,
+        r#"
+        global = ["global"]
+
+        [extensions]
+        md = ["mdword"]
+
+        [lang]
+        en = ["colour"]
+        "#,
+        r#"
+        project = ["projword"]
+
+        [lang]
+        en = ["projcolour"]
+        "#,
+    );
+    store.ignore_regex(r"^tmpVar\w+$", None).unwrap();
+    store.ignore_regex_for_project("^gen[0-9]+$").unwrap();
+    let foreign = RelativePath::from_path_unchecked(temp_dir.path().join("notes.md"));
+    assert!(foreign.is_foreign());
+
+    for word in ["global", "mdword", "colour", "tmpVarXq3"] {
+        assert!(store.should_ignore(word, &foreign, "en_US"), "{word}");
+    }
+    for word in ["projword", "projcolour", "gen42"] {
+        assert!(!store.should_ignore(word, &foreign, "en_US"), "{word}");
+    }
+}
+
+#[test]
+fn test_ignored_identifiers_are_saved_in_both_files() {
+    let temp_dir = get_test_dir();
+    let mut store = load_with_local(&temp_dir, "").unwrap();
+
+    store.ignore_identifier("XgboostClassifier").unwrap();
+    store.ignore_identifier_for_project("parse_tmpVar").unwrap();
+
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = temp_dir.path().join("project").join("skyspell.toml");
+    let global: GlobalIgnore = load(&global_toml).unwrap();
+    let local: LocalIgnore = load(&local_toml).unwrap();
+    assert_eq!(
+        global.identifiers,
+        BTreeSet::from(["XgboostClassifier".to_string()])
+    );
+    assert_eq!(
+        local.identifiers,
+        BTreeSet::from(["parse_tmpVar".to_string()])
+    );
+    let store = IgnoreStore::load(global_toml, local_toml).unwrap();
+    assert!(store.is_ignored_identifier("XgboostClassifier"));
+    assert!(!store.is_ignored_identifier_for_project("XgboostClassifier"));
+    assert!(store.should_ignore_identifier("parse_tmpVar"));
+    assert_eq!(
+        store.ignored_identifiers(),
+        ["XgboostClassifier", "parse_tmpVar"]
+    );
+    // Only the whole identifier is ignored, not its words
+    let readme = relative_path("README.md");
+    assert!(!store.should_ignore("Xgboost", &readme, "en_US"));
+}
+
+#[test]
+fn test_remove_ignored_identifiers() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    store.ignore_identifier("XgboostClassifier").unwrap();
+    store
+        .ignore_identifier_for_project("XgboostClassifier")
+        .unwrap();
+
+    store
+        .remove_ignored_identifier("XgboostClassifier")
+        .unwrap();
+    store
+        .remove_ignored_identifier_for_project("XgboostClassifier")
+        .unwrap();
+
+    assert!(!store.should_ignore_identifier("XgboostClassifier"));
+    let err = store
+        .remove_ignored_identifier("XgboostClassifier")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "'XgboostClassifier' is not in the identifiers ignore list"
+    );
+}
+
+#[test]
+fn test_local_ignore_entries() {
+    let temp_dir = get_test_dir();
+    let local_toml = temp_dir.path().join("skyspell-ignore.toml");
+    std::fs::write(
+        &local_toml,
+        r#"
+patterns = ["Cargo.lock"]
+project = ["foo"]
+identifiers = ["XgboostClassifier"]
+
+[project_categories]
+medical = ["dexamethasone"]
+
+[lang]
+fr = ["hello"]
+
+[paths]
+'src\main.rs' = ["bar"]
+"#,
+    )
+    .unwrap();
+
+    let local = LocalIgnore::load(&local_toml).unwrap();
+
+    assert_eq!(
+        local.entries(),
+        [
+            Operation::new_ignore_for_project("foo"),
+            Operation::new_ignore_for_project_in_category("dexamethasone", "medical"),
+            Operation::new_ignore_for_project_and_lang("hello", "fr"),
+            Operation::new_ignore_for_path("bar", &relative_path("src/main.rs")),
+            Operation::new_ignore_identifier_for_project("XgboostClassifier"),
+            Operation::new_skip("Cargo.lock"),
+        ]
+    );
+}
@@ -0,0 +1,106 @@
+//! Words can also be ignored when they match a regular expression, for
+//! generated identifiers like `tmpVarXq3`
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+// Keep compiled patterns small, so that a bad pattern cannot make
+// every lookup slow
+const REGEX_SIZE_LIMIT: usize = 256 * 1024;
+
+/// Longer words are never matched against the patterns
+pub const MAX_REGEX_WORD_LENGTH: usize = 256;
+
+pub(crate) fn compile(pattern: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .with_context(|| format!("Invalid regex '{pattern}'"))
+}
+
+/// The `regex` section of the global ignore file
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct GlobalRegexes {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) global: BTreeSet<String>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) extensions: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl GlobalRegexes {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.extensions.is_empty()
+    }
+}
+
+/// The `regex` section of the local ignore file
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct LocalRegexes {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) project: BTreeSet<String>,
+}
+
+impl LocalRegexes {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.project.is_empty()
+    }
+}
+
+/// All the patterns, compiled once when the ignore files are loaded
+#[derive(Debug, Default, Clone)]
+pub(crate) struct CompiledRegexes {
+    global: Vec<Regex>,
+    extensions: BTreeMap<String, Vec<Regex>>,
+    project: Vec<Regex>,
+}
+
+fn compile_all(patterns: &BTreeSet<String>) -> Result<Vec<Regex>> {
+    patterns.iter().map(|p| compile(p)).collect()
+}
+
+impl CompiledRegexes {
+    pub(crate) fn new(global: &GlobalRegexes, local: &LocalRegexes) -> Result<Self> {
+        let extensions = global
+            .extensions
+            .iter()
+            .map(|(extension, patterns)| Ok((extension.clone(), compile_all(patterns)?)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            global: compile_all(&global.global)?,
+            extensions,
+            project: compile_all(&local.project)?,
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.extensions.is_empty() && self.project.is_empty()
+    }
+
+    /// The first pattern matching the word, if any
+    pub(crate) fn find_match(&self, word: &str, extension: Option<&str>) -> Option<&str> {
+        self.find_global_match(word, extension)
+            .or_else(|| find_in(&self.project, word))
+    }
+
+    /// Like find_match(), but without the patterns of the project
+    pub(crate) fn find_global_match(&self, word: &str, extension: Option<&str>) -> Option<&str> {
+        let for_extension = extension.and_then(|e| self.extensions.get(e));
+        find_in(&self.global, word).or_else(|| find_in(for_extension?, word))
+    }
+}
+
+fn find_in<'a>(regexes: &'a [Regex], word: &str) -> Option<&'a str> {
+    if word.len() > MAX_REGEX_WORD_LENGTH {
+        return None;
+    }
+    regexes
+        .iter()
+        .find(|r| r.is_match(word))
+        .map(|r| r.as_str())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,45 @@
+use super::*;
+
+#[test]
+fn test_invalid_pattern() {
+    let err = compile("tmp(").unwrap_err();
+
+    assert!(err.to_string().contains("Invalid regex 'tmp('"));
+}
+
+#[test]
+fn test_pattern_too_large() {
+    assert!(compile(r"\w{1000}\w{1000}").is_err());
+}
+
+#[test]
+fn test_match_by_scope() {
+    let global = GlobalRegexes {
+        global: ["^tmpVar\\w+$".to_string()].into(),
+        extensions: [("rs".to_string(), ["^fixture_[a-f0-9]+$".to_string()].into())].into(),
+    };
+    let local = LocalRegexes::default();
+    let regexes = CompiledRegexes::new(&global, &local).unwrap();
+
+    assert_eq!(regexes.find_match("tmpVarXq3", None), Some("^tmpVar\\w+$"));
+    assert!(regexes.find_match("mytmpVarXq3", None).is_none());
+    assert_eq!(
+        regexes.find_match("fixture_ab12cd", Some("rs")),
+        Some("^fixture_[a-f0-9]+$")
+    );
+    assert!(regexes.find_match("fixture_ab12cd", Some("py")).is_none());
+    assert!(regexes.find_match("fixture_ab12cd", None).is_none());
+}
+
+#[test]
+fn test_long_words_are_not_matched() {
+    let local = LocalRegexes {
+        project: [".*".to_string()].into(),
+    };
+    let regexes = CompiledRegexes::new(&GlobalRegexes::default(), &local).unwrap();
+
+    assert!(regexes.find_match("short", None).is_some());
+    assert!(regexes
+        .find_match(&"a".repeat(MAX_REGEX_WORD_LENGTH + 1), None)
+        .is_none());
+}
@@ -0,0 +1,92 @@
+//! Naive English inflection rules, used to accept plural and verb forms
+//! of ignored words when `match_inflections` is set.
+//!
+//! Irregular forms (`ponies` -> `pony`, `ran` -> `run`, ...) are not handled.
+
+const SUFFIXES: [&str; 6] = ["ers", "er", "ing", "ed", "es", "s"];
+
+// Suffixes that cause a final `e` to be dropped, or a final consonant to be doubled
+const VOWEL_SUFFIXES: [&str; 4] = ["ers", "er", "ing", "ed"];
+
+// Do not strip suffixes from very short words: 'bus' is not an inflection of 'bu'
+const MIN_STEM_LEN: usize = 3;
+
+fn is_vowel(c: char) -> bool {
+    matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+/// Possible base forms of `word`: 'stopped' -> 'stop', 'frobnicates' -> 'frobnicate'
+fn base_forms(word: &str) -> Vec<String> {
+    let mut res = vec![];
+    for suffix in SUFFIXES {
+        let stem = match word.strip_suffix(suffix) {
+            Some(s) if s.len() >= MIN_STEM_LEN => s,
+            _ => continue,
+        };
+        res.push(stem.to_string());
+        if suffix != "s" {
+            res.push(format!("{stem}e"));
+        }
+        let mut chars = stem.chars().rev();
+        if let (Some(last), Some(before_last)) = (chars.next(), chars.next()) {
+            if VOWEL_SUFFIXES.contains(&suffix) && last == before_last && !is_vowel(last) {
+                res.push(stem[..stem.len() - 1].to_string());
+            }
+        }
+    }
+    res
+}
+
+// Consonant-vowel-consonant endings get their final consonant doubled: stop -> stopped
+fn should_double(base: &str) -> bool {
+    let chars: Vec<char> = base.chars().rev().take(3).collect();
+    match chars[..] {
+        [last, middle, first] => {
+            !is_vowel(last)
+                && !matches!(last, 'w' | 'x' | 'y')
+                && is_vowel(middle)
+                && !is_vowel(first)
+        }
+        _ => false,
+    }
+}
+
+/// Possible inflected forms of `base`: 'frobnicate' -> 'frobnicates', 'frobnicating', ...
+fn inflected_forms(base: &str) -> Vec<String> {
+    let mut res = vec![format!("{base}s"), format!("{base}es")];
+    for suffix in VOWEL_SUFFIXES {
+        match base.strip_suffix('e') {
+            Some(stem) => res.push(format!("{stem}{suffix}")),
+            None => {
+                res.push(format!("{base}{suffix}"));
+                if should_double(base) {
+                    let last = &base[base.len() - 1..];
+                    res.push(format!("{base}{last}{suffix}"));
+                }
+            }
+        }
+    }
+    res
+}
+
+/// Return the words that `word` may be an inflection of, or that may be
+/// inflections of the same base form as `word`.
+///
+/// Only ASCII alphabetic words are handled - an empty list is returned
+/// for everything else.
+pub fn inflection_candidates(word: &str) -> Vec<String> {
+    if word.len() < MIN_STEM_LEN || !word.chars().all(|c| c.is_ascii_alphabetic()) {
+        return vec![];
+    }
+    let mut bases = base_forms(word);
+    bases.push(word.to_string());
+    let mut res: Vec<String> = bases.iter().flat_map(|b| inflected_forms(b)).collect();
+    res.extend(bases);
+    res.retain(|x| x != word);
+    res.sort();
+    res.dedup();
+    res
+}
+
+#[cfg(test)]
+mod tests;
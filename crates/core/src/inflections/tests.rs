@@ -0,0 +1,77 @@
+use super::*;
+
+fn assert_candidate(word: &str, expected: &str) {
+    let candidates = inflection_candidates(word);
+    assert!(
+        candidates.contains(&expected.to_string()),
+        "{expected} not found in candidates for {word}: {candidates:?}"
+    );
+}
+
+#[test]
+fn test_plurals() {
+    assert_candidate("frobnicates", "frobnicate");
+    assert_candidate("boxes", "box");
+    assert_candidate("widgets", "widget");
+}
+
+#[test]
+fn test_past_tense_with_e_restoration() {
+    assert_candidate("frobnicated", "frobnicate");
+}
+
+#[test]
+fn test_gerund_with_e_restoration() {
+    assert_candidate("frobnicating", "frobnicate");
+}
+
+#[test]
+fn test_doubled_consonant() {
+    assert_candidate("stopped", "stop");
+    assert_candidate("stopping", "stop");
+    assert_candidate("stopper", "stop");
+    assert_candidate("stoppers", "stop");
+}
+
+#[test]
+fn test_inflected_forms_of_base_word() {
+    assert_candidate("frobnicate", "frobnicates");
+    assert_candidate("frobnicate", "frobnicated");
+    assert_candidate("frobnicate", "frobnicating");
+    assert_candidate("stop", "stopped");
+    assert_candidate("box", "boxes");
+}
+
+#[test]
+fn test_sibling_inflections() {
+    assert_candidate("frobnicated", "frobnicates");
+    assert_candidate("stopping", "stopped");
+}
+
+#[test]
+fn test_no_doubling_after_w_x_or_y() {
+    assert!(!inflection_candidates("fix").contains(&"fixxed".to_string()));
+    assert!(!inflection_candidates("play").contains(&"playyed".to_string()));
+}
+
+#[test]
+fn test_does_not_return_word_itself() {
+    assert!(!inflection_candidates("frobnicates").contains(&"frobnicates".to_string()));
+}
+
+#[test]
+fn test_short_stems_are_not_stripped() {
+    assert!(!inflection_candidates("bus").contains(&"bu".to_string()));
+    assert!(inflection_candidates("is").is_empty());
+}
+
+#[test]
+fn test_non_ascii_words_are_ignored() {
+    assert!(inflection_candidates("café").is_empty());
+    assert!(inflection_candidates("foo_bar").is_empty());
+}
+
+#[test]
+fn test_irregular_plurals_are_out_of_scope() {
+    assert!(!inflection_candidates("ponies").contains(&"pony".to_string()));
+}
@@ -0,0 +1,194 @@
+//! Language-specific rules applied when a word is not found in the
+//! dictionary, so that 'Donaudampfschiff' or 'uber' are accepted in German
+//! even if the dictionary only knows 'Donau', 'Dampfschiff' and 'über'.
+//!
+//! Everything here is off by default, see `compound_langs` and
+//! `fold_diacritics` in the local ignore file.
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+
+use crate::Dictionary;
+
+// Both halves of a compound word must be at least this long, so that
+// misspellings are not accepted just because they start or end with
+// a very short word
+const MIN_COMPOUND_PART_LEN: usize = 3;
+
+#[derive(Debug, Default, Clone)]
+pub struct LangOptions {
+    /// Language codes ('de', 'nl') or full languages ('de_DE') where
+    /// compound words should be split
+    pub compound_langs: BTreeSet<String>,
+    pub fold_diacritics: bool,
+}
+
+/// A Dictionary that tries language-specific rules when the
+/// wrapped dictionary does not know a word
+pub struct LangRules<D: Dictionary> {
+    dictionary: D,
+    split_compounds: bool,
+    fold_diacritics: bool,
+}
+
+impl<D: Dictionary> LangRules<D> {
+    pub fn new(dictionary: D, options: &LangOptions) -> Self {
+        let lang = dictionary.lang();
+        let split_compounds = options.compound_langs.contains(lang)
+            || options.compound_langs.contains(lang_code(lang));
+        let fold_diacritics = options.fold_diacritics && !diacritics_table(lang).is_empty();
+        Self {
+            dictionary,
+            split_compounds,
+            fold_diacritics,
+        }
+    }
+
+    fn check_compound(&self, word: &str) -> Result<bool> {
+        for (i, _) in word.char_indices() {
+            let (head, tail) = word.split_at(i);
+            if head.chars().count() < MIN_COMPOUND_PART_LEN {
+                continue;
+            }
+            if tail.chars().count() < MIN_COMPOUND_PART_LEN {
+                break;
+            }
+            if !self.dictionary.check(head)? {
+                continue;
+            }
+            // German nouns keep their capital letter when they stand alone:
+            // 'Donau' + 'dampfschiff' -> 'Dampfschiff'
+            if self.dictionary.check(tail)? || self.dictionary.check(&capitalize(tail))? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn check_folded(&self, word: &str) -> Result<bool> {
+        for candidate in diacritics_candidates(word, diacritics_table(self.lang())) {
+            if self.dictionary.check(&candidate)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl<D: Dictionary> Dictionary for LangRules<D> {
+    fn check(&self, word: &str) -> Result<bool> {
+        if self.dictionary.check(word)? {
+            return Ok(true);
+        }
+        if self.split_compounds && self.check_compound(word)? {
+            return Ok(true);
+        }
+        if self.fold_diacritics && self.check_folded(word)? {
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn suggest(&self, error: &str) -> Result<Vec<String>> {
+        self.dictionary.suggest(error)
+    }
+
+    fn lang(&self) -> &str {
+        self.dictionary.lang()
+    }
+
+    fn provider(&self) -> &str {
+        self.dictionary.provider()
+    }
+}
+
+// 'de_DE' -> 'de'
+fn lang_code(lang: &str) -> &str {
+    lang.split(['_', '-']).next().unwrap_or(lang)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Pairs of (letter with diacritic, letter without) for the given language
+fn diacritics_table(lang: &str) -> &'static [(char, char)] {
+    match lang_code(lang) {
+        "de" => &[('ä', 'a'), ('ö', 'o'), ('ü', 'u')],
+        "nl" => &[('ë', 'e'), ('ï', 'i'), ('é', 'e'), ('è', 'e')],
+        "fr" => &[
+            ('à', 'a'),
+            ('â', 'a'),
+            ('ç', 'c'),
+            ('é', 'e'),
+            ('è', 'e'),
+            ('ê', 'e'),
+            ('ë', 'e'),
+            ('î', 'i'),
+            ('ï', 'i'),
+            ('ô', 'o'),
+            ('ù', 'u'),
+            ('û', 'u'),
+        ],
+        "es" => &[
+            ('á', 'a'),
+            ('é', 'e'),
+            ('í', 'i'),
+            ('ñ', 'n'),
+            ('ó', 'o'),
+            ('ú', 'u'),
+            ('ü', 'u'),
+        ],
+        _ => &[],
+    }
+}
+
+// Apply `f` to the lower-case version of `c`, preserving case
+fn map_lowercase(c: char, f: impl Fn(char) -> Option<char>) -> Option<char> {
+    let lower = c.to_lowercase().next()?;
+    let mapped = f(lower)?;
+    if c.is_uppercase() {
+        mapped.to_uppercase().next()
+    } else {
+        Some(mapped)
+    }
+}
+
+/// Variants of `word` to look up in the dictionary: the word with all its
+/// diacritics removed, and the word with a diacritic added to one of its
+/// letters ('uber' -> 'über')
+fn diacritics_candidates(word: &str, table: &[(char, char)]) -> Vec<String> {
+    let mut res = vec![];
+
+    let folded: String = word
+        .chars()
+        .map(|c| {
+            map_lowercase(c, |l| table.iter().find(|(d, _)| *d == l).map(|(_, b)| *b)).unwrap_or(c)
+        })
+        .collect();
+    if folded != word {
+        res.push(folded);
+    }
+
+    let chars: Vec<char> = word.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        for (with_diacritic, base) in table {
+            let replacement = match map_lowercase(*c, |l| (l == *base).then_some(*with_diacritic)) {
+                Some(r) => r,
+                None => continue,
+            };
+            let mut candidate = chars.clone();
+            candidate[i] = replacement;
+            res.push(candidate.into_iter().collect());
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests;
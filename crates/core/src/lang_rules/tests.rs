@@ -0,0 +1,127 @@
+use super::*;
+
+use crate::tests::FakeDictionary;
+
+struct GermanDictionary(FakeDictionary);
+
+impl Dictionary for GermanDictionary {
+    fn check(&self, word: &str) -> Result<bool> {
+        self.0.check(word)
+    }
+
+    fn suggest(&self, error: &str) -> Result<Vec<String>> {
+        self.0.suggest(error)
+    }
+
+    fn lang(&self) -> &str {
+        "de_DE"
+    }
+
+    fn provider(&self) -> &str {
+        "fake"
+    }
+}
+
+fn german_dictionary(known: &[&str]) -> GermanDictionary {
+    let mut dictionary = FakeDictionary::new();
+    for word in known {
+        dictionary.add_known(word);
+    }
+    GermanDictionary(dictionary)
+}
+
+fn options(compound_langs: &[&str], fold_diacritics: bool) -> LangOptions {
+    LangOptions {
+        compound_langs: compound_langs.iter().map(|x| x.to_string()).collect(),
+        fold_diacritics,
+    }
+}
+
+#[test]
+fn test_lang_code() {
+    assert_eq!(lang_code("de_DE"), "de");
+    assert_eq!(lang_code("nl-BE"), "nl");
+    assert_eq!(lang_code("fr"), "fr");
+}
+
+#[test]
+fn test_off_by_default() {
+    let dictionary = german_dictionary(&["Donau", "Dampfschiff", "über"]);
+    let rules = LangRules::new(dictionary, &LangOptions::default());
+
+    assert!(rules.check("Donau").unwrap());
+    assert!(!rules.check("DonauDampfschiff").unwrap());
+    assert!(!rules.check("uber").unwrap());
+}
+
+#[test]
+fn test_compound_with_capitalized_second_part() {
+    let dictionary = german_dictionary(&["Donau", "Dampfschiff"]);
+    let rules = LangRules::new(dictionary, &options(&["de"], false));
+
+    assert!(rules.check("Donaudampfschiff").unwrap());
+    assert!(!rules.check("Donaudampfschif").unwrap());
+}
+
+#[test]
+fn test_compound_lang_must_match_dictionary() {
+    let dictionary = german_dictionary(&["Donau", "Dampfschiff"]);
+    let rules = LangRules::new(dictionary, &options(&["nl"], false));
+
+    assert!(!rules.check("Donaudampfschiff").unwrap());
+}
+
+#[test]
+fn test_compound_parts_have_a_minimum_length() {
+    let dictionary = german_dictionary(&["ab", "fahrt", "Bahn", "hof"]);
+    let rules = LangRules::new(dictionary, &options(&["de_DE"], false));
+
+    assert!(!rules.check("abfahrt").unwrap());
+    assert!(rules.check("Bahnhof").unwrap());
+}
+
+#[test]
+fn test_compound_with_multi_byte_chars() {
+    let dictionary = german_dictionary(&["Größe", "Ordnung"]);
+    let rules = LangRules::new(dictionary, &options(&["de"], false));
+
+    assert!(!rules.check("Größenordnung").unwrap());
+    assert!(rules.check("Größeordnung").unwrap());
+}
+
+#[test]
+fn test_fold_diacritics_adds_missing_diacritic() {
+    let dictionary = german_dictionary(&["über", "Ärger"]);
+    let rules = LangRules::new(dictionary, &options(&[], true));
+
+    assert!(rules.check("uber").unwrap());
+    assert!(rules.check("Arger").unwrap());
+    assert!(!rules.check("ubar").unwrap());
+}
+
+#[test]
+fn test_fold_diacritics_removes_diacritics() {
+    let dictionary = german_dictionary(&["Muller"]);
+    let rules = LangRules::new(dictionary, &options(&[], true));
+
+    assert!(rules.check("Müller").unwrap());
+}
+
+#[test]
+fn test_no_folding_for_languages_without_a_table() {
+    // FakeDictionary is en_US
+    let mut dictionary = FakeDictionary::new();
+    dictionary.add_known("cafe");
+    let rules = LangRules::new(dictionary, &options(&[], true));
+
+    assert!(!rules.check("café").unwrap());
+}
+
+#[test]
+fn test_diacritics_candidates() {
+    let candidates = diacritics_candidates("Tur", diacritics_table("de"));
+    assert_eq!(candidates, &["Tür"]);
+
+    let candidates = diacritics_candidates("Tür", diacritics_table("de"));
+    assert_eq!(candidates, &["Tur"]);
+}
@@ -1,12 +1,37 @@
 #[macro_use]
 extern crate lazy_static;
 
+mod atomic_write;
+mod builtin_lists;
+mod classifier;
+mod confusables;
 mod dictionary;
+mod dictionary_cache;
+mod encoding;
+mod error;
+mod error_sink;
+mod explain;
+mod file_lang;
+mod file_lock;
+mod hooks;
+mod ignore_regex;
+mod inflections;
+mod lang_rules;
+mod ops_file;
+mod personal_dictionary;
+mod po;
+mod providers;
+mod structured;
+mod suggestions;
 
-#[cfg(target_family = "unix")]
+#[cfg(all(target_family = "unix", feature = "enchant"))]
 #[path = "system_dictionary/unix.rs"]
 mod system_dictionary;
 
+#[cfg(all(target_family = "unix", not(feature = "enchant")))]
+#[path = "system_dictionary/none.rs"]
+mod system_dictionary;
+
 #[cfg(target_family = "windows")]
 #[path = "system_dictionary/windows.rs"]
 mod system_dictionary;
@@ -16,17 +41,49 @@ pub use system_dictionary::SystemDictionary;
 pub mod ignore;
 pub mod operations;
 pub mod os_io;
+pub mod output;
 pub mod project;
+pub mod report;
 pub mod skip_file;
 pub mod tests;
 pub mod tokens;
 
-pub use checker::{Checker, CheckerState, ProcessOutcome, SpellingError};
-pub use dictionary::Dictionary;
-pub use ignore::{global_path, GlobalIgnore, IgnoreStore, LocalIgnore};
+pub use builtin_lists::{builtin_list, builtin_list_names};
+pub use checker::{state_dir, MIN_TOKENS_FOR_ERROR_RATIO, SKYSPELL_STATE_DIR};
+pub use checker::{Checker, CheckerState, HistoryEntry, ProcessOutcome, SkipReason, SpellingError};
+pub use classifier::{Classification, TokenClassifier};
+pub use confusables::{detect_mixed_script, MixedScript};
+pub use dictionary::{close_languages, edit_distance, normalize_lang, Dictionary};
+pub use dictionary_cache::{
+    DictionaryCache, PersistentCachedDictionary, DICTIONARY_CACHE_MAX_AGE, DICTIONARY_CACHE_SIZE,
+};
+pub use encoding::{decode_to_utf8, detect_encoding, DetectedEncoding, Encoding};
+pub use error::SkyspellError;
+pub use error_sink::{ErrorSink, SinkChecker};
+pub use explain::FileExplanation;
+pub use file_lang::{find_lang_tag, lang_tag, LangDictionaries, LangOpener, LANG_TAG_LINES};
+pub use hooks::{HookEvent, HookOptions, Hooks, SKYSPELL_IN_HOOK};
+pub use ignore::{global_path, GlobalIgnore, IgnoreReason, IgnoreStats, IgnoreStore, LocalIgnore};
+pub use ignore::{AcceptAcronyms, Extends, IgnoredWord, UsedEntries, DEFAULT_ACRONYM_MIN_LENGTH};
+pub use ignore::{IgnoreLimits, LimitUsage};
+pub use ignore::{DEFAULT_MAX_LINE_ERROR_RATIO, IGNORE_SOURCES};
+pub use inflections::inflection_candidates;
+pub use lang_rules::{LangOptions, LangRules};
 pub use operations::Operation;
+pub use ops_file::{append_to_ops_file, load_ops_file};
 pub use os_io::{OperatingSystemIO, StandardIO};
-pub use project::{Project, ProjectPath, RelativePath, SKYSPELL_LOCAL_IGNORE};
-pub use skip_file::SkipFile;
-pub use tokens::TokenProcessor;
+pub use output::Verbosity;
+pub use personal_dictionary::{
+    parse_forbidden_words, parse_personal_dictionary, PersonalDictionary,
+};
+pub use po::{is_po_file, msgstr_ranges, po_language, PoOptions};
+pub use project::{find_project_root, normalize_path, parse_file_size};
+pub use project::{Project, ProjectPath, RelativePath};
+pub use project::{DEFAULT_MAX_FILE_SIZE, NO_EXTENSION, SKYSPELL_LOCAL_IGNORE};
+pub use providers::{open_dictionary, provider_names, register_provider, DictionaryFactory};
+pub use report::{Report, ReportEntry, SeenFindings};
+pub use skip_file::{directory_pattern, walk_exclusion, SkipFile, SkipRule, WalkExclusion};
+pub use structured::{string_values, StructuredFormat};
+pub use suggestions::{clean_suggestions, suggest_replacements, SuggestionWorker};
+pub use tokens::{Columns, ExtractMode, SkippedRegion, Token, TokenProcessor};
 pub(crate) mod checker;
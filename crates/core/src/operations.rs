@@ -2,8 +2,8 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
-use crate::IgnoreStore;
 use crate::RelativePath;
+use crate::{IgnoreStore, SkyspellError};
 
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub enum Operation {
@@ -12,6 +12,12 @@ pub enum Operation {
     IgnoreForPath(IgnoreForPath),
     IgnoreForProject(IgnoreForProject),
     IgnoreForLang(IgnoreForLang),
+    IgnoreForProjectAndLang(IgnoreForProjectAndLang),
+    IgnoreIdentifier(IgnoreIdentifier),
+    IgnoreIdentifierForProject(IgnoreIdentifierForProject),
+    Skip(Skip),
+    Remove(Remove),
+    Batch(Batch),
 }
 
 impl Operation {
@@ -23,6 +29,14 @@ impl Operation {
     pub fn new_ignore_for_project(word: &str) -> Self {
         Self::IgnoreForProject(IgnoreForProject {
             word: word.to_string(),
+            category: None,
+        })
+    }
+
+    pub fn new_ignore_for_project_in_category(word: &str, category: &str) -> Self {
+        Self::IgnoreForProject(IgnoreForProject {
+            word: word.to_string(),
+            category: Some(category.to_string()),
         })
     }
 
@@ -47,6 +61,183 @@ impl Operation {
         })
     }
 
+    pub fn new_ignore_for_project_and_lang(word: &str, lang: &str) -> Self {
+        Self::IgnoreForProjectAndLang(IgnoreForProjectAndLang {
+            word: word.to_string(),
+            lang: lang.to_string(),
+        })
+    }
+
+    /// Stop checking the words of a whole identifier, like `XgboostClassifier`
+    pub fn new_ignore_identifier(identifier: &str) -> Self {
+        Self::IgnoreIdentifier(IgnoreIdentifier {
+            identifier: identifier.to_string(),
+        })
+    }
+
+    pub fn new_ignore_identifier_for_project(identifier: &str) -> Self {
+        Self::IgnoreIdentifierForProject(IgnoreIdentifierForProject {
+            identifier: identifier.to_string(),
+        })
+    }
+
+    /// Add a pattern to the skipped files of the project
+    pub fn new_skip(pattern: &str) -> Self {
+        Self::Skip(Skip {
+            pattern: pattern.to_string(),
+        })
+    }
+
+    /// Remove the word from the ignore lists of all the given operations.
+    /// Undoing it adds the word back
+    pub fn new_remove(removed: Vec<Operation>) -> Self {
+        Self::Remove(Remove { removed })
+    }
+
+    /// Apply several operations at once - they are undone together
+    pub fn new_batch(operations: Vec<Operation>) -> Self {
+        let mut words: Vec<_> = operations.iter().map(|o| o.word().to_string()).collect();
+        words.dedup();
+        Self::Batch(Batch {
+            words: words.join(", "),
+            operations,
+        })
+    }
+
+    pub fn is_removal(&self) -> bool {
+        matches!(self, Self::Remove(_))
+    }
+
+    /// True for skip patterns, and batches made only of them
+    pub fn is_skip(&self) -> bool {
+        match self {
+            Self::Skip(_) => true,
+            Self::Batch(b) => !b.operations.is_empty() && b.operations.iter().all(Self::is_skip),
+            _ => false,
+        }
+    }
+
+    pub fn word(&self) -> &str {
+        use Operation::*;
+        match self {
+            Ignore(o) => &o.word,
+            IgnoreForExtension(o) => &o.word,
+            IgnoreForLang(o) => &o.word,
+            IgnoreForPath(o) => &o.word,
+            IgnoreForProject(o) => &o.word,
+            IgnoreForProjectAndLang(o) => &o.word,
+            IgnoreIdentifier(o) => &o.identifier,
+            IgnoreIdentifierForProject(o) => &o.identifier,
+            Skip(o) => &o.pattern,
+            Remove(o) => o.removed.first().map(|o| o.word()).unwrap_or_default(),
+            Batch(o) => &o.words,
+        }
+    }
+
+    /// The file the word is ignored for, if the operation is about one
+    pub fn relative_path(&self) -> Option<&RelativePath> {
+        match self {
+            Self::IgnoreForPath(o) => Some(&o.path),
+            _ => None,
+        }
+    }
+
+    /// Name of the variant, as used in ops files
+    pub fn kind(&self) -> &'static str {
+        use Operation::*;
+        match self {
+            Ignore(_) => "Ignore",
+            IgnoreForExtension(_) => "IgnoreForExtension",
+            IgnoreForLang(_) => "IgnoreForLang",
+            IgnoreForPath(_) => "IgnoreForPath",
+            IgnoreForProject(_) => "IgnoreForProject",
+            IgnoreForProjectAndLang(_) => "IgnoreForProjectAndLang",
+            IgnoreIdentifier(_) => "IgnoreIdentifier",
+            IgnoreIdentifierForProject(_) => "IgnoreIdentifierForProject",
+            Skip(_) => "Skip",
+            Remove(_) => "Remove",
+            Batch(_) => "Batch",
+        }
+    }
+
+    /// Whether the operation applies to all projects
+    pub fn is_global(&self) -> bool {
+        use Operation::*;
+        match self {
+            Ignore(_) | IgnoreForExtension(_) | IgnoreForLang(_) | IgnoreIdentifier(_) => true,
+            Remove(o) => o.removed.iter().all(|o| o.is_global()),
+            Batch(o) => o.operations.iter().all(|o| o.is_global()),
+            _ => false,
+        }
+    }
+
+    /// Whether the word is in the ignore list the operation applies to
+    pub fn is_applied(&self, ignore_store: &IgnoreStore) -> bool {
+        use Operation::*;
+        match self {
+            Ignore(o) => ignore_store.is_ignored(&o.word),
+            IgnoreForExtension(o) => ignore_store.is_ignored_for_extension(&o.word, &o.extension),
+            IgnoreForLang(o) => ignore_store.is_ignored_for_lang(&o.word, &o.lang),
+            IgnoreForPath(o) => ignore_store.is_ignored_for_path(&o.word, &o.path),
+            IgnoreForProject(o) => ignore_store.is_ignored_for_project(&o.word),
+            IgnoreForProjectAndLang(o) => {
+                ignore_store.is_ignored_for_project_and_lang(&o.word, &o.lang)
+            }
+            IgnoreIdentifier(o) => ignore_store.is_ignored_identifier(&o.identifier),
+            IgnoreIdentifierForProject(o) => {
+                ignore_store.is_ignored_identifier_for_project(&o.identifier)
+            }
+            Skip(o) => ignore_store.has_skip_pattern(&o.pattern),
+            Remove(o) => o.removed.iter().all(|o| !o.is_applied(ignore_store)),
+            Batch(o) => o.operations.iter().all(|o| o.is_applied(ignore_store)),
+        }
+    }
+
+    /// Make sure the ignore store still contains what the operation did,
+    /// so that undoing it makes sense - the ignore files may have been
+    /// edited by hand since then
+    pub fn ensure_applied(&self, ignore_store: &IgnoreStore) -> Result<()> {
+        if self.is_applied(ignore_store) {
+            return Ok(());
+        }
+        let word = self.word();
+        let scope = self.scope();
+        let description = if self.is_removal() {
+            format!("'{word}' was added back to the {scope} ignore list")
+        } else {
+            format!("'{word}' is no longer in the {scope} ignore list")
+        };
+        Err(SkyspellError::OperationConflict(description).into())
+    }
+
+    /// Human-readable description of the ignore list the operation applies to
+    pub fn scope(&self) -> String {
+        use Operation::*;
+        match self {
+            Ignore(_) => "global".to_string(),
+            IgnoreForExtension(o) => format!("extension {}", o.extension),
+            IgnoreForLang(o) => format!("lang {}", o.lang),
+            IgnoreForPath(o) => format!("path {}", o.path.normalize()),
+            IgnoreForProject(_) => "project".to_string(),
+            IgnoreForProjectAndLang(o) => format!("project and lang {}", o.lang),
+            IgnoreIdentifier(_) => "identifiers".to_string(),
+            IgnoreIdentifierForProject(_) => "project identifiers".to_string(),
+            Skip(_) => "skipped files".to_string(),
+            Remove(o) => o
+                .removed
+                .iter()
+                .map(|o| o.scope())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Batch(o) => {
+                let mut scopes: Vec<_> = o.operations.iter().map(|o| o.scope()).collect();
+                scopes.sort();
+                scopes.dedup();
+                scopes.join(", ")
+            }
+        }
+    }
+
     pub fn execute(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
         use Operation::*;
         match self {
@@ -55,6 +246,12 @@ impl Operation {
             IgnoreForLang(o) => o.execute(ignore_store),
             IgnoreForPath(o) => o.execute(ignore_store),
             IgnoreForProject(o) => o.execute(ignore_store),
+            IgnoreForProjectAndLang(o) => o.execute(ignore_store),
+            IgnoreIdentifier(o) => o.execute(ignore_store),
+            IgnoreIdentifierForProject(o) => o.execute(ignore_store),
+            Skip(o) => o.execute(ignore_store),
+            Remove(o) => o.execute(ignore_store),
+            Batch(o) => o.execute(ignore_store),
         }
     }
 
@@ -66,6 +263,12 @@ impl Operation {
             IgnoreForLang(o) => o.undo(ignore_store),
             IgnoreForPath(o) => o.undo(ignore_store),
             IgnoreForProject(o) => o.undo(ignore_store),
+            IgnoreForProjectAndLang(o) => o.undo(ignore_store),
+            IgnoreIdentifier(o) => o.undo(ignore_store),
+            IgnoreIdentifierForProject(o) => o.undo(ignore_store),
+            Skip(o) => o.undo(ignore_store),
+            Remove(o) => o.undo(ignore_store),
+            Batch(o) => o.undo(ignore_store),
         }
     }
 }
@@ -120,11 +323,16 @@ impl IgnoreForLang {
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct IgnoreForProject {
     word: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
 }
 
 impl IgnoreForProject {
     fn execute(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
-        ignore_store.ignore_for_project(&self.word)
+        match &self.category {
+            Some(category) => ignore_store.ignore_for_project_in_category(&self.word, category),
+            None => ignore_store.ignore_for_project(&self.word),
+        }
     }
 
     fn undo(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
@@ -132,6 +340,22 @@ impl IgnoreForProject {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct IgnoreForProjectAndLang {
+    word: String,
+    lang: String,
+}
+
+impl IgnoreForProjectAndLang {
+    fn execute(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        ignore_store.ignore_for_project_and_lang(&self.word, &self.lang)
+    }
+
+    fn undo(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        ignore_store.remove_ignored_for_project_and_lang(&self.word, &self.lang)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct IgnoreForPath {
     word: String,
@@ -148,5 +372,297 @@ impl IgnoreForPath {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct IgnoreIdentifier {
+    identifier: String,
+}
+
+impl IgnoreIdentifier {
+    fn execute(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        ignore_store.ignore_identifier(&self.identifier)
+    }
+
+    fn undo(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        ignore_store.remove_ignored_identifier(&self.identifier)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct IgnoreIdentifierForProject {
+    identifier: String,
+}
+
+impl IgnoreIdentifierForProject {
+    fn execute(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        ignore_store.ignore_identifier_for_project(&self.identifier)
+    }
+
+    fn undo(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        ignore_store.remove_ignored_identifier_for_project(&self.identifier)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Skip {
+    pattern: String,
+}
+
+impl Skip {
+    fn execute(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        ignore_store.add_skip_pattern(&self.pattern)
+    }
+
+    fn undo(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        ignore_store.remove_skip_pattern(&self.pattern)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Remove {
+    removed: Vec<Operation>,
+}
+
+impl Remove {
+    fn execute(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        apply_all(
+            ignore_store,
+            self.removed.iter_mut(),
+            Operation::undo,
+            Operation::execute,
+        )
+    }
+
+    fn undo(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        apply_all(
+            ignore_store,
+            self.removed.iter_mut(),
+            Operation::execute,
+            Operation::undo,
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Batch {
+    // Used to describe the operation in the history
+    words: String,
+    operations: Vec<Operation>,
+}
+
+impl Batch {
+    fn execute(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        apply_all(
+            ignore_store,
+            self.operations.iter_mut(),
+            Operation::execute,
+            Operation::undo,
+        )
+    }
+
+    fn undo(&mut self, ignore_store: &mut IgnoreStore) -> Result<()> {
+        apply_all(
+            ignore_store,
+            self.operations.iter_mut().rev(),
+            Operation::undo,
+            Operation::execute,
+        )
+    }
+}
+
+type Step = fn(&mut Operation, &mut IgnoreStore) -> Result<()>;
+
+// Apply the steps of a compound operation in order. If one of them fails,
+// revert the ones already applied, so that the ignore store is left as it
+// was instead of half-updated.
+// Rollback errors are dropped: the error that caused the rollback is the
+// one worth reporting
+fn apply_all<'a>(
+    ignore_store: &mut IgnoreStore,
+    steps: impl Iterator<Item = &'a mut Operation>,
+    apply: Step,
+    revert: Step,
+) -> Result<()> {
+    let mut applied = vec![];
+    for step in steps {
+        if let Err(e) = apply(step, ignore_store) {
+            for done in applied.into_iter().rev() {
+                let _ = revert(done, ignore_store);
+            }
+            return Err(e);
+        }
+        applied.push(step);
+    }
+    Ok(())
+}
+
+/// Version of the format the operations are persisted with, see
+/// `versioned`. Bump it when Operation changes in a way older versions
+/// cannot read, and keep reading the previous versions
+pub const OPERATION_FORMAT_VERSION: u32 = 1;
+
+/// Serde functions for the operations of the history in the state file,
+/// written as `{ v = 1, op = { Ignore = { word = "foo" } } }`, so that a
+/// change of the format is detected instead of read as something else.
+/// Operations written before the envelope existed - just the `op` part -
+/// are still read, and written with it on the next save
+pub(crate) mod versioned {
+    use std::fmt;
+
+    use serde::de::value::StringDeserializer;
+    use serde::de::{
+        DeserializeSeed, EnumAccess, Error, IgnoredAny, IntoDeserializer, MapAccess, Unexpected,
+        VariantAccess, Visitor,
+    };
+    use serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Operation, OPERATION_FORMAT_VERSION};
+
+    #[derive(Serialize)]
+    struct Envelope<'a> {
+        v: u32,
+        op: &'a Operation,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        operation: &Operation,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let envelope = Envelope {
+            v: OPERATION_FORMAT_VERSION,
+            op: operation,
+        };
+        envelope.serialize(serializer)
+    }
+
+    // Entries are read straight from the underlying deserializer (instead of
+    // going through an untagged enum) so that errors keep their location
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Operation, D::Error> {
+        deserializer.deserialize_map(PersistedVisitor)
+    }
+
+    struct PersistedVisitor;
+
+    impl<'de> Visitor<'de> for PersistedVisitor {
+        type Value = Operation;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an operation, optionally wrapped in `v` and `op` fields")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Operation, A::Error> {
+            let first: String = map
+                .next_key()?
+                .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+            if first != "v" && first != "op" {
+                let operation = Operation::deserialize(BareOperation {
+                    variant: first,
+                    map: &mut map,
+                })?;
+                if map.next_key::<IgnoredAny>()?.is_some() {
+                    return Err(A::Error::custom("expected a single operation"));
+                }
+                return Ok(operation);
+            }
+
+            let mut version = None;
+            let mut operation = None;
+            let mut key = Some(first);
+            while let Some(k) = key {
+                match k.as_str() {
+                    "v" if version.is_none() => version = Some(map.next_value::<u32>()?),
+                    "op" if operation.is_none() => operation = Some(map.next_value()?),
+                    "v" | "op" => return Err(A::Error::custom(format!("duplicate field `{k}`"))),
+                    _ => return Err(A::Error::unknown_field(&k, &["v", "op"])),
+                }
+                key = map.next_key()?;
+            }
+            let version = version.ok_or_else(|| A::Error::missing_field("v"))?;
+            let operation = operation.ok_or_else(|| A::Error::missing_field("op"))?;
+            if version != OPERATION_FORMAT_VERSION {
+                return Err(A::Error::custom(format!(
+                    "operation format version {version} is not supported, expected {OPERATION_FORMAT_VERSION}"
+                )));
+            }
+            Ok(operation)
+        }
+    }
+
+    // An unversioned entry is the externally tagged `Operation` itself, whose
+    // variant name has already been read as the first key of the map
+    struct BareOperation<'a, A> {
+        variant: String,
+        map: &'a mut A,
+    }
+
+    impl<'de, A: MapAccess<'de>> Deserializer<'de> for BareOperation<'_, A> {
+        type Error = A::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, A::Error> {
+            visitor.visit_enum(self)
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    impl<'de, A: MapAccess<'de>> EnumAccess<'de> for BareOperation<'_, A> {
+        type Error = A::Error;
+        type Variant = Self;
+
+        fn variant_seed<V: DeserializeSeed<'de>>(
+            self,
+            seed: V,
+        ) -> Result<(V::Value, Self), A::Error> {
+            let variant: StringDeserializer<A::Error> = self.variant.clone().into_deserializer();
+            Ok((seed.deserialize(variant)?, self))
+        }
+    }
+
+    impl<'de, A: MapAccess<'de>> VariantAccess<'de> for BareOperation<'_, A> {
+        type Error = A::Error;
+
+        fn unit_variant(self) -> Result<(), A::Error> {
+            Err(A::Error::invalid_type(
+                Unexpected::NewtypeVariant,
+                &"unit variant",
+            ))
+        }
+
+        fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+            self,
+            seed: T,
+        ) -> Result<T::Value, A::Error> {
+            self.map.next_value_seed(seed)
+        }
+
+        fn tuple_variant<V: Visitor<'de>>(
+            self,
+            _len: usize,
+            _visitor: V,
+        ) -> Result<V::Value, A::Error> {
+            Err(A::Error::invalid_type(
+                Unexpected::NewtypeVariant,
+                &"tuple variant",
+            ))
+        }
+
+        fn struct_variant<V: Visitor<'de>>(
+            self,
+            _fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, A::Error> {
+            Err(A::Error::invalid_type(
+                Unexpected::NewtypeVariant,
+                &"struct variant",
+            ))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -55,6 +55,22 @@ fn test_undo_ignore_for_project() {
     assert!(!store.is_ignored_for_project("foo"));
 }
 
+#[test]
+fn test_undo_skip() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let cargo_lock = relative_path("sub/Cargo.lock");
+    let mut operation = Operation::new_skip("Cargo.lock");
+    operation.execute(&mut store).unwrap();
+    assert!(store.is_skipped(&cargo_lock));
+    assert!(operation.is_applied(&store));
+
+    operation.undo(&mut store).unwrap();
+
+    assert!(!store.is_skipped(&cargo_lock));
+    assert!(!operation.is_applied(&store));
+}
+
 #[test]
 fn test_undo_ignore_for_lang() {
     let temp_dir = get_test_dir();
@@ -67,3 +83,237 @@ fn test_undo_ignore_for_lang() {
 
     assert!(!store.is_ignored_for_lang("foo", "en_US"));
 }
+
+#[test]
+fn test_undo_ignore_for_project_and_lang() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let mut operation = Operation::new_ignore_for_project_and_lang("colour", "en_GB");
+    operation.execute(&mut store).unwrap();
+    assert!(store.is_ignored_for_project_and_lang("colour", "en_GB"));
+
+    operation.undo(&mut store).unwrap();
+
+    assert!(!store.is_ignored_for_project_and_lang("colour", "en_GB"));
+}
+
+#[test]
+fn test_undo_ignore_identifier() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let mut global = Operation::new_ignore_identifier("XgboostClassifier");
+    let mut for_project = Operation::new_ignore_identifier_for_project("parse_tmpVar");
+    global.execute(&mut store).unwrap();
+    for_project.execute(&mut store).unwrap();
+    assert!(global.is_global());
+    assert!(!for_project.is_global());
+    assert!(store.is_ignored_identifier("XgboostClassifier"));
+    assert!(store.is_ignored_identifier_for_project("parse_tmpVar"));
+
+    global.undo(&mut store).unwrap();
+    for_project.undo(&mut store).unwrap();
+
+    assert!(store.ignored_identifiers().is_empty());
+}
+
+#[test]
+fn test_undo_remove() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    store.ignore("foo").unwrap();
+    store.ignore_for_project("foo").unwrap();
+    let mut operation = Operation::new_remove(store.operations_for("foo"));
+    assert_eq!(operation.scope(), "global, project");
+    operation.execute(&mut store).unwrap();
+    assert!(!store.is_ignored("foo"));
+    assert!(!store.is_ignored_for_project("foo"));
+
+    operation.undo(&mut store).unwrap();
+
+    assert!(store.is_ignored("foo"));
+    assert!(store.is_ignored_for_project("foo"));
+}
+
+#[test]
+fn test_undo_batch() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let mut operation = Operation::new_batch(vec![
+        Operation::new_ignore("foo"),
+        Operation::new_ignore_for_project("bar"),
+    ]);
+    assert_eq!(operation.word(), "foo, bar");
+    assert_eq!(operation.scope(), "global, project");
+    operation.execute(&mut store).unwrap();
+    assert!(store.is_ignored("foo"));
+    assert!(store.is_ignored_for_project("bar"));
+
+    operation.undo(&mut store).unwrap();
+
+    assert!(!store.is_ignored("foo"));
+    assert!(!store.is_ignored_for_project("bar"));
+}
+
+#[test]
+fn test_failed_remove_leaves_store_unchanged() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    store.ignore("foo").unwrap();
+    store.ignore_for_project("foo").unwrap();
+    let mut operation = Operation::new_remove(store.operations_for("foo"));
+    // Edited by hand since the operation was created
+    store.remove_ignored_for_project("foo").unwrap();
+
+    operation.execute(&mut store).unwrap_err();
+
+    assert!(store.is_ignored("foo"));
+}
+
+#[test]
+fn test_failed_batch_undo_leaves_store_unchanged() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let mut operation = Operation::new_batch(vec![
+        Operation::new_ignore_for_project("bar"),
+        Operation::new_ignore("foo"),
+    ]);
+    operation.execute(&mut store).unwrap();
+    store.remove_ignored_for_project("bar").unwrap();
+
+    // "foo" is removed first, then "bar" cannot be
+    operation.undo(&mut store).unwrap_err();
+
+    assert!(store.is_ignored("foo"));
+    assert!(!store.is_ignored_for_project("bar"));
+}
+
+#[test]
+fn test_ensure_applied() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    let mut operation = Operation::new_ignore_for_project("foo");
+    operation.execute(&mut store).unwrap();
+    operation.ensure_applied(&store).unwrap();
+
+    store.remove_ignored_for_project("foo").unwrap();
+
+    let err = operation.ensure_applied(&store).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(SkyspellError::OperationConflict(_))
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Cannot undo the last operation: 'foo' is no longer in the project ignore list"
+    );
+}
+
+#[test]
+fn test_ensure_applied_for_removal() {
+    let temp_dir = get_test_dir();
+    let mut store = get_empty_store(&temp_dir);
+    store.ignore("foo").unwrap();
+    let mut operation = Operation::new_remove(store.operations_for("foo"));
+    operation.execute(&mut store).unwrap();
+    operation.ensure_applied(&store).unwrap();
+
+    store.ignore("foo").unwrap();
+
+    let err = operation.ensure_applied(&store).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cannot undo the last operation: 'foo' was added back to the global ignore list"
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Row {
+    #[serde(with = "versioned")]
+    operation: Operation,
+}
+
+// One operation of each variant, compound ones included. The match has no
+// wildcard so that adding a variant breaks the build until it is added here
+fn one_of_each_variant() -> Vec<Operation> {
+    let foo_py = relative_path("foo.py");
+    let operations = vec![
+        Operation::new_ignore("foo"),
+        Operation::new_ignore_for_extension("foo", "py"),
+        Operation::new_ignore_for_path("foo", &foo_py),
+        Operation::new_ignore_for_project("foo"),
+        Operation::new_ignore_for_project_in_category("foo", "names"),
+        Operation::new_ignore_for_lang("foo", "fr"),
+        Operation::new_ignore_for_project_and_lang("foo", "fr"),
+        Operation::new_ignore_identifier("XgboostClassifier"),
+        Operation::new_ignore_identifier_for_project("XgboostClassifier"),
+        Operation::new_skip("*.lock"),
+        Operation::new_remove(vec![
+            Operation::new_ignore("foo"),
+            Operation::new_ignore_for_path("foo", &foo_py),
+        ]),
+        Operation::new_batch(vec![
+            Operation::new_ignore("foo"),
+            Operation::new_remove(vec![Operation::new_ignore_for_lang("bar", "fr")]),
+        ]),
+    ];
+    for operation in &operations {
+        match operation {
+            Operation::Ignore(_)
+            | Operation::IgnoreForExtension(_)
+            | Operation::IgnoreForPath(_)
+            | Operation::IgnoreForProject(_)
+            | Operation::IgnoreForLang(_)
+            | Operation::IgnoreForProjectAndLang(_)
+            | Operation::IgnoreIdentifier(_)
+            | Operation::IgnoreIdentifierForProject(_)
+            | Operation::Skip(_)
+            | Operation::Remove(_)
+            | Operation::Batch(_) => (),
+        }
+    }
+    operations
+}
+
+#[test]
+fn test_versioned_round_trip_of_every_variant() {
+    for operation in one_of_each_variant() {
+        let row = Row {
+            operation: operation.clone(),
+        };
+        let contents = toml_edit::ser::to_string_pretty(&row).unwrap();
+        assert!(contents.contains("v = 1"), "{contents}");
+
+        let read: Row = toml::from_str(&contents).unwrap();
+        assert_eq!(read.operation, operation, "{contents}");
+    }
+}
+
+#[test]
+fn test_read_unversioned_operation() {
+    let contents = r#"
+    [operation.IgnoreForLang]
+    word = "foo"
+    lang = "fr"
+    "#;
+
+    let row: Row = toml::from_str(contents).unwrap();
+
+    assert_eq!(row.operation, Operation::new_ignore_for_lang("foo", "fr"));
+}
+
+#[test]
+fn test_reject_unsupported_operation_format_version() {
+    let contents = r#"
+    [operation]
+    v = 2
+    [operation.op.Ignore]
+    word = "foo"
+    "#;
+
+    let err = toml::from_str::<Row>(contents).unwrap_err();
+
+    assert!(
+        err.to_string().contains("version 2 is not supported"),
+        "{err}"
+    );
+}
@@ -0,0 +1,71 @@
+//! Files listing operations on the ignore lists, so that a team can
+//! review them and apply them to every checkout of the project
+//!
+//! The operations use the same representation as the history in the
+//! state file, with the version of the format in each entry - since the
+//! file is only appended to, see `operations::versioned`:
+//!
+//! ```toml
+//! [[operations]]
+//! v = 1
+//! op = { Ignore = { word = "foo" } }
+//!
+//! [[operations]]
+//! v = 1
+//! op = { IgnoreForExtension = { word = "kwargs", extension = "py" } }
+//! ```
+//!
+//! Entries without a version, like `Skip = { pattern = "Cargo.lock" }`,
+//! are read as version 1
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::Operation;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OpsFile {
+    #[serde(default)]
+    operations: Vec<OpsEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+struct OpsEntry(#[serde(with = "crate::operations::versioned")] Operation);
+
+pub fn load_ops_file(path: &Path) -> Result<Vec<Operation>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read from {}", path.display()))?;
+    parse_ops(&contents).with_context(|| format!("Could not parse {}", path.display()))
+}
+
+// The toml error contains the line and column of the problem
+fn parse_ops(contents: &str) -> Result<Vec<Operation>> {
+    let ops_file: OpsFile = toml::from_str(contents)?;
+    Ok(ops_file.operations.into_iter().map(|e| e.0).collect())
+}
+
+/// Append the operation at the end of the file, creating it if needed.
+/// The existing contents are never rewritten
+pub fn append_to_ops_file(path: &Path, operation: &Operation) -> Result<()> {
+    let ops_file = OpsFile {
+        operations: vec![OpsEntry(operation.clone())],
+    };
+    let mut text = toml::to_string(&ops_file).expect("operations should be serializable");
+    let is_empty = std::fs::metadata(path).map_or(true, |m| m.len() == 0);
+    if !is_empty {
+        text.insert(0, '\n');
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Could not open {}", path.display()))?;
+    file.write_all(text.as_bytes())
+        .with_context(|| format!("Could not write to {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,125 @@
+use crate::tests::{get_test_dir, relative_path};
+
+use super::*;
+
+#[test]
+fn test_parse_ops() {
+    let contents = r#"
+    [[operations]]
+    Ignore = { word = "foo" }
+
+    [[operations]]
+    IgnoreForExtension = { word = "kwargs", extension = "py" }
+
+    [[operations]]
+    IgnoreForPath = { word = "fooo", path = "docs/notes.md" }
+
+    [[operations]]
+    Skip = { pattern = "Cargo.lock" }
+
+    [[operations]]
+    Remove = { removed = [{ IgnoreForProject = { word = "baz" } }] }
+    "#;
+
+    let operations = parse_ops(contents).unwrap();
+
+    assert_eq!(
+        operations,
+        [
+            Operation::new_ignore("foo"),
+            Operation::new_ignore_for_extension("kwargs", "py"),
+            Operation::new_ignore_for_path("fooo", &relative_path("docs/notes.md")),
+            Operation::new_skip("Cargo.lock"),
+            Operation::new_remove(vec![Operation::new_ignore_for_project("baz")]),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_empty_ops_file() {
+    assert!(parse_ops("").unwrap().is_empty());
+}
+
+#[test]
+fn test_parse_error_has_a_location() {
+    let contents = r#"
+    [[operations]]
+    Ignore = { word = "foo" }
+
+    [[operations]]
+    Ignore = { wrd = "bar" }
+    "#;
+
+    let message = parse_ops(contents).unwrap_err().to_string();
+
+    assert!(message.contains("line 6"), "{message}");
+}
+
+#[test]
+fn test_append_to_ops_file() {
+    let temp_dir = get_test_dir();
+    let ops_toml = temp_dir.path().join("ops.toml");
+    let operations = [
+        Operation::new_ignore("foo"),
+        Operation::new_remove(vec![Operation::new_ignore_for_lang("bar", "fr")]),
+        Operation::new_batch(vec![
+            Operation::new_ignore_for_project("baz"),
+            Operation::new_skip("yarn.lock"),
+        ]),
+    ];
+
+    for operation in &operations {
+        append_to_ops_file(&ops_toml, operation).unwrap();
+    }
+
+    assert_eq!(load_ops_file(&ops_toml).unwrap(), operations);
+}
+
+#[test]
+fn test_appended_operations_are_versioned() {
+    let temp_dir = get_test_dir();
+    let ops_toml = temp_dir.path().join("ops.toml");
+    std::fs::write(&ops_toml, "[[operations]]\nIgnore = { word = \"foo\" }\n").unwrap();
+
+    append_to_ops_file(&ops_toml, &Operation::new_ignore("bar")).unwrap();
+
+    let contents = std::fs::read_to_string(&ops_toml).unwrap();
+    assert_eq!(contents.matches("v = 1").count(), 1, "{contents}");
+    assert_eq!(
+        load_ops_file(&ops_toml).unwrap(),
+        [Operation::new_ignore("foo"), Operation::new_ignore("bar")]
+    );
+}
+
+#[test]
+fn test_reject_unsupported_ops_format_version() {
+    let contents = r#"
+    [[operations]]
+    v = 2
+    op = { Ignore = { word = "foo" } }
+    "#;
+
+    let err = parse_ops(contents).unwrap_err();
+
+    assert!(
+        err.to_string().contains("version 2 is not supported"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_versioned_parse_error_has_a_location() {
+    let contents = r#"
+    [[operations]]
+    v = 1
+    op = { Ignore = { word = "foo" } }
+
+    [[operations]]
+    v = 1
+    op = { Ignore = { wrd = "bar" } }
+    "#;
+
+    let message = parse_ops(contents).unwrap_err().to_string();
+
+    assert!(message.contains("line 8"), "{message}");
+}
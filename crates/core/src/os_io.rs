@@ -1,8 +1,15 @@
-use anyhow::{anyhow, Result};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
 
 pub trait OperatingSystemIO {
     fn get_env_var(&self, key: &str) -> Result<String>;
     fn print(&self, text: &str);
+    /// Write `contents` to a new file that only the current user can
+    /// read, and return its path. It is not removed automatically
+    fn write_temp_file(&self, contents: &str) -> Result<PathBuf>;
+    fn remove_file(&self, path: &Path) -> Result<()>;
 }
 
 #[derive(Copy, Clone)]
@@ -16,4 +23,23 @@ impl OperatingSystemIO for StandardIO {
     fn print(&self, text: &str) {
         print!("{}", text);
     }
+
+    fn write_temp_file(&self, contents: &str) -> Result<PathBuf> {
+        // Temporary files are created with 0600 permissions on Unix
+        let mut file = tempfile::Builder::new()
+            .prefix("skyspell-")
+            .tempfile()
+            .context("Could not create temporary file")?;
+        file.write_all(contents.as_bytes())
+            .with_context(|| format!("Could not write to {}", file.path().display()))?;
+        let (_, path) = file.keep().context("Could not keep temporary file")?;
+        Ok(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        std::fs::remove_file(path).with_context(|| format!("Could not remove {}", path.display()))
+    }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn test_write_temp_file() {
+    let io = StandardIO;
+    let path = io.write_temp_file("echo hello\n").unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "echo hello\n");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    io.remove_file(&path).unwrap();
+    assert!(!path.exists());
+}
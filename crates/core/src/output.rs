@@ -0,0 +1,161 @@
+//! Messages printed by the binaries, filtered by a process-wide verbosity
+//! set from their `--quiet` and `-v` flags
+use std::cell::RefCell;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only results and errors
+    Quiet,
+    #[default]
+    Normal,
+    /// Also explain what was skipped, and show the output of hooks
+    Verbose,
+    /// Also list every checked file
+    Debug,
+}
+
+impl Verbosity {
+    /// From the `--quiet` flag and the number of `-v` flags
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::Debug,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Verbosity::Quiet,
+            1 => Verbosity::Normal,
+            2 => Verbosity::Verbose,
+            _ => Verbosity::Debug,
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Captured>> = const { RefCell::new(None) };
+}
+
+/// What was printed during capture()
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Captured {
+    pub stdout: String,
+    pub stderr: String,
+    verbosity: Verbosity,
+}
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    let captured = CAPTURED.with(|c| c.borrow().as_ref().map(|c| c.verbosity));
+    captured.unwrap_or_else(|| Verbosity::from_u8(VERBOSITY.load(Ordering::Relaxed)))
+}
+
+/// Run `f` with the given verbosity, and return what it printed instead
+/// of printing it. Only affects the current thread, so that tests can
+/// run in parallel
+pub fn capture(verbosity: Verbosity, f: impl FnOnce()) -> Captured {
+    CAPTURED.with(|c| {
+        *c.borrow_mut() = Some(Captured {
+            verbosity,
+            ..Default::default()
+        })
+    });
+    f();
+    CAPTURED
+        .with(|c| c.borrow_mut().take())
+        .expect("capture() should not be nested")
+}
+
+// Append to the captured output if any, print otherwise
+fn write(message: &str, to_stderr: bool) {
+    let captured = CAPTURED.with(|c| {
+        let mut captured = c.borrow_mut();
+        let captured = match captured.as_mut() {
+            Some(captured) => captured,
+            None => return false,
+        };
+        let out = match to_stderr {
+            true => &mut captured.stderr,
+            false => &mut captured.stdout,
+        };
+        out.push_str(message);
+        out.push('\n');
+        true
+    });
+    match (captured, to_stderr) {
+        (true, _) => (),
+        (false, false) => println!("{message}"),
+        (false, true) => eprintln!("{message}"),
+    }
+}
+
+/// The first of stdin and stdout that is not a terminal, if any. Captured
+/// output never goes to a terminal
+pub fn non_terminal_stream() -> Option<&'static str> {
+    let captured = CAPTURED.with(|c| c.borrow().is_some());
+    if !std::io::stdin().is_terminal() {
+        Some("stdin")
+    } else if captured || !std::io::stdout().is_terminal() {
+        Some("stdout")
+    } else {
+        None
+    }
+}
+
+/// Results, like spelling errors, are printed on stdout whatever the verbosity
+pub fn print(message: &str) {
+    write(message, false);
+}
+
+/// Progress and summaries, printed on stdout unless quiet
+pub fn info(message: &str) {
+    if verbosity() > Verbosity::Quiet {
+        write(message, false);
+    }
+}
+
+/// Printed on stderr with -v
+pub fn verbose(message: &str) {
+    if verbosity() >= Verbosity::Verbose {
+        write(message, true);
+    }
+}
+
+/// Printed on stderr with -vv
+pub fn debug(message: &str) {
+    if verbosity() >= Verbosity::Debug {
+        write(message, true);
+    }
+}
+
+/// Things the user should know about, printed on stderr unless quiet
+pub fn note(message: &str) {
+    if verbosity() > Verbosity::Quiet {
+        write(message, true);
+    }
+}
+
+/// Printed on stderr unless quiet
+pub fn warning(message: &str) {
+    if verbosity() > Verbosity::Quiet {
+        write(message, true);
+    }
+}
+
+/// Printed on stderr whatever the verbosity
+pub fn error(message: &str) {
+    write(message, true);
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,43 @@
+use super::*;
+
+fn print_all() {
+    print("result");
+    info("info");
+    verbose("verbose");
+    debug("debug");
+    note("note");
+    warning("warning");
+    error("error");
+}
+
+#[test]
+fn test_verbosity_from_flags() {
+    assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+    assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Verbose);
+    assert_eq!(Verbosity::from_flags(false, 3), Verbosity::Debug);
+    assert_eq!(Verbosity::from_flags(true, 0), Verbosity::Quiet);
+}
+
+#[test]
+fn test_quiet_only_prints_results_and_errors() {
+    let captured = capture(Verbosity::Quiet, print_all);
+
+    assert_eq!(captured.stdout, "result\n");
+    assert_eq!(captured.stderr, "error\n");
+}
+
+#[test]
+fn test_normal_verbosity() {
+    let captured = capture(Verbosity::Normal, print_all);
+
+    assert_eq!(captured.stdout, "result\ninfo\n");
+    assert_eq!(captured.stderr, "note\nwarning\nerror\n");
+}
+
+#[test]
+fn test_debug_verbosity() {
+    let captured = capture(Verbosity::Debug, print_all);
+
+    assert_eq!(captured.stdout, "result\ninfo\n");
+    assert_eq!(captured.stderr, "verbose\ndebug\nnote\nwarning\nerror\n");
+}
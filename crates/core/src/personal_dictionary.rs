@@ -0,0 +1,140 @@
+//! Personal dictionaries maintained with aspell, hunspell or Enchant,
+//! used as a read-only list of ignored words
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use directories_next::BaseDirs;
+
+/// Words of a personal dictionary file, see IgnoreStore::set_personal_dictionary().
+/// Loaded once per run, and never written to
+#[derive(Debug, Clone, Default)]
+pub struct PersonalDictionary {
+    path: PathBuf,
+    words: HashSet<String>,
+    // The words starting with `*` in hunspell dictionaries
+    forbidden: HashSet<String>,
+}
+
+impl PersonalDictionary {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Could not read personal dictionary {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            words: parse_personal_dictionary(&bytes),
+            forbidden: parse_forbidden_words(&bytes),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Like the providers, accept a lower case word at the
+    /// start of a sentence
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word) || self.words.contains(&word.to_lowercase())
+    }
+
+    /// Words that must never be suggested as replacements
+    pub fn forbidden_words(&self) -> &HashSet<String> {
+        &self.forbidden
+    }
+
+    /// The first existing personal dictionary for `lang`, in the places
+    /// used by the given provider - or by any of them, for Enchant
+    pub fn find(provider: Option<&str>, lang: &str) -> Option<PathBuf> {
+        let base_dirs = BaseDirs::new()?;
+        candidates(&base_dirs, provider, lang)
+            .into_iter()
+            .find(|p| p.is_file())
+    }
+}
+
+fn candidates(base_dirs: &BaseDirs, provider: Option<&str>, lang: &str) -> Vec<PathBuf> {
+    let home = base_dirs.home_dir();
+    let short_lang = lang.split(['_', '-']).next().unwrap_or(lang);
+    let aspell = [
+        home.join(format!(".aspell.{lang}.pws")),
+        home.join(format!(".aspell.{short_lang}.pws")),
+    ];
+    let hunspell = [home.join(format!(".hunspell_{lang}"))];
+    let enchant = [
+        base_dirs
+            .config_dir()
+            .join("enchant")
+            .join(format!("{lang}.dic")),
+        base_dirs
+            .config_dir()
+            .join("enchant")
+            .join(format!("{short_lang}.dic")),
+    ];
+    match provider {
+        Some("aspell") => aspell.to_vec(),
+        Some("hunspell") | Some("nuspell") => hunspell.to_vec(),
+        _ => enchant.into_iter().chain(aspell).chain(hunspell).collect(),
+    }
+}
+
+/// Parse the personal dictionary formats of the providers:
+///  - aspell: a `personal_ws-1.1 en 2 utf-8` header, then one word per line
+///  - hunspell: one word per line, optionally followed by `/` and an
+///    affix model. Words starting with `*` are forbidden, and skipped
+///  - Enchant: one word per line
+pub fn parse_personal_dictionary(bytes: &[u8]) -> HashSet<String> {
+    entries(bytes)
+        .into_iter()
+        .filter(|e| !e.starts_with('*'))
+        .collect()
+}
+
+/// The forbidden words of a hunspell personal dictionary, without their `*`
+pub fn parse_forbidden_words(bytes: &[u8]) -> HashSet<String> {
+    entries(bytes)
+        .into_iter()
+        .filter_map(|e| e.strip_prefix('*').map(|w| w.to_string()))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+// The words of the dictionary, without their affix model
+fn entries(bytes: &[u8]) -> Vec<String> {
+    let contents = decode(bytes);
+    let mut lines = contents.lines().peekable();
+    // Some tools write the number of words on the first line, like in .dic files
+    if lines
+        .peek()
+        .is_some_and(|l| l.starts_with("personal_ws-") || l.trim().parse::<usize>().is_ok())
+    {
+        lines.next();
+    }
+    lines
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.split_once('/').map_or(l, |(word, _)| word).to_string())
+        .collect()
+}
+
+// aspell writes the encoding in the header, and it's not always utf-8
+fn decode(bytes: &[u8]) -> String {
+    let header = bytes.split(|b| *b == b'\n').next().unwrap_or_default();
+    let header = String::from_utf8_lossy(header).to_lowercase();
+    let is_latin1 = header.starts_with("personal_ws-")
+        && (header.contains("iso-8859-1") || header.contains("iso8859-1"));
+    match is_latin1 {
+        true => bytes.iter().map(|b| *b as char).collect(),
+        false => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,71 @@
+use super::*;
+use crate::tests::get_test_dir;
+
+fn sorted(words: HashSet<String>) -> Vec<String> {
+    let mut res: Vec<_> = words.into_iter().collect();
+    res.sort();
+    res
+}
+
+#[test]
+fn test_aspell_header_is_skipped() {
+    let words = parse_personal_dictionary(include_bytes!("fixtures/aspell.en.pws"));
+
+    assert_eq!(sorted(words), ["Kakoune", "café", "rustfmt", "skyspell"]);
+}
+
+#[test]
+fn test_aspell_latin1_encoding() {
+    let words = parse_personal_dictionary(include_bytes!("fixtures/latin1.pws"));
+
+    assert_eq!(sorted(words), ["déjàvu", "naïve"]);
+}
+
+#[test]
+fn test_hunspell_affixes_and_forbidden_words() {
+    let words = parse_personal_dictionary(include_bytes!("fixtures/hunspell_en_US"));
+
+    assert_eq!(sorted(words), ["frobnicate", "naïveté", "skyspell"]);
+}
+
+#[test]
+fn test_hunspell_forbidden_words() {
+    let words = parse_forbidden_words(include_bytes!("fixtures/hunspell_en_US"));
+
+    assert_eq!(sorted(words), ["teh"]);
+}
+
+#[test]
+fn test_word_count_on_the_first_line() {
+    let words = parse_personal_dictionary(b"2\nfoo\nbar\n");
+
+    assert_eq!(sorted(words), ["bar", "foo"]);
+}
+
+#[test]
+fn test_contains() {
+    let temp_dir = get_test_dir();
+    let path = temp_dir.path().join(".aspell.en.pws");
+    std::fs::write(&path, include_bytes!("fixtures/aspell.en.pws")).unwrap();
+
+    let dictionary = PersonalDictionary::load(&path).unwrap();
+
+    assert_eq!(dictionary.path(), path);
+    assert!(dictionary.contains("skyspell"));
+    assert!(dictionary.contains("Skyspell"));
+    assert!(dictionary.contains("Kakoune"));
+    assert!(!dictionary.contains("kakoune"));
+    assert!(!dictionary.contains("personal_ws"));
+}
+
+#[test]
+fn test_missing_file() {
+    let temp_dir = get_test_dir();
+    let path = temp_dir.path().join("nope.pws");
+
+    let err = PersonalDictionary::load(&path).unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("Could not read personal dictionary"));
+}
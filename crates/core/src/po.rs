@@ -0,0 +1,160 @@
+//! Find the translations in gettext catalogs, so that only the msgstr
+//! strings of .po and .pot files are checked - the msgid strings are
+//! in the source language
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::file_lang::valid_lang;
+
+/// Value of the `po` table in the local config
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PoOptions {
+    /// Don't check the entries flagged as fuzzy
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_fuzzy: bool,
+    /// Don't check the obsolete entries, the ones starting with `#~`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_obsolete: bool,
+}
+
+impl PoOptions {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+pub fn is_po_file(file_name: &str) -> bool {
+    matches!(file_name.rsplit_once('.'), Some((_, "po" | "pot")))
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    #[default]
+    None,
+    Msgid,
+    Msgstr,
+    // msgctxt and msgid_plural
+    Other,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    field: Field,
+    fuzzy: bool,
+    // The msgstr of the entry with an empty msgid is the catalog header
+    has_msgid: bool,
+}
+
+/// The byte ranges of the msgstr strings in `text`, line by line, like
+/// string_values(). Escape sequences are left out, and so is the header
+pub fn msgstr_ranges(text: &str, options: PoOptions) -> Vec<Vec<Range<usize>>> {
+    let mut res = vec![];
+    let mut entry = Entry::default();
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            entry = Entry::default();
+            res.push(vec![]);
+            continue;
+        }
+        let (obsolete, content) = match trimmed.strip_prefix("#~") {
+            // `#~|` is the previous msgid of an obsolete entry
+            Some(rest) if !rest.starts_with('|') => (true, rest.trim_start()),
+            _ => (false, trimmed),
+        };
+        let keyword = content.split('"').next().unwrap_or_default().trim();
+        let field = if content.starts_with('#') {
+            None
+        } else if keyword.is_empty() {
+            // Continuation of the previous string
+            Some(Field::None)
+        } else if keyword.starts_with("msgstr") {
+            Some(Field::Msgstr)
+        } else if keyword == "msgid" {
+            Some(Field::Msgid)
+        } else {
+            Some(Field::Other)
+        };
+        // Comments or keywords after a msgstr start the next entry,
+        // even without a blank line
+        let starts_entry = !matches!(field, Some(Field::None) | Some(Field::Msgstr));
+        if entry.field == Field::Msgstr && starts_entry {
+            entry = Entry::default();
+        }
+        let field = match field {
+            None => {
+                if content.starts_with("#,") && content.contains("fuzzy") {
+                    entry.fuzzy = true;
+                }
+                res.push(vec![]);
+                continue;
+            }
+            Some(Field::None) => entry.field,
+            Some(field) => {
+                entry.field = field;
+                field
+            }
+        };
+        let start = line.len() - line.trim_start().len() + (trimmed.len() - content.len());
+        let ranges = string_ranges(line, start);
+        match field {
+            Field::Msgid => entry.has_msgid |= !ranges.is_empty(),
+            Field::Msgstr => {
+                let skipped = !entry.has_msgid
+                    || (entry.fuzzy && options.skip_fuzzy)
+                    || (obsolete && options.skip_obsolete);
+                if !skipped {
+                    res.push(ranges);
+                    continue;
+                }
+            }
+            _ => (),
+        }
+        res.push(vec![]);
+    }
+    res
+}
+
+// The contents of the first string of `line` after `start`, without
+// the escape sequences
+fn string_ranges(line: &str, start: usize) -> Vec<Range<usize>> {
+    let bytes = line.as_bytes();
+    let open = match line[start..].find('"') {
+        Some(i) => start + i + 1,
+        None => return vec![],
+    };
+    let mut res = vec![];
+    let mut range_start = open;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => {
+                if range_start < i {
+                    res.push(range_start..i);
+                }
+                i += 2;
+                range_start = i;
+            }
+            b'"' => break,
+            _ => i += 1,
+        }
+    }
+    let end = i.min(bytes.len());
+    if range_start < end {
+        res.push(range_start..end);
+    }
+    res
+}
+
+/// The value of the `Language:` field of the catalog header, if any
+pub fn po_language(lines: &[&str]) -> Option<String> {
+    lines.iter().find_map(|line| {
+        let value = line.trim().strip_prefix("\"Language:")?;
+        let value = value.trim_end_matches('"').trim_end_matches("\\n");
+        valid_lang(value.trim())
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,115 @@
+use super::*;
+use crate::TokenProcessor;
+
+const FR_PO: &str = include_str!("fixtures/fr.po");
+
+// Tokens with their line and column in chars
+fn msgstr_tokens(contents: &str, options: PoOptions) -> Vec<(String, usize, usize)> {
+    let mut processor = TokenProcessor::new(contents.as_bytes(), "fr.po");
+    processor.set_string_values(msgstr_ranges(contents, options));
+    processor
+        .map(|token| {
+            let token = token.unwrap();
+            (token.text, token.pos.0, token.columns.char)
+        })
+        .collect()
+}
+
+fn words(tokens: &[(String, usize, usize)]) -> Vec<&str> {
+    tokens.iter().map(|(text, _, _)| text.as_str()).collect()
+}
+
+#[test]
+fn test_is_po_file() {
+    assert!(is_po_file("fr.po"));
+    assert!(is_po_file("messages.pot"));
+    assert!(!is_po_file("po"));
+    assert!(!is_po_file("README.md"));
+}
+
+#[test]
+fn test_only_msgstr_is_checked() {
+    let actual = msgstr_tokens(FR_PO, PoOptions::default());
+
+    let actual: Vec<_> = actual
+        .iter()
+        .map(|(text, line, column)| (text.as_str(), *line, *column))
+        .collect();
+    assert_eq!(
+        actual,
+        [
+            ("Bonjour", 10, 8),
+            ("le", 10, 16),
+            ("monde", 10, 19),
+            ("Un", 16, 1),
+            ("long", 16, 4),
+            ("mesage", 16, 9),
+            ("sur", 17, 1),
+            ("deux", 17, 5),
+            ("lignes", 17, 10),
+            ("Un", 21, 11),
+            ("fichier", 21, 14),
+            ("Des", 22, 11),
+            ("fichiers", 22, 15),
+            ("Anuler", 26, 8),
+            ("Vieux", 29, 11),
+        ]
+    );
+}
+
+#[test]
+fn test_skip_fuzzy_entries() {
+    let options = PoOptions {
+        skip_fuzzy: true,
+        ..Default::default()
+    };
+
+    let actual = msgstr_tokens(FR_PO, options);
+
+    assert!(!words(&actual).contains(&"Anuler"));
+    assert!(words(&actual).contains(&"Vieux"));
+}
+
+#[test]
+fn test_skip_obsolete_entries() {
+    let options = PoOptions {
+        skip_obsolete: true,
+        ..Default::default()
+    };
+
+    let actual = msgstr_tokens(FR_PO, options);
+
+    assert!(words(&actual).contains(&"Anuler"));
+    assert!(!words(&actual).contains(&"Vieux"));
+}
+
+#[test]
+fn test_entries_without_blank_lines() {
+    let contents = "msgid \"Yes\"\nmsgstr \"Oui\"\n#, fuzzy\nmsgid \"No\"\nmsgstr \"Non\"\nmsgid \"Maybe\"\nmsgstr \"Sans doute\"\n";
+    let options = PoOptions {
+        skip_fuzzy: true,
+        ..Default::default()
+    };
+
+    let actual = msgstr_tokens(contents, options);
+
+    assert_eq!(words(&actual), ["Oui", "Sans", "doute"]);
+}
+
+#[test]
+fn test_escaped_quotes() {
+    let contents = "msgid \"Say \\\"hi\\\"\"\nmsgstr \"Dites \\\"salut\\\"\"\n";
+
+    let actual = msgstr_tokens(contents, PoOptions::default());
+
+    assert_eq!(words(&actual), ["Dites", "salut"]);
+}
+
+#[test]
+fn test_language_header() {
+    let lines: Vec<_> = FR_PO.lines().collect();
+
+    assert_eq!(po_language(&lines), Some("fr".to_string()));
+    assert_eq!(po_language(&["msgid \"\"", "msgstr \"\""]), None);
+    assert_eq!(po_language(&["\"Language: \\n\""]), None);
+}
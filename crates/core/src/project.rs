@@ -1,42 +1,194 @@
 use std::borrow::Cow;
+use std::collections::BTreeSet;
 use std::fmt::Display;
 use std::path::{Path, PathBuf};
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use ignore::{Walk, WalkBuilder};
 use serde::{Deserialize, Serialize};
 
-use crate::{global_path, IgnoreStore, SkipFile};
+use crate::{global_path, IgnoreStore, LocalIgnore, SkipFile, SkyspellError};
 
 pub const SKYSPELL_LOCAL_IGNORE: &str = "skyspell-ignore.toml";
 
+/// Files larger than this are not checked, unless configured otherwise
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Used in extension filters to match files without an extension
+pub const NO_EXTENSION: &str = "none";
+
+/// What a project root contains, by order of priority
+const PROJECT_MARKERS: [&[&str]; 3] = [&[SKYSPELL_LOCAL_IGNORE], &[".git"], &[".hg", ".svn"]];
+
+/// The closest ancestor of `start` (or `start` itself) containing a
+/// skyspell-ignore.toml file - or else a .git, or else a .hg or .svn
+/// directory
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    PROJECT_MARKERS.iter().find_map(|markers| {
+        start
+            .ancestors()
+            .find(|dir| markers.iter().any(|marker| dir.join(marker).exists()))
+            .map(Path::to_path_buf)
+    })
+}
+
+/// Parse a human-friendly file size, like `500`, `20kB` or `5 MB`.
+/// Units are powers of 1024
+pub fn parse_file_size(text: &str) -> Result<u64> {
+    let text = text.trim();
+    let index = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (value, unit) = text.split_at(index);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| anyhow!("Could not parse '{text}' as a file size"))?;
+    let multiplier: u64 = match unit.trim().to_lowercase().as_ref() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        _ => bail!("Unknown unit in file size: '{text}'"),
+    };
+    value
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow!("File size too big: '{text}'"))
+}
+
 #[derive(Debug, Clone)]
 pub struct Project {
     path: ProjectPath,
     skip_file: SkipFile,
+    extra_roots: Vec<PathBuf>,
+    check_paths: Vec<PathBuf>,
+    max_file_size: u64,
+    extensions: Option<BTreeSet<String>>,
 }
 
 impl Project {
     pub fn new(path: &Path) -> Result<Self> {
-        let skip_file = SkipFile::new(path)?;
+        let local = LocalIgnore::load(&path.join(SKYSPELL_LOCAL_IGNORE))?;
+        Self::with_local_ignore(path, &local)
+    }
+
+    /// Like new(), but without reading the local ignore file - used when it's broken
+    pub fn new_without_local_ignore(path: &Path) -> Result<Self> {
+        Self::with_local_ignore(path, &LocalIgnore::default())
+    }
+
+    fn with_local_ignore(path: &Path, local: &LocalIgnore) -> Result<Self> {
+        let skip_file = SkipFile::new(path, local)?;
         let path = ProjectPath::new(path)?;
-        Ok(Self { path, skip_file })
+        let max_file_size = match &local.max_file_size {
+            Some(s) => parse_file_size(s)?,
+            None => DEFAULT_MAX_FILE_SIZE,
+        };
+        let check_paths = local
+            .check_paths
+            .iter()
+            .map(|p| path.as_ref().join(p))
+            .collect();
+        let mut project = Self {
+            path,
+            skip_file,
+            extra_roots: vec![],
+            check_paths,
+            max_file_size,
+            extensions: None,
+        };
+        for extra_path in &local.extra_paths {
+            let extra_root = project.path.as_ref().join(extra_path);
+            project.add_extra_root(&extra_root)?;
+        }
+        Ok(project)
+    }
+
+    /// Add a directory outside the project that should be checked
+    /// using the project's ignore rules
+    pub fn add_extra_root(&mut self, path: &Path) -> Result<()> {
+        let path = std::fs::canonicalize(path)
+            .with_context(|| format!("Could not canonicalize extra path: {}", path.display()))?;
+        if !self.extra_roots.contains(&path) {
+            self.extra_roots.push(path);
+        }
+        Ok(())
+    }
+
+    pub fn extra_roots(&self) -> &[PathBuf] {
+        &self.extra_roots
+    }
+
+    /// What to check when no path is given, instead of the whole project,
+    /// from `check_paths` in the local config. They may not exist
+    pub fn check_paths(&self) -> &[PathBuf] {
+        &self.check_paths
+    }
+
+    /// Files larger than this size (in bytes) are skipped. 0 means no limit
+    pub fn max_file_size(&self) -> u64 {
+        self.max_file_size
+    }
+
+    pub fn set_max_file_size(&mut self, max_file_size: u64) {
+        self.max_file_size = max_file_size
+    }
+
+    /// Only check files with one of these extensions ('md' or '.md'),
+    /// or with no extension at all if NO_EXTENSION is in the list
+    pub fn set_extensions(&mut self, extensions: &[String]) {
+        let extensions = extensions
+            .iter()
+            .map(|e| e.trim().trim_start_matches('.').to_string())
+            .collect();
+        self.extensions = Some(extensions)
+    }
+
+    pub fn has_checked_extension(&self, relative_path: &RelativePath) -> bool {
+        self.is_checked_extension(relative_path.extension().as_deref())
+    }
+
+    /// Like has_checked_extension(), for a file assumed to have this
+    /// extension - None for no extension
+    pub fn is_checked_extension(&self, extension: Option<&str>) -> bool {
+        let extensions = match &self.extensions {
+            None => return true,
+            Some(e) => e,
+        };
+        match extension {
+            None => extensions.contains(NO_EXTENSION),
+            Some(e) => extensions.contains(e),
+        }
     }
 
     pub fn path(&self) -> &ProjectPath {
         &self.path
     }
 
-    pub fn as_str(&self) -> Cow<str> {
+    pub fn as_str(&self) -> Cow<'_, str> {
         self.path.as_str()
     }
 
     pub fn as_relative_path(&self, path: &str) -> Result<RelativePath> {
-        RelativePath::new(self.path(), Path::new(path))
+        self.get_relative_path(Path::new(path))
     }
 
+    /// Files outside the project and its extra roots get an
+    /// absolute path, see RelativePath::is_foreign()
     pub fn get_relative_path(&self, path: &Path) -> Result<RelativePath> {
-        RelativePath::new(self.path(), path)
+        if self.contains(path)? {
+            RelativePath::new(self.path(), path)
+        } else {
+            RelativePath::foreign(path)
+        }
+    }
+
+    /// Whether the file is in the project or in one of its extra roots,
+    /// once symbolic links are resolved
+    pub fn contains(&self, path: &Path) -> Result<bool> {
+        let path = std::fs::canonicalize(path)
+            .with_context(|| format!("Could not canonicalize path: {}", path.display()))?;
+        let in_root = |root: &Path| path.starts_with(root);
+        Ok(in_root(self.path.as_ref()) || self.extra_roots.iter().any(|r| in_root(r)))
     }
 
     pub fn ignore_path(&self) -> PathBuf {
@@ -51,12 +203,49 @@ impl Project {
         IgnoreStore::load(global_path, local_path)
     }
 
+    /// Ignore store with an empty local ignore list, that cannot be modified
+    pub fn ignore_store_without_local(&self) -> Result<IgnoreStore> {
+        let local_path = self.path.0.join(SKYSPELL_LOCAL_IGNORE);
+        let global_path = global_path()?;
+
+        IgnoreStore::load_without_local(global_path, local_path)
+    }
+
     pub fn skip_file(&self) -> &SkipFile {
         &self.skip_file
     }
 
     pub fn walk(&self) -> Result<Walk> {
-        Ok(WalkBuilder::new(self.path().as_ref()).build())
+        let mut builder = WalkBuilder::new(self.path().as_ref());
+        for extra_root in &self.extra_roots {
+            builder.add(extra_root);
+        }
+        Ok(self.prune_skipped_directories(&mut builder).build())
+    }
+
+    /// Like walk(), but starting from the given paths instead of the
+    /// project root. The extra roots are still walked
+    pub fn walk_from(&self, paths: &[PathBuf]) -> Option<Walk> {
+        let mut roots = paths.iter().chain(&self.extra_roots);
+        let mut builder = WalkBuilder::new(roots.next()?);
+        for root in roots {
+            builder.add(root);
+        }
+        Some(self.prune_skipped_directories(&mut builder).build())
+    }
+
+    // Every file in a skipped directory would be skipped anyway, so don't
+    // even list them
+    fn prune_skipped_directories<'a>(&self, builder: &'a mut WalkBuilder) -> &'a mut WalkBuilder {
+        let root = self.path.as_ref().to_path_buf();
+        let skip_file = self.skip_file.clone();
+        builder.filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            match entry.path().strip_prefix(&root) {
+                Ok(relative_dir) if is_dir => !skip_file.skips_directory(relative_dir),
+                _ => true,
+            }
+        })
     }
 }
 
@@ -65,16 +254,16 @@ pub struct ProjectPath(PathBuf);
 
 impl ProjectPath {
     pub fn new(project_path: &Path) -> Result<Self> {
-        let path = std::fs::canonicalize(project_path).with_context(|| {
-            anyhow!(
-                "Could not canonicalize project path: {}",
-                project_path.display()
-            )
+        let path = std::fs::canonicalize(project_path).map_err(|error| {
+            SkyspellError::ProjectNotFound {
+                path: project_path.to_path_buf(),
+                error,
+            }
         })?;
         Ok(ProjectPath(path))
     }
 
-    pub fn as_str(&self) -> Cow<str> {
+    pub fn as_str(&self) -> Cow<'_, str> {
         self.0.to_string_lossy()
     }
 }
@@ -91,6 +280,20 @@ impl Display for ProjectPath {
     }
 }
 
+/// Use forward slashes on all platforms, and remove the `\\?\` prefix
+/// canonicalize() adds on Windows, so that paths written on one platform
+/// can be matched on another
+pub fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    if let Some(rest) = path.strip_prefix("//?/UNC/") {
+        return format!("//{rest}");
+    }
+    match path.strip_prefix("//?/") {
+        Some(rest) => rest.to_string(),
+        None => path,
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RelativePath(PathBuf);
 
@@ -119,17 +322,50 @@ impl RelativePath {
         Self(path)
     }
 
+    /// For files outside the project and its extra roots: the path is
+    /// the canonical one, and is never relative
+    pub fn foreign(source_path: &Path) -> Result<Self> {
+        let path = std::fs::canonicalize(source_path).with_context(|| {
+            anyhow!(
+                "Could not canonicalize relative path: {}",
+                source_path.display()
+            )
+        })?;
+        Ok(Self(path))
+    }
+
+    /// True for files found in extra roots, which are reported
+    /// with a path like `../handbook/intro.md`, and for foreign files
+    pub fn is_outside_project(&self) -> bool {
+        self.0.starts_with("..") || self.is_foreign()
+    }
+
+    /// True for files outside the project and its extra roots, which
+    /// are only checked with the global ignore lists
+    pub fn is_foreign(&self) -> bool {
+        self.0.is_absolute()
+    }
+
+    /// The path as stored in the ignore files, see normalize_path()
     pub fn normalize(&self) -> String {
-        self.0.to_string_lossy().replace("\\", "/")
+        normalize_path(&self.0.to_string_lossy())
     }
 
-    pub fn file_name(&self) -> Option<Cow<str>> {
+    pub fn file_name(&self) -> Option<Cow<'_, str>> {
         self.0.file_name().map(|x| x.to_string_lossy())
     }
 
-    pub fn extension(&self) -> Option<Cow<str>> {
+    pub fn extension(&self) -> Option<Cow<'_, str>> {
         self.0.extension().map(|x| x.to_string_lossy())
     }
+
+    /// The directory containing the file, if it is not the project root
+    pub fn parent(&self) -> Option<RelativePath> {
+        self.0
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .map(|p| Self(p.to_path_buf()))
+    }
 }
 
 impl AsRef<Path> for RelativePath {
@@ -143,3 +379,6 @@ impl Display for RelativePath {
         write!(f, "{}", self.0.display())
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,334 @@
+use super::*;
+use crate::tests::get_test_dir;
+
+#[test]
+fn test_parse_file_size_bytes() {
+    assert_eq!(parse_file_size("500").unwrap(), 500);
+    assert_eq!(parse_file_size("500B").unwrap(), 500);
+}
+
+#[test]
+fn test_parse_file_size_with_units() {
+    assert_eq!(parse_file_size("20kB").unwrap(), 20 * 1024);
+    assert_eq!(parse_file_size("5 MB").unwrap(), 5 * 1024 * 1024);
+    assert_eq!(parse_file_size("1G").unwrap(), 1024 * 1024 * 1024);
+}
+
+#[test]
+fn test_parse_file_size_errors() {
+    assert!(parse_file_size("").is_err());
+    assert!(parse_file_size("MB").is_err());
+    assert!(parse_file_size("5 parsecs").is_err());
+    assert!(parse_file_size("99999999999999999999 GB").is_err());
+}
+
+#[test]
+fn test_project_not_found() {
+    let temp_dir = get_test_dir();
+    let missing = temp_dir.path().join("missing");
+
+    let err = ProjectPath::new(&missing).unwrap_err();
+
+    match err.downcast_ref() {
+        Some(SkyspellError::ProjectNotFound { path, .. }) => assert_eq!(path, &missing),
+        _ => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn test_project_without_local_ignore() {
+    let temp_dir = get_test_dir();
+    let project_path = temp_dir.path();
+    std::fs::write(project_path.join(SKYSPELL_LOCAL_IGNORE), "<<<<<<< HEAD").unwrap();
+
+    assert!(Project::new(project_path).is_err());
+
+    let project = Project::new_without_local_ignore(project_path).unwrap();
+    assert_eq!(project.max_file_size(), DEFAULT_MAX_FILE_SIZE);
+}
+
+#[test]
+fn test_extension_filter() {
+    let temp_dir = get_test_dir();
+    for name in ["README.md", "main.rs", "Makefile"] {
+        std::fs::write(temp_dir.path().join(name), "").unwrap();
+    }
+    let mut project = Project::new(temp_dir.path()).unwrap();
+    let readme = project
+        .get_relative_path(&temp_dir.path().join("README.md"))
+        .unwrap();
+    let main = project
+        .get_relative_path(&temp_dir.path().join("main.rs"))
+        .unwrap();
+    let makefile = project
+        .get_relative_path(&temp_dir.path().join("Makefile"))
+        .unwrap();
+
+    assert!(project.has_checked_extension(&main));
+
+    project.set_extensions(&[".md".to_string(), "none".to_string()]);
+
+    assert!(project.has_checked_extension(&readme));
+    assert!(project.has_checked_extension(&makefile));
+    assert!(!project.has_checked_extension(&main));
+}
+
+#[test]
+fn test_normalize_path() {
+    assert_eq!(normalize_path("src/foo.rs"), "src/foo.rs");
+    assert_eq!(normalize_path("src\\foo.rs"), "src/foo.rs");
+    assert_eq!(
+        normalize_path("..\\handbook\\intro.md"),
+        "../handbook/intro.md"
+    );
+    assert_eq!(normalize_path("\\\\?\\C:\\src\\foo.rs"), "C:/src/foo.rs");
+    assert_eq!(
+        normalize_path("\\\\?\\UNC\\server\\share\\foo.rs"),
+        "//server/share/foo.rs"
+    );
+}
+
+#[cfg(windows)]
+#[test]
+fn test_relative_path_uses_forward_slashes_on_windows() {
+    let temp_dir = get_test_dir();
+    let src = temp_dir.path().join("src");
+    std::fs::create_dir(&src).unwrap();
+    std::fs::write(src.join("foo.rs"), "").unwrap();
+    let project = Project::new(temp_dir.path()).unwrap();
+
+    let relative_path = project.get_relative_path(&src.join("foo.rs")).unwrap();
+
+    assert_eq!(relative_path.normalize(), "src/foo.rs");
+}
+
+#[test]
+fn test_files_outside_the_project() {
+    let temp_dir = get_test_dir();
+    let project_path = temp_dir.path().join("project");
+    let handbook = temp_dir.path().join("handbook");
+    let other = temp_dir.path().join("other");
+    for dir in [&project_path, &handbook, &other] {
+        std::fs::create_dir(dir).unwrap();
+        std::fs::write(dir.join("intro.md"), "").unwrap();
+    }
+    let mut project = Project::new(&project_path).unwrap();
+    project.add_extra_root(&handbook).unwrap();
+
+    assert!(project.contains(&project_path.join("intro.md")).unwrap());
+    assert!(project.contains(&handbook.join("intro.md")).unwrap());
+    assert!(!project.contains(&other.join("intro.md")).unwrap());
+    assert!(project.contains(&other.join("missing.md")).is_err());
+
+    let in_extra_root = project
+        .get_relative_path(&handbook.join("intro.md"))
+        .unwrap();
+    assert_eq!(in_extra_root.normalize(), "../handbook/intro.md");
+    assert!(in_extra_root.is_outside_project());
+    assert!(!in_extra_root.is_foreign());
+
+    let foreign = project.get_relative_path(&other.join("intro.md")).unwrap();
+    let expected = std::fs::canonicalize(other.join("intro.md")).unwrap();
+    assert_eq!(foreign.as_ref(), expected);
+    assert!(foreign.is_outside_project());
+    assert!(foreign.is_foreign());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_escaping_the_project() {
+    let temp_dir = get_test_dir();
+    let project_path = temp_dir.path().join("project");
+    let other = temp_dir.path().join("other");
+    std::fs::create_dir(&project_path).unwrap();
+    std::fs::create_dir(&other).unwrap();
+    std::fs::write(other.join("secret.md"), "").unwrap();
+    std::os::unix::fs::symlink(other.join("secret.md"), project_path.join("link.md")).unwrap();
+    std::os::unix::fs::symlink(&other, project_path.join("linked_dir")).unwrap();
+    let project = Project::new(&project_path).unwrap();
+
+    assert!(!project.contains(&project_path.join("link.md")).unwrap());
+    assert!(!project
+        .contains(&project_path.join("linked_dir/secret.md"))
+        .unwrap());
+}
+
+#[test]
+fn test_find_project_root_from_nested_directory() {
+    let temp_dir = get_test_dir();
+    let root = temp_dir.path().join("project");
+    let nested = root.join("src/nested");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::create_dir(root.join(".git")).unwrap();
+
+    assert_eq!(find_project_root(&nested), Some(root.clone()));
+    assert_eq!(find_project_root(&root), Some(root));
+}
+
+#[test]
+fn test_local_ignore_takes_precedence_over_vcs_root() {
+    let temp_dir = get_test_dir();
+    let root = temp_dir.path().join("project");
+    // Like a git submodule in a project with a skyspell-ignore.toml file
+    let submodule = root.join("vendor/lib");
+    let nested = submodule.join("src");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(root.join(SKYSPELL_LOCAL_IGNORE), "").unwrap();
+    std::fs::write(submodule.join(".git"), "gitdir: ../../.git/modules/lib\n").unwrap();
+
+    assert_eq!(find_project_root(&nested), Some(root));
+}
+
+#[test]
+fn test_find_project_root_with_other_vcs() {
+    let temp_dir = get_test_dir();
+    let root = temp_dir.path().join("project");
+    let nested = root.join("src");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::create_dir(root.join(".hg")).unwrap();
+
+    assert_eq!(find_project_root(&nested), Some(root));
+}
+
+#[test]
+fn test_walk_does_not_enter_skipped_directories() {
+    let temp_dir = get_test_dir();
+    let project_path = temp_dir.path().canonicalize().unwrap();
+    for dir in ["assets/fonts", "src/assets"] {
+        std::fs::create_dir_all(project_path.join(dir)).unwrap();
+    }
+    for file in [
+        "assets/logo.svg",
+        "assets/fonts/LICENSE",
+        "src/assets/notes.md",
+        "README.md",
+    ] {
+        std::fs::write(project_path.join(file), "").unwrap();
+    }
+    std::fs::write(
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+        "patterns = ['/assets/']\n",
+    )
+    .unwrap();
+    let project = Project::new(&project_path).unwrap();
+
+    let mut walked: Vec<_> = project
+        .walk()
+        .unwrap()
+        .map(|e| e.unwrap().into_path())
+        .filter(|p| p.is_file())
+        .map(|p| normalize_path(&p.strip_prefix(&project_path).unwrap().to_string_lossy()))
+        .collect();
+    walked.sort();
+
+    assert_eq!(
+        walked,
+        ["README.md", "skyspell-ignore.toml", "src/assets/notes.md"]
+    );
+}
+
+// Counts what the walker yields, and how much of it is under `dir`
+struct CountingWalk {
+    walk: Walk,
+    dir: PathBuf,
+    total: usize,
+    under_dir: usize,
+}
+
+impl CountingWalk {
+    fn new(walk: Walk, dir: PathBuf) -> Self {
+        Self {
+            walk,
+            dir,
+            total: 0,
+            under_dir: 0,
+        }
+    }
+}
+
+impl Iterator for CountingWalk {
+    type Item = ignore::DirEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.walk.next()?.unwrap();
+        self.total += 1;
+        if entry.path().starts_with(&self.dir) {
+            self.under_dir += 1;
+        }
+        Some(entry)
+    }
+}
+
+// A tree like node_modules/d0/d1/.../d19, with a few files at each level
+fn create_deep_tree(root: &Path, depth: usize, files_per_level: usize) {
+    let mut dir = root.to_path_buf();
+    for level in 0..depth {
+        dir = dir.join(format!("d{level}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        for i in 0..files_per_level {
+            std::fs::write(dir.join(format!("file{i}.js")), "").unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_walk_never_yields_entries_of_deeply_nested_skipped_directories() {
+    let temp_dir = get_test_dir();
+    let project_path = temp_dir.path().canonicalize().unwrap();
+    let node_modules = project_path.join("node_modules");
+    create_deep_tree(&node_modules, 20, 5);
+    std::fs::write(project_path.join("README.md"), "").unwrap();
+    std::fs::write(
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+        "patterns = ['node_modules/']\n",
+    )
+    .unwrap();
+    let project = Project::new(&project_path).unwrap();
+
+    let mut walk = CountingWalk::new(project.walk().unwrap(), node_modules);
+    walk.by_ref().for_each(drop);
+
+    assert_eq!(walk.under_dir, 0);
+    // The project root, README.md and skyspell-ignore.toml
+    assert_eq!(walk.total, 3);
+}
+
+#[test]
+fn test_pruning_skipped_directories_is_not_slower() {
+    let temp_dir = get_test_dir();
+    let project_path = temp_dir.path().canonicalize().unwrap();
+    let vendor = project_path.join("vendor");
+    for tree in 0..20 {
+        create_deep_tree(&vendor.join(format!("t{tree}")), 10, 10);
+    }
+    let walk_all = || {
+        let project = Project::new(&project_path).unwrap();
+        let start = std::time::Instant::now();
+        project.walk().unwrap().for_each(drop);
+        start.elapsed()
+    };
+    let full = walk_all();
+    std::fs::write(
+        project_path.join(SKYSPELL_LOCAL_IGNORE),
+        "patterns = ['/vendor/']\n",
+    )
+    .unwrap();
+    let pruned = walk_all();
+
+    // Timings are noisy on busy machines: only compare them when walking
+    // the whole tree took long enough to be measured
+    if full > std::time::Duration::from_millis(20) {
+        assert!(pruned < full, "pruned: {pruned:?}, full: {full:?}");
+    }
+}
+
+#[test]
+fn test_relative_path_parent() {
+    let relative_path = RelativePath::from_path_unchecked("docs/api/index.md".into());
+    let parent = relative_path.parent().unwrap();
+
+    assert_eq!(parent.normalize(), "docs/api");
+    assert!(RelativePath::from_path_unchecked("README.md".into())
+        .parent()
+        .is_none());
+}
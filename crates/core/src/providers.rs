@@ -0,0 +1,114 @@
+//! Registry of dictionary providers, so that programs embedding skyspell
+//! can plug in their own dictionaries:
+//!
+//! ```ignore
+//! skyspell_core::register_provider(MyFactory);
+//! skyspell::main()
+//! ```
+//!
+//! and then use `skyspell --provider my-provider`
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use crate::dictionary::{normalize_lang, unknown_lang_message};
+use crate::{Dictionary, SkyspellError};
+
+pub trait DictionaryFactory: Send + Sync {
+    /// Used to select the provider, for instance with `--provider`
+    fn name(&self) -> &str;
+    fn create(&self, lang: &str) -> Result<Box<dyn Dictionary>>;
+    /// Used to suggest a language when create() fails
+    fn available_languages(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+lazy_static! {
+    static ref REGISTERED: Mutex<Vec<Arc<dyn DictionaryFactory>>> = Mutex::new(vec![]);
+}
+
+/// Make a provider available to open_dictionary(). Registered providers
+/// take precedence over the built-in ones with the same name
+pub fn register_provider(factory: impl DictionaryFactory + 'static) {
+    let mut registered = REGISTERED.lock().expect("registry should not be poisoned");
+    registered.retain(|f| f.name() != factory.name());
+    registered.push(Arc::new(factory));
+}
+
+fn factories() -> Vec<Arc<dyn DictionaryFactory>> {
+    let registered = REGISTERED.lock().expect("registry should not be poisoned");
+    let mut res = registered.clone();
+    res.extend(crate::system_dictionary::builtin_factories());
+    res
+}
+
+/// Names of all the providers that can be passed to open_dictionary()
+pub fn provider_names() -> Vec<String> {
+    let mut res: Vec<_> = factories().iter().map(|f| f.name().to_string()).collect();
+    res.dedup();
+    res
+}
+
+// The system dictionary, or the first registered provider when skyspell
+// is built without one
+fn default_factory() -> Option<Arc<dyn DictionaryFactory>> {
+    let builtin = crate::system_dictionary::builtin_factories();
+    builtin
+        .into_iter()
+        .next()
+        .or_else(|| factories().into_iter().next())
+}
+
+/// Open the dictionary for `lang` with the given provider, or
+/// with SystemDictionary if no provider is given.
+/// Mistakes like `en-us` for `en_US` are fixed, and if the dictionary
+/// cannot be opened, the error lists the closest available languages
+pub fn open_dictionary(provider: Option<&str>, lang: &str) -> Result<Box<dyn Dictionary>> {
+    let lang = normalize_lang(lang);
+    let factory = match provider {
+        None => match default_factory() {
+            Some(factory) => factory,
+            None => {
+                return Err(SkyspellError::DictionaryUnavailable {
+                    lang,
+                    provider: "default".to_string(),
+                    message: "skyspell was built without Enchant support, and no dictionary provider was registered".to_string(),
+                }
+                .into())
+            }
+        },
+        Some(provider) => match factories().into_iter().find(|f| f.name() == provider) {
+            Some(factory) => factory,
+            None => {
+                return Err(SkyspellError::DictionaryUnavailable {
+                    lang,
+                    provider: provider.to_string(),
+                    message: format!(
+                        "Unknown provider '{provider}'\nAvailable providers: {}",
+                        provider_names().join(", ")
+                    ),
+                }
+                .into())
+            }
+        },
+    };
+    factory.create(&lang).map_err(|e| {
+        let mut available = factory.available_languages();
+        available.sort();
+        available.dedup();
+        let message = match unknown_lang_message(&lang, &available) {
+            message if message.is_empty() => format!("{e:#}"),
+            message => format!("{e:#}\n{message}"),
+        };
+        SkyspellError::DictionaryUnavailable {
+            provider: factory.name().to_string(),
+            lang: lang.clone(),
+            message,
+        }
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,105 @@
+use anyhow::bail;
+
+use super::*;
+
+use crate::tests::FakeDictionary;
+
+// Note: the registry is global and tests run in parallel, so
+// each test uses its own provider name
+struct FakeFactory {
+    name: &'static str,
+    known: &'static str,
+}
+
+impl DictionaryFactory for FakeFactory {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn create(&self, _lang: &str) -> Result<Box<dyn Dictionary>> {
+        let mut dictionary = FakeDictionary::new();
+        dictionary.add_known(self.known);
+        Ok(Box::new(dictionary))
+    }
+}
+
+#[test]
+fn test_open_registered_provider() {
+    register_provider(FakeFactory {
+        name: "fake-open",
+        known: "hello",
+    });
+
+    let dictionary = open_dictionary(Some("fake-open"), "en_US").unwrap();
+
+    assert!(dictionary.check("hello").unwrap());
+    assert!(!dictionary.check("world").unwrap());
+}
+
+#[test]
+fn test_registering_twice_replaces_the_provider() {
+    register_provider(FakeFactory {
+        name: "fake-twice",
+        known: "hello",
+    });
+    register_provider(FakeFactory {
+        name: "fake-twice",
+        known: "world",
+    });
+
+    let dictionary = open_dictionary(Some("fake-twice"), "en_US").unwrap();
+
+    assert!(dictionary.check("world").unwrap());
+    let names = provider_names();
+    assert_eq!(names.iter().filter(|n| *n == "fake-twice").count(), 1);
+}
+
+#[test]
+fn test_unknown_provider_lists_available_providers() {
+    register_provider(FakeFactory {
+        name: "fake-listed",
+        known: "hello",
+    });
+
+    let err = open_dictionary(Some("no-such-provider"), "en_US")
+        .err()
+        .unwrap();
+
+    let message = err.to_string();
+    assert!(message.contains("Unknown provider 'no-such-provider'"));
+    assert!(message.contains("fake-listed"));
+}
+
+struct NoDictionaryFactory;
+
+impl DictionaryFactory for NoDictionaryFactory {
+    fn name(&self) -> &str {
+        "fake-empty"
+    }
+
+    fn create(&self, lang: &str) -> Result<Box<dyn Dictionary>> {
+        bail!("No dictionary for '{lang}'")
+    }
+
+    fn available_languages(&self) -> Vec<String> {
+        vec!["en_US".to_string(), "fr_FR".to_string()]
+    }
+}
+
+#[test]
+fn test_unknown_lang_suggests_close_languages() {
+    register_provider(NoDictionaryFactory);
+
+    let err = open_dictionary(Some("fake-empty"), "en-su").err().unwrap();
+
+    let message = err.to_string();
+    assert!(message.contains("No dictionary for 'en_SU'"));
+    assert!(message.contains("Did you mean: en_US?"));
+    match err.downcast_ref() {
+        Some(SkyspellError::DictionaryUnavailable { lang, provider, .. }) => {
+            assert_eq!(lang, "en_SU");
+            assert_eq!(provider, "fake-empty");
+        }
+        _ => panic!("unexpected error: {err:?}"),
+    }
+}
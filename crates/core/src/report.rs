@@ -0,0 +1,112 @@
+//! Errors found by the last non-interactive run, saved in the state file so
+//! that words can be added by index afterwards
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ProjectPath;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ReportEntry {
+    pub word: String,
+    /// Relative to the project, with forward slashes. Absolute for
+    /// files outside the project, see RelativePath::is_foreign()
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ReportEntry {
+    pub fn extension(&self) -> Option<&str> {
+        let file_name = self.path.rsplit('/').next()?;
+        let (stem, extension) = file_name.rsplit_once('.')?;
+        if stem.is_empty() {
+            return None;
+        }
+        Some(extension)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub timestamp: i64,
+    // Hash of the files containing errors, so that we know when
+    // the indexes no longer match what's in the files
+    files_hash: String,
+    pub entries: Vec<ReportEntry>,
+}
+
+impl Report {
+    pub(crate) fn new(
+        project_path: &ProjectPath,
+        entries: Vec<ReportEntry>,
+        timestamp: i64,
+    ) -> Self {
+        let files_hash = files_hash(project_path, &entries);
+        Self {
+            timestamp,
+            files_hash,
+            entries,
+        }
+    }
+
+    pub(crate) fn is_stale(&self, project_path: &ProjectPath) -> bool {
+        files_hash(project_path, &self.entries) != self.files_hash
+    }
+
+    /// Get an entry from its index, as displayed to the user - starting at 1
+    pub fn entry(&self, index: usize) -> Result<&ReportEntry> {
+        let size = self.entries.len();
+        match index.checked_sub(1).and_then(|i| self.entries.get(i)) {
+            Some(entry) => Ok(entry),
+            None => bail!("Index {index} is out of range: the report contains {size} errors"),
+        }
+    }
+}
+
+/// The unknown words found in each file by a run, saved in the state file
+/// so that `check --only-new` can hide them the next time
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SeenFindings(BTreeMap<String, BTreeSet<String>>);
+
+impl SeenFindings {
+    pub fn from_entries(entries: &[ReportEntry]) -> Self {
+        let mut res = BTreeMap::<String, BTreeSet<String>>::new();
+        for entry in entries {
+            res.entry(entry.path.clone())
+                .or_default()
+                .insert(entry.word.clone());
+        }
+        Self(res)
+    }
+
+    pub fn contains(&self, path: &str, word: &str) -> bool {
+        self.0.get(path).is_some_and(|words| words.contains(word))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn files_hash(project_path: &ProjectPath, entries: &[ReportEntry]) -> String {
+    let mut paths: Vec<_> = entries.iter().map(|e| e.path.as_str()).collect();
+    paths.sort();
+    paths.dedup();
+    let mut hasher = DefaultHasher::new();
+    for path in paths {
+        path.hash(&mut hasher);
+        // A missing file counts as a change
+        std::fs::read(project_path.as_ref().join(path))
+            .ok()
+            .hash(&mut hasher);
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests;
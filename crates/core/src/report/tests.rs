@@ -0,0 +1,95 @@
+use crate::tests::{get_test_dir, new_project_path};
+use crate::CheckerState;
+
+use super::*;
+
+fn entry(word: &str, path: &str) -> ReportEntry {
+    ReportEntry {
+        word: word.to_string(),
+        path: path.to_string(),
+        line: 1,
+        column: 0,
+    }
+}
+
+#[test]
+fn test_entry_by_index() {
+    let temp_dir = get_test_dir();
+    let project_path = new_project_path(&temp_dir, "project");
+    let report = Report::new(
+        &project_path,
+        vec![entry("foo", "a.md"), entry("bar", "b.md")],
+        0,
+    );
+
+    assert_eq!(report.entry(2).unwrap().word, "bar");
+    let err = report.entry(3).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Index 3 is out of range: the report contains 2 errors"
+    );
+    assert!(report.entry(0).is_err());
+}
+
+#[test]
+fn test_extension() {
+    assert_eq!(entry("foo", "src/a.md").extension(), Some("md"));
+    assert_eq!(entry("foo", "src.d/README").extension(), None);
+    assert_eq!(entry("foo", ".bashrc").extension(), None);
+}
+
+#[test]
+fn test_report_is_stale_when_files_change() {
+    let temp_dir = get_test_dir();
+    let project_path = new_project_path(&temp_dir, "project");
+    let a_md = project_path.as_ref().join("a.md");
+    std::fs::write(&a_md, "fooo").unwrap();
+    let state_toml = temp_dir.path().join("state.toml");
+    let mut state = CheckerState::load(Some(state_toml.clone())).unwrap();
+    state
+        .save_report(&project_path, vec![entry("fooo", "a.md")])
+        .unwrap();
+
+    let state = CheckerState::load(Some(state_toml.clone())).unwrap();
+    assert_eq!(state.report(&project_path).unwrap().entries.len(), 1);
+
+    std::fs::write(&a_md, "foo").unwrap();
+    let err = state.report(&project_path).unwrap_err();
+    assert!(err.to_string().contains("out of date"));
+}
+
+#[test]
+fn test_clean_run_removes_report() {
+    let temp_dir = get_test_dir();
+    let project_path = new_project_path(&temp_dir, "project");
+    let state_toml = temp_dir.path().join("state.toml");
+    let mut state = CheckerState::load(Some(state_toml)).unwrap();
+    state
+        .save_report(&project_path, vec![entry("fooo", "a.md")])
+        .unwrap();
+
+    state.save_report(&project_path, vec![]).unwrap();
+
+    let err = state.report(&project_path).unwrap_err();
+    assert!(err.to_string().contains("No report found"));
+}
+
+#[test]
+fn test_seen_findings() {
+    let temp_dir = get_test_dir();
+    let project_path = new_project_path(&temp_dir, "project");
+    let state_toml = temp_dir.path().join("state.toml");
+    let mut state = CheckerState::load(Some(state_toml.clone())).unwrap();
+    let findings = SeenFindings::from_entries(&[entry("fooo", "a.md"), entry("barr", "b.md")]);
+    state.save_seen_findings(&project_path, findings).unwrap();
+
+    let mut state = CheckerState::load(Some(state_toml)).unwrap();
+    let seen = state.seen_findings(&project_path).unwrap();
+    assert!(seen.contains("a.md", "fooo"));
+    assert!(!seen.contains("a.md", "barr"));
+
+    state
+        .save_seen_findings(&project_path, SeenFindings::default())
+        .unwrap();
+    assert!(state.seen_findings(&project_path).is_none());
+}
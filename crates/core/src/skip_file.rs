@@ -1,9 +1,10 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
 use ignore::gitignore::{Gitignore, GitignoreBuilder};
-use ignore::{Walk, WalkBuilder};
+use ignore::Match;
+use ignore::Walk;
 
 use crate::project::SKYSPELL_LOCAL_IGNORE;
 use crate::LocalIgnore;
@@ -12,30 +13,151 @@ use crate::{Project, RelativePath};
 #[derive(Debug, Clone)]
 pub struct SkipFile(Gitignore);
 
+/// Why a file is skipped, see `SkipFile::skip_rule()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipRule {
+    /// The local ignore file and its backup are never checked
+    LocalIgnoreFile,
+    /// One of the patterns of the local ignore file
+    Pattern(String),
+}
+
+impl std::fmt::Display for SkipRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipRule::LocalIgnoreFile => {
+                write!(
+                    f,
+                    "{SKYSPELL_LOCAL_IGNORE} and its backup are never checked"
+                )
+            }
+            SkipRule::Pattern(pattern) => write!(f, "matches the skip pattern '{pattern}'"),
+        }
+    }
+}
+
 impl SkipFile {
-    pub fn new(root_path: &Path) -> Result<Self> {
-        let ignore_path = root_path.join(SKYSPELL_LOCAL_IGNORE);
+    pub fn new(root_path: &Path, local: &LocalIgnore) -> Result<Self> {
         let mut gitignore_builder = GitignoreBuilder::new(root_path);
-        let local = LocalIgnore::load(&ignore_path)?;
-        let patterns = local.patterns;
-        for glob in patterns {
-            gitignore_builder.add_line(None, &glob)?;
+        for glob in &local.patterns {
+            gitignore_builder.add_line(None, glob)?;
         }
         Ok(Self(gitignore_builder.build()?))
     }
 
     pub fn is_skipped(&self, relative_path: &RelativePath) -> bool {
-        if relative_path.normalize().ends_with(SKYSPELL_LOCAL_IGNORE) {
-            return true;
+        self.skip_rule(relative_path).is_some()
+    }
+
+    /// Should walking the project stay out of this directory, given
+    /// relative to the project? Never when a pattern starts with `!`, since
+    /// it could bring back some of the files inside
+    pub fn skips_directory(&self, relative_dir: &Path) -> bool {
+        if self.0.num_whitelists() > 0 || relative_dir.as_os_str().is_empty() {
+            return false;
         }
         self.0
-            .matched_path_or_any_parents(relative_path, false)
+            .matched_path_or_any_parents(relative_dir, true)
             .is_ignore()
     }
+
+    /// Patterns are relative to the project, so foreign files are never skipped
+    pub fn skip_rule(&self, relative_path: &RelativePath) -> Option<SkipRule> {
+        let normalized = relative_path.normalize();
+        let file_name = normalized.strip_suffix(".bak").unwrap_or(&normalized);
+        if file_name.ends_with(SKYSPELL_LOCAL_IGNORE) {
+            return Some(SkipRule::LocalIgnoreFile);
+        }
+        if relative_path.is_foreign() {
+            return None;
+        }
+        match self.0.matched_path_or_any_parents(relative_path, false) {
+            Match::Ignore(glob) => Some(SkipRule::Pattern(glob.original().to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// The pattern skipping everything in a directory of the project
+pub fn directory_pattern(relative_dir: &RelativePath) -> String {
+    format!("/{}/", relative_dir.normalize().trim_end_matches('/'))
+}
+
+/// Why walking the project does not list a file. Such files are
+/// still checked when given on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalkExclusion {
+    /// The file, or one of its parent directories, is hidden
+    Hidden(String),
+    /// Matched by a pattern of a .gitignore or .ignore file
+    Ignored {
+        ignore_file: PathBuf,
+        pattern: String,
+    },
+}
+
+impl std::fmt::Display for WalkExclusion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalkExclusion::Hidden(name) => write!(f, "'{name}' is hidden"),
+            WalkExclusion::Ignored {
+                ignore_file,
+                pattern,
+            } => write!(f, "ignored by '{pattern}' in {}", ignore_file.display()),
+        }
+    }
+}
+
+/// Replay the filters applied by walk() for a path in the project.
+/// Only .gitignore and .ignore files inside the project are looked at
+pub fn walk_exclusion(root_path: &Path, path: &Path) -> Option<WalkExclusion> {
+    let relative_path = path.strip_prefix(root_path).ok()?;
+    for component in relative_path.components() {
+        let name = component.as_os_str().to_string_lossy();
+        if name.starts_with('.') && name != "." && name != ".." {
+            return Some(WalkExclusion::Hidden(name.to_string()));
+        }
+    }
+
+    // Like the walker, only use .gitignore files in git repositories
+    let in_git_repo = root_path.ancestors().any(|p| p.join(".git").exists());
+    let is_dir = path.is_dir();
+    // Files in the deepest directories win, and .ignore files win
+    // over .gitignore files in the same directory
+    let directories = path
+        .ancestors()
+        .skip(1)
+        .take_while(|d| d.starts_with(root_path));
+    for directory in directories {
+        let mut names = vec![".ignore"];
+        if in_git_repo {
+            names.push(".gitignore");
+        }
+        for name in names {
+            let ignore_file = directory.join(name);
+            if !ignore_file.exists() {
+                continue;
+            }
+            let (gitignore, _) = Gitignore::new(&ignore_file);
+            match gitignore.matched_path_or_any_parents(path, is_dir) {
+                Match::Ignore(glob) => {
+                    return Some(WalkExclusion::Ignored {
+                        ignore_file,
+                        pattern: glob.original().to_string(),
+                    })
+                }
+                Match::Whitelist(_) => return None,
+                Match::None => {}
+            }
+        }
+    }
+    None
 }
 
+/// Walk the project - the directories skipped by the local ignore file
+/// are never entered, see `Project::walk()`
 pub fn walk(project: &Project) -> Result<Walk> {
-    Ok(WalkBuilder::new(project.path().as_ref()).build())
+    project.walk()
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use super::*;
+use crate::tests::get_test_dir;
 
 #[test]
 fn test_skipping_file_in_subdir() {
@@ -13,3 +14,122 @@ fn test_skipping_file_in_subdir() {
     let actual = gitignore.matched_path_or_any_parents("foo/bar", false);
     assert!(actual.is_ignore());
 }
+
+#[test]
+fn test_skip_rule() {
+    let mut local = LocalIgnore::default();
+    local.patterns = ["yarn.lock".to_string(), "/vendor/".to_string()].into();
+    let skip_file = SkipFile::new(Path::new("."), &local).unwrap();
+    let rule = |path: &str| skip_file.skip_rule(&RelativePath::from_path_unchecked(path.into()));
+
+    assert_eq!(
+        rule("front/yarn.lock"),
+        Some(SkipRule::Pattern("yarn.lock".to_string()))
+    );
+    assert_eq!(
+        rule("vendor/lib.c"),
+        Some(SkipRule::Pattern("/vendor/".to_string()))
+    );
+    assert_eq!(
+        rule("sub/skyspell-ignore.toml"),
+        Some(SkipRule::LocalIgnoreFile)
+    );
+    assert_eq!(
+        rule("skyspell-ignore.toml.bak"),
+        Some(SkipRule::LocalIgnoreFile)
+    );
+    assert_eq!(rule("src/vendor.c"), None);
+}
+
+#[test]
+fn test_skips_directory() {
+    let mut local = LocalIgnore::default();
+    local.patterns = ["/assets/".to_string(), "*.lock".to_string()].into();
+    let skip_file = SkipFile::new(Path::new("."), &local).unwrap();
+
+    assert!(skip_file.skips_directory(Path::new("assets")));
+    assert!(skip_file.skips_directory(Path::new("assets/fonts")));
+    assert!(!skip_file.skips_directory(Path::new("src")));
+    assert!(!skip_file.skips_directory(Path::new("src/assets")));
+    assert!(!skip_file.skips_directory(Path::new("")));
+}
+
+#[test]
+fn test_directories_are_never_pruned_with_negated_patterns() {
+    let mut local = LocalIgnore::default();
+    local.patterns = ["/assets/".to_string(), "!/assets/README.md".to_string()].into();
+    let skip_file = SkipFile::new(Path::new("."), &local).unwrap();
+
+    assert!(!skip_file.skips_directory(Path::new("assets")));
+}
+
+#[test]
+fn test_directory_pattern() {
+    let relative_dir = RelativePath::from_path_unchecked("db/migrations/".into());
+
+    assert_eq!(directory_pattern(&relative_dir), "/db/migrations/");
+}
+
+#[test]
+fn test_walk_exclusion_hidden() {
+    let temp_dir = get_test_dir();
+    let root = temp_dir.path().canonicalize().unwrap();
+
+    assert_eq!(
+        walk_exclusion(&root, &root.join(".github/workflows/ci.yml")),
+        Some(WalkExclusion::Hidden(".github".to_string()))
+    );
+    assert_eq!(walk_exclusion(&root, &root.join("src/main.rs")), None);
+}
+
+#[test]
+fn test_walk_exclusion_gitignore() {
+    let temp_dir = get_test_dir();
+    let root = temp_dir.path().canonicalize().unwrap();
+    std::fs::create_dir_all(root.join("sub/target")).unwrap();
+    std::fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+    std::fs::write(root.join("sub/.gitignore"), "!keep.log\n").unwrap();
+    let target = root.join("sub/target/out.txt");
+    std::fs::write(&target, "").unwrap();
+
+    // .gitignore files are only used in git repositories
+    assert_eq!(walk_exclusion(&root, &target), None);
+
+    std::fs::create_dir(root.join(".git")).unwrap();
+    assert_eq!(
+        walk_exclusion(&root, &target),
+        Some(WalkExclusion::Ignored {
+            ignore_file: root.join(".gitignore"),
+            pattern: "target/".to_string(),
+        })
+    );
+    assert!(walk_exclusion(&root, &root.join("debug.log")).is_some());
+    // Patterns in deeper directories win
+    assert_eq!(walk_exclusion(&root, &root.join("sub/keep.log")), None);
+}
+
+#[test]
+fn test_walk_exclusion_ignore_file() {
+    let temp_dir = get_test_dir();
+    let root = temp_dir.path().canonicalize().unwrap();
+    std::fs::write(root.join(".ignore"), "generated.md\n").unwrap();
+
+    assert_eq!(
+        walk_exclusion(&root, &root.join("docs/generated.md")),
+        Some(WalkExclusion::Ignored {
+            ignore_file: root.join(".ignore"),
+            pattern: "generated.md".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_foreign_files_are_not_skipped() {
+    let mut local = LocalIgnore::default();
+    local.patterns = ["*.md".to_string()].into();
+    let skip_file = SkipFile::new(Path::new("."), &local).unwrap();
+    let temp_dir = get_test_dir();
+    let foreign = RelativePath::from_path_unchecked(temp_dir.path().join("notes.md"));
+
+    assert_eq!(skip_file.skip_rule(&foreign), None);
+}
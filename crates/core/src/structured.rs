@@ -0,0 +1,564 @@
+//! Find the string values of JSON, YAML and TOML files, so that their keys
+//! are not checked when `string_values_only` is set
+use anyhow::{anyhow, bail, Result};
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuredFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl StructuredFormat {
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+        match extension {
+            "json" => Some(StructuredFormat::Json),
+            "yaml" | "yml" => Some(StructuredFormat::Yaml),
+            "toml" => Some(StructuredFormat::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StructuredFormat::Json => "JSON",
+            StructuredFormat::Yaml => "YAML",
+            StructuredFormat::Toml => "TOML",
+        }
+    }
+}
+
+/// The byte ranges of the string values in `text`, line by line: the first
+/// item is for line 1, and the ranges start at the beginning of the line.
+/// Fails if `text` cannot be parsed
+pub fn string_values(format: StructuredFormat, text: &str) -> Result<Vec<Vec<Range<usize>>>> {
+    let ranges = match format {
+        StructuredFormat::Json => json_values(text)?,
+        StructuredFormat::Yaml => yaml_values(text)?,
+        StructuredFormat::Toml => toml_values(text)?,
+    };
+    Ok(split_lines(text, ranges))
+}
+
+// `ranges` must be sorted and must not overlap
+fn split_lines(text: &str, ranges: Vec<Range<usize>>) -> Vec<Vec<Range<usize>>> {
+    let mut res = vec![];
+    let mut ranges = ranges.into_iter().peekable();
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        let mut line_ranges = vec![];
+        while let Some(range) = ranges.peek() {
+            if range.start >= line_end {
+                break;
+            }
+            let start = range.start.max(line_start);
+            let end = range.end.min(line_end);
+            if start < end {
+                line_ranges.push(start - line_start..end - line_start);
+            }
+            if range.end > line_end {
+                // Continues on the next line
+                break;
+            }
+            ranges.next();
+        }
+        res.push(line_ranges);
+        line_start = line_end;
+    }
+    res
+}
+
+// Multi-line delimiters first
+const TOML_DELIMITERS: [&[u8]; 4] = [b"\"\"\"", b"'''", b"\"", b"'"];
+
+fn json_values(text: &str) -> Result<Vec<Range<usize>>> {
+    serde_json::from_str::<serde_json::Value>(text)?;
+    let bytes = text.as_bytes();
+    let mut res = vec![];
+    // For each enclosing object or array: whether it's an object
+    let mut in_object = vec![];
+    let mut expect_key = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let end = closing_quote(bytes, i + 1, b'"').unwrap_or(bytes.len());
+                if !expect_key {
+                    res.push(i + 1..end);
+                }
+                i = end;
+            }
+            b'{' => {
+                in_object.push(true);
+                expect_key = true;
+            }
+            b'[' => {
+                in_object.push(false);
+                expect_key = false;
+            }
+            b'}' | b']' => {
+                in_object.pop();
+                expect_key = false;
+            }
+            b',' => expect_key = in_object.last() == Some(&true),
+            b':' => expect_key = false,
+            _ => (),
+        }
+        i += 1;
+    }
+    Ok(res)
+}
+
+fn toml_values(text: &str) -> Result<Vec<Range<usize>>> {
+    text.parse::<toml::Table>()?;
+    let bytes = text.as_bytes();
+    let mut res = vec![];
+    // For each enclosing array or inline table: whether it's a table
+    let mut in_table = vec![];
+    let mut expect_key = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let delimiter = TOML_DELIMITERS
+            .into_iter()
+            .find(|d| bytes[i..].starts_with(d));
+        if let Some(delimiter) = delimiter {
+            let start = i + delimiter.len();
+            let mut end = match delimiter {
+                b"\"" => closing_quote(bytes, start, b'"'),
+                b"\"\"\"" => closing_quote_triple(bytes, start),
+                // Literal strings have no escapes
+                _ => find(bytes, start, delimiter),
+            }
+            .unwrap_or(bytes.len());
+            if delimiter.len() == 3 {
+                // Up to two quotes can be right before the closing delimiter
+                for _ in 0..2 {
+                    if bytes.get(end + 3) == Some(&delimiter[0]) {
+                        end += 1;
+                    }
+                }
+            }
+            if !expect_key {
+                res.push(start..end);
+            }
+            i = end + delimiter.len();
+            continue;
+        }
+        match bytes[i] {
+            b'#' => {
+                i = line_end(bytes, i);
+                continue;
+            }
+            b'\n' if in_table.is_empty() => expect_key = true,
+            b'[' if expect_key && in_table.is_empty() => {
+                // Table header
+                i = line_end(bytes, i);
+                continue;
+            }
+            b'[' | b'{' => {
+                in_table.push(bytes[i] == b'{');
+                expect_key = bytes[i] == b'{';
+            }
+            b']' | b'}' => {
+                in_table.pop();
+                expect_key = false;
+            }
+            b',' => expect_key = in_table.last() == Some(&true),
+            b'=' => expect_key = false,
+            _ => (),
+        }
+        i += 1;
+    }
+    Ok(res)
+}
+
+// Position of the closing quote of a string starting at `start`, where
+// quotes can be escaped with a backslash
+fn closing_quote(bytes: &[u8], start: usize, quote: u8) -> Option<usize> {
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b == quote => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn closing_quote_triple(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' if bytes[i..].starts_with(b"\"\"\"") => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn find(bytes: &[u8], start: usize, needle: &[u8]) -> Option<usize> {
+    bytes[start..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| start + p)
+}
+
+fn line_end(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |p| start + p)
+}
+
+fn yaml_values(text: &str) -> Result<Vec<Range<usize>>> {
+    let mut scanner = YamlScanner::default();
+    let mut offset = 0;
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches(['\n', '\r']);
+        scanner
+            .scan_line(content, offset)
+            .map_err(|e| anyhow!("line {}: {e}", index + 1))?;
+        offset += line.len();
+    }
+    if scanner.quote.is_some() {
+        bail!("unterminated quoted string");
+    }
+    if !scanner.flow.is_empty() {
+        bail!("unterminated flow collection");
+    }
+    Ok(scanner.res)
+}
+
+#[derive(Debug)]
+struct FlowCollection {
+    is_mapping: bool,
+    expect_key: bool,
+}
+
+/// A line-based YAML scanner - just enough to tell keys from values,
+/// without building the document
+#[derive(Debug, Default)]
+struct YamlScanner {
+    res: Vec<Range<usize>>,
+    // Column of the node whose block scalar (| or >) we are in
+    block_scalar: Option<usize>,
+    // Column of the node whose plain scalar may continue on the next line
+    plain_scalar: Option<usize>,
+    // Quote of the string continuing on the next line
+    quote: Option<u8>,
+    // Enclosing flow collections ([] or {})
+    flow: Vec<FlowCollection>,
+}
+
+impl YamlScanner {
+    fn push(&mut self, offset: usize, start: usize, end: usize) {
+        if start < end {
+            self.res.push(offset + start..offset + end);
+        }
+    }
+
+    fn scan_line(&mut self, line: &str, offset: usize) -> Result<()> {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let blank = line.trim().is_empty();
+        let is_comment = line[indent..].starts_with('#');
+
+        if let Some(column) = self.block_scalar {
+            if blank {
+                return Ok(());
+            }
+            if indent > column {
+                self.push(offset, indent, line.len());
+                return Ok(());
+            }
+            self.block_scalar = None;
+        }
+
+        if self.quote.is_some() {
+            let pos = self.scan_quoted(line, offset, indent);
+            if self.quote.is_none() && !self.flow.is_empty() {
+                return self.scan_flow(line, offset, pos);
+            }
+            return Ok(());
+        }
+
+        if !self.flow.is_empty() {
+            return self.scan_flow(line, offset, indent);
+        }
+
+        if let Some(column) = self.plain_scalar {
+            if blank {
+                return Ok(());
+            }
+            if indent > column && !is_comment {
+                let end = plain_end(line, indent);
+                self.push(offset, indent, end);
+                return Ok(());
+            }
+            self.plain_scalar = None;
+        }
+
+        if blank || is_comment || line.starts_with('%') {
+            return Ok(());
+        }
+        if is_marker(line, "---") || is_marker(line, "...") {
+            return Ok(());
+        }
+
+        let mut pos = indent;
+        let mut column = indent;
+        // Sequence entries, maybe nested like in '- - foo'
+        while is_marker(&line[pos..], "-") {
+            column = pos;
+            pos = skip_spaces(line, pos + 1);
+        }
+        if pos == line.len() || is_marker(&line[pos..], "?") {
+            // Complex keys are not checked
+            return Ok(());
+        }
+        if let Some(value) = mapping_value(line, pos)? {
+            column = pos;
+            pos = value;
+        }
+        self.scan_value(line, offset, pos, column)
+    }
+
+    // Scan the value starting at `pos`, in the node starting at `column`
+    fn scan_value(&mut self, line: &str, offset: usize, pos: usize, column: usize) -> Result<()> {
+        let bytes = line.as_bytes();
+        let mut pos = pos;
+        // Anchors and tags
+        while pos < bytes.len() && matches!(bytes[pos], b'&' | b'!') {
+            pos = skip_spaces(line, token_end(line, pos));
+        }
+        if pos == bytes.len() {
+            return Ok(());
+        }
+        match bytes[pos] {
+            // Aliases and comments
+            b'*' | b'#' => (),
+            b'|' | b'>' => self.block_scalar = Some(column),
+            b'"' | b'\'' => {
+                self.quote = Some(bytes[pos]);
+                self.scan_quoted(line, offset, pos + 1);
+            }
+            b'[' | b'{' => self.scan_flow(line, offset, pos)?,
+            _ => {
+                let end = plain_end(line, pos);
+                self.push(offset, pos, end);
+                self.plain_scalar = Some(column);
+            }
+        }
+        Ok(())
+    }
+
+    // Scan the rest of a quoted string from `start`, and return the position
+    // after its closing quote - or the end of the line if it continues on
+    // the next one
+    fn scan_quoted(&mut self, line: &str, offset: usize, start: usize) -> usize {
+        let quote = match self.quote {
+            Some(quote) => quote,
+            None => return start,
+        };
+        match yaml_closing_quote(line, start, quote) {
+            Some(end) => {
+                self.push(offset, start, end);
+                self.quote = None;
+                end + 1
+            }
+            None => {
+                self.push(offset, start, line.len());
+                line.len()
+            }
+        }
+    }
+
+    fn expect_key(&self) -> bool {
+        self.flow.last().is_some_and(|f| f.expect_key)
+    }
+
+    // Scan the flow collections from `pos`, up to the end of the line or
+    // the end of the outermost collection
+    fn scan_flow(&mut self, line: &str, offset: usize, pos: usize) -> Result<()> {
+        let bytes = line.as_bytes();
+        let mut pos = pos;
+        while pos < bytes.len() {
+            let b = bytes[pos];
+            match b {
+                b' ' | b'\t' => pos += 1,
+                b'#' if pos == 0 || bytes[pos - 1] == b' ' => return Ok(()),
+                b'[' | b'{' => {
+                    self.flow.push(FlowCollection {
+                        is_mapping: b == b'{',
+                        expect_key: b == b'{',
+                    });
+                    pos += 1;
+                }
+                b']' | b'}' => {
+                    self.flow.pop();
+                    if self.flow.is_empty() {
+                        let rest = line[pos + 1..].trim_start();
+                        if !rest.is_empty() && !rest.starts_with('#') {
+                            bail!("unexpected text after '{}'", b as char);
+                        }
+                        return Ok(());
+                    }
+                    pos += 1;
+                }
+                b',' => {
+                    if let Some(last) = self.flow.last_mut() {
+                        last.expect_key = last.is_mapping;
+                    }
+                    pos += 1;
+                }
+                b':' => {
+                    if let Some(last) = self.flow.last_mut() {
+                        last.expect_key = false;
+                    }
+                    pos += 1;
+                }
+                b'"' | b'\'' => {
+                    let is_key = self.expect_key();
+                    match yaml_closing_quote(line, pos + 1, b) {
+                        Some(end) => {
+                            if !is_key {
+                                self.push(offset, pos + 1, end);
+                            }
+                            pos = end + 1;
+                        }
+                        None if is_key => bail!("multi-line keys are not supported"),
+                        None => {
+                            self.quote = Some(b);
+                            self.scan_quoted(line, offset, pos + 1);
+                            return Ok(());
+                        }
+                    }
+                }
+                b'&' | b'!' | b'*' => pos = token_end(line, pos),
+                _ => {
+                    let end = flow_plain_end(line, pos);
+                    if !self.expect_key() {
+                        self.push(offset, pos, end);
+                    }
+                    pos = end.max(pos + 1);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// If a key starts at `pos`, return where its value starts
+fn mapping_value(line: &str, pos: usize) -> Result<Option<usize>> {
+    let bytes = line.as_bytes();
+    let colon = match bytes[pos] {
+        b'"' | b'\'' => {
+            let end = match yaml_closing_quote(line, pos + 1, bytes[pos]) {
+                Some(end) => end,
+                // A multi-line string, which is not a key
+                None => return Ok(None),
+            };
+            let colon = skip_spaces(line, end + 1);
+            if !is_indicator(bytes, colon, b':') {
+                return Ok(None);
+            }
+            colon
+        }
+        b'[' | b'{' | b'|' | b'>' | b'*' => return Ok(None),
+        _ => {
+            let mut colon = None;
+            for i in pos..bytes.len() {
+                if bytes[i] == b'#' && i > 0 && bytes[i - 1] == b' ' {
+                    break;
+                }
+                if is_indicator(bytes, i, b':') {
+                    colon = Some(i);
+                    break;
+                }
+            }
+            match colon {
+                Some(colon) => colon,
+                None => return Ok(None),
+            }
+        }
+    };
+    Ok(Some(skip_spaces(line, colon + 1)))
+}
+
+// Whether `indicator` is at `pos`, followed by a space or the end of the line
+fn is_indicator(bytes: &[u8], pos: usize, indicator: u8) -> bool {
+    bytes.get(pos) == Some(&indicator) && matches!(bytes.get(pos + 1), None | Some(b' ' | b'\t'))
+}
+
+fn is_marker(text: &str, marker: &str) -> bool {
+    match text.strip_prefix(marker) {
+        Some(rest) => rest.is_empty() || rest.starts_with([' ', '\t']),
+        None => false,
+    }
+}
+
+fn skip_spaces(line: &str, pos: usize) -> usize {
+    line.len() - line[pos..].trim_start_matches([' ', '\t']).len()
+}
+
+fn token_end(line: &str, pos: usize) -> usize {
+    line[pos..]
+        .find([' ', '\t', ',', ']', '}'])
+        .map_or(line.len(), |p| pos + p)
+}
+
+// End of a plain scalar in block context: before a comment or the end
+// of the line, without trailing spaces
+fn plain_end(line: &str, pos: usize) -> usize {
+    let end = line[pos..].find(" #").map_or(line.len(), |p| pos + p);
+    line[..end].trim_end().len()
+}
+
+// Same as plain_end(), also stopping at the indicators of flow collections
+fn flow_plain_end(line: &str, pos: usize) -> usize {
+    let bytes = line.as_bytes();
+    let mut end = bytes.len();
+    for i in pos..bytes.len() {
+        let stop = match bytes[i] {
+            b',' | b'[' | b']' | b'{' | b'}' => true,
+            b':' => matches!(
+                bytes.get(i + 1),
+                None | Some(b' ' | b'\t' | b',' | b']' | b'}')
+            ),
+            b'#' => i > 0 && bytes[i - 1] == b' ',
+            _ => false,
+        };
+        if stop {
+            end = i;
+            break;
+        }
+    }
+    line[..end].trim_end().len()
+}
+
+// Double-quoted strings use backslash escapes, single-quoted ones use ''
+fn yaml_closing_quote(line: &str, start: usize, quote: u8) -> Option<usize> {
+    if quote == b'"' {
+        return closing_quote(line.as_bytes(), start, quote);
+    }
+    let bytes = line.as_bytes();
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i] == b'\'' {
+            if bytes.get(i + 1) == Some(&b'\'') {
+                i += 2;
+                continue;
+            }
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,165 @@
+use super::*;
+use crate::TokenProcessor;
+
+// Tokens with their line and column in chars
+fn value_tokens(file_name: &str, contents: &str) -> Vec<(String, usize, usize)> {
+    let format = StructuredFormat::from_file_name(file_name).unwrap();
+    let values = string_values(format, contents).unwrap();
+    let mut processor = TokenProcessor::new(contents.as_bytes(), file_name);
+    processor.set_string_values(values);
+    processor
+        .map(|token| {
+            let token = token.unwrap();
+            (token.text, token.pos.0, token.columns.char)
+        })
+        .collect()
+}
+
+fn assert_tokens(actual: Vec<(String, usize, usize)>, expected: &[(&str, usize, usize)]) {
+    let actual: Vec<_> = actual
+        .iter()
+        .map(|(text, line, column)| (text.as_str(), *line, *column))
+        .collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_format_from_file_name() {
+    assert_eq!(
+        StructuredFormat::from_file_name("en.json"),
+        Some(StructuredFormat::Json)
+    );
+    assert_eq!(
+        StructuredFormat::from_file_name("strings.yml"),
+        Some(StructuredFormat::Yaml)
+    );
+    assert_eq!(
+        StructuredFormat::from_file_name("Cargo.toml"),
+        Some(StructuredFormat::Toml)
+    );
+    assert_eq!(StructuredFormat::from_file_name("README.md"), None);
+    assert_eq!(StructuredFormat::from_file_name("json"), None);
+}
+
+#[test]
+fn test_json_values() {
+    let actual = value_tokens("nested.json", include_str!("fixtures/nested.json"));
+
+    assert_tokens(
+        actual,
+        &[
+            ("Hello", 2, 15),
+            ("wrold", 2, 21),
+            ("Open", 4, 20),
+            ("fille", 4, 25),
+            ("Close", 4, 34),
+            ("Savve", 5, 26),
+            ("changes", 5, 32),
+            ("Crème", 7, 16),
+            ("brulée", 7, 22),
+        ],
+    );
+}
+
+#[test]
+fn test_yaml_values() {
+    let actual = value_tokens("strings.yaml", include_str!("fixtures/strings.yaml"));
+
+    assert_tokens(
+        actual,
+        &[
+            ("Welcom", 2, 7),
+            ("home", 2, 14),
+            ("Open", 4, 12),
+            ("fille", 4, 17),
+            ("ctrl", 5, 18),
+            ("o", 5, 23),
+            ("Quoted", 6, 5),
+            ("itemm", 6, 12),
+            // Block scalar
+            ("First", 8, 2),
+            ("linne", 8, 8),
+            ("of", 8, 14),
+            ("the", 8, 17),
+            ("block", 8, 21),
+            ("second", 9, 2),
+            ("line", 9, 9),
+            ("Folded", 12, 2),
+            ("texxt", 12, 9),
+            // Multi-line plain scalar
+            ("a", 13, 12),
+            ("plain", 13, 14),
+            ("scalar", 13, 20),
+            ("that", 14, 2),
+            ("continues", 14, 7),
+            ("herre", 14, 17),
+            // Multi-line quoted string
+            ("a", 15, 14),
+            ("double", 15, 16),
+            ("quoted", 15, 23),
+            ("string", 16, 2),
+            ("spanning", 16, 9),
+            ("linnes", 16, 18),
+            // Flow collections
+            ("alpha", 17, 12),
+            ("betta", 17, 19),
+            ("valuue", 18, 23),
+            ("singel", 18, 43),
+            ("quoted", 18, 50),
+            ("anchored", 19, 27),
+            ("valuue", 19, 36),
+        ],
+    );
+}
+
+#[test]
+fn test_toml_values() {
+    let actual = value_tokens("config.toml", include_str!("fixtures/config.toml"));
+
+    assert_tokens(
+        actual,
+        &[
+            ("Welcom", 2, 9),
+            ("home", 2, 16),
+            ("valuue", 3, 16),
+            ("Multi", 7, 0),
+            ("linne", 7, 6),
+            ("message", 8, 0),
+            ("literal", 9, 15),
+            ("strinng", 9, 23),
+            ("alpha", 11, 3),
+            ("betta", 12, 3),
+            ("inline", 14, 29),
+            ("valuue", 14, 36),
+            ("literall", 14, 58),
+        ],
+    );
+}
+
+#[test]
+fn test_invalid_json() {
+    let actual = string_values(StructuredFormat::Json, "{\"key\": ");
+
+    assert!(actual.is_err());
+}
+
+#[test]
+fn test_invalid_toml() {
+    let actual = string_values(StructuredFormat::Toml, "key = \"value");
+
+    assert!(actual.is_err());
+}
+
+#[test]
+fn test_unterminated_yaml_string() {
+    let err = string_values(StructuredFormat::Yaml, "key: \"value\nother: value\n").unwrap_err();
+
+    assert_eq!(err.to_string(), "unterminated quoted string");
+}
+
+#[test]
+fn test_unexpected_yaml_bracket() {
+    let err = string_values(StructuredFormat::Yaml, "key: value\nlist: [a]]\n").unwrap_err();
+
+    assert_eq!(err.to_string(), "line 2: unexpected text after ']'");
+}
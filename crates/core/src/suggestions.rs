@@ -0,0 +1,229 @@
+//! Some providers take seconds to suggest replacements for a word, so
+//! suggestions are computed on a worker thread that callers never wait for
+//! longer than they asked to
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::Dictionary;
+
+/// Maximum number of split forms added by suggest_replacements()
+const MAX_SPLIT_SUGGESTIONS: usize = 3;
+
+/// Each half of a split form is at least this number of characters long
+const MIN_SPLIT_PART_LENGTH: usize = 2;
+
+/// Maximum number of replies kept for words that were not asked for yet -
+/// the oldest ones are dropped first
+const MAX_PENDING_REPLIES: usize = 100;
+
+/// Suggestions from the dictionary, after the ways to split `word` in two
+/// known words, like "this is" for "thisis", which providers often miss.
+/// See clean_suggestions() for the ones left out
+pub fn suggest_replacements<D: Dictionary + ?Sized>(
+    dictionary: &D,
+    word: &str,
+    forbidden: &HashSet<String>,
+) -> Result<Vec<String>> {
+    let mut res = split_suggestions(dictionary, word)?;
+    res.extend(dictionary.suggest(word)?);
+    Ok(clean_suggestions(word, res, forbidden))
+}
+
+/// Remove from `suggestions`, keeping the order of the others:
+///  - `word` itself, whatever its case, since some providers return it
+///  - the forbidden words, see IgnoreStore::forbidden_words()
+///  - the duplicates once the case is ignored: the variant capitalized
+///    like `word` wins, at the rank of the best ranked variant
+pub fn clean_suggestions(
+    word: &str,
+    suggestions: Vec<String>,
+    forbidden: &HashSet<String>,
+) -> Vec<String> {
+    let lower_word = word.to_lowercase();
+    let capitalized = is_capitalized(word);
+    let mut res: Vec<String> = vec![];
+    for suggestion in suggestions {
+        let lower = suggestion.to_lowercase();
+        if lower == lower_word || forbidden.contains(&suggestion) || forbidden.contains(&lower) {
+            continue;
+        }
+        match res.iter_mut().find(|s| s.to_lowercase() == lower) {
+            None => res.push(suggestion),
+            Some(kept) => {
+                if is_capitalized(kept) != capitalized && is_capitalized(&suggestion) == capitalized
+                {
+                    *kept = suggestion;
+                }
+            }
+        }
+    }
+    res
+}
+
+fn is_capitalized(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_uppercase)
+}
+
+/// The ways to split `word` in two known words, the most balanced first
+fn split_suggestions<D: Dictionary + ?Sized>(dictionary: &D, word: &str) -> Result<Vec<String>> {
+    if word.contains(char::is_whitespace) {
+        return Ok(vec![]);
+    }
+    let indexes: Vec<_> = word.char_indices().map(|(i, _)| i).collect();
+    let length = indexes.len();
+    let mut splits = vec![];
+    for (left_length, &index) in indexes.iter().enumerate() {
+        if left_length < MIN_SPLIT_PART_LENGTH || length - left_length < MIN_SPLIT_PART_LENGTH {
+            continue;
+        }
+        let (left, right) = word.split_at(index);
+        if dictionary.check(left)? && dictionary.check(right)? {
+            let balance = left_length.abs_diff(length - left_length);
+            splits.push((balance, format!("{left} {right}")));
+        }
+    }
+    // Stable, so splits as balanced as each other stay in order
+    splits.sort_by_key(|(balance, _)| *balance);
+    Ok(splits
+        .into_iter()
+        .take(MAX_SPLIT_SUGGESTIONS)
+        .map(|(_, split)| split)
+        .collect())
+}
+
+struct Request {
+    word: String,
+    reply: Sender<Vec<String>>,
+}
+
+/// Computes suggestions on a single thread, reused for all the words,
+/// and stopped when the worker is dropped
+pub struct SuggestionWorker {
+    requests: Option<Sender<Request>>,
+    thread: Option<JoinHandle<()>>,
+    // Set when dropped, so that the queued requests are skipped
+    stopped: Arc<AtomicBool>,
+    // Replies for words that were prefetched but not asked for yet,
+    // oldest first
+    pending: RefCell<VecDeque<(String, Receiver<Vec<String>>)>>,
+    // Known by the caller only, so filtered out of the replies
+    forbidden: HashSet<String>,
+}
+
+impl SuggestionWorker {
+    /// The dictionary is opened by the worker thread, so that
+    /// it does not need to be Send
+    pub fn new<D, F>(open_dictionary: F) -> Self
+    where
+        D: Dictionary,
+        F: FnOnce() -> Result<D> + Send + 'static,
+    {
+        let (requests, receiver) = mpsc::channel::<Request>();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+        let thread = std::thread::spawn(move || {
+            let dictionary = match open_dictionary() {
+                Ok(d) => d,
+                // Every request will get an empty reply
+                Err(_) => return,
+            };
+            for Request { word, reply } in receiver {
+                if thread_stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                let suggestions =
+                    suggest_replacements(&dictionary, &word, &HashSet::new()).unwrap_or_default();
+                // The caller may have given up waiting
+                let _ = reply.send(suggestions);
+            }
+        });
+        Self {
+            requests: Some(requests),
+            thread: Some(thread),
+            stopped,
+            pending: RefCell::new(VecDeque::new()),
+            forbidden: HashSet::new(),
+        }
+    }
+
+    /// Never suggest these words, see IgnoreStore::forbidden_words()
+    pub fn set_forbidden_words(&mut self, forbidden: HashSet<String>) {
+        self.forbidden = forbidden;
+    }
+
+    fn request(&self, word: &str) -> Receiver<Vec<String>> {
+        let (reply, receiver) = mpsc::channel();
+        let request = Request {
+            word: word.to_string(),
+            reply,
+        };
+        if let Some(requests) = &self.requests {
+            // If the worker is gone, the reply sender is dropped and
+            // waiting for the reply returns immediately
+            let _ = requests.send(request);
+        }
+        receiver
+    }
+
+    fn add_pending(&self, word: &str, receiver: Receiver<Vec<String>>) {
+        let mut pending = self.pending.borrow_mut();
+        if pending.len() >= MAX_PENDING_REPLIES {
+            pending.pop_front();
+        }
+        pending.push_back((word.to_string(), receiver));
+    }
+
+    fn take_pending(&self, word: &str) -> Option<Receiver<Vec<String>>> {
+        let mut pending = self.pending.borrow_mut();
+        let index = pending.iter().position(|(w, _)| w == word)?;
+        pending.remove(index).map(|(_, receiver)| receiver)
+    }
+
+    /// Start computing suggestions for `word` without waiting for them
+    pub fn prefetch(&self, word: &str) {
+        let is_pending = self.pending.borrow().iter().any(|(w, _)| w == word);
+        if !is_pending {
+            self.add_pending(word, self.request(word));
+        }
+    }
+
+    /// Suggestions for `word`, or an empty list if they could not be
+    /// computed within `timeout`
+    pub fn suggest_with_timeout(&self, word: &str, timeout: Duration) -> Vec<String> {
+        let receiver = self
+            .take_pending(word)
+            .unwrap_or_else(|| self.request(word));
+        match receiver.recv_timeout(timeout) {
+            Ok(suggestions) => clean_suggestions(word, suggestions, &self.forbidden),
+            Err(RecvTimeoutError::Timeout) => {
+                // Keep the request around, the next call may get the reply
+                self.add_pending(word, receiver);
+                vec![]
+            }
+            Err(RecvTimeoutError::Disconnected) => vec![],
+        }
+    }
+}
+
+impl Drop for SuggestionWorker {
+    fn drop(&mut self) {
+        self.pending.borrow_mut().clear();
+        // The worker skips the queued requests, and stops once it's done
+        // with the current word
+        self.stopped.store(true, Ordering::Relaxed);
+        self.requests.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
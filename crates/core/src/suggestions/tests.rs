@@ -0,0 +1,279 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+use std::time::Instant;
+
+use super::*;
+
+use crate::tests::FakeDictionary;
+
+type Threads = Arc<Mutex<Vec<ThreadId>>>;
+
+// Counts calls to suggest(), and takes its time for words starting with "slow"
+struct SlowDictionary {
+    calls: Arc<AtomicUsize>,
+    threads: Threads,
+}
+
+impl Dictionary for SlowDictionary {
+    fn check(&self, _word: &str) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn suggest(&self, error: &str) -> Result<Vec<String>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.threads
+            .lock()
+            .unwrap()
+            .push(std::thread::current().id());
+        if error.starts_with("slow") {
+            std::thread::sleep(Duration::from_millis(300));
+        }
+        Ok(vec![format!("{error}!")])
+    }
+
+    fn lang(&self) -> &str {
+        "en_US"
+    }
+
+    fn provider(&self) -> &str {
+        "slow"
+    }
+}
+
+fn slow_worker() -> (SuggestionWorker, Arc<AtomicUsize>, Threads) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let threads = Arc::new(Mutex::new(vec![]));
+    let dictionary = SlowDictionary {
+        calls: calls.clone(),
+        threads: threads.clone(),
+    };
+    (
+        SuggestionWorker::new(move || Ok(dictionary)),
+        calls,
+        threads,
+    )
+}
+
+#[test]
+fn test_suggest_with_timeout() {
+    let worker = SuggestionWorker::new(|| {
+        let mut dictionary = FakeDictionary::new();
+        dictionary.add_suggestions("missstake", &["mistake".to_string()]);
+        Ok(dictionary)
+    });
+
+    let suggestions = worker.suggest_with_timeout("missstake", Duration::from_secs(5));
+
+    assert_eq!(suggestions, &["mistake"]);
+}
+
+#[test]
+fn test_timeout_returns_empty_suggestions() {
+    let (worker, _, _) = slow_worker();
+
+    let start = Instant::now();
+    let suggestions = worker.suggest_with_timeout("slowword", Duration::from_millis(10));
+
+    assert!(suggestions.is_empty());
+    assert!(start.elapsed() < Duration::from_millis(250));
+    // The reply is not lost
+    let suggestions = worker.suggest_with_timeout("slowword", Duration::from_secs(5));
+    assert_eq!(suggestions, &["slowword!"]);
+}
+
+#[test]
+fn test_prefetch() {
+    let (worker, calls, _) = slow_worker();
+
+    worker.prefetch("slowword");
+    worker.prefetch("slowword");
+    let suggestions = worker.suggest_with_timeout("slowword", Duration::from_secs(5));
+
+    assert_eq!(suggestions, &["slowword!"]);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_worker_thread_is_reused() {
+    let (worker, _, threads) = slow_worker();
+
+    for word in ["one", "two", "three"] {
+        worker.suggest_with_timeout(word, Duration::from_secs(5));
+    }
+    drop(worker);
+
+    let threads = threads.lock().unwrap();
+    assert_eq!(threads.len(), 3);
+    assert!(threads.iter().all(|t| *t == threads[0]));
+    assert_ne!(threads[0], std::thread::current().id());
+}
+
+#[test]
+fn test_dictionary_that_cannot_be_opened() {
+    let worker =
+        SuggestionWorker::new(|| -> Result<FakeDictionary> { anyhow::bail!("no such dictionary") });
+
+    let suggestions = worker.suggest_with_timeout("foo", Duration::from_secs(5));
+
+    assert!(suggestions.is_empty());
+}
+
+fn dictionary_with_known_words(words: &[&str]) -> FakeDictionary {
+    let mut dictionary = FakeDictionary::new();
+    for word in words {
+        dictionary.add_known(word);
+    }
+    dictionary
+}
+
+#[test]
+fn test_suggest_replacements_splits_joined_words() {
+    let mut dictionary = dictionary_with_known_words(&["this", "is", "in", "the"]);
+    dictionary.add_suggestions("thisis", &["thesis".to_string(), "this is".to_string()]);
+
+    let suggestions = suggest_replacements(&dictionary, "thisis", &HashSet::new()).unwrap();
+
+    assert_eq!(suggestions, &["this is", "thesis"]);
+}
+
+#[test]
+fn test_suggest_replacements_without_split() {
+    let mut dictionary = dictionary_with_known_words(&["a", "pple", "mistake"]);
+    dictionary.add_suggestions("apple", &["ample".to_string()]);
+    dictionary.add_suggestions("missstake", &["mistake".to_string()]);
+
+    // Both halves must be at least 2 characters long
+    assert_eq!(
+        suggest_replacements(&dictionary, "apple", &HashSet::new()).unwrap(),
+        &["ample"]
+    );
+    assert_eq!(
+        suggest_replacements(&dictionary, "missstake", &HashSet::new()).unwrap(),
+        &["mistake"]
+    );
+}
+
+#[test]
+fn test_most_balanced_splits_first() {
+    let dictionary = dictionary_with_known_words(&["ab", "cdefgh", "abc", "defgh", "abcd", "efgh"]);
+
+    let suggestions = suggest_replacements(&dictionary, "abcdefgh", &HashSet::new()).unwrap();
+
+    assert_eq!(suggestions, &["abcd efgh", "abc defgh", "ab cdefgh"]);
+}
+
+#[test]
+fn test_at_most_three_splits() {
+    let dictionary = dictionary_with_known_words(&[
+        "ab", "cdefgh", "abc", "defgh", "abcd", "efgh", "abcde", "fgh",
+    ]);
+
+    let suggestions = suggest_replacements(&dictionary, "abcdefgh", &HashSet::new()).unwrap();
+
+    assert_eq!(suggestions, &["abcd efgh", "abc defgh", "abcde fgh"]);
+}
+
+#[test]
+fn test_original_word_is_never_suggested() {
+    let mut dictionary = FakeDictionary::new();
+    dictionary.add_suggestions(
+        "Teh",
+        &["teh".to_string(), "the".to_string(), "Teh".to_string()],
+    );
+
+    let suggestions = suggest_replacements(&dictionary, "Teh", &HashSet::new()).unwrap();
+
+    assert_eq!(suggestions, &["the"]);
+}
+
+#[test]
+fn test_forbidden_words_are_never_suggested() {
+    let mut dictionary = FakeDictionary::new();
+    dictionary.add_suggestions(
+        "cancelled",
+        &[
+            "canceled".to_string(),
+            "Canceled".to_string(),
+            "cancels".to_string(),
+        ],
+    );
+    let forbidden = HashSet::from(["canceled".to_string()]);
+
+    let suggestions = suggest_replacements(&dictionary, "cancelled", &forbidden).unwrap();
+
+    assert_eq!(suggestions, &["cancels"]);
+}
+
+#[test]
+fn test_case_duplicates_keep_the_capitalization_of_the_word() {
+    let suggestions = |word: &str| {
+        let candidates = ["the", "The", "then", "THE", "Then"];
+        let candidates = candidates.iter().map(|s| s.to_string()).collect();
+        clean_suggestions(word, candidates, &HashSet::new())
+    };
+
+    assert_eq!(suggestions("teh"), &["the", "then"]);
+    // The best ranked variant gives its place to the capitalized one
+    assert_eq!(suggestions("Teh"), &["The", "Then"]);
+}
+
+#[test]
+fn test_split_suggestions_are_not_repeated() {
+    let mut dictionary = dictionary_with_known_words(&["this", "is"]);
+    dictionary.add_suggestions("thisis", &["This is".to_string(), "this is".to_string()]);
+
+    let suggestions = suggest_replacements(&dictionary, "thisis", &HashSet::new()).unwrap();
+
+    assert_eq!(suggestions, &["this is"]);
+}
+
+#[test]
+fn test_worker_never_suggests_forbidden_words() {
+    let mut worker = SuggestionWorker::new(|| {
+        let mut dictionary = FakeDictionary::new();
+        dictionary.add_suggestions("teh", &["teh".to_string(), "the".to_string()]);
+        dictionary.add_suggestions("thn", &["then".to_string(), "than".to_string()]);
+        Ok(dictionary)
+    });
+    worker.set_forbidden_words(HashSet::from(["than".to_string()]));
+
+    assert_eq!(
+        worker.suggest_with_timeout("teh", Duration::from_secs(5)),
+        &["the"]
+    );
+    assert_eq!(
+        worker.suggest_with_timeout("thn", Duration::from_secs(5)),
+        &["then"]
+    );
+}
+
+#[test]
+fn test_pending_replies_are_bounded() {
+    let (worker, _, _) = slow_worker();
+
+    for i in 0..MAX_PENDING_REPLIES + 10 {
+        worker.prefetch(&format!("word{i}"));
+    }
+
+    let pending = worker.pending.borrow();
+    assert_eq!(pending.len(), MAX_PENDING_REPLIES);
+    assert_eq!(pending[0].0, "word10");
+}
+
+#[test]
+fn test_drop_skips_queued_requests() {
+    let (worker, calls, _) = slow_worker();
+    for i in 0..10 {
+        worker.prefetch(&format!("slowword{i}"));
+    }
+    // Let the worker start on the first word
+    std::thread::sleep(Duration::from_millis(50));
+
+    let start = Instant::now();
+    drop(worker);
+
+    assert!(start.elapsed() < Duration::from_millis(1000));
+    // At most the word the worker was busy with
+    assert!(calls.load(Ordering::SeqCst) <= 1);
+}
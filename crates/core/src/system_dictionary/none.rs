@@ -0,0 +1,50 @@
+/// SystemDictionary when skyspell is built without the `enchant` feature:
+/// it cannot be created, and dictionaries come from registered providers
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+
+use crate::{Dictionary, DictionaryFactory};
+
+pub struct SystemDictionary {
+    never: Infallible,
+}
+
+impl SystemDictionary {
+    /// Must be called in main()
+    pub fn init() {}
+
+    pub fn new(lang: &str) -> Result<Self> {
+        bail!(
+            "Could not request dict for lang '{lang}': skyspell was built without Enchant support"
+        )
+    }
+
+    /// Return the (provider, lang) pairs for every dictionary installed
+    pub fn available_languages() -> Vec<(String, String)> {
+        vec![]
+    }
+}
+
+impl Dictionary for SystemDictionary {
+    fn check(&self, _word: &str) -> Result<bool> {
+        match self.never {}
+    }
+
+    fn suggest(&self, _error: &str) -> Result<Vec<String>> {
+        match self.never {}
+    }
+
+    fn lang(&self) -> &str {
+        match self.never {}
+    }
+
+    fn provider(&self) -> &str {
+        match self.never {}
+    }
+}
+
+pub(crate) fn builtin_factories() -> Vec<Arc<dyn DictionaryFactory>> {
+    vec![]
+}
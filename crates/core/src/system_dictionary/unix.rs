@@ -1,7 +1,12 @@
 /// Export a SystemDictionary that relies on Enchant Rust wrapper
-use anyhow::{anyhow, Result};
+use std::sync::Arc;
 
-use crate::Dictionary;
+use anyhow::{anyhow, bail, Result};
+
+use crate::{output, Dictionary, DictionaryFactory};
+
+// Enchant backends that can be selected with `--provider`
+const BACKENDS: [&str; 3] = ["aspell", "hunspell", "nuspell"];
 
 pub struct SystemDictionary {
     dict: enchant::Dict,
@@ -14,14 +19,59 @@ impl SystemDictionary {
 
     pub fn new(lang: &str) -> Result<Self> {
         let mut broker = enchant::Broker::new();
+        let error = match broker.request_dict(lang) {
+            Ok(dict) => {
+                return Ok(Self {
+                    dict,
+                    lang: lang.to_string(),
+                })
+            }
+            Err(e) => e,
+        };
+
+        // Fall back from, say, `en_GB` to `en`
+        if let Some((short_lang, _)) = lang.split_once(['_', '-']) {
+            if let Ok(dict) = broker.request_dict(short_lang) {
+                output::warning(&format!(
+                    "Warning: no dictionary found for '{lang}', using '{short_lang}' instead"
+                ));
+                return Ok(Self {
+                    dict,
+                    lang: short_lang.to_string(),
+                });
+            }
+        }
+
+        bail!("Could not request dict for lang '{lang}': {error}")
+    }
+
+    /// Like new(), but only use the given Enchant backend, like 'aspell'
+    pub fn with_backend(lang: &str, backend: &str) -> Result<Self> {
+        let mut broker = enchant::Broker::new();
+        broker.set_ordering(lang, backend);
         let dict = broker
             .request_dict(lang)
             .map_err(|e| anyhow!("Could not request dict for lang '{lang}': {e}"))?;
+        if dict.get_provider_name() != backend {
+            bail!("No {backend} dictionary found for lang '{lang}'");
+        }
         Ok(Self {
             dict,
             lang: lang.to_string(),
         })
     }
+
+    /// Return the (provider, lang) pairs for every dictionary installed
+    pub fn available_languages() -> Vec<(String, String)> {
+        let mut broker = enchant::Broker::new();
+        let mut res: Vec<_> = broker
+            .list_dicts()
+            .into_iter()
+            .map(|d| (d.provider.name, d.lang))
+            .collect();
+        res.sort();
+        res
+    }
 }
 
 impl Dictionary for SystemDictionary {
@@ -43,3 +93,40 @@ impl Dictionary for SystemDictionary {
         self.dict.get_provider_name()
     }
 }
+
+struct EnchantFactory {
+    // None means any backend
+    backend: Option<&'static str>,
+}
+
+impl DictionaryFactory for EnchantFactory {
+    fn name(&self) -> &str {
+        self.backend.unwrap_or("enchant")
+    }
+
+    fn create(&self, lang: &str) -> Result<Box<dyn Dictionary>> {
+        let dictionary = match self.backend {
+            None => SystemDictionary::new(lang)?,
+            Some(backend) => SystemDictionary::with_backend(lang, backend)?,
+        };
+        Ok(Box::new(dictionary))
+    }
+
+    fn available_languages(&self) -> Vec<String> {
+        SystemDictionary::available_languages()
+            .into_iter()
+            .filter(|(provider, _)| self.backend.is_none_or(|b| b == provider))
+            .map(|(_, lang)| lang)
+            .collect()
+    }
+}
+
+pub(crate) fn builtin_factories() -> Vec<Arc<dyn DictionaryFactory>> {
+    let mut res: Vec<Arc<dyn DictionaryFactory>> = vec![Arc::new(EnchantFactory { backend: None })];
+    for backend in BACKENDS {
+        res.push(Arc::new(EnchantFactory {
+            backend: Some(backend),
+        }));
+    }
+    res
+}
@@ -1,4 +1,6 @@
 /// Export a SystemDictionary that relies on Win32 Globalization API
+use std::sync::Arc;
+
 use anyhow::{bail, Result};
 use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
 use windows::Win32::System::Com::{CoTaskMemFree, CLSCTX_ALL};
@@ -10,7 +12,7 @@ use windows::{
     },
 };
 
-use crate::Dictionary;
+use crate::{Dictionary, DictionaryFactory};
 
 pub struct SystemDictionary {
     spell_checker: ISpellChecker,
@@ -90,3 +92,19 @@ impl Dictionary for SystemDictionary {
         "windows"
     }
 }
+
+struct WindowsFactory;
+
+impl DictionaryFactory for WindowsFactory {
+    fn name(&self) -> &str {
+        "windows"
+    }
+
+    fn create(&self, lang: &str) -> Result<Box<dyn Dictionary>> {
+        Ok(Box::new(SystemDictionary::new(lang)?))
+    }
+}
+
+pub(crate) fn builtin_factories() -> Vec<Arc<dyn DictionaryFactory>> {
+    vec![Arc::new(WindowsFactory)]
+}
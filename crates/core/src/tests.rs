@@ -10,6 +10,11 @@ pub mod fake_io;
 pub use fake_dictionary::FakeDictionary;
 pub use fake_io::FakeIO;
 
+/// A line where bytes, chars and UTF-16 code units differ: `fooo` starts
+/// at byte 23, char 12 and UTF-16 code unit 13, after an emoji, combining
+/// accents and CJK characters
+pub const NON_ASCII_LINE: &str = "👍 e\u{301}te\u{301} 日本語 fooo";
+
 pub fn new_project_path(temp_dir: &TempDir, name: &str) -> ProjectPath {
     let path = temp_dir.path().join(name);
     std::fs::create_dir_all(&path).unwrap();
@@ -29,7 +34,7 @@ pub(crate) fn get_test_dir() -> TempDir {
         .unwrap()
 }
 
-pub(crate) fn create_store(temp_dir: &TempDir, global: &str, local: &str) -> IgnoreStore {
+pub fn create_store(temp_dir: &TempDir, global: &str, local: &str) -> IgnoreStore {
     let temp_path = temp_dir.path();
     let global_toml = temp_path.join("global.toml");
     std::fs::write(&global_toml, global).unwrap();
@@ -1,12 +1,16 @@
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use crate::Dictionary;
 use anyhow::Result;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct FakeDictionary {
     known: Vec<String>,
     suggestions: HashMap<String, Vec<String>>,
+    // Shared by the clones, so that tests can look at the lookups made
+    // by a checker owning one of them
+    checked: Arc<Mutex<Vec<String>>>,
 }
 
 impl FakeDictionary {
@@ -22,10 +26,16 @@ impl FakeDictionary {
         self.suggestions
             .insert(error.to_string(), suggestions.to_vec());
     }
+
+    /// Every word passed to check() so far, in order
+    pub fn checked_words(&self) -> Vec<String> {
+        self.checked.lock().unwrap().clone()
+    }
 }
 
 impl Dictionary for FakeDictionary {
     fn check(&self, word: &str) -> Result<bool> {
+        self.checked.lock().unwrap().push(word.to_string());
         Ok(self.known.contains(&word.to_string()))
     }
 
@@ -49,6 +59,7 @@ fn test_fake_dictionary_check() {
 
     assert!(fake_dictionary.check("hello").unwrap());
     assert!(!fake_dictionary.check("foo").unwrap());
+    assert_eq!(fake_dictionary.checked_words(), ["hello", "foo"]);
 }
 
 #[test]
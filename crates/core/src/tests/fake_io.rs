@@ -1,5 +1,6 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Result};
 
@@ -8,6 +9,9 @@ use crate::OperatingSystemIO;
 pub struct FakeIO {
     env: HashMap<String, String>,
     stdout: RefCell<String>,
+    // Files written by write_temp_file() and not removed yet, by path
+    temp_files: RefCell<BTreeMap<PathBuf, String>>,
+    num_temp_files: RefCell<usize>,
 }
 
 impl Default for FakeIO {
@@ -21,6 +25,8 @@ impl FakeIO {
         Self {
             env: HashMap::new(),
             stdout: RefCell::new(String::new()),
+            temp_files: RefCell::new(BTreeMap::new()),
+            num_temp_files: RefCell::new(0),
         }
     }
 
@@ -28,6 +34,11 @@ impl FakeIO {
         self.stdout.borrow().to_string()
     }
 
+    /// The contents of the temporary files that still exist, by path
+    pub fn temp_files(&self) -> BTreeMap<PathBuf, String> {
+        self.temp_files.borrow().clone()
+    }
+
     pub fn set_env_var(&mut self, key: &str, value: &str) {
         self.env.insert(key.to_string(), value.to_string());
     }
@@ -45,4 +56,22 @@ impl OperatingSystemIO for FakeIO {
     fn print(&self, text: &str) {
         self.stdout.borrow_mut().push_str(text)
     }
+
+    fn write_temp_file(&self, contents: &str) -> Result<PathBuf> {
+        let mut num_temp_files = self.num_temp_files.borrow_mut();
+        *num_temp_files += 1;
+        let path = PathBuf::from(format!("/tmp/skyspell-{num_temp_files}"));
+        self.temp_files
+            .borrow_mut()
+            .insert(path.clone(), contents.to_string());
+        Ok(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        self.temp_files
+            .borrow_mut()
+            .remove(path)
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("No such file: {}", path.display()))
+    }
 }
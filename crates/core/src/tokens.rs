@@ -1,7 +1,8 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use regex::{Regex, RegexBuilder};
 use std::collections::HashSet;
 use std::io::BufRead;
+use std::ops::Range;
 
 const GIT_SCISSORS: &str = "# ------------------------ >8 ------------------------";
 
@@ -62,6 +63,12 @@ lazy_static! {
     ).ignore_whitespace(true).build().expect("syntax error in static regex");
 
 
+    // YAML front matter at the top of Markdown files
+    static ref FRONT_MATTER_BEGIN_RE: Regex = Regex::new(r"^---\s*$")
+        .expect("syntax error in static regex");
+    static ref FRONT_MATTER_END_RE: Regex = Regex::new(r"^(---|\.\.\.)\s*$")
+        .expect("syntax error in static regex");
+
     static ref IDENT_RE_LATEX: Regex = RegexBuilder::new(
         r"
         # Same as IDENT_RE, without handling \n, \r or \t
@@ -77,6 +84,8 @@ const PYTHON_STRING_PREFIXES: [&str; 24] = [
     "b'", "B'", "br'", "Br'", "bR'", "BR'", "rb'", "rB'", "Rb'", "RB'",
 ];
 
+/// How words are extracted from the tokens - chosen from the file
+/// extension by default
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExtractMode {
     Default,
@@ -97,6 +106,9 @@ impl ExtractMode {
 struct Tokenizer<'input, 'skipped> {
     input: &'input str,
     pos: usize,
+    // Where the token we are extracting words from is in the input -
+    // the words after the first one are found in what's left of it
+    current_token: Range<usize>,
     extract_mode: ExtractMode,
     skipped: &'skipped HashSet<String>,
 }
@@ -110,6 +122,7 @@ impl<'input, 'skipped> Tokenizer<'input, 'skipped> {
         Self {
             input,
             pos: 0,
+            current_token: 0..0,
             extract_mode,
             skipped,
         }
@@ -140,10 +153,7 @@ impl<'input, 'skipped> Tokenizer<'input, 'skipped> {
             ExtractMode::Default | ExtractMode::Python => (IDENT_RE_DEFAULT.captures(token), 2),
         };
 
-        let captures = match captures {
-            None => return None,
-            Some(c) => c,
-        };
+        let captures = captures?;
 
         // The `index` comes for the call to `captures()` already, so this
         // should not panic:
@@ -215,7 +225,8 @@ impl<'input, 'skipped> Tokenizer<'input, 'skipped> {
 }
 
 impl<'input> Iterator for Tokenizer<'input, '_> {
-    type Item = (&'input str, usize);
+    // The word, its position in the input, and the token it was extracted from
+    type Item = (&'input str, usize, &'input str);
 
     fn next(&mut self) -> Option<<Self as Iterator>::Item> {
         // Algorithm:
@@ -232,9 +243,14 @@ impl<'input> Iterator for Tokenizer<'input, '_> {
                 self.pos += start + token.len();
                 continue;
             }
+            let token_start = self.pos + start;
+            if !self.current_token.contains(&token_start) {
+                self.current_token = token_start..token_start + token.len();
+            }
             let next_word = self.extract_word(token);
             if let Some((w, pos)) = next_word {
-                let res = (w, self.pos + start + pos);
+                let identifier = &self.input[self.current_token.clone()];
+                let res = (w, token_start + pos, identifier);
                 self.pos += start + pos + w.len();
                 return Some(res);
             } else {
@@ -244,38 +260,161 @@ impl<'input> Iterator for Tokenizer<'input, '_> {
     }
 }
 
+/// Whether the token looks like a proper noun: an uppercase letter
+/// followed by lowercase letters - like Alice, or Alice's
+pub fn is_capitalized(token: &str) -> bool {
+    let mut chars = token.chars();
+    let first_is_upper = chars.next().is_some_and(char::is_uppercase);
+    let rest = chars.as_str();
+    first_is_upper
+        && rest.chars().any(char::is_lowercase)
+        && rest.chars().all(|c| c.is_lowercase() || c == '\'')
+}
+
+// Opening quotes and brackets do not end the previous sentence, but
+// may start the next one - like in `He said: "Alice ..."`
+const SENTENCE_OPENERS: &[char] = &['"', '\'', '(', '[', '“', '‘', '«', '*', '_'];
+
+// True if `byte` is the first thing on the line once the opening quotes
+// and brackets are left out, or follows a sentence-ending punctuation
+fn starts_sentence(line: &str, byte: usize) -> bool {
+    let prefix = line.get(..byte).unwrap_or_default();
+    let previous = prefix
+        .chars()
+        .rev()
+        .find(|c| !c.is_whitespace() && !SENTENCE_OPENERS.contains(c));
+    match previous {
+        None => true,
+        Some(c) => matches!(c, '.' | '!' | '?' | '…'),
+    }
+}
+
+/// Whether the token is made of at least `min_length` uppercase letters,
+/// maybe followed by a 's' - like URL or URLs
+pub fn is_acronym(token: &str, min_length: usize) -> bool {
+    match CONSTANT_RE.captures(token) {
+        Some(captures) => captures[1].chars().count() >= min_length,
+        None => false,
+    }
+}
+
+/// Where a token starts in its line, in the units used by the various
+/// frontends. All of them start at 0:
+///  - `byte`: UTF-8 bytes, like Kakoune
+///  - `char`: Unicode scalar values, shown to humans
+///  - `utf16`: UTF-16 code units, like the Language Server Protocol
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Columns {
+    pub byte: usize,
+    pub char: usize,
+    pub utf16: usize,
+}
+
+impl Columns {
+    /// The columns of the given byte offset in `line`. Offsets past the
+    /// end of the line, or inside a char, count the bytes as chars
+    pub fn new(line: &str, byte: usize) -> Self {
+        let prefix = match line.get(..byte) {
+            Some(prefix) => prefix,
+            None => return Self::ascii(byte),
+        };
+        Self {
+            byte,
+            char: prefix.chars().count(),
+            utf16: prefix.encode_utf16().count(),
+        }
+    }
+
+    /// For callers that only know the byte offset - right as long as
+    /// the line is ASCII
+    pub fn ascii(byte: usize) -> Self {
+        Self {
+            byte,
+            char: byte,
+            utf16: byte,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Token {
     pub text: String,
+    /// Line, starting at 1, and byte offset in the line
     pub pos: (usize, usize),
+    pub columns: Columns,
+    /// What the word was extracted from, like `fooBar` for `foo`
+    pub identifier: String,
+    /// True for the first word of a line, and for the words after
+    /// a `.`, `!` or `?`
+    pub sentence_start: bool,
 }
 
 impl Token {
-    pub(crate) fn new(text: &str, pos: (usize, usize)) -> Self {
+    /// A token found in `line` at the given position
+    pub fn new(text: &str, line: &str, pos: (usize, usize)) -> Self {
         Self {
             text: text.to_string(),
             pos,
+            columns: Columns::new(line, pos.1),
+            identifier: text.to_string(),
+            sentence_start: starts_sentence(line, pos.1),
         }
     }
 
+    pub fn with_identifier(mut self, identifier: &str) -> Self {
+        self.identifier = identifier.to_string();
+        self
+    }
+
     fn cloned(&self) -> Self {
         Self {
             text: self.text.to_string(),
             pos: self.pos,
+            columns: self.columns,
+            identifier: self.identifier.to_string(),
+            sentence_start: self.sentence_start,
         }
     }
 }
 
+/// Lines between a line matching `begin` and a line matching `end`
+/// (both included) are not checked
+#[derive(Debug, Clone)]
+pub struct SkippedRegion {
+    begin: Regex,
+    end: Regex,
+}
+
+impl SkippedRegion {
+    pub fn new(begin: &str, end: &str) -> Result<Self> {
+        let begin =
+            Regex::new(begin).map_err(|e| anyhow!("Invalid region begin marker '{begin}': {e}"))?;
+        let end = Regex::new(end).map_err(|e| anyhow!("Invalid region end marker '{end}': {e}"))?;
+        Ok(Self { begin, end })
+    }
+}
+
 pub struct TokenProcessor<R: BufRead> {
     reader: R,
     file_name: String,
     current_line: String,
+    // Raw bytes of the current line, before decoding
+    line_bytes: Vec<u8>,
+    strict_utf8: bool,
+    invalid_utf8_lines: usize,
     current_tokens: Vec<Token>,
     extract_mode: ExtractMode,
     word_index: usize,
     line_index: usize,
     skipped_tokens: HashSet<String>,
     is_git_message: bool,
+    is_markdown: bool,
+    skip_front_matter: bool,
+    skipped_regions: Vec<SkippedRegion>,
+    // End marker and starting line of the region we are in, if any
+    current_region: Option<(Regex, usize)>,
+    // Parts of each line to check, if not the whole line
+    string_values: Option<Vec<Vec<Range<usize>>>>,
 }
 
 impl<R: BufRead> TokenProcessor<R> {
@@ -283,20 +422,67 @@ impl<R: BufRead> TokenProcessor<R> {
         let is_git_message = file_name == "COMMIT_EDITMSG";
         let extension = file_name.rsplit(".").next().unwrap_or_default();
         let extract_mode = ExtractMode::from_extension(extension);
+        let is_markdown = matches!(extension, "md" | "markdown");
 
         Self {
             reader,
             file_name: file_name.to_owned(),
             extract_mode,
             current_line: String::new(),
+            line_bytes: Vec::new(),
+            strict_utf8: false,
+            invalid_utf8_lines: 0,
             current_tokens: Vec::new(),
             word_index: 0,
             line_index: 0,
             skipped_tokens: HashSet::new(),
             is_git_message,
+            is_markdown,
+            skip_front_matter: false,
+            skipped_regions: Vec::new(),
+            current_region: None,
+            string_values: None,
         }
     }
 
+    /// Extract words as for the given mode, instead of the one matching
+    /// the file extension
+    pub fn set_extract_mode(&mut self, extract_mode: ExtractMode) {
+        self.extract_mode = extract_mode;
+    }
+
+    /// Do not check the YAML front matter of Markdown files
+    pub fn set_skip_front_matter(&mut self, skip_front_matter: bool) {
+        self.skip_front_matter = skip_front_matter;
+    }
+
+    /// Fail on lines that are not valid UTF-8, instead of ignoring
+    /// the invalid bytes
+    pub fn set_strict_utf8(&mut self, strict_utf8: bool) {
+        self.strict_utf8 = strict_utf8;
+    }
+
+    /// Number of lines read so far that were not valid UTF-8
+    pub fn invalid_utf8_lines(&self) -> usize {
+        self.invalid_utf8_lines
+    }
+
+    /// Only check the given byte ranges of each line, as returned by
+    /// string_values()
+    pub fn set_string_values(&mut self, string_values: Vec<Vec<Range<usize>>>) {
+        self.string_values = Some(string_values);
+    }
+
+    pub fn skip_regions(&mut self, regions: &[SkippedRegion]) {
+        self.skipped_regions.extend_from_slice(regions);
+    }
+
+    /// Line where the region we are in started, if any.
+    /// When called after the last token, this means the region was never closed
+    pub fn unterminated_region(&self) -> Option<usize> {
+        self.current_region.as_ref().map(|(_, line)| *line)
+    }
+
     pub fn skip_tokens(&mut self, tokens: &[String]) {
         for token in tokens {
             self.skipped_tokens.insert(token.to_string());
@@ -306,17 +492,50 @@ impl<R: BufRead> TokenProcessor<R> {
     // Return Ok(true) if reached end of file
     fn read_next_line(&mut self) -> Result<bool> {
         self.current_line.clear();
+        self.line_bytes.clear();
         self.line_index += 1;
-        let bytes_read = self.reader.read_line(&mut self.current_line);
+        let bytes_read = self.reader.read_until(b'\n', &mut self.line_bytes);
         match bytes_read {
             Err(read_error) => Err(anyhow!(
                 "Error when reading: '{}': {read_error}",
                 self.file_name,
             )),
-            Ok(n) => Ok(n == 0),
+            Ok(0) => Ok(true),
+            Ok(_) => {
+                self.decode_line()?;
+                Ok(false)
+            }
         }
     }
 
+    // Invalid bytes are replaced by spaces rather than by U+FFFD, like
+    // String::from_utf8_lossy() does, so that they are never part of a
+    // token and the columns still match the bytes in the file
+    fn decode_line(&mut self) -> Result<()> {
+        let mut valid = true;
+        for chunk in self.line_bytes.utf8_chunks() {
+            self.current_line.push_str(chunk.valid());
+            let invalid = chunk.invalid();
+            if invalid.is_empty() {
+                continue;
+            }
+            if self.strict_utf8 {
+                bail!(
+                    "Error when reading: '{}': line {} is not valid UTF-8",
+                    self.file_name,
+                    self.line_index
+                );
+            }
+            valid = false;
+            self.current_line
+                .extend(std::iter::repeat_n(' ', invalid.len()));
+        }
+        if !valid {
+            self.invalid_utf8_lines += 1;
+        }
+        Ok(())
+    }
+
     // Return Ok(true) if reached end of file
     fn on_last_token(&mut self) -> Result<bool> {
         let is_end_of_file = self.read_next_line()?;
@@ -326,19 +545,81 @@ impl<R: BufRead> TokenProcessor<R> {
         if self.is_git_message && self.current_line.trim() == GIT_SCISSORS {
             return Ok(true);
         }
+        if self.in_skipped_region() {
+            self.word_index = 0;
+            self.current_tokens.clear();
+            return Ok(false);
+        }
         self.extract_tokens();
         Ok(false)
     }
 
+    // Update the current region from the current line, and
+    // return true if the current line should not be checked
+    fn in_skipped_region(&mut self) -> bool {
+        if let Some((end, _)) = &self.current_region {
+            if end.is_match(&self.current_line) {
+                self.current_region = None;
+            }
+            return true;
+        }
+        let line = &self.current_line;
+        let is_front_matter = self.skip_front_matter
+            && self.is_markdown
+            && self.line_index == 1
+            && FRONT_MATTER_BEGIN_RE.is_match(line);
+        let end = if is_front_matter {
+            Some(FRONT_MATTER_END_RE.clone())
+        } else {
+            self.skipped_regions
+                .iter()
+                .find(|r| r.begin.is_match(line))
+                .map(|r| r.end.clone())
+        };
+        match end {
+            Some(end) => {
+                self.current_region = Some((end, self.line_index));
+                true
+            }
+            None => false,
+        }
+    }
+
     fn extract_tokens(&mut self) {
         self.word_index = 0;
-        let tokenizer = Tokenizer::new(&self.current_line, self.extract_mode, &self.skipped_tokens);
+        let masked;
+        let input = match &self.string_values {
+            Some(values) => {
+                let ranges = values.get(self.line_index - 1).map_or(&[][..], |r| r);
+                masked = mask_line(&self.current_line, ranges);
+                &masked
+            }
+            None => &self.current_line,
+        };
+        let tokenizer = Tokenizer::new(input, self.extract_mode, &self.skipped_tokens);
         self.current_tokens = tokenizer
-            .map(|(token, column)| Token::new(token, (self.line_index, column)))
+            .map(|(word, column, identifier)| {
+                Token::new(word, &self.current_line, (self.line_index, column))
+                    .with_identifier(identifier)
+            })
             .collect();
     }
 }
 
+// Replace the chars outside of `ranges` by spaces, keeping the byte
+// offsets of the others
+fn mask_line(line: &str, ranges: &[Range<usize>]) -> String {
+    let mut res = String::with_capacity(line.len());
+    for (i, c) in line.char_indices() {
+        if ranges.iter().any(|r| r.contains(&i)) {
+            res.push(c);
+        } else {
+            res.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        }
+    }
+    res
+}
+
 impl<R: BufRead> Iterator for TokenProcessor<R> {
     type Item = Result<Token>;
 
@@ -0,0 +1,5 @@
+/* Written by Ren Dupont */
+int main() {
+    /* caf au lait,  */
+    return 0; /* été */
+}
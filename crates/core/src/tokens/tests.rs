@@ -1,4 +1,5 @@
 use super::*;
+use crate::tests::NON_ASCII_LINE;
 
 fn extract_word_default(word: &str) -> Option<(&str, usize)> {
     let skipped = HashSet::new();
@@ -83,7 +84,7 @@ fn test_pascal_case() {
 fn get_tokens_default(text: &str) -> Vec<&str> {
     let skipped = HashSet::new();
     let tokenizer = Tokenizer::new(text, ExtractMode::Default, &skipped);
-    tokenizer.map(|(x, _index)| x).collect()
+    tokenizer.map(|(x, _index, _identifier)| x).collect()
 }
 
 #[test]
@@ -200,7 +201,7 @@ fn test_use_sqlite() {
 fn get_tokens_latex(text: &str) -> Vec<&str> {
     let skipped = HashSet::new();
     let tokenizer = Tokenizer::new(text, ExtractMode::Latex, &skipped);
-    tokenizer.map(|(x, _index)| x).collect()
+    tokenizer.map(|(x, _index, _identifier)| x).collect()
 }
 
 #[test]
@@ -218,7 +219,7 @@ fn test_extract_mode_for_tex_extension() {
 fn get_tokens_python(text: &str) -> Vec<&str> {
     let skipped = HashSet::new();
     let tokenizer = Tokenizer::new(text, ExtractMode::Python, &skipped);
-    tokenizer.map(|(x, _index)| x).collect()
+    tokenizer.map(|(x, _index, _identifier)| x).collect()
 }
 
 #[test]
@@ -292,3 +293,324 @@ Some irrelevant stuff here
     let actual = collect_tokens(&contents, "COMMIT_EDITMSG", &[]);
     assert_eq!(actual, &["This", "is", "a", "git", "message"]);
 }
+
+fn collect_tokens_with(
+    contents: &str,
+    file_name: &str,
+    skip_front_matter: bool,
+    regions: &[SkippedRegion],
+) -> (Vec<String>, Option<usize>) {
+    let file = Cursor::new(contents.as_bytes());
+    let reader = BufReader::new(file);
+    let mut processor = TokenProcessor::new(reader, file_name);
+    processor.set_skip_front_matter(skip_front_matter);
+    processor.skip_regions(regions);
+
+    let tokens = processor
+        .by_ref()
+        .map(|token| token.unwrap().text.to_owned())
+        .collect();
+    (tokens, processor.unterminated_region())
+}
+
+const WITH_FRONT_MATTER: &str = "---
+title: Lorem ipsum
+tags: [sdfsd]
+---
+Hello world
+";
+
+#[test]
+fn test_skip_front_matter_in_markdown() {
+    let (actual, unterminated) = collect_tokens_with(WITH_FRONT_MATTER, "post.md", true, &[]);
+
+    assert_eq!(actual, &["Hello", "world"]);
+    assert_eq!(unterminated, None);
+}
+
+#[test]
+fn test_front_matter_is_checked_by_default() {
+    let (actual, _) = collect_tokens_with(WITH_FRONT_MATTER, "post.md", false, &[]);
+
+    assert!(actual.contains(&"sdfsd".to_string()));
+}
+
+#[test]
+fn test_front_matter_is_only_skipped_in_markdown_files() {
+    let (actual, _) = collect_tokens_with(WITH_FRONT_MATTER, "post.txt", true, &[]);
+
+    assert!(actual.contains(&"sdfsd".to_string()));
+}
+
+#[test]
+fn test_front_matter_must_be_at_the_top() {
+    let contents = format!("Intro\n{WITH_FRONT_MATTER}");
+
+    let (actual, _) = collect_tokens_with(&contents, "post.md", true, &[]);
+
+    assert!(actual.contains(&"sdfsd".to_string()));
+}
+
+#[test]
+fn test_skip_regions_between_markers() {
+    let contents = "\
+Before
+// BEGIN GENERATED
+sdfsd
+// END GENERATED
+After
+// BEGIN GENERATED
+qwerty
+// END GENERATED
+";
+    let region = SkippedRegion::new("BEGIN GENERATED", "END GENERATED").unwrap();
+
+    let (actual, unterminated) = collect_tokens_with(contents, "foo.rs", false, &[region]);
+
+    assert_eq!(actual, &["Before", "After"]);
+    assert_eq!(unterminated, None);
+}
+
+#[test]
+fn test_unterminated_region_extends_to_end_of_file() {
+    let contents = "\
+Before
+DO NOT EDIT
+sdfsd
+";
+    let region = SkippedRegion::new("DO NOT EDIT", "END OF GENERATED CODE").unwrap();
+
+    let (actual, unterminated) = collect_tokens_with(contents, "foo.rs", false, &[region]);
+
+    assert_eq!(actual, &["Before"]);
+    assert_eq!(unterminated, Some(2));
+}
+
+#[test]
+fn test_invalid_region_marker() {
+    let err = SkippedRegion::new("(", "end").unwrap_err();
+
+    assert!(err.to_string().contains("Invalid region begin marker"));
+}
+
+#[test]
+fn test_is_acronym() {
+    assert!(is_acronym("URL", 2));
+    assert!(is_acronym("URLs", 2));
+    assert!(is_acronym("MYPROJ", 6));
+    assert!(!is_acronym("MYPROJ", 7));
+    assert!(!is_acronym("A", 2));
+    assert!(!is_acronym("Url", 2));
+    assert!(!is_acronym("urL", 2));
+    assert!(!is_acronym("HTTPError", 2));
+}
+
+#[test]
+fn test_is_capitalized() {
+    assert!(is_capitalized("Alice"));
+    assert!(is_capitalized("Alice's"));
+    assert!(is_capitalized("Éloïse"));
+    assert!(!is_capitalized("A"));
+    assert!(!is_capitalized("alice"));
+    assert!(!is_capitalized("URL"));
+    assert!(!is_capitalized("McCoy"));
+}
+
+fn sentence_starts(contents: &str) -> Vec<(String, bool)> {
+    let reader = BufReader::new(Cursor::new(contents.as_bytes()));
+    TokenProcessor::new(reader, "book.md")
+        .map(|token| {
+            let token = token.unwrap();
+            (token.text, token.sentence_start)
+        })
+        .collect()
+}
+
+#[test]
+fn test_sentence_start() {
+    let actual = sentence_starts("Alice met Bob. Then Bob left!\n  Bob said: \"Carol is here\"");
+
+    let expected = [
+        ("Alice", true),
+        ("met", false),
+        ("Bob", false),
+        ("Then", true),
+        ("Bob", false),
+        ("left", false),
+        ("Bob", true),
+        ("said", false),
+        ("Carol", false),
+        ("is", false),
+        ("here", false),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(text, start)| (text.to_string(), start))
+        .collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_quotes_and_markup_do_not_hide_the_sentence_start() {
+    let actual = sentence_starts("**Bob** is here. \"Carol\" too");
+
+    assert_eq!(actual[0], ("Bob".to_string(), true));
+    assert_eq!(actual[3], ("Carol".to_string(), true));
+}
+
+const LATIN1_SOURCE: &[u8] = include_bytes!("fixtures/latin1.c");
+
+fn latin1_processor(strict_utf8: bool) -> TokenProcessor<BufReader<Cursor<&'static [u8]>>> {
+    let reader = BufReader::new(Cursor::new(LATIN1_SOURCE));
+    let mut processor = TokenProcessor::new(reader, "latin1.c");
+    processor.set_strict_utf8(strict_utf8);
+    processor
+}
+
+#[test]
+fn test_invalid_utf8_bytes_are_ignored() {
+    let mut processor = latin1_processor(false);
+
+    let tokens: Vec<_> = processor.by_ref().map(|t| t.unwrap()).collect();
+
+    let words: Vec<_> = tokens.iter().map(|t| t.text.as_str()).collect();
+    #[rustfmt::skip]
+    let expected = vec![
+        "Written", "by", "Ren", "Dupont",
+        "int", "main",
+        "caf", "au", "lait",
+        "return", "été",
+    ];
+    assert_eq!(words, expected);
+    assert_eq!(processor.invalid_utf8_lines(), 2);
+}
+
+#[test]
+fn test_columns_after_invalid_utf8_bytes() {
+    let tokens: Vec<_> = latin1_processor(false).map(|t| t.unwrap()).collect();
+
+    let dupont = tokens.iter().find(|t| t.text == "Dupont").unwrap();
+    assert_eq!(dupont.pos, (1, 19));
+    let lait = tokens.iter().find(|t| t.text == "lait").unwrap();
+    assert_eq!(lait.pos, (3, 15));
+}
+
+#[test]
+fn test_strict_utf8() {
+    let mut processor = latin1_processor(true);
+
+    let err = processor.find_map(|t| t.err()).unwrap();
+
+    assert!(err.to_string().contains("line 1 is not valid UTF-8"));
+}
+
+#[test]
+fn test_columns_in_ascii_line() {
+    assert_eq!(Columns::new("hello world", 6), Columns::ascii(6));
+}
+
+#[test]
+fn test_columns_after_emoji() {
+    // U+1F44D is 4 bytes in UTF-8, and a surrogate pair in UTF-16
+    let columns = Columns::new("👍 fooo", 5);
+
+    assert_eq!(
+        columns,
+        Columns {
+            byte: 5,
+            char: 2,
+            utf16: 3
+        }
+    );
+}
+
+#[test]
+fn test_columns_after_combining_characters() {
+    // Each U+0301 is a char of its own, 2 bytes long
+    let columns = Columns::new("e\u{301}te\u{301} fooo", 8);
+
+    assert_eq!(
+        columns,
+        Columns {
+            byte: 8,
+            char: 6,
+            utf16: 6
+        }
+    );
+}
+
+#[test]
+fn test_columns_after_cjk() {
+    let columns = Columns::new("日本語 fooo", 10);
+
+    assert_eq!(
+        columns,
+        Columns {
+            byte: 10,
+            char: 4,
+            utf16: 4
+        }
+    );
+}
+
+#[test]
+fn test_columns_outside_of_char_boundaries() {
+    assert_eq!(Columns::new("日本語", 1), Columns::ascii(1));
+    assert_eq!(Columns::new("abc", 10), Columns::ascii(10));
+}
+
+#[test]
+fn test_token_columns_in_non_ascii_line() {
+    let reader = BufReader::new(Cursor::new(NON_ASCII_LINE));
+    let processor = TokenProcessor::new(reader, "foo.md");
+
+    let tokens: Vec<_> = processor.map(|t| t.unwrap()).collect();
+
+    let fooo = tokens.last().unwrap();
+    assert_eq!(fooo.text, "fooo");
+    assert_eq!(fooo.pos, (1, 23));
+    assert_eq!(
+        fooo.columns,
+        Columns {
+            byte: 23,
+            char: 12,
+            utf16: 13
+        }
+    );
+}
+
+#[test]
+fn test_identifiers() {
+    let text = "let fooBar = HTTPError; done";
+    let skipped = HashSet::new();
+    let tokenizer = Tokenizer::new(text, ExtractMode::Default, &skipped);
+
+    let actual: Vec<_> = tokenizer
+        .map(|(word, _index, identifier)| (word, identifier))
+        .collect();
+
+    assert_eq!(
+        &actual,
+        &[
+            ("let", "let"),
+            ("foo", "fooBar"),
+            ("Bar", "fooBar"),
+            ("HTTP", "HTTPError"),
+            ("Error", "HTTPError"),
+            ("done", "done"),
+        ]
+    );
+}
+
+#[test]
+fn test_forced_extract_mode() {
+    let reader = BufReader::new(Cursor::new(r"\newpage"));
+    let mut processor = TokenProcessor::new(reader, "notes.txt");
+    processor.set_extract_mode(ExtractMode::Latex);
+
+    let tokens: Vec<_> = processor.map(|t| t.unwrap()).collect();
+
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].text, "newpage");
+    assert_eq!(tokens[0].identifier, r"\newpage");
+}
@@ -1,16 +1,52 @@
 use crate::kak::io::KakouneIO;
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
 use skyspell_core::Checker;
 use skyspell_core::CheckerState;
 use skyspell_core::Dictionary;
 use skyspell_core::IgnoreStore;
+use skyspell_core::LangDictionaries;
+use skyspell_core::LangOpener;
 use skyspell_core::OperatingSystemIO;
 use skyspell_core::Operation;
 use skyspell_core::Project;
+use skyspell_core::RelativePath;
+use skyspell_core::ReportEntry;
 use skyspell_core::SpellingError;
+use skyspell_core::TokenClassifier;
 use std::path::PathBuf;
 
+// Defined by the init script
+const DEFAULT_FACE: &str = "SpellingError";
+
+/// The line of the *spelling* buffer for an error: the path, the range
+/// and the word, separated by tabs - neither the range nor the word can
+/// contain one, so paths containing `: ` or spaces are read back as they
+/// are, see cli::parse_line_selection(). Paths are relative to the project,
+/// like in the output of `skyspell check`
+pub(crate) fn spelling_line(entry: &ReportEntry) -> String {
+    let ReportEntry {
+        word,
+        path,
+        line,
+        column,
+    } = entry;
+    let end = column + word.len() - 1;
+    format!("{path}\t{line}.{column},{line}.{end}\t{word}")
+}
+
+// The keys typing `text` in insert mode: `<` would start a key name
+fn insert_keys(text: &str) -> String {
+    text.replace('<', "<lt>").replace('\t', "<tab>")
+}
+
+/// How errors are grouped in the *spelling* buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListOrder {
+    ByPath,
+    ByWord,
+}
+
 pub struct Error {
     pub pos: (usize, usize),
     pub buffer: String,
@@ -23,7 +59,13 @@ pub struct KakouneChecker<D: Dictionary, S: OperatingSystemIO> {
     ignore_store: IgnoreStore,
     project: Project,
     dictionary: D,
+    // Used for the buffers whose `skyspell_lang` option differs from the
+    // global one, and for the files tagged with another language
+    langs: LangDictionaries<D>,
+    buffer_lang: Option<String>,
     errors: Vec<Error>,
+    // Every checked buffer gets its ranges set, even when it has no errors
+    buffers: Vec<String>,
     state: CheckerState,
 }
 
@@ -33,11 +75,14 @@ impl<D: Dictionary, S: OperatingSystemIO> Checker<D> for KakouneChecker<D, S> {
     type SourceContext = String;
 
     fn handle_error(&mut self, error: &SpellingError, context: &Self::SourceContext) -> Result<()> {
-        let pos = error.pos();
+        // Kakoune counts columns in bytes
+        let (line, _) = error.pos();
+        let pos = (line, error.columns().byte);
         let buffer = context;
         let path = error.relative_path();
         let full_path = self.project.path().as_ref().join(path);
         let word = error.word();
+        self.add_buffer(buffer);
         self.errors.push(Error {
             full_path,
             pos,
@@ -57,8 +102,18 @@ impl<D: Dictionary, S: OperatingSystemIO> Checker<D> for KakouneChecker<D, S> {
         &mut self.ignore_store
     }
 
+    fn classifier(&self) -> TokenClassifier<'_, D> {
+        TokenClassifier::new(self.dictionary(), &self.ignore_store)
+    }
+
     fn dictionary(&self) -> &D {
-        &self.dictionary
+        self.langs.current().unwrap_or(&self.dictionary)
+    }
+
+    // The language set for the buffer wins over the language tag
+    fn select_lang(&mut self, lang: Option<&str>) -> bool {
+        let lang = self.buffer_lang.as_deref().or(lang);
+        self.langs.select(lang, self.dictionary.lang())
     }
 
     fn project(&self) -> &Project {
@@ -67,7 +122,8 @@ impl<D: Dictionary, S: OperatingSystemIO> Checker<D> for KakouneChecker<D, S> {
 
     fn apply_operation(&mut self, mut operation: Operation) -> Result<()> {
         operation.execute(&mut self.ignore_store)?;
-        self.state.set_last_operation(operation.clone())
+        self.state
+            .set_last_operation(operation.clone(), self.project.path())
     }
 
     fn state(&mut self) -> Option<&mut CheckerState> {
@@ -83,13 +139,17 @@ impl<D: Dictionary, S: OperatingSystemIO> KakouneChecker<D, S> {
         kakoune_io: KakouneIO<S>,
         state_toml: Option<PathBuf>,
     ) -> Result<Self> {
-        let state = CheckerState::load(state_toml)?;
+        let mut state = CheckerState::load(state_toml)?;
+        state.set_post_operation_hook(ignore_store.post_operation_hook(), Default::default());
         Ok(Self {
             project,
             dictionary,
+            langs: LangDictionaries::default(),
+            buffer_lang: None,
             kakoune_io,
             ignore_store,
             errors: vec![],
+            buffers: vec![],
             state,
         })
     }
@@ -102,15 +162,80 @@ impl<D: Dictionary, S: OperatingSystemIO> KakouneChecker<D, S> {
         self.kakoune_io.print(command)
     }
 
-    pub fn write_code(&self) -> Result<()> {
+    /// Used to open the dictionaries of the other languages, once per
+    /// language and per run
+    pub fn set_lang_opener(&mut self, opener: LangOpener<D>) {
+        self.langs.set_opener(opener);
+    }
+
+    /// The language of the next buffer to check, if it is not the
+    /// global one
+    pub fn set_buffer_lang(&mut self, lang: Option<&str>) {
+        self.buffer_lang = lang.map(|l| l.to_string());
+    }
+
+    /// Register a buffer about to be checked, so that its ranges are
+    /// reset by write_code() if no errors are found in it
+    pub fn add_buffer(&mut self, buffer: &str) {
+        if !self.buffers.iter().any(|b| b == buffer) {
+            self.buffers.push(buffer.to_string());
+        }
+    }
+
+    pub fn write_code(&mut self) -> Result<()> {
         let kak_timestamp = self.kakoune_io.get_timestamp()?;
-        self.write_spelling_buffer();
-        self.write_ranges(kak_timestamp);
-        self.write_status();
+        let entries = self.report_entries();
+        // With many errors, the commands may be too large to be printed
+        // directly, see KakouneIO::print_or_source()
+        let commands = self.kakoune_io.capture(|| {
+            self.write_spelling_buffer(&entries, ListOrder::ByPath);
+            self.write_ranges(kak_timestamp);
+            self.write_status();
+        });
+        self.kakoune_io.print_or_source(&commands)?;
+        // Saved so that the list can be sorted or filtered without
+        // checking the buffers again
+        self.state.save_report(self.project.path(), entries)
+    }
 
+    /// Errors found by the last check, optionally filtered to one word
+    pub fn write_saved_errors(&self, order: ListOrder, word: Option<&str>) -> Result<()> {
+        let report = self
+            .state
+            .last_report(self.project.path())
+            .ok_or_else(|| anyhow!("No spelling errors to list - run skyspell-check first"))?;
+        let entries: Vec<_> = report
+            .entries
+            .iter()
+            .filter(|e| word.map(|w| e.word == w).unwrap_or(true))
+            .cloned()
+            .collect();
+        if let (Some(word), true) = (word, entries.is_empty()) {
+            bail!("No spelling errors for '{word}'");
+        }
+        self.write_spelling_buffer(&entries, order);
         Ok(())
     }
 
+    fn report_entries(&self) -> Vec<ReportEntry> {
+        self.errors
+            .iter()
+            .map(|e| {
+                let (line, column) = e.pos;
+                let path = e.full_path.strip_prefix(self.project.path().as_ref());
+                ReportEntry {
+                    word: e.token.clone(),
+                    path: RelativePath::from_path_unchecked(
+                        path.unwrap_or(&e.full_path).to_path_buf(),
+                    )
+                    .normalize(),
+                    line,
+                    column: column + 1,
+                }
+            })
+            .collect()
+    }
+
     pub fn write_status(&self) {
         let project_path = &self.project.path();
         let errors_count = self.errors.len();
@@ -128,7 +253,7 @@ impl<D: Dictionary, S: OperatingSystemIO> KakouneChecker<D, S> {
         }
     }
 
-    fn write_spelling_buffer(&self) {
+    fn write_spelling_buffer(&self, entries: &[ReportEntry], order: ListOrder) {
         // Only write in draft mode
         self.print("evaluate-commands -draft %{");
 
@@ -138,12 +263,33 @@ impl<D: Dictionary, S: OperatingSystemIO> KakouneChecker<D, S> {
         // Delete everything
         self.print(r"execute-keys -draft \% <ret> d ");
 
-        // Insert all errors
+        // Insert all errors, with a header line for each group
         self.print("i %{");
 
-        for error in self.errors.iter() {
-            self.write_error(error);
+        let mut entries: Vec<_> = entries.iter().collect();
+        match order {
+            ListOrder::ByPath => entries
+                .sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column))),
+            ListOrder::ByWord => entries.sort_by(|a, b| {
+                (&a.word, &a.path, a.line, a.column).cmp(&(&b.word, &b.path, b.line, b.column))
+            }),
+        }
+        let group_key = |e: &&ReportEntry| match order {
+            ListOrder::ByPath => e.path.clone(),
+            ListOrder::ByWord => e.word.clone(),
+        };
+        for (key, group) in &entries.into_iter().group_by(group_key) {
+            let group: Vec<_> = group.collect();
+            let count = match group.len() {
+                1 => "1 error".to_string(),
+                n => format!("{n} errors"),
+            };
+            self.print(&insert_keys(&format!("{key} - {count}")));
             self.print("<ret>");
+            for entry in group {
+                self.write_error(entry);
+                self.print("<ret>");
+            }
         }
         self.print("} ");
 
@@ -152,51 +298,64 @@ impl<D: Dictionary, S: OperatingSystemIO> KakouneChecker<D, S> {
         self.print("<esc>}\n");
     }
 
-    fn write_error(&self, error: &Error) {
-        let Error {
-            pos,
-            token,
-            full_path,
-            ..
-        } = error;
-        let (line, start) = pos;
-        let end = start + token.len();
-        self.print(&format!(
-            "{}: {}.{},{}.{} {}",
-            full_path.display(),
-            line,
-            // Columns start at 1
-            start + 1,
-            line,
-            end,
-            token
-        ));
+    fn write_error(&self, entry: &ReportEntry) {
+        self.print(&insert_keys(&spelling_line(entry)));
     }
 
+    // Read from the `skyspell_face` option, which may not be declared
+    // by init scripts generated by older versions
+    fn face(&self) -> String {
+        match self.kakoune_io.get_option("skyspell_face") {
+            Ok(face) if !face.is_empty() => face,
+            _ => DEFAULT_FACE.to_string(),
+        }
+    }
+
+    /// The options containing the ranges of the errors - with
+    /// `init --compat-spell`, the one used by spell.kak is set too
+    pub fn range_options(&self) -> &'static [&'static str] {
+        match self
+            .kakoune_io
+            .get_option("skyspell_compat_spell")
+            .as_deref()
+        {
+            Ok("true") => &["skyspell_errors", "spell_errors"],
+            _ => &["skyspell_errors"],
+        }
+    }
+
+    // Nothing is printed before all the buffers are checked, and all the
+    // options are set in one block, so that highlights don't flicker and
+    // are left untouched if the check fails
     fn write_ranges(&self, timestamp: usize) {
-        for (buffer, group) in &self.errors.iter().group_by(|e| &e.buffer) {
-            self.print(&format!(
-                "set-option %{{buffer={}}} skyspell_errors {} ",
-                buffer, timestamp
-            ));
-            for error in group {
-                self.write_error_range(error);
-                self.print(" ");
+        if self.buffers.is_empty() {
+            return;
+        }
+        let face = self.face();
+        let options = self.range_options();
+        self.print("evaluate-commands -no-hooks %{\n");
+        for buffer in &self.buffers {
+            // Computed once, so that all the options get the same ranges
+            let ranges: String = self
+                .errors
+                .iter()
+                .filter(|e| &e.buffer == buffer)
+                .map(|e| error_range(e, &face) + " ")
+                .collect();
+            for option in options {
+                self.print(&format!(
+                    "set-option %{{buffer={buffer}}} {option} {timestamp} {ranges}\n"
+                ));
             }
-            self.print("\n");
         }
+        self.print("}\n");
     }
+}
 
-    fn write_error_range(&self, error: &Error) {
-        let Error { pos, token, .. } = error;
-        let (line, start) = pos;
-        self.print(&format!(
-            "{}.{}+{}|SpellingError",
-            line,
-            start + 1,
-            token.len()
-        ));
-    }
+fn error_range(error: &Error, face: &str) -> String {
+    let Error { pos, token, .. } = error;
+    let (line, start) = pos;
+    format!("{}.{}+{}|{}", line, start + 1, token.len(), face)
 }
 
 #[cfg(test)]
@@ -1,7 +1,7 @@
 use super::*;
 
-use crate::kak::io::tests::new_fake_io;
-use skyspell_core::tests::{FakeDictionary, FakeIO};
+use crate::kak::io::tests::{new_fake_io, FakeKakouneIO};
+use skyspell_core::tests::{FakeDictionary, FakeIO, NON_ASCII_LINE};
 use skyspell_core::IgnoreStore;
 use skyspell_core::RelativePath;
 use tempfile::TempDir;
@@ -13,6 +13,10 @@ impl FakeChecker {
         self.kakoune_io.get_output()
     }
 
+    pub(crate) fn io_mut(&mut self) -> &mut FakeKakouneIO {
+        &mut self.kakoune_io
+    }
+
     pub(crate) fn ensure_path(&self, relative_name: &str) -> RelativePath {
         let project_path = self.project.path();
         let full_path = project_path.as_ref().join(relative_name);
@@ -47,20 +51,163 @@ fn test_write_errors_in_spelling_buffer() {
         .unwrap();
     let mut checker = new_fake_checker(&temp_dir);
     let hello_js = checker.ensure_path("hello.js");
-    checker.ensure_path("hello.js");
     let error = make_error("foo", &hello_js, (2, 4));
     checker.handle_error(&error, &hello_js.normalize()).unwrap();
-    checker.write_spelling_buffer();
+    let entries = checker.report_entries();
+    checker.write_spelling_buffer(&entries, ListOrder::ByPath);
     let actual = checker.get_output();
-    let expected = format!(
-        "evaluate-commands -draft %{{edit -scratch *spelling*
-execute-keys -draft \\% <ret> d i %{{{}/hello.js: 2.5,2.7 foo<ret>}} <esc>}}
-",
-        temp_dir.path().display()
-    );
+    let expected = "evaluate-commands -draft %{edit -scratch *spelling*
+execute-keys -draft \\% <ret> d i %{hello.js - 1 error<ret>hello.js<tab>2.5,2.7<tab>foo<ret>} <esc>}
+";
     assert_eq!(actual, expected);
 }
 
+#[test]
+fn test_paths_are_not_read_as_keys_in_spelling_buffer() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut checker = new_fake_checker(&temp_dir);
+    let path = checker.ensure_path("<ret>: x.js");
+    let error = make_error("foo", &path, (1, 0));
+    checker.handle_error(&error, &path.normalize()).unwrap();
+    let entries = checker.report_entries();
+
+    checker.write_spelling_buffer(&entries, ListOrder::ByPath);
+
+    let output = checker.get_output();
+    assert!(
+        output.contains("<lt>ret>: x.js<tab>1.1,1.3<tab>foo<ret>"),
+        "{output}"
+    );
+    let lines = spelling_buffer_lines(&output);
+    assert_eq!(lines[1], "<ret>: x.js\t1.1,1.3\tfoo");
+}
+
+fn add_errors(checker: &mut FakeChecker) {
+    let foo_js = checker.ensure_path("foo.js");
+    let bar_js = checker.ensure_path("bar.js");
+    for (word, path, pos) in [
+        ("spam", &foo_js, (3, 0)),
+        ("eggs", &foo_js, (1, 4)),
+        ("spam", &bar_js, (2, 0)),
+    ] {
+        let error = make_error(word, path, pos);
+        checker.handle_error(&error, &path.normalize()).unwrap();
+    }
+}
+
+fn spelling_buffer_lines(output: &str) -> Vec<String> {
+    let (_, inserted) = output.split_once("i %{").unwrap();
+    let (inserted, _) = inserted.split_once("} <esc>").unwrap();
+    inserted
+        .split("<ret>")
+        .filter(|l| !l.is_empty())
+        .map(|l| l.replace("<tab>", "\t").replace("<lt>", "<"))
+        .collect()
+}
+
+#[test]
+fn test_spelling_buffer_is_sorted_and_grouped_by_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut checker = new_fake_checker(&temp_dir);
+    checker.io_mut().set_timestamp(42);
+    add_errors(&mut checker);
+
+    checker.write_code().unwrap();
+
+    let output = checker.get_output();
+    let lines = spelling_buffer_lines(&output);
+    assert_eq!(
+        lines,
+        [
+            "bar.js - 1 error".to_string(),
+            "bar.js\t2.1,2.4\tspam".to_string(),
+            "foo.js - 2 errors".to_string(),
+            "foo.js\t1.5,1.8\teggs".to_string(),
+            "foo.js\t3.1,3.4\tspam".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_list_saved_errors_by_word() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut checker = new_fake_checker(&temp_dir);
+    checker.io_mut().set_timestamp(42);
+    add_errors(&mut checker);
+    checker.write_code().unwrap();
+    let checker = new_fake_checker(&temp_dir);
+
+    checker.write_saved_errors(ListOrder::ByWord, None).unwrap();
+
+    let output = checker.get_output();
+    let lines = spelling_buffer_lines(&output);
+    assert_eq!(
+        lines,
+        [
+            "eggs - 1 error".to_string(),
+            "foo.js\t1.5,1.8\teggs".to_string(),
+            "spam - 2 errors".to_string(),
+            "bar.js\t2.1,2.4\tspam".to_string(),
+            "foo.js\t3.1,3.4\tspam".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_list_saved_errors_for_one_word() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut checker = new_fake_checker(&temp_dir);
+    checker.io_mut().set_timestamp(42);
+    add_errors(&mut checker);
+    checker.write_code().unwrap();
+    let checker = new_fake_checker(&temp_dir);
+
+    checker
+        .write_saved_errors(ListOrder::ByPath, Some("eggs"))
+        .unwrap();
+    let err = checker
+        .write_saved_errors(ListOrder::ByPath, Some("nope"))
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "No spelling errors for 'nope'");
+    let output = checker.get_output();
+    let lines = spelling_buffer_lines(&output);
+    assert_eq!(
+        lines,
+        [
+            "foo.js - 1 error".to_string(),
+            "foo.js\t1.5,1.8\teggs".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_list_saved_errors_before_any_check() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let checker = new_fake_checker(&temp_dir);
+
+    let err = checker
+        .write_saved_errors(ListOrder::ByWord, None)
+        .unwrap_err();
+
+    assert!(err.to_string().contains("run skyspell-check first"));
+}
+
 #[test]
 fn test_write_errors_as_buffer_options() {
     let temp_dir = tempfile::Builder::new()
@@ -84,7 +231,146 @@ fn test_write_errors_as_buffer_options() {
 
     let actual = checker.get_output();
     let expected = "\
+    evaluate-commands -no-hooks %{\n\
     set-option %{buffer=foo.js} skyspell_errors 42 2.5+3|SpellingError 3.7+3|SpellingError \n\
-    set-option %{buffer=bar.js} skyspell_errors 42 1.6+4|SpellingError \n";
+    set-option %{buffer=bar.js} skyspell_errors 42 1.6+4|SpellingError \n\
+    }\n";
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn test_write_errors_with_configured_face() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut checker = new_fake_checker(&temp_dir);
+    checker.kakoune_io.set_option("skyspell_face", "Typo");
+    let foo_js = checker.ensure_path("foo.js");
+    let error = make_error("foo", &foo_js, (2, 4));
+    checker.handle_error(&error, &foo_js.normalize()).unwrap();
+
+    checker.write_ranges(42);
+
+    let actual = checker.get_output();
+    assert_eq!(
+        actual,
+        "evaluate-commands -no-hooks %{\n\
+        set-option %{buffer=foo.js} skyspell_errors 42 2.5+3|Typo \n\
+        }\n"
+    );
+}
+
+#[test]
+fn test_write_errors_in_spell_kak_option_too_in_compat_mode() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut checker = new_fake_checker(&temp_dir);
+    checker
+        .kakoune_io
+        .set_option("skyspell_compat_spell", "true");
+    let foo_js = checker.ensure_path("foo.js");
+    let error = make_error("foo", &foo_js, (2, 4));
+    checker.handle_error(&error, &foo_js.normalize()).unwrap();
+
+    checker.write_ranges(42);
+
+    let actual = checker.get_output();
+    assert_eq!(
+        actual,
+        "\
+    evaluate-commands -no-hooks %{\n\
+    set-option %{buffer=foo.js} skyspell_errors 42 2.5+3|SpellingError \n\
+    set-option %{buffer=foo.js} spell_errors 42 2.5+3|SpellingError \n\
+    }\n"
+    );
+}
+
+#[test]
+fn test_ranges_are_in_bytes() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut checker = new_fake_checker(&temp_dir);
+    for word in ["e", "te", "日本語"] {
+        checker.dictionary.add_known(word);
+    }
+    let foo_md = temp_dir.path().join("foo.md");
+    std::fs::write(&foo_md, NON_ASCII_LINE).unwrap();
+
+    checker.process(&foo_md, &"foo.md".to_string()).unwrap();
+    checker.write_ranges(42);
+
+    let actual = checker.get_output();
+    assert_eq!(
+        actual,
+        "evaluate-commands -no-hooks %{\n\
+        set-option %{buffer=foo.md} skyspell_errors 42 1.24+4|SpellingError \n\
+        }\n"
+    );
+}
+
+#[test]
+fn test_clean_buffers_get_empty_ranges() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut checker = new_fake_checker(&temp_dir);
+    checker.add_buffer("clean.js");
+    let foo_js = checker.ensure_path("foo.js");
+    let error = make_error("foo", &foo_js, (2, 4));
+    checker.handle_error(&error, &foo_js.normalize()).unwrap();
+
+    checker.write_ranges(42);
+
+    let actual = checker.get_output();
+    assert_eq!(
+        actual,
+        "\
+    evaluate-commands -no-hooks %{\n\
+    set-option %{buffer=clean.js} skyspell_errors 42 \n\
+    set-option %{buffer=foo.js} skyspell_errors 42 2.5+3|SpellingError \n\
+    }\n"
+    );
+}
+
+#[test]
+fn test_no_ranges_written_without_buffers() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let checker = new_fake_checker(&temp_dir);
+
+    checker.write_ranges(42);
+
+    assert_eq!(checker.get_output(), "");
+}
+
+#[test]
+fn test_write_code_through_a_temp_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut checker = new_fake_checker(&temp_dir);
+    checker.io_mut().set_timestamp(42);
+    checker.io_mut().set_tempfile_threshold(10);
+    add_errors(&mut checker);
+
+    checker.write_code().unwrap();
+
+    let temp_files = checker.kakoune_io.temp_files();
+    let (path, contents) = temp_files.first_key_value().unwrap();
+    assert!(contents.contains("edit -scratch *spelling*"));
+    assert!(contents.contains("set global skyspell_error_count 3\n"));
+    let path = path.display().to_string();
+    let actual = checker.get_output();
+    assert!(actual.starts_with(&format!("try %{{ source '{path}' }}")));
+    assert!(actual.ends_with(&format!("nop %sh{{ rm -f '{path}' }}\n")));
+    assert!(!actual.contains("skyspell_error_count"));
+}
@@ -1,14 +1,24 @@
+use crate::kak::checker::ListOrder;
+use crate::kak::io::quote;
 use crate::kak::{new_kakoune_io, KakouneChecker, KakouneIO};
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use clap::Parser;
 use directories_next::BaseDirs;
+use skyspell_core::normalize_lang;
+use skyspell_core::open_dictionary;
+use skyspell_core::output;
+use skyspell_core::suggest_replacements;
 use skyspell_core::Checker;
 use skyspell_core::Dictionary;
+use skyspell_core::LangRules;
 use skyspell_core::OperatingSystemIO;
 use skyspell_core::Operation;
+use skyspell_core::PersonalDictionary;
 use skyspell_core::Project;
-use skyspell_core::SystemDictionary;
+use skyspell_core::RelativePath;
+use skyspell_core::Verbosity;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 // Warning: most of the things written to stdout while this code is
 // called will be interpreted as a Kakoune command. Use the debug()
@@ -20,6 +30,22 @@ pub struct Opts {
     #[clap(long, help = "Language to use")]
     pub lang: Option<String>,
 
+    #[clap(
+        long,
+        short = 'q',
+        conflicts_with = "verbose",
+        help = "Do not write to the *debug* buffer"
+    )]
+    quiet: bool,
+
+    #[clap(
+        long,
+        short = 'v',
+        action = clap::ArgAction::Count,
+        help = "Also write the buffers that are not checked to the *debug* buffer"
+    )]
+    verbose: u8,
+
     #[clap(subcommand)]
     action: Action,
 }
@@ -27,7 +53,7 @@ pub struct Opts {
 #[derive(Parser)]
 enum Action {
     #[clap(about = "Dump initial kakoune script")]
-    Init,
+    Init(InitOpts),
 
     #[clap(about = "Add selection to the global ignore list")]
     AddGlobal,
@@ -52,15 +78,37 @@ enum Action {
     #[clap(about = "Jump to the next error")]
     NextError(MoveOpts),
 
+    #[clap(about = "List the errors from the last check, grouped by word")]
+    ListSortByWord,
+    #[clap(about = "List the errors from the last check for the given word")]
+    ListFilter(ListFilterOpts),
+
     #[clap(about = "Undo last operation")]
     Undo,
 }
 
+#[derive(Parser)]
+struct InitOpts {
+    #[clap(long, help = "Face used to highlight spelling errors")]
+    face: Option<String>,
+
+    #[clap(
+        long,
+        help = "Also set the `spell_errors` option of spell.kak, and make its commands use skyspell"
+    )]
+    compat_spell: bool,
+}
+
 #[derive(Parser)]
 struct CheckOpts {
     buflist: Vec<String>,
 }
 
+#[derive(Parser)]
+struct ListFilterOpts {
+    word: String,
+}
+
 #[derive(Parser)]
 struct MoveOpts {
     range_spec: String,
@@ -80,24 +128,65 @@ enum Direction {
 
 pub fn main() -> Result<()> {
     let opts: Opts = Opts::parse();
+    output::set_verbosity(Verbosity::from_flags(opts.quiet, opts.verbose));
+    if let Err(e) = run(opts) {
+        // Display the error in Kakoune, and keep the details in the
+        // *debug* buffer. Still fail, so that the scripts in init.kak
+        // trigger the skyspell-kak-on-failure hook
+        new_kakoune_io().print_error(&format!("{e:#}"));
+        eprintln!("{e:?}");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn run(opts: Opts) -> Result<()> {
     // Note: init is the only command that does not require a KakouneChecker
-    if matches!(opts.action, Action::Init) {
-        print!("{}", include_str!("init.kak"));
+    if let Action::Init(init_opts) = &opts.action {
+        print!(
+            "{}",
+            init_script(init_opts.face.as_deref(), init_opts.compat_spell)
+        );
         return Ok(());
     }
 
     let kakoune_io = new_kakoune_io();
 
     let lang = &kakoune_io.get_option("skyspell_lang")?;
-    let dictionary = SystemDictionary::new(lang)?;
 
-    let project_path = kakoune_io.get_option("skyspell_project")?;
-    let project_path = PathBuf::from(project_path);
-    let project = Project::new(&project_path)?;
-    let ignore_store = project.ignore_store()?;
+    // Without a project (scratch notes, files opened from elsewhere), use
+    // a project rooted in the home directory and only the global ignore file
+    let project_path = kakoune_io
+        .get_option("skyspell_project")
+        .unwrap_or_default();
+    let has_project = !project_path.is_empty();
+    let (project, mut ignore_store) = if has_project {
+        let project = Project::new(&PathBuf::from(project_path))?;
+        let ignore_store = project.ignore_store()?;
+        (project, ignore_store)
+    } else {
+        let project = Project::new_without_local_ignore(Path::new(&home_dir()?))?;
+        let ignore_store = project.ignore_store_without_local()?;
+        (project, ignore_store)
+    };
+
+    if ignore_store.use_personal_dictionary() {
+        if let Some(path) = PersonalDictionary::find(None, lang) {
+            ignore_store.set_personal_dictionary(PersonalDictionary::load(&path)?);
+        }
+    }
+
+    let lang_options = ignore_store.lang_options();
+    let dictionary = open_dictionary(None, lang)?;
+    let dictionary = LangRules::new(dictionary, &lang_options);
 
-    let checker = KakouneChecker::new(project, dictionary, ignore_store, kakoune_io, None)?;
-    let mut cli = KakCli::new(checker)?;
+    let mut checker = KakouneChecker::new(project, dictionary, ignore_store, kakoune_io, None)?;
+    // For the buffers where `skyspell_lang` is set to another language
+    checker.set_lang_opener(Arc::new(move |lang: &str| {
+        let dictionary = open_dictionary(None, lang)?;
+        Ok(LangRules::new(dictionary, &lang_options))
+    }));
+    let mut cli = KakCli::new(checker, has_project)?;
 
     match opts.action {
         Action::AddExtension => cli.add_extension(),
@@ -110,26 +199,99 @@ pub fn main() -> Result<()> {
         Action::NextError(opts) => cli.goto_next_error(opts),
         Action::PreviousError(opts) => cli.goto_previous_error(opts),
         Action::Suggest => cli.suggest(),
+        Action::ListSortByWord => cli.checker.write_saved_errors(ListOrder::ByWord, None),
+        Action::ListFilter(opts) => cli
+            .checker
+            .write_saved_errors(ListOrder::ByPath, Some(&opts.word)),
         Action::Undo => cli.checker.undo(),
-        Action::Init => Ok(()), // handled above
+        Action::Init(_) => Ok(()), // handled above
     }
 }
 
+// Lines in the *spelling* buffer look like `<path>\t<range>\t<word>` -
+// see spelling_line() - with a header line before each group of errors.
+// None for the headers, and for lines that are not from *spelling* at all
+fn parse_line_selection<S: OperatingSystemIO>(
+    kakoune_io: &KakouneIO<S>,
+    line_selection: &str,
+) -> Option<LineSelection> {
+    // Only the path may contain tabs
+    let mut fields = line_selection.rsplitn(3, '\t');
+    let (word, selection, path) = match (fields.next(), fields.next(), fields.next()) {
+        (Some(word), Some(selection), Some(path)) => (word, selection, path),
+        // Written by an older version of skyspell-kak
+        _ => return parse_legacy_line_selection(kakoune_io, line_selection),
+    };
+    new_line_selection(kakoune_io, path, selection, word)
+}
+
+// Before the fields were separated by tabs, lines looked like
+// `<path>: <range> <word>`
+fn parse_legacy_line_selection<S: OperatingSystemIO>(
+    kakoune_io: &KakouneIO<S>,
+    line_selection: &str,
+) -> Option<LineSelection> {
+    let (path, rest) = line_selection.split_once(": ")?;
+    let (selection, word) = rest.split_once(' ')?;
+    if word.contains(' ') {
+        return None;
+    }
+    new_line_selection(kakoune_io, path, selection, word)
+}
+
+fn new_line_selection<S: OperatingSystemIO>(
+    kakoune_io: &KakouneIO<S>,
+    path: &str,
+    selection: &str,
+    word: &str,
+) -> Option<LineSelection> {
+    let (start, end) = selection.split_once(',')?;
+    kakoune_io.parse_cursor(start).ok()?;
+    kakoune_io.parse_cursor(end).ok()?;
+    if path.is_empty() || word.is_empty() {
+        return None;
+    }
+    Some(LineSelection {
+        path: path.to_string(),
+        word: word.to_string(),
+        selection: selection.to_string(),
+    })
+}
+
+fn init_script(face: Option<&str>, compat_spell: bool) -> String {
+    let mut script = include_str!("init.kak").to_string();
+    if let Some(face) = face {
+        script.push_str(&format!("set-option global skyspell_face {face}\n"));
+    }
+    if compat_spell {
+        script.push_str(include_str!("compat_spell.kak"));
+    }
+    script
+}
+
+fn home_dir() -> Result<String> {
+    let base_dirs = BaseDirs::new().ok_or_else(|| anyhow!("Could not get home directory"))?;
+    let home_dir = base_dirs
+        .home_dir()
+        .to_str()
+        .ok_or_else(|| anyhow!("Non-UTF8 chars in home dir"))?;
+    Ok(home_dir.to_string())
+}
+
 struct KakCli<D: Dictionary, S: OperatingSystemIO> {
     checker: KakouneChecker<D, S>,
     home_dir: String,
+    // False when `skyspell_project` is not set - the checker project is
+    // then rooted in the home directory, and has no local ignore file
+    has_project: bool,
 }
 
 impl<D: Dictionary, S: OperatingSystemIO> KakCli<D, S> {
-    fn new(checker: KakouneChecker<D, S>) -> Result<Self> {
-        let base_dirs = BaseDirs::new().ok_or_else(|| anyhow!("Could not get home directory"))?;
-        let home_dir = base_dirs
-            .home_dir()
-            .to_str()
-            .ok_or_else(|| anyhow!("Non-UTF8 chars in home dir"))?;
+    fn new(checker: KakouneChecker<D, S>, has_project: bool) -> Result<Self> {
         Ok(Self {
-            home_dir: home_dir.to_string(),
+            home_dir: home_dir()?,
             checker,
+            has_project,
         })
     }
 
@@ -142,16 +304,36 @@ impl<D: Dictionary, S: OperatingSystemIO> KakCli<D, S> {
     }
 
     fn print_error(&self, error: &str) {
-        self.kakoune_io()
-            .print(&format!("echo -markup {{Error}} {error}"))
+        self.kakoune_io().print_error(error)
     }
 
     fn dictionary(&self) -> &D {
         self.checker.dictionary()
     }
 
+    /// The relative path for a path found in the *spelling* buffer, or
+    /// None (after displaying an error) if the file was deleted or renamed
+    /// since the last check. Without a project, any existing file is accepted
+    fn relative_path_in_project(&self, path: &str) -> Result<Option<RelativePath>> {
+        if !Path::new(path).exists() {
+            self.print_error(&format!(
+                "{path} no longer exists - run skyspell-check again"
+            ));
+            return Ok(None);
+        }
+        let relative_path = self.checker.project().as_relative_path(path)?;
+        if self.has_project && relative_path.is_outside_project() {
+            self.print_error(&format!("{path} is outside the current project"));
+            return Ok(None);
+        }
+        Ok(Some(relative_path))
+    }
+
     fn add_extension(&mut self) -> Result<()> {
         let LineSelection { path, word, .. } = &self.parse_line_selection()?;
+        if self.relative_path_in_project(path)?.is_none() {
+            return Ok(());
+        }
         let (_, ext) = path
             .rsplit_once('.')
             .ok_or_else(|| anyhow!("File has no extension"))?;
@@ -165,8 +347,11 @@ impl<D: Dictionary, S: OperatingSystemIO> KakCli<D, S> {
     }
 
     fn add_lang(&mut self) -> Result<()> {
-        let lang = self.dictionary().lang().to_owned();
-        let LineSelection { word, .. } = &self.parse_line_selection()?;
+        let LineSelection { path, word, .. } = &self.parse_line_selection()?;
+        let lang = match self.buffer_lang(Path::new(path)) {
+            Some(lang) => normalize_lang(&lang),
+            None => self.dictionary().lang().to_owned(),
+        };
         let operation = Operation::new_ignore_for_lang(word, &lang);
         self.checker.apply_operation(operation)?;
         self.recheck();
@@ -176,10 +361,26 @@ impl<D: Dictionary, S: OperatingSystemIO> KakCli<D, S> {
         Ok(())
     }
 
+    /// False (after displaying an error) when there is no project to
+    /// write the local ignore file into
+    fn ensure_project(&self, action: &str) -> bool {
+        if !self.has_project {
+            self.print_error(&format!(
+                "Cannot {action} without a project - set the skyspell_project option, or use skyspell-enable in the project directory"
+            ));
+        }
+        self.has_project
+    }
+
     fn add_file(&mut self) -> Result<()> {
+        if !self.ensure_project("add to the ignore list for a file") {
+            return Ok(());
+        }
         let LineSelection { path, word, .. } = &self.parse_line_selection()?;
-        let project = &self.checker.project().clone();
-        let relative_path = project.as_relative_path(path)?;
+        let relative_path = match self.relative_path_in_project(path)? {
+            Some(p) => p,
+            None => return Ok(()),
+        };
         let operation = Operation::new_ignore_for_path(word, &relative_path);
         self.checker.apply_operation(operation)?;
         self.recheck();
@@ -199,6 +400,9 @@ impl<D: Dictionary, S: OperatingSystemIO> KakCli<D, S> {
     }
 
     fn add_project(&mut self) -> Result<()> {
+        if !self.ensure_project("add to the ignore list for the project") {
+            return Ok(());
+        }
         let LineSelection { word, .. } = &self.parse_line_selection()?;
         let operation = Operation::new_ignore_for_project(word);
         self.checker.apply_operation(operation)?;
@@ -213,7 +417,10 @@ impl<D: Dictionary, S: OperatingSystemIO> KakCli<D, S> {
         let LineSelection {
             path, selection, ..
         } = self.parse_line_selection()?;
-        self.print(&format!("edit {path}\n"));
+        if self.relative_path_in_project(&path)?.is_none() {
+            return Ok(());
+        }
+        self.print(&format!("edit {}\n", quote(&path)));
         self.print(&format!("select {selection}\n"));
         Ok(())
     }
@@ -239,45 +446,69 @@ impl<D: Dictionary, S: OperatingSystemIO> KakCli<D, S> {
                 continue;
             }
 
-            // cleanup any errors that may have been set during last run
-            self.print(&format!(
-                "unset-option %{{buffer={bufname}}} skyspell_errors\n"
-            ));
+            // Errors from the last run are cleared even if the buffer
+            // can't be checked
+            self.checker.add_buffer(&bufname);
 
             let full_path = bufname.replace('~', &self.home_dir);
             let source_path = Path::new(&full_path);
 
             if !source_path.exists() {
-                // Buffer has not been written to a file yet
+                // Buffer has not been written to a file yet, or the file
+                // was deleted or renamed - in any case there's nothing to check
+                self.kakoune_io().debug(&format!(
+                    "skyspell: {full_path} not found on disk, skipping"
+                ));
                 continue;
             }
 
-            let relative_path = self.checker.to_relative_path(source_path)?;
-
-            if relative_path.normalize().starts_with("..") {
-                // Buffer is outside the current project
+            // Same rule as `skyspell check` without --allow-outside-project:
+            // buffers in the extra roots of the project are checked too.
+            // Without a project, every buffer is checked
+            if self.has_project && !self.checker.project().contains(source_path)? {
+                self.kakoune_io().verbose(&format!(
+                    "skyspell: {full_path} is not in the project, skipping"
+                ));
                 continue;
             }
 
+            let lang = self.buffer_lang(source_path);
+            self.checker.set_buffer_lang(lang.as_deref());
             self.checker.process(source_path, &bufname)?;
         }
 
         self.checker.write_code()
     }
 
+    /// The `skyspell_lang` option of the buffer editing `path`, as
+    /// collected by skyspell-check, if known
+    fn buffer_lang(&self, path: &Path) -> Option<String> {
+        let entries = self
+            .kakoune_io()
+            .get_list_option("skyspell_buffer_langs")
+            .ok()?;
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        entries.iter().find_map(|entry| {
+            let (buffile, lang) = entry.rsplit_once('=')?;
+            let buffile = std::fs::canonicalize(buffile).unwrap_or_else(|_| buffile.into());
+            (buffile == path && !lang.is_empty()).then(|| lang.to_string())
+        })
+    }
+
+    // The paths in the *spelling* buffer are relative to the project
+    // (or absolute for files outside of it): make them absolute so
+    // that they don't depend on the working directory of kakoune. The
+    // actions are mapped in the *spelling* buffer only, but they can still
+    // be called on a header line, or from another buffer
     fn parse_line_selection(&self) -> Result<LineSelection> {
         let line_selection = self.kakoune_io().get_selection()?;
-        let (path, rest) = line_selection
-            .split_once(": ")
-            .with_context(|| "line selection should contain :")?;
-        let (selection, word) = rest
-            .split_once(' ')
-            .with_context(|| "expected at least two words after the path name in line selection")?;
-        Ok(LineSelection {
-            path: path.to_string(),
-            word: word.to_string(),
-            selection: selection.to_string(),
-        })
+        let mut parsed = match parse_line_selection(self.kakoune_io(), &line_selection) {
+            Some(parsed) => parsed,
+            None => bail!("skyspell: press this key in the *spelling* buffer"),
+        };
+        let full_path = self.checker.project().path().as_ref().join(&parsed.path);
+        parsed.path = full_path.to_string_lossy().to_string();
+        Ok(parsed)
     }
 
     fn goto_error(&self, opts: MoveOpts, direction: Direction) -> Result<()> {
@@ -315,9 +546,8 @@ impl<D: Dictionary, S: OperatingSystemIO> KakCli<D, S> {
             return Ok(());
         }
 
-        let suggestions = self
-            .dictionary()
-            .suggest(selection)
+        let forbidden = self.checker.ignore_store().forbidden_words();
+        let suggestions = suggest_replacements(self.dictionary(), selection, &forbidden)
             .context("While getting suggestions")?;
 
         if suggestions.is_empty() {
@@ -344,3 +574,6 @@ impl<D: Dictionary, S: OperatingSystemIO> KakCli<D, S> {
         self.print("skyspell-list\n");
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,544 @@
+use super::*;
+
+use crate::kak::checker::spelling_line;
+use crate::kak::checker::tests::{new_fake_checker, FakeChecker};
+use skyspell_core::tests::FakeDictionary;
+use skyspell_core::tests::FakeIO;
+use skyspell_core::IgnoreStore;
+use skyspell_core::ReportEntry;
+use tempfile::TempDir;
+
+type FakeCli = KakCli<FakeDictionary, FakeIO>;
+
+fn new_fake_cli(temp_dir: &TempDir) -> FakeCli {
+    let mut checker = new_fake_checker(temp_dir);
+    checker.io_mut().set_timestamp(42);
+    KakCli {
+        checker,
+        home_dir: "/home/user".to_string(),
+        has_project: true,
+    }
+}
+
+// Like run() when `skyspell_project` is not set: the project is
+// rooted in the home directory, and there is no local ignore file
+fn new_fake_cli_without_project(temp_dir: &TempDir) -> FakeCli {
+    let home_dir = temp_dir.path().join("home");
+    std::fs::create_dir(&home_dir).unwrap();
+    let project = Project::new_without_local_ignore(&home_dir).unwrap();
+    let global_toml = temp_dir.path().join("global.toml");
+    let local_toml = home_dir.join(skyspell_core::SKYSPELL_LOCAL_IGNORE);
+    let ignore_store = IgnoreStore::load_without_local(global_toml, local_toml).unwrap();
+    let state_toml = temp_dir.path().join("state.toml");
+    let mut fake_io = crate::kak::io::tests::new_fake_io();
+    fake_io.set_timestamp(42);
+    let checker = KakouneChecker::new(
+        project,
+        FakeDictionary::new(),
+        ignore_store,
+        fake_io,
+        Some(state_toml),
+    )
+    .unwrap();
+    KakCli {
+        checker,
+        home_dir: home_dir.to_string_lossy().to_string(),
+        has_project: false,
+    }
+}
+
+impl FakeCli {
+    fn set_selection(&mut self, text: &str) {
+        self.checker.io_mut().set_selection(text)
+    }
+
+    fn get_output(self) -> String {
+        FakeChecker::get_output(self.checker)
+    }
+}
+
+#[test]
+fn test_init_script_declares_options_set_by_skyspell_kak() {
+    let script = init_script(None, false);
+
+    for declaration in [
+        "declare-option str skyspell_lang",
+        "declare-option -hidden str-list skyspell_buffer_langs",
+        "declare-option str skyspell_project",
+        "declare-option range-specs skyspell_errors",
+        "declare-option int skyspell_error_count",
+        "declare-option str skyspell_face SpellingError",
+    ] {
+        assert!(
+            script.contains(declaration),
+            "'{declaration}' not found in init script"
+        );
+    }
+}
+
+#[test]
+fn test_init_script_with_custom_face() {
+    let script = init_script(Some("Typo"), false);
+
+    assert!(script.ends_with("set-option global skyspell_face Typo\n"));
+}
+
+#[test]
+fn test_init_script_without_custom_face() {
+    let script = init_script(None, false);
+
+    assert!(!script.contains("set-option global skyspell_face"));
+}
+
+#[test]
+fn test_init_script_with_compat_spell() {
+    let script = init_script(None, true);
+
+    assert!(script.contains("set-option global skyspell_compat_spell true"));
+    assert!(script.contains("alias global spell-next skyspell-next"));
+    assert!(script.contains("alias global spell-prev skyspell-previous"));
+}
+
+#[test]
+fn test_init_script_without_compat_spell() {
+    let script = init_script(None, false);
+
+    assert!(!script.contains("alias global spell-next"));
+}
+
+#[test]
+fn test_check_clears_spell_kak_errors_in_compat_mode() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    cli.checker
+        .io_mut()
+        .set_option("skyspell_compat_spell", "true");
+    let deleted = temp_dir.path().join("deleted.md");
+    let deleted = deleted.to_string_lossy().to_string();
+
+    cli.check(&CheckOpts {
+        buflist: vec![deleted.clone()],
+    })
+    .unwrap();
+
+    let output = cli.get_output();
+    for option in ["skyspell_errors", "spell_errors"] {
+        assert!(output.contains(&format!("set-option %{{buffer={deleted}}} {option} 42 \n")));
+    }
+}
+
+#[test]
+fn test_check_skips_deleted_files() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let deleted = temp_dir.path().join("deleted.md");
+    let deleted = deleted.to_string_lossy().to_string();
+
+    cli.check(&CheckOpts {
+        buflist: vec![deleted.clone()],
+    })
+    .unwrap();
+
+    let output = cli.get_output();
+    assert!(output.contains(&format!(
+        "set-option %{{buffer={deleted}}} skyspell_errors 42 \n"
+    )));
+    assert!(!output.contains("unset-option"));
+    assert!(output.contains(&format!(
+        "echo -debug 'skyspell: {deleted} not found on disk"
+    )));
+}
+
+#[test]
+fn test_deleted_file_with_special_chars_is_quoted_in_debug_buffer() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let deleted = temp_dir.path().join("my notes; echo %sh{ls}.md");
+    let deleted = deleted.to_string_lossy().to_string();
+
+    cli.check(&CheckOpts {
+        buflist: vec![deleted.clone()],
+    })
+    .unwrap();
+
+    let output = cli.get_output();
+    assert!(output.contains(&format!(
+        "echo -debug 'skyspell: {deleted} not found on disk, skipping'\n"
+    )));
+}
+
+#[test]
+fn test_buffer_outside_the_project_is_quoted_in_debug_buffer() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let other_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let outside = other_dir.path().join("my notes; echo %sh{ls}.md");
+    std::fs::write(&outside, "fooo").unwrap();
+    let outside = outside.to_string_lossy().to_string();
+
+    output::capture(Verbosity::Verbose, || {
+        cli.check(&CheckOpts {
+            buflist: vec![outside.clone()],
+        })
+        .unwrap();
+    });
+
+    let output = cli.get_output();
+    assert!(output.contains(&format!(
+        "echo -debug 'skyspell: {outside} is not in the project, skipping'\n"
+    )));
+}
+
+#[test]
+fn test_check_after_rename_uses_the_new_path() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let old_path = temp_dir.path().join("old.md");
+    let new_path = temp_dir.path().join("new.md");
+    std::fs::write(&old_path, "fooo").unwrap();
+    std::fs::rename(&old_path, &new_path).unwrap();
+    let old_path = old_path.to_string_lossy().to_string();
+    let new_path = new_path.to_string_lossy().to_string();
+
+    cli.check(&CheckOpts {
+        buflist: vec![old_path, new_path],
+    })
+    .unwrap();
+
+    let output = cli.get_output();
+    assert!(output.contains("new.md<tab>1.1,1.4<tab>fooo"), "{output}");
+    assert!(!output.contains("old.md<tab>"), "{output}");
+}
+
+#[test]
+fn test_add_file_when_file_was_deleted() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let deleted = temp_dir.path().join("deleted.md");
+    cli.set_selection(&format!("{}: 1.1,1.4 fooo", deleted.display()));
+
+    cli.add_file().unwrap();
+
+    let local_toml = temp_dir.path().join("skyspell.toml");
+    assert!(!local_toml.exists(), "nothing should have been written");
+    let output = cli.get_output();
+    assert!(output.contains("no longer exists"));
+}
+
+#[test]
+fn test_jump_when_file_was_renamed() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let old_path = temp_dir.path().join("old.md");
+    std::fs::write(&old_path, "fooo").unwrap();
+    cli.set_selection(&format!("{}: 1.1,1.4 fooo", old_path.display()));
+    std::fs::rename(&old_path, temp_dir.path().join("new.md")).unwrap();
+
+    cli.jump().unwrap();
+
+    let output = cli.get_output();
+    assert!(!output.contains("edit "));
+    assert!(output.contains("no longer exists"));
+}
+
+#[test]
+fn test_jump_to_a_path_relative_to_the_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let foo_md = temp_dir.path().join("foo.md");
+    std::fs::write(&foo_md, "fooo").unwrap();
+    cli.set_selection("foo.md\t1.1,1.4\tfooo");
+
+    cli.jump().unwrap();
+
+    let output = cli.get_output();
+    assert!(
+        output.contains(&format!("edit '{}'\n", foo_md.display())),
+        "{output}"
+    );
+    assert!(output.contains("select 1.1,1.4\n"));
+}
+
+#[test]
+fn test_add_with_header_line_selected() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let foo_md = temp_dir.path().join("foo.md");
+    cli.set_selection(&format!("{} - 2 errors", foo_md.display()));
+
+    let err = cli.add_global().unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "skyspell: press this key in the *spelling* buffer"
+    );
+}
+
+#[test]
+fn test_jump_to_a_path_containing_spaces() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let notes = temp_dir.path().join("my notes: it's été.md");
+    std::fs::write(&notes, "fooo").unwrap();
+    cli.set_selection("my notes: it's été.md\t1.1,1.4\tfooo");
+
+    cli.jump().unwrap();
+
+    let output = cli.get_output();
+    let expected = format!("edit '{}'\n", notes.display()).replace("it's", "it''s");
+    assert!(output.contains(&expected), "{output}");
+}
+
+#[test]
+fn test_parse_line_selection() {
+    let kakoune_io = crate::kak::io::tests::new_fake_io();
+
+    let parsed = parse_line_selection(&kakoune_io, "/path/to/foo.md\t2.5,2.7\tfoo").unwrap();
+
+    assert_eq!(
+        parsed,
+        LineSelection {
+            path: "/path/to/foo.md".to_string(),
+            word: "foo".to_string(),
+            selection: "2.5,2.7".to_string(),
+        }
+    );
+    for line in [
+        "spam - 2 errors",
+        "/path/to/foo.md\tfooo",
+        "/path/to/foo.md\t2.5\tfooo",
+        "/path/to/foo.md\t2.5,2.7\t",
+        "/path/to/foo.md: fooo",
+        "",
+    ] {
+        assert!(parse_line_selection(&kakoune_io, line).is_none(), "{line}");
+    }
+}
+
+#[test]
+fn test_parse_line_selection_written_by_older_versions() {
+    let kakoune_io = crate::kak::io::tests::new_fake_io();
+
+    let parsed = parse_line_selection(&kakoune_io, "/path/to/foo.md: 2.5,2.7 foo").unwrap();
+
+    assert_eq!(parsed.path, "/path/to/foo.md");
+    assert_eq!(parsed.selection, "2.5,2.7");
+    assert_eq!(parsed.word, "foo");
+}
+
+#[test]
+fn test_line_selection_round_trip() {
+    let kakoune_io = crate::kak::io::tests::new_fake_io();
+
+    for (path, word) in [
+        ("foo.md", "fooo"),
+        ("notes: draft.md", "fooo"),
+        ("my notes/to do.md", "fooo"),
+        ("été/café: déjà vu.md", "élan"),
+        ("with\ttab.md", "fooo"),
+        ("foo.md", "foo bar"),
+    ] {
+        let entry = ReportEntry {
+            word: word.to_string(),
+            path: path.to_string(),
+            line: 3,
+            column: 2,
+        };
+        let line = spelling_line(&entry);
+
+        let parsed = parse_line_selection(&kakoune_io, &line).unwrap();
+
+        let end = 2 + word.len() - 1;
+        assert_eq!(
+            parsed,
+            LineSelection {
+                path: path.to_string(),
+                word: word.to_string(),
+                selection: format!("3.2,3.{end}"),
+            },
+            "{line}"
+        );
+    }
+}
+
+#[test]
+fn test_check_without_project_checks_any_buffer() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli_without_project(&temp_dir);
+    let notes = temp_dir.path().join("notes.md");
+    std::fs::write(&notes, "fooo").unwrap();
+    let notes = notes.to_string_lossy().to_string();
+
+    cli.check(&CheckOpts {
+        buflist: vec![notes.clone()],
+    })
+    .unwrap();
+
+    let output = cli.get_output();
+    assert!(output.contains(&format!("{notes}<tab>1.1,1.4<tab>fooo")));
+    assert!(output.contains("1 spelling error"));
+}
+
+#[test]
+fn test_add_project_without_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli_without_project(&temp_dir);
+    let notes = temp_dir.path().join("notes.md");
+    std::fs::write(&notes, "fooo").unwrap();
+    cli.set_selection(&format!("{}: 1.1,1.4 fooo", notes.display()));
+
+    cli.add_project().unwrap();
+
+    let output = cli.get_output();
+    assert!(output.starts_with(
+        "echo -markup '{Error}Cannot add to the ignore list for the project without a project"
+    ));
+    assert!(!output.contains("skyspell-check"));
+}
+
+#[test]
+fn test_add_file_without_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli_without_project(&temp_dir);
+    let notes = temp_dir.path().join("notes.md");
+    std::fs::write(&notes, "fooo").unwrap();
+    cli.set_selection(&format!("{}: 1.1,1.4 fooo", notes.display()));
+
+    cli.add_file().unwrap();
+
+    let output = cli.get_output();
+    assert!(output.contains("{Error}Cannot add to the ignore list for a file without a project"));
+    let local_toml = temp_dir
+        .path()
+        .join("home")
+        .join(skyspell_core::SKYSPELL_LOCAL_IGNORE);
+    assert!(!local_toml.exists());
+}
+
+#[test]
+fn test_add_global_and_extension_without_project() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli_without_project(&temp_dir);
+    let notes = temp_dir.path().join("notes.md");
+    std::fs::write(&notes, "fooo barr").unwrap();
+
+    cli.set_selection(&format!("{}: 1.1,1.4 fooo", notes.display()));
+    cli.add_global().unwrap();
+    cli.set_selection(&format!("{}: 1.6,1.9 barr", notes.display()));
+    cli.add_extension().unwrap();
+
+    let output = cli.get_output();
+    assert!(!output.contains("{Error}"), "{output}");
+    let global_toml = std::fs::read_to_string(temp_dir.path().join("global.toml")).unwrap();
+    assert!(global_toml.contains("fooo"));
+    assert!(global_toml.contains("barr"));
+}
+
+#[test]
+fn test_each_buffer_is_checked_with_its_lang() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let opened = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = opened.clone();
+    cli.checker.set_lang_opener(Arc::new(move |_lang: &str| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let mut dictionary = FakeDictionary::new();
+        dictionary.add_known("bonjour");
+        Ok(dictionary)
+    }));
+    let mut buflist = vec![];
+    let mut langs = vec![];
+    for (name, lang) in [("en.md", "en_US"), ("fr.md", "fr_FR"), ("fr2.md", "fr_FR")] {
+        let path = temp_dir.path().join(name);
+        std::fs::write(&path, "bonjour").unwrap();
+        let path = path.to_string_lossy().to_string();
+        langs.push(format!("'{path}={lang}'"));
+        buflist.push(path);
+    }
+    cli.checker
+        .io_mut()
+        .set_env_var("kak_quoted_opt_skyspell_buffer_langs", &langs.join(" "));
+
+    cli.check(&CheckOpts {
+        buflist: buflist.clone(),
+    })
+    .unwrap();
+
+    assert_eq!(opened.load(std::sync::atomic::Ordering::SeqCst), 1);
+    let output = cli.get_output();
+    assert!(output.contains("en.md<tab>1.1,1.7<tab>bonjour"));
+    assert!(!output.contains("fr.md<tab>1.1,1.7<tab>bonjour"));
+    assert!(!output.contains("fr2.md<tab>1.1,1.7<tab>bonjour"));
+    assert!(output.contains("1 spelling error"));
+}
+
+#[test]
+fn test_add_lang_uses_the_lang_of_the_buffer() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test-skyspell")
+        .tempdir()
+        .unwrap();
+    let mut cli = new_fake_cli(&temp_dir);
+    let notes = temp_dir.path().join("notes.md");
+    std::fs::write(&notes, "bonjour").unwrap();
+    let notes = notes.to_string_lossy().to_string();
+    cli.checker.io_mut().set_env_var(
+        "kak_quoted_opt_skyspell_buffer_langs",
+        &format!("'{notes}=fr-FR'"),
+    );
+    cli.set_selection(&format!("{notes}: 1.1,1.7 bonjour"));
+
+    cli.add_lang().unwrap();
+
+    let ignore_store = cli.checker.ignore_store();
+    assert!(ignore_store.is_ignored_for_lang("bonjour", "fr_FR"));
+    assert!(!ignore_store.is_ignored_for_lang("bonjour", "en_US"));
+}
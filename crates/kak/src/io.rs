@@ -1,8 +1,17 @@
+use std::cell::RefCell;
+
 use anyhow::{anyhow, Context, Result};
-use skyspell_core::{OperatingSystemIO, StandardIO};
+use skyspell_core::{output, OperatingSystemIO, StandardIO, Verbosity};
+
+// Above this many bytes, print_or_source() goes through a temporary file
+// unless `skyspell_use_tempfile` says otherwise
+const TEMPFILE_THRESHOLD: usize = 64 * 1024;
 
 pub struct KakouneIO<S: OperatingSystemIO> {
     os_io: S,
+    // Set while capture() runs
+    captured: RefCell<Option<String>>,
+    tempfile_threshold: usize,
 }
 
 pub type StdKakouneIO = KakouneIO<StandardIO>;
@@ -12,14 +21,87 @@ pub fn new_kakoune_io() -> StdKakouneIO {
     KakouneIO::new(io)
 }
 
+/// Quote a string so that Kakoune sees it as a single argument
+pub(crate) fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+// Make sure '{' is not interpreted as the beginning of a face in markup strings
+fn escape_markup(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('{', "\\{")
+}
+
+/// Quote a string for the shell, inside a `%sh{}` block
+fn quote_for_shell(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+// The path is used inside `%{}` and `%sh{}` blocks, where unbalanced braces
+// would end the block early. Temporary files never need anything else
+fn is_safe_temp_path(path: &str) -> bool {
+    !path.is_empty()
+        && path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-+~".contains(c))
+}
+
+/// Split the value of a `kak_quoted_*` variable, where each item is
+/// quoted for the shell, like `'a b' 'it'\''s'`
+fn split_quoted(text: &str) -> Vec<String> {
+    let mut items = vec![];
+    let mut current: Option<String> = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let item = current.get_or_insert_with(String::new);
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    item.push(c);
+                }
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    current.get_or_insert_with(String::new).push(c);
+                }
+            }
+            ' ' => items.extend(current.take()),
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    items.extend(current);
+    items
+}
+
 impl<S: OperatingSystemIO> KakouneIO<S> {
     pub(crate) fn new(os_io: S) -> Self {
-        Self { os_io }
+        Self {
+            os_io,
+            captured: RefCell::new(None),
+            tempfile_threshold: TEMPFILE_THRESHOLD,
+        }
     }
 
-    #[allow(dead_code)]
+    /// Write to the *debug* buffer, unless --quiet was used
     pub fn debug(&self, message: &str) {
-        self.os_io.print(&format!("echo -debug {message}\n"));
+        if output::verbosity() > Verbosity::Quiet {
+            self.print(&format!("echo -debug {}\n", quote(message)));
+        }
+    }
+
+    /// Write to the *debug* buffer with -v
+    pub fn verbose(&self, message: &str) {
+        if output::verbosity() >= Verbosity::Verbose {
+            self.print(&format!("echo -debug {}\n", quote(message)));
+        }
+    }
+
+    /// Display an error in the status line
+    pub fn print_error(&self, message: &str) {
+        let markup = format!("{{Error}}{}", escape_markup(message));
+        self.print(&format!("echo -markup {}\n", quote(&markup)));
     }
 
     pub fn get_variable(&self, key: &str) -> Result<String> {
@@ -31,6 +113,14 @@ impl<S: OperatingSystemIO> KakouneIO<S> {
         self.os_io.get_env_var(&key)
     }
 
+    /// The items of a list option, like `str-list`, read from the
+    /// quoted variable so that items may contain spaces
+    pub fn get_list_option(&self, name: &str) -> Result<Vec<String>> {
+        let key = format!("kak_quoted_opt_{name}");
+        let value = self.os_io.get_env_var(&key)?;
+        Ok(split_quoted(&value))
+    }
+
     pub(crate) fn parse_usize(&self, v: &str) -> Result<usize> {
         v.parse()
             .map_err(|_| anyhow!("could not parse '{v}' as a positive number"))
@@ -76,7 +166,58 @@ impl<S: OperatingSystemIO> KakouneIO<S> {
     }
 
     pub fn print(&self, command: &str) {
-        self.os_io.print(command);
+        match &mut *self.captured.borrow_mut() {
+            Some(captured) => captured.push_str(command),
+            None => self.os_io.print(command),
+        }
+    }
+
+    /// Return what `f` prints instead of printing it
+    pub fn capture(&self, f: impl FnOnce()) -> String {
+        let previous = self.captured.replace(Some(String::new()));
+        f();
+        self.captured.replace(previous).unwrap_or_default()
+    }
+
+    pub(crate) fn set_tempfile_threshold(&mut self, tempfile_threshold: usize) {
+        self.tempfile_threshold = tempfile_threshold;
+    }
+
+    // From the `skyspell_use_tempfile` option: 'true', 'false', or
+    // anything else to only use a temporary file for large outputs
+    fn use_tempfile(&self, commands: &str) -> bool {
+        match self.get_option("skyspell_use_tempfile").as_deref() {
+            Ok("true") => true,
+            Ok("false") => false,
+            _ => commands.len() > self.tempfile_threshold,
+        }
+    }
+
+    /// Print `commands`, or, when they are too large to go through the
+    /// output of `%sh{}` comfortably, write them to a temporary file and
+    /// print the commands to source it then remove it - even when
+    /// sourcing fails
+    pub fn print_or_source(&self, commands: &str) -> Result<()> {
+        if !self.use_tempfile(commands) {
+            self.print(commands);
+            return Ok(());
+        }
+        let path = self.os_io.write_temp_file(commands)?;
+        let path_str = match path.to_str() {
+            Some(p) if is_safe_temp_path(p) => p,
+            _ => {
+                self.os_io.remove_file(&path)?;
+                self.print(commands);
+                return Ok(());
+            }
+        };
+        let remove = format!("nop %sh{{ rm -f {} }}", quote_for_shell(path_str));
+        self.print(&format!(
+            "try %{{ source {} }} catch %{{ {remove}; fail %val{{error}} }}\n",
+            quote(path_str)
+        ));
+        self.print(&format!("{remove}\n"));
+        Ok(())
     }
 
     fn parse_range(&self, range: &str) -> Result<(usize, usize, usize)> {
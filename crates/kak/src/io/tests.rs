@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use skyspell_core::tests::FakeIO;
 
 use super::*;
@@ -9,6 +12,10 @@ impl FakeKakouneIO {
         self.os_io.get_output()
     }
 
+    pub(crate) fn temp_files(&self) -> BTreeMap<PathBuf, String> {
+        self.os_io.temp_files()
+    }
+
     pub(crate) fn set_env_var(&mut self, key: &str, value: &str) {
         self.os_io.set_env_var(key, value);
     }
@@ -42,7 +49,36 @@ fn test_debug() {
     let kakoune_io = new_fake_io();
     kakoune_io.debug("This is a debug message");
     let actual = kakoune_io.get_output();
-    assert_eq!(actual, "echo -debug This is a debug message\n");
+    assert_eq!(actual, "echo -debug 'This is a debug message'\n");
+}
+
+#[test]
+fn test_debug_is_quoted() {
+    let kakoune_io = new_fake_io();
+    kakoune_io.debug("can't open a b; %sh{ls}");
+    let actual = kakoune_io.get_output();
+    assert_eq!(actual, "echo -debug 'can''t open a b; %sh{ls}'\n");
+}
+
+#[test]
+fn test_no_debug_when_quiet() {
+    let kakoune_io = new_fake_io();
+    output::capture(Verbosity::Quiet, || {
+        kakoune_io.debug("This is a debug message");
+    });
+    let actual = kakoune_io.get_output();
+    assert_eq!(actual, "");
+}
+
+#[test]
+fn test_verbose_debug() {
+    let kakoune_io = new_fake_io();
+    kakoune_io.verbose("Not shown by default");
+    output::capture(Verbosity::Verbose, || {
+        kakoune_io.verbose("This is a verbose message");
+    });
+    let actual = kakoune_io.get_output();
+    assert_eq!(actual, "echo -debug 'This is a verbose message'\n");
 }
 
 #[test]
@@ -205,3 +241,104 @@ fn test_get_next_selection_between_two_selections_same_line() {
     let actual = kakoune_io.get_next_selection(pos, &ranges).unwrap();
     assert_eq!(actual, &(1, 19, 21));
 }
+
+#[test]
+fn test_print_error() {
+    let kakoune_io = new_fake_io();
+    kakoune_io.print_error("Selection is empty");
+    let actual = kakoune_io.get_output();
+    assert_eq!(actual, "echo -markup '{Error}Selection is empty'\n");
+}
+
+#[test]
+fn test_print_error_is_quoted() {
+    let kakoune_io = new_fake_io();
+    kakoune_io.print_error("can't parse {foo} in C:\\skyspell.toml");
+    let actual = kakoune_io.get_output();
+    assert_eq!(
+        actual,
+        "echo -markup '{Error}can''t parse \\{foo} in C:\\\\skyspell.toml'\n"
+    );
+}
+
+#[test]
+fn test_split_quoted() {
+    let actual = split_quoted(r"'/tmp/a b.md=fr_FR' 'it'\''s=' '/tmp/c.md='");
+    assert_eq!(actual, ["/tmp/a b.md=fr_FR", "it's=", "/tmp/c.md="]);
+}
+
+#[test]
+fn test_get_list_option() {
+    let mut kakoune_io = new_fake_io();
+    kakoune_io.set_env_var("kak_quoted_opt_my_list", "'one' 'two words'");
+    let actual = kakoune_io.get_list_option("my_list").unwrap();
+    assert_eq!(actual, ["one", "two words"]);
+}
+
+#[test]
+fn test_capture() {
+    let kakoune_io = new_fake_io();
+    let captured = kakoune_io.capture(|| {
+        kakoune_io.print("echo one\n");
+        kakoune_io.print_error("two");
+    });
+    kakoune_io.print("echo three\n");
+
+    assert_eq!(captured, "echo one\necho -markup '{Error}two'\n");
+    assert_eq!(kakoune_io.get_output(), "echo three\n");
+}
+
+#[test]
+fn test_small_outputs_are_printed_directly() {
+    let kakoune_io = new_fake_io();
+    kakoune_io.print_or_source("echo hello\n").unwrap();
+
+    assert!(kakoune_io.temp_files().is_empty());
+    assert_eq!(kakoune_io.get_output(), "echo hello\n");
+}
+
+#[test]
+fn test_large_outputs_are_sourced_from_a_temp_file() {
+    let mut kakoune_io = new_fake_io();
+    kakoune_io.set_tempfile_threshold(5);
+    kakoune_io.print_or_source("echo hello\n").unwrap();
+
+    let temp_files = kakoune_io.temp_files();
+    let (path, contents) = temp_files.first_key_value().unwrap();
+    assert_eq!(path, &PathBuf::from("/tmp/skyspell-1"));
+    assert_eq!(contents, "echo hello\n");
+    assert_eq!(
+        kakoune_io.get_output(),
+        "try %{ source '/tmp/skyspell-1' } \
+        catch %{ nop %sh{ rm -f '/tmp/skyspell-1' }; fail %val{error} }\n\
+        nop %sh{ rm -f '/tmp/skyspell-1' }\n"
+    );
+}
+
+#[test]
+fn test_use_tempfile_option() {
+    let mut kakoune_io = new_fake_io();
+    kakoune_io.set_option("skyspell_use_tempfile", "true");
+    kakoune_io.print_or_source("echo hello\n").unwrap();
+    assert_eq!(kakoune_io.temp_files().len(), 1);
+
+    let mut kakoune_io = new_fake_io();
+    kakoune_io.set_tempfile_threshold(5);
+    kakoune_io.set_option("skyspell_use_tempfile", "false");
+    kakoune_io.print_or_source("echo hello\n").unwrap();
+    assert!(kakoune_io.temp_files().is_empty());
+    assert_eq!(kakoune_io.get_output(), "echo hello\n");
+}
+
+#[test]
+fn test_quote_for_shell() {
+    assert_eq!(quote_for_shell("it's"), r"'it'\''s'");
+}
+
+#[test]
+fn test_unsafe_temp_paths() {
+    assert!(is_safe_temp_path("/tmp/skyspell-Ab3_x.kak"));
+    assert!(!is_safe_temp_path("/tmp/my dir/skyspell-1"));
+    assert!(!is_safe_temp_path("/tmp/}/skyspell-1"));
+    assert!(!is_safe_temp_path(""));
+}